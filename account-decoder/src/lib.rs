@@ -6,11 +6,14 @@ extern crate serde_derive;
 
 pub mod parse_account_data;
 pub mod parse_config;
+pub mod parse_escrow;
 pub mod parse_nonce;
 pub mod parse_stake;
+pub mod parse_stake_pool;
 pub mod parse_sysvar;
 pub mod parse_token;
 pub mod parse_vote;
+pub mod parse_wasm_loader;
 pub mod validator_info;
 
 use {
@@ -1,35 +1,47 @@
 use {
     crate::{
         parse_config::parse_config,
+        parse_escrow::parse_escrow,
         parse_nonce::parse_nonce,
         parse_stake::parse_stake,
+        parse_stake_pool::parse_stake_pool,
         parse_sysvar::parse_sysvar,
         parse_token::{parse_token, mundis_token_ids},
         parse_vote::parse_vote,
+        parse_wasm_loader::parse_wasm_loader,
     },
     inflector::Inflector,
     serde_json::Value,
-    mundis_sdk::{instruction::InstructionError, pubkey::Pubkey, stake, system_program, sysvar},
+    mundis_sdk::{
+        instruction::InstructionError, pubkey::Pubkey, stake, system_program, sysvar,
+        wasm_loader_upgradeable,
+    },
     std::collections::HashMap,
     thiserror::Error,
 };
 
 lazy_static! {
     static ref CONFIG_PROGRAM_ID: Pubkey = mundis_config_program::id();
+    static ref ESCROW_PROGRAM_ID: Pubkey = mundis_escrow_program::id();
     static ref STAKE_PROGRAM_ID: Pubkey = stake::program::id();
+    static ref STAKE_POOL_PROGRAM_ID: Pubkey = mundis_stake_pool_program::id();
     static ref SYSTEM_PROGRAM_ID: Pubkey = system_program::id();
     static ref SYSVAR_PROGRAM_ID: Pubkey = sysvar::id();
     static ref VOTE_PROGRAM_ID: Pubkey = mundis_vote_program::id();
+    static ref WASM_LOADER_PROGRAM_ID: Pubkey = wasm_loader_upgradeable::id();
     pub static ref PARSABLE_PROGRAM_IDS: HashMap<Pubkey, ParsableAccount> = {
         let mut m = HashMap::new();
         m.insert(*CONFIG_PROGRAM_ID, ParsableAccount::Config);
+        m.insert(*ESCROW_PROGRAM_ID, ParsableAccount::Escrow);
         m.insert(*SYSTEM_PROGRAM_ID, ParsableAccount::Nonce);
         for token_id in mundis_token_ids() {
             m.insert(token_id, ParsableAccount::Token);
         }
         m.insert(*STAKE_PROGRAM_ID, ParsableAccount::Stake);
+        m.insert(*STAKE_POOL_PROGRAM_ID, ParsableAccount::StakePool);
         m.insert(*SYSVAR_PROGRAM_ID, ParsableAccount::Sysvar);
         m.insert(*VOTE_PROGRAM_ID, ParsableAccount::Vote);
+        m.insert(*WASM_LOADER_PROGRAM_ID, ParsableAccount::WasmLoader);
         m
     };
 }
@@ -64,11 +76,14 @@ pub struct ParsedAccount {
 #[serde(rename_all = "camelCase")]
 pub enum ParsableAccount {
     Config,
+    Escrow,
     Nonce,
     Token,
     Stake,
+    StakePool,
     Sysvar,
     Vote,
+    WasmLoader,
 }
 
 #[derive(Default)]
@@ -88,13 +103,16 @@ pub fn parse_account_data(
     let additional_data = additional_data.unwrap_or_default();
     let parsed_json = match program_name {
         ParsableAccount::Config => serde_json::to_value(parse_config(data, pubkey)?)?,
+        ParsableAccount::Escrow => serde_json::to_value(parse_escrow(data)?)?,
         ParsableAccount::Nonce => serde_json::to_value(parse_nonce(data)?)?,
         ParsableAccount::Token => {
             serde_json::to_value(parse_token(data, additional_data.token_decimals)?)?
         }
         ParsableAccount::Stake => serde_json::to_value(parse_stake(data)?)?,
+        ParsableAccount::StakePool => serde_json::to_value(parse_stake_pool(data)?)?,
         ParsableAccount::Sysvar => serde_json::to_value(parse_sysvar(data, pubkey)?)?,
         ParsableAccount::Vote => serde_json::to_value(parse_vote(data)?)?,
+        ParsableAccount::WasmLoader => serde_json::to_value(parse_wasm_loader(data)?)?,
     };
     Ok(ParsedAccount {
         program: format!("{:?}", program_name).to_kebab_case(),
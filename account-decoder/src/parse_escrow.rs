@@ -0,0 +1,103 @@
+use {
+    crate::{
+        parse_account_data::{ParsableAccount, ParseAccountError},
+        StringAmount,
+    },
+    mundis_escrow_program::state::{EscrowState, Offer},
+    mundis_sdk::clock::Slot,
+};
+
+pub fn parse_escrow(data: &[u8]) -> Result<EscrowAccountType, ParseAccountError> {
+    let escrow_state = EscrowState::deserialize(data)
+        .map_err(|_| ParseAccountError::AccountNotParsable(ParsableAccount::Escrow))?;
+    let parsed_account = match escrow_state {
+        EscrowState::Uninitialized => EscrowAccountType::Uninitialized,
+        EscrowState::Offer(offer) => EscrowAccountType::Offer(offer.into()),
+    };
+    Ok(parsed_account)
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+#[allow(clippy::large_enum_variant)]
+pub enum EscrowAccountType {
+    Uninitialized,
+    Offer(UiOffer),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiOffer {
+    pub maker: String,
+    pub offer_mint: String,
+    pub want_mint: String,
+    pub maker_receive_account: String,
+    pub maker_refund_account: String,
+    pub offer_amount: StringAmount,
+    pub want_amount: StringAmount,
+    pub remaining_amount: StringAmount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry_slot: Option<Slot>,
+}
+
+impl From<Offer> for UiOffer {
+    fn from(offer: Offer) -> Self {
+        Self {
+            maker: offer.maker.to_string(),
+            offer_mint: offer.offer_mint.to_string(),
+            want_mint: offer.want_mint.to_string(),
+            maker_receive_account: offer.maker_receive_account.to_string(),
+            maker_refund_account: offer.maker_refund_account.to_string(),
+            offer_amount: offer.offer_amount.to_string(),
+            want_amount: offer.want_amount.to_string(),
+            remaining_amount: offer.remaining_amount.to_string(),
+            expiry_slot: offer.expiry_slot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, bincode::serialize, mundis_sdk::pubkey::Pubkey};
+
+    #[test]
+    fn test_parse_escrow() {
+        let data = serialize(&EscrowState::Uninitialized).unwrap();
+        assert_eq!(
+            parse_escrow(&data).unwrap(),
+            EscrowAccountType::Uninitialized
+        );
+
+        let maker = Pubkey::new_unique();
+        let offer_mint = Pubkey::new_unique();
+        let want_mint = Pubkey::new_unique();
+        let offer = Offer {
+            maker,
+            offer_mint,
+            want_mint,
+            offer_amount: 100,
+            want_amount: 50,
+            remaining_amount: 90,
+            expiry_slot: Some(42),
+            ..Offer::default()
+        };
+        let mut data = vec![0; EscrowState::offer_size_of()];
+        bincode::serialize_into(&mut data[..], &EscrowState::Offer(offer)).unwrap();
+        assert_eq!(
+            parse_escrow(&data).unwrap(),
+            EscrowAccountType::Offer(UiOffer {
+                maker: maker.to_string(),
+                offer_mint: offer_mint.to_string(),
+                want_mint: want_mint.to_string(),
+                maker_receive_account: Pubkey::default().to_string(),
+                maker_refund_account: Pubkey::default().to_string(),
+                offer_amount: "100".to_string(),
+                want_amount: "50".to_string(),
+                remaining_amount: "90".to_string(),
+                expiry_slot: Some(42),
+            })
+        );
+
+        assert!(parse_escrow(&[1, 2, 3]).is_err());
+    }
+}
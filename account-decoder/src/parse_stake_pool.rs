@@ -0,0 +1,206 @@
+use {
+    crate::{
+        parse_account_data::{ParsableAccount, ParseAccountError},
+        StringAmount,
+    },
+    mundis_sdk::clock::Epoch,
+    mundis_stake_pool_program::state::{
+        Fee, StakePool, StakePoolState, ValidatorStakeInfo, WithdrawalTicket,
+    },
+};
+
+pub fn parse_stake_pool(data: &[u8]) -> Result<StakePoolAccountType, ParseAccountError> {
+    let stake_pool_state = StakePoolState::deserialize(data)
+        .map_err(|_| ParseAccountError::AccountNotParsable(ParsableAccount::StakePool))?;
+    let parsed_account = match stake_pool_state {
+        StakePoolState::Uninitialized => StakePoolAccountType::Uninitialized,
+        StakePoolState::StakePool(stake_pool) => {
+            StakePoolAccountType::StakePool(stake_pool.into())
+        }
+        StakePoolState::WithdrawalTicket(ticket) => {
+            StakePoolAccountType::WithdrawalTicket(ticket.into())
+        }
+    };
+    Ok(parsed_account)
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum StakePoolAccountType {
+    Uninitialized,
+    StakePool(UiStakePool),
+    WithdrawalTicket(UiWithdrawalTicket),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakePool {
+    pub manager: String,
+    pub staker: String,
+    pub pool_mint: String,
+    pub manager_fee_account: String,
+    pub total_lamports: StringAmount,
+    pub pool_token_supply: StringAmount,
+    pub pending_withdrawal_lamports: StringAmount,
+    pub last_update_epoch: Epoch,
+    pub epoch_fee: UiFee,
+    pub withdrawal_fee: UiFee,
+    pub max_validators: u32,
+    pub validators: Vec<UiValidatorStakeInfo>,
+}
+
+impl From<StakePool> for UiStakePool {
+    fn from(stake_pool: StakePool) -> Self {
+        Self {
+            manager: stake_pool.manager.to_string(),
+            staker: stake_pool.staker.to_string(),
+            pool_mint: stake_pool.pool_mint.to_string(),
+            manager_fee_account: stake_pool.manager_fee_account.to_string(),
+            total_lamports: stake_pool.total_lamports.to_string(),
+            pool_token_supply: stake_pool.pool_token_supply.to_string(),
+            pending_withdrawal_lamports: stake_pool.pending_withdrawal_lamports.to_string(),
+            last_update_epoch: stake_pool.last_update_epoch,
+            epoch_fee: stake_pool.epoch_fee.into(),
+            withdrawal_fee: stake_pool.withdrawal_fee.into(),
+            max_validators: stake_pool.max_validators,
+            validators: stake_pool
+                .validators
+                .into_iter()
+                .map(UiValidatorStakeInfo::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiFee {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl From<Fee> for UiFee {
+    fn from(fee: Fee) -> Self {
+        Self {
+            numerator: fee.numerator,
+            denominator: fee.denominator,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiValidatorStakeInfo {
+    pub vote_account: String,
+    pub active_stake_lamports: StringAmount,
+    pub transient_stake_lamports: StringAmount,
+    pub last_update_epoch: Epoch,
+}
+
+impl From<ValidatorStakeInfo> for UiValidatorStakeInfo {
+    fn from(info: ValidatorStakeInfo) -> Self {
+        Self {
+            vote_account: info.vote_account.to_string(),
+            active_stake_lamports: info.active_stake_lamports.to_string(),
+            transient_stake_lamports: info.transient_stake_lamports.to_string(),
+            last_update_epoch: info.last_update_epoch,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiWithdrawalTicket {
+    pub stake_pool: String,
+    pub beneficiary: String,
+    pub lamports: StringAmount,
+    pub claimable_epoch: Epoch,
+}
+
+impl From<WithdrawalTicket> for UiWithdrawalTicket {
+    fn from(ticket: WithdrawalTicket) -> Self {
+        Self {
+            stake_pool: ticket.stake_pool.to_string(),
+            beneficiary: ticket.beneficiary.to_string(),
+            lamports: ticket.lamports.to_string(),
+            claimable_epoch: ticket.claimable_epoch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, bincode::serialize, mundis_sdk::pubkey::Pubkey};
+
+    #[test]
+    fn test_parse_stake_pool() {
+        let data = serialize(&StakePoolState::Uninitialized).unwrap();
+        assert_eq!(
+            parse_stake_pool(&data).unwrap(),
+            StakePoolAccountType::Uninitialized
+        );
+
+        let manager = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let stake_pool = StakePool {
+            manager,
+            total_lamports: 42,
+            epoch_fee: Fee {
+                numerator: 1,
+                denominator: 100,
+            },
+            max_validators: 4,
+            validators: vec![ValidatorStakeInfo {
+                vote_account,
+                active_stake_lamports: 40,
+                transient_stake_lamports: 2,
+                last_update_epoch: 3,
+            }],
+            ..StakePool::default()
+        };
+        let mut data = vec![0; StakePoolState::stake_pool_size_of(4)];
+        bincode::serialize_into(&mut data[..], &StakePoolState::StakePool(stake_pool)).unwrap();
+        let parsed = match parse_stake_pool(&data).unwrap() {
+            StakePoolAccountType::StakePool(parsed) => parsed,
+            _ => panic!("expected stake pool"),
+        };
+        assert_eq!(parsed.manager, manager.to_string());
+        assert_eq!(parsed.total_lamports, "42".to_string());
+        assert_eq!(
+            parsed.epoch_fee,
+            UiFee {
+                numerator: 1,
+                denominator: 100
+            }
+        );
+        assert_eq!(
+            parsed.validators,
+            vec![UiValidatorStakeInfo {
+                vote_account: vote_account.to_string(),
+                active_stake_lamports: "40".to_string(),
+                transient_stake_lamports: "2".to_string(),
+                last_update_epoch: 3,
+            }]
+        );
+
+        let beneficiary = Pubkey::new_unique();
+        let data = serialize(&StakePoolState::WithdrawalTicket(WithdrawalTicket {
+            beneficiary,
+            lamports: 10,
+            claimable_epoch: 5,
+            ..WithdrawalTicket::default()
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_stake_pool(&data).unwrap(),
+            StakePoolAccountType::WithdrawalTicket(UiWithdrawalTicket {
+                stake_pool: Pubkey::default().to_string(),
+                beneficiary: beneficiary.to_string(),
+                lamports: "10".to_string(),
+                claimable_epoch: 5,
+            })
+        );
+
+        assert!(parse_stake_pool(&[1, 2, 3]).is_err());
+    }
+}
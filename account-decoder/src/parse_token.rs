@@ -7,7 +7,7 @@ use {
     std::str::FromStr,
 };
 use mundis_sdk::program_pack::Pack;
-use mundis_token_program::state::{TokenAccount, AccountState, Mint, Multisig};
+use mundis_token_program::state::{TokenAccount, AccountState, MemoTransfer, Mint, Multisig};
 
 // A helper function to convert anima_token::id() to mundis_sdk::pubkey::Pubkey
 fn mundis_token_id() -> Pubkey {
@@ -72,6 +72,11 @@ pub fn parse_token(
                 Some(pubkey) => Some(pubkey.to_string()),
                 None => None,
             },
+            memo_transfer: if account.memo_transfer.is_required() {
+                Some(account.memo_transfer.into())
+            } else {
+                None
+            },
         }))
     } else if data.len() == Mint::get_packed_len() {
         let mint = Mint::unpack(data)
@@ -144,6 +149,8 @@ pub struct UiTokenAccount {
     pub delegated_amount: Option<UiTokenAmount>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub close_authority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo_transfer: Option<UiMemoTransfer>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -164,6 +171,24 @@ impl From<AccountState> for UiAccountState {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiMemoTransfer {
+    Disabled,
+    Required,
+    RequiredUtf8,
+}
+
+impl From<MemoTransfer> for UiMemoTransfer {
+    fn from(memo_transfer: MemoTransfer) -> Self {
+        match memo_transfer {
+            MemoTransfer::Disabled => UiMemoTransfer::Disabled,
+            MemoTransfer::Required => UiMemoTransfer::Required,
+            MemoTransfer::RequiredUtf8 => UiMemoTransfer::RequiredUtf8,
+        }
+    }
+}
+
 pub fn real_number_string(amount: u64, decimals: u8) -> StringDecimals {
     let decimals = decimals as usize;
     if decimals > 0 {
@@ -292,9 +317,21 @@ mod test {
                 rent_exempt_reserve: None,
                 delegated_amount: None,
                 close_authority: Some(owner_pubkey.to_string()),
+                memo_transfer: None,
             }),
         );
 
+        let mut account = TokenAccount::unpack(&account_data).unwrap();
+        account.memo_transfer = MemoTransfer::RequiredUtf8;
+        TokenAccount::pack(account, &mut account_data).unwrap();
+        match parse_token(&account_data, Some(2)).unwrap() {
+            TokenAccountType::Account(ui_account) => assert_eq!(
+                ui_account.memo_transfer,
+                Some(UiMemoTransfer::RequiredUtf8)
+            ),
+            _ => panic!("expected token account"),
+        }
+
         let mut mint_data = vec![0; Mint::get_packed_len()];
         let mut mint = Mint::unpack_unchecked(&mint_data).unwrap();
         mint.mint_authority = Some(owner_pubkey);
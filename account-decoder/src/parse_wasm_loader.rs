@@ -0,0 +1,107 @@
+use {
+    crate::parse_account_data::{ParsableAccount, ParseAccountError},
+    mundis_sdk::wasm_loader_upgradeable::UpgradeableLoaderState,
+};
+
+pub fn parse_wasm_loader(data: &[u8]) -> Result<WasmLoaderAccountType, ParseAccountError> {
+    let account_state = UpgradeableLoaderState::deserialize(data)
+        .map_err(|_| ParseAccountError::AccountNotParsable(ParsableAccount::WasmLoader))?;
+    let parsed_account = match account_state {
+        UpgradeableLoaderState::Uninitialized => WasmLoaderAccountType::Uninitialized,
+        UpgradeableLoaderState::Buffer { authority_address } => {
+            WasmLoaderAccountType::Buffer(UiBuffer {
+                authority: authority_address.map(|pubkey| pubkey.to_string()),
+                data_len: data
+                    .len()
+                    .saturating_sub(UpgradeableLoaderState::buffer_metadata_size()),
+            })
+        }
+        UpgradeableLoaderState::Program {
+            programdata_address,
+        } => WasmLoaderAccountType::Program(UiProgram {
+            program_data: programdata_address.to_string(),
+        }),
+        UpgradeableLoaderState::ProgramData {
+            slot,
+            upgrade_authority_address,
+        } => WasmLoaderAccountType::ProgramData(UiProgramData {
+            slot,
+            authority: upgrade_authority_address.map(|pubkey| pubkey.to_string()),
+            data_len: data
+                .len()
+                .saturating_sub(UpgradeableLoaderState::programdata_metadata_size()),
+        }),
+    };
+    Ok(parsed_account)
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum WasmLoaderAccountType {
+    Uninitialized,
+    Buffer(UiBuffer),
+    Program(UiProgram),
+    ProgramData(UiProgramData),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiBuffer {
+    pub authority: Option<String>,
+    pub data_len: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiProgram {
+    pub program_data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiProgramData {
+    pub slot: u64,
+    pub authority: Option<String>,
+    pub data_len: usize,
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, mundis_sdk::pubkey::Pubkey};
+
+    #[test]
+    fn test_parse_wasm_loader() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0; UpgradeableLoaderState::programdata_len(10)];
+        UpgradeableLoaderState::ProgramData {
+            slot: 42,
+            upgrade_authority_address: Some(authority),
+        }
+        .serialize(&mut data)
+        .unwrap();
+        assert_eq!(
+            parse_wasm_loader(&data).unwrap(),
+            WasmLoaderAccountType::ProgramData(UiProgramData {
+                slot: 42,
+                authority: Some(authority.to_string()),
+                data_len: 10,
+            })
+        );
+
+        let programdata_address = Pubkey::new_unique();
+        let mut data = vec![0; UpgradeableLoaderState::program_len()];
+        UpgradeableLoaderState::Program {
+            programdata_address,
+        }
+        .serialize(&mut data)
+        .unwrap();
+        assert_eq!(
+            parse_wasm_loader(&data).unwrap(),
+            WasmLoaderAccountType::Program(UiProgram {
+                program_data: programdata_address.to_string(),
+            })
+        );
+
+        assert!(parse_wasm_loader(&[9, 9]).is_err());
+    }
+}
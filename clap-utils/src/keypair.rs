@@ -12,6 +12,7 @@
 use {
     crate::{
         input_parsers::{pubkeys_sigs_of, STDOUT_OUTFILE_TOKEN},
+        name_service::{is_name, resolve_name, NAME_SCHEME},
         offline::{SIGNER_ARG, SIGN_ONLY_ARG},
         ArgConstant,
     },
@@ -406,6 +407,7 @@ pub(crate) enum SignerSourceKind {
     Usb(RemoteWalletLocator),
     Stdin,
     Pubkey(Pubkey),
+    Name(String),
 }
 
 impl AsRef<str> for SignerSourceKind {
@@ -416,6 +418,7 @@ impl AsRef<str> for SignerSourceKind {
             Self::Usb(_) => SIGNER_SOURCE_USB,
             Self::Stdin => SIGNER_SOURCE_STDIN,
             Self::Pubkey(_) => SIGNER_SOURCE_PUBKEY,
+            Self::Name(_) => NAME_SCHEME,
         }
     }
 }
@@ -482,6 +485,9 @@ pub(crate) fn parse_signer_source<S: AsRef<str>>(
                         legacy: false,
                     }),
                     SIGNER_SOURCE_STDIN => Ok(SignerSource::new(SignerSourceKind::Stdin)),
+                    NAME_SCHEME => Ok(SignerSource::new(SignerSourceKind::Name(
+                        uri.path().to_string(),
+                    ))),
                     _ => {
                         #[cfg(target_family = "windows")]
                         // On Windows, an absolute path's drive letter will be parsed as the URI
@@ -498,9 +504,13 @@ pub(crate) fn parse_signer_source<S: AsRef<str>>(
                     ASK_KEYWORD => Ok(SignerSource::new_legacy(SignerSourceKind::Prompt)),
                     _ => match Pubkey::from_str(source.as_str()) {
                         Ok(pubkey) => Ok(SignerSource::new(SignerSourceKind::Pubkey(pubkey))),
-                        Err(_) => std::fs::metadata(source.as_str())
-                            .map(|_| SignerSource::new(SignerSourceKind::Filepath(source)))
-                            .map_err(|err| err.into()),
+                        Err(_) => match std::fs::metadata(source.as_str()) {
+                            Ok(_) => Ok(SignerSource::new(SignerSourceKind::Filepath(source))),
+                            Err(_) if is_name(&source) => {
+                                Ok(SignerSource::new(SignerSourceKind::Name(source)))
+                            }
+                            Err(err) => Err(err.into()),
+                        },
                     },
                 }
             }
@@ -617,6 +627,11 @@ pub struct SignerFromPathConfig {
 ///   - `usb://ledger/9rPVSygg3brqghvdZ6wsL2i5YNQTGhXGdJzF65YxaCQd`
 ///   - `usb://ledger/9rPVSygg3brqghvdZ6wsL2i5YNQTGhXGdJzF65YxaCQd?key=0/0`
 ///
+/// - `name:` &mdash; Resolve a name registered with the name service, such as
+///   `name:world`, to its owner's pubkey with the resolver set by
+///   [`set_name_resolver`]. The signer is then created as for a base-58 public
+///   key, described below.
+///
 /// Next the `path` argument may be one of the following strings:
 ///
 /// - `-` &mdash; Read the keypair from stdin. This is the same as the `stdin:`
@@ -645,6 +660,11 @@ pub struct SignerFromPathConfig {
 /// then the signer is created by reading that file as a JSON-serialized
 /// keypair. This is the same as the `file:` URI scheme.
 ///
+/// Otherwise, a `path` made of at least two dot-separated labels, such as
+/// `alice.world`, is treated as a name. This is the same as the `name:` URI
+/// scheme.
+///
+/// [`set_name_resolver`]: crate::name_service::set_name_resolver
 /// [qs]: https://en.wikipedia.org/wiki/Query_string
 /// [dp]: https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki
 /// [URI]: https://en.wikipedia.org/wiki/Uniform_Resource_Identifier
@@ -800,22 +820,29 @@ pub fn signer_from_path_with_config(
                 Err(RemoteWalletError::NoDeviceFound.into())
             }
         }
-        SignerSourceKind::Pubkey(pubkey) => {
-            let presigner = pubkeys_sigs_of(matches, SIGNER_ARG.name)
-                .as_ref()
-                .and_then(|presigners| presigner_from_pubkey_sigs(&pubkey, presigners));
-            if let Some(presigner) = presigner {
-                Ok(Box::new(presigner))
-            } else if config.allow_null_signer || matches.is_present(SIGN_ONLY_ARG.name) {
-                Ok(Box::new(NullSigner::new(&pubkey)))
-            } else {
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("missing signature for supplied pubkey: {}", pubkey),
-                )
-                .into())
-            }
-        }
+        SignerSourceKind::Pubkey(pubkey) => signer_from_pubkey(matches, &pubkey, config),
+        SignerSourceKind::Name(name) => signer_from_pubkey(matches, &resolve_name(&name)?, config),
+    }
+}
+
+fn signer_from_pubkey(
+    matches: &ArgMatches,
+    pubkey: &Pubkey,
+    config: &SignerFromPathConfig,
+) -> Result<Box<dyn Signer>, Box<dyn error::Error>> {
+    let presigner = pubkeys_sigs_of(matches, SIGNER_ARG.name)
+        .as_ref()
+        .and_then(|presigners| presigner_from_pubkey_sigs(pubkey, presigners));
+    if let Some(presigner) = presigner {
+        Ok(Box::new(presigner))
+    } else if config.allow_null_signer || matches.is_present(SIGN_ONLY_ARG.name) {
+        Ok(Box::new(NullSigner::new(pubkey)))
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("missing signature for supplied pubkey: {}", pubkey),
+        )
+        .into())
     }
 }
 
@@ -865,6 +892,7 @@ pub fn pubkey_from_path(
     let SignerSource { kind, .. } = parse_signer_source(path)?;
     match kind {
         SignerSourceKind::Pubkey(pubkey) => Ok(pubkey),
+        SignerSourceKind::Name(name) => resolve_name(&name),
         _ => Ok(signer_from_path(matches, path, keypair_name, wallet_manager)?.pubkey()),
     }
 }
@@ -1264,6 +1292,23 @@ mod tests {
             parse_signer_source(&junk),
             Err(SignerSourceError::IoError(_))
         ));
+        // Unless it reads as a name
+        assert!(matches!(
+            parse_signer_source("alice.world").unwrap(),
+            SignerSource {
+                kind: SignerSourceKind::Name(n),
+                derivation_path: None,
+                legacy: false,
+            } if n == "alice.world"
+        ));
+        assert!(matches!(
+            parse_signer_source("name:world").unwrap(),
+            SignerSource {
+                kind: SignerSourceKind::Name(n),
+                derivation_path: None,
+                legacy: false,
+            } if n == "world"
+        ));
 
         let prompt = "prompt:".to_string();
         assert!(matches!(
@@ -28,5 +28,6 @@ pub mod input_parsers;
 pub mod input_validators;
 pub mod keypair;
 pub mod memo;
+pub mod name_service;
 pub mod nonce;
 pub mod offline;
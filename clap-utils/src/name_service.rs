@@ -0,0 +1,101 @@
+//! Resolution of names registered with the name service, such as `alice.world`.
+//!
+//! Resolving a name requires a round trip to a node, which this crate cannot
+//! make on its own. Applications that want pubkey and signer arguments to
+//! accept names register a [`NameResolver`] with [`set_name_resolver`] before
+//! parsing their arguments.
+
+use {
+    lazy_static::lazy_static,
+    mundis_sdk::pubkey::Pubkey,
+    std::{
+        error,
+        sync::{Arc, RwLock},
+    },
+};
+
+/// Scheme that marks a signer source as a name, e.g. `name:world`
+pub(crate) const NAME_SCHEME: &str = "name";
+
+/// Looks up the owner of a registered name
+pub trait NameResolver: Send + Sync {
+    fn resolve_name(&self, name: &str) -> Result<Pubkey, Box<dyn error::Error>>;
+}
+
+impl<F> NameResolver for F
+where
+    F: Fn(&str) -> Result<Pubkey, Box<dyn error::Error>> + Send + Sync,
+{
+    fn resolve_name(&self, name: &str) -> Result<Pubkey, Box<dyn error::Error>> {
+        self(name)
+    }
+}
+
+lazy_static! {
+    static ref NAME_RESOLVER: RwLock<Option<Arc<dyn NameResolver>>> = RwLock::new(None);
+}
+
+/// Sets the resolver used when a pubkey or signer argument is given a name
+pub fn set_name_resolver(resolver: Arc<dyn NameResolver>) {
+    *NAME_RESOLVER.write().unwrap() = Some(resolver);
+}
+
+pub fn resolve_name(name: &str) -> Result<Pubkey, Box<dyn error::Error>> {
+    let resolver = NAME_RESOLVER.read().unwrap().clone();
+    match resolver {
+        Some(resolver) => resolver.resolve_name(name),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("unable to resolve name \"{}\": no name resolver configured", name),
+        )
+        .into()),
+    }
+}
+
+/// Returns true if `source` can be read as a name without the `name:` scheme.
+/// Such names need at least two labels so single words are still treated as
+/// file paths. The rules match the name service program's.
+pub(crate) fn is_name(source: &str) -> bool {
+    source.len() <= 253
+        && source.contains('.')
+        && source.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_name() {
+        assert!(is_name("alice.world"));
+        assert!(is_name("pay.alice.world"));
+        assert!(!is_name("world"));
+        assert!(!is_name("alice..world"));
+        assert!(!is_name("Alice.world"));
+        assert!(!is_name("./id.json.bak/"));
+        assert!(!is_name("~/.config/mundis/id.json"));
+    }
+
+    #[test]
+    fn test_resolve_name() {
+        let pubkey = Pubkey::new_unique();
+        assert!(resolve_name("alice.world").is_err());
+        set_name_resolver(Arc::new(move |name: &str| {
+            if name == "alice.world" {
+                Ok(pubkey)
+            } else {
+                Err("not registered".into())
+            }
+        }));
+        assert_eq!(resolve_name("alice.world").unwrap(), pubkey);
+        assert!(resolve_name("bob.world").is_err());
+    }
+}
@@ -48,6 +48,7 @@ use {
     },
 };
 use mundis_account_decoder::parse_token::{UiAccountState, UiTokenAmount};
+use mundis_account_decoder::parse_stake_pool::{UiFee, UiStakePool};
 
 static CHECK_MARK: Emoji = Emoji("✅ ", "");
 static CROSS_MARK: Emoji = Emoji("❌ ", "");
@@ -2744,6 +2745,73 @@ impl<T> QuietDisplay for CliMint<T>
 impl<T> VerboseDisplay for CliMint<T>
     where T: Serialize + fmt::Display + QuietDisplay + VerboseDisplay, {}
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliStakePool {
+    pub address: String,
+    #[serde(flatten)]
+    pub stake_pool: UiStakePool,
+    #[serde(skip_serializing)]
+    pub use_lamports_unit: bool,
+}
+
+impl fmt::Display for CliStakePool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lamports = |amount: &str| {
+            build_balance_message(amount.parse().unwrap_or_default(), self.use_lamports_unit, true)
+        };
+        let fee = |fee: &UiFee| {
+            if fee.numerator == 0 {
+                "none".to_string()
+            } else {
+                format!("{}/{}", fee.numerator, fee.denominator)
+            }
+        };
+        writeln!(f)?;
+        writeln_name_value(f, "Stake Pool:", &self.address)?;
+        writeln_name_value(f, "Manager:", &self.stake_pool.manager)?;
+        writeln_name_value(f, "Staker:", &self.stake_pool.staker)?;
+        writeln_name_value(f, "Pool Mint:", &self.stake_pool.pool_mint)?;
+        writeln_name_value(f, "Manager Fee Account:", &self.stake_pool.manager_fee_account)?;
+        writeln_name_value(f, "Total Balance:", &lamports(&self.stake_pool.total_lamports))?;
+        writeln_name_value(f, "Pool Token Supply:", &self.stake_pool.pool_token_supply)?;
+        writeln_name_value(
+            f,
+            "Pending Withdrawals:",
+            &lamports(&self.stake_pool.pending_withdrawal_lamports),
+        )?;
+        writeln_name_value(
+            f,
+            "Last Update Epoch:",
+            &self.stake_pool.last_update_epoch.to_string(),
+        )?;
+        writeln_name_value(f, "Epoch Fee:", &fee(&self.stake_pool.epoch_fee))?;
+        writeln_name_value(f, "Withdrawal Fee:", &fee(&self.stake_pool.withdrawal_fee))?;
+        writeln_name_value(
+            f,
+            "Validators:",
+            &format!(
+                "{} of {}",
+                self.stake_pool.validators.len(),
+                self.stake_pool.max_validators
+            ),
+        )?;
+        for validator in &self.stake_pool.validators {
+            writeln!(
+                f,
+                "  {}  active: {}  transient: {}",
+                validator.vote_account,
+                lamports(&validator.active_stake_lamports),
+                lamports(&validator.transient_stake_lamports),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliStakePool {}
+impl VerboseDisplay for CliStakePool {}
+
 #[derive(Serialize, Deserialize)]
 pub struct UnsupportedAccount {
     pub address: String,
@@ -324,6 +324,16 @@ pub fn write_transaction<W: io::Write>(
             }
         }
 
+        if let Some(return_data) = &transaction_status.return_data {
+            let (data, _encoding) = &return_data.data;
+            writeln!(
+                w,
+                "{}Return Data from Program {}:",
+                prefix, return_data.program_id
+            )?;
+            writeln!(w, "{}  {}", prefix, data)?;
+        }
+
         if let Some(rewards) = &transaction_status.rewards {
             if !rewards.is_empty() {
                 writeln!(w, "{}Rewards:", prefix,)?;
@@ -7,6 +7,9 @@ use {
     mundis_clap_utils::{self, input_validators::*, keypair::*},
     mundis_cli_config::CONFIG_FILE,
 };
+use crate::governance::GovernanceSubCommands;
+use crate::program::ProgramSubCommands;
+use crate::stake_pool::StakePoolSubCommands;
 use crate::token::TokenSubCommands;
 
 pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, 'v> {
@@ -123,10 +126,13 @@ pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> A
         )
         .cluster_query_subcommands()
         .feature_subcommands()
+        .governance_subcommands()
         .inflation_subcommands()
         .token_subcommands()
         .nonce_subcommands()
+        .program_subcommands()
         .stake_subcommands()
+        .stake_pool_subcommands()
         .validator_info_subcommands()
         .vote_subcommands()
         .wallet_subcommands()
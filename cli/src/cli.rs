@@ -1,7 +1,7 @@
 use {
     crate::{
-        clap_app::*, cluster_query::*, feature::*, inflation::*, nonce::*,
-        spend_utils::*, stake::*, validator_info::*, vote::*, wallet::*, token::*
+        clap_app::*, cluster_query::*, feature::*, governance::*, inflation::*, nonce::*,
+        program::*, spend_utils::*, stake::*, stake_pool::*, validator_info::*, vote::*, wallet::*, token::*
     },
     clap::{crate_description, crate_name, value_t_or_exit, ArgMatches, Shell},
     log::*,
@@ -274,6 +274,13 @@ pub enum CliCommand {
         seed: Option<String>,
         fee_payer: SignerIndex,
     },
+    // Stake Pool Commands
+    StakePool(StakePoolCliCommand),
+    // Governance Commands
+    Governance(GovernanceCliCommand),
+    // Program Commands
+    Program(ProgramCliCommand),
+
     // Validator Info Commands
     GetValidatorInfo(Option<Pubkey>),
     SetValidatorInfo {
@@ -926,6 +933,21 @@ pub fn parse_command(
         ("stake-account", Some(matches)) => parse_show_stake_account(matches, wallet_manager),
         ("stake-history", Some(matches)) => parse_show_stake_history(matches),
 
+        // Stake Pool Commands
+        ("stake-pool", Some(matches)) => {
+            parse_stake_pool_subcommand(matches, default_signer, wallet_manager)
+        }
+
+        // Governance Commands
+        ("governance", Some(matches)) => {
+            parse_governance_subcommand(matches, default_signer, wallet_manager)
+        }
+
+        // Program Commands
+        ("program", Some(matches)) => {
+            parse_program_subcommand(matches, default_signer, wallet_manager)
+        }
+
         // Validator Info Commands
         ("validator-info", Some(matches)) => match matches.subcommand() {
             ("publish", Some(matches)) => {
@@ -1516,6 +1538,21 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *fee_payer,
         ),
 
+        // Stake Pool Commands
+        CliCommand::StakePool(stake_pool_subcommand) => {
+            process_stake_pool_subcommand(&rpc_client, config, stake_pool_subcommand)
+        }
+
+        // Governance Commands
+        CliCommand::Governance(governance_subcommand) => {
+            process_governance_subcommand(&rpc_client, config, governance_subcommand)
+        }
+
+        // Program Commands
+        CliCommand::Program(program_subcommand) => {
+            process_program_subcommand(&rpc_client, config, program_subcommand)
+        }
+
         // Validator Info Commands
 
         // Return all or single validator info
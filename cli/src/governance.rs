@@ -0,0 +1,611 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand, value_t_or_exit};
+use console::style;
+use serde::{Deserialize, Serialize};
+
+use mundis_clap_utils::fee_payer::fee_payer_arg;
+use mundis_clap_utils::input_parsers::{pubkey_of, pubkeys_of, signer_of, signer_of_or_else};
+use mundis_clap_utils::input_validators::{is_valid_pubkey, is_valid_signer};
+use mundis_clap_utils::keypair::DefaultSigner;
+use mundis_clap_utils::nonce::NonceArgs;
+use mundis_clap_utils::offline::OfflineArgs;
+use mundis_cli_output::{QuietDisplay, VerboseDisplay};
+use mundis_client::rpc_client::RpcClient;
+use mundis_governance_program::{find_authority_address, MAX_VOTING_EPOCHS};
+use mundis_governance_program::governance_instruction::{
+    cast_vote, create_proposal, escrow_feature, execute_proposal, finalize_proposal,
+};
+use mundis_governance_program::state::{
+    GovernanceState, Proposal, ProposalAction, ProposalStatus, VoteChoice,
+};
+use mundis_remote_wallet::remote_wallet::RemoteWalletManager;
+use mundis_sdk::clock::Epoch;
+use mundis_sdk::feature::Feature;
+use mundis_sdk::instruction::Instruction;
+use mundis_sdk::pubkey::Pubkey;
+use mundis_sdk::signer::Signer;
+
+use crate::cli::{CliCommand, CliCommandInfo, CliConfig, CliError, create_tx_info, ProcessResult, TxInfo};
+use crate::token::{add_default_signers, handle_tx, new_throwaway_signer, println_display, send_instructions, signer_or_exit, format_tx_return};
+
+pub const DEFAULT_VOTING_EPOCHS: &str = "2";
+
+#[derive(Debug, PartialEq)]
+pub enum GovernanceCliCommand {
+    EscrowFeature {
+        feature: Pubkey,
+        tx_info: TxInfo,
+    },
+    Propose {
+        proposal: Pubkey,
+        vote_account: Pubkey,
+        authorized_withdrawer: Pubkey,
+        description: String,
+        actions: Vec<ProposalAction>,
+        voting_epochs: Epoch,
+        tx_info: TxInfo,
+    },
+    Vote {
+        proposal: Pubkey,
+        vote_account: Pubkey,
+        authorized_withdrawer: Pubkey,
+        choice: VoteChoice,
+        tx_info: TxInfo,
+    },
+    Finalize {
+        proposal: Pubkey,
+        tx_info: TxInfo,
+    },
+    Execute {
+        proposal: Pubkey,
+        tx_info: TxInfo,
+    },
+    Show {
+        proposal: Pubkey,
+    },
+    List,
+}
+
+pub trait GovernanceSubCommands {
+    fn governance_subcommands(self) -> Self;
+}
+
+impl GovernanceSubCommands for App<'_, '_> {
+    fn governance_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("governance")
+                .about("Propose and vote on changes to the cluster with delegated stake")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .arg(fee_payer_arg().global(true))
+                .subcommand(
+                    SubCommand::with_name("escrow-feature")
+                        .about("Hand a feature over to governance, so a passed proposal can activate it")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(
+                            Arg::with_name("feature")
+                                .index(1)
+                                .value_name("FEATURE_KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .required(true)
+                                .help("Keypair of the feature to escrow"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("propose")
+                        .about("Create a proposal")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(
+                            Arg::with_name("description")
+                                .index(1)
+                                .value_name("DESCRIPTION")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Description of the proposal, usually a title and a link to the discussion"),
+                        )
+                        .arg(
+                            Arg::with_name("activate_feature")
+                                .long("activate-feature")
+                                .value_name("FEATURE_ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help("Activate an escrowed feature once the proposal passes. May be specified multiple times"),
+                        )
+                        .arg(
+                            Arg::with_name("instruction")
+                                .long("instruction")
+                                .value_name("BASE58_INSTRUCTION")
+                                .validator(is_instruction)
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help("Base58 encoded, bincode serialized instruction to invoke once the proposal \
+                                       passes. Only the governance authority can sign it. May be specified multiple times"),
+                        )
+                        .arg(
+                            Arg::with_name("voting_epochs")
+                                .long("voting-epochs")
+                                .value_name("EPOCHS")
+                                .validator(is_voting_epochs)
+                                .takes_value(true)
+                                .default_value(DEFAULT_VOTING_EPOCHS)
+                                .help("Number of epochs the proposal accepts votes"),
+                        )
+                        .arg(
+                            Arg::with_name("proposal_keypair")
+                                .long("proposal-keypair")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Proposal keypair [default: randomly generated keypair]"),
+                        )
+                        .arg(vote_account_arg())
+                        .arg(authorized_withdrawer_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("vote")
+                        .about("Vote on a proposal with the stake delegated to a vote account")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(proposal_arg())
+                        .arg(
+                            Arg::with_name("choice")
+                                .index(2)
+                                .value_name("CHOICE")
+                                .possible_values(&["yes", "no"])
+                                .takes_value(true)
+                                .required(true)
+                                .help("Vote for or against the proposal"),
+                        )
+                        .arg(vote_account_arg())
+                        .arg(authorized_withdrawer_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("finalize")
+                        .about("Settle a proposal whose voting period ended")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(proposal_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("execute")
+                        .about("Execute the actions of a passed proposal")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(proposal_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Show a proposal")
+                        .arg(proposal_arg()),
+                )
+                .subcommand(SubCommand::with_name("list").about("List all proposals")),
+        )
+    }
+}
+
+fn proposal_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("proposal")
+        .index(1)
+        .value_name("PROPOSAL_ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .required(true)
+        .help("Address of the proposal")
+}
+
+fn vote_account_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("vote_account")
+        .long("vote-account")
+        .value_name("VOTE_ACCOUNT_ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .required(true)
+        .help("Vote account whose delegated stake is used")
+}
+
+fn authorized_withdrawer_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("authorized_withdrawer")
+        .long("authorized-withdrawer")
+        .value_name("KEYPAIR")
+        .validator(is_valid_signer)
+        .takes_value(true)
+        .help("Authorized withdrawer of the vote account [default: the client keypair]")
+}
+
+fn parse_instruction(string: &str) -> Result<Instruction, String> {
+    let bytes = bs58::decode(string).into_vec().map_err(|e| e.to_string())?;
+    bincode::deserialize(&bytes).map_err(|e| e.to_string())
+}
+
+fn is_instruction(string: String) -> Result<(), String> {
+    parse_instruction(&string).map(|_| ())
+}
+
+fn is_voting_epochs(string: String) -> Result<(), String> {
+    match Epoch::from_str(&string) {
+        Ok(epochs) if epochs > 0 && epochs <= MAX_VOTING_EPOCHS => Ok(()),
+        Ok(_) => Err(format!("must be between 1 and {}", MAX_VOTING_EPOCHS)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn parse_governance_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let (subcommand, matches) = matches.subcommand();
+    let matches = matches.unwrap();
+    match subcommand {
+        "show" => {
+            return Ok(CliCommandInfo {
+                command: CliCommand::Governance(GovernanceCliCommand::Show {
+                    proposal: pubkey_of(matches, "proposal").unwrap(),
+                }),
+                signers: vec![],
+            })
+        }
+        "list" => {
+            return Ok(CliCommandInfo {
+                command: CliCommand::Governance(GovernanceCliCommand::List),
+                signers: vec![],
+            })
+        }
+        _ => {}
+    }
+
+    let mut bulk_signers: Vec<Option<Box<dyn Signer>>> = vec![];
+    let default_signers = add_default_signers(matches, wallet_manager, &mut bulk_signers)?;
+    let proposal = pubkey_of(matches, "proposal");
+
+    // Every arm registers its signers before the transaction info can be resolved
+    let command: Box<dyn FnOnce(TxInfo) -> GovernanceCliCommand> = match subcommand {
+        "escrow-feature" => {
+            let (feature_signer, feature) = signer_of(matches, "feature", wallet_manager)?;
+            bulk_signers.push(feature_signer);
+            Box::new(move |tx_info| GovernanceCliCommand::EscrowFeature {
+                feature: feature.unwrap(),
+                tx_info,
+            })
+        }
+        "propose" => {
+            let (proposal_signer, proposal) =
+                signer_of_or_else(matches, "proposal_keypair", wallet_manager, new_throwaway_signer)?;
+            bulk_signers.push(proposal_signer);
+            let authorized_withdrawer = signer_or_exit(
+                matches,
+                "authorized_withdrawer",
+                default_signer,
+                wallet_manager,
+                &mut bulk_signers,
+            );
+            let vote_account = pubkey_of(matches, "vote_account").unwrap();
+            let description = value_t_or_exit!(matches, "description", String);
+            let mut actions: Vec<ProposalAction> = pubkeys_of(matches, "activate_feature")
+                .unwrap_or_default()
+                .into_iter()
+                .map(ProposalAction::ActivateFeature)
+                .collect();
+            actions.extend(
+                matches
+                    .values_of("instruction")
+                    .into_iter()
+                    .flatten()
+                    .map(|instruction| ProposalAction::Invoke(parse_instruction(instruction).unwrap())),
+            );
+            if actions.is_empty() {
+                return Err(CliError::BadParameter(
+                    "a proposal needs --activate-feature or --instruction".to_string(),
+                ));
+            }
+            let voting_epochs = value_t_or_exit!(matches, "voting_epochs", Epoch);
+            Box::new(move |tx_info| GovernanceCliCommand::Propose {
+                proposal: proposal.unwrap(),
+                vote_account,
+                authorized_withdrawer,
+                description,
+                actions,
+                voting_epochs,
+                tx_info,
+            })
+        }
+        "vote" => {
+            let authorized_withdrawer = signer_or_exit(
+                matches,
+                "authorized_withdrawer",
+                default_signer,
+                wallet_manager,
+                &mut bulk_signers,
+            );
+            let vote_account = pubkey_of(matches, "vote_account").unwrap();
+            let choice = match matches.value_of("choice").unwrap() {
+                "yes" => VoteChoice::Yes,
+                _ => VoteChoice::No,
+            };
+            Box::new(move |tx_info| GovernanceCliCommand::Vote {
+                proposal: proposal.unwrap(),
+                vote_account,
+                authorized_withdrawer,
+                choice,
+                tx_info,
+            })
+        }
+        "finalize" => Box::new(move |tx_info| GovernanceCliCommand::Finalize {
+            proposal: proposal.unwrap(),
+            tx_info,
+        }),
+        "execute" => Box::new(move |tx_info| GovernanceCliCommand::Execute {
+            proposal: proposal.unwrap(),
+            tx_info,
+        }),
+        _ => unreachable!(),
+    };
+
+    let signer_info = default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+    let (fee_payer_pubkey, nonce_account, nonce_authority_pubkey, _) = default_signers;
+    let tx_info = create_tx_info(matches, &signer_info, fee_payer_pubkey, nonce_account, nonce_authority_pubkey);
+
+    Ok(CliCommandInfo {
+        command: CliCommand::Governance(command(tx_info)),
+        signers: signer_info.signers,
+    })
+}
+
+pub fn process_governance_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    governance_subcommand: &GovernanceCliCommand,
+) -> ProcessResult {
+    match governance_subcommand {
+        GovernanceCliCommand::EscrowFeature { feature, tx_info } => {
+            println_display(config, format!("Escrowing feature {} with governance", feature));
+            let fee_payer = config.signers[tx_info.fee_payer].pubkey();
+            let lamports = if !tx_info.sign_only {
+                rpc_client.get_minimum_balance_for_rent_exemption(Feature::size_of())?
+            } else {
+                0
+            };
+            send_instructions(
+                rpc_client,
+                config,
+                escrow_feature(&fee_payer, feature, lamports),
+                tx_info,
+            )
+        }
+        GovernanceCliCommand::Propose {
+            proposal,
+            vote_account,
+            authorized_withdrawer,
+            description,
+            actions,
+            voting_epochs,
+            tx_info,
+        } => {
+            println_display(config, format!("Creating proposal {}", proposal));
+            let fee_payer = config.signers[tx_info.fee_payer].pubkey();
+            let size = GovernanceState::proposal_size_of(description, actions);
+            let minimum_balance = if !tx_info.sign_only {
+                rpc_client.get_minimum_balance_for_rent_exemption(size)?
+            } else {
+                0
+            };
+            let tx_return = handle_tx(
+                rpc_client,
+                config,
+                minimum_balance,
+                vec![create_proposal(
+                    &fee_payer,
+                    proposal,
+                    vote_account,
+                    authorized_withdrawer,
+                    description,
+                    actions.clone(),
+                    *voting_epochs,
+                )],
+                tx_info,
+            )?;
+            Ok(format_tx_return(config, tx_return))
+        }
+        GovernanceCliCommand::Vote {
+            proposal,
+            vote_account,
+            authorized_withdrawer,
+            choice,
+            tx_info,
+        } => {
+            let fee_payer = config.signers[tx_info.fee_payer].pubkey();
+            send_instructions(
+                rpc_client,
+                config,
+                vec![cast_vote(proposal, vote_account, authorized_withdrawer, &fee_payer, *choice)],
+                tx_info,
+            )
+        }
+        GovernanceCliCommand::Finalize { proposal, tx_info } => send_instructions(
+            rpc_client,
+            config,
+            vec![finalize_proposal(proposal)],
+            tx_info,
+        ),
+        GovernanceCliCommand::Execute { proposal, tx_info } => {
+            let state = get_proposal(rpc_client, proposal)?;
+            send_instructions(
+                rpc_client,
+                config,
+                vec![execute_proposal(proposal, &state.actions)],
+                tx_info,
+            )
+        }
+        GovernanceCliCommand::Show { proposal } => {
+            let state = get_proposal(rpc_client, proposal)?;
+            Ok(config
+                .output_format
+                .formatted_string(&CliProposal::new(proposal, state)))
+        }
+        GovernanceCliCommand::List => {
+            let mut proposals: Vec<_> = rpc_client
+                .get_program_accounts(&mundis_governance_program::id())?
+                .into_iter()
+                .filter_map(|(address, account)| {
+                    GovernanceState::deserialize(&account.data)
+                        .ok()
+                        .and_then(GovernanceState::proposal)
+                        .map(|state| CliProposal::new(&address, state))
+                })
+                .collect();
+            proposals.sort_by(|a, b| b.voting_end_epoch.cmp(&a.voting_end_epoch));
+            Ok(config.output_format.formatted_string(&CliProposals {
+                authority: find_authority_address().0.to_string(),
+                proposals,
+            }))
+        }
+    }
+}
+
+fn get_proposal(
+    rpc_client: &RpcClient,
+    proposal: &Pubkey,
+) -> Result<Proposal, Box<dyn std::error::Error>> {
+    let account = rpc_client.get_account(proposal)?;
+    if account.owner != mundis_governance_program::id() {
+        return Err(CliError::RpcRequestError(format!(
+            "{:?} is not a proposal",
+            proposal
+        ))
+        .into());
+    }
+    GovernanceState::deserialize(&account.data)?
+        .proposal()
+        .ok_or_else(|| format!("{:?} is not a proposal", proposal).into())
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProposal {
+    pub address: String,
+    pub proposer: String,
+    pub description: String,
+    pub status: String,
+    pub stake_epoch: Epoch,
+    pub voting_end_epoch: Epoch,
+    pub yes_stake: u64,
+    pub no_stake: u64,
+    pub total_stake: u64,
+    pub actions: Vec<String>,
+}
+
+impl CliProposal {
+    fn new(address: &Pubkey, proposal: Proposal) -> Self {
+        Self {
+            address: address.to_string(),
+            proposer: proposal.proposer.to_string(),
+            description: proposal.description,
+            status: match proposal.status {
+                ProposalStatus::Voting => "voting",
+                ProposalStatus::Succeeded => "succeeded",
+                ProposalStatus::Defeated => "defeated",
+                ProposalStatus::Executed => "executed",
+            }
+            .to_string(),
+            stake_epoch: proposal.stake_epoch,
+            voting_end_epoch: proposal.voting_end_epoch,
+            yes_stake: proposal.yes_stake,
+            no_stake: proposal.no_stake,
+            total_stake: proposal.total_stake,
+            actions: proposal
+                .actions
+                .iter()
+                .map(|action| match action {
+                    ProposalAction::Invoke(instruction) => format!(
+                        "invoke {} with {} accounts and {} bytes of data",
+                        instruction.program_id,
+                        instruction.accounts.len(),
+                        instruction.data.len()
+                    ),
+                    ProposalAction::ActivateFeature(feature) => format!("activate feature {}", feature),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn stake_percentage(stake: u64, total_stake: u64) -> f64 {
+    if total_stake == 0 {
+        0.
+    } else {
+        stake as f64 * 100. / total_stake as f64
+    }
+}
+
+impl fmt::Display for CliProposal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", style(format!("Proposal {}", self.address)).bold())?;
+        writeln!(f, "  Description: {}", self.description)?;
+        writeln!(f, "  Proposer: {}", self.proposer)?;
+        writeln!(f, "  Status: {}", self.status)?;
+        writeln!(f, "  Stake Epoch: {}", self.stake_epoch)?;
+        writeln!(f, "  Voting Ends: epoch {}", self.voting_end_epoch)?;
+        writeln!(
+            f,
+            "  Yes: {:.2}%  No: {:.2}%",
+            stake_percentage(self.yes_stake, self.total_stake),
+            stake_percentage(self.no_stake, self.total_stake),
+        )?;
+        for action in &self.actions {
+            writeln!(f, "  Action: {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliProposal {}
+impl VerboseDisplay for CliProposal {}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProposals {
+    pub authority: String,
+    pub proposals: Vec<CliProposal>,
+}
+
+impl fmt::Display for CliProposals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Governance Authority: {}", self.authority)?;
+        for proposal in &self.proposals {
+            writeln!(f)?;
+            write!(f, "{}", proposal)?;
+        }
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliProposals {}
+impl VerboseDisplay for CliProposals {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instruction() {
+        let instruction = mundis_sdk::system_instruction::transfer(
+            &find_authority_address().0,
+            &Pubkey::new_unique(),
+            42,
+        );
+        let encoded = bs58::encode(bincode::serialize(&instruction).unwrap()).into_string();
+        assert_eq!(parse_instruction(&encoded), Ok(instruction));
+        assert!(parse_instruction("not an instruction").is_err());
+        assert!(is_voting_epochs("1".to_string()).is_ok());
+        assert!(is_voting_epochs("0".to_string()).is_err());
+        assert!(is_voting_epochs((MAX_VOTING_EPOCHS + 1).to_string()).is_err());
+    }
+}
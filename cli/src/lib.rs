@@ -28,11 +28,14 @@ pub mod clap_app;
 pub mod cli;
 pub mod cluster_query;
 pub mod feature;
+pub mod governance;
 pub mod inflation;
 pub mod memo;
 pub mod nonce;
+pub mod program;
 pub mod spend_utils;
 pub mod stake;
+pub mod stake_pool;
 pub mod test_utils;
 pub mod token;
 pub mod validator_info;
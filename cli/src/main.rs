@@ -4,6 +4,7 @@ use {
     mundis_clap_utils::{
         input_validators::normalize_to_url_if_moniker,
         keypair::{CliSigners, DefaultSigner},
+        name_service::set_name_resolver,
         DisplayError,
     },
     mundis_cli::{
@@ -12,7 +13,7 @@ use {
     },
     mundis_cli_config::Config,
     mundis_cli_output::{display::println_name_value, OutputFormat},
-    mundis_client::rpc_config::RpcSendTransactionConfig,
+    mundis_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig},
     mundis_remote_wallet::remote_wallet::RemoteWalletManager,
     std::{collections::HashMap, error, path::PathBuf, sync::Arc, time::Duration},
 };
@@ -185,6 +186,23 @@ pub fn parse_args<'a>(
 
     let default_signer = DefaultSigner::new(default_signer_arg_name, &default_signer_path);
 
+    let (_, commitment) = CliConfig::compute_commitment_config(
+        matches.value_of("commitment").unwrap_or(""),
+        &config.commitment,
+    );
+
+    // Let pubkey arguments accept names registered with the name service
+    let name_rpc_client = RpcClient::new_with_timeout_and_commitment(
+        json_rpc_url.clone(),
+        rpc_timeout,
+        commitment,
+    );
+    set_name_resolver(Arc::new(move |name: &str| {
+        name_rpc_client
+            .resolve_name(name)
+            .map_err(|err| err.into())
+    }));
+
     let CliCommandInfo {
         command,
         mut signers,
@@ -201,11 +219,6 @@ pub fn parse_args<'a>(
     let verbose = matches.is_present("verbose");
     let output_format = OutputFormat::from_matches(matches, "output_format", verbose);
 
-    let (_, commitment) = CliConfig::compute_commitment_config(
-        matches.value_of("commitment").unwrap_or(""),
-        &config.commitment,
-    );
-
     let address_labels = if matches.is_present("no_address_labels") {
         HashMap::new()
     } else {
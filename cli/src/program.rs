@@ -0,0 +1,523 @@
+use std::fmt;
+use std::fs;
+use std::sync::Arc;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use console::style;
+use serde::{Deserialize, Serialize};
+
+use mundis_clap_utils::fee_payer::fee_payer_arg;
+use mundis_clap_utils::input_parsers::{pubkey_of, signer_of_or_else};
+use mundis_clap_utils::input_validators::{is_parsable, is_valid_pubkey, is_valid_signer};
+use mundis_clap_utils::keypair::{DefaultSigner, SignerIndex};
+use mundis_cli_output::{CliSignature, QuietDisplay, VerboseDisplay};
+use mundis_client::rpc_client::RpcClient;
+use mundis_remote_wallet::remote_wallet::RemoteWalletManager;
+use mundis_sdk::instruction::Instruction;
+use mundis_sdk::message::Message;
+use mundis_sdk::pubkey::Pubkey;
+use mundis_sdk::signature::{Keypair, Signature};
+use mundis_sdk::signer::Signer;
+use mundis_sdk::transaction::Transaction;
+use mundis_wasm_loader_program::loader_instruction::{
+    close_program, create_buffer, deploy_with_max_program_len, set_upgrade_authority, upgrade,
+    write,
+};
+use mundis_wasm_loader_program::syscalls::Syscall;
+use mundis_wasm_loader_program::vm::Module;
+use mundis_wasm_loader_program::UpgradeableLoaderState;
+
+use crate::cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult};
+use crate::token::{add_default_signers, new_throwaway_signer, println_display, signer_or_exit};
+
+/// Bytecode written per transaction, leaving room for the signatures,
+/// account keys and instruction header within the packet size
+const WRITE_CHUNK_SIZE: usize = 900;
+
+#[derive(Debug, PartialEq)]
+pub enum ProgramCliCommand {
+    Deploy {
+        program_location: String,
+        program: Pubkey,
+        upgrade_authority: Pubkey,
+        max_len: Option<usize>,
+        fee_payer: SignerIndex,
+    },
+    Upgrade {
+        program_location: String,
+        program: Pubkey,
+        upgrade_authority: Pubkey,
+        fee_payer: SignerIndex,
+    },
+    SetUpgradeAuthority {
+        program: Pubkey,
+        upgrade_authority: Pubkey,
+        new_upgrade_authority: Option<Pubkey>,
+        fee_payer: SignerIndex,
+    },
+    Close {
+        program: Pubkey,
+        recipient: Pubkey,
+        upgrade_authority: Pubkey,
+        fee_payer: SignerIndex,
+    },
+    Show {
+        program: Pubkey,
+    },
+}
+
+pub trait ProgramSubCommands {
+    fn program_subcommands(self) -> Self;
+}
+
+impl ProgramSubCommands for App<'_, '_> {
+    fn program_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("program")
+                .about("Deploy and manage upgradeable WebAssembly programs")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .arg(fee_payer_arg().global(true))
+                .subcommand(
+                    SubCommand::with_name("deploy")
+                        .about("Deploy a program")
+                        .arg(program_location_arg())
+                        .arg(
+                            Arg::with_name("program_keypair")
+                                .long("program-id")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Keypair of the program's address [default: randomly generated keypair]"),
+                        )
+                        .arg(
+                            Arg::with_name("max_len")
+                                .long("max-len")
+                                .value_name("BYTES")
+                                .validator(is_parsable::<usize>)
+                                .takes_value(true)
+                                .help("Largest bytecode the program can be upgraded to [default: twice the size of the program]"),
+                        )
+                        .arg(upgrade_authority_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("upgrade")
+                        .about("Replace the bytecode of a deployed program")
+                        .arg(program_location_arg())
+                        .arg(program_arg(2))
+                        .arg(upgrade_authority_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-upgrade-authority")
+                        .about("Change the upgrade authority of a program, or make it immutable")
+                        .arg(program_arg(1))
+                        .arg(
+                            Arg::with_name("new_upgrade_authority")
+                                .long("new-upgrade-authority")
+                                .value_name("ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .required_unless("final")
+                                .help("New upgrade authority"),
+                        )
+                        .arg(
+                            Arg::with_name("final")
+                                .long("final")
+                                .conflicts_with("new_upgrade_authority")
+                                .help("Make the program immutable, it can never be upgraded or closed again"),
+                        )
+                        .arg(upgrade_authority_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("close")
+                        .about("Close a program and reclaim the lamports of its bytecode")
+                        .arg(program_arg(1))
+                        .arg(
+                            Arg::with_name("recipient")
+                                .long("recipient")
+                                .value_name("ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .help("Recipient of the reclaimed lamports [default: the fee payer]"),
+                        )
+                        .arg(upgrade_authority_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Show a deployed program")
+                        .arg(program_arg(1)),
+                ),
+        )
+    }
+}
+
+fn program_location_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("program_location")
+        .index(1)
+        .value_name("PROGRAM_FILEPATH")
+        .takes_value(true)
+        .required(true)
+        .help("WebAssembly bytecode of the program")
+}
+
+fn program_arg<'a, 'b>(index: u64) -> Arg<'a, 'b> {
+    Arg::with_name("program")
+        .index(index)
+        .value_name("PROGRAM_ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .required(true)
+        .help("Address of the program")
+}
+
+fn upgrade_authority_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("upgrade_authority")
+        .long("upgrade-authority")
+        .value_name("KEYPAIR")
+        .validator(is_valid_signer)
+        .takes_value(true)
+        .help("Upgrade authority of the program [default: the client keypair]")
+}
+
+pub fn parse_program_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let (subcommand, matches) = matches.subcommand();
+    let matches = matches.unwrap();
+    let program = pubkey_of(matches, "program");
+    if subcommand == "show" {
+        return Ok(CliCommandInfo {
+            command: CliCommand::Program(ProgramCliCommand::Show {
+                program: program.unwrap(),
+            }),
+            signers: vec![],
+        });
+    }
+
+    let mut bulk_signers: Vec<Option<Box<dyn Signer>>> = vec![];
+    let (fee_payer_pubkey, ..) = add_default_signers(matches, wallet_manager, &mut bulk_signers)?;
+    let upgrade_authority = signer_or_exit(
+        matches,
+        "upgrade_authority",
+        default_signer,
+        wallet_manager,
+        &mut bulk_signers,
+    );
+    let program_location = matches.value_of("program_location").map(str::to_string);
+
+    // Every arm registers its signers before the fee payer can be resolved
+    let command: Box<dyn FnOnce(SignerIndex, Pubkey) -> ProgramCliCommand> = match subcommand {
+        "deploy" => {
+            let (program_signer, program) =
+                signer_of_or_else(matches, "program_keypair", wallet_manager, new_throwaway_signer)?;
+            bulk_signers.push(program_signer);
+            let max_len = matches
+                .value_of("max_len")
+                .map(|max_len| max_len.parse().unwrap());
+            Box::new(move |fee_payer, _| ProgramCliCommand::Deploy {
+                program_location: program_location.unwrap(),
+                program: program.unwrap(),
+                upgrade_authority,
+                max_len,
+                fee_payer,
+            })
+        }
+        "upgrade" => Box::new(move |fee_payer, _| ProgramCliCommand::Upgrade {
+            program_location: program_location.unwrap(),
+            program: program.unwrap(),
+            upgrade_authority,
+            fee_payer,
+        }),
+        "set-upgrade-authority" => {
+            let new_upgrade_authority = pubkey_of(matches, "new_upgrade_authority");
+            Box::new(move |fee_payer, _| ProgramCliCommand::SetUpgradeAuthority {
+                program: program.unwrap(),
+                upgrade_authority,
+                new_upgrade_authority,
+                fee_payer,
+            })
+        }
+        "close" => {
+            let recipient = pubkey_of(matches, "recipient");
+            Box::new(move |fee_payer, fee_payer_pubkey| ProgramCliCommand::Close {
+                program: program.unwrap(),
+                recipient: recipient.unwrap_or(fee_payer_pubkey),
+                upgrade_authority,
+                fee_payer,
+            })
+        }
+        _ => unreachable!(),
+    };
+
+    let signer_info = default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+    let fee_payer = signer_info.index_of(fee_payer_pubkey).unwrap();
+    let fee_payer_pubkey = signer_info.signers[fee_payer].pubkey();
+    Ok(CliCommandInfo {
+        command: CliCommand::Program(command(fee_payer, fee_payer_pubkey)),
+        signers: signer_info.signers,
+    })
+}
+
+pub fn process_program_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_subcommand: &ProgramCliCommand,
+) -> ProcessResult {
+    match program_subcommand {
+        ProgramCliCommand::Deploy {
+            program_location,
+            program,
+            upgrade_authority,
+            max_len,
+            fee_payer,
+        } => {
+            let bytecode = read_program(program_location)?;
+            let max_len = max_len.unwrap_or(bytecode.len() * 2);
+            if max_len < bytecode.len() {
+                return Err(CliError::BadParameter(format!(
+                    "--max-len must be at least the size of the program, {} bytes",
+                    bytecode.len()
+                ))
+                .into());
+            }
+            if rpc_client.get_account(program).is_ok() {
+                return Err(format!("Program {} already exists", program).into());
+            }
+            let buffer = write_buffer(rpc_client, config, *fee_payer, upgrade_authority, &bytecode)?;
+            let program_lamports = rpc_client
+                .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::program_len())?;
+            let signature = send(
+                rpc_client,
+                config,
+                *fee_payer,
+                &deploy_with_max_program_len(
+                    &config.signers[*fee_payer].pubkey(),
+                    program,
+                    &buffer,
+                    upgrade_authority,
+                    program_lamports,
+                    max_len,
+                )?,
+                &[],
+            )?;
+            println_display(config, format!("Program Id: {}", program));
+            Ok(config.output_format.formatted_string(&CliSignature {
+                signature: signature.to_string(),
+            }))
+        }
+        ProgramCliCommand::Upgrade {
+            program_location,
+            program,
+            upgrade_authority,
+            fee_payer,
+        } => {
+            let bytecode = read_program(program_location)?;
+            let buffer = write_buffer(rpc_client, config, *fee_payer, upgrade_authority, &bytecode)?;
+            let fee_payer_pubkey = config.signers[*fee_payer].pubkey();
+            let signature = send(
+                rpc_client,
+                config,
+                *fee_payer,
+                &[upgrade(program, &buffer, upgrade_authority, &fee_payer_pubkey)],
+                &[],
+            )?;
+            Ok(config.output_format.formatted_string(&CliSignature {
+                signature: signature.to_string(),
+            }))
+        }
+        ProgramCliCommand::SetUpgradeAuthority {
+            program,
+            upgrade_authority,
+            new_upgrade_authority,
+            fee_payer,
+        } => {
+            let signature = send(
+                rpc_client,
+                config,
+                *fee_payer,
+                &[set_upgrade_authority(
+                    program,
+                    upgrade_authority,
+                    new_upgrade_authority.as_ref(),
+                )],
+                &[],
+            )?;
+            Ok(config.output_format.formatted_string(&CliSignature {
+                signature: signature.to_string(),
+            }))
+        }
+        ProgramCliCommand::Close {
+            program,
+            recipient,
+            upgrade_authority,
+            fee_payer,
+        } => {
+            let signature = send(
+                rpc_client,
+                config,
+                *fee_payer,
+                &[close_program(program, recipient, upgrade_authority)],
+                &[],
+            )?;
+            Ok(config.output_format.formatted_string(&CliSignature {
+                signature: signature.to_string(),
+            }))
+        }
+        ProgramCliCommand::Show { program } => {
+            let account = rpc_client.get_account(program)?;
+            if account.owner != mundis_wasm_loader_program::id() {
+                return Err(format!("{} is not an upgradeable program", program).into());
+            }
+            let programdata_address = match UpgradeableLoaderState::deserialize(&account.data)? {
+                UpgradeableLoaderState::Program {
+                    programdata_address,
+                } => programdata_address,
+                _ => return Err(format!("{} is not an upgradeable program", program).into()),
+            };
+            let programdata = rpc_client.get_account(&programdata_address)?;
+            match UpgradeableLoaderState::deserialize(&programdata.data)? {
+                UpgradeableLoaderState::ProgramData {
+                    slot,
+                    upgrade_authority_address,
+                } => Ok(config.output_format.formatted_string(&CliProgram {
+                    program_id: program.to_string(),
+                    programdata_address: programdata_address.to_string(),
+                    authority: upgrade_authority_address
+                        .map(|authority| authority.to_string())
+                        .unwrap_or_else(|| "none".to_string()),
+                    last_deploy_slot: slot,
+                    data_len: programdata.data.len()
+                        - UpgradeableLoaderState::programdata_metadata_size(),
+                    lamports: programdata.lamports,
+                })),
+                _ => Err(format!("Program {} has been closed", program).into()),
+            }
+        }
+    }
+}
+
+/// Reads a program and checks the loader will accept it
+fn read_program(program_location: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let bytecode = fs::read(program_location)
+        .map_err(|err| format!("Unable to read {}: {}", program_location, err))?;
+    Module::new(&bytecode, Syscall::resolve)
+        .map_err(|err| format!("{} is not a valid program: {}", program_location, err))?;
+    Ok(bytecode)
+}
+
+/// Creates a buffer holding `bytecode`, returning its address
+fn write_buffer(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    fee_payer: SignerIndex,
+    authority: &Pubkey,
+    bytecode: &[u8],
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let buffer = Keypair::new();
+    let lamports = rpc_client.get_minimum_balance_for_rent_exemption(
+        UpgradeableLoaderState::buffer_len(bytecode.len()),
+    )?;
+    send(
+        rpc_client,
+        config,
+        fee_payer,
+        &create_buffer(
+            &config.signers[fee_payer].pubkey(),
+            &buffer.pubkey(),
+            authority,
+            lamports,
+            bytecode.len(),
+        ),
+        &[&buffer],
+    )?;
+    let chunks = bytecode.chunks(WRITE_CHUNK_SIZE).count();
+    for (index, chunk) in bytecode.chunks(WRITE_CHUNK_SIZE).enumerate() {
+        println_display(config, format!("Writing chunk {} of {}", index + 1, chunks));
+        send(
+            rpc_client,
+            config,
+            fee_payer,
+            &[write(
+                &buffer.pubkey(),
+                authority,
+                (index * WRITE_CHUNK_SIZE) as u32,
+                chunk.to_vec(),
+            )],
+            &[],
+        )?;
+    }
+    Ok(buffer.pubkey())
+}
+
+/// Sends a transaction signed by those of the configured signers it needs,
+/// plus `extra_signers`
+fn send(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    fee_payer: SignerIndex,
+    instructions: &[Instruction],
+    extra_signers: &[&dyn Signer],
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let message = Message::new(instructions, Some(&config.signers[fee_payer].pubkey()));
+    let required = &message.account_keys[..message.header.num_required_signatures as usize];
+    let mut signers: Vec<&dyn Signer> = config
+        .signers
+        .iter()
+        .filter(|signer| required.contains(&signer.pubkey()))
+        .copied()
+        .collect();
+    signers.extend_from_slice(extra_signers);
+    signers.sort_by_key(|signer| signer.pubkey());
+    signers.dedup_by_key(|signer| signer.pubkey());
+
+    let mut transaction = Transaction::new_unsigned(message);
+    let recent_blockhash = rpc_client.get_latest_blockhash()?;
+    transaction.try_sign(&signers, recent_blockhash)?;
+    Ok(rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgram {
+    pub program_id: String,
+    pub programdata_address: String,
+    pub authority: String,
+    pub last_deploy_slot: u64,
+    pub data_len: usize,
+    pub lamports: u64,
+}
+
+impl fmt::Display for CliProgram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", style(format!("Program {}", self.program_id)).bold())?;
+        writeln!(f, "  ProgramData Address: {}", self.programdata_address)?;
+        writeln!(f, "  Authority: {}", self.authority)?;
+        writeln!(f, "  Last Deployed In Slot: {}", self.last_deploy_slot)?;
+        writeln!(f, "  Data Length: {} bytes", self.data_len)?;
+        writeln!(f, "  Balance: {} lamports", self.lamports)?;
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliProgram {}
+impl VerboseDisplay for CliProgram {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_chunk_fits_in_a_transaction() {
+        let payer = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let message = Message::new(
+            &[write(&buffer, &authority, u32::MAX, vec![0; WRITE_CHUNK_SIZE])],
+            Some(&payer),
+        );
+        let transaction = Transaction::new_unsigned(message);
+        let size = bincode::serialized_size(&transaction).unwrap() as usize;
+        assert!(size <= mundis_sdk::packet::PACKET_DATA_SIZE);
+    }
+}
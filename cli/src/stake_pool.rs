@@ -0,0 +1,1093 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand, value_t_or_exit};
+
+use mundis_account_decoder::parse_stake_pool::UiStakePool;
+use mundis_clap_utils::fee_payer::fee_payer_arg;
+use mundis_clap_utils::input_parsers::{pubkey_of, pubkey_of_signer, signer_of, signer_of_or_else};
+use mundis_clap_utils::input_validators::{is_amount, is_valid_pubkey, is_valid_signer};
+use mundis_clap_utils::keypair::DefaultSigner;
+use mundis_clap_utils::nonce::NonceArgs;
+use mundis_clap_utils::offline::OfflineArgs;
+use mundis_cli_output::CliStakePool;
+use mundis_client::rpc_client::RpcClient;
+use mundis_remote_wallet::remote_wallet::RemoteWalletManager;
+use mundis_sdk::native_token::mdis_to_lamports;
+use mundis_sdk::program_pack::Pack;
+use mundis_sdk::pubkey::Pubkey;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::system_instruction;
+use mundis_stake_pool_program::{find_reserve_program_address, find_withdraw_authority_program_address, stake_pool_instruction};
+use mundis_stake_pool_program::state::{Fee, FeeType, StakePool, StakePoolState};
+use mundis_token_account_program::get_associated_token_address;
+use mundis_token_account_program::token_account_instruction::create_associated_token_account;
+use mundis_token_program::native_mint;
+use mundis_token_program::state::Mint;
+use mundis_token_program::token_instruction::initialize_mint;
+
+use crate::cli::{CliCommand, CliCommandInfo, CliConfig, CliError, create_tx_info, ProcessResult, TxInfo};
+use crate::token::{add_default_signers, format_tx_return, handle_tx, new_throwaway_signer, println_display, send_instructions, signer_or_exit};
+
+pub const DEFAULT_MAX_VALIDATORS: &str = "64";
+
+#[derive(Debug, PartialEq)]
+pub enum StakePoolCliCommand {
+    CreatePool {
+        stake_pool: Pubkey,
+        pool_mint: Pubkey,
+        manager: Pubkey,
+        staker: Pubkey,
+        epoch_fee: Fee,
+        withdrawal_fee: Fee,
+        max_validators: u32,
+        name: String,
+        symbol: String,
+        tx_info: TxInfo,
+    },
+    AddValidator {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        vote_account: Pubkey,
+        tx_info: TxInfo,
+    },
+    RemoveValidator {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        vote_account: Pubkey,
+        tx_info: TxInfo,
+    },
+    IncreaseValidatorStake {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        vote_account: Pubkey,
+        lamports: u64,
+        tx_info: TxInfo,
+    },
+    DecreaseValidatorStake {
+        stake_pool: Pubkey,
+        staker: Pubkey,
+        vote_account: Pubkey,
+        lamports: u64,
+        tx_info: TxInfo,
+    },
+    Update {
+        stake_pool: Pubkey,
+        tx_info: TxInfo,
+    },
+    Deposit {
+        stake_pool: Pubkey,
+        depositor: Pubkey,
+        pool_token_account: Option<Pubkey>,
+        lamports: u64,
+        tx_info: TxInfo,
+    },
+    Withdraw {
+        stake_pool: Pubkey,
+        owner: Pubkey,
+        pool_token_account: Option<Pubkey>,
+        destination: Pubkey,
+        pool_tokens: u64,
+        withdrawal_ticket: Option<Pubkey>,
+        tx_info: TxInfo,
+    },
+    ClaimWithdrawal {
+        stake_pool: Pubkey,
+        withdrawal_ticket: Pubkey,
+        beneficiary: Pubkey,
+        destination: Pubkey,
+        tx_info: TxInfo,
+    },
+    SetFee {
+        stake_pool: Pubkey,
+        manager: Pubkey,
+        fee_type: FeeType,
+        fee: Fee,
+        tx_info: TxInfo,
+    },
+    SetStaker {
+        stake_pool: Pubkey,
+        authority: Pubkey,
+        new_staker: Pubkey,
+        tx_info: TxInfo,
+    },
+    SetManager {
+        stake_pool: Pubkey,
+        manager: Pubkey,
+        new_manager: Pubkey,
+        new_manager_fee_account: Pubkey,
+        tx_info: TxInfo,
+    },
+    Show {
+        stake_pool: Pubkey,
+        use_lamports_unit: bool,
+    },
+}
+
+pub trait StakePoolSubCommands {
+    fn stake_pool_subcommands(self) -> Self;
+}
+
+impl StakePoolSubCommands for App<'_, '_> {
+    fn stake_pool_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("stake-pool")
+                .about("Manage liquid staking pools")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .arg(fee_payer_arg().global(true))
+                .subcommand(
+                    SubCommand::with_name("create-pool")
+                        .about("Create a new stake pool and its pool token mint")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(
+                            Arg::with_name("pool_keypair")
+                                .long("pool-keypair")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Stake pool keypair [default: randomly generated keypair]"),
+                        )
+                        .arg(
+                            Arg::with_name("mint_keypair")
+                                .long("mint-keypair")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Pool token mint keypair [default: randomly generated keypair]"),
+                        )
+                        .arg(manager_arg())
+                        .arg(
+                            Arg::with_name("staker")
+                                .long("staker")
+                                .value_name("ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .help("Staker authority of the pool [default: the manager]"),
+                        )
+                        .arg(
+                            Arg::with_name("epoch_fee")
+                                .long("epoch-fee")
+                                .value_name("NUMERATOR/DENOMINATOR")
+                                .validator(is_fee)
+                                .takes_value(true)
+                                .default_value("0/1")
+                                .help("Fee taken from staking rewards every epoch"),
+                        )
+                        .arg(
+                            Arg::with_name("withdrawal_fee")
+                                .long("withdrawal-fee")
+                                .value_name("NUMERATOR/DENOMINATOR")
+                                .validator(is_fee)
+                                .takes_value(true)
+                                .default_value("0/1")
+                                .help("Fee taken from instant and delayed withdrawals"),
+                        )
+                        .arg(
+                            Arg::with_name("max_validators")
+                                .long("max-validators")
+                                .value_name("NUMBER")
+                                .validator(|s| u32::from_str(&s).map(|_| ()).map_err(|e| e.to_string()))
+                                .takes_value(true)
+                                .default_value(DEFAULT_MAX_VALIDATORS)
+                                .help("Maximum number of validators the pool can delegate to"),
+                        )
+                        .arg(
+                            Arg::with_name("name")
+                                .long("name")
+                                .value_name("TOKEN_NAME")
+                                .takes_value(true)
+                                .default_value("Staked MUNDIS")
+                                .help("Name of the pool token"),
+                        )
+                        .arg(
+                            Arg::with_name("symbol")
+                                .long("symbol")
+                                .value_name("TOKEN_SYMBOL")
+                                .takes_value(true)
+                                .default_value("stMDIS")
+                                .help("Symbol of the pool token"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("add-validator")
+                        .about("Add a validator to the pool's validator list")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(vote_account_arg())
+                        .arg(staker_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove-validator")
+                        .about("Remove a validator without pool stake from the validator list")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(vote_account_arg())
+                        .arg(staker_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("increase-validator-stake")
+                        .about("Delegate MUNDIS from the pool reserve to a validator")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(vote_account_arg())
+                        .arg(amount_arg(3, "Amount to delegate, in MUNDIS"))
+                        .arg(staker_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("decrease-validator-stake")
+                        .about("Deactivate stake delegated to a validator, returning it to the reserve after cooldown")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(vote_account_arg())
+                        .arg(amount_arg(3, "Amount to deactivate, in MUNDIS"))
+                        .arg(staker_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("update")
+                        .about("Merge and reclaim transient stake and update the pool's exchange rate for the current epoch")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("deposit")
+                        .about("Deposit MUNDIS into the pool in exchange for pool tokens")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(amount_arg(2, "Amount to deposit, in MUNDIS"))
+                        .arg(
+                            Arg::with_name("from")
+                                .long("from")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Source account of the deposit [default: the client keypair]"),
+                        )
+                        .arg(pool_token_account_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("withdraw")
+                        .about("Burn pool tokens in exchange for MUNDIS")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(amount_arg(2, "Amount of pool tokens to burn"))
+                        .arg(
+                            Arg::with_name("owner")
+                                .long("owner")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Owner of the pool token account [default: the client keypair]"),
+                        )
+                        .arg(pool_token_account_arg())
+                        .arg(destination_arg())
+                        .arg(
+                            Arg::with_name("delayed")
+                                .long("delayed")
+                                .takes_value(false)
+                                .help(
+                                    "Create a withdrawal ticket, claimable next epoch, instead of \
+                                    withdrawing from the reserve immediately"
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("ticket_keypair")
+                                .long("ticket-keypair")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .requires("delayed")
+                                .help("Withdrawal ticket keypair [default: randomly generated keypair]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("claim-withdrawal")
+                        .about("Claim the MUNDIS owed by a withdrawal ticket")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(
+                            Arg::with_name("withdrawal_ticket")
+                                .index(2)
+                                .value_name("TICKET_ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .required(true)
+                                .help("Withdrawal ticket to claim"),
+                        )
+                        .arg(
+                            Arg::with_name("beneficiary")
+                                .long("beneficiary")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Beneficiary of the ticket [default: the client keypair]"),
+                        )
+                        .arg(destination_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-fee")
+                        .about("Change one of the pool's fees")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(
+                            Arg::with_name("fee_type")
+                                .index(2)
+                                .value_name("FEE_TYPE")
+                                .possible_values(&["epoch", "withdrawal"])
+                                .takes_value(true)
+                                .required(true)
+                                .help("Fee to change"),
+                        )
+                        .arg(
+                            Arg::with_name("fee")
+                                .index(3)
+                                .value_name("NUMERATOR/DENOMINATOR")
+                                .validator(is_fee)
+                                .takes_value(true)
+                                .required(true)
+                                .help("New fee rate"),
+                        )
+                        .arg(manager_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-staker")
+                        .about("Change the pool's staker authority")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(
+                            Arg::with_name("new_staker")
+                                .index(2)
+                                .value_name("ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .required(true)
+                                .help("New staker authority"),
+                        )
+                        .arg(
+                            Arg::with_name("authority")
+                                .long("authority")
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .help("Current manager or staker [default: the client keypair]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-manager")
+                        .about("Change the pool's manager and manager fee account")
+                        .nonce_args(true)
+                        .offline_args()
+                        .arg(stake_pool_arg())
+                        .arg(
+                            Arg::with_name("new_manager")
+                                .index(2)
+                                .value_name("KEYPAIR")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .required(true)
+                                .help("New manager authority"),
+                        )
+                        .arg(
+                            Arg::with_name("new_manager_fee_account")
+                                .index(3)
+                                .value_name("TOKEN_ACCOUNT_ADDRESS")
+                                .validator(is_valid_pubkey)
+                                .takes_value(true)
+                                .required(true)
+                                .help("Pool token account that receives the manager's fees"),
+                        )
+                        .arg(manager_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Show the contents of a stake pool")
+                        .arg(stake_pool_arg())
+                        .arg(
+                            Arg::with_name("lamports")
+                                .long("lamports")
+                                .takes_value(false)
+                                .help("Display balances in lamports instead of MUNDIS"),
+                        ),
+                )
+        )
+    }
+}
+
+fn stake_pool_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("stake_pool")
+        .index(1)
+        .value_name("STAKE_POOL_ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .required(true)
+        .help("Address of the stake pool")
+}
+
+fn vote_account_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("vote_account")
+        .index(2)
+        .value_name("VOTE_ACCOUNT_ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .required(true)
+        .help("Vote account of the validator")
+}
+
+fn amount_arg<'a, 'b>(index: u64, help: &'static str) -> Arg<'a, 'b> {
+    Arg::with_name("amount")
+        .index(index)
+        .value_name("AMOUNT")
+        .validator(is_amount)
+        .takes_value(true)
+        .required(true)
+        .help(help)
+}
+
+fn staker_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("staker")
+        .long("staker")
+        .value_name("KEYPAIR")
+        .validator(is_valid_signer)
+        .takes_value(true)
+        .help("Staker authority of the pool [default: the client keypair]")
+}
+
+fn manager_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("manager")
+        .long("manager")
+        .value_name("KEYPAIR")
+        .validator(is_valid_signer)
+        .takes_value(true)
+        .help("Manager authority of the pool [default: the client keypair]")
+}
+
+fn pool_token_account_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("pool_token_account")
+        .long("pool-token-account")
+        .value_name("TOKEN_ACCOUNT_ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .help("Pool token account [default: associated token account of the owner]")
+}
+
+fn destination_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("destination")
+        .long("destination")
+        .value_name("ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .help("Account receiving the withdrawn MUNDIS [default: the client keypair address]")
+}
+
+fn parse_fee(string: &str) -> Result<Fee, String> {
+    let (numerator, denominator) = string
+        .split_once('/')
+        .ok_or_else(|| "expected NUMERATOR/DENOMINATOR".to_string())?;
+    let fee = Fee {
+        numerator: u64::from_str(numerator).map_err(|e| e.to_string())?,
+        denominator: u64::from_str(denominator).map_err(|e| e.to_string())?,
+    };
+    if !fee.is_valid() {
+        return Err("fee must be between 0 and 1".to_string());
+    }
+    Ok(fee)
+}
+
+fn is_fee(string: String) -> Result<(), String> {
+    parse_fee(&string).map(|_| ())
+}
+
+fn fee_of(matches: &ArgMatches<'_>, name: &str) -> Fee {
+    parse_fee(matches.value_of(name).unwrap()).unwrap()
+}
+
+fn lamports_of(matches: &ArgMatches<'_>) -> u64 {
+    mdis_to_lamports(value_t_or_exit!(matches, "amount", f64))
+}
+
+fn pubkey_or_default_signer(
+    matches: &ArgMatches<'_>,
+    name: &str,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<Pubkey, CliError> {
+    if let Some(pubkey) = pubkey_of_signer(matches, name, wallet_manager)? {
+        Ok(pubkey)
+    } else {
+        Ok(default_signer.signer_from_path(matches, wallet_manager)?.pubkey())
+    }
+}
+
+pub fn parse_stake_pool_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let (subcommand, matches) = matches.subcommand();
+    let matches = matches.unwrap();
+    if subcommand == "show" {
+        return Ok(CliCommandInfo {
+            command: CliCommand::StakePool(StakePoolCliCommand::Show {
+                stake_pool: pubkey_of(matches, "stake_pool").unwrap(),
+                use_lamports_unit: matches.is_present("lamports"),
+            }),
+            signers: vec![],
+        });
+    }
+
+    let mut bulk_signers: Vec<Option<Box<dyn Signer>>> = vec![];
+    let default_signers = add_default_signers(matches, wallet_manager, &mut bulk_signers)?;
+    let stake_pool = pubkey_of(matches, "stake_pool");
+
+    // Every arm registers its signers before the transaction info can be resolved
+    let command: Box<dyn FnOnce(TxInfo) -> StakePoolCliCommand> = match subcommand {
+        "create-pool" => {
+            let (pool_signer, stake_pool) =
+                signer_of_or_else(matches, "pool_keypair", wallet_manager, new_throwaway_signer)?;
+            bulk_signers.push(pool_signer);
+            let (mint_signer, pool_mint) =
+                signer_of_or_else(matches, "mint_keypair", wallet_manager, new_throwaway_signer)?;
+            bulk_signers.push(mint_signer);
+            let manager = signer_or_exit(matches, "manager", default_signer, wallet_manager, &mut bulk_signers);
+            let staker = pubkey_of(matches, "staker").unwrap_or(manager);
+            let epoch_fee = fee_of(matches, "epoch_fee");
+            let withdrawal_fee = fee_of(matches, "withdrawal_fee");
+            let max_validators = value_t_or_exit!(matches, "max_validators", u32);
+            let name = value_t_or_exit!(matches, "name", String);
+            let symbol = value_t_or_exit!(matches, "symbol", String);
+            Box::new(move |tx_info| StakePoolCliCommand::CreatePool {
+                stake_pool: stake_pool.unwrap(),
+                pool_mint: pool_mint.unwrap(),
+                manager,
+                staker,
+                epoch_fee,
+                withdrawal_fee,
+                max_validators,
+                name,
+                symbol,
+                tx_info,
+            })
+        }
+        "add-validator" => {
+            let staker = signer_or_exit(matches, "staker", default_signer, wallet_manager, &mut bulk_signers);
+            let vote_account = pubkey_of(matches, "vote_account").unwrap();
+            Box::new(move |tx_info| StakePoolCliCommand::AddValidator {
+                stake_pool: stake_pool.unwrap(),
+                staker,
+                vote_account,
+                tx_info,
+            })
+        }
+        "remove-validator" => {
+            let staker = signer_or_exit(matches, "staker", default_signer, wallet_manager, &mut bulk_signers);
+            let vote_account = pubkey_of(matches, "vote_account").unwrap();
+            Box::new(move |tx_info| StakePoolCliCommand::RemoveValidator {
+                stake_pool: stake_pool.unwrap(),
+                staker,
+                vote_account,
+                tx_info,
+            })
+        }
+        "increase-validator-stake" => {
+            let staker = signer_or_exit(matches, "staker", default_signer, wallet_manager, &mut bulk_signers);
+            let vote_account = pubkey_of(matches, "vote_account").unwrap();
+            let lamports = lamports_of(matches);
+            Box::new(move |tx_info| StakePoolCliCommand::IncreaseValidatorStake {
+                stake_pool: stake_pool.unwrap(),
+                staker,
+                vote_account,
+                lamports,
+                tx_info,
+            })
+        }
+        "decrease-validator-stake" => {
+            let staker = signer_or_exit(matches, "staker", default_signer, wallet_manager, &mut bulk_signers);
+            let vote_account = pubkey_of(matches, "vote_account").unwrap();
+            let lamports = lamports_of(matches);
+            Box::new(move |tx_info| StakePoolCliCommand::DecreaseValidatorStake {
+                stake_pool: stake_pool.unwrap(),
+                staker,
+                vote_account,
+                lamports,
+                tx_info,
+            })
+        }
+        "update" => Box::new(move |tx_info| StakePoolCliCommand::Update {
+            stake_pool: stake_pool.unwrap(),
+            tx_info,
+        }),
+        "deposit" => {
+            let depositor = signer_or_exit(matches, "from", default_signer, wallet_manager, &mut bulk_signers);
+            let pool_token_account = pubkey_of(matches, "pool_token_account");
+            let lamports = lamports_of(matches);
+            Box::new(move |tx_info| StakePoolCliCommand::Deposit {
+                stake_pool: stake_pool.unwrap(),
+                depositor,
+                pool_token_account,
+                lamports,
+                tx_info,
+            })
+        }
+        "withdraw" => {
+            let owner = signer_or_exit(matches, "owner", default_signer, wallet_manager, &mut bulk_signers);
+            let withdrawal_ticket = if matches.is_present("delayed") {
+                let (ticket_signer, ticket) =
+                    signer_of_or_else(matches, "ticket_keypair", wallet_manager, new_throwaway_signer)?;
+                bulk_signers.push(ticket_signer);
+                ticket
+            } else {
+                None
+            };
+            let pool_token_account = pubkey_of(matches, "pool_token_account");
+            let destination = pubkey_or_default_signer(matches, "destination", default_signer, wallet_manager)?;
+            let pool_tokens = lamports_of(matches);
+            Box::new(move |tx_info| StakePoolCliCommand::Withdraw {
+                stake_pool: stake_pool.unwrap(),
+                owner,
+                pool_token_account,
+                destination,
+                pool_tokens,
+                withdrawal_ticket,
+                tx_info,
+            })
+        }
+        "claim-withdrawal" => {
+            let withdrawal_ticket = pubkey_of(matches, "withdrawal_ticket").unwrap();
+            let beneficiary = signer_or_exit(matches, "beneficiary", default_signer, wallet_manager, &mut bulk_signers);
+            let destination = pubkey_or_default_signer(matches, "destination", default_signer, wallet_manager)?;
+            Box::new(move |tx_info| StakePoolCliCommand::ClaimWithdrawal {
+                stake_pool: stake_pool.unwrap(),
+                withdrawal_ticket,
+                beneficiary,
+                destination,
+                tx_info,
+            })
+        }
+        "set-fee" => {
+            let manager = signer_or_exit(matches, "manager", default_signer, wallet_manager, &mut bulk_signers);
+            let fee_type = match matches.value_of("fee_type").unwrap() {
+                "epoch" => FeeType::Epoch,
+                _ => FeeType::Withdrawal,
+            };
+            let fee = fee_of(matches, "fee");
+            Box::new(move |tx_info| StakePoolCliCommand::SetFee {
+                stake_pool: stake_pool.unwrap(),
+                manager,
+                fee_type,
+                fee,
+                tx_info,
+            })
+        }
+        "set-staker" => {
+            let authority = signer_or_exit(matches, "authority", default_signer, wallet_manager, &mut bulk_signers);
+            let new_staker = pubkey_of(matches, "new_staker").unwrap();
+            Box::new(move |tx_info| StakePoolCliCommand::SetStaker {
+                stake_pool: stake_pool.unwrap(),
+                authority,
+                new_staker,
+                tx_info,
+            })
+        }
+        "set-manager" => {
+            let manager = signer_or_exit(matches, "manager", default_signer, wallet_manager, &mut bulk_signers);
+            let (new_manager_signer, new_manager) = signer_of(matches, "new_manager", wallet_manager)?;
+            bulk_signers.push(new_manager_signer);
+            let new_manager_fee_account = pubkey_of(matches, "new_manager_fee_account").unwrap();
+            Box::new(move |tx_info| StakePoolCliCommand::SetManager {
+                stake_pool: stake_pool.unwrap(),
+                manager,
+                new_manager: new_manager.unwrap(),
+                new_manager_fee_account,
+                tx_info,
+            })
+        }
+        _ => unreachable!(),
+    };
+
+    let signer_info = default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+    let (fee_payer_pubkey, nonce_account, nonce_authority_pubkey, _) = default_signers;
+    let tx_info = create_tx_info(matches, &signer_info, fee_payer_pubkey, nonce_account, nonce_authority_pubkey);
+
+    Ok(CliCommandInfo {
+        command: CliCommand::StakePool(command(tx_info)),
+        signers: signer_info.signers,
+    })
+}
+
+pub fn process_stake_pool_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    stake_pool_subcommand: &StakePoolCliCommand,
+) -> ProcessResult {
+    match stake_pool_subcommand {
+        StakePoolCliCommand::CreatePool {
+            stake_pool,
+            pool_mint,
+            manager,
+            staker,
+            epoch_fee,
+            withdrawal_fee,
+            max_validators,
+            name,
+            symbol,
+            tx_info,
+        } => {
+            println_display(config, format!("Creating stake pool {}", stake_pool));
+            let fee_payer = config.signers[tx_info.fee_payer].pubkey();
+            let stake_pool_size = StakePoolState::stake_pool_size_of(*max_validators);
+            let (stake_pool_rent, mint_rent, reserve_rent) = if !tx_info.sign_only {
+                (
+                    rpc_client.get_minimum_balance_for_rent_exemption(stake_pool_size)?,
+                    rpc_client.get_minimum_balance_for_rent_exemption(Mint::get_packed_len())?,
+                    rpc_client.get_minimum_balance_for_rent_exemption(0)?,
+                )
+            } else {
+                (0, 0, 0)
+            };
+            let (withdraw_authority, _) = find_withdraw_authority_program_address(stake_pool);
+            let (reserve, _) = find_reserve_program_address(stake_pool);
+            let manager_fee_account = get_associated_token_address(manager, pool_mint);
+            let instructions = vec![
+                system_instruction::create_account(
+                    &fee_payer,
+                    stake_pool,
+                    stake_pool_rent,
+                    stake_pool_size as u64,
+                    &mundis_stake_pool_program::id(),
+                ),
+                system_instruction::create_account(
+                    &fee_payer,
+                    pool_mint,
+                    mint_rent,
+                    Mint::get_packed_len() as u64,
+                    &mundis_token_program::id(),
+                ),
+                initialize_mint(
+                    &mundis_token_program::id(),
+                    pool_mint,
+                    &withdraw_authority,
+                    None,
+                    name,
+                    symbol,
+                    native_mint::DECIMALS,
+                )?,
+                create_associated_token_account(&fee_payer, manager, pool_mint),
+                system_instruction::transfer(&fee_payer, &reserve, reserve_rent),
+                stake_pool_instruction::initialize(
+                    stake_pool,
+                    manager,
+                    staker,
+                    pool_mint,
+                    &manager_fee_account,
+                    *epoch_fee,
+                    *withdrawal_fee,
+                    *max_validators,
+                ),
+            ];
+            let tx_return = handle_tx(
+                rpc_client,
+                config,
+                stake_pool_rent + mint_rent + reserve_rent,
+                instructions,
+                tx_info,
+            )?;
+            println_display(config, format!("Pool token mint {}", pool_mint));
+            Ok(format_tx_return(config, tx_return))
+        }
+        StakePoolCliCommand::AddValidator {
+            stake_pool,
+            staker,
+            vote_account,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::add_validator(stake_pool, staker, vote_account)],
+            tx_info,
+        ),
+        StakePoolCliCommand::RemoveValidator {
+            stake_pool,
+            staker,
+            vote_account,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::remove_validator(stake_pool, staker, vote_account)],
+            tx_info,
+        ),
+        StakePoolCliCommand::IncreaseValidatorStake {
+            stake_pool,
+            staker,
+            vote_account,
+            lamports,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::increase_validator_stake(
+                stake_pool,
+                staker,
+                vote_account,
+                *lamports,
+            )],
+            tx_info,
+        ),
+        StakePoolCliCommand::DecreaseValidatorStake {
+            stake_pool,
+            staker,
+            vote_account,
+            lamports,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::decrease_validator_stake(
+                stake_pool,
+                staker,
+                vote_account,
+                *lamports,
+            )],
+            tx_info,
+        ),
+        StakePoolCliCommand::Update {
+            stake_pool,
+            tx_info,
+        } => {
+            let pool = get_stake_pool(rpc_client, stake_pool)?;
+            let vote_accounts: Vec<Pubkey> = pool
+                .validators
+                .iter()
+                .map(|validator| validator.vote_account)
+                .collect();
+            send_instructions(
+                rpc_client,
+                config,
+                vec![stake_pool_instruction::update_stake_pool(
+                    stake_pool,
+                    &pool.pool_mint,
+                    &pool.manager_fee_account,
+                    &vote_accounts,
+                )],
+                tx_info,
+            )
+        }
+        StakePoolCliCommand::Deposit {
+            stake_pool,
+            depositor,
+            pool_token_account,
+            lamports,
+            tx_info,
+        } => {
+            let pool = get_stake_pool(rpc_client, stake_pool)?;
+            let mut instructions = vec![];
+            let pool_token_account = match pool_token_account {
+                Some(pool_token_account) => *pool_token_account,
+                None => {
+                    let address = get_associated_token_address(depositor, &pool.pool_mint);
+                    if rpc_client
+                        .get_account_with_commitment(&address, config.commitment)?
+                        .value
+                        .is_none()
+                    {
+                        println_display(config, format!("Creating pool token account {}", address));
+                        instructions.push(create_associated_token_account(
+                            &config.signers[tx_info.fee_payer].pubkey(),
+                            depositor,
+                            &pool.pool_mint,
+                        ));
+                    }
+                    address
+                }
+            };
+            instructions.push(stake_pool_instruction::deposit_mundis(
+                stake_pool,
+                depositor,
+                &pool_token_account,
+                &pool.pool_mint,
+                *lamports,
+            ));
+            send_instructions(rpc_client, config, instructions, tx_info)
+        }
+        StakePoolCliCommand::Withdraw {
+            stake_pool,
+            owner,
+            pool_token_account,
+            destination,
+            pool_tokens,
+            withdrawal_ticket,
+            tx_info,
+        } => {
+            let pool = get_stake_pool(rpc_client, stake_pool)?;
+            let pool_token_account = pool_token_account
+                .unwrap_or_else(|| get_associated_token_address(owner, &pool.pool_mint));
+            if let Some(withdrawal_ticket) = withdrawal_ticket {
+                let ticket_size = StakePoolState::withdrawal_ticket_size_of();
+                let ticket_rent = if !tx_info.sign_only {
+                    rpc_client.get_minimum_balance_for_rent_exemption(ticket_size)?
+                } else {
+                    0
+                };
+                println_display(config, format!("Creating withdrawal ticket {}", withdrawal_ticket));
+                let instructions = vec![
+                    system_instruction::create_account(
+                        &config.signers[tx_info.fee_payer].pubkey(),
+                        withdrawal_ticket,
+                        ticket_rent,
+                        ticket_size as u64,
+                        &mundis_stake_pool_program::id(),
+                    ),
+                    stake_pool_instruction::request_withdrawal(
+                        stake_pool,
+                        withdrawal_ticket,
+                        &pool_token_account,
+                        owner,
+                        destination,
+                        &pool.manager_fee_account,
+                        &pool.pool_mint,
+                        *pool_tokens,
+                    ),
+                ];
+                let tx_return = handle_tx(rpc_client, config, ticket_rent, instructions, tx_info)?;
+                Ok(format_tx_return(config, tx_return))
+            } else {
+                send_instructions(
+                    rpc_client,
+                    config,
+                    vec![stake_pool_instruction::withdraw_instant(
+                        stake_pool,
+                        &pool_token_account,
+                        owner,
+                        destination,
+                        &pool.manager_fee_account,
+                        &pool.pool_mint,
+                        *pool_tokens,
+                    )],
+                    tx_info,
+                )
+            }
+        }
+        StakePoolCliCommand::ClaimWithdrawal {
+            stake_pool,
+            withdrawal_ticket,
+            beneficiary,
+            destination,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::claim_withdrawal(
+                stake_pool,
+                withdrawal_ticket,
+                beneficiary,
+                destination,
+            )],
+            tx_info,
+        ),
+        StakePoolCliCommand::SetFee {
+            stake_pool,
+            manager,
+            fee_type,
+            fee,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::set_fee(stake_pool, manager, *fee_type, *fee)],
+            tx_info,
+        ),
+        StakePoolCliCommand::SetStaker {
+            stake_pool,
+            authority,
+            new_staker,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::set_staker(stake_pool, authority, new_staker)],
+            tx_info,
+        ),
+        StakePoolCliCommand::SetManager {
+            stake_pool,
+            manager,
+            new_manager,
+            new_manager_fee_account,
+            tx_info,
+        } => send_instructions(
+            rpc_client,
+            config,
+            vec![stake_pool_instruction::set_manager(
+                stake_pool,
+                manager,
+                new_manager,
+                new_manager_fee_account,
+            )],
+            tx_info,
+        ),
+        StakePoolCliCommand::Show {
+            stake_pool,
+            use_lamports_unit,
+        } => {
+            let pool = get_stake_pool(rpc_client, stake_pool)?;
+            Ok(config.output_format.formatted_string(&CliStakePool {
+                address: stake_pool.to_string(),
+                stake_pool: UiStakePool::from(pool),
+                use_lamports_unit: *use_lamports_unit,
+            }))
+        }
+    }
+}
+
+fn get_stake_pool(
+    rpc_client: &RpcClient,
+    stake_pool: &Pubkey,
+) -> Result<StakePool, Box<dyn std::error::Error>> {
+    let account = rpc_client.get_account(stake_pool)?;
+    if account.owner != mundis_stake_pool_program::id() {
+        return Err(CliError::RpcRequestError(format!(
+            "{:?} is not a stake pool",
+            stake_pool
+        ))
+        .into());
+    }
+    StakePoolState::deserialize(&account.data)?
+        .stake_pool()
+        .ok_or_else(|| format!("{:?} is not an initialized stake pool", stake_pool).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fee() {
+        assert_eq!(
+            parse_fee("3/100"),
+            Ok(Fee {
+                numerator: 3,
+                denominator: 100
+            })
+        );
+        assert_eq!(
+            parse_fee("0/1"),
+            Ok(Fee {
+                numerator: 0,
+                denominator: 1
+            })
+        );
+        assert!(parse_fee("3").is_err());
+        assert!(parse_fee("101/100").is_err());
+        assert!(parse_fee("1/0").is_err());
+        assert!(parse_fee("a/b").is_err());
+    }
+}
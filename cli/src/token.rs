@@ -118,7 +118,7 @@ impl MintArgs for App<'_, '_> {
     }
 }
 
-enum TransactionReturnData {
+pub(crate) enum TransactionReturnData {
     CliSignature(CliSignature),
     CliSignOnlyData(CliSignOnlyData),
 }
@@ -3165,7 +3165,7 @@ fn check_fee_payer_balance(rpc_client: &RpcClient, fee_payer: &Pubkey, required_
     }
 }
 
-fn handle_tx(
+pub(crate) fn handle_tx(
     rpc_client: &RpcClient,
     config: &CliConfig,
     minimum_balance_for_rent_exemption: u64,
@@ -3222,6 +3222,43 @@ fn handle_tx(
     }
 }
 
+pub(crate) fn signer_or_exit(
+    matches: &ArgMatches<'_>,
+    name: &str,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+    bulk_signers: &mut Vec<Option<Box<dyn Signer>>>,
+) -> Pubkey {
+    let (signer, pubkey) = signer_or_default(matches, name, default_signer, wallet_manager)
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            exit(1);
+        });
+    bulk_signers.push(signer);
+    pubkey.unwrap()
+}
+
+pub(crate) fn send_instructions(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    instructions: Vec<Instruction>,
+    tx_info: &TxInfo,
+) -> ProcessResult {
+    let tx_return = handle_tx(rpc_client, config, 0, instructions, tx_info)?;
+    Ok(format_tx_return(config, tx_return))
+}
+
+pub(crate) fn format_tx_return(config: &CliConfig, tx_return: TransactionReturnData) -> String {
+    match tx_return {
+        TransactionReturnData::CliSignature(signature) => {
+            config.output_format.formatted_string(&signature)
+        }
+        TransactionReturnData::CliSignOnlyData(sign_only_data) => {
+            config.output_format.formatted_string(&sign_only_data)
+        }
+    }
+}
+
 pub(crate) fn println_display(config: &CliConfig, message: String) {
     match config.output_format {
         OutputFormat::Display | OutputFormat::DisplayVerbose => {
@@ -3231,7 +3268,7 @@ pub(crate) fn println_display(config: &CliConfig, message: String) {
     }
 }
 
-fn new_throwaway_signer() -> (Option<Box<dyn Signer>>, Option<Pubkey>) {
+pub(crate) fn new_throwaway_signer() -> (Option<Box<dyn Signer>>, Option<Pubkey>) {
     let keypair = Keypair::new();
     let pubkey = keypair.pubkey();
     (Some(Box::new(keypair) as Box<dyn Signer>), Some(pubkey))
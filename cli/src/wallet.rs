@@ -556,6 +556,7 @@ pub fn process_confirm(
                         RpcTransactionConfig {
                             encoding: Some(UiTransactionEncoding::Base64),
                             commitment: Some(CommitmentConfig::confirmed()),
+                            max_supported_transaction_version: None,
                         },
                     ) {
                         Ok(confirmed_transaction) => {
@@ -7,10 +7,11 @@ use {
         rpc_request::RpcRequest,
         rpc_response::{
             Response, RpcAccountBalance, RpcBlockProduction, RpcBlockProductionRange, RpcBlockhash,
-            RpcConfirmedTransactionStatusWithSignature, RpcContactInfo, RpcFees, RpcIdentity,
-            RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcKeyedAccount,
-            RpcPerfSample, RpcResponseContext, RpcSimulateTransactionResult, RpcSnapshotSlotInfo,
-            RpcStakeActivation, RpcSupply, RpcVersionInfo, RpcVoteAccountInfo,
+            RpcBundleStatus, RpcConfirmedTransactionStatusWithSignature, RpcContactInfo,
+            RpcDurableNonceTransactionStatus, RpcFees, RpcIdentity, RpcInflationGovernor,
+            RpcInflationRate, RpcInflationReward, RpcKeyedAccount, RpcPerfSample,
+            RpcPrioritizationFee, RpcResponseContext, RpcSimulateTransactionResult,
+            RpcSnapshotSlotInfo, RpcStakeActivation, RpcSupply, RpcVersionInfo, RpcVoteAccountInfo,
             RpcVoteAccountStatus, StakeActivationState,
         },
         rpc_sender::*,
@@ -19,6 +20,7 @@ use {
     mundis_account_decoder::{UiAccount, UiAccountEncoding},
     mundis_sdk::{
         account::Account,
+        bundle::VersionedBundle,
         clock::{Slot, UnixTimestamp},
         epoch_info::EpochInfo,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
@@ -32,8 +34,8 @@ use {
     mundis_transaction_status::{
         EncodedConfirmedBlock, EncodedConfirmedTransaction, EncodedTransaction,
         EncodedTransactionWithStatusMeta, Rewards, TransactionConfirmationStatus,
-        TransactionStatus, UiCompiledInstruction, UiMessage, UiRawMessage, UiTransaction,
-        UiTransactionEncoding, UiTransactionStatusMeta,
+        TransactionStatus, TransactionVersion, UiCompiledInstruction, UiMessage, UiRawMessage,
+        UiTransaction, UiTransactionEncoding, UiTransactionStatusMeta,
     },
     mundis_version::Version,
     std::{collections::HashMap, net::SocketAddr, str::FromStr, sync::RwLock},
@@ -185,6 +187,51 @@ impl RpcSender for MockSender {
                     value: statuses,
                 })?
             }
+            "getBundleStatuses" => {
+                let statuses: Vec<Option<RpcBundleStatus>> = params.as_array().unwrap()[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|bundle_id| {
+                        if self.url == "sig_not_found" {
+                            None
+                        } else {
+                            Some(RpcBundleStatus {
+                                bundle_id: bundle_id.as_str().unwrap().to_string(),
+                                slot: 1,
+                                transactions: vec![],
+                                confirmation_status: Some(
+                                    TransactionConfirmationStatus::Finalized,
+                                ),
+                            })
+                        }
+                    })
+                    .collect();
+                serde_json::to_value(Response {
+                    context: RpcResponseContext { slot: 1 },
+                    value: statuses,
+                })?
+            }
+            "getDurableNonceTransactionStatuses" => {
+                let statuses: Vec<Option<RpcDurableNonceTransactionStatus>> = params
+                    .as_array()
+                    .unwrap()[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|_| {
+                        if self.url == "sig_not_found" {
+                            None
+                        } else {
+                            Some(RpcDurableNonceTransactionStatus::Landed { slot: 1 })
+                        }
+                    })
+                    .collect();
+                serde_json::to_value(Response {
+                    context: RpcResponseContext { slot: 1 },
+                    value: statuses,
+                })?
+            }
             "getTransaction" => serde_json::to_value(EncodedConfirmedTransaction {
                 slot: 2,
                 transaction: EncodedTransactionWithStatusMeta {
@@ -209,6 +256,7 @@ impl RpcSender for MockSender {
                                         accounts: vec![0, 1],
                                         data: "3Bxs49DitAvXtoDR".to_string(),
                                     }],
+                                    address_table_lookups: None,
                                 })
                         }),
                     meta: Some(UiTransactionStatusMeta {
@@ -222,7 +270,11 @@ impl RpcSender for MockSender {
                             pre_token_balances: None,
                             post_token_balances: None,
                             rewards: None,
+                            return_data: None,
+                            loaded_addresses: None,
+                            compute_units_consumed: None,
                         }),
+                    version: Some(TransactionVersion::LEGACY),
                 },
                 block_time: Some(1628633791),
             })?,
@@ -326,6 +378,19 @@ impl RpcSender for MockSender {
                 };
                 Value::String(signature)
             }
+            "sendBundle" => {
+                let signatures: Vec<Signature> = params.as_array().unwrap()[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|tx_str| {
+                        let data = base64::decode(tx_str.as_str().unwrap()).unwrap();
+                        let tx: Transaction = bincode::deserialize(&data).unwrap();
+                        tx.signatures[0]
+                    })
+                    .collect();
+                Value::String(VersionedBundle::id_from_signatures(signatures.iter()).to_string())
+            }
             "simulateTransaction" => serde_json::to_value(Response {
                 context: RpcResponseContext { slot: 1 },
                 value: RpcSimulateTransactionResult {
@@ -333,8 +398,29 @@ impl RpcSender for MockSender {
                     logs: None,
                     accounts: None,
                     units_consumed: None,
+                    return_data: None,
+                    inner_instructions: None,
+                    pre_token_balances: None,
+                    post_token_balances: None,
+                    loaded_accounts_data_size: None,
+                    loaded_accounts_data_size_limit: None,
                 },
             })?,
+            "simulateBundle" => serde_json::to_value(Response {
+                context: RpcResponseContext { slot: 1 },
+                value: vec![RpcSimulateTransactionResult {
+                    err: None,
+                    logs: None,
+                    accounts: None,
+                    units_consumed: None,
+                    return_data: None,
+                    inner_instructions: None,
+                    pre_token_balances: None,
+                    post_token_balances: None,
+                    loaded_accounts_data_size: None,
+                    loaded_accounts_data_size_limit: None,
+                }],
+            })?,
             "getMinimumBalanceForRentExemption" => json![20],
             "getVersion" => {
                 let version = Version::default();
@@ -377,6 +463,7 @@ impl RpcSender for MockSender {
                         UiTransactionEncoding::Base58,
                     ),
                     meta: None,
+                    version: Some(TransactionVersion::LEGACY),
                 }],
                 rewards: Rewards::new(),
                 block_time: None,
@@ -402,6 +489,10 @@ impl RpcSender for MockSender {
                 num_slots: 123,
                 sample_period_secs: 60,
             }])?,
+            "getRecentPrioritizationFees" => serde_json::to_value(vec![RpcPrioritizationFee {
+                slot: 347873,
+                prioritization_fee: 0,
+            }])?,
             "getIdentity" => serde_json::to_value(RpcIdentity {
                 identity: PUBKEY.to_string(),
             })?,
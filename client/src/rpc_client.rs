@@ -47,6 +47,10 @@ use {
         EncodedConfirmedBlock, EncodedConfirmedTransaction, TransactionStatus, UiConfirmedBlock,
         UiTransactionEncoding,
     },
+    mundis_name_service_program::{
+        find_name_record_address, parent_name,
+        state::{NameRecord, NameServiceState},
+    },
     mundis_vote_program::vote_state::MAX_LOCKOUT_HISTORY,
     std::{
         cmp::min,
@@ -1255,6 +1259,224 @@ impl RpcClient {
         )
     }
 
+    /// Simulates sending a bundle of transactions.
+    ///
+    /// The transactions are simulated sequentially against the same bank, so
+    /// each transaction observes the accounts written by the successful
+    /// transactions before it. Nothing is committed. One
+    /// [`RpcSimulateTransactionResult`] is returned per transaction, in order.
+    ///
+    /// The [`RpcSimulateTransactionConfig`] applies to every transaction of the
+    /// bundle.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the `simulateBundle` RPC method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mundis_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::RpcSimulateTransactionConfig,
+    /// # };
+    /// # use mundis_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// #     system_transaction,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Alice funds Bob, who then pays Carol
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// # let carol = Keypair::new();
+    /// let latest_blockhash = rpc_client.get_latest_blockhash().unwrap();
+    /// let transactions = vec![
+    ///     system_transaction::transfer(&alice, &bob.pubkey(), 100, latest_blockhash),
+    ///     system_transaction::transfer(&bob, &carol.pubkey(), 50, latest_blockhash),
+    /// ];
+    /// let config = RpcSimulateTransactionConfig {
+    ///     token_balances: true,
+    ///     .. RpcSimulateTransactionConfig::default()
+    /// };
+    /// let results = rpc_client.simulate_bundle_with_config(
+    ///     &transactions,
+    ///     config,
+    /// ).unwrap();
+    /// assert!(results.value.iter().all(|result| result.err.is_none()));
+    /// ```
+    pub fn simulate_bundle_with_config(
+        &self,
+        transactions: &[Transaction],
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<Vec<RpcSimulateTransactionResult>> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding()?
+        };
+        let commitment = config.commitment.unwrap_or_default();
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(encoding),
+            commitment: Some(commitment),
+            ..config
+        };
+        let serialized_encoded = transactions
+            .iter()
+            .map(|transaction| serialize_and_encode::<Transaction>(transaction, encoding))
+            .collect::<ClientResult<Vec<_>>>()?;
+        self.send(
+            RpcRequest::SimulateBundle,
+            json!([serialized_encoded, config]),
+        )
+    }
+
+    /// Submits a bundle of transactions to be processed atomically.
+    ///
+    /// The transactions of a bundle are executed in order, in the same slot,
+    /// and are only committed if every one of them succeeds. A failed bundle
+    /// is dropped without charging any fee.
+    ///
+    /// Each transaction of a bundle observes the accounts written by the
+    /// transactions before it, so a transaction may spend what an earlier one
+    /// of the bundle received.
+    ///
+    /// Unless [`skip_preflight`] is set, the bundle is first simulated with
+    /// the same rules and rejected if any transaction fails.
+    ///
+    /// Returns the bundle id, which can be passed to
+    /// [`get_bundle_statuses`].
+    ///
+    /// [`skip_preflight`]: crate::rpc_config::RpcSendTransactionConfig::skip_preflight
+    /// [`get_bundle_statuses`]: RpcClient::get_bundle_statuses
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the `sendBundle` RPC method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mundis_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::RpcSendTransactionConfig,
+    /// # };
+    /// # use mundis_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// #     system_transaction,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Alice funds Bob, who then pays Carol
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// # let carol = Keypair::new();
+    /// let latest_blockhash = rpc_client.get_latest_blockhash().unwrap();
+    /// let transactions = vec![
+    ///     system_transaction::transfer(&alice, &bob.pubkey(), 100, latest_blockhash),
+    ///     system_transaction::transfer(&bob, &carol.pubkey(), 50, latest_blockhash),
+    /// ];
+    /// let bundle_id = rpc_client.send_bundle_with_config(
+    ///     &transactions,
+    ///     RpcSendTransactionConfig::default(),
+    /// ).unwrap();
+    /// ```
+    pub fn send_bundle_with_config(
+        &self,
+        transactions: &[Transaction],
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<String> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding()?
+        };
+        let preflight_commitment = CommitmentConfig {
+            commitment: config.preflight_commitment.unwrap_or_default(),
+        };
+        let config = RpcSendTransactionConfig {
+            encoding: Some(encoding),
+            preflight_commitment: Some(preflight_commitment.commitment),
+            ..config
+        };
+        let serialized_encoded = transactions
+            .iter()
+            .map(|transaction| serialize_and_encode::<Transaction>(transaction, encoding))
+            .collect::<ClientResult<Vec<_>>>()?;
+        self.send(RpcRequest::SendBundle, json!([serialized_encoded, config]))
+    }
+
+    /// Check the confirmation status of bundles submitted with
+    /// [`send_bundle_with_config`].
+    ///
+    /// Returns one status per bundle id, `None` if the bundle has not landed
+    /// or is not known to the node. A bundle is only reported once every one
+    /// of its transactions has been committed.
+    ///
+    /// [`send_bundle_with_config`]: RpcClient::send_bundle_with_config
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the `getBundleStatuses` RPC method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mundis_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let bundle_id = "4TLEwYNKJiwEEGBvoMRPCq3pGTfq9LN1pXDXYMKbmg7x".to_string();
+    /// let statuses = rpc_client.get_bundle_statuses(&[bundle_id]).unwrap().value;
+    /// ```
+    pub fn get_bundle_statuses(
+        &self,
+        bundle_ids: &[String],
+    ) -> RpcResult<Vec<Option<RpcBundleStatus>>> {
+        self.send(RpcRequest::GetBundleStatuses, json!([bundle_ids]))
+    }
+
+    /// Check what became of transactions that use a durable nonce.
+    ///
+    /// The node keeps retrying such a transaction until its nonce account
+    /// advances, then reports whether the transaction landed or the nonce was
+    /// consumed by another transaction, in which case it can never land.
+    /// Returns `None` for signatures the node was not asked to send.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the `getDurableNonceTransactionStatuses` RPC
+    /// method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mundis_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # use mundis_sdk::signature::Signature;
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let signature = Signature::default();
+    /// let statuses = rpc_client
+    ///     .get_durable_nonce_transaction_statuses(&[signature])
+    ///     .unwrap()
+    ///     .value;
+    /// ```
+    pub fn get_durable_nonce_transaction_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<RpcDurableNonceTransactionStatus>>> {
+        let signatures: Vec<_> = signatures.iter().map(|s| s.to_string()).collect();
+        self.send(
+            RpcRequest::GetDurableNonceTransactionStatuses,
+            json!([signatures]),
+        )
+    }
+
     /// Returns the highest slot information that the node has snapshots for.
     ///
     /// This will find the highest full snapshot slot, and the highest incremental snapshot slot
@@ -1954,6 +2176,76 @@ impl RpcClient {
         )
     }
 
+    /// Returns the record of a name registered with the name service, such as
+    /// `alice.world`.
+    ///
+    /// The record is returned whether or not its lease has expired; use
+    /// [`resolve_name`] to look up the current owner of a name.
+    ///
+    /// [`resolve_name`]: RpcClient::resolve_name
+    ///
+    /// This method uses the configured [commitment level][cl].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    ///
+    /// # Errors
+    ///
+    /// If the name is not registered, this method returns
+    /// [`RpcError::ForUser`].
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getAccountInfo`] RPC method.
+    ///
+    /// [`getAccountInfo`]: https://docs.solana.com/developing/clients/jsonrpc-api#getaccountinfo
+    pub fn get_name_record(&self, name: &str) -> ClientResult<NameRecord> {
+        let address = find_name_record_address(name).0;
+        self.get_account_with_commitment(&address, self.commitment())?
+            .value
+            .filter(|account| account.owner == mundis_name_service_program::id())
+            .and_then(|account| NameServiceState::deserialize(&account.data).ok())
+            .and_then(NameServiceState::name_record)
+            .ok_or_else(|| RpcError::ForUser(format!("Name not registered: {}", name)).into())
+    }
+
+    /// Resolves a name registered with the name service to its owner.
+    ///
+    /// A name only resolves while its lease, and the lease of every name above
+    /// it, has not expired.
+    ///
+    /// This method uses the configured [commitment level][cl].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getEpochInfo`] and [`getAccountInfo`]
+    /// RPC methods.
+    ///
+    /// [`getEpochInfo`]: https://docs.solana.com/developing/clients/jsonrpc-api#getepochinfo
+    /// [`getAccountInfo`]: https://docs.solana.com/developing/clients/jsonrpc-api#getaccountinfo
+    pub fn resolve_name(&self, name: &str) -> ClientResult<Pubkey> {
+        let epoch = self.get_epoch_info()?.epoch;
+        let mut owner = None;
+        let mut current = Some(name);
+        while let Some(name) = current {
+            let record = self.get_name_record(name)?;
+            owner.get_or_insert(record.owner);
+            if !record.is_active(epoch) {
+                return Err(RpcError::ForUser(format!(
+                    "Name expired: {} (epoch {})",
+                    name, record.expiry_epoch
+                ))
+                .into());
+            }
+            current = parent_name(name);
+            if record.parent != current.map(|parent| find_name_record_address(parent).0) {
+                return Err(RpcError::ForUser(format!("Name not registered: {}", name)).into());
+            }
+        }
+        Ok(owner.unwrap())
+    }
+
     /// Returns information about the current supply.
     ///
     /// This method uses the configured [commitment level][cl].
@@ -2307,6 +2599,7 @@ impl RpcClient {
     ///     transaction_details: Some(TransactionDetails::None),
     ///     rewards: Some(true),
     ///     commitment: None,
+    ///     max_supported_transaction_version: Some(0),
     /// };
     /// let block = rpc_client.get_block_with_config(
     ///     slot,
@@ -2904,6 +3197,7 @@ impl RpcClient {
     /// let config = RpcTransactionConfig {
     ///     encoding: Some(UiTransactionEncoding::Json),
     ///     commitment: Some(CommitmentConfig::confirmed()),
+    ///     max_supported_transaction_version: Some(0),
     /// };
     /// let transaction = rpc_client.get_transaction_with_config(
     ///     &signature,
@@ -3209,6 +3503,41 @@ impl RpcClient {
         self.send(RpcRequest::GetRecentPerformanceSamples, json!([limit]))
     }
 
+    /// Returns the minimum priority fees recently required to write to the given accounts.
+    ///
+    /// Each entry covers a recent slot and holds the highest local minimum
+    /// priority fee of any of `addresses` in that slot, which is the
+    /// `additional_fee` a transaction writing to those accounts needed to
+    /// offer to be preferred by the block producer. With no addresses, every
+    /// fee is zero.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `getRecentPrioritizationFees` RPC method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mundis_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # use mundis_sdk::pubkey::Pubkey;
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let market = Pubkey::new_unique();
+    /// let prioritization_fees = rpc_client.get_recent_prioritization_fees(&[market]).unwrap();
+    /// ```
+    pub fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        self.send(RpcRequest::GetRecentPrioritizationFees, json!([addresses]))
+    }
+
     /// Returns the identity pubkey for the current node.
     ///
     /// # RPC Reference
@@ -75,6 +75,7 @@ pub enum RpcRequest {
     )]
     GetRecentBlockhash,
     GetRecentPerformanceSamples,
+    GetRecentPrioritizationFees,
     GetHighestSnapshotSlot,
     #[deprecated(
         since = "1.9.0",
@@ -82,6 +83,8 @@ pub enum RpcRequest {
     )]
     GetSnapshotSlot,
     GetSignaturesForAddress,
+    GetBundleStatuses,
+    GetDurableNonceTransactionStatuses,
     GetSignatureStatuses,
     GetSlot,
     GetSlotLeader,
@@ -104,7 +107,9 @@ pub enum RpcRequest {
     MinimumLedgerSlot,
     RegisterNode,
     RequestAirdrop,
+    SendBundle,
     SendTransaction,
+    SimulateBundle,
     SimulateTransaction,
     SignVote,
 }
@@ -152,9 +157,12 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
+            RpcRequest::GetRecentPrioritizationFees => "getRecentPrioritizationFees",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
             RpcRequest::GetSnapshotSlot => "getSnapshotSlot",
             RpcRequest::GetSignaturesForAddress => "getSignaturesForAddress",
+            RpcRequest::GetBundleStatuses => "getBundleStatuses",
+            RpcRequest::GetDurableNonceTransactionStatuses => "getDurableNonceTransactionStatuses",
             RpcRequest::GetSignatureStatuses => "getSignatureStatuses",
             RpcRequest::GetSlot => "getSlot",
             RpcRequest::GetSlotLeader => "getSlotLeader",
@@ -177,7 +185,9 @@ impl fmt::Display for RpcRequest {
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
+            RpcRequest::SendBundle => "sendBundle",
             RpcRequest::SendTransaction => "sendTransaction",
+            RpcRequest::SimulateBundle => "simulateBundle",
             RpcRequest::SimulateTransaction => "simulateTransaction",
             RpcRequest::SignVote => "signVote",
         };
@@ -187,6 +197,7 @@ impl fmt::Display for RpcRequest {
 }
 
 pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
+pub const MAX_GET_RECENT_PRIORITIZATION_FEES_QUERY_ITEMS: usize = 128;
 pub const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
@@ -2,7 +2,10 @@
 //
 // Each interval, publish the snapshat hash which is the full accounts state
 // hash on gossip. Monitor gossip for messages from validators in the `--known-validator`s
-// set and halt the node if a mismatch is detected.
+// set and halt the node if a mismatch is detected. Incremental snapshots only carry the
+// incremental accounts hash of the accounts modified since their full snapshot, which is
+// checked for every incremental snapshot and published by the snapshot packager with the
+// incremental snapshot hashes instead.
 
 use {
     rayon::ThreadPool,
@@ -106,7 +109,9 @@ impl AccountsHashVerifier {
         thread_pool: Option<&ThreadPool>,
         ledger_path: &Path,
     ) {
-        Self::verify_accounts_package_hash(&accounts_package, thread_pool, ledger_path);
+        if !Self::verify_accounts_package_hash(&accounts_package, thread_pool, ledger_path) {
+            return;
+        }
 
         Self::push_accounts_hashes_to_cluster(
             &accounts_package,
@@ -121,13 +126,34 @@ impl AccountsHashVerifier {
         Self::submit_for_packaging(accounts_package, pending_snapshot_package, snapshot_config);
     }
 
+    /// Returns false if the hash of an incremental snapshot package does not match its storages,
+    /// in which case the package is neither gossiped nor archived
+    #[must_use]
     fn verify_accounts_package_hash(
         accounts_package: &AccountsPackage,
         thread_pool: Option<&ThreadPool>,
         ledger_path: &Path,
-    ) {
+    ) -> bool {
         let mut measure_hash = Measure::start("hash");
-        if let Some(expected_hash) = accounts_package.hash_for_testing {
+        let mut verified = true;
+        if let Some(SnapshotType::IncrementalSnapshot(base_slot)) = accounts_package.snapshot_type {
+            // The storages of an incremental snapshot package only hold the slots after its base
+            // slot, which is all its incremental accounts hash covers, so it is cheap enough to
+            // check every time
+            let hash = AccountsDb::calculate_incremental_accounts_hash(
+                &accounts_package.snapshot_storages,
+            );
+            if let Some(expected_hash) = accounts_package.hash_for_testing {
+                assert_eq!(expected_hash, hash);
+            }
+            if hash != accounts_package.hash {
+                error!(
+                    "Incremental accounts hash mismatch for slot {} based on slot {}: {} (calculated) != {} (expected)",
+                    accounts_package.slot, base_slot, hash, accounts_package.hash
+                );
+                verified = false;
+            }
+        } else if let Some(expected_hash) = accounts_package.hash_for_testing {
             let sorted_storages = SortedStorages::new(&accounts_package.snapshot_storages);
             let (hash, lamports) = AccountsDb::calculate_accounts_hash_without_index(
                 ledger_path,
@@ -149,6 +175,7 @@ impl AccountsHashVerifier {
             "accounts_hash_verifier",
             ("calculate_hash", measure_hash.as_us(), i64),
         );
+        verified
     }
 
     fn push_accounts_hashes_to_cluster(
@@ -160,6 +187,12 @@ impl AccountsHashVerifier {
         exit: &Arc<AtomicBool>,
         fault_injection_rate_slots: u64,
     ) {
+        // The hash of an incremental snapshot package only covers the accounts modified since
+        // its base slot, so it cannot be compared with the accounts hashes of other nodes
+        if let Some(SnapshotType::IncrementalSnapshot(_)) = accounts_package.snapshot_type {
+            return;
+        }
+
         let hash = accounts_package.hash;
         if fault_injection_rate_slots != 0
             && accounts_package.slot % fault_injection_rate_slots == 0
@@ -409,4 +442,99 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_verify_incremental_accounts_hash() {
+        mundis_logger::setup();
+        use {
+            mundis_runtime::snapshot_archive_info::SnapshotArchiveInfoGetter,
+            std::{path::PathBuf, sync::Mutex},
+            tempfile::TempDir,
+        };
+        let keypair = Keypair::new();
+
+        let contact_info = ContactInfo::new_localhost(&keypair.pubkey(), 0);
+        let cluster_info = Arc::new(new_test_cluster_info(contact_info));
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let mut hashes = vec![];
+        let pending_snapshot_package = Arc::new(Mutex::new(None));
+        let snapshot_config = SnapshotConfig {
+            full_snapshot_archive_interval_slots: 100,
+            incremental_snapshot_archive_interval_slots: 10,
+            ..SnapshotConfig::default()
+        };
+        let new_accounts_package = |slot, hash, snapshot_type| AccountsPackage {
+            slot,
+            block_height: slot,
+            slot_deltas: vec![],
+            snapshot_links: TempDir::new().unwrap(),
+            snapshot_storages: vec![],
+            hash,
+            archive_format: ArchiveFormat::TarBzip2,
+            snapshot_version: SnapshotVersion::default(),
+            snapshot_archives_dir: PathBuf::default(),
+            expected_capitalization: 0,
+            hash_for_testing: None,
+            cluster_type: ClusterType::MainnetBeta,
+            snapshot_type,
+        };
+        let full_hash = hash(&[1]);
+        // An incremental snapshot package without any storages has the hash of no accounts
+        let incremental_hash = AccountsDb::calculate_incremental_accounts_hash(&vec![]);
+        let accounts_packages = vec![
+            (
+                new_accounts_package(100, full_hash, Some(SnapshotType::FullSnapshot)),
+                true,
+            ),
+            (
+                new_accounts_package(
+                    110,
+                    incremental_hash,
+                    Some(SnapshotType::IncrementalSnapshot(100)),
+                ),
+                true,
+            ),
+            // Does not match its storages, so it is not archived
+            (
+                new_accounts_package(
+                    120,
+                    hash(&[2]),
+                    Some(SnapshotType::IncrementalSnapshot(100)),
+                ),
+                false,
+            ),
+        ];
+
+        let ledger_path = TempDir::new().unwrap();
+        for (accounts_package, is_submitted) in accounts_packages {
+            let slot = accounts_package.slot;
+            AccountsHashVerifier::process_accounts_package(
+                accounts_package,
+                &cluster_info,
+                None,
+                false,
+                Some(&pending_snapshot_package),
+                &mut hashes,
+                &exit,
+                0,
+                Some(&snapshot_config),
+                None,
+                ledger_path.path(),
+            );
+            let snapshot_package = pending_snapshot_package.lock().unwrap().take();
+            assert_eq!(
+                snapshot_package.map(|snapshot_package| snapshot_package.slot()),
+                is_submitted.then(|| slot)
+            );
+        }
+        cluster_info.flush_push_queue();
+
+        // Only the full accounts hash is compared with the accounts hashes of other nodes, and
+        // the incremental snapshot hashes are left to the snapshot packager
+        assert_eq!(hashes, vec![(100, full_hash)]);
+        assert!(cluster_info
+            .get_incremental_snapshot_hashes_for_node(&keypair.pubkey())
+            .is_none());
+    }
 }
@@ -37,16 +37,19 @@ use {
         vote_sender_types::ReplayVoteSender,
     },
     mundis_sdk::{
+        bundle::{has_conflicting_account_locks, BundlePacketHeader, VersionedBundle},
         clock::{
             Slot, DEFAULT_TICKS_PER_SLOT, MAX_PROCESSING_AGE, MAX_TRANSACTION_FORWARDING_DELAY,
             MAX_TRANSACTION_FORWARDING_DELAY_GPU,
         },
         feature_set,
+        hash::Hash,
         message::{
             v0::{LoadedAddresses, MessageAddressTableLookup},
             Message,
         },
         pubkey::Pubkey,
+        saturating_add_assign,
         short_vec::decode_shortu16_len,
         signature::Signature,
         timing::{duration_as_ms, timestamp, AtomicInterval},
@@ -96,6 +99,10 @@ const SLOT_BOUNDARY_CHECK_PERIOD: Duration = Duration::from_millis(10);
 pub struct ProcessTransactionBatchOutput {
     // The number of transactions filtered out by the cost model
     cost_model_throttled_transactions_count: usize,
+    // The number of those transactions filtered out by the block's new account data limit
+    account_data_throttled_transactions_count: usize,
+    // The number of those transactions paying less than a local minimum priority fee
+    priority_fee_throttled_transactions_count: usize,
     // Amount of time spent running the cost model
     cost_model_us: u64,
     execute_and_commit_transactions_output: ExecuteAndCommitTransactionsOutput,
@@ -135,6 +142,19 @@ pub struct ExecuteAndCommitTransactionsOutput {
     error_counters: TransactionErrorMetrics,
 }
 
+#[derive(Debug)]
+pub enum BundleExecutionError {
+    // The bundle could not be executed in this slot, but may succeed if retried later
+    Retryable(TransactionError),
+    // A transaction of the bundle failed, so none of them were committed
+    TransactionFailure(TransactionError),
+    // The transactions of the bundle lock the same accounts, so they cannot be recorded as a
+    // single entry
+    ConflictingTransactions,
+    // The executed bundle could not be recorded into the Poh stream
+    PohRecordError(PohRecorderError),
+}
+
 #[derive(Debug, Default)]
 pub struct BankingStageStats {
     last_report: AtomicInterval,
@@ -1373,6 +1393,19 @@ impl BankingStage {
             qos_service.select_transactions_per_cost(txs.iter(), transaction_costs.iter(), bank);
 
         let cost_model_throttled_transactions_count = txs.len().saturating_sub(num_included);
+        let account_data_throttled_transactions_count = transactions_qos_results
+            .iter()
+            .filter(|result| {
+                matches!(
+                    result,
+                    Err(TransactionError::WouldExceedMaxAccountDataCostLimit)
+                )
+            })
+            .count();
+        let priority_fee_throttled_transactions_count = transactions_qos_results
+            .iter()
+            .filter(|result| matches!(result, Err(TransactionError::InsufficientPriorityFee)))
+            .count();
 
         qos_service.accumulate_estimated_transaction_costs(
             &Self::accumulate_batched_transaction_costs(
@@ -1390,8 +1423,8 @@ impl BankingStage {
         lock_time.stop();
 
         // retryable_txs includes AccountInUse, WouldExceedMaxBlockCostLimit
-        // WouldExceedMaxAccountCostLimit, WouldExceedMaxVoteCostLimit
-        // and WouldExceedMaxAccountDataCostLimit
+        // WouldExceedMaxAccountCostLimit, WouldExceedMaxVoteCostLimit,
+        // WouldExceedMaxAccountDataCostLimit and InsufficientPriorityFee
         let mut execute_and_commit_transactions_output =
             Self::execute_and_commit_transactions_locked(
                 bank,
@@ -1439,11 +1472,224 @@ impl BankingStage {
 
         ProcessTransactionBatchOutput {
             cost_model_throttled_transactions_count,
+            account_data_throttled_transactions_count,
+            priority_fee_throttled_transactions_count,
             cost_model_us: cost_model_time.as_us(),
             execute_and_commit_transactions_output,
         }
     }
 
+    /// Executes the transactions of a bundle in order and, only if all of them succeed, records
+    /// them as a single entry and commits them in the current slot.
+    ///
+    /// Returns the number of committed transactions. A failed bundle is dropped without charging
+    /// any fee. Until `execute_conflicting_entries_in_order` is active, a bundle whose transactions
+    /// conflict with each other is rejected, because replay locks the accounts of an entry all at
+    /// once.
+    pub fn process_and_record_bundle(
+        bank: &Arc<Bank>,
+        txs: &[SanitizedTransaction],
+        poh: &TransactionRecorder,
+        transaction_status_sender: Option<TransactionStatusSender>,
+        gossip_vote_sender: &ReplayVoteSender,
+        qos_service: &Arc<QosService>,
+        execute_and_commit_timings: &mut LeaderExecuteAndCommitTimings,
+    ) -> Result<usize, BundleExecutionError> {
+        if !bank
+            .feature_set
+            .is_active(&feature_set::execute_conflicting_entries_in_order::id())
+            && has_conflicting_account_locks(txs)
+        {
+            return Err(BundleExecutionError::ConflictingTransactions);
+        }
+
+        // The whole bundle must fit into the block
+        let transaction_costs = qos_service.compute_transaction_costs(txs.iter());
+        let (transactions_qos_results, num_included) =
+            qos_service.select_transactions_per_cost(txs.iter(), transaction_costs.iter(), bank);
+        if num_included != txs.len() {
+            QosService::update_or_remove_transaction_costs(
+                transaction_costs.iter(),
+                transactions_qos_results.iter(),
+                None,
+                bank,
+            );
+            let err = transactions_qos_results
+                .into_iter()
+                .find_map(|result| result.err())
+                .unwrap();
+            return Err(BundleExecutionError::Retryable(err));
+        }
+        qos_service.accumulate_estimated_transaction_costs(
+            &Self::accumulate_batched_transaction_costs(
+                transaction_costs.iter(),
+                transactions_qos_results.iter(),
+            ),
+        );
+
+        let result = Self::execute_and_commit_bundle_locked(
+            bank,
+            txs,
+            poh,
+            transaction_status_sender,
+            gossip_vote_sender,
+            execute_and_commit_timings,
+        );
+
+        QosService::update_or_remove_transaction_costs(
+            transaction_costs.iter(),
+            transactions_qos_results.iter(),
+            result.as_ref().ok(),
+            bank,
+        );
+        result.map(|commit_transactions_details| commit_transactions_details.len())
+    }
+
+    fn execute_and_commit_bundle_locked(
+        bank: &Arc<Bank>,
+        txs: &[SanitizedTransaction],
+        poh: &TransactionRecorder,
+        transaction_status_sender: Option<TransactionStatusSender>,
+        gossip_vote_sender: &ReplayVoteSender,
+        execute_and_commit_timings: &mut LeaderExecuteAndCommitTimings,
+    ) -> Result<Vec<CommitTransactionDetails>, BundleExecutionError> {
+        // All the accounts of the bundle stay locked until it is committed
+        let batch = bank.prepare_sanitized_bundle_batch(txs);
+        match batch.lock_results().first() {
+            Some(Err(TransactionError::AccountInUse)) => {
+                return Err(BundleExecutionError::Retryable(
+                    TransactionError::AccountInUse,
+                ));
+            }
+            Some(Err(err)) => return Err(BundleExecutionError::TransactionFailure(err.clone())),
+            _ => (),
+        }
+
+        let (outputs, load_execute_time) = Measure::this(
+            |_| {
+                bank.load_and_execute_bundle(
+                    &batch,
+                    MAX_PROCESSING_AGE,
+                    transaction_status_sender.is_some(),
+                    transaction_status_sender.is_some(),
+                    &mut execute_and_commit_timings.execute_timings,
+                )
+            },
+            (),
+            "load_execute",
+        );
+        saturating_add_assign!(
+            execute_and_commit_timings.load_execute_us,
+            load_execute_time.as_us()
+        );
+
+        if let Some(output) = outputs.last() {
+            let execution_result = &output.execution_results[0];
+            if !execution_result.was_executed_successfully() {
+                let err = execution_result.flattened_result().unwrap_err();
+                return Err(if output.retryable_transaction_indexes.is_empty() {
+                    BundleExecutionError::TransactionFailure(err)
+                } else {
+                    BundleExecutionError::Retryable(err)
+                });
+            }
+        }
+
+        let (last_blockhash, lamports_per_signature) =
+            bank.last_blockhash_and_lamports_per_signature();
+        let (freeze_lock, freeze_lock_time) =
+            Measure::this(|_| bank.freeze_lock(), (), "freeze_lock");
+        saturating_add_assign!(
+            execute_and_commit_timings.freeze_lock_us,
+            freeze_lock_time.as_us()
+        );
+
+        let transactions: Vec<VersionedTransaction> =
+            txs.iter().map(|tx| tx.to_versioned_transaction()).collect();
+        let (record_result, record_time) = Measure::this(
+            |_| poh.record(bank.slot(), hash_transactions(&transactions), transactions),
+            (),
+            "record",
+        );
+        saturating_add_assign!(execute_and_commit_timings.record_us, record_time.as_us());
+        record_result.map_err(BundleExecutionError::PohRecordError)?;
+
+        // The transactions are committed one at a time, so that the balances collected for each
+        // of them account for the transactions before it
+        let mut mint_decimals: HashMap<Pubkey, u8> = HashMap::new();
+        let mut commit_transactions_details = Vec::with_capacity(txs.len());
+        let mut commit_time = Measure::start("commit");
+        for (tx, output) in txs.chunks(1).zip(outputs) {
+            let LoadAndExecuteTransactionsOutput {
+                mut loaded_transactions,
+                execution_results,
+                signature_count,
+                ..
+            } = output;
+            commit_transactions_details.push(CommitTransactionDetails::Committed {
+                compute_units: execution_results[0]
+                    .details()
+                    .map(|details| details.executed_units)
+                    .unwrap_or_default(),
+            });
+
+            let tx_batch = bank.prepare_unlocked_batch(tx);
+            let (pre_balances, pre_token_balances) = if transaction_status_sender.is_some() {
+                (
+                    bank.collect_balances(&tx_batch),
+                    collect_token_balances(bank, &tx_batch, &mut mint_decimals),
+                )
+            } else {
+                (vec![], vec![])
+            };
+
+            let tx_results = bank.commit_transactions(
+                tx,
+                &mut loaded_transactions,
+                execution_results,
+                last_blockhash,
+                lamports_per_signature,
+                CommitTransactionCounts {
+                    committed_transactions_count: 1,
+                    committed_with_failure_result_count: 0,
+                    signature_count,
+                },
+                &mut execute_and_commit_timings.execute_timings,
+            );
+
+            bank_utils::find_and_send_votes(tx, &tx_results, Some(gossip_vote_sender));
+            if let Some(transaction_status_sender) = &transaction_status_sender {
+                let post_balances = bank.collect_balances(&tx_batch);
+                let post_token_balances =
+                    collect_token_balances(bank, &tx_batch, &mut mint_decimals);
+                transaction_status_sender.send_transaction_status_batch(
+                    bank.clone(),
+                    tx.to_vec(),
+                    tx_results.execution_results,
+                    TransactionBalancesSet::new(pre_balances, post_balances),
+                    TransactionTokenBalancesSet::new(pre_token_balances, post_token_balances),
+                    tx_results.rent_debits,
+                );
+            }
+        }
+        commit_time.stop();
+        saturating_add_assign!(execute_and_commit_timings.commit_us, commit_time.as_us());
+
+        drop(freeze_lock);
+        drop(batch);
+
+        debug!(
+            "bank: {} process_and_record_bundle: {}us record: {}us commit: {}us txs_len: {}",
+            bank.slot(),
+            load_execute_time.as_us(),
+            record_time.as_us(),
+            commit_time.as_us(),
+            txs.len(),
+        );
+
+        Ok(commit_transactions_details)
+    }
+
     // rollup transaction cost details, eg signature_cost, write_lock_cost, data_bytes_cost and
     // execution_cost from the batch of transactions selected for block.
     fn accumulate_batched_transaction_costs<'a>(
@@ -1513,6 +1759,8 @@ impl BankingStage {
         // slot ended
         let mut total_failed_commit_count: usize = 0;
         let mut total_cost_model_throttled_transactions_count: usize = 0;
+        let mut total_account_data_throttled_transactions_count: usize = 0;
+        let mut total_priority_fee_throttled_transactions_count: usize = 0;
         let mut total_cost_model_us: u64 = 0;
         let mut total_execute_and_commit_timings = LeaderExecuteAndCommitTimings::default();
         let mut total_error_counters = TransactionErrorMetrics::default();
@@ -1534,12 +1782,22 @@ impl BankingStage {
 
             let ProcessTransactionBatchOutput {
                 cost_model_throttled_transactions_count: new_cost_model_throttled_transactions_count,
+                account_data_throttled_transactions_count:
+                    new_account_data_throttled_transactions_count,
+                priority_fee_throttled_transactions_count:
+                    new_priority_fee_throttled_transactions_count,
                 cost_model_us: new_cost_model_us,
                 execute_and_commit_transactions_output,
             } = process_transaction_batch_output;
             total_cost_model_throttled_transactions_count =
                 total_cost_model_throttled_transactions_count
                     .saturating_add(new_cost_model_throttled_transactions_count);
+            total_account_data_throttled_transactions_count =
+                total_account_data_throttled_transactions_count
+                    .saturating_add(new_account_data_throttled_transactions_count);
+            total_priority_fee_throttled_transactions_count =
+                total_priority_fee_throttled_transactions_count
+                    .saturating_add(new_priority_fee_throttled_transactions_count);
             total_cost_model_us = total_cost_model_us.saturating_add(new_cost_model_us);
 
             let ExecuteAndCommitTransactionsOutput {
@@ -1615,6 +1873,10 @@ impl BankingStage {
             failed_commit_count: total_failed_commit_count,
             retryable_transaction_indexes: all_retryable_tx_indexes,
             cost_model_throttled_transactions_count: total_cost_model_throttled_transactions_count,
+            account_data_throttled_transactions_count:
+                total_account_data_throttled_transactions_count,
+            priority_fee_throttled_transactions_count:
+                total_priority_fee_throttled_transactions_count,
             cost_model_us: total_cost_model_us,
             execute_and_commit_timings: total_execute_and_commit_timings,
             error_counters: total_error_counters,
@@ -1674,6 +1936,10 @@ impl BankingStage {
                 if votes_only && !p.meta.is_simple_vote_tx() {
                     return None;
                 }
+                // Bundles are converted separately, see `bundle_from_packets()`
+                if p.meta.is_bundle() {
+                    return None;
+                }
 
                 let tx: VersionedTransaction = p.deserialize_slice(..).ok()?;
                 let message_bytes = Self::packet_message(p)?;
@@ -1691,6 +1957,31 @@ impl BankingStage {
             .unzip()
     }
 
+    // This function reassembles a bundle from its packets, computes the blake3 hash of the
+    // messages of its transactions and verifies their secp256k1 instructions. The signatures of
+    // a bundle are verified by the sigverify stage.
+    fn bundle_from_packets<'a>(
+        packets: impl Iterator<Item = &'a Packet>,
+        feature_set: &Arc<feature_set::FeatureSet>,
+        address_loader: impl Fn(&[MessageAddressTableLookup]) -> transaction::Result<LoadedAddresses>,
+    ) -> Option<Vec<SanitizedTransaction>> {
+        let bundle = VersionedBundle::from_packets_data(
+            packets.map(|packet| &packet.data[..packet.meta.size]),
+        )?;
+        bundle.sanitize().ok()?;
+        bundle
+            .transactions
+            .into_iter()
+            .map(|tx| {
+                let message_hash = tx.message.hash();
+                let tx = SanitizedTransaction::try_create(tx, message_hash, None, &address_loader)
+                    .ok()?;
+                tx.verify_precompiles(feature_set).ok()?;
+                Some(tx)
+            })
+            .collect()
+    }
+
     /// This function filters pending packets that are still valid
     /// # Arguments
     /// * `transactions` - a batch of transactions deserialized from packets
@@ -1764,22 +2055,33 @@ impl BankingStage {
             .fetch_add(packet_conversion_us, Ordering::Relaxed);
         inc_new_counter_info!("banking_stage-packet_conversion", 1);
 
-        // Process transactions
-        let (mut process_transactions_summary, process_transactions_time) = Measure::this(
-            |_| {
-                Self::process_transactions(
-                    bank,
-                    bank_creation_time,
-                    &transactions,
-                    poh,
-                    transaction_status_sender,
-                    gossip_vote_sender,
-                    qos_service,
-                )
-            },
-            (),
-            "process_transaction_time",
-        );
+        // Process bundles, then transactions
+        let ((bundles_summary, mut process_transactions_summary), process_transactions_time) =
+            Measure::this(
+                |_| {
+                    let bundles_summary = Self::process_bundle_packets(
+                        bank,
+                        packet_batch,
+                        &packet_indexes,
+                        poh,
+                        transaction_status_sender.clone(),
+                        gossip_vote_sender,
+                        qos_service,
+                    );
+                    let process_transactions_summary = Self::process_transactions(
+                        bank,
+                        bank_creation_time,
+                        &transactions,
+                        poh,
+                        transaction_status_sender,
+                        gossip_vote_sender,
+                        qos_service,
+                    );
+                    (bundles_summary, process_transactions_summary)
+                },
+                (),
+                "process_transaction_time",
+            );
         let process_transactions_us = process_transactions_time.as_us();
         slot_metrics_tracker.increment_process_transactions_us(process_transactions_us);
         banking_stage_stats
@@ -1794,6 +2096,8 @@ impl BankingStage {
 
         slot_metrics_tracker.accumulate_process_transactions_summary(&process_transactions_summary);
         slot_metrics_tracker.accumulate_transaction_errors(error_counters);
+        slot_metrics_tracker.accumulate_process_transactions_summary(&bundles_summary);
+        slot_metrics_tracker.accumulate_transaction_errors(&bundles_summary.error_counters);
 
         let retryable_tx_count = retryable_transaction_indexes.len();
         inc_new_counter_info!("banking_stage-unprocessed_transactions", retryable_tx_count);
@@ -1831,11 +2135,155 @@ impl BankingStage {
                 .saturating_sub(filtered_retryable_transaction_indexes.len())
         );
 
-        process_transactions_summary.retryable_transaction_indexes =
-            filtered_retryable_transaction_indexes;
+        // Bundles are retried as a whole, their indexes already refer to packets
+        let mut retryable_packet_indexes = filtered_retryable_transaction_indexes;
+        retryable_packet_indexes.extend(bundles_summary.retryable_transaction_indexes);
+        retryable_packet_indexes.sort_unstable();
+
+        process_transactions_summary.reached_max_poh_height |=
+            bundles_summary.reached_max_poh_height;
+        process_transactions_summary.retryable_transaction_indexes = retryable_packet_indexes;
         process_transactions_summary
     }
 
+    // Groups the indexes of the bundle packets of `packet_indexes` by bundle
+    fn bundle_packet_indexes(
+        packet_batch: &PacketBatch,
+        packet_indexes: &[usize],
+    ) -> Vec<Vec<usize>> {
+        let mut bundles: Vec<(Hash, Vec<usize>)> = vec![];
+        for packet_index in packet_indexes {
+            let packet = &packet_batch.packets[*packet_index];
+            if !packet.meta.is_bundle() {
+                continue;
+            }
+            let bundle_id =
+                match BundlePacketHeader::from_packet_data(&packet.data[..packet.meta.size]) {
+                    Some((header, _transaction)) => header.bundle_id,
+                    None => continue,
+                };
+            match bundles.iter_mut().find(|(id, _)| *id == bundle_id) {
+                Some((_, bundle_packet_indexes)) => bundle_packet_indexes.push(*packet_index),
+                None => bundles.push((bundle_id, vec![*packet_index])),
+            }
+        }
+        bundles
+            .into_iter()
+            .map(|(_, bundle_packet_indexes)| bundle_packet_indexes)
+            .collect()
+    }
+
+    /// Processes the bundles of `packet_indexes`, one at a time. The sigverify stage hands each
+    /// bundle over as a packet batch of its own, holding one packet per transaction.
+    ///
+    /// The retryable indexes of the returned summary are the indexes of the packets of the
+    /// bundles that were not committed but may be retried.
+    fn process_bundle_packets(
+        bank: &Arc<Bank>,
+        packet_batch: &PacketBatch,
+        packet_indexes: &[usize],
+        poh: &TransactionRecorder,
+        transaction_status_sender: Option<TransactionStatusSender>,
+        gossip_vote_sender: &ReplayVoteSender,
+        qos_service: &Arc<QosService>,
+    ) -> ProcessTransactionsSummary {
+        let mut summary = ProcessTransactionsSummary {
+            reached_max_poh_height: false,
+            transactions_attempted_execution_count: 0,
+            committed_transactions_count: 0,
+            committed_transactions_with_successful_result_count: 0,
+            failed_commit_count: 0,
+            retryable_transaction_indexes: vec![],
+            cost_model_throttled_transactions_count: 0,
+            account_data_throttled_transactions_count: 0,
+            priority_fee_throttled_transactions_count: 0,
+            cost_model_us: 0,
+            execute_and_commit_timings: LeaderExecuteAndCommitTimings::default(),
+            error_counters: TransactionErrorMetrics::default(),
+        };
+        // Bundles never land in a vote only bank
+        if bank.vote_only_bank() {
+            return summary;
+        }
+
+        for bundle_packet_indexes in Self::bundle_packet_indexes(packet_batch, packet_indexes) {
+            if summary.reached_max_poh_height {
+                summary
+                    .retryable_transaction_indexes
+                    .extend(bundle_packet_indexes);
+                continue;
+            }
+            let txs = match Self::bundle_from_packets(
+                bundle_packet_indexes
+                    .iter()
+                    .map(|packet_index| &packet_batch.packets[*packet_index]),
+                &bank.feature_set,
+                |lookup| bank.load_lookup_table_addresses(lookup),
+            ) {
+                Some(txs) => txs,
+                None => continue,
+            };
+
+            summary.transactions_attempted_execution_count += txs.len();
+            let result = Self::process_and_record_bundle(
+                bank,
+                &txs,
+                poh,
+                transaction_status_sender.clone(),
+                gossip_vote_sender,
+                qos_service,
+                &mut summary.execute_and_commit_timings,
+            );
+            trace!("process_bundle_packets result: {:?}", result);
+            match result {
+                Ok(committed_transactions_count) => {
+                    inc_new_counter_info!("banking_stage-committed_bundles", 1);
+                    summary.committed_transactions_count += committed_transactions_count;
+                    summary.committed_transactions_with_successful_result_count +=
+                        committed_transactions_count;
+                }
+                Err(BundleExecutionError::Retryable(err)) => {
+                    if matches!(
+                        err,
+                        TransactionError::WouldExceedMaxBlockCostLimit
+                            | TransactionError::WouldExceedMaxVoteCostLimit
+                            | TransactionError::WouldExceedMaxAccountCostLimit
+                            | TransactionError::WouldExceedMaxAccountDataCostLimit
+                            | TransactionError::InsufficientPriorityFee
+                    ) {
+                        summary.cost_model_throttled_transactions_count += txs.len();
+                    }
+                    if err == TransactionError::WouldExceedMaxAccountDataCostLimit {
+                        summary.account_data_throttled_transactions_count += txs.len();
+                    }
+                    if err == TransactionError::InsufficientPriorityFee {
+                        summary.priority_fee_throttled_transactions_count += txs.len();
+                    }
+                    summary
+                        .retryable_transaction_indexes
+                        .extend(bundle_packet_indexes);
+                }
+                Err(
+                    BundleExecutionError::TransactionFailure(_)
+                    | BundleExecutionError::ConflictingTransactions,
+                ) => {
+                    inc_new_counter_info!("banking_stage-failed_bundles", 1);
+                }
+                Err(BundleExecutionError::PohRecordError(PohRecorderError::MaxHeightReached)) => {
+                    summary.failed_commit_count += txs.len();
+                    summary
+                        .retryable_transaction_indexes
+                        .extend(bundle_packet_indexes);
+                    summary.reached_max_poh_height = true;
+                }
+                Err(BundleExecutionError::PohRecordError(err)) => {
+                    warn!("failed to record bundle: {:?}", err);
+                }
+            }
+        }
+        summary
+    }
+
     fn filter_unprocessed_packets_at_end_of_slot(
         bank: &Arc<Bank>,
         packet_batch: &PacketBatch,
@@ -1865,7 +2313,7 @@ impl BankingStage {
         unprocessed_packet_conversion_time.stop();
 
         let unprocessed_tx_indexes = (0..transactions.len()).collect_vec();
-        let filtered_unprocessed_packet_indexes = Self::filter_pending_packets_from_pending_txs(
+        let mut filtered_unprocessed_packet_indexes = Self::filter_pending_packets_from_pending_txs(
             bank,
             &transactions,
             &transaction_to_packet_indexes,
@@ -1885,6 +2333,16 @@ impl BankingStage {
                 Ordering::Relaxed,
             );
 
+        // Bundles are kept as they are, so that they can be forwarded to the next leader
+        if !bank.vote_only_bank() {
+            filtered_unprocessed_packet_indexes.extend(
+                transaction_indexes
+                    .iter()
+                    .filter(|index| packet_batch.packets[**index].meta.is_bundle()),
+            );
+            filtered_unprocessed_packet_indexes.sort_unstable();
+        }
+
         filtered_unprocessed_packet_indexes
     }
 
@@ -2138,6 +2596,7 @@ mod tests {
                 log_messages: None,
                 inner_instructions: None,
                 durable_nonce_fee: None,
+                return_data: None,
                 executed_units: 0u64,
             },
             executors: Rc::new(RefCell::new(Executors::default())),
@@ -2872,6 +3331,167 @@ mod tests {
         Blockstore::destroy(ledger_path.path()).unwrap();
     }
 
+    #[test]
+    fn test_bank_process_and_record_bundle() {
+        mundis_logger::setup();
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_slow_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_no_wallclock_throttle_for_tests(&genesis_config));
+        let keypair = Keypair::new();
+        let pubkey = mundis_sdk::pubkey::new_rand();
+        let mint_pubkey = mundis_sdk::pubkey::new_rand();
+        bank.transfer(1_000, &mint_keypair, &keypair.pubkey())
+            .unwrap();
+
+        // Two signers each pay someone
+        let bundle = sanitize_transactions(vec![
+            system_transaction::transfer(&mint_keypair, &mint_pubkey, 100, genesis_config.hash()),
+            system_transaction::transfer(&keypair, &pubkey, 10, genesis_config.hash()),
+        ]);
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        {
+            let blockstore = Blockstore::open(ledger_path.path())
+                .expect("Expected to be able to open database ledger");
+            let (poh_recorder, entry_receiver, record_receiver) = PohRecorder::new(
+                bank.tick_height(),
+                bank.last_blockhash(),
+                bank.clone(),
+                Some((4, 4)),
+                bank.ticks_per_slot(),
+                &pubkey,
+                &Arc::new(blockstore),
+                &Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+                &Arc::new(PohConfig::default()),
+                Arc::new(AtomicBool::default()),
+            );
+            let recorder = poh_recorder.recorder();
+            let poh_recorder = Arc::new(Mutex::new(poh_recorder));
+
+            let poh_simulator = simulate_poh(record_receiver, &poh_recorder);
+
+            poh_recorder.lock().unwrap().set_bank(&bank);
+            let (gossip_vote_sender, _gossip_vote_receiver) = unbounded();
+            let qos_service =
+                Arc::new(QosService::new(Arc::new(RwLock::new(CostModel::default()))));
+
+            let result = BankingStage::process_and_record_bundle(
+                &bank,
+                &bundle,
+                &recorder,
+                None,
+                &gossip_vote_sender,
+                &qos_service,
+                &mut LeaderExecuteAndCommitTimings::default(),
+            );
+            assert_matches!(result, Ok(2));
+            assert_eq!(bank.get_balance(&mint_pubkey), 100);
+            assert_eq!(bank.get_balance(&pubkey), 10);
+
+            // The bundle is recorded as a single entry
+            let entries: Vec<_> = entry_receiver
+                .try_iter()
+                .map(|(_bank, (entry, _tick_height))| entry)
+                .filter(|entry| !entry.is_tick())
+                .collect();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(
+                entries[0].transactions,
+                bundle
+                    .iter()
+                    .map(|tx| tx.to_versioned_transaction())
+                    .collect::<Vec<_>>()
+            );
+
+            // The second transaction spends the lamports received in the first one
+            let buyer = Keypair::new();
+            let dependent_bundle = sanitize_transactions(vec![
+                system_transaction::transfer(
+                    &mint_keypair,
+                    &buyer.pubkey(),
+                    100,
+                    genesis_config.hash(),
+                ),
+                system_transaction::transfer(&buyer, &pubkey, 50, genesis_config.hash()),
+            ]);
+            let result = BankingStage::process_and_record_bundle(
+                &bank,
+                &dependent_bundle,
+                &recorder,
+                None,
+                &gossip_vote_sender,
+                &qos_service,
+                &mut LeaderExecuteAndCommitTimings::default(),
+            );
+            assert_matches!(result, Ok(2));
+            assert_eq!(bank.get_balance(&pubkey), 60);
+            assert_eq!(
+                entry_receiver
+                    .try_iter()
+                    .filter(|(_, (entry, _))| !entry.is_tick())
+                    .count(),
+                1
+            );
+            let mint_balance = bank.get_balance(&mint_keypair.pubkey());
+
+            // The second transaction fails, so neither is committed and no fee is charged
+            let failing_bundle = sanitize_transactions(vec![
+                system_transaction::transfer(&mint_keypair, &mint_pubkey, 1, genesis_config.hash()),
+                system_transaction::transfer(&keypair, &pubkey, 1_000_000, genesis_config.hash()),
+            ]);
+            let result = BankingStage::process_and_record_bundle(
+                &bank,
+                &failing_bundle,
+                &recorder,
+                None,
+                &gossip_vote_sender,
+                &qos_service,
+                &mut LeaderExecuteAndCommitTimings::default(),
+            );
+            assert_matches!(result, Err(BundleExecutionError::TransactionFailure(_)));
+            assert_eq!(bank.get_balance(&mint_keypair.pubkey()), mint_balance);
+            assert_eq!(bank.get_balance(&mint_pubkey), 100);
+            assert_eq!(bank.get_balance(&pubkey), 60);
+            assert_eq!(
+                entry_receiver
+                    .try_iter()
+                    .filter(|(_, (entry, _))| !entry.is_tick())
+                    .count(),
+                0
+            );
+
+            // A bundle whose accounts are in use can be retried
+            let batch = bank.prepare_sanitized_batch(&failing_bundle[..1]);
+            let result = BankingStage::process_and_record_bundle(
+                &bank,
+                &failing_bundle,
+                &recorder,
+                None,
+                &gossip_vote_sender,
+                &qos_service,
+                &mut LeaderExecuteAndCommitTimings::default(),
+            );
+            assert_matches!(
+                result,
+                Err(BundleExecutionError::Retryable(
+                    TransactionError::AccountInUse
+                ))
+            );
+            drop(batch);
+
+            poh_recorder
+                .lock()
+                .unwrap()
+                .is_exited
+                .store(true, Ordering::Relaxed);
+            let _ = poh_simulator.join();
+        }
+        Blockstore::destroy(ledger_path.path()).unwrap();
+    }
+
     #[test]
     fn test_bank_process_and_record_transactions_cost_tracker() {
         mundis_logger::setup();
@@ -0,0 +1,212 @@
+//! The `bundle_assembler` module collects the packets of bundles as they come out of
+//! signature verification. Each transaction of a bundle travels in a packet of its own, so the
+//! packets of a bundle may be received in different batches. Once every packet of a bundle has
+//! arrived, the bundle is handed over to the banking stage as a packet batch of its own.
+
+use {
+    mundis_perf::packet::{Packet, PacketBatch},
+    mundis_sdk::{
+        bundle::{BundlePacketHeader, MAX_BUNDLE_TRANSACTIONS},
+        hash::Hash,
+    },
+    std::{
+        collections::HashMap,
+        time::{Duration, Instant},
+    },
+};
+
+struct PendingBundle {
+    packets: Vec<Option<Packet>>,
+    first_received: Instant,
+}
+
+pub struct BundleAssembler {
+    max_pending_bundles: usize,
+    max_age: Duration,
+    pending_bundles: HashMap<Hash, PendingBundle>,
+}
+
+impl BundleAssembler {
+    pub fn new(max_pending_bundles: usize, max_age: Duration) -> Self {
+        Self {
+            max_pending_bundles,
+            max_age,
+            pending_bundles: HashMap::default(),
+        }
+    }
+
+    /// Takes the valid bundle packets out of `batches`, by marking them as discarded, and
+    /// appends a packet batch for every bundle whose packets have now all arrived.
+    ///
+    /// Returns the number of bundles that were appended.
+    pub fn assemble(&mut self, batches: &mut Vec<PacketBatch>) -> usize {
+        let now = Instant::now();
+        let max_age = self.max_age;
+        self.pending_bundles
+            .retain(|_, bundle| now.duration_since(bundle.first_received) < max_age);
+
+        let mut bundle_batches = vec![];
+        for packet in batches
+            .iter_mut()
+            .flat_map(|batch| batch.packets.iter_mut())
+        {
+            if packet.meta.discard() || !packet.meta.is_bundle() {
+                continue;
+            }
+            packet.meta.set_discard(true);
+            if let Some(bundle_batch) = self.insert(packet, now) {
+                bundle_batches.push(bundle_batch);
+            }
+        }
+        let num_bundles = bundle_batches.len();
+        batches.extend(bundle_batches);
+        num_bundles
+    }
+
+    fn insert(&mut self, packet: &Packet, now: Instant) -> Option<PacketBatch> {
+        let (header, _transaction) =
+            BundlePacketHeader::from_packet_data(&packet.data[..packet.meta.size])?;
+        if header.count == 0 || header.count as usize > MAX_BUNDLE_TRANSACTIONS {
+            return None;
+        }
+        if !self.pending_bundles.contains_key(&header.bundle_id)
+            && self.pending_bundles.len() >= self.max_pending_bundles
+        {
+            return None;
+        }
+        let bundle = self
+            .pending_bundles
+            .entry(header.bundle_id)
+            .or_insert_with(|| PendingBundle {
+                packets: vec![None; header.count as usize],
+                first_received: now,
+            });
+        if bundle.packets.len() != header.count as usize {
+            return None;
+        }
+        let pending_packet = &mut bundle.packets[header.index as usize];
+        if pending_packet.is_some() {
+            return None;
+        }
+        let mut packet = packet.clone();
+        packet.meta.set_discard(false);
+        *pending_packet = Some(packet);
+
+        if bundle.packets.iter().any(Option::is_none) {
+            return None;
+        }
+        let bundle = self.pending_bundles.remove(&header.bundle_id)?;
+        Some(PacketBatch::new(
+            bundle.packets.into_iter().flatten().collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        mundis_perf::test_tx::test_tx,
+        mundis_sdk::{
+            bundle::{VersionedBundle, BUNDLE_PACKET_HEADER_SIZE},
+            packet::PacketFlags,
+        },
+    };
+
+    fn bundle_packets(bundle: &VersionedBundle) -> Vec<Packet> {
+        bundle
+            .to_packets_data()
+            .unwrap()
+            .into_iter()
+            .map(|data| {
+                let mut packet = Packet::default();
+                packet.meta.size = data.len();
+                packet.meta.flags |= PacketFlags::BUNDLE;
+                packet.data[..data.len()].copy_from_slice(&data);
+                packet
+            })
+            .collect()
+    }
+
+    fn test_tx_packet() -> Packet {
+        Packet::from_data(None, test_tx()).unwrap()
+    }
+
+    fn valid_packets(batches: &[PacketBatch]) -> usize {
+        batches
+            .iter()
+            .flat_map(|batch| &batch.packets)
+            .filter(|packet| !packet.meta.discard())
+            .count()
+    }
+
+    #[test]
+    fn test_bundle_assembler() {
+        let mut assembler = BundleAssembler::new(1, Duration::from_secs(60));
+        let bundle = VersionedBundle::new(vec![test_tx().into(), test_tx().into()]);
+        let packets = bundle_packets(&bundle);
+
+        // The packets of a bundle are held until all of them have arrived
+        let mut batches = vec![PacketBatch::new(vec![test_tx_packet(), packets[1].clone()])];
+        assert_eq!(assembler.assemble(&mut batches), 0);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(valid_packets(&batches), 1);
+
+        // Only one bundle can be pending at a time
+        let other_bundle = VersionedBundle::new(vec![test_tx().into(), test_tx().into()]);
+        let mut batches = vec![PacketBatch::new(
+            bundle_packets(&other_bundle)[..1].to_vec(),
+        )];
+        assert_eq!(assembler.assemble(&mut batches), 0);
+        assert_eq!(valid_packets(&batches), 0);
+
+        let mut batches = vec![PacketBatch::new(packets[..1].to_vec())];
+        assert_eq!(assembler.assemble(&mut batches), 1);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(valid_packets(&batches[..1]), 0);
+        let bundle_batch = &batches[1];
+        assert_eq!(
+            VersionedBundle::from_packets_data(
+                bundle_batch
+                    .packets
+                    .iter()
+                    .map(|packet| &packet.data[..packet.meta.size])
+            ),
+            Some(bundle)
+        );
+        assert!(bundle_batch
+            .packets
+            .iter()
+            .all(|packet| packet.meta.is_bundle() && !packet.meta.discard()));
+        assert!(assembler.pending_bundles.is_empty());
+    }
+
+    #[test]
+    fn test_bundle_assembler_too_many_transactions() {
+        let mut assembler = BundleAssembler::new(1, Duration::from_secs(60));
+        let mut packet = bundle_packets(&VersionedBundle::new(vec![test_tx().into()]))
+            .pop()
+            .unwrap();
+        // Claim more transactions than a bundle may hold
+        packet.data[BUNDLE_PACKET_HEADER_SIZE - 1] = MAX_BUNDLE_TRANSACTIONS as u8 + 1;
+
+        let mut batches = vec![PacketBatch::new(vec![packet])];
+        assert_eq!(assembler.assemble(&mut batches), 0);
+        assert_eq!(valid_packets(&batches), 0);
+        assert!(assembler.pending_bundles.is_empty());
+    }
+
+    #[test]
+    fn test_bundle_assembler_max_age() {
+        let mut assembler = BundleAssembler::new(1, Duration::ZERO);
+        let bundle = VersionedBundle::new(vec![test_tx().into(), test_tx().into()]);
+        let packets = bundle_packets(&bundle);
+
+        let mut batches = vec![PacketBatch::new(packets[..1].to_vec())];
+        assert_eq!(assembler.assemble(&mut batches), 0);
+        // The first packet has expired by the time the second one arrives
+        let mut batches = vec![PacketBatch::new(packets[1..].to_vec())];
+        assert_eq!(assembler.assemble(&mut batches), 0);
+        assert_eq!(batches.len(), 1);
+    }
+}
@@ -13,21 +13,31 @@ use {
         sync::atomic::{AtomicBool, Ordering},
         sync::{mpsc::Receiver, Arc, RwLock},
         thread::{self, Builder, JoinHandle},
-        time::Duration,
+        time::{Duration, Instant},
     },
 };
 
+/// How often learned costs are written back to the blockstore
+const PERSIST_COST_TABLE_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Default)]
 pub struct CostUpdateServiceTiming {
     last_print: u64,
     update_cost_model_count: u64,
     update_cost_model_elapsed: u64,
+    persist_cost_table_elapsed: u64,
 }
 
 impl CostUpdateServiceTiming {
-    fn update(&mut self, update_cost_model_count: u64, update_cost_model_elapsed: u64) {
+    fn update(
+        &mut self,
+        update_cost_model_count: u64,
+        update_cost_model_elapsed: u64,
+        persist_cost_table_elapsed: u64,
+    ) {
         self.update_cost_model_count += update_cost_model_count;
         self.update_cost_model_elapsed += update_cost_model_elapsed;
+        self.persist_cost_table_elapsed += persist_cost_table_elapsed;
 
         let now = timestamp();
         let elapsed_ms = now - self.last_print;
@@ -45,6 +55,11 @@ impl CostUpdateServiceTiming {
                     self.update_cost_model_elapsed as i64,
                     i64
                 ),
+                (
+                    "persist_cost_table_elapsed",
+                    self.persist_cost_table_elapsed as i64,
+                    i64
+                ),
             );
 
             *self = CostUpdateServiceTiming::default();
@@ -92,13 +107,15 @@ impl CostUpdateService {
 
     fn service_loop(
         exit: Arc<AtomicBool>,
-        _blockstore: Arc<Blockstore>,
+        blockstore: Arc<Blockstore>,
         cost_model: Arc<RwLock<CostModel>>,
         cost_update_receiver: CostUpdateReceiver,
     ) {
         let mut cost_update_service_timing = CostUpdateServiceTiming::default();
         let mut update_count: u64;
         let wait_timer = Duration::from_millis(100);
+        let mut dirty = false;
+        let mut last_persist = Instant::now();
 
         loop {
             if exit.load(Ordering::Relaxed) {
@@ -115,14 +132,26 @@ impl CostUpdateService {
                     CostUpdate::ExecuteTiming {
                         mut execute_timings,
                     } => {
-                        Self::update_cost_model(&cost_model, &mut execute_timings);
+                        dirty |= Self::update_cost_model(&cost_model, &mut execute_timings);
                         update_count += 1;
                     }
                 }
             }
             update_cost_model_time.stop();
 
-            cost_update_service_timing.update(update_count, update_cost_model_time.as_us());
+            let mut persist_cost_table_time = Measure::start("persist_cost_table_time");
+            if dirty && last_persist.elapsed() >= PERSIST_COST_TABLE_INTERVAL {
+                Self::persist_cost_table(&blockstore, &cost_model);
+                dirty = false;
+                last_persist = Instant::now();
+            }
+            persist_cost_table_time.stop();
+
+            cost_update_service_timing.update(
+                update_count,
+                update_cost_model_time.as_us(),
+                persist_cost_table_time.as_us(),
+            );
 
             thread::sleep(wait_timer);
         }
@@ -164,6 +193,42 @@ impl CostUpdateService {
                     }
                 }
             }
+
+            for (key, instruction_timings) in
+                &mut execute_timings.details.per_builtin_instruction_timings
+            {
+                let current_estimated_instruction_cost = cost_model
+                    .read()
+                    .unwrap()
+                    .find_builtin_instruction_cost(key);
+                instruction_timings.coalesce_error_timings(current_estimated_instruction_cost);
+
+                if instruction_timings.count < 1 {
+                    continue;
+                }
+
+                let units =
+                    instruction_timings.accumulated_units / instruction_timings.count as u64;
+                match cost_model
+                    .write()
+                    .unwrap()
+                    .upsert_builtin_instruction_cost(key, units)
+                {
+                    Ok(c) => {
+                        debug!(
+                            "after replayed into bank, builtin instruction {:?} has averaged cost {}",
+                            key, c
+                        );
+                        dirty = true;
+                    }
+                    Err(err) => {
+                        debug!(
+                            "after replayed into bank, builtin instruction {:?} failed to update cost, err: {}",
+                            key, err
+                        );
+                    }
+                }
+            }
         }
         debug!(
            "after replayed into bank, updated cost model instruction cost table, current values: {:?}",
@@ -171,11 +236,67 @@ impl CostUpdateService {
         );
         dirty
     }
+
+    /// Writes the learned cost tables to the blockstore so they survive restarts, dropping
+    /// the records the cost model has since pruned
+    fn persist_cost_table(blockstore: &Blockstore, cost_model: &RwLock<CostModel>) {
+        let cost_model = cost_model.read().unwrap();
+
+        let cost_table = cost_model.get_instruction_cost_table();
+        match blockstore.read_program_costs() {
+            Ok(db_records) => db_records
+                .iter()
+                .filter(|(program_id, _)| !cost_table.contains_key(program_id))
+                .for_each(|(program_id, _)| {
+                    if let Err(err) = blockstore.delete_program_cost(program_id) {
+                        warn!("failed to delete program cost of {}: {:?}", program_id, err);
+                    }
+                }),
+            Err(err) => warn!("failed to read program costs: {:?}", err),
+        }
+        for (program_id, cost) in cost_table {
+            if let Err(err) = blockstore.write_program_cost(program_id, cost) {
+                warn!(
+                    "failed to persist program cost of {}: {:?}",
+                    program_id, err
+                );
+            }
+        }
+
+        let cost_table = cost_model.get_builtin_instruction_cost_table();
+        match blockstore.read_builtin_instruction_costs() {
+            Ok(db_records) => db_records
+                .iter()
+                .filter(|(key, _)| !cost_table.contains_key(key))
+                .for_each(|(key, _)| {
+                    if let Err(err) = blockstore.delete_builtin_instruction_cost(key) {
+                        warn!(
+                            "failed to delete builtin instruction cost of {:?}: {:?}",
+                            key, err
+                        );
+                    }
+                }),
+            Err(err) => warn!("failed to read builtin instruction costs: {:?}", err),
+        }
+        for (key, cost) in cost_table {
+            if let Err(err) = blockstore.write_builtin_instruction_cost(key, cost) {
+                warn!(
+                    "failed to persist builtin instruction cost of {:?}: {:?}",
+                    key, err
+                );
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use {super::*, mundis_program_runtime::timings::ProgramTiming, mundis_sdk::pubkey::Pubkey};
+    use {
+        super::*,
+        mundis_ledger::get_tmp_ledger_path_auto_delete,
+        mundis_program_runtime::timings::ProgramTiming,
+        mundis_sdk::{pubkey::Pubkey, system_program},
+    };
 
     #[test]
     fn test_update_cost_model_with_empty_execute_timings() {
@@ -377,4 +498,110 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_update_cost_model_with_builtin_instruction_timings() {
+        let cost_model = Arc::new(RwLock::new(CostModel::default()));
+        cost_model
+            .write()
+            .unwrap()
+            .upsert_instruction_cost(&system_program::id(), 150)
+            .unwrap();
+        let mut execute_timings = ExecuteTimings::default();
+        let transfer_key = (system_program::id(), 2);
+        let create_account_key = (system_program::id(), 0);
+
+        execute_timings
+            .details
+            .per_builtin_instruction_timings
+            .insert(
+                transfer_key,
+                ProgramTiming {
+                    accumulated_us: 1000,
+                    accumulated_units: 500,
+                    count: 10,
+                    errored_txs_compute_consumed: vec![],
+                    total_errored_units: 0,
+                },
+            );
+        // only erroring instructions are costed at no less than their program
+        execute_timings
+            .details
+            .per_builtin_instruction_timings
+            .insert(
+                create_account_key,
+                ProgramTiming {
+                    accumulated_us: 1000,
+                    accumulated_units: 0,
+                    count: 0,
+                    errored_txs_compute_consumed: vec![100; 2],
+                    total_errored_units: 200,
+                },
+            );
+        assert!(CostUpdateService::update_cost_model(
+            &cost_model,
+            &mut execute_timings
+        ));
+
+        let cost_model = cost_model.read().unwrap();
+        assert_eq!(2, cost_model.get_builtin_instruction_cost_table().len());
+        assert_eq!(50, cost_model.find_builtin_instruction_cost(&transfer_key));
+        assert_eq!(
+            150,
+            cost_model.find_builtin_instruction_cost(&create_account_key)
+        );
+        // unseen instructions still fall back to the program cost
+        assert_eq!(
+            150,
+            cost_model.find_builtin_instruction_cost(&(system_program::id(), 1))
+        );
+    }
+
+    #[test]
+    fn test_persist_cost_table() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let cost_model = RwLock::new(CostModel::default());
+        let program_key = Pubkey::new_unique();
+        let stale_program_key = Pubkey::new_unique();
+        let instruction_key = (system_program::id(), 2);
+        let stale_instruction_key = (system_program::id(), 9);
+        {
+            let mut cost_model = cost_model.write().unwrap();
+            cost_model
+                .upsert_instruction_cost(&program_key, 100)
+                .unwrap();
+            cost_model
+                .upsert_builtin_instruction_cost(&instruction_key, 40)
+                .unwrap();
+        }
+        blockstore
+            .write_program_cost(&stale_program_key, &1)
+            .unwrap();
+        blockstore
+            .write_builtin_instruction_cost(&stale_instruction_key, &1)
+            .unwrap();
+
+        CostUpdateService::persist_cost_table(&blockstore, &cost_model);
+        assert_eq!(
+            blockstore.read_program_costs().unwrap(),
+            vec![(program_key, 100)]
+        );
+        assert_eq!(
+            blockstore.read_builtin_instruction_costs().unwrap(),
+            vec![(instruction_key, 40)]
+        );
+
+        // a restarted node picks up where it left off
+        let mut restored_cost_model = CostModel::default();
+        restored_cost_model.initialize_cost_table(&blockstore.read_program_costs().unwrap());
+        restored_cost_model.initialize_builtin_instruction_cost_table(
+            &blockstore.read_builtin_instruction_costs().unwrap(),
+        );
+        assert_eq!(100, restored_cost_model.find_instruction_cost(&program_key));
+        assert_eq!(
+            40,
+            restored_cost_model.find_builtin_instruction_cost(&instruction_key)
+        );
+    }
 }
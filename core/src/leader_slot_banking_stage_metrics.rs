@@ -41,6 +41,14 @@ pub(crate) struct ProcessTransactionsSummary {
     // The number of transactions filtered out by the cost model
     pub cost_model_throttled_transactions_count: usize,
 
+    // The number of transactions filtered out by the cost model because they would exceed
+    // the block's new account data limit
+    pub account_data_throttled_transactions_count: usize,
+
+    // The number of transactions filtered out by the cost model because they pay less than the
+    // local minimum priority fee of an account they write
+    pub priority_fee_throttled_transactions_count: usize,
+
     // Total amount of time spent running the cost model
     pub cost_model_us: u64,
 
@@ -102,6 +110,16 @@ struct LeaderSlotPacketCountMetrics {
     // already counted in `self.retrayble_errored_transaction_count`.
     cost_model_throttled_transactions_count: u64,
 
+    // total number of transactions that were excluded from the block because the new account data
+    // they allocate would exceed the block's limit. These are already counted in
+    // `self.cost_model_throttled_transactions_count`.
+    account_data_throttled_transactions_count: u64,
+
+    // total number of transactions that were excluded from the block because they pay less than
+    // the local minimum priority fee of a contended account they write. These are already counted
+    // in `self.cost_model_throttled_transactions_count`.
+    priority_fee_throttled_transactions_count: u64,
+
     // total number of forwardsable packets that failed forwarding
     failed_forwarded_packets_count: u64,
 
@@ -189,6 +207,16 @@ impl LeaderSlotPacketCountMetrics {
                 self.cost_model_throttled_transactions_count as i64,
                 i64
             ),
+            (
+                "account_data_throttled_transactions_count",
+                self.account_data_throttled_transactions_count as i64,
+                i64
+            ),
+            (
+                "priority_fee_throttled_transactions_count",
+                self.priority_fee_throttled_transactions_count as i64,
+                i64
+            ),
             (
                 "failed_forwarded_packets_count",
                 self.failed_forwarded_packets_count as i64,
@@ -342,6 +370,8 @@ impl LeaderSlotMetricsTracker {
                 failed_commit_count,
                 ref retryable_transaction_indexes,
                 cost_model_throttled_transactions_count,
+                account_data_throttled_transactions_count,
+                priority_fee_throttled_transactions_count,
                 cost_model_us,
                 ref execute_and_commit_timings,
                 ..
@@ -398,6 +428,20 @@ impl LeaderSlotMetricsTracker {
                 *cost_model_throttled_transactions_count as u64
             );
 
+            saturating_add_assign!(
+                leader_slot_metrics
+                    .packet_count_metrics
+                    .account_data_throttled_transactions_count,
+                *account_data_throttled_transactions_count as u64
+            );
+
+            saturating_add_assign!(
+                leader_slot_metrics
+                    .packet_count_metrics
+                    .priority_fee_throttled_transactions_count,
+                *priority_fee_throttled_transactions_count as u64
+            );
+
             saturating_add_assign!(
                 leader_slot_metrics
                     .timing_metrics
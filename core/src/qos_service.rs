@@ -5,6 +5,7 @@
 use {
     crate::banking_stage::{BatchedTransactionCostDetails, CommitTransactionDetails},
     mundis_measure::measure::Measure,
+    mundis_program_runtime::compute_budget::ComputeBudget,
     mundis_runtime::{
         bank::Bank,
         cost_model::{CostModel, TransactionCost},
@@ -118,27 +119,42 @@ impl QosService {
         let mut num_included = 0;
         let select_results = transactions
             .zip(transactions_costs)
-            .map(|(tx, cost)| match cost_tracker.try_add(cost) {
-                Ok(current_block_cost) => {
-                    debug!("slot {:?}, transaction {:?}, cost {:?}, fit into current block, current block cost {}", bank.slot(), tx, cost, current_block_cost);
-                    self.metrics.selected_txs_count.fetch_add(1, Ordering::Relaxed);
-                    num_included += 1;
-                    Ok(())
-                },
-                Err(e) => {
-                    debug!("slot {:?}, transaction {:?}, cost {:?}, not fit into current block, '{:?}'", bank.slot(), tx, cost, e);
-                    match e {
-                        CostTrackerError::WouldExceedBlockMaxLimit => {
-                            self.metrics.retried_txs_per_block_limit_count.fetch_add(1, Ordering::Relaxed);
-                            Err(TransactionError::WouldExceedMaxBlockCostLimit)
-                        }
-                        CostTrackerError::WouldExceedVoteMaxLimit => {
-                            self.metrics.retried_txs_per_vote_limit_count.fetch_add(1, Ordering::Relaxed);
-                            Err(TransactionError::WouldExceedMaxVoteCostLimit)
-                        }
-                        CostTrackerError::WouldExceedAccountMaxLimit => {
-                            self.metrics.retried_txs_per_account_limit_count.fetch_add(1, Ordering::Relaxed);
-                            Err(TransactionError::WouldExceedMaxAccountCostLimit)
+            .map(|(tx, cost)| {
+                // Transactions writing to a contended account must pay at least that
+                // account's local minimum priority fee; cheaper ones are retried so that
+                // better paying transactions get the remaining account capacity first.
+                let local_minimum_fee = cost_tracker.transaction_minimum_priority_fee(cost);
+                if local_minimum_fee > 0 && Self::priority_fee(tx) < local_minimum_fee {
+                    debug!("slot {:?}, transaction {:?}, cost {:?}, pays below local minimum priority fee {}", bank.slot(), tx, cost, local_minimum_fee);
+                    self.metrics.retried_txs_per_local_fee_count.fetch_add(1, Ordering::Relaxed);
+                    return Err(TransactionError::InsufficientPriorityFee);
+                }
+                match cost_tracker.try_add(cost) {
+                    Ok(current_block_cost) => {
+                        debug!("slot {:?}, transaction {:?}, cost {:?}, fit into current block, current block cost {}", bank.slot(), tx, cost, current_block_cost);
+                        self.metrics.selected_txs_count.fetch_add(1, Ordering::Relaxed);
+                        num_included += 1;
+                        Ok(())
+                    },
+                    Err(e) => {
+                        debug!("slot {:?}, transaction {:?}, cost {:?}, not fit into current block, '{:?}'", bank.slot(), tx, cost, e);
+                        match e {
+                            CostTrackerError::WouldExceedBlockMaxLimit => {
+                                self.metrics.retried_txs_per_block_limit_count.fetch_add(1, Ordering::Relaxed);
+                                Err(TransactionError::WouldExceedMaxBlockCostLimit)
+                            }
+                            CostTrackerError::WouldExceedVoteMaxLimit => {
+                                self.metrics.retried_txs_per_vote_limit_count.fetch_add(1, Ordering::Relaxed);
+                                Err(TransactionError::WouldExceedMaxVoteCostLimit)
+                            }
+                            CostTrackerError::WouldExceedAccountMaxLimit => {
+                                self.metrics.retried_txs_per_account_limit_count.fetch_add(1, Ordering::Relaxed);
+                                Err(TransactionError::WouldExceedMaxAccountCostLimit)
+                            }
+                            CostTrackerError::WouldExceedAccountDataBlockLimit => {
+                                self.metrics.retried_txs_per_account_data_block_limit_count.fetch_add(1, Ordering::Relaxed);
+                                Err(TransactionError::WouldExceedMaxAccountDataCostLimit)
+                            }
                         }
                     }
                 }
@@ -151,6 +167,14 @@ impl QosService {
         (select_results, num_included)
     }
 
+    /// Priority fee the transaction offers through its compute budget instructions;
+    /// malformed requests are treated as offering none.
+    fn priority_fee(transaction: &SanitizedTransaction) -> u64 {
+        ComputeBudget::default()
+            .process_message(transaction.message(), false, false)
+            .unwrap_or_default()
+    }
+
     /// Update the transaction cost in the cost_tracker with the real cost for
     /// transactions that were executed successfully;
     /// Otherwise remove the cost from the cost tracker, therefore preventing cost_tracker
@@ -269,6 +293,8 @@ struct QosServiceMetrics {
     retried_txs_per_block_limit_count: AtomicU64,
     retried_txs_per_vote_limit_count: AtomicU64,
     retried_txs_per_account_limit_count: AtomicU64,
+    retried_txs_per_local_fee_count: AtomicU64,
+    retried_txs_per_account_data_block_limit_count: AtomicU64,
 
     // accumulated estimated signature Compute Unites to be packed into block
     estimated_signature_cu: AtomicU64,
@@ -332,6 +358,18 @@ impl QosServiceMetrics {
                         .swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "retried_txs_per_local_fee_count",
+                    self.retried_txs_per_local_fee_count
+                        .swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
+                (
+                    "retried_txs_per_account_data_block_limit_count",
+                    self.retried_txs_per_account_data_block_limit_count
+                        .swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
                 (
                     "estimated_signature_cu",
                     self.estimated_signature_cu.swap(0, Ordering::Relaxed) as i64,
@@ -372,11 +410,14 @@ mod tests {
     use {
         super::*,
         itertools::Itertools,
+        mundis_runtime::cost_tracker::MAX_LOCAL_MINIMUM_PRIORITY_FEE,
         mundis_runtime::genesis_utils::{create_genesis_config, GenesisConfigInfo},
         mundis_sdk::{
+            compute_budget::ComputeBudgetInstruction,
             hash::Hash,
             signature::{Keypair, Signer},
-            system_transaction,
+            system_instruction, system_transaction,
+            transaction::Transaction,
         },
         mundis_vote_program::vote_transaction,
     };
@@ -473,6 +514,57 @@ mod tests {
         assert!(results[3].is_err());
     }
 
+    #[test]
+    fn test_select_transactions_per_local_fee() {
+        mundis_logger::setup();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let cost_model = Arc::new(RwLock::new(CostModel::default()));
+
+        let keypair = Keypair::new();
+        let transfer_tx = SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default()),
+        );
+        let priority_transfer_tx =
+            SanitizedTransaction::from_transaction_for_tests(Transaction::new_signed_with_payer(
+                &[
+                    ComputeBudgetInstruction::request_units(
+                        200_000,
+                        MAX_LOCAL_MINIMUM_PRIORITY_FEE as u32,
+                    ),
+                    system_instruction::transfer(&keypair.pubkey(), &keypair.pubkey(), 1),
+                ],
+                Some(&keypair.pubkey()),
+                &[&keypair],
+                Hash::default(),
+            ));
+        let txs = vec![transfer_tx, priority_transfer_tx];
+
+        let qos_service = QosService::new(cost_model);
+        let txs_costs = qos_service.compute_transaction_costs(txs.iter());
+
+        // make the payer account contended, so writing to it requires a priority fee
+        let account_cost_limit = txs_costs.iter().map(|cost| cost.sum()).max().unwrap() * 10;
+        {
+            let mut cost_tracker = bank.write_cost_tracker().unwrap();
+            cost_tracker.set_limits(account_cost_limit, u64::MAX, u64::MAX);
+            cost_tracker.add_executed_transaction_cost(&TransactionCost {
+                writable_accounts: vec![keypair.pubkey()],
+                execution_cost: account_cost_limit * 3 / 4,
+                ..TransactionCost::default()
+            });
+            assert!(cost_tracker.local_minimum_priority_fee(&keypair.pubkey()) > 0);
+        }
+
+        let (results, num_selected) =
+            qos_service.select_transactions_per_cost(txs.iter(), txs_costs.iter(), &bank);
+        assert_eq!(num_selected, 1);
+        assert_eq!(
+            results,
+            vec![Err(TransactionError::InsufficientPriorityFee), Ok(())]
+        );
+    }
+
     #[test]
     fn test_async_report_metrics() {
         mundis_logger::setup();
@@ -6,7 +6,7 @@
 //! if perf-libs are available
 
 use {
-    crate::sigverify,
+    crate::{bundle_assembler::BundleAssembler, sigverify},
     core::time::Duration,
     crossbeam_channel::{SendError, Sender as CrossbeamSender},
     itertools::Itertools,
@@ -36,6 +36,10 @@ const MAX_DEDUP_BATCH: usize = 165_000;
 // 50ms/(25us/packet) = 2000 packets
 const MAX_SIGVERIFY_BATCH: usize = 2_000;
 
+// Bundles whose packets have not all arrived within this long are dropped
+const MAX_BUNDLE_ASSEMBLY_AGE: Duration = Duration::from_secs(2);
+const MAX_PENDING_BUNDLES: usize = 10_000;
+
 // Packet batch shrinker will reorganize packets into compacted batches if 10%
 // or more of the packets in a group of packet batches have been discarded.
 const MAX_DISCARDED_PACKET_RATE: f64 = 0.10;
@@ -75,6 +79,7 @@ struct SigVerifierStats {
     total_dedup: usize,
     total_excess_fail: usize,
     total_valid_packets: usize,
+    total_bundles: usize,
     total_shrinks: usize,
     total_discard_random: usize,
     total_dedup_time_us: usize,
@@ -191,6 +196,7 @@ impl SigVerifierStats {
             ("total_dedup", self.total_dedup, i64),
             ("total_excess_fail", self.total_excess_fail, i64),
             ("total_valid_packets", self.total_valid_packets, i64),
+            ("total_bundles", self.total_bundles, i64),
             ("total_discard_random", self.total_discard_random, i64),
             ("total_shrinks", self.total_shrinks, i64),
             ("total_dedup_time_us", self.total_dedup_time_us, i64),
@@ -275,6 +281,7 @@ impl SigVerifyStage {
 
     fn verifier<T: SigVerifier>(
         deduper: &Deduper,
+        bundle_assembler: &mut BundleAssembler,
         recvr: &PacketBatchReceiver,
         sendr: &CrossbeamSender<Vec<PacketBatch>>,
         verifier: &T,
@@ -320,6 +327,9 @@ impl SigVerifyStage {
         let num_valid_packets = count_valid_packets(&batches);
         verify_time.stop();
 
+        // The packets of a bundle move to a batch of their own once all of them are verified
+        let num_bundles = bundle_assembler.assemble(&mut batches);
+
         // Post-shrink packet batches if many packets are discarded from sigverify
         let (post_shrink_time_us, post_shrink_total) = Self::maybe_shrink_batches(&mut batches);
 
@@ -356,6 +366,7 @@ impl SigVerifyStage {
         stats.total_packets += num_packets;
         stats.total_dedup += dedup_fail;
         stats.total_valid_packets += num_valid_packets;
+        stats.total_bundles += num_bundles;
         stats.total_discard_random_time_us += discard_random_time.as_us() as usize;
         stats.total_discard_random += num_discarded_randomly;
         stats.total_excess_fail += excess_fail;
@@ -383,10 +394,13 @@ impl SigVerifyStage {
             .name("mundis-verifier".to_string())
             .spawn(move || {
                 let mut deduper = Deduper::new(MAX_DEDUPER_ITEMS, MAX_DEDUPER_AGE);
+                let mut bundle_assembler =
+                    BundleAssembler::new(MAX_PENDING_BUNDLES, MAX_BUNDLE_ASSEMBLY_AGE);
                 loop {
                     deduper.reset();
                     if let Err(e) = Self::verifier(
                         &deduper,
+                        &mut bundle_assembler,
                         &packet_receiver,
                         &verified_sender,
                         &verifier,
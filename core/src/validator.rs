@@ -792,8 +792,14 @@ impl Validator {
 
         let vote_tracker = Arc::<VoteTracker>::default();
         let mut cost_model = CostModel::default();
-        // initialize cost model with built-in instruction costs only
-        cost_model.initialize_cost_table(&[]);
+        // initialize cost model with built-in instruction costs and the costs learned before
+        // the last restart
+        cost_model.initialize_cost_table(&blockstore.read_program_costs().unwrap_or_default());
+        cost_model.initialize_builtin_instruction_cost_table(
+            &blockstore
+                .read_builtin_instruction_costs()
+                .unwrap_or_default(),
+        );
         let cost_model = Arc::new(RwLock::new(cost_model));
 
         let (retransmit_slots_sender, retransmit_slots_receiver) = unbounded();
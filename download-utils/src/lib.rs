@@ -9,10 +9,12 @@ use {
     },
     mundis_sdk::{clock::Slot, genesis_config::DEFAULT_GENESIS_ARCHIVE, hash::Hash},
     std::{
-        fs::{self, File},
-        io::{self, Read},
+        collections::VecDeque,
+        fs::{self, File, OpenOptions},
+        io::{self, BufReader, Read, Seek, SeekFrom},
         net::SocketAddr,
         path::{Path, PathBuf},
+        thread::{Builder, JoinHandle},
         time::{Duration, Instant},
     },
 };
@@ -55,6 +57,208 @@ pub struct DownloadProgressRecord {
 type DownloadProgressCallback<'a> = Box<dyn FnMut(&DownloadProgressRecord) -> bool + 'a>;
 type DownloadProgressCallbackOption<'a> = Option<DownloadProgressCallback<'a>>;
 
+/// Bytes fetched by each HTTP range request
+const DOWNLOAD_CHUNK_SIZE: u64 = 32 * 1024 * 1024;
+/// Range requests kept in flight at once, when the server supports them
+const MAX_DOWNLOAD_CONNECTIONS: usize = 4;
+/// Times an interrupted download is resumed from its partial file before giving up
+const MAX_DOWNLOAD_ATTEMPTS: usize = 5;
+
+enum DownloadError {
+    /// The progress callback asked for the download to stop
+    Aborted(String),
+    Failed(String),
+}
+
+/// Files can also be fetched from local storage by `file://` URL
+fn local_path(url: &str) -> Option<PathBuf> {
+    url.strip_prefix("file://").map(PathBuf::from)
+}
+
+fn partial_file_len(path: &Path) -> u64 {
+    fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+/// Parse a `Content-Range: bytes start-end/length` header into its (inclusive) start and end
+/// and the length of the whole file
+fn parse_content_range(content_range: &str) -> Option<(u64, u64, u64)> {
+    let (range, file_length) = content_range.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    Some((
+        start.parse().ok()?,
+        end.parse().ok()?,
+        file_length.parse().ok()?,
+    ))
+}
+
+/// Read the body of a range request for bytes `start..end`, which must be complete
+fn read_range_response(
+    url: &str,
+    mut response: reqwest::blocking::Response,
+    start: u64,
+    end: u64,
+) -> Result<Vec<u8>, String> {
+    let mut data = Vec::with_capacity((end - start) as usize);
+    response
+        .read_to_end(&mut data)
+        .map_err(|err| err.to_string())?;
+    if data.len() as u64 != end - start {
+        return Err(format!(
+            "Received {} bytes instead of {} for bytes {}-{} of {}",
+            data.len(),
+            end - start,
+            start,
+            end,
+            url
+        ));
+    }
+    Ok(data)
+}
+
+fn fetch_range(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    start: u64,
+    end: u64,
+) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", start, end - 1),
+        )
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(format!("{} ignored a range request", url));
+    }
+    read_range_response(url, response, start, end)
+}
+
+/// Reads a file over HTTP by keeping up to `MAX_DOWNLOAD_CONNECTIONS` range requests in
+/// flight, and returns their bytes in order
+struct RangeReader {
+    client: reqwest::blocking::Client,
+    url: String,
+    current: io::Cursor<Vec<u8>>,
+    next_offset: u64,
+    file_length: u64,
+    in_flight: VecDeque<JoinHandle<Result<Vec<u8>, String>>>,
+}
+
+impl RangeReader {
+    fn fetch_ahead(&mut self) -> io::Result<()> {
+        while self.in_flight.len() < MAX_DOWNLOAD_CONNECTIONS && self.next_offset < self.file_length
+        {
+            let start = self.next_offset;
+            let end = (start + DOWNLOAD_CHUNK_SIZE).min(self.file_length);
+            self.next_offset = end;
+            let client = self.client.clone();
+            let url = self.url.clone();
+            self.in_flight.push_back(
+                Builder::new()
+                    .name("mundis-download-range".to_string())
+                    .spawn(move || fetch_range(&client, &url, start, end))?,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fetch_ahead()?;
+        loop {
+            let n = self.current.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            let chunk = match self.in_flight.pop_front() {
+                Some(chunk) => chunk,
+                None => return Ok(0),
+            };
+            let chunk = chunk
+                .join()
+                .map_err(|_| io::Error::new(io::ErrorKind::Other, "range request panicked"))?
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            self.current = io::Cursor::new(chunk);
+            self.fetch_ahead()?;
+        }
+    }
+}
+
+/// Open `url` for reading from `offset`.  Returns the source, the offset it actually starts at,
+/// which is zero if the download cannot be resumed, and the length of the file, which is zero
+/// if unknown.
+fn open_download_source(url: &str, offset: u64) -> Result<(Box<dyn Read>, u64, u64), String> {
+    if let Some(path) = local_path(url) {
+        let mut file =
+            File::open(&path).map_err(|err| format!("Unable to open {:?}: {}", path, err))?;
+        let file_length = file.metadata().map_err(|err| err.to_string())?.len();
+        let offset = if offset <= file_length { offset } else { 0 };
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|err| err.to_string())?;
+        return Ok((Box::new(BufReader::new(file)), offset, file_length));
+    }
+
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .get(url)
+        .header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", offset, offset + DOWNLOAD_CHUNK_SIZE - 1),
+        )
+        .send()
+        .map_err(|err| err.to_string())?;
+    let content_range = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|content_range| content_range.to_str().ok())
+        .map(|content_range| content_range.to_string());
+
+    match response.status() {
+        reqwest::StatusCode::PARTIAL_CONTENT => {
+            let (start, end, file_length) = content_range
+                .as_deref()
+                .and_then(parse_content_range)
+                .filter(|(start, _, _)| *start == offset)
+                .ok_or_else(|| format!("Invalid Content-Range {:?} from {}", content_range, url))?;
+            let first_chunk = read_range_response(url, response, start, end + 1)?;
+            let mut range_reader = RangeReader {
+                client,
+                url: url.to_string(),
+                current: io::Cursor::new(first_chunk),
+                next_offset: end + 1,
+                file_length,
+                in_flight: VecDeque::new(),
+            };
+            range_reader.fetch_ahead().map_err(|err| err.to_string())?;
+            Ok((Box::new(range_reader), offset, file_length))
+        }
+        reqwest::StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+            let file_length = content_range
+                .as_deref()
+                .and_then(|content_range| content_range.strip_prefix("bytes */"))
+                .and_then(|file_length| file_length.parse().ok());
+            if file_length == Some(offset) {
+                // the partial file already holds all of it
+                Ok((Box::new(io::empty()), offset, offset))
+            } else {
+                open_download_source(url, 0)
+            }
+        }
+        _ => {
+            // the server ignored the range, so the whole file comes over this connection
+            let response = response.error_for_status().map_err(|err| err.to_string())?;
+            let file_length = response.content_length().unwrap_or(0);
+            Ok((Box::new(response), 0, file_length))
+        }
+    }
+}
+
 /// This callback allows the caller to get notified of the download progress modelled by DownloadProgressRecord
 /// Return "true" to continue the download
 /// Return "false" to abort the download
@@ -64,6 +268,31 @@ pub fn download_file<'a, 'b>(
     use_progress_bar: bool,
     progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
 ) -> Result<(), String> {
+    download_and_verify_file(
+        url,
+        destination_file,
+        use_progress_bar,
+        progress_notify_callback,
+        |_| Ok(()),
+    )
+}
+
+/// Download `url` to `destination_file`, which is only moved into place once `verify` accepts it.
+///
+/// `url` may also be a `file://` URL.  The file is first written to a
+/// `tmp-` prefixed file next to `destination_file`; an interrupted download resumes from it,
+/// with range requests if the server supports them, and a file that fails `verify` is deleted
+/// so the next download starts over.
+pub fn download_and_verify_file<'a, 'b, F>(
+    url: &str,
+    destination_file: &Path,
+    use_progress_bar: bool,
+    progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
+    verify: F,
+) -> Result<(), String>
+where
+    F: Fn(&Path) -> Result<(), String>,
+{
     if destination_file.is_file() {
         return Err(format!("{:?} already exists", destination_file));
     }
@@ -82,31 +311,74 @@ pub fn download_file<'a, 'b>(
             .expect("to_str")
     ));
 
+    let mut attempt = 1;
+    let download_size = loop {
+        let partial_len = partial_file_len(&temp_destination_file);
+        match download_attempt(
+            url,
+            &temp_destination_file,
+            use_progress_bar,
+            progress_notify_callback,
+        ) {
+            Ok(download_size) => break download_size,
+            Err(DownloadError::Aborted(err)) => return Err(err),
+            Err(DownloadError::Failed(err)) => {
+                // Only a transfer that was interrupted after making progress is resumed,
+                // anything else (like a missing file) is reported straight away
+                if attempt >= MAX_DOWNLOAD_ATTEMPTS
+                    || partial_file_len(&temp_destination_file) <= partial_len
+                {
+                    return Err(err);
+                }
+                attempt += 1;
+                warn!("{}. Resuming download of {}, attempt {}", err, url, attempt);
+            }
+        }
+    };
+
+    if let Err(err) = verify(&temp_destination_file) {
+        let _ignored = fs::remove_file(&temp_destination_file);
+        return Err(format!("{} failed verification: {}", url, err));
+    }
+
+    info!(
+        "  {}{}",
+        SPARKLE,
+        format!(
+            "Downloaded {} ({} bytes) in {:?}",
+            url,
+            download_size,
+            Instant::now().duration_since(download_start),
+        )
+    );
+
+    std::fs::rename(temp_destination_file, destination_file)
+        .map_err(|err| format!("Unable to rename: {:?}", err))?;
+
+    Ok(())
+}
+
+/// Download the rest of `url` into `temp_destination_file`, returning the length of the file
+fn download_attempt<'a, 'b>(
+    url: &str,
+    temp_destination_file: &Path,
+    use_progress_bar: bool,
+    progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
+) -> Result<u64, DownloadError> {
     let progress_bar = new_spinner_progress_bar();
     if use_progress_bar {
         progress_bar.set_message(format!("{}Downloading {}...", TRUCK, url));
     }
 
-    let response = reqwest::blocking::Client::new()
-        .get(url)
-        .send()
-        .and_then(|response| response.error_for_status())
-        .map_err(|err| {
+    let (response, start_offset, download_size) =
+        open_download_source(url, partial_file_len(temp_destination_file)).map_err(|err| {
             progress_bar.finish_and_clear();
-            err.to_string()
+            DownloadError::Failed(err)
         })?;
 
-    let download_size = {
-        response
-            .headers()
-            .get(reqwest::header::CONTENT_LENGTH)
-            .and_then(|content_length| content_length.to_str().ok())
-            .and_then(|content_length| content_length.parse().ok())
-            .unwrap_or(0)
-    };
-
     if use_progress_bar {
         progress_bar.set_length(download_size);
+        progress_bar.set_position(start_offset);
         progress_bar.set_style(
             ProgressStyle::default_bar()
                 .template(
@@ -115,6 +387,11 @@ pub fn download_file<'a, 'b>(
                 .progress_chars("=> "),
         );
         progress_bar.set_message(format!("{}Downloading~ {}", TRUCK, url));
+    } else if start_offset > 0 {
+        info!(
+            "Resuming download of {} bytes from {} at byte {}",
+            download_size, url, start_offset
+        );
     } else {
         info!("Downloading {} bytes from {}", download_size, url);
     }
@@ -130,6 +407,7 @@ pub fn download_file<'a, 'b>(
         start_time: Instant,
         callback: &'f mut DownloadProgressCallbackOption<'e>,
         notification_count: u64,
+        aborted: bool,
     }
 
     impl<'e, 'f, R: Read> Read for DownloadProgress<'e, 'f, R> {
@@ -181,6 +459,7 @@ pub fn download_file<'a, 'b>(
             if let Some(callback) = self.callback {
                 if to_update_progress && !callback(&progress_record) {
                     info!("Download is aborted by the caller");
+                    self.aborted = true;
                     return Err(io::Error::new(
                         io::ErrorKind::Other,
                         "Download is aborted by the caller",
@@ -196,35 +475,44 @@ pub fn download_file<'a, 'b>(
         progress_bar,
         response,
         last_print: Instant::now(),
-        current_bytes: 0,
-        last_print_bytes: 0,
+        current_bytes: start_offset as usize,
+        last_print_bytes: start_offset as usize,
         download_size: (download_size as f32).max(1f32),
         use_progress_bar,
         start_time: Instant::now(),
         callback: progress_notify_callback,
         notification_count: 0,
+        aborted: false,
     };
 
-    File::create(&temp_destination_file)
-        .and_then(|mut file| std::io::copy(&mut source, &mut file))
-        .map_err(|err| format!("Unable to write {:?}: {:?}", temp_destination_file, err))?;
-
+    let result = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open(temp_destination_file)
+        .and_then(|mut file| {
+            // drop anything past the offset the source resumes from
+            file.set_len(start_offset)?;
+            file.seek(SeekFrom::End(0))?;
+            std::io::copy(&mut source, &mut file)
+        });
     source.progress_bar.finish_and_clear();
-    info!(
-        "  {}{}",
-        SPARKLE,
-        format!(
-            "Downloaded {} ({} bytes) in {:?}",
-            url,
-            download_size,
-            Instant::now().duration_since(download_start),
-        )
-    );
-
-    std::fs::rename(temp_destination_file, destination_file)
-        .map_err(|err| format!("Unable to rename: {:?}", err))?;
+    if let Err(err) = result {
+        let err = format!("Unable to write {:?}: {:?}", temp_destination_file, err);
+        return Err(if source.aborted {
+            DownloadError::Aborted(err)
+        } else {
+            DownloadError::Failed(err)
+        });
+    }
 
-    Ok(())
+    let file_length = partial_file_len(temp_destination_file);
+    if download_size > 0 && file_length != download_size {
+        return Err(DownloadError::Failed(format!(
+            "Download of {} stopped after {} of {} bytes",
+            url, file_length, download_size
+        )));
+    }
+    Ok(file_length)
 }
 
 pub fn download_genesis_if_missing(
@@ -261,6 +549,32 @@ pub fn download_snapshot_archive<'a, 'b>(
     maximum_incremental_snapshot_archives_to_retain: usize,
     use_progress_bar: bool,
     progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
+) -> Result<(), String> {
+    download_snapshot_archive_from_url(
+        &format!("http://{}", rpc_addr),
+        snapshot_archives_dir,
+        desired_snapshot_hash,
+        snapshot_type,
+        maximum_full_snapshot_archives_to_retain,
+        maximum_incremental_snapshot_archives_to_retain,
+        use_progress_bar,
+        progress_notify_callback,
+    )
+}
+
+/// Download a snapshot archive from under `base_url`, which may be an RPC node, an HTTP mirror
+/// or the `file://` URL of a local directory.  The archive is only accepted once the bank snapshot
+/// inside matches `desired_snapshot_hash`.
+#[allow(clippy::too_many_arguments)]
+pub fn download_snapshot_archive_from_url<'a, 'b>(
+    base_url: &str,
+    snapshot_archives_dir: &Path,
+    desired_snapshot_hash: (Slot, Hash),
+    snapshot_type: SnapshotType,
+    maximum_full_snapshot_archives_to_retain: usize,
+    maximum_incremental_snapshot_archives_to_retain: usize,
+    use_progress_bar: bool,
+    progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
 ) -> Result<(), String> {
     snapshot_utils::purge_old_snapshot_archives(
         snapshot_archives_dir,
@@ -269,7 +583,9 @@ pub fn download_snapshot_archive<'a, 'b>(
     );
 
     for archive_format in [
+        ArchiveFormat::TarZstdSeekable,
         ArchiveFormat::TarZstd,
+        ArchiveFormat::TarLz4,
         ArchiveFormat::TarGzip,
         ArchiveFormat::TarBzip2,
         ArchiveFormat::Tar, // `mundis-test-validator` creates uncompressed snapshots
@@ -296,15 +612,25 @@ pub fn download_snapshot_archive<'a, 'b>(
             return Ok(());
         }
 
-        match download_file(
+        match download_and_verify_file(
             &format!(
-                "http://{}/{}",
-                rpc_addr,
+                "{}/{}",
+                base_url.trim_end_matches('/'),
                 destination_path.file_name().unwrap().to_str().unwrap()
             ),
             &destination_path,
             use_progress_bar,
             progress_notify_callback,
+            |archive_path| {
+                info!("Verifying {:?}", archive_path);
+                snapshot_utils::verify_snapshot_archive_slot_hash(
+                    archive_path,
+                    archive_format,
+                    snapshot_type,
+                    desired_snapshot_hash,
+                )
+                .map_err(|err| err.to_string())
+            },
         ) {
             Ok(()) => return Ok(()),
             Err(err) => info!("{}", err),
@@ -312,6 +638,6 @@ pub fn download_snapshot_archive<'a, 'b>(
     }
     Err(format!(
         "Failed to download a snapshot archive for slot {} from {}",
-        desired_snapshot_hash.0, rpc_addr
+        desired_snapshot_hash.0, base_url
     ))
 }
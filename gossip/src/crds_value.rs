@@ -205,6 +205,9 @@ impl SnapshotHashes {
     }
 }
 
+/// The incremental snapshots a node has, all based on the full snapshot `base`, which is a full
+/// accounts hash.  The `hashes` are incremental accounts hashes, of only the accounts modified
+/// after the base slot.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, AbiExample)]
 pub struct IncrementalSnapshotHashes {
     pub from: Pubkey,
@@ -0,0 +1,522 @@
+/// The `accounts export` subcommand
+use {
+    crate::snapshot::snapshot_archive_infos,
+    clap::{value_t, values_t, App, Arg, ArgMatches, SubCommand},
+    log::*,
+    mundis_account_decoder::{UiAccount, UiAccountData, UiAccountEncoding},
+    mundis_clap_utils::input_validators::{is_parsable, is_pubkey},
+    mundis_measure::measure::Measure,
+    mundis_runtime::{accounts_db::AccountsDb, snapshot_utils},
+    mundis_sdk::{account::ReadableAccount, pubkey::Pubkey},
+    parquet::{
+        column::writer::ColumnWriter,
+        data_type::ByteArray,
+        errors::ParquetError,
+        file::{
+            properties::WriterProperties,
+            writer::{FileWriter, RowGroupWriter, SerializedFileWriter},
+        },
+        schema::parser::parse_message_type,
+    },
+    std::{
+        collections::HashSet,
+        fs::File,
+        io::{self, BufWriter, Write},
+        mem,
+        path::{Path, PathBuf},
+        process::exit,
+        sync::Arc,
+    },
+};
+
+/// Number of accounts buffered into each parquet row group
+const PARQUET_ROW_GROUP_SIZE: usize = 65_536;
+
+const PARQUET_SCHEMA: &str = "
+    message account {
+        REQUIRED BYTE_ARRAY pubkey (UTF8);
+        REQUIRED BYTE_ARRAY owner (UTF8);
+        REQUIRED INT64 lamports (UINT_64);
+        REQUIRED INT64 rent_epoch (UINT_64);
+        REQUIRED BOOLEAN executable;
+        REQUIRED BYTE_ARRAY data (UTF8);
+    }
+";
+
+const CSV_HEADER: [&str; 6] = [
+    "pubkey",
+    "owner",
+    "lamports",
+    "rent_epoch",
+    "executable",
+    "data",
+];
+
+/// Which accounts to export
+#[derive(Debug, Default)]
+struct ExportFilter {
+    owners: Option<HashSet<Pubkey>>,
+    min_data_size: Option<usize>,
+    max_data_size: Option<usize>,
+}
+
+impl ExportFilter {
+    fn matches(&self, account: &impl ReadableAccount) -> bool {
+        let data_size = account.data().len();
+        self.owners
+            .as_ref()
+            .map_or(true, |owners| owners.contains(account.owner()))
+            && self
+                .min_data_size
+                .map_or(true, |min_data_size| data_size >= min_data_size)
+            && self
+                .max_data_size
+                .map_or(true, |max_data_size| data_size <= max_data_size)
+    }
+}
+
+/// The data of an exported account as text: base64, or the JSON `account-decoder` parsed it into
+fn data_to_string(data: &UiAccountData) -> Result<String, String> {
+    match data {
+        UiAccountData::LegacyBinary(data) | UiAccountData::Binary(data, _) => Ok(data.clone()),
+        UiAccountData::Json(parsed_account) => serde_json::to_string(parsed_account)
+            .map_err(|err| format!("Unable to serialize parsed account data: {}", err)),
+    }
+}
+
+trait AccountsWriter {
+    fn write(&mut self, pubkey: &Pubkey, account: &UiAccount) -> Result<(), String>;
+
+    /// Flush whatever is still buffered.  Called once, after the last account
+    fn finish(&mut self) -> Result<(), String>;
+}
+
+struct CsvAccountsWriter<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> CsvAccountsWriter<W> {
+    fn new(output: W) -> Result<Self, String> {
+        let mut writer = csv::Writer::from_writer(output);
+        writer
+            .write_record(&CSV_HEADER)
+            .map_err(|err| format!("Unable to write csv: {}", err))?;
+        Ok(Self { writer })
+    }
+}
+
+impl<W: Write> AccountsWriter for CsvAccountsWriter<W> {
+    fn write(&mut self, pubkey: &Pubkey, account: &UiAccount) -> Result<(), String> {
+        self.writer
+            .write_record(&[
+                pubkey.to_string(),
+                account.owner.clone(),
+                account.lamports.to_string(),
+                account.rent_epoch.to_string(),
+                account.executable.to_string(),
+                data_to_string(&account.data)?,
+            ])
+            .map_err(|err| format!("Unable to write csv: {}", err))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|err| format!("Unable to write csv: {}", err))
+    }
+}
+
+/// Writes one `{"pubkey": ..., "account": ...}` object per line, the same shape as the
+/// `getProgramAccounts` RPC results
+struct JsonlAccountsWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> AccountsWriter for JsonlAccountsWriter<W> {
+    fn write(&mut self, pubkey: &Pubkey, account: &UiAccount) -> Result<(), String> {
+        let keyed_account = serde_json::json!({
+            "pubkey": pubkey.to_string(),
+            "account": account,
+        });
+        serde_json::to_writer(&mut self.writer, &keyed_account)
+            .map_err(|err| format!("Unable to write jsonl: {}", err))?;
+        writeln!(self.writer).map_err(|err| format!("Unable to write jsonl: {}", err))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|err| format!("Unable to write jsonl: {}", err))
+    }
+}
+
+/// The columns of the parquet row group being buffered
+#[derive(Default)]
+struct ParquetColumns {
+    pubkey: Vec<ByteArray>,
+    owner: Vec<ByteArray>,
+    lamports: Vec<i64>,
+    rent_epoch: Vec<i64>,
+    executable: Vec<bool>,
+    data: Vec<ByteArray>,
+}
+
+impl ParquetColumns {
+    fn len(&self) -> usize {
+        self.pubkey.len()
+    }
+}
+
+fn parquet_error(err: ParquetError) -> String {
+    format!("Unable to write parquet: {}", err)
+}
+
+struct ParquetAccountsWriter {
+    writer: SerializedFileWriter<File>,
+    columns: ParquetColumns,
+}
+
+impl ParquetAccountsWriter {
+    fn new(output: File) -> Result<Self, String> {
+        let schema = Arc::new(parse_message_type(PARQUET_SCHEMA).map_err(parquet_error)?);
+        let properties = Arc::new(WriterProperties::builder().build());
+        let writer =
+            SerializedFileWriter::new(output, schema, properties).map_err(parquet_error)?;
+        Ok(Self {
+            writer,
+            columns: ParquetColumns::default(),
+        })
+    }
+
+    fn write_row_group(&mut self) -> Result<(), String> {
+        if self.columns.pubkey.is_empty() {
+            return Ok(());
+        }
+        let columns = mem::take(&mut self.columns);
+        let mut row_group_writer = self.writer.next_row_group().map_err(parquet_error)?;
+        let mut column_index = 0;
+        while let Some(mut column_writer) = row_group_writer.next_column().map_err(parquet_error)? {
+            match (column_index, &mut column_writer) {
+                (0, ColumnWriter::ByteArrayColumnWriter(writer)) => {
+                    writer.write_batch(&columns.pubkey, None, None)
+                }
+                (1, ColumnWriter::ByteArrayColumnWriter(writer)) => {
+                    writer.write_batch(&columns.owner, None, None)
+                }
+                (2, ColumnWriter::Int64ColumnWriter(writer)) => {
+                    writer.write_batch(&columns.lamports, None, None)
+                }
+                (3, ColumnWriter::Int64ColumnWriter(writer)) => {
+                    writer.write_batch(&columns.rent_epoch, None, None)
+                }
+                (4, ColumnWriter::BoolColumnWriter(writer)) => {
+                    writer.write_batch(&columns.executable, None, None)
+                }
+                (5, ColumnWriter::ByteArrayColumnWriter(writer)) => {
+                    writer.write_batch(&columns.data, None, None)
+                }
+                _ => unreachable!("column {} does not match the parquet schema", column_index),
+            }
+            .map_err(parquet_error)?;
+            row_group_writer
+                .close_column(column_writer)
+                .map_err(parquet_error)?;
+            column_index += 1;
+        }
+        self.writer
+            .close_row_group(row_group_writer)
+            .map_err(parquet_error)
+    }
+}
+
+impl AccountsWriter for ParquetAccountsWriter {
+    fn write(&mut self, pubkey: &Pubkey, account: &UiAccount) -> Result<(), String> {
+        // UINT_64 columns are stored as INT64 with the same bits
+        self.columns
+            .pubkey
+            .push(ByteArray::from(pubkey.to_string().into_bytes()));
+        self.columns
+            .owner
+            .push(ByteArray::from(account.owner.clone().into_bytes()));
+        self.columns.lamports.push(account.lamports as i64);
+        self.columns.rent_epoch.push(account.rent_epoch as i64);
+        self.columns.executable.push(account.executable);
+        self.columns
+            .data
+            .push(ByteArray::from(data_to_string(&account.data)?.into_bytes()));
+        if self.columns.len() >= PARQUET_ROW_GROUP_SIZE {
+            self.write_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.write_row_group()?;
+        self.writer.close().map_err(parquet_error)?;
+        Ok(())
+    }
+}
+
+fn accounts_writer(
+    format: &str,
+    output_path: Option<&Path>,
+) -> Result<Box<dyn AccountsWriter>, String> {
+    let create_output = |output_path: &Path| {
+        File::create(output_path)
+            .map_err(|err| format!("Unable to create {:?}: {}", output_path, err))
+    };
+    let buffered_output = || -> Result<Box<dyn Write>, String> {
+        Ok(match output_path {
+            Some(output_path) => Box::new(BufWriter::new(create_output(output_path)?)),
+            None => Box::new(BufWriter::new(io::stdout())),
+        })
+    };
+
+    match format {
+        "csv" => Ok(Box::new(CsvAccountsWriter::new(buffered_output()?)?)),
+        "jsonl" => Ok(Box::new(JsonlAccountsWriter {
+            writer: buffered_output()?,
+        })),
+        "parquet" => {
+            let output_path = output_path.ok_or("The parquet format requires an --output file")?;
+            Ok(Box::new(ParquetAccountsWriter::new(create_output(
+                output_path,
+            )?)?))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Stream the accounts of the bank of `archive_path` that match `filter` into `writer`, reading
+/// them straight out of the account storages of the archive, so neither the bank nor the accounts
+/// index has to be rebuilt.  Returns the number of accounts exported.
+fn export(
+    archive_path: &Path,
+    filter: &ExportFilter,
+    encoding: UiAccountEncoding,
+    writer: &mut dyn AccountsWriter,
+) -> Result<usize, String> {
+    let (full_snapshot_archive_info, incremental_snapshot_archive_info) =
+        snapshot_archive_infos(archive_path)?;
+    let temp_dir = tempfile::Builder::new()
+        .prefix("ledger-tool-accounts-export.")
+        .tempdir()
+        .map_err(|err| format!("Unable to create temporary directory: {}", err))?;
+    let account_paths = vec![temp_dir.path().join("accounts")];
+    let bank_snapshots_dir = temp_dir.path().join("snapshot");
+    std::fs::create_dir_all(&bank_snapshots_dir).map_err(|err| {
+        format!(
+            "Unable to create directory {:?}: {}",
+            bank_snapshots_dir, err
+        )
+    })?;
+
+    let mut measure = Measure::start("unpack storages");
+    let storages = snapshot_utils::snapshot_storages_from_snapshot_archives(
+        &account_paths,
+        &bank_snapshots_dir,
+        &full_snapshot_archive_info,
+        incremental_snapshot_archive_info.as_ref(),
+    )
+    .map_err(|err| format!("Unable to unpack {:?}: {}", archive_path, err))?;
+    measure.stop();
+    info!("{}", measure);
+
+    let mut measure = Measure::start("export accounts");
+    let mut num_exported = 0;
+    AccountsDb::scan_latest_stored_accounts(&storages, |_slot, account| {
+        if !filter.matches(account) {
+            return Ok(());
+        }
+        let pubkey = account.meta.pubkey;
+        writer.write(
+            &pubkey,
+            &UiAccount::encode(&pubkey, account, encoding, None, None),
+        )?;
+        num_exported += 1;
+        Ok(())
+    })?;
+    writer.finish()?;
+    measure.stop();
+    info!("{}", measure);
+    Ok(num_exported)
+}
+
+pub fn accounts_export_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("export")
+        .about(
+            "Export the accounts of a snapshot archive, streaming them from its account \
+             storages",
+        )
+        .arg(
+            Arg::with_name("archive")
+                .index(1)
+                .value_name("ARCHIVE")
+                .takes_value(true)
+                .required(true)
+                .help(
+                    "Full or incremental snapshot archive. The full snapshot archive of an \
+                     incremental snapshot archive must be in the same directory",
+                ),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["csv", "jsonl", "parquet"])
+                .default_value("csv")
+                .help("Output format"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Write to this file instead of stdout. Required for parquet"),
+        )
+        .arg(
+            Arg::with_name("owner")
+                .long("owner")
+                .value_name("PROGRAM_ID")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_pubkey)
+                .help(
+                    "Only export accounts owned by this program. May be specified multiple times",
+                ),
+        )
+        .arg(
+            Arg::with_name("min_data_size")
+                .long("min-data-size")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Only export accounts with at least this many bytes of data"),
+        )
+        .arg(
+            Arg::with_name("max_data_size")
+                .long("max-data-size")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Only export accounts with at most this many bytes of data"),
+        )
+        .arg(
+            Arg::with_name("parsed")
+                .long("parsed")
+                .takes_value(false)
+                .help(
+                    "Export the data of accounts owned by parsable programs as JSON, \
+                     instead of base64",
+                ),
+        )
+}
+
+pub fn accounts_export_process_command(arg_matches: &ArgMatches<'_>) {
+    let archive_path = PathBuf::from(arg_matches.value_of("archive").unwrap());
+    let output_path = arg_matches.value_of("output").map(PathBuf::from);
+    let filter = ExportFilter {
+        owners: values_t!(arg_matches, "owner", Pubkey)
+            .ok()
+            .map(|owners| owners.into_iter().collect()),
+        min_data_size: value_t!(arg_matches, "min_data_size", usize).ok(),
+        max_data_size: value_t!(arg_matches, "max_data_size", usize).ok(),
+    };
+    let encoding = if arg_matches.is_present("parsed") {
+        UiAccountEncoding::JsonParsed
+    } else {
+        UiAccountEncoding::Base64
+    };
+
+    let result = accounts_writer(
+        arg_matches.value_of("format").unwrap(),
+        output_path.as_deref(),
+    )
+    .and_then(|mut writer| export(&archive_path, &filter, encoding, writer.as_mut()));
+    match result {
+        Ok(num_exported) => eprintln!("Exported {} accounts", num_exported),
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, mundis_sdk::account::AccountSharedData};
+
+    #[test]
+    fn test_export_filter() {
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(1, 10, &owner);
+        assert!(ExportFilter::default().matches(&account));
+
+        let filter = ExportFilter {
+            owners: Some([owner].into_iter().collect()),
+            ..ExportFilter::default()
+        };
+        assert!(filter.matches(&account));
+        assert!(!filter.matches(&AccountSharedData::new(1, 10, &Pubkey::new_unique())));
+
+        let filter = ExportFilter {
+            min_data_size: Some(10),
+            max_data_size: Some(20),
+            ..ExportFilter::default()
+        };
+        assert!(filter.matches(&account));
+        assert!(!filter.matches(&AccountSharedData::new(1, 9, &owner)));
+        assert!(!filter.matches(&AccountSharedData::new(1, 21, &owner)));
+    }
+
+    #[test]
+    fn test_csv_accounts_writer() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(42, 3, &owner);
+        let ui_account =
+            UiAccount::encode(&pubkey, &account, UiAccountEncoding::Base64, None, None);
+
+        let mut output = vec![];
+        {
+            let mut writer = CsvAccountsWriter::new(&mut output).unwrap();
+            writer.write(&pubkey, &ui_account).unwrap();
+            writer.finish().unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "pubkey,owner,lamports,rent_epoch,executable,data\n{},{},42,0,false,AAAA\n",
+                pubkey, owner
+            )
+        );
+    }
+
+    #[test]
+    fn test_jsonl_accounts_writer() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(42, 3, &owner);
+        let ui_account =
+            UiAccount::encode(&pubkey, &account, UiAccountEncoding::Base64, None, None);
+
+        let mut writer = JsonlAccountsWriter { writer: vec![] };
+        writer.write(&pubkey, &ui_account).unwrap();
+        writer.write(&pubkey, &ui_account).unwrap();
+        writer.finish().unwrap();
+
+        let output = String::from_utf8(writer.writer).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let keyed_account: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(keyed_account["pubkey"], pubkey.to_string());
+        assert_eq!(keyed_account["account"]["lamports"], 42);
+        assert_eq!(keyed_account["account"]["owner"], owner.to_string());
+        assert_eq!(
+            keyed_account["account"]["data"],
+            serde_json::json!(["AAAA", "base64"])
+        );
+    }
+}
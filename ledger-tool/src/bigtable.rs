@@ -16,12 +16,14 @@ use {
     },
     mundis_ledger::{blockstore::Blockstore, blockstore_db::AccessType},
     mundis_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    mundis_storage_bigtable::{LedgerStorageAdapter, LocalLedgerStorage},
     mundis_transaction_status::{
-        ConfirmedBlockWithOptionalMetadata, EncodedTransaction, UiTransactionEncoding,
+        ConfirmedBlockWithOptionalMetadata, EncodeError, EncodedTransaction,
+        UiTransactionEncoding,
     },
     std::{
         collections::HashSet,
-        path::Path,
+        path::{Path, PathBuf},
         process::exit,
         result::Result,
         sync::{atomic::AtomicBool, Arc},
@@ -33,10 +35,19 @@ async fn upload(
     starting_slot: Slot,
     ending_slot: Option<Slot>,
     force_reupload: bool,
+    local_ledger_storage_path: Option<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = mundis_storage_bigtable::LedgerStorage::new(false, None, None)
-        .await
-        .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
+    let bigtable: Arc<dyn LedgerStorageAdapter> = match local_ledger_storage_path {
+        Some(path) => Arc::new(
+            LocalLedgerStorage::open(&path, false)
+                .map_err(|err| format!("Failed to open local storage: {:?}", err))?,
+        ),
+        None => Arc::new(
+            mundis_storage_bigtable::LedgerStorage::new(false, None, None)
+                .await
+                .map_err(|err| format!("Failed to connect to storage: {:?}", err))?,
+        ),
+    };
 
     mundis_ledger::bigtable_upload::upload_confirmed_blocks(
         Arc::new(blockstore),
@@ -75,8 +86,16 @@ async fn block(slot: Slot, output_format: OutputFormat) -> Result<(), Box<dyn st
 
     let block = bigtable.get_confirmed_block(slot).await?;
 
+    let encoded_block = block
+        .encode(UiTransactionEncoding::Base64, None)
+        .map_err(|err| match err {
+            EncodeError::UnsupportedTransactionVersion(version) => format!(
+                "Failed to process unsupported transaction version ({}) in block",
+                version
+            ),
+        })?;
     let cli_block = CliBlock {
-        encoded_confirmed_block: block.encode(UiTransactionEncoding::Base64),
+        encoded_confirmed_block: encoded_block,
         slot,
     };
     println!("{}", output_format.formatted_string(&cli_block));
@@ -154,18 +173,30 @@ async fn confirm(
     if verbose {
         match bigtable.get_confirmed_transaction(signature).await {
             Ok(Some(confirmed_transaction)) => {
-                transaction = Some(CliTransaction {
-                    transaction: EncodedTransaction::encode(
-                        confirmed_transaction.transaction.transaction.clone(),
-                        UiTransactionEncoding::Json,
-                    ),
-                    meta: confirmed_transaction.transaction.meta.map(|m| m.into()),
-                    block_time: confirmed_transaction.block_time,
-                    slot: Some(confirmed_transaction.slot),
-                    decoded_transaction: confirmed_transaction.transaction.transaction,
-                    prefix: "  ".to_string(),
-                    sigverify_status: vec![],
-                });
+                match confirmed_transaction
+                    .transaction
+                    .transaction
+                    .into_legacy_transaction()
+                {
+                    Some(decoded_transaction) => {
+                        transaction = Some(CliTransaction {
+                            transaction: EncodedTransaction::encode(
+                                decoded_transaction.clone(),
+                                UiTransactionEncoding::Json,
+                            ),
+                            meta: confirmed_transaction.transaction.meta.map(|m| m.into()),
+                            block_time: confirmed_transaction.block_time,
+                            slot: Some(confirmed_transaction.slot),
+                            decoded_transaction,
+                            prefix: "  ".to_string(),
+                            sigverify_status: vec![],
+                        });
+                    }
+                    None => {
+                        get_transaction_error =
+                            Some("Unsupported transaction version".to_string());
+                    }
+                }
             }
             Ok(None) => {}
             Err(err) => {
@@ -243,13 +274,23 @@ pub async fn transaction_history(
                                     );
                                 }
                                 Some(transaction_with_meta) => {
-                                    println_transaction(
-                                        &transaction_with_meta.transaction,
-                                        &transaction_with_meta.meta.clone().map(|m| m.into()),
-                                        "  ",
-                                        None,
-                                        None,
-                                    );
+                                    match transaction_with_meta
+                                        .transaction
+                                        .clone()
+                                        .into_legacy_transaction()
+                                    {
+                                        Some(transaction) => println_transaction(
+                                            &transaction,
+                                            &transaction_with_meta.meta.clone().map(|m| m.into()),
+                                            "  ",
+                                            None,
+                                            None,
+                                        ),
+                                        None => println!(
+                                            "  Transaction {} has an unsupported version",
+                                            result.signature
+                                        ),
+                                    }
                                 }
                             }
                             break;
@@ -315,6 +356,16 @@ impl BigTableSubCommand for App<'_, '_> {
                                     Note: reupload will *not* delete any data from the tx-by-addr table;\
                                     Use with care.",
                                 ),
+                        )
+                        .arg(
+                            Arg::with_name("local_ledger_storage")
+                                .long("local-ledger-storage")
+                                .value_name("DIR")
+                                .takes_value(true)
+                                .help(
+                                    "Upload into the local RocksDB ledger storage in DIR \
+                                    instead of BigTable",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -501,6 +552,9 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
             let force_reupload = arg_matches.is_present("force_reupload");
+            let local_ledger_storage_path = arg_matches
+                .value_of("local_ledger_storage")
+                .map(PathBuf::from);
             let blockstore = crate::open_blockstore(
                 &canonicalize_ledger_path(ledger_path),
                 AccessType::TryPrimaryThenSecondary,
@@ -512,6 +566,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 starting_slot,
                 ending_slot,
                 force_reupload,
+                local_ledger_storage_path,
             ))
         }
         ("delete-slots", Some(arg_matches)) => {
@@ -78,10 +78,14 @@ use {
     },
 };
 
+mod accounts_export;
+use accounts_export::*;
 mod bigtable;
 use bigtable::*;
 mod ledger_path;
 use ledger_path::*;
+mod snapshot;
+use snapshot::*;
 
 #[derive(PartialEq)]
 enum LedgerOutputMethod {
@@ -663,6 +667,32 @@ fn analyze_storage(database: &Database) {
     analyze_column::<PerfSamples>(database, "PerfSamples");
     analyze_column::<BlockHeight>(database, "BlockHeight");
     analyze_column::<ProgramCosts>(database, "ProgramCosts");
+    analyze_column::<BuiltinInstructionCosts>(database, "BuiltinInstructionCosts");
+}
+
+fn print_program_costs(blockstore: &Blockstore) {
+    let mut program_costs = blockstore.read_program_costs().unwrap_or_else(|err| {
+        eprintln!("Failed to read program costs: {:?}", err);
+        exit(1);
+    });
+    program_costs.sort_by_key(|(program_id, _)| *program_id);
+    println!("Program costs (compute units):");
+    for (program_id, cost) in program_costs {
+        println!("  {}: {}", program_id, cost);
+    }
+
+    let mut builtin_instruction_costs =
+        blockstore
+            .read_builtin_instruction_costs()
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to read builtin instruction costs: {:?}", err);
+                exit(1);
+            });
+    builtin_instruction_costs.sort_by_key(|(key, _)| *key);
+    println!("Builtin instruction costs (compute units):");
+    for ((program_id, discriminator), cost) in builtin_instruction_costs {
+        println!("  {} instruction {}: {}", program_id, discriminator, cost);
+    }
 }
 
 fn open_blockstore(
@@ -765,6 +795,9 @@ fn compute_slot_cost(blockstore: &Blockstore, slot: Slot) -> Result<(), String>
     let mut program_ids = HashMap::new();
     let mut cost_model = CostModel::default();
     cost_model.initialize_cost_table(&blockstore.read_program_costs().unwrap());
+    cost_model.initialize_builtin_instruction_cost_table(
+        &blockstore.read_builtin_instruction_costs().unwrap(),
+    );
     let mut cost_tracker = CostTracker::default();
 
     for entry in entries {
@@ -1045,6 +1078,7 @@ fn main() {
                 .help("Show additional information where supported"),
         )
         .bigtable_subcommand()
+        .snapshot_subcommand()
         .subcommand(
             SubCommand::with_name("print")
             .about("Print the ledger")
@@ -1436,6 +1470,7 @@ fn main() {
                     .help("Do not print account data when printing account contents."),
             )
             .arg(&max_genesis_archive_unpacked_size_arg)
+            .subcommand(accounts_export_subcommand())
         ).subcommand(
             SubCommand::with_name("capitalization")
             .about("Print capitalization (aka, total supply) while checksumming it")
@@ -1601,6 +1636,11 @@ fn main() {
                     .help("Slots that their blocks are computed for cost, default to all slots in ledger"),
             )
         )
+        .subcommand(
+            SubCommand::with_name("program-costs")
+            .about("Print the execution costs the cost model has learned, \
+                   per program and per builtin program instruction")
+        )
         .get_matches();
 
     info!("{} {}", crate_name!(), mundis_version::version!());
@@ -2473,6 +2513,9 @@ fn main() {
                     }
                 }
             }
+            ("accounts", Some(arg_matches)) if arg_matches.subcommand_name() == Some("export") => {
+                accounts_export_process_command(arg_matches.subcommand_matches("export").unwrap())
+            }
             ("accounts", Some(arg_matches)) => {
                 let dev_halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
                 let process_options = ProcessOptions {
@@ -3311,6 +3354,15 @@ fn main() {
                     }
                 }
             }
+            ("snapshot", Some(arg_matches)) => snapshot_process_command(&ledger_path, arg_matches),
+            ("program-costs", Some(_arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+                print_program_costs(&blockstore);
+            }
             ("", _) => {
                 eprintln!("{}", matches.usage());
                 exit(1);
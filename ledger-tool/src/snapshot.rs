@@ -0,0 +1,703 @@
+/// The `snapshot` subcommand
+use {
+    clap::{value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand},
+    log::*,
+    mundis_clap_utils::input_validators::is_parsable,
+    mundis_ledger::{bank_forks_utils, blockstore_processor::ProcessOptions},
+    mundis_measure::measure::Measure,
+    mundis_runtime::{
+        bank::Bank,
+        hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+        snapshot_archive_info::{
+            FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
+        },
+        snapshot_utils::{self, SnapshotVersion},
+    },
+    mundis_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+        genesis_config::GenesisConfig,
+        hash::Hash,
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+        process::exit,
+        result::Result,
+    },
+    tempfile::TempDir,
+};
+
+type SnapshotArchiveInfos = (
+    FullSnapshotArchiveInfo,
+    Option<IncrementalSnapshotArchiveInfo>,
+);
+
+/// Find the full snapshot archive, and the incremental snapshot archive if `archive_path` is
+/// one, needed to rebuild the bank of `archive_path`.  The full snapshot archive an incremental
+/// snapshot archive is based on must be in the same directory.
+pub(crate) fn snapshot_archive_infos(archive_path: &Path) -> Result<SnapshotArchiveInfos, String> {
+    if let Ok(full_snapshot_archive_info) =
+        FullSnapshotArchiveInfo::new_from_path(archive_path.to_path_buf())
+    {
+        return Ok((full_snapshot_archive_info, None));
+    }
+
+    let incremental_snapshot_archive_info =
+        IncrementalSnapshotArchiveInfo::new_from_path(archive_path.to_path_buf())
+            .map_err(|err| format!("{:?} is not a snapshot archive: {}", archive_path, err))?;
+    let base_slot = incremental_snapshot_archive_info.base_slot();
+    let snapshot_archives_dir = archive_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let full_snapshot_archive_info =
+        snapshot_utils::get_full_snapshot_archives(snapshot_archives_dir)
+            .into_iter()
+            .find(|full_snapshot_archive_info| full_snapshot_archive_info.slot() == base_slot)
+            .ok_or_else(|| {
+                format!(
+                    "No full snapshot archive for base slot {} found in {:?}",
+                    base_slot, snapshot_archives_dir
+                )
+            })?;
+    Ok((
+        full_snapshot_archive_info,
+        Some(incremental_snapshot_archive_info),
+    ))
+}
+
+/// A bank rebuilt from snapshot archives, along with the temporary directory its accounts and
+/// bank snapshot were unpacked into
+struct SnapshotBank {
+    bank: Bank,
+    /// The hash in the name of the snapshot archive the bank was rebuilt from
+    archive_hash: Hash,
+    /// The base slot of the incremental snapshot archive the bank was rebuilt from, if it was
+    base_slot: Option<Slot>,
+    _temp_dir: TempDir,
+}
+
+impl SnapshotBank {
+    /// Calculate the hash of the kind in the snapshot archive name: the accounts hash for a full
+    /// snapshot archive, and the incremental accounts hash for an incremental one
+    fn calculate_archive_hash(&self) -> Hash {
+        match self.base_slot {
+            None => self.bank.update_accounts_hash(),
+            Some(base_slot) => self.bank.update_incremental_accounts_hash(base_slot),
+        }
+    }
+}
+
+fn load_snapshot_bank(
+    genesis_config: &GenesisConfig,
+    archive_path: &Path,
+) -> Result<SnapshotBank, String> {
+    let (full_snapshot_archive_info, incremental_snapshot_archive_info) =
+        snapshot_archive_infos(archive_path)?;
+    let archive_hash = incremental_snapshot_archive_info
+        .as_ref()
+        .map(|info| *info.hash())
+        .unwrap_or_else(|| *full_snapshot_archive_info.hash());
+    let base_slot = incremental_snapshot_archive_info
+        .as_ref()
+        .map(|info| info.base_slot());
+
+    let temp_dir = tempfile::Builder::new()
+        .prefix("ledger-tool-snapshot.")
+        .tempdir()
+        .map_err(|err| format!("Unable to create temporary directory: {}", err))?;
+    let account_paths = vec![temp_dir.path().join("accounts")];
+    let bank_snapshots_dir = temp_dir.path().join("snapshot");
+    std::fs::create_dir_all(&bank_snapshots_dir).map_err(|err| {
+        format!(
+            "Unable to create directory {:?}: {}",
+            bank_snapshots_dir, err
+        )
+    })?;
+
+    let mut measure = Measure::start("load bank");
+    let bank = bank_forks_utils::bank_from_snapshot_archives(
+        genesis_config,
+        &account_paths,
+        &bank_snapshots_dir,
+        &full_snapshot_archive_info,
+        incremental_snapshot_archive_info.as_ref(),
+        &ProcessOptions {
+            accounts_db_skip_shrink: true,
+            ..ProcessOptions::default()
+        },
+    )
+    .map_err(|err| format!("Unable to rebuild bank from {:?}: {}", archive_path, err))?;
+    measure.stop();
+    info!(
+        "Rebuilt bank at slot {} from {:?}. {}",
+        bank.slot(),
+        archive_path,
+        measure
+    );
+
+    Ok(SnapshotBank {
+        bank,
+        archive_hash,
+        base_slot,
+        _temp_dir: temp_dir,
+    })
+}
+
+fn verify(genesis_config: &GenesisConfig, archive_path: &Path) -> Result<bool, String> {
+    let snapshot_bank = load_snapshot_bank(genesis_config, archive_path)?;
+
+    let mut measure = Measure::start("accounts hash");
+    let accounts_hash = snapshot_bank.calculate_archive_hash();
+    measure.stop();
+    info!("{}", measure);
+
+    println!("Slot: {}", snapshot_bank.bank.slot());
+    println!("Archive hash: {}", snapshot_bank.archive_hash);
+    match snapshot_bank.base_slot {
+        None => println!("Accounts hash: {}", accounts_hash),
+        Some(base_slot) => println!(
+            "Incremental accounts hash since slot {}: {}",
+            base_slot, accounts_hash
+        ),
+    }
+    Ok(accounts_hash == snapshot_bank.archive_hash)
+}
+
+fn load_accounts(bank: &Bank) -> Result<BTreeMap<Pubkey, AccountSharedData>, String> {
+    Ok(bank
+        .get_all_accounts_with_modified_slots()
+        .map_err(|err| format!("Unable to scan accounts: {:?}", err))?
+        .into_iter()
+        .map(|(pubkey, account, _slot)| (pubkey, account))
+        .collect())
+}
+
+/// The ways an account can differ between two snapshots
+fn account_differences(a: &AccountSharedData, b: &AccountSharedData) -> Vec<&'static str> {
+    let mut differences = vec![];
+    if a.lamports() != b.lamports() {
+        differences.push("lamports");
+    }
+    if a.owner() != b.owner() {
+        differences.push("owner");
+    }
+    if a.data() != b.data() {
+        differences.push("data");
+    }
+    differences
+}
+
+fn diff(
+    genesis_config: &GenesisConfig,
+    archive_path_a: &Path,
+    archive_path_b: &Path,
+) -> Result<usize, String> {
+    let accounts_a = {
+        let snapshot_bank = load_snapshot_bank(genesis_config, archive_path_a)?;
+        load_accounts(&snapshot_bank.bank)?
+    };
+    let mut accounts_b = {
+        let snapshot_bank = load_snapshot_bank(genesis_config, archive_path_b)?;
+        load_accounts(&snapshot_bank.bank)?
+    };
+
+    let mut num_differences = 0;
+    for (pubkey, account_a) in &accounts_a {
+        match accounts_b.remove(pubkey) {
+            None => {
+                println!("{}: only in A ({} lamports)", pubkey, account_a.lamports());
+                num_differences += 1;
+            }
+            Some(account_b) => {
+                let differences = account_differences(account_a, &account_b);
+                if differences.is_empty() {
+                    continue;
+                }
+                println!("{}: {} differ", pubkey, differences.join(", "));
+                if account_a.lamports() != account_b.lamports() {
+                    println!(
+                        "  lamports: {} -> {}",
+                        account_a.lamports(),
+                        account_b.lamports()
+                    );
+                }
+                if account_a.owner() != account_b.owner() {
+                    println!("  owner: {} -> {}", account_a.owner(), account_b.owner());
+                }
+                if account_a.data() != account_b.data() {
+                    println!(
+                        "  data: {} bytes -> {} bytes",
+                        account_a.data().len(),
+                        account_b.data().len()
+                    );
+                }
+                num_differences += 1;
+            }
+        }
+    }
+    for (pubkey, account_b) in &accounts_b {
+        println!("{}: only in B ({} lamports)", pubkey, account_b.lamports());
+        num_differences += 1;
+    }
+    Ok(num_differences)
+}
+
+/// Rewrite the snapshot archive `archive_path` as a `snapshot_version` snapshot archive of the
+/// same kind and archive format in `output_directory`.  An incremental snapshot archive stays
+/// based on the same full snapshot slot, so the full snapshot archive does not have to be
+/// converted along with it.
+fn convert(
+    genesis_config: &GenesisConfig,
+    archive_path: &Path,
+    snapshot_version: SnapshotVersion,
+    output_directory: &Path,
+) -> Result<PathBuf, String> {
+    let (full_snapshot_archive_info, incremental_snapshot_archive_info) =
+        snapshot_archive_infos(archive_path)?;
+    let snapshot_bank = load_snapshot_bank(genesis_config, archive_path)?;
+    let bank_snapshots_dir = tempfile::Builder::new()
+        .prefix("ledger-tool-snapshot-convert.")
+        .tempdir()
+        .map_err(|err| format!("Unable to create temporary directory: {}", err))?;
+
+    // never purge anything that was already in the output directory
+    let (snapshot_archive_path, snapshot_archive_hash) = match incremental_snapshot_archive_info {
+        None => snapshot_utils::bank_to_full_snapshot_archive(
+            bank_snapshots_dir.path(),
+            &snapshot_bank.bank,
+            Some(snapshot_version),
+            output_directory,
+            full_snapshot_archive_info.archive_format(),
+            usize::MAX,
+            usize::MAX,
+        )
+        .map(|snapshot_archive_info| {
+            (
+                snapshot_archive_info.path().clone(),
+                *snapshot_archive_info.hash(),
+            )
+        }),
+        Some(incremental_snapshot_archive_info) => {
+            snapshot_utils::bank_to_incremental_snapshot_archive(
+                bank_snapshots_dir.path(),
+                &snapshot_bank.bank,
+                incremental_snapshot_archive_info.base_slot(),
+                Some(snapshot_version),
+                output_directory,
+                incremental_snapshot_archive_info.archive_format(),
+                usize::MAX,
+                usize::MAX,
+            )
+            .map(|snapshot_archive_info| {
+                (
+                    snapshot_archive_info.path().clone(),
+                    *snapshot_archive_info.hash(),
+                )
+            })
+        }
+    }
+    .map_err(|err| format!("Unable to create snapshot archive: {}", err))?;
+
+    // creating the snapshot archive recalculated the hash in its name
+    if snapshot_archive_hash != snapshot_bank.archive_hash {
+        return Err(format!(
+            "Accounts hash of {:?} does not match the snapshot archive name",
+            archive_path
+        ));
+    }
+    Ok(snapshot_archive_path)
+}
+
+pub trait SnapshotSubCommand {
+    fn snapshot_subcommand(self) -> Self;
+}
+
+impl SnapshotSubCommand for App<'_, '_> {
+    fn snapshot_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("snapshot")
+                .about("Inspect snapshot archives")
+                .setting(AppSettings::InferSubcommands)
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about(
+                            "Rebuild the bank of a snapshot archive and check that its \
+                             accounts hash matches the hash in the archive name",
+                        )
+                        .arg(
+                            Arg::with_name("archive")
+                                .index(1)
+                                .value_name("ARCHIVE")
+                                .takes_value(true)
+                                .required(true)
+                                .help(
+                                    "Full or incremental snapshot archive. The full snapshot \
+                                     archive of an incremental snapshot archive must be in \
+                                     the same directory",
+                                ),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("diff")
+                        .about(
+                            "List the accounts whose lamports, owner or data differ between \
+                             two snapshot archives",
+                        )
+                        .arg(
+                            Arg::with_name("archive_a")
+                                .index(1)
+                                .value_name("ARCHIVE_A")
+                                .takes_value(true)
+                                .required(true)
+                                .help("First full or incremental snapshot archive"),
+                        )
+                        .arg(
+                            Arg::with_name("archive_b")
+                                .index(2)
+                                .value_name("ARCHIVE_B")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Second full or incremental snapshot archive"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("convert")
+                        .about(
+                            "Rewrite a snapshot archive in another snapshot version, keeping \
+                             its slot, accounts and archive format",
+                        )
+                        .arg(
+                            Arg::with_name("archive")
+                                .index(1)
+                                .value_name("ARCHIVE")
+                                .takes_value(true)
+                                .required(true)
+                                .help(
+                                    "Full or incremental snapshot archive. The full snapshot \
+                                     archive of an incremental snapshot archive must be in \
+                                     the same directory",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("snapshot_version")
+                                .long("snapshot-version")
+                                .value_name("SNAPSHOT_VERSION")
+                                .validator(is_parsable::<SnapshotVersion>)
+                                .takes_value(true)
+                                .required(true)
+                                .help("Output snapshot version"),
+                        )
+                        .arg(
+                            Arg::with_name("output_directory")
+                                .long("output-directory")
+                                .value_name("DIR")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Directory to write the converted snapshot archive to"),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn snapshot_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
+    let genesis_config = open_genesis_config(ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE);
+
+    match matches.subcommand() {
+        ("verify", Some(arg_matches)) => {
+            let archive_path = PathBuf::from(arg_matches.value_of("archive").unwrap());
+            match verify(&genesis_config, &archive_path) {
+                Ok(true) => println!("Snapshot archive verified"),
+                Ok(false) => {
+                    eprintln!("Accounts hash does not match the snapshot archive name");
+                    exit(1);
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            }
+        }
+        ("diff", Some(arg_matches)) => {
+            let archive_path_a = PathBuf::from(arg_matches.value_of("archive_a").unwrap());
+            let archive_path_b = PathBuf::from(arg_matches.value_of("archive_b").unwrap());
+            match diff(&genesis_config, &archive_path_a, &archive_path_b) {
+                Ok(num_differences) => println!("{} accounts differ", num_differences),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            }
+        }
+        ("convert", Some(arg_matches)) => {
+            let archive_path = PathBuf::from(arg_matches.value_of("archive").unwrap());
+            let snapshot_version =
+                value_t_or_exit!(arg_matches, "snapshot_version", SnapshotVersion);
+            let output_directory = PathBuf::from(arg_matches.value_of("output_directory").unwrap());
+            match convert(
+                &genesis_config,
+                &archive_path,
+                snapshot_version,
+                &output_directory,
+            ) {
+                Ok(snapshot_archive_path) => println!(
+                    "Converted snapshot archive to version {}: {}",
+                    snapshot_version,
+                    snapshot_archive_path.display()
+                ),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        mundis_runtime::snapshot_utils::{
+            ArchiveFormat, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        },
+        mundis_sdk::{account::WritableAccount, genesis_config::create_genesis_config},
+        std::sync::Arc,
+    };
+
+    /// Create a full snapshot archive of slot 1, and an incremental snapshot archive of slot 2
+    /// based on it, in `snapshot_archives_dir`
+    fn create_snapshot_archives(snapshot_archives_dir: &Path) -> (GenesisConfig, PathBuf, PathBuf) {
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000);
+        let collector = Pubkey::new_unique();
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        bank0
+            .transfer(1, &mint_keypair, &Pubkey::new_unique())
+            .unwrap();
+        while !bank0.is_complete() {
+            bank0.register_tick(&Hash::new_unique());
+        }
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &collector, 1));
+        bank1
+            .transfer(2, &mint_keypair, &Pubkey::new_unique())
+            .unwrap();
+        while !bank1.is_complete() {
+            bank1.register_tick(&Hash::new_unique());
+        }
+
+        let bank_snapshots_dir = TempDir::new().unwrap();
+        let full_snapshot_archive_info = snapshot_utils::bank_to_full_snapshot_archive(
+            bank_snapshots_dir.path(),
+            &bank1,
+            None,
+            snapshot_archives_dir,
+            ArchiveFormat::TarZstd,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+
+        let bank2 = Arc::new(Bank::new_from_parent(&bank1, &collector, 2));
+        bank2
+            .transfer(3, &mint_keypair, &Pubkey::new_unique())
+            .unwrap();
+        while !bank2.is_complete() {
+            bank2.register_tick(&Hash::new_unique());
+        }
+        let incremental_snapshot_archive_info =
+            snapshot_utils::bank_to_incremental_snapshot_archive(
+                bank_snapshots_dir.path(),
+                &bank2,
+                bank1.slot(),
+                None,
+                snapshot_archives_dir,
+                ArchiveFormat::TarZstd,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+
+        (
+            genesis_config,
+            full_snapshot_archive_info.path().clone(),
+            incremental_snapshot_archive_info.path().clone(),
+        )
+    }
+
+    /// Copy the snapshot archives into `corrupted_dir`, renamed to carry another hash than the
+    /// accounts hash of the snapshot they hold
+    fn corrupt_snapshot_archives(
+        full_snapshot_archive_path: &Path,
+        incremental_snapshot_archive_path: &Path,
+        corrupted_dir: &Path,
+    ) -> (PathBuf, PathBuf) {
+        let full = FullSnapshotArchiveInfo::new_from_path(full_snapshot_archive_path.to_path_buf())
+            .unwrap();
+        let incremental = IncrementalSnapshotArchiveInfo::new_from_path(
+            incremental_snapshot_archive_path.to_path_buf(),
+        )
+        .unwrap();
+        let corrupted_full_snapshot_archive_path = snapshot_utils::build_full_snapshot_archive_path(
+            corrupted_dir.to_path_buf(),
+            full.slot(),
+            &Hash::new_unique(),
+            full.archive_format(),
+        );
+        let corrupted_incremental_snapshot_archive_path =
+            snapshot_utils::build_incremental_snapshot_archive_path(
+                corrupted_dir.to_path_buf(),
+                incremental.base_slot(),
+                incremental.slot(),
+                &Hash::new_unique(),
+                incremental.archive_format(),
+            );
+        std::fs::copy(
+            full_snapshot_archive_path,
+            &corrupted_full_snapshot_archive_path,
+        )
+        .unwrap();
+        std::fs::copy(
+            incremental_snapshot_archive_path,
+            &corrupted_incremental_snapshot_archive_path,
+        )
+        .unwrap();
+        (
+            corrupted_full_snapshot_archive_path,
+            corrupted_incremental_snapshot_archive_path,
+        )
+    }
+
+    #[test]
+    fn test_verify() {
+        mundis_logger::setup();
+        let snapshot_archives_dir = TempDir::new().unwrap();
+        let (genesis_config, full_snapshot_archive_path, incremental_snapshot_archive_path) =
+            create_snapshot_archives(snapshot_archives_dir.path());
+        assert_eq!(
+            verify(&genesis_config, &full_snapshot_archive_path),
+            Ok(true)
+        );
+        assert_eq!(
+            verify(&genesis_config, &incremental_snapshot_archive_path),
+            Ok(true)
+        );
+
+        let corrupted_dir = TempDir::new().unwrap();
+        let (corrupted_full_snapshot_archive_path, corrupted_incremental_snapshot_archive_path) =
+            corrupt_snapshot_archives(
+                &full_snapshot_archive_path,
+                &incremental_snapshot_archive_path,
+                corrupted_dir.path(),
+            );
+        assert!(verify(&genesis_config, &corrupted_full_snapshot_archive_path).is_err());
+        assert!(verify(
+            &genesis_config,
+            &corrupted_incremental_snapshot_archive_path
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        mundis_logger::setup();
+        let snapshot_archives_dir = TempDir::new().unwrap();
+        let (genesis_config, full_snapshot_archive_path, incremental_snapshot_archive_path) =
+            create_snapshot_archives(snapshot_archives_dir.path());
+        assert_eq!(
+            diff(
+                &genesis_config,
+                &full_snapshot_archive_path,
+                &full_snapshot_archive_path
+            ),
+            Ok(0)
+        );
+        // at least the mint account and the account transferred to in slot 2 differ
+        assert!(
+            diff(
+                &genesis_config,
+                &full_snapshot_archive_path,
+                &incremental_snapshot_archive_path
+            )
+            .unwrap()
+                >= 2
+        );
+
+        let corrupted_dir = TempDir::new().unwrap();
+        let (corrupted_full_snapshot_archive_path, corrupted_incremental_snapshot_archive_path) =
+            corrupt_snapshot_archives(
+                &full_snapshot_archive_path,
+                &incremental_snapshot_archive_path,
+                corrupted_dir.path(),
+            );
+        assert!(diff(
+            &genesis_config,
+            &full_snapshot_archive_path,
+            &corrupted_full_snapshot_archive_path
+        )
+        .is_err());
+        assert!(diff(
+            &genesis_config,
+            &corrupted_incremental_snapshot_archive_path,
+            &incremental_snapshot_archive_path
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_account_differences() {
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(42, 4, &owner);
+        assert!(account_differences(&account, &account.clone()).is_empty());
+
+        let mut other = account.clone();
+        other.set_lamports(43);
+        other.set_owner(Pubkey::new_unique());
+        assert_eq!(
+            account_differences(&account, &other),
+            vec!["lamports", "owner"]
+        );
+
+        let other = AccountSharedData::new(42, 5, &owner);
+        assert_eq!(account_differences(&account, &other), vec!["data"]);
+    }
+
+    #[test]
+    fn test_snapshot_archive_infos() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let hash = Hash::new_unique();
+        let full_snapshot_archive_path = snapshot_utils::build_full_snapshot_archive_path(
+            temp_dir.path().to_path_buf(),
+            100,
+            &hash,
+            ArchiveFormat::TarZstd,
+        );
+        let incremental_snapshot_archive_path =
+            snapshot_utils::build_incremental_snapshot_archive_path(
+                temp_dir.path().to_path_buf(),
+                100,
+                120,
+                &hash,
+                ArchiveFormat::TarZstd,
+            );
+        std::fs::File::create(&full_snapshot_archive_path).unwrap();
+
+        let (full, incremental) = snapshot_archive_infos(&full_snapshot_archive_path).unwrap();
+        assert_eq!(full.slot(), 100);
+        assert!(incremental.is_none());
+
+        let (full, incremental) =
+            snapshot_archive_infos(&incremental_snapshot_archive_path).unwrap();
+        assert_eq!(full.path(), &full_snapshot_archive_path);
+        assert_eq!(incremental.unwrap().slot(), 120);
+
+        std::fs::remove_file(&full_snapshot_archive_path).unwrap();
+        assert!(snapshot_archive_infos(&incremental_snapshot_archive_path).is_err());
+        assert!(snapshot_archive_infos(Path::new("not-a-snapshot.tar.zst")).is_err());
+    }
+}
@@ -11,15 +11,22 @@ use {
     mundis_entry::entry::VerifyRecyclers,
     mundis_runtime::{
         accounts_update_notifier_interface::AccountsUpdateNotifier,
+        bank::Bank,
         bank_forks::BankForks,
-        snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_archive_info::{
+            FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
+        },
         snapshot_config::SnapshotConfig,
         snapshot_hash::{FullSnapshotHash, IncrementalSnapshotHash, StartingSnapshotHashes},
         snapshot_package::AccountsPackageSender,
         snapshot_utils,
     },
     mundis_sdk::{clock::Slot, genesis_config::GenesisConfig},
-    std::{fs, path::PathBuf, process, result},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        process, result,
+    },
 };
 
 pub type LoadResult = result::Result<
@@ -146,6 +153,37 @@ fn load_from_genesis(
     )
 }
 
+/// Rebuild the bank of a full snapshot archive, and of an incremental snapshot archive on top of
+/// it if given, without replaying any of the ledger
+pub fn bank_from_snapshot_archives(
+    genesis_config: &GenesisConfig,
+    account_paths: &[PathBuf],
+    bank_snapshots_dir: &Path,
+    full_snapshot_archive_info: &FullSnapshotArchiveInfo,
+    incremental_snapshot_archive_info: Option<&IncrementalSnapshotArchiveInfo>,
+    process_options: &ProcessOptions,
+) -> snapshot_utils::Result<Bank> {
+    snapshot_utils::bank_from_snapshot_archives(
+        account_paths,
+        bank_snapshots_dir,
+        full_snapshot_archive_info,
+        incremental_snapshot_archive_info,
+        genesis_config,
+        process_options.debug_keys.clone(),
+        Some(&crate::builtins::get()),
+        process_options.account_indexes.clone(),
+        process_options.accounts_db_caching_enabled,
+        process_options.limit_load_slot_count_from_snapshot,
+        process_options.shrink_ratio,
+        process_options.accounts_db_test_hash_calculation,
+        process_options.accounts_db_skip_shrink,
+        process_options.verify_index,
+        process_options.accounts_db_config.clone(),
+        None,
+    )
+    .map(|(bank, _timings)| bank)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn load_from_snapshot(
     genesis_config: &GenesisConfig,
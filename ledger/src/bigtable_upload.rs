@@ -3,6 +3,7 @@ use {
     log::*,
     mundis_measure::measure::Measure,
     mundis_sdk::clock::Slot,
+    mundis_storage_bigtable::LedgerStorageAdapter,
     std::{
         collections::HashSet,
         result::Result,
@@ -22,7 +23,7 @@ const BLOCK_READ_AHEAD_DEPTH: usize = NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL * 2;
 
 pub async fn upload_confirmed_blocks(
     blockstore: Arc<Blockstore>,
-    bigtable: mundis_storage_bigtable::LedgerStorage,
+    bigtable: Arc<dyn LedgerStorageAdapter>,
     starting_slot: Slot,
     ending_slot: Option<Slot>,
     force_reupload: bool,
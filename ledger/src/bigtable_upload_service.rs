@@ -1,6 +1,7 @@
 use {
     crate::{bigtable_upload, blockstore::Blockstore},
     mundis_runtime::commitment::BlockCommitmentCache,
+    mundis_storage_bigtable::LedgerStorageAdapter,
     std::{
         cmp::min,
         sync::{
@@ -19,7 +20,7 @@ pub struct BigTableUploadService {
 impl BigTableUploadService {
     pub fn new(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: mundis_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LedgerStorageAdapter>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
@@ -45,7 +46,7 @@ impl BigTableUploadService {
 
     fn run(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: mundis_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LedgerStorageAdapter>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
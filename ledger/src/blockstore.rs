@@ -156,6 +156,7 @@ pub struct Blockstore {
     perf_samples_cf: LedgerColumn<cf::PerfSamples>,
     block_height_cf: LedgerColumn<cf::BlockHeight>,
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
+    builtin_instruction_costs_cf: LedgerColumn<cf::BuiltinInstructionCosts>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     last_root: Arc<RwLock<Slot>>,
     insert_shreds_lock: Arc<Mutex<()>>,
@@ -398,6 +399,7 @@ impl Blockstore {
         let perf_samples_cf = db.column();
         let block_height_cf = db.column();
         let program_costs_cf = db.column();
+        let builtin_instruction_costs_cf = db.column();
         let bank_hash_cf = db.column();
 
         let db = Arc::new(db);
@@ -449,6 +451,7 @@ impl Blockstore {
             perf_samples_cf,
             block_height_cf,
             program_costs_cf,
+            builtin_instruction_costs_cf,
             bank_hash_cf,
             new_shreds_signals: vec![],
             completed_slots_senders: vec![],
@@ -1950,19 +1953,14 @@ impl Blockstore {
         iterator: impl Iterator<Item = VersionedTransaction>,
     ) -> Result<Vec<TransactionWithMetadata>> {
         iterator
-            .map(|versioned_tx| {
-                // TODO: add support for versioned transactions
-                if let Some(transaction) = versioned_tx.into_legacy_transaction() {
-                    let signature = transaction.signatures[0];
-                    Ok(TransactionWithMetadata {
-                        transaction,
-                        meta: self
-                            .read_transaction_status((signature, slot))?
-                            .ok_or(BlockstoreError::MissingTransactionMetadata)?,
-                    })
-                } else {
-                    Err(BlockstoreError::UnsupportedTransactionVersion)
-                }
+            .map(|transaction| {
+                let signature = transaction.signatures[0];
+                Ok(TransactionWithMetadata {
+                    transaction,
+                    meta: self
+                        .read_transaction_status((signature, slot))?
+                        .ok_or(BlockstoreError::MissingTransactionMetadata)?,
+                })
             })
             .collect()
     }
@@ -2252,11 +2250,6 @@ impl Blockstore {
                 .find_transaction_in_slot(slot, signature)?
                 .ok_or(BlockstoreError::TransactionStatusSlotMismatch)?; // Should not happen
 
-            // TODO: support retrieving versioned transactions
-            let transaction = transaction
-                .into_legacy_transaction()
-                .ok_or(BlockstoreError::UnsupportedTransactionVersion)?;
-
             let block_time = self.get_block_time(slot)?;
             Ok(Some(ConfirmedTransaction {
                 slot,
@@ -2690,6 +2683,27 @@ impl Blockstore {
         self.program_costs_cf.delete(*key)
     }
 
+    /// Learned costs of builtin program instructions, by program id and instruction discriminator
+    pub fn read_builtin_instruction_costs(&self) -> Result<Vec<((Pubkey, u32), u64)>> {
+        Ok(self
+            .db
+            .iter::<cf::BuiltinInstructionCosts>(IteratorMode::End)?
+            .map(|(key, data)| {
+                let instruction_cost: ProgramCost = deserialize(&data).unwrap();
+                (key, instruction_cost.cost)
+            })
+            .collect())
+    }
+
+    pub fn write_builtin_instruction_cost(&self, key: &(Pubkey, u32), value: &u64) -> Result<()> {
+        self.builtin_instruction_costs_cf
+            .put(*key, &ProgramCost { cost: *value })
+    }
+
+    pub fn delete_builtin_instruction_cost(&self, key: &(Pubkey, u32)) -> Result<()> {
+        self.builtin_instruction_costs_cf.delete(*key)
+    }
+
     /// Returns the entry vector for the slot starting with `shred_start_index`
     pub fn get_slot_entries(&self, slot: Slot, shred_start_index: u64) -> Result<Vec<Entry>> {
         self.get_slot_entries_with_shred_info(slot, shred_start_index, false)
@@ -3955,10 +3969,11 @@ pub mod tests {
         mundis_sdk::{
             hash::{self, hash, Hash},
             instruction::CompiledInstruction,
+            message::v0::LoadedAddresses,
             packet::PACKET_DATA_SIZE,
             pubkey::Pubkey,
             signature::Signature,
-            transaction::{Transaction, TransactionError},
+            transaction::{Transaction, TransactionError, TransactionReturnData},
         },
         mundis_storage_proto::convert::generated,
         mundis_transaction_status::{InnerInstructions, Reward, Rewards, TransactionTokenBalance},
@@ -6079,6 +6094,9 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6095,6 +6113,9 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6111,6 +6132,9 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6118,7 +6142,7 @@ pub mod tests {
                     .put_protobuf((0, signature, slot + 2), &status)
                     .unwrap();
                 TransactionWithMetadata {
-                    transaction,
+                    transaction: transaction.into(),
                     meta: TransactionStatusMeta {
                         status: Ok(()),
                         fee: 42,
@@ -6129,6 +6153,9 @@ pub mod tests {
                         pre_token_balances: Some(vec![]),
                         post_token_balances: Some(vec![]),
                         rewards: Some(vec![]),
+                        return_data: None,
+                        loaded_addresses: LoadedAddresses::default(),
+                        compute_units_consumed: None,
                     },
                 }
             })
@@ -6237,6 +6264,15 @@ pub mod tests {
         let pre_token_balances_vec = vec![];
         let post_token_balances_vec = vec![];
         let rewards_vec = vec![];
+        let test_return_data = TransactionReturnData {
+            program_id: Pubkey::new_unique(),
+            data: vec![1, 2, 3],
+        };
+        let test_loaded_addresses = LoadedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![Pubkey::new_unique()],
+        };
+        let test_compute_units_consumed = 4321;
 
         // result not found
         assert!(transaction_status_cf
@@ -6255,6 +6291,9 @@ pub mod tests {
             pre_token_balances: Some(pre_token_balances_vec.clone()),
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
+            return_data: Some(test_return_data.clone()),
+            loaded_addresses: test_loaded_addresses.clone(),
+            compute_units_consumed: Some(test_compute_units_consumed),
         }
         .into();
         assert!(transaction_status_cf
@@ -6272,6 +6311,9 @@ pub mod tests {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((0, Signature::default(), 0))
             .unwrap()
@@ -6287,6 +6329,9 @@ pub mod tests {
         assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
+        assert_eq!(return_data.unwrap(), test_return_data);
+        assert_eq!(loaded_addresses, test_loaded_addresses);
+        assert_eq!(compute_units_consumed.unwrap(), test_compute_units_consumed);
 
         // insert value
         let status = TransactionStatusMeta {
@@ -6299,6 +6344,9 @@ pub mod tests {
             pre_token_balances: Some(pre_token_balances_vec.clone()),
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            compute_units_consumed: None,
         }
         .into();
         assert!(transaction_status_cf
@@ -6316,6 +6364,9 @@ pub mod tests {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                 0,
@@ -6337,6 +6388,9 @@ pub mod tests {
         assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
+        assert!(return_data.is_none());
+        assert_eq!(loaded_addresses, LoadedAddresses::default());
+        assert!(compute_units_consumed.is_none());
     }
 
     #[test]
@@ -6564,6 +6618,9 @@ pub mod tests {
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            compute_units_consumed: None,
         }
         .into();
 
@@ -6758,6 +6815,9 @@ pub mod tests {
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            compute_units_consumed: None,
         }
         .into();
 
@@ -6943,6 +7003,9 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6950,7 +7013,7 @@ pub mod tests {
                     .put_protobuf((0, signature, slot), &status)
                     .unwrap();
                 TransactionWithMetadata {
-                    transaction,
+                    transaction: transaction.into(),
                     meta: TransactionStatusMeta {
                         status: Ok(()),
                         fee: 42,
@@ -6961,6 +7024,9 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        return_data: None,
+                        loaded_addresses: LoadedAddresses::default(),
+                        compute_units_consumed: None,
                     },
                 }
             })
@@ -7047,6 +7113,9 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -7054,7 +7123,7 @@ pub mod tests {
                     .put_protobuf((0, signature, slot), &status)
                     .unwrap();
                 TransactionWithMetadata {
-                    transaction,
+                    transaction: transaction.into(),
                     meta: TransactionStatusMeta {
                         status: Ok(()),
                         fee: 42,
@@ -7065,6 +7134,9 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        return_data: None,
+                        loaded_addresses: LoadedAddresses::default(),
+                        compute_units_consumed: None,
                     },
                 }
             })
@@ -7828,6 +7900,9 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: LoadedAddresses::default(),
+                compute_units_consumed: None,
             }
             .into();
             transaction_status_cf
@@ -8383,6 +8458,9 @@ pub mod tests {
                 reward_type: Some(RewardType::Rent),
                 commission: None,
             }]),
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            compute_units_consumed: None,
         };
         let deprecated_status: StoredTransactionStatusMeta = status.clone().into();
         let protobuf_status: generated::TransactionStatusMeta = status.into();
@@ -8919,6 +8997,48 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_read_write_builtin_instruction_cost_table() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let program_id = Pubkey::new_unique();
+        let cost_table: HashMap<(Pubkey, u32), u64> = vec![
+            ((program_id, 0), 100),
+            ((program_id, 1), 200),
+            ((program_id, u32::MAX), 300),
+            ((Pubkey::new_unique(), 1), 400),
+        ]
+        .into_iter()
+        .collect();
+        for (key, cost) in cost_table.iter() {
+            blockstore
+                .write_builtin_instruction_cost(key, cost)
+                .expect("write a builtin instruction");
+        }
+
+        // instructions of the same program are kept apart
+        let read_back = blockstore
+            .read_builtin_instruction_costs()
+            .expect("read builtin instructions");
+        assert_eq!(read_back.len(), cost_table.len());
+        for (read_key, read_cost) in read_back {
+            assert_eq!(read_cost, *cost_table.get(&read_key).unwrap());
+        }
+
+        // builtin instruction costs don't leak into the program costs
+        assert!(blockstore.read_program_costs().unwrap().is_empty());
+
+        blockstore
+            .delete_builtin_instruction_cost(&(program_id, 1))
+            .expect("delete a builtin instruction");
+        let read_back = blockstore
+            .read_builtin_instruction_costs()
+            .expect("read builtin instructions");
+        assert_eq!(read_back.len(), cost_table.len() - 1);
+        assert!(!read_back.iter().any(|(key, _)| *key == (program_id, 1)));
+    }
+
     #[test]
     fn test_delete_old_records_from_cost_table() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -77,6 +77,8 @@ const PERF_SAMPLES_CF: &str = "perf_samples";
 const BLOCK_HEIGHT_CF: &str = "block_height";
 /// Column family for ProgramCosts
 const PROGRAM_COSTS_CF: &str = "program_costs";
+/// Column family for BuiltinInstructionCosts
+const BUILTIN_INSTRUCTION_COSTS_CF: &str = "builtin_instruction_costs";
 
 // 1 day is chosen for the same reasoning of DEFAULT_COMPACTION_SLOT_INTERVAL
 const PERIODIC_COMPACTION_SECONDS: u64 = 60 * 60 * 24;
@@ -101,7 +103,6 @@ pub enum BlockstoreError {
     ProtobufDecodeError(#[from] prost::DecodeError),
     ParentEntriesUnavailable,
     SlotUnavailable,
-    UnsupportedTransactionVersion,
     MissingTransactionMetadata,
 }
 pub type Result<T> = std::result::Result<T, BlockstoreError>;
@@ -194,6 +195,12 @@ pub mod columns {
     #[derive(Debug)]
     // The program costs column
     pub struct ProgramCosts;
+
+    #[derive(Debug)]
+    /// The builtin instruction costs column
+    ///
+    /// index type: (`Pubkey`, `u32`): (program id, instruction discriminator)
+    pub struct BuiltinInstructionCosts;
 }
 
 pub enum AccessType {
@@ -368,6 +375,10 @@ impl Rocks {
             ProgramCosts::NAME,
             get_cf_options::<ProgramCosts>(&access_type, &oldest_slot),
         );
+        let builtin_instruction_costs_cf_descriptor = ColumnFamilyDescriptor::new(
+            BuiltinInstructionCosts::NAME,
+            get_cf_options::<BuiltinInstructionCosts>(&access_type, &oldest_slot),
+        );
         // Don't forget to add to both run_purge_with_stats() and
         // compact_storage() in ledger/src/blockstore/blockstore_purge.rs!!
 
@@ -394,6 +405,10 @@ impl Rocks {
             (PerfSamples::NAME, perf_samples_cf_descriptor),
             (BlockHeight::NAME, block_height_cf_descriptor),
             (ProgramCosts::NAME, program_costs_cf_descriptor),
+            (
+                BuiltinInstructionCosts::NAME,
+                builtin_instruction_costs_cf_descriptor,
+            ),
         ];
         let cf_names: Vec<_> = cfs.iter().map(|c| c.0).collect();
 
@@ -514,6 +529,7 @@ impl Rocks {
             PerfSamples::NAME,
             BlockHeight::NAME,
             ProgramCosts::NAME,
+            BuiltinInstructionCosts::NAME,
         ]
     }
 
@@ -853,6 +869,40 @@ impl Column for columns::ProgramCosts {
     }
 }
 
+impl ColumnName for columns::BuiltinInstructionCosts {
+    const NAME: &'static str = BUILTIN_INSTRUCTION_COSTS_CF;
+}
+impl TypedColumn for columns::BuiltinInstructionCosts {
+    type Type = blockstore_meta::ProgramCost;
+}
+impl Column for columns::BuiltinInstructionCosts {
+    type Index = (Pubkey, u32);
+
+    fn key((pubkey, discriminator): (Pubkey, u32)) -> Vec<u8> {
+        let mut key = vec![0; 32 + 4]; // size_of Pubkey + size_of u32
+        key[0..32].clone_from_slice(&pubkey.as_ref()[0..32]);
+        BigEndian::write_u32(&mut key[32..36], discriminator);
+        key
+    }
+
+    fn index(key: &[u8]) -> Self::Index {
+        (Pubkey::new(&key[0..32]), BigEndian::read_u32(&key[32..36]))
+    }
+
+    fn primary_index(_index: Self::Index) -> u64 {
+        unimplemented!()
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn as_index(_index: u64) -> Self::Index {
+        (Pubkey::default(), 0)
+    }
+}
+
 impl Column for columns::ShredCode {
     type Index = (u64, u64);
 
@@ -1422,6 +1472,7 @@ fn excludes_from_compaction(cf_name: &str) -> bool {
     let no_compaction_cfs: HashSet<&'static str> = vec![
         columns::TransactionStatusIndex::NAME,
         columns::ProgramCosts::NAME,
+        columns::BuiltinInstructionCosts::NAME,
         columns::TransactionMemos::NAME,
     ]
     .into_iter()
@@ -1489,6 +1540,9 @@ pub mod tests {
             columns::TransactionStatusIndex::NAME
         ));
         assert!(excludes_from_compaction(columns::ProgramCosts::NAME));
+        assert!(excludes_from_compaction(
+            columns::BuiltinInstructionCosts::NAME
+        ));
         assert!(excludes_from_compaction(columns::TransactionMemos::NAME));
         assert!(!excludes_from_compaction("something else"));
     }
@@ -38,6 +38,7 @@ use {
         vote_sender_types::ReplayVoteSender,
     },
     mundis_sdk::{
+        bundle::has_conflicting_account_locks,
         clock::{Slot, MAX_PROCESSING_AGE},
         feature_set,
         genesis_config::GenesisConfig,
@@ -160,6 +161,24 @@ fn aggregate_total_execution_units(execute_timings: &ExecuteTimings) -> u64 {
     execute_cost_units
 }
 
+/// Record the write-lock costs of executed transactions in the bank's cost tracker so
+/// that replaying nodes can derive per-account local fee markets as well as leaders.
+fn track_executed_transaction_costs(
+    bank: &Bank,
+    sanitized_txs: &[SanitizedTransaction],
+    execution_results: &[TransactionExecutionResult],
+) {
+    let cost_model = CostModel::new();
+    let mut cost_tracker = bank.write_cost_tracker().unwrap();
+    for (tx, execution_result) in sanitized_txs.iter().zip(execution_results) {
+        if let Some(details) = execution_result.details() {
+            let mut tx_cost = cost_model.calculate_cost(tx);
+            tx_cost.execution_cost = details.executed_units;
+            cost_tracker.add_executed_transaction_cost(&tx_cost);
+        }
+    }
+}
+
 fn execute_batch(
     batch: &TransactionBatch,
     bank: &Arc<Bank>,
@@ -232,6 +251,8 @@ fn execute_batch(
         check_accounts_data_size(&execution_results)?;
     }
 
+    track_executed_transaction_costs(bank, batch.sanitized_transactions(), &execution_results);
+
     if let Some(transaction_status_sender) = transaction_status_sender {
         let transactions = batch.sanitized_transactions().to_vec();
         let post_token_balances = if record_token_balances {
@@ -453,6 +474,39 @@ fn process_entries_with_callback(
                         .send_cost_details(bank.clone(), transactions.iter());
                 }
 
+                // The transactions of a bundle may depend on each other, so an entry that
+                // conflicts with itself is executed one transaction at a time, in order
+                if bank
+                    .feature_set
+                    .is_active(&feature_set::execute_conflicting_entries_in_order::id())
+                    && has_conflicting_account_locks(transactions)
+                {
+                    execute_batches(
+                        bank,
+                        &batches,
+                        entry_callback,
+                        transaction_status_sender,
+                        replay_vote_sender,
+                        timings,
+                        cost_capacity_meter.clone(),
+                    )?;
+                    batches.clear();
+                    for transaction in transactions.chunks(1) {
+                        let batch = bank.prepare_sanitized_batch(transaction);
+                        first_err(batch.lock_results())?;
+                        execute_batches(
+                            bank,
+                            &[batch],
+                            entry_callback,
+                            transaction_status_sender,
+                            replay_vote_sender,
+                            timings,
+                            cost_capacity_meter.clone(),
+                        )?;
+                    }
+                    continue;
+                }
+
                 if randomize {
                     transactions.shuffle(&mut rng);
                 }
@@ -2732,7 +2786,9 @@ pub mod tests {
             mint_keypair,
             ..
         } = create_genesis_config(1000);
-        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        bank.deactivate_feature(&feature_set::execute_conflicting_entries_in_order::id());
+        let bank = Arc::new(bank);
         let keypair1 = Keypair::new();
         let keypair2 = Keypair::new();
         let keypair3 = Keypair::new();
@@ -2822,6 +2878,47 @@ pub mod tests {
         assert_eq!(bank.get_balance(&keypair3.pubkey()), 2);
     }
 
+    #[test]
+    fn test_process_entries_conflict_with_self_in_order() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        assert_matches!(bank.transfer(5, &mint_keypair, &keypair1.pubkey()), Ok(_));
+
+        // keypair2 spends the lamports it receives earlier in the same entry, as in a bundle
+        let entry = next_entry(
+            &bank.last_blockhash(),
+            1,
+            vec![
+                system_transaction::transfer(
+                    &keypair1,
+                    &keypair2.pubkey(),
+                    4,
+                    bank.last_blockhash(),
+                ),
+                system_transaction::transfer(
+                    &keypair2,
+                    &keypair3.pubkey(),
+                    3,
+                    bank.last_blockhash(),
+                ),
+            ],
+        );
+        assert_eq!(
+            process_entries_for_tests(&bank, vec![entry], true, None, None),
+            Ok(())
+        );
+        assert_eq!(bank.get_balance(&keypair1.pubkey()), 1);
+        assert_eq!(bank.get_balance(&keypair2.pubkey()), 1);
+        assert_eq!(bank.get_balance(&keypair3.pubkey()), 3);
+    }
+
     #[test]
     fn test_process_entries_2_entries_par() {
         let GenesisConfigInfo {
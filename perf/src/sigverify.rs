@@ -19,6 +19,7 @@ use {
     mundis_metrics::inc_new_counter_debug,
     mundis_rayon_threadlimit::get_thread_count,
     mundis_sdk::{
+        bundle::{bundle_transaction_from_packet_data, is_bundle_packet_data},
         hash::Hash,
         message::{MESSAGE_HEADER_LENGTH, MESSAGE_VERSION_PREFIX},
         pubkey::Pubkey,
@@ -118,6 +119,11 @@ fn verify_packet(packet: &mut Packet, reject_non_vote: bool) {
         return;
     }
 
+    if is_bundle_packet_data(&packet.data[..packet.meta.size]) {
+        verify_bundle_packet(packet, reject_non_vote);
+        return;
+    }
+
     let packet_offsets = get_packet_offsets(packet, 0, reject_non_vote);
     let mut sig_start = packet_offsets.sig_start as usize;
     let mut pubkey_start = packet_offsets.pubkey_start as usize;
@@ -163,6 +169,17 @@ fn verify_packet(packet: &mut Packet, reject_non_vote: bool) {
     }
 }
 
+// The transaction of a bundle packet follows the bundle header, so it is not laid out like a
+// transaction packet and its signatures are always verified on the CPU
+fn verify_bundle_packet(packet: &mut Packet, reject_non_vote: bool) {
+    packet.meta.flags |= PacketFlags::BUNDLE;
+    let verified = !reject_non_vote
+        && bundle_transaction_from_packet_data(&packet.data[..packet.meta.size])
+            .map(|(_header, transaction)| transaction.verify_and_hash_message().is_ok())
+            .unwrap_or(false);
+    packet.meta.set_discard(!verified);
+}
+
 pub fn count_packets_in_batches(batches: &[PacketBatch]) -> usize {
     batches.iter().map(|batch| batch.packets.len()).sum()
 }
@@ -628,7 +645,7 @@ pub fn get_checked_scalar(scalar: &[u8; 32]) -> Result<[u8; 32], PacketError> {
 pub fn mark_disabled(batches: &mut [PacketBatch], r: &[Vec<u8>]) {
     for (batch, v) in batches.iter_mut().zip(r) {
         for (pkt, f) in batch.packets.iter_mut().zip(v) {
-            if !pkt.meta.discard() {
+            if !pkt.meta.discard() && !pkt.meta.is_bundle() {
                 pkt.meta.set_discard(*f == 0);
             }
         }
@@ -666,6 +683,14 @@ pub fn ed25519_verify(
         return ed25519_verify_cpu(batches, reject_non_vote, valid_packet_count);
     }
 
+    batches
+        .iter_mut()
+        .flat_map(|batch| batch.packets.iter_mut())
+        .filter(|packet| {
+            !packet.meta.discard() && is_bundle_packet_data(&packet.data[..packet.meta.size])
+        })
+        .for_each(|packet| verify_bundle_packet(packet, reject_non_vote));
+
     let (signature_offsets, pubkey_offsets, msg_start_offsets, msg_sizes, sig_lens) =
         generate_offsets(batches, recycler, reject_non_vote);
 
@@ -739,6 +764,7 @@ mod tests {
         },
         bincode::{deserialize, serialize},
         mundis_sdk::{
+            bundle::VersionedBundle,
             instruction::CompiledInstruction,
             message::{Message, MessageHeader},
             signature::{Keypair, Signature},
@@ -1159,6 +1185,31 @@ mod tests {
             .all(|p| p.meta.discard()));
     }
 
+    #[test]
+    fn test_verify_bundle() {
+        let mut bundle = VersionedBundle::new(vec![test_tx().into(), test_multisig_tx().into()]);
+        // A bad signature only discards the packet of its transaction
+        bundle.transactions[1].signatures[0] = Signature::default();
+        let packets: Vec<_> = bundle
+            .to_packets_data()
+            .unwrap()
+            .into_iter()
+            .map(|data| {
+                let mut packet = Packet::default();
+                packet.meta.size = data.len();
+                packet.data[..data.len()].copy_from_slice(&data);
+                packet
+            })
+            .collect();
+
+        let mut batches = vec![PacketBatch::new(packets)];
+        ed25519_verify(&mut batches);
+        let packets = &batches[0].packets;
+        assert!(packets.iter().all(|packet| packet.meta.is_bundle()));
+        assert!(!packets[0].meta.discard());
+        assert!(packets[1].meta.discard());
+    }
+
     #[test]
     fn test_verify_zero() {
         test_verify_n(0, false);
@@ -10,6 +10,8 @@ use mundis_sdk::{
 pub const DEFAULT_UNITS: u32 = 200_000;
 pub const MAX_UNITS: u32 = 1_400_000;
 const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+/// Maximum total data size, in bytes, of the accounts a transaction can load
+pub const MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES: u32 = 64 * 1024 * 1024;
 
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
 impl ::mundis_frozen_abi::abi_example::AbiExample for ComputeBudget {
@@ -62,6 +64,8 @@ pub struct ComputeBudget {
     pub heap_cost: u64,
     /// Memory operation syscall base cost
     pub mem_op_base_cost: u64,
+    /// Maximum total data size, in bytes, of the accounts a transaction can load
+    pub loaded_accounts_data_size_limit: usize,
 }
 
 impl Default for ComputeBudget {
@@ -92,6 +96,7 @@ impl ComputeBudget {
             heap_size: None,
             heap_cost: 8,
             mem_op_base_cost: 10,
+            loaded_accounts_data_size_limit: MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES as usize,
         }
     }
 
@@ -132,6 +137,13 @@ impl ComputeBudget {
                             }
                             self.heap_size = Some(bytes as usize);
                         }
+                        Ok(ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(bytes)) => {
+                            if bytes == 0 {
+                                return Err(error);
+                            }
+                            self.loaded_accounts_data_size_limit =
+                                bytes.min(MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES) as usize;
+                        }
                         _ => return Err(error),
                     }
                 }
@@ -347,6 +359,45 @@ mod tests {
             }
         );
 
+        // LoadedAccountsDataSizeLimit
+        test!(
+            &[
+                ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(1024),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Ok(0),
+            ComputeBudget {
+                max_units: DEFAULT_UNITS as u64,
+                loaded_accounts_data_size_limit: 1024,
+                ..ComputeBudget::default()
+            }
+        );
+        test!(
+            &[
+                ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(
+                    MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES + 1
+                ),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Ok(0),
+            ComputeBudget {
+                max_units: DEFAULT_UNITS as u64,
+                loaded_accounts_data_size_limit: MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES as usize,
+                ..ComputeBudget::default()
+            }
+        );
+        test!(
+            &[
+                ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(0),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData,
+            )),
+            ComputeBudget::default()
+        );
+
         // Combined
         test!(
             &[
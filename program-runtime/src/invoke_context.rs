@@ -26,6 +26,7 @@ use {
         pubkey::Pubkey,
         rent::Rent,
         saturating_add_assign,
+        wasm_loader_upgradeable::{self, UpgradeableLoaderState},
     },
     std::{borrow::Cow, cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc, sync::Arc},
 };
@@ -676,6 +677,32 @@ impl<'a> InvokeContext<'a> {
             return Err(InstructionError::AccountNotExecutable);
         }
         let mut program_indices = vec![];
+        if wasm_loader_upgradeable::check_id(program_account.borrow().owner()) {
+            if let Ok(UpgradeableLoaderState::Program {
+                programdata_address,
+            }) = UpgradeableLoaderState::deserialize(program_account.borrow().data())
+            {
+                if let Some((programdata_account_index, _)) =
+                    self.get_account(&programdata_address)
+                {
+                    program_indices.push(programdata_account_index);
+                } else {
+                    ic_msg!(
+                        self,
+                        "Unknown upgradeable programdata account {}",
+                        programdata_address,
+                    );
+                    return Err(InstructionError::MissingAccount);
+                }
+            } else {
+                ic_msg!(
+                    self,
+                    "Invalid upgradeable program account {}",
+                    callee_program_id,
+                );
+                return Err(InstructionError::MissingAccount);
+            }
+        }
         program_indices.push(program_account_index);
 
         Ok((message, caller_write_privileges, program_indices))
@@ -920,6 +947,25 @@ impl<'a> InvokeContext<'a> {
         &self.instruction_trace
     }
 
+    /// Get the `index`th most recent instruction processed before the current one at the same
+    /// stack height, counting from zero
+    pub fn get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        let stack_height = self.get_stack_height();
+        if stack_height == TRANSACTION_LEVEL_STACK_HEIGHT {
+            let instruction_index = self
+                .instruction_trace
+                .len()
+                .checked_sub(2)?
+                .checked_sub(index)?;
+            self.instruction_trace.get(instruction_index)?.get(0)
+        } else {
+            // Cross-program invocations are recorded at the stack height of their caller
+            self.instruction_trace
+                .last()?
+                .find(stack_height.saturating_sub(1), index)
+        }
+    }
+
     /// Get the mutable instruction trace
     pub fn get_instruction_trace_mut(&mut self) -> &mut Vec<InstructionRecorder> {
         &mut self.instruction_trace
@@ -2,12 +2,14 @@
 use mundis_sdk::sysvar::fees::Fees;
 use {
     mundis_sdk::{
+        clock::Epoch,
         instruction::InstructionError,
+        pubkey::Pubkey,
         sysvar::{
             clock::Clock, epoch_schedule::EpochSchedule, rent::Rent, slot_hashes::SlotHashes,
         },
     },
-    std::sync::Arc,
+    std::{collections::HashMap, sync::Arc},
 };
 
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
@@ -18,6 +20,22 @@ impl ::mundis_frozen_abi::abi_example::AbiExample for SysvarCache {
     }
 }
 
+/// Stake delegated to each vote account, as used for leader selection in
+/// `epoch`. It is not a sysvar account, but builtins that weigh votes by stake
+/// read it through the cache.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct EpochVoteStakes {
+    pub epoch: Epoch,
+    pub total_stake: u64,
+    pub vote_stakes: HashMap<Pubkey, u64>,
+}
+
+impl EpochVoteStakes {
+    pub fn vote_account_stake(&self, vote_account: &Pubkey) -> u64 {
+        self.vote_stakes.get(vote_account).copied().unwrap_or(0)
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct SysvarCache {
     clock: Option<Arc<Clock>>,
@@ -26,6 +44,8 @@ pub struct SysvarCache {
     fees: Option<Arc<Fees>>,
     rent: Option<Arc<Rent>>,
     slot_hashes: Option<Arc<SlotHashes>>,
+    epoch_vote_stakes: Option<Arc<EpochVoteStakes>>,
+    previous_epoch_vote_stakes: Vec<Arc<EpochVoteStakes>>,
 }
 
 impl SysvarCache {
@@ -78,4 +98,32 @@ impl SysvarCache {
     pub fn set_slot_hashes(&mut self, slot_hashes: SlotHashes) {
         self.slot_hashes = Some(Arc::new(slot_hashes));
     }
+
+    pub fn get_epoch_vote_stakes(&self) -> Result<Arc<EpochVoteStakes>, InstructionError> {
+        self.epoch_vote_stakes
+            .clone()
+            .ok_or(InstructionError::UnsupportedSysvar)
+    }
+
+    pub fn set_epoch_vote_stakes(&mut self, epoch_vote_stakes: EpochVoteStakes) {
+        self.epoch_vote_stakes = Some(Arc::new(epoch_vote_stakes));
+    }
+
+    /// Returns the stakes of `epoch`, either the current epoch or one of the
+    /// few before it whose stakes the bank still holds
+    pub fn get_epoch_vote_stakes_at(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Arc<EpochVoteStakes>, InstructionError> {
+        self.epoch_vote_stakes
+            .iter()
+            .chain(self.previous_epoch_vote_stakes.iter())
+            .find(|epoch_vote_stakes| epoch_vote_stakes.epoch == epoch)
+            .cloned()
+            .ok_or(InstructionError::UnsupportedSysvar)
+    }
+
+    pub fn set_previous_epoch_vote_stakes(&mut self, epoch_vote_stakes: Vec<EpochVoteStakes>) {
+        self.previous_epoch_vote_stakes = epoch_vote_stakes.into_iter().map(Arc::new).collect();
+    }
 }
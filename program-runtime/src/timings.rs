@@ -32,6 +32,22 @@ impl ProgramTiming {
             .extend(other.errored_txs_compute_consumed.clone());
         saturating_add_assign!(self.total_errored_units, other.total_errored_units);
     }
+
+    fn accumulate_execution(&mut self, us: u64, compute_units_consumed: u64, is_error: bool) {
+        self.accumulated_us = self.accumulated_us.saturating_add(us);
+        if is_error {
+            self.errored_txs_compute_consumed
+                .push(compute_units_consumed);
+            self.total_errored_units = self
+                .total_errored_units
+                .saturating_add(compute_units_consumed);
+        } else {
+            self.accumulated_units = self
+                .accumulated_units
+                .saturating_add(compute_units_consumed);
+            self.count = self.count.saturating_add(1);
+        };
+    }
 }
 
 #[derive(Default, Debug)]
@@ -128,6 +144,8 @@ pub struct ExecuteDetailsTimings {
     pub create_executor_verify_code_us: u64,
     pub create_executor_jit_compile_us: u64,
     pub per_program_timings: HashMap<Pubkey, ProgramTiming>,
+    /// Timings of builtin program instructions, by program id and instruction discriminator
+    pub per_builtin_instruction_timings: HashMap<(Pubkey, u32), ProgramTiming>,
 }
 
 impl ExecuteDetailsTimings {
@@ -164,6 +182,13 @@ impl ExecuteDetailsTimings {
             let program_timing = self.per_program_timings.entry(*id).or_default();
             program_timing.accumulate_program_timings(other);
         }
+        for (key, other) in &other.per_builtin_instruction_timings {
+            let instruction_timing = self
+                .per_builtin_instruction_timings
+                .entry(*key)
+                .or_default();
+            instruction_timing.accumulate_program_timings(other);
+        }
     }
 
     pub fn accumulate_program(
@@ -173,21 +198,26 @@ impl ExecuteDetailsTimings {
         compute_units_consumed: u64,
         is_error: bool,
     ) {
-        let program_timing = self.per_program_timings.entry(*program_id).or_default();
-        program_timing.accumulated_us = program_timing.accumulated_us.saturating_add(us);
-        if is_error {
-            program_timing
-                .errored_txs_compute_consumed
-                .push(compute_units_consumed);
-            program_timing.total_errored_units = program_timing
-                .total_errored_units
-                .saturating_add(compute_units_consumed);
-        } else {
-            program_timing.accumulated_units = program_timing
-                .accumulated_units
-                .saturating_add(compute_units_consumed);
-            program_timing.count = program_timing.count.saturating_add(1);
-        };
+        self.per_program_timings
+            .entry(*program_id)
+            .or_default()
+            .accumulate_execution(us, compute_units_consumed, is_error);
+    }
+
+    /// Like `accumulate_program`, for a builtin program instruction identified by its
+    /// discriminator
+    pub fn accumulate_builtin_instruction(
+        &mut self,
+        program_id: &Pubkey,
+        discriminator: u32,
+        us: u64,
+        compute_units_consumed: u64,
+        is_error: bool,
+    ) {
+        self.per_builtin_instruction_timings
+            .entry((*program_id, discriminator))
+            .or_default()
+            .accumulate_execution(us, compute_units_consumed, is_error);
     }
 }
 
@@ -238,6 +268,35 @@ mod tests {
         execute_details_timings
     }
 
+    #[test]
+    fn test_execute_details_timing_accumulate_builtin_instruction() {
+        let program_id = Pubkey::new_unique();
+        let mut execute_details_timings = ExecuteDetailsTimings::default();
+        execute_details_timings.accumulate_builtin_instruction(&program_id, 3, 100, 10, false);
+        execute_details_timings.accumulate_builtin_instruction(&program_id, 3, 100, 30, false);
+        execute_details_timings.accumulate_builtin_instruction(&program_id, 4, 100, 20, true);
+
+        let instruction_timings = execute_details_timings
+            .per_builtin_instruction_timings
+            .get(&(program_id, 3))
+            .unwrap();
+        assert_eq!(instruction_timings.accumulated_us, 200);
+        assert_eq!(instruction_timings.accumulated_units, 40);
+        assert_eq!(instruction_timings.count, 2);
+
+        let instruction_timings = execute_details_timings
+            .per_builtin_instruction_timings
+            .get(&(program_id, 4))
+            .unwrap();
+        assert_eq!(instruction_timings.count, 0);
+        assert_eq!(instruction_timings.errored_txs_compute_consumed, vec![20]);
+
+        // Builtin instruction timings are accumulated alongside per-program timings
+        let mut accumulated_timings = ExecuteDetailsTimings::default();
+        accumulated_timings.accumulate(&execute_details_timings);
+        assert_eq!(accumulated_timings, execute_details_timings);
+    }
+
     #[test]
     fn test_execute_details_timing_acumulate_program() {
         // Acumulate an erroring transaction
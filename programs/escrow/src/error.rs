@@ -0,0 +1,64 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use thiserror::Error;
+
+use mundis_sdk::decode_error::{DecodeError, PrintInstructionError};
+use mundis_sdk::instruction::InstructionError;
+
+/// Errors that may be returned by the Escrow program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum EscrowError {
+    // 0
+    /// Amounts must be greater than zero.
+    #[error("Invalid amount")]
+    InvalidAmount,
+    /// The expiry slot has already passed.
+    #[error("Invalid expiry slot")]
+    InvalidExpiry,
+    /// The offer has expired and can only be cancelled.
+    #[error("Offer has expired")]
+    Expired,
+    /// The vault holds fewer tokens than requested.
+    #[error("Insufficient tokens left in the offer")]
+    InsufficientOffer,
+    /// An account does not match the one recorded in the offer.
+    #[error("Account does not match the offer")]
+    AccountMismatch,
+
+    // 5
+    /// A token account does not hold the expected mint.
+    #[error("Invalid mint")]
+    InvalidMint,
+    /// Only the maker can cancel an offer before it expires.
+    #[error("Offer has not expired")]
+    NotExpired,
+    /// A program derived address does not match the expected derivation.
+    #[error("Invalid program address")]
+    InvalidProgramAddress,
+    /// Operation overflowed
+    #[error("Operation overflowed")]
+    Overflow,
+}
+
+impl From<EscrowError> for InstructionError {
+    fn from(e: EscrowError) -> Self {
+        InstructionError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for EscrowError {
+    fn type_of() -> &'static str {
+        "EscrowError"
+    }
+}
+
+impl PrintInstructionError for EscrowError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintInstructionError + FromPrimitive,
+    {
+        eprintln!("{}", self);
+    }
+}
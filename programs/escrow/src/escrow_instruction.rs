@@ -0,0 +1,165 @@
+//! Instruction types
+
+use {
+    crate::{find_authority_address, find_vault_address, id},
+    mundis_sdk::{
+        clock::Slot,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Instructions supported by the escrow program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum EscrowInstruction {
+    /// Creates an offer and moves the offered tokens into its vault.
+    ///
+    /// Unfilled tokens are returned to the source account when the offer is
+    /// cancelled.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer of the offer's and the vault's rent.
+    ///   1. `[writable, signer]` New offer account.
+    ///   2. `[writable]` Vault, derived from the offer.
+    ///   3. `[]` Vault authority, derived from the offer.
+    ///   4. `[signer]` Maker, owner of the source account.
+    ///   5. `[writable]` Maker's token account holding the offered tokens.
+    ///   6. `[]` Mint of the offered tokens.
+    ///   7. `[]` Maker's token account receiving the wanted tokens.
+    ///   8. `[]` System program.
+    ///   9. `[]` Token program.
+    CreateOffer {
+        /// Tokens locked in the vault
+        offer_amount: u64,
+        /// Tokens of the receive account's mint wanted for all of them
+        want_amount: u64,
+        /// First slot the offer can no longer be taken
+        expiry_slot: Option<Slot>,
+    },
+
+    /// Buys offered tokens at the offer's price. The taker pays the maker and
+    /// receives from the vault atomically. Once the vault is empty the offer
+    /// is closed and its rent returned to the maker.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Offer.
+    ///   1. `[writable]` Vault.
+    ///   2. `[]` Vault authority.
+    ///   3. `[signer]` Taker, owner of the payment account.
+    ///   4. `[writable]` Taker's token account paying the wanted tokens.
+    ///   5. `[writable]` Taker's token account receiving the offered tokens.
+    ///   6. `[writable]` Maker's token account receiving the wanted tokens.
+    ///   7. `[writable]` Maker.
+    ///   8. `[]` Token program.
+    Take {
+        /// Offered tokens to buy
+        amount: u64,
+    },
+
+    /// Returns the unfilled tokens to the maker and closes the offer. The
+    /// maker can cancel at any time, anyone can once the offer has expired.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Offer.
+    ///   1. `[writable]` Vault.
+    ///   2. `[]` Vault authority.
+    ///   3. `[writable, signer]` Maker, need not sign once the offer expired.
+    ///   4. `[writable]` Maker's token account receiving the unfilled tokens.
+    ///   5. `[]` Token program.
+    Cancel,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_offer(
+    payer: &Pubkey,
+    offer: &Pubkey,
+    maker: &Pubkey,
+    source: &Pubkey,
+    offer_mint: &Pubkey,
+    receive_account: &Pubkey,
+    offer_amount: u64,
+    want_amount: u64,
+    expiry_slot: Option<Slot>,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &EscrowInstruction::CreateOffer {
+            offer_amount,
+            want_amount,
+            expiry_slot,
+        },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*offer, true),
+            AccountMeta::new(find_vault_address(offer).0, false),
+            AccountMeta::new_readonly(find_authority_address(offer).0, false),
+            AccountMeta::new_readonly(*maker, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*offer_mint, false),
+            AccountMeta::new_readonly(*receive_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(mundis_token_program::id(), false),
+        ],
+    )
+}
+
+pub fn take(
+    offer: &Pubkey,
+    taker: &Pubkey,
+    payment_account: &Pubkey,
+    receive_account: &Pubkey,
+    maker_receive_account: &Pubkey,
+    maker: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &EscrowInstruction::Take { amount },
+        vec![
+            AccountMeta::new(*offer, false),
+            AccountMeta::new(find_vault_address(offer).0, false),
+            AccountMeta::new_readonly(find_authority_address(offer).0, false),
+            AccountMeta::new_readonly(*taker, true),
+            AccountMeta::new(*payment_account, false),
+            AccountMeta::new(*receive_account, false),
+            AccountMeta::new(*maker_receive_account, false),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new_readonly(mundis_token_program::id(), false),
+        ],
+    )
+}
+
+/// Cancels an offer with the maker's signature
+pub fn cancel(offer: &Pubkey, maker: &Pubkey, refund_account: &Pubkey) -> Instruction {
+    cancel_instruction(offer, maker, refund_account, true)
+}
+
+/// Closes an expired offer, anyone can send it
+pub fn close_expired(offer: &Pubkey, maker: &Pubkey, refund_account: &Pubkey) -> Instruction {
+    cancel_instruction(offer, maker, refund_account, false)
+}
+
+fn cancel_instruction(
+    offer: &Pubkey,
+    maker: &Pubkey,
+    refund_account: &Pubkey,
+    maker_signs: bool,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &EscrowInstruction::Cancel,
+        vec![
+            AccountMeta::new(*offer, false),
+            AccountMeta::new(find_vault_address(offer).0, false),
+            AccountMeta::new_readonly(find_authority_address(offer).0, false),
+            AccountMeta::new(*maker, maker_signs),
+            AccountMeta::new(*refund_account, false),
+            AccountMeta::new_readonly(mundis_token_program::id(), false),
+        ],
+    )
+}
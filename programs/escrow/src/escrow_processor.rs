@@ -0,0 +1,407 @@
+use {
+    crate::{
+        error::EscrowError,
+        escrow_instruction::EscrowInstruction,
+        find_authority_address, find_vault_address, price_of,
+        state::{EscrowState, Offer},
+    },
+    mundis_program_runtime::{ic_msg, invoke_context::InvokeContext},
+    mundis_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        account_utils::State,
+        clock::Slot,
+        decode_error::PrintInstructionError,
+        instruction::InstructionError,
+        keyed_account::{keyed_account_at_index, next_keyed_account, KeyedAccount},
+        program_pack::Pack,
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+    mundis_token_program::{state::TokenAccount, token_instruction},
+};
+
+pub fn process_instruction(
+    first_instruction_account: usize,
+    data: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    if let Err(error) = Processor::process(first_instruction_account, data, invoke_context) {
+        // catch the error so we can print it
+        error.print::<EscrowError>();
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn get_offer(offer_info: &KeyedAccount) -> Result<Offer, InstructionError> {
+    if offer_info.owner()? != crate::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    EscrowState::deserialize(offer_info.try_account_ref()?.data())?
+        .offer()
+        .ok_or(InstructionError::UninitializedAccount)
+}
+
+fn get_token_account(token_account_info: &KeyedAccount) -> Result<TokenAccount, InstructionError> {
+    if token_account_info.owner()? != mundis_token_program::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    TokenAccount::unpack(token_account_info.try_account_ref()?.data())
+}
+
+fn check_address(
+    invoke_context: &InvokeContext,
+    keyed_account: &KeyedAccount,
+    expected: &Pubkey,
+) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != expected {
+        ic_msg!(
+            invoke_context,
+            "Expected account {}, got {}",
+            expected,
+            keyed_account.unsigned_key()
+        );
+        return Err(EscrowError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+fn check_program(
+    keyed_account: &KeyedAccount,
+    program_id: &Pubkey,
+) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != program_id {
+        return Err(InstructionError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+fn check_offer_account(
+    keyed_account: &KeyedAccount,
+    expected: &Pubkey,
+) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != expected {
+        return Err(EscrowError::AccountMismatch.into());
+    }
+    Ok(())
+}
+
+pub struct Processor {}
+
+impl Processor {
+    fn process(
+        first_instruction_account: usize,
+        data: &[u8],
+        invoke_context: &mut InvokeContext,
+    ) -> Result<(), InstructionError> {
+        match limited_deserialize(data)? {
+            EscrowInstruction::CreateOffer {
+                offer_amount,
+                want_amount,
+                expiry_slot,
+            } => {
+                ic_msg!(invoke_context, "Instruction: CreateOffer");
+                Self::process_create_offer(
+                    invoke_context,
+                    first_instruction_account,
+                    offer_amount,
+                    want_amount,
+                    expiry_slot,
+                )
+            }
+            EscrowInstruction::Take { amount } => {
+                ic_msg!(invoke_context, "Instruction: Take");
+                Self::process_take(invoke_context, first_instruction_account, amount)
+            }
+            EscrowInstruction::Cancel => {
+                ic_msg!(invoke_context, "Instruction: Cancel");
+                Self::process_cancel(invoke_context, first_instruction_account)
+            }
+        }
+    }
+
+    fn process_create_offer(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        offer_amount: u64,
+        want_amount: u64,
+        expiry_slot: Option<Slot>,
+    ) -> Result<(), InstructionError> {
+        if offer_amount == 0 || want_amount == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        if matches!(expiry_slot, Some(expiry_slot) if expiry_slot <= clock.slot) {
+            return Err(EscrowError::InvalidExpiry.into());
+        }
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let payer_info = next_keyed_account(accounts_iter)?;
+        let offer_info = next_keyed_account(accounts_iter)?;
+        let vault_info = next_keyed_account(accounts_iter)?;
+        let authority_info = next_keyed_account(accounts_iter)?;
+        let maker_info = next_keyed_account(accounts_iter)?;
+        let source_info = next_keyed_account(accounts_iter)?;
+        let offer_mint_info = next_keyed_account(accounts_iter)?;
+        let receive_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let payer = *payer_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let offer = *offer_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let maker = *maker_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let vault = find_vault_address(&offer).0;
+        check_address(invoke_context, vault_info, &vault)?;
+        let authority = find_authority_address(&offer).0;
+        check_address(invoke_context, authority_info, &authority)?;
+        let source = *source_info.unsigned_key();
+        let offer_mint = *offer_mint_info.unsigned_key();
+        if get_token_account(source_info)?.mint != offer_mint {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        let want_mint = get_token_account(receive_info)?.mint;
+        if want_mint == offer_mint {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        let maker_receive_account = *receive_info.unsigned_key();
+
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let offer_size = EscrowState::offer_size_of();
+        invoke_context.native_invoke(
+            system_instruction::create_account(
+                &payer,
+                &offer,
+                rent.minimum_balance(offer_size),
+                offer_size as u64,
+                &crate::id(),
+            ),
+            &[payer, offer],
+        )?;
+        invoke_context.native_invoke(
+            system_instruction::create_account(
+                &payer,
+                &vault,
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &mundis_token_program::id(),
+            ),
+            &[payer, vault],
+        )?;
+        invoke_context.native_invoke(
+            token_instruction::initialize_account(
+                &mundis_token_program::id(),
+                &vault,
+                &offer_mint,
+                &authority,
+            )?,
+            &[],
+        )?;
+        invoke_context.native_invoke(
+            token_instruction::transfer(
+                &mundis_token_program::id(),
+                &source,
+                &vault,
+                &maker,
+                &[],
+                offer_amount,
+            )?,
+            &[],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let offer_info = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        offer_info.set_state(&EscrowState::Offer(Offer {
+            maker,
+            offer_mint,
+            want_mint,
+            maker_receive_account,
+            maker_refund_account: source,
+            offer_amount,
+            want_amount,
+            remaining_amount: offer_amount,
+            expiry_slot,
+        }))
+    }
+
+    fn process_take(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        amount: u64,
+    ) -> Result<(), InstructionError> {
+        if amount == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let offer_info = next_keyed_account(accounts_iter)?;
+        let vault_info = next_keyed_account(accounts_iter)?;
+        let authority_info = next_keyed_account(accounts_iter)?;
+        let taker_info = next_keyed_account(accounts_iter)?;
+        let payment_info = next_keyed_account(accounts_iter)?;
+        let receive_info = next_keyed_account(accounts_iter)?;
+        let maker_receive_info = next_keyed_account(accounts_iter)?;
+        let maker_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let offer_key = *offer_info.unsigned_key();
+        let vault = find_vault_address(&offer_key).0;
+        check_address(invoke_context, vault_info, &vault)?;
+        let authority = find_authority_address(&offer_key).0;
+        check_address(invoke_context, authority_info, &authority)?;
+        let taker = *taker_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let mut offer = get_offer(offer_info)?;
+        check_offer_account(maker_receive_info, &offer.maker_receive_account)?;
+        check_offer_account(maker_info, &offer.maker)?;
+
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        if offer.is_expired(clock.slot) {
+            return Err(EscrowError::Expired.into());
+        }
+        if amount > offer.remaining_amount {
+            ic_msg!(
+                invoke_context,
+                "Offer has {} tokens left",
+                offer.remaining_amount
+            );
+            return Err(EscrowError::InsufficientOffer.into());
+        }
+        let price =
+            price_of(amount, offer.offer_amount, offer.want_amount).ok_or(EscrowError::Overflow)?;
+        offer.remaining_amount -= amount;
+        let filled = offer.remaining_amount == 0;
+        offer_info.set_state(&EscrowState::Offer(offer.clone()))?;
+        let payment = *payment_info.unsigned_key();
+        let receive = *receive_info.unsigned_key();
+
+        invoke_context.native_invoke(
+            token_instruction::transfer(
+                &mundis_token_program::id(),
+                &payment,
+                &offer.maker_receive_account,
+                &taker,
+                &[],
+                price,
+            )?,
+            &[],
+        )?;
+        invoke_context.native_invoke(
+            token_instruction::transfer(
+                &mundis_token_program::id(),
+                &vault,
+                &receive,
+                &authority,
+                &[],
+                amount,
+            )?,
+            &[authority],
+        )?;
+        if filled {
+            Self::close_offer(
+                invoke_context,
+                first_instruction_account,
+                &vault,
+                &authority,
+                &offer.maker,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn process_cancel(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let offer_info = next_keyed_account(accounts_iter)?;
+        let vault_info = next_keyed_account(accounts_iter)?;
+        let authority_info = next_keyed_account(accounts_iter)?;
+        let maker_info = next_keyed_account(accounts_iter)?;
+        let refund_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let offer_key = *offer_info.unsigned_key();
+        let vault = find_vault_address(&offer_key).0;
+        check_address(invoke_context, vault_info, &vault)?;
+        let authority = find_authority_address(&offer_key).0;
+        check_address(invoke_context, authority_info, &authority)?;
+        let offer = get_offer(offer_info)?;
+        check_offer_account(maker_info, &offer.maker)?;
+        check_offer_account(refund_info, &offer.maker_refund_account)?;
+        if maker_info.signer_key().is_none() {
+            let clock = invoke_context.get_sysvar_cache().get_clock()?;
+            if !offer.is_expired(clock.slot) {
+                return Err(EscrowError::NotExpired.into());
+            }
+        }
+
+        if offer.remaining_amount > 0 {
+            invoke_context.native_invoke(
+                token_instruction::transfer(
+                    &mundis_token_program::id(),
+                    &vault,
+                    &offer.maker_refund_account,
+                    &authority,
+                    &[],
+                    offer.remaining_amount,
+                )?,
+                &[authority],
+            )?;
+        }
+        Self::close_offer(
+            invoke_context,
+            first_instruction_account,
+            &vault,
+            &authority,
+            &offer.maker,
+        )
+    }
+
+    /// Closes the empty vault and the offer, returning their rent to the maker
+    fn close_offer(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        vault: &Pubkey,
+        authority: &Pubkey,
+        maker: &Pubkey,
+    ) -> Result<(), InstructionError> {
+        invoke_context.native_invoke(
+            token_instruction::close_account(
+                &mundis_token_program::id(),
+                vault,
+                maker,
+                authority,
+                &[],
+            )?,
+            &[*authority],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let offer_info = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let maker_info = keyed_accounts[first_instruction_account..]
+            .iter()
+            .find(|keyed_account| keyed_account.unsigned_key() == maker)
+            .ok_or(InstructionError::NotEnoughAccountKeys)?;
+        let offer_lamports = offer_info.lamports()?;
+        offer_info.set_state(&EscrowState::Uninitialized)?;
+        offer_info.try_account_ref_mut()?.set_lamports(0);
+        maker_info
+            .try_account_ref_mut()?
+            .checked_add_lamports(offer_lamports)?;
+        Ok(())
+    }
+}
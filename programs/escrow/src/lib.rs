@@ -0,0 +1,65 @@
+#![allow(incomplete_features)]
+#![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(specialization))]
+#![cfg_attr(RUSTC_NEEDS_PROC_MACRO_HYGIENE, feature(proc_macro_hygiene))]
+
+//! Trust-minimized token trades. A maker locks tokens in a vault owned by the
+//! program and names the price in another token. Takers fill the offer, in
+//! whole or in part, by paying the maker and receiving from the vault in the
+//! same instruction.
+
+use mundis_sdk::pubkey::Pubkey;
+
+pub mod error;
+pub mod escrow_instruction;
+pub mod escrow_processor;
+pub mod state;
+
+pub use mundis_sdk::escrow::program::{check_id, id};
+
+/// Seed for the authority that owns an offer's vault
+const AUTHORITY: &[u8] = b"authority";
+/// Seed for the token account holding the offered tokens
+const VAULT: &[u8] = b"vault";
+
+/// Derives the authority that owns the vault of an offer
+pub fn find_authority_address(offer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[offer.as_ref(), AUTHORITY], &id())
+}
+
+/// Derives the token account holding the offered tokens
+pub fn find_vault_address(offer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[offer.as_ref(), VAULT], &id())
+}
+
+/// Returns the wanted tokens a taker pays for `amount` offered tokens, at the
+/// price of `want_amount` for `offer_amount`. Rounds up, in the maker's favor.
+pub fn price_of(amount: u64, offer_amount: u64, want_amount: u64) -> Option<u64> {
+    if offer_amount == 0 {
+        return None;
+    }
+    let numerator = (amount as u128).checked_mul(want_amount as u128)?;
+    let price = numerator
+        .checked_add(offer_amount as u128 - 1)?
+        .checked_div(offer_amount as u128)?;
+    u64::try_from(price).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_of() {
+        assert_eq!(price_of(100, 100, 50), Some(50));
+        assert_eq!(price_of(10, 100, 50), Some(5));
+        assert_eq!(price_of(1, 100, 50), Some(1));
+        assert_eq!(price_of(3, 2, 1), Some(2));
+        assert_eq!(price_of(0, 100, 50), Some(0));
+        assert_eq!(price_of(1, 0, 50), None);
+        assert_eq!(price_of(u64::MAX, 1, u64::MAX), None);
+        assert_ne!(
+            find_vault_address(&Pubkey::default()),
+            find_authority_address(&Pubkey::default())
+        );
+    }
+}
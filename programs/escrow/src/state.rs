@@ -0,0 +1,96 @@
+//! State transition types
+
+use {
+    mundis_sdk::{clock::Slot, instruction::InstructionError, pubkey::Pubkey},
+    serde_derive::{Deserialize, Serialize},
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct Offer {
+    /// Creator of the offer, receives the rent back once it is closed
+    pub maker: Pubkey,
+    /// Mint of the tokens locked in the vault
+    pub offer_mint: Pubkey,
+    /// Mint of the tokens the maker wants in exchange
+    pub want_mint: Pubkey,
+    /// Maker's token account credited with the wanted tokens
+    pub maker_receive_account: Pubkey,
+    /// Maker's token account that gets unfilled tokens back
+    pub maker_refund_account: Pubkey,
+    /// Offered tokens at creation, the price basis
+    pub offer_amount: u64,
+    /// Wanted tokens for the whole offer
+    pub want_amount: u64,
+    /// Offered tokens still in the vault
+    pub remaining_amount: u64,
+    /// First slot the offer can no longer be taken, `None` if it never expires
+    pub expiry_slot: Option<Slot>,
+}
+
+impl Offer {
+    pub fn is_expired(&self, slot: Slot) -> bool {
+        matches!(self.expiry_slot, Some(expiry_slot) if slot >= expiry_slot)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum EscrowState {
+    Uninitialized,
+    Offer(Offer),
+}
+
+impl Default for EscrowState {
+    fn default() -> Self {
+        EscrowState::Uninitialized
+    }
+}
+
+impl EscrowState {
+    pub fn offer_size_of() -> usize {
+        let offer = Offer {
+            expiry_slot: Some(0),
+            ..Offer::default()
+        };
+        bincode::serialized_size(&EscrowState::Offer(offer)).unwrap() as usize
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, InstructionError> {
+        bincode::deserialize(data).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    pub fn offer(self) -> Option<Offer> {
+        match self {
+            EscrowState::Offer(offer) => Some(offer),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offer_size_of() {
+        let offer = EscrowState::Offer(Offer {
+            maker: Pubkey::new_unique(),
+            offer_amount: 100,
+            want_amount: 50,
+            remaining_amount: 100,
+            expiry_slot: Some(42),
+            ..Offer::default()
+        });
+        let mut data = vec![0; EscrowState::offer_size_of()];
+        bincode::serialize_into(&mut data[..], &offer).unwrap();
+        assert_eq!(EscrowState::deserialize(&data).unwrap(), offer);
+        assert_eq!(
+            EscrowState::deserialize(&[0; 4]).unwrap(),
+            EscrowState::Uninitialized
+        );
+
+        let offer = offer.offer().unwrap();
+        assert!(!offer.is_expired(41));
+        assert!(offer.is_expired(42));
+        assert!(!Offer::default().is_expired(u64::MAX));
+    }
+}
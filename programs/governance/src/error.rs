@@ -0,0 +1,72 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use thiserror::Error;
+
+use mundis_sdk::decode_error::{DecodeError, PrintInstructionError};
+use mundis_sdk::instruction::InstructionError;
+
+/// Errors that may be returned by the Governance program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum GovernanceError {
+    // 0
+    /// The description is longer than allowed.
+    #[error("Description too long")]
+    DescriptionTooLong,
+    /// The proposal has no actions, too many, or an action that cannot be executed.
+    #[error("Invalid proposal action")]
+    InvalidAction,
+    /// The voting period is zero or longer than allowed.
+    #[error("Invalid voting period")]
+    InvalidVotingPeriod,
+    /// The vote account has no stake in the epoch the proposal was created in.
+    #[error("Vote account has no stake")]
+    NoStake,
+    /// The authorized withdrawer of the vote account did not sign.
+    #[error("Wrong vote account withdrawer")]
+    WrongWithdrawer,
+
+    // 5
+    /// The proposal is not accepting votes.
+    #[error("Proposal is not in voting")]
+    NotVoting,
+    /// The proposal has not passed, or its actions were already executed.
+    #[error("Proposal has not succeeded")]
+    NotSucceeded,
+    /// The vote account already voted on the proposal.
+    #[error("Already voted")]
+    AlreadyVoted,
+    /// A program derived address does not match the expected derivation.
+    #[error("Invalid program address")]
+    InvalidProgramAddress,
+    /// The feature account is not escrowed with the program.
+    #[error("Invalid feature account")]
+    InvalidFeature,
+
+    // 10
+    /// Operation overflowed
+    #[error("Operation overflowed")]
+    Overflow,
+}
+
+impl From<GovernanceError> for InstructionError {
+    fn from(e: GovernanceError) -> Self {
+        InstructionError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for GovernanceError {
+    fn type_of() -> &'static str {
+        "GovernanceError"
+    }
+}
+
+impl PrintInstructionError for GovernanceError {
+    fn print<E>(&self)
+        where
+            E: 'static + std::error::Error + DecodeError<E> + PrintInstructionError + FromPrimitive,
+    {
+        eprintln!("{}", self);
+    }
+}
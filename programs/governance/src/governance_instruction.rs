@@ -0,0 +1,168 @@
+//! Instruction types
+
+use {
+    crate::{
+        find_authority_address, find_vote_record_address, id,
+        state::{ProposalAction, VoteChoice},
+    },
+    mundis_sdk::{
+        clock::Epoch,
+        feature::Feature,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Instructions supported by the governance program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum GovernanceInstruction {
+    /// Creates a proposal. Only vote accounts with stake in the current epoch
+    /// can propose.
+    ///
+    /// Instructions invoked by the proposal can only require the signature of
+    /// the governance authority, and cannot target the governance program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer of the proposal's rent.
+    ///   1. `[writable, signer]` New proposal account.
+    ///   2. `[]` Vote account of the proposer.
+    ///   3. `[signer]` Authorized withdrawer of the vote account.
+    ///   4. `[]` System program.
+    CreateProposal {
+        /// Free-form description, usually a title and a link
+        description: String,
+        /// Actions executed once the proposal passes
+        actions: Vec<ProposalAction>,
+        /// Number of epochs the proposal accepts votes
+        voting_epochs: Epoch,
+    },
+
+    /// Votes on a proposal with the stake delegated to a vote account in the
+    /// epoch the proposal was created in, so stake moved to another vote
+    /// account afterwards does not vote twice. A vote account votes once per
+    /// proposal. The proposal
+    /// passes as soon as the yes votes reach the approval threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Proposal.
+    ///   1. `[writable]` Vote record, derived from the proposal and the vote account.
+    ///   2. `[]` Vote account.
+    ///   3. `[signer]` Authorized withdrawer of the vote account.
+    ///   4. `[writable, signer]` Payer of the vote record's rent.
+    ///   5. `[]` System program.
+    CastVote {
+        choice: VoteChoice,
+    },
+
+    /// Settles a proposal whose voting period ended, or that can no longer
+    /// pass because of the stake that voted no. Anyone can finalize.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Proposal.
+    FinalizeProposal,
+
+    /// Executes the actions of a passed proposal. Anyone can execute.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Proposal.
+    ///   1. `[writable]` Governance authority.
+    ///   2. .. `[]` Programs and accounts used by the actions, escrowed
+    ///      feature accounts writable.
+    ExecuteProposal,
+}
+
+pub fn create_proposal(
+    payer: &Pubkey,
+    proposal: &Pubkey,
+    vote_account: &Pubkey,
+    authorized_withdrawer: &Pubkey,
+    description: &str,
+    actions: Vec<ProposalAction>,
+    voting_epochs: Epoch,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &GovernanceInstruction::CreateProposal {
+            description: description.to_string(),
+            actions,
+            voting_epochs,
+        },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*proposal, true),
+            AccountMeta::new_readonly(*vote_account, false),
+            AccountMeta::new_readonly(*authorized_withdrawer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn cast_vote(
+    proposal: &Pubkey,
+    vote_account: &Pubkey,
+    authorized_withdrawer: &Pubkey,
+    payer: &Pubkey,
+    choice: VoteChoice,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &GovernanceInstruction::CastVote { choice },
+        vec![
+            AccountMeta::new(*proposal, false),
+            AccountMeta::new(find_vote_record_address(proposal, vote_account).0, false),
+            AccountMeta::new_readonly(*vote_account, false),
+            AccountMeta::new_readonly(*authorized_withdrawer, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn finalize_proposal(proposal: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &GovernanceInstruction::FinalizeProposal,
+        vec![AccountMeta::new(*proposal, false)],
+    )
+}
+
+/// Executes `proposal`, passing along the accounts its `actions` need
+pub fn execute_proposal(proposal: &Pubkey, actions: &[ProposalAction]) -> Instruction {
+    let authority = find_authority_address().0;
+    let mut accounts = vec![
+        AccountMeta::new(*proposal, false),
+        AccountMeta::new(authority, false),
+    ];
+    for action in actions {
+        match action {
+            ProposalAction::Invoke(instruction) => {
+                accounts.push(AccountMeta::new_readonly(instruction.program_id, false));
+                accounts.extend(instruction.accounts.iter().map(|meta| AccountMeta {
+                    is_signer: false,
+                    ..meta.clone()
+                }));
+            }
+            ProposalAction::ActivateFeature(feature_id) => {
+                accounts.push(AccountMeta::new(*feature_id, false));
+            }
+        }
+    }
+    Instruction::new_with_bincode(id(), &GovernanceInstruction::ExecuteProposal, accounts)
+}
+
+/// Hands a feature over to governance. The feature account is funded and
+/// allocated, but owned by the governance program instead of the feature
+/// program until a proposal activating it passes.
+pub fn escrow_feature(funding: &Pubkey, feature_id: &Pubkey, lamports: u64) -> Vec<Instruction> {
+    vec![
+        system_instruction::transfer(funding, feature_id, lamports),
+        system_instruction::allocate(feature_id, Feature::size_of() as u64),
+        system_instruction::assign(feature_id, &id()),
+    ]
+}
@@ -0,0 +1,510 @@
+use {
+    crate::{
+        error::GovernanceError,
+        find_authority_address, find_vote_record_address,
+        governance_instruction::GovernanceInstruction,
+        state::{GovernanceState, Proposal, ProposalAction, ProposalStatus, VoteChoice, VoteRecord},
+        MAX_ACTIONS, MAX_DESCRIPTION_LENGTH, MAX_VOTING_EPOCHS,
+    },
+    mundis_program_runtime::{ic_msg, invoke_context::InvokeContext},
+    mundis_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        account_utils::State,
+        clock::Epoch,
+        decode_error::PrintInstructionError,
+        feature::{self, Feature},
+        instruction::InstructionError,
+        keyed_account::{keyed_account_at_index, next_keyed_account, KeyedAccount},
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+    mundis_vote_program::vote_state::VoteState,
+};
+
+pub fn process_instruction(
+    first_instruction_account: usize,
+    data: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    if let Err(error) = Processor::process(first_instruction_account, data, invoke_context) {
+        // catch the error so we can print it
+        error.print::<GovernanceError>();
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn get_proposal(proposal_info: &KeyedAccount) -> Result<Proposal, InstructionError> {
+    if proposal_info.owner()? != crate::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    GovernanceState::deserialize(proposal_info.try_account_ref()?.data())?
+        .proposal()
+        .ok_or(InstructionError::InvalidAccountData)
+}
+
+fn check_address(
+    invoke_context: &InvokeContext,
+    keyed_account: &KeyedAccount,
+    expected: &Pubkey,
+) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != expected {
+        ic_msg!(
+            invoke_context,
+            "Expected account {}, got {}",
+            expected,
+            keyed_account.unsigned_key()
+        );
+        return Err(GovernanceError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+fn check_program(keyed_account: &KeyedAccount, program_id: &Pubkey) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != program_id {
+        return Err(InstructionError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Checks that the vote account's authorized withdrawer signed
+fn check_withdrawer(
+    vote_account_info: &KeyedAccount,
+    withdrawer_info: &KeyedAccount,
+) -> Result<(), InstructionError> {
+    if vote_account_info.owner()? != mundis_vote_program::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    let vote_state = VoteState::deserialize(vote_account_info.try_account_ref()?.data())?;
+    if withdrawer_info.signer_key() != Some(&vote_state.authorized_withdrawer) {
+        return Err(GovernanceError::WrongWithdrawer.into());
+    }
+    Ok(())
+}
+
+/// Returns the stake of `vote_account` and the total stake in `epoch`
+fn get_stake(
+    invoke_context: &InvokeContext,
+    epoch: Epoch,
+    vote_account: &Pubkey,
+) -> Result<(u64, u64), InstructionError> {
+    let epoch_vote_stakes = invoke_context
+        .get_sysvar_cache()
+        .get_epoch_vote_stakes_at(epoch)?;
+    let stake = epoch_vote_stakes.vote_account_stake(vote_account);
+    if stake == 0 {
+        ic_msg!(
+            invoke_context,
+            "{} has no stake in epoch {}",
+            vote_account,
+            epoch_vote_stakes.epoch
+        );
+        return Err(GovernanceError::NoStake.into());
+    }
+    Ok((stake, epoch_vote_stakes.total_stake))
+}
+
+fn check_action(
+    invoke_context: &InvokeContext,
+    action: &ProposalAction,
+    authority: &Pubkey,
+) -> Result<(), InstructionError> {
+    if let ProposalAction::Invoke(instruction) = action {
+        if crate::check_id(&instruction.program_id) {
+            ic_msg!(invoke_context, "Proposals cannot invoke the governance program");
+            return Err(GovernanceError::InvalidAction.into());
+        }
+        if let Some(meta) = instruction
+            .accounts
+            .iter()
+            .find(|meta| meta.is_signer && &meta.pubkey != authority)
+        {
+            ic_msg!(invoke_context, "{} cannot sign for a proposal", meta.pubkey);
+            return Err(GovernanceError::InvalidAction.into());
+        }
+    }
+    Ok(())
+}
+
+pub struct Processor {}
+
+impl Processor {
+    fn process(
+        first_instruction_account: usize,
+        data: &[u8],
+        invoke_context: &mut InvokeContext,
+    ) -> Result<(), InstructionError> {
+        match limited_deserialize(data)? {
+            GovernanceInstruction::CreateProposal {
+                description,
+                actions,
+                voting_epochs,
+            } => {
+                ic_msg!(invoke_context, "Instruction: CreateProposal");
+                Self::process_create_proposal(
+                    invoke_context,
+                    first_instruction_account,
+                    description,
+                    actions,
+                    voting_epochs,
+                )
+            }
+            GovernanceInstruction::CastVote { choice } => {
+                ic_msg!(invoke_context, "Instruction: CastVote");
+                Self::process_cast_vote(invoke_context, first_instruction_account, choice)
+            }
+            GovernanceInstruction::FinalizeProposal => {
+                ic_msg!(invoke_context, "Instruction: FinalizeProposal");
+                Self::process_finalize_proposal(invoke_context, first_instruction_account)
+            }
+            GovernanceInstruction::ExecuteProposal => {
+                ic_msg!(invoke_context, "Instruction: ExecuteProposal");
+                Self::process_execute_proposal(invoke_context, first_instruction_account)
+            }
+        }
+    }
+
+    fn process_create_proposal(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        description: String,
+        actions: Vec<ProposalAction>,
+        voting_epochs: Epoch,
+    ) -> Result<(), InstructionError> {
+        if description.len() > MAX_DESCRIPTION_LENGTH {
+            return Err(GovernanceError::DescriptionTooLong.into());
+        }
+        if actions.is_empty() || actions.len() > MAX_ACTIONS {
+            return Err(GovernanceError::InvalidAction.into());
+        }
+        if voting_epochs == 0 || voting_epochs > MAX_VOTING_EPOCHS {
+            return Err(GovernanceError::InvalidVotingPeriod.into());
+        }
+        let authority = find_authority_address().0;
+        for action in &actions {
+            check_action(invoke_context, action, &authority)?;
+        }
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let payer_info = next_keyed_account(accounts_iter)?;
+        let proposal_info = next_keyed_account(accounts_iter)?;
+        let vote_account_info = next_keyed_account(accounts_iter)?;
+        let withdrawer_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        let payer = *payer_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let proposal = *proposal_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        check_withdrawer(vote_account_info, withdrawer_info)?;
+        let proposer = *vote_account_info.unsigned_key();
+        let epoch = invoke_context.get_sysvar_cache().get_clock()?.epoch;
+        let (_stake, total_stake) = get_stake(invoke_context, epoch, &proposer)?;
+        let voting_end_epoch = epoch
+            .checked_add(voting_epochs)
+            .ok_or(GovernanceError::Overflow)?;
+
+        let size = GovernanceState::proposal_size_of(&description, &actions);
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        invoke_context.native_invoke(
+            system_instruction::create_account(
+                &payer,
+                &proposal,
+                rent.minimum_balance(size),
+                size as u64,
+                &crate::id(),
+            ),
+            &[payer, proposal],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let proposal_info = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        proposal_info.set_state(&GovernanceState::Proposal(Proposal {
+            proposer,
+            description,
+            actions,
+            stake_epoch: epoch,
+            total_stake,
+            voting_end_epoch,
+            yes_stake: 0,
+            no_stake: 0,
+            status: ProposalStatus::Voting,
+        }))
+    }
+
+    fn process_cast_vote(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        choice: VoteChoice,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let proposal_info = next_keyed_account(accounts_iter)?;
+        let vote_record_info = next_keyed_account(accounts_iter)?;
+        let vote_account_info = next_keyed_account(accounts_iter)?;
+        let withdrawer_info = next_keyed_account(accounts_iter)?;
+        let payer_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        let payer = *payer_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let proposal = *proposal_info.unsigned_key();
+        let vote_account = *vote_account_info.unsigned_key();
+        let vote_record = find_vote_record_address(&proposal, &vote_account).0;
+        check_address(invoke_context, vote_record_info, &vote_record)?;
+        check_withdrawer(vote_account_info, withdrawer_info)?;
+        if vote_record_info.lamports()? > 0 || vote_record_info.owner()? == crate::id() {
+            return Err(GovernanceError::AlreadyVoted.into());
+        }
+
+        let mut state = get_proposal(proposal_info)?;
+        let epoch = invoke_context.get_sysvar_cache().get_clock()?.epoch;
+        if state.status != ProposalStatus::Voting || epoch >= state.voting_end_epoch {
+            return Err(GovernanceError::NotVoting.into());
+        }
+        let (stake, _total_stake) = get_stake(invoke_context, state.stake_epoch, &vote_account)?;
+        match choice {
+            VoteChoice::Yes => {
+                state.yes_stake = state
+                    .yes_stake
+                    .checked_add(stake)
+                    .ok_or(GovernanceError::Overflow)?;
+            }
+            VoteChoice::No => {
+                state.no_stake = state
+                    .no_stake
+                    .checked_add(stake)
+                    .ok_or(GovernanceError::Overflow)?;
+            }
+        }
+        state.status = state.tally(epoch);
+        proposal_info.set_state(&GovernanceState::Proposal(state))?;
+
+        let size = GovernanceState::vote_record_size_of();
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        invoke_context.native_invoke(
+            system_instruction::create_account(
+                &payer,
+                &vote_record,
+                rent.minimum_balance(size),
+                size as u64,
+                &crate::id(),
+            ),
+            &[payer, vote_record],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let vote_record_info =
+            keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        vote_record_info.set_state(&GovernanceState::VoteRecord(VoteRecord {
+            proposal,
+            vote_account,
+            choice,
+            stake,
+        }))
+    }
+
+    fn process_finalize_proposal(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let proposal_info = next_keyed_account(accounts_iter)?;
+
+        let mut state = get_proposal(proposal_info)?;
+        if state.status != ProposalStatus::Voting {
+            return Err(GovernanceError::NotVoting.into());
+        }
+        let epoch = invoke_context.get_sysvar_cache().get_clock()?.epoch;
+        state.status = state.tally(epoch);
+        ic_msg!(invoke_context, "Proposal status: {:?}", state.status);
+        proposal_info.set_state(&GovernanceState::Proposal(state))
+    }
+
+    fn process_execute_proposal(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let proposal_info = next_keyed_account(accounts_iter)?;
+        let authority_info = next_keyed_account(accounts_iter)?;
+
+        let authority = find_authority_address().0;
+        check_address(invoke_context, authority_info, &authority)?;
+        let mut state = get_proposal(proposal_info)?;
+        if state.status != ProposalStatus::Succeeded {
+            return Err(GovernanceError::NotSucceeded.into());
+        }
+        state.status = ProposalStatus::Executed;
+        proposal_info.set_state(&GovernanceState::Proposal(state.clone()))?;
+
+        for action in state.actions {
+            match action {
+                ProposalAction::Invoke(instruction) => {
+                    invoke_context.native_invoke(instruction, &[authority])?;
+                }
+                ProposalAction::ActivateFeature(feature_id) => {
+                    Self::activate_feature(invoke_context, first_instruction_account, &feature_id)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands an escrowed feature account to the feature program. Its data is
+    /// zeroed, which the runtime reads as a pending activation.
+    fn activate_feature(
+        invoke_context: &InvokeContext,
+        first_instruction_account: usize,
+        feature_id: &Pubkey,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let feature_info = keyed_accounts[first_instruction_account..]
+            .iter()
+            .find(|keyed_account| keyed_account.unsigned_key() == feature_id)
+            .ok_or(InstructionError::NotEnoughAccountKeys)?;
+        if feature_info.owner()? != crate::id()
+            || feature_info.data_len()? != Feature::size_of()
+            || feature_info
+                .try_account_ref()?
+                .data()
+                .iter()
+                .any(|byte| *byte != 0)
+        {
+            ic_msg!(invoke_context, "Feature {} is not escrowed", feature_id);
+            return Err(GovernanceError::InvalidFeature.into());
+        }
+        feature_info
+            .try_account_ref_mut()?
+            .set_owner(feature::id());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::governance_instruction::cast_vote,
+        mundis_program_runtime::{
+            invoke_context::mock_process_instruction_with_sysvars,
+            sysvar_cache::{EpochVoteStakes, SysvarCache},
+        },
+        mundis_sdk::{account::AccountSharedData, clock::Clock, instruction::Instruction},
+        mundis_vote_program::vote_state,
+        std::{cell::RefCell, collections::HashMap, rc::Rc},
+    };
+
+    fn epoch_vote_stakes(epoch: Epoch, vote_stakes: &[(Pubkey, u64)]) -> EpochVoteStakes {
+        EpochVoteStakes {
+            epoch,
+            total_stake: vote_stakes.iter().map(|(_, stake)| stake).sum(),
+            vote_stakes: vote_stakes.iter().copied().collect(),
+        }
+    }
+
+    fn process(
+        instruction: &Instruction,
+        mut accounts: HashMap<Pubkey, AccountSharedData>,
+        sysvar_cache: &SysvarCache,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let account = accounts.remove(&meta.pubkey).unwrap_or_default();
+                (
+                    meta.is_signer,
+                    meta.is_writable,
+                    meta.pubkey,
+                    Rc::new(RefCell::new(account)),
+                )
+            })
+            .collect();
+        mock_process_instruction_with_sysvars(
+            &crate::id(),
+            Vec::new(),
+            &instruction.data,
+            &keyed_accounts,
+            sysvar_cache,
+            process_instruction,
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_cast_vote_with_redelegated_stake() {
+        let proposal = Pubkey::new_unique();
+        let first_voter = Pubkey::new_unique();
+        let second_voter = Pubkey::new_unique();
+        let other_voter = Pubkey::new_unique();
+        let withdrawer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        // The proposal was created in epoch 1, where the first vote account
+        // held 60 of the 100 stake and voted yes with it
+        let state = Proposal {
+            proposer: first_voter,
+            stake_epoch: 1,
+            total_stake: 100,
+            voting_end_epoch: 3,
+            yes_stake: 60,
+            ..Proposal::default()
+        };
+        let proposal_account =
+            AccountSharedData::new_data(1, &GovernanceState::Proposal(state), &crate::id())
+                .unwrap();
+
+        // In epoch 2 the same stake is delegated to the second vote account
+        let mut sysvar_cache = SysvarCache::default();
+        sysvar_cache.set_clock(Clock {
+            epoch: 2,
+            ..Clock::default()
+        });
+        sysvar_cache.set_epoch_vote_stakes(epoch_vote_stakes(
+            2,
+            &[(second_voter, 60), (other_voter, 40)],
+        ));
+        sysvar_cache.set_previous_epoch_vote_stakes(vec![epoch_vote_stakes(
+            1,
+            &[(first_voter, 60), (other_voter, 40)],
+        )]);
+
+        let accounts = HashMap::from([
+            (proposal, proposal_account),
+            (
+                second_voter,
+                vote_state::create_account_with_authorized(
+                    &Pubkey::new_unique(),
+                    &second_voter,
+                    &withdrawer,
+                    0,
+                    1,
+                ),
+            ),
+        ]);
+        assert_eq!(
+            process(
+                &cast_vote(
+                    &proposal,
+                    &second_voter,
+                    &withdrawer,
+                    &payer,
+                    VoteChoice::Yes
+                ),
+                accounts,
+                &sysvar_cache,
+            ),
+            Err(GovernanceError::NoStake.into())
+        );
+    }
+}
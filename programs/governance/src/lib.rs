@@ -0,0 +1,74 @@
+#![allow(incomplete_features)]
+#![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(specialization))]
+#![cfg_attr(RUSTC_NEEDS_PROC_MACRO_HYGIENE, feature(proc_macro_hygiene))]
+
+//! On-chain governance for Architects. Vote account owners create proposals
+//! and vote on them with the stake delegated to their vote accounts. Passed
+//! proposals execute their stored instructions, signed by the governance
+//! authority, and may activate runtime features escrowed with the program.
+
+use mundis_sdk::{clock::Epoch, pubkey::Pubkey};
+
+pub mod error;
+pub mod governance_instruction;
+pub mod governance_processor;
+pub mod state;
+
+pub use mundis_sdk::governance::program::{check_id, id};
+
+/// Seed for the address that signs the instructions of passed proposals
+const AUTHORITY: &[u8] = b"authority";
+/// Seed for the account recording a vote account's vote on a proposal
+const VOTE_RECORD: &[u8] = b"vote";
+
+/// Longest proposal description
+pub const MAX_DESCRIPTION_LENGTH: usize = 512;
+/// Most actions a single proposal can carry
+pub const MAX_ACTIONS: usize = 16;
+/// Longest voting period a proposal can ask for. Votes are weighed by the
+/// stakes of the epoch the proposal was created in, and the bank only holds
+/// the stakes of a few epochs back.
+pub const MAX_VOTING_EPOCHS: Epoch = 4;
+
+/// Share of the total stake that must vote yes for a proposal to pass
+pub const APPROVAL_THRESHOLD_NUMERATOR: u64 = 2;
+pub const APPROVAL_THRESHOLD_DENOMINATOR: u64 = 3;
+
+/// Finds the address that signs for passed proposals. It can hold lamports
+/// and act as the authority of other programs' accounts.
+pub fn find_authority_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY], &id())
+}
+
+/// Finds the address of the record of `vote_account`'s vote on `proposal`
+pub fn find_vote_record_address(proposal: &Pubkey, vote_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&proposal.to_bytes(), &vote_account.to_bytes(), VOTE_RECORD],
+        &id(),
+    )
+}
+
+/// Returns true if `stake` meets the approval threshold of `total_stake`
+pub fn meets_threshold(stake: u64, total_stake: u64) -> bool {
+    total_stake > 0
+        && stake as u128 * APPROVAL_THRESHOLD_DENOMINATOR as u128
+            >= total_stake as u128 * APPROVAL_THRESHOLD_NUMERATOR as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meets_threshold() {
+        assert!(meets_threshold(2, 3));
+        assert!(meets_threshold(67, 100));
+        assert!(!meets_threshold(66, 100));
+        assert!(!meets_threshold(0, 0));
+        assert!(meets_threshold(u64::MAX, u64::MAX));
+        assert_ne!(
+            find_vote_record_address(&Pubkey::new_unique(), &Pubkey::new_unique()),
+            find_vote_record_address(&Pubkey::new_unique(), &Pubkey::new_unique())
+        );
+    }
+}
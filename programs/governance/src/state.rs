@@ -0,0 +1,222 @@
+//! State transition types
+
+use {
+    crate::meets_threshold,
+    mundis_sdk::{
+        clock::Epoch,
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// What a proposal does once it passes
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum ProposalAction {
+    /// Invokes an instruction, with the governance authority as a signer
+    Invoke(Instruction),
+    /// Activates a feature whose account was escrowed with the program
+    ActivateFeature(Pubkey),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProposalStatus {
+    /// Accepting votes
+    Voting,
+    /// Passed, the actions can be executed
+    Succeeded,
+    /// Rejected, or not enough stake voted yes in time
+    Defeated,
+    /// Passed and the actions were executed
+    Executed,
+}
+
+impl Default for ProposalStatus {
+    fn default() -> Self {
+        ProposalStatus::Voting
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Proposal {
+    /// Vote account of the proposer
+    pub proposer: Pubkey,
+    /// Free-form description, usually a title and a link to the discussion
+    pub description: String,
+    /// Actions executed in order once the proposal passes
+    pub actions: Vec<ProposalAction>,
+    /// Epoch the proposal was created in. Every vote is weighed by the stakes
+    /// of this epoch, wherever it is cast.
+    pub stake_epoch: Epoch,
+    /// Total stake of `stake_epoch`
+    pub total_stake: u64,
+    /// First epoch the proposal no longer accepts votes
+    pub voting_end_epoch: Epoch,
+    /// Stake that voted for the proposal
+    pub yes_stake: u64,
+    /// Stake that voted against the proposal
+    pub no_stake: u64,
+    pub status: ProposalStatus,
+}
+
+impl Proposal {
+    /// Returns the status the proposal moves to in `epoch`. A proposal passes
+    /// as soon as enough of the stake of `stake_epoch` votes yes, and fails as
+    /// soon as enough votes no that it can no longer pass.
+    pub fn tally(&self, epoch: Epoch) -> ProposalStatus {
+        if self.status != ProposalStatus::Voting {
+            return self.status;
+        }
+        if meets_threshold(self.yes_stake, self.total_stake) {
+            ProposalStatus::Succeeded
+        } else if epoch >= self.voting_end_epoch
+            || !meets_threshold(
+                self.total_stake.saturating_sub(self.no_stake),
+                self.total_stake,
+            )
+        {
+            ProposalStatus::Defeated
+        } else {
+            ProposalStatus::Voting
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VoteChoice {
+    Yes,
+    No,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub vote_account: Pubkey,
+    pub choice: VoteChoice,
+    /// Stake of the vote account in the proposal's stake epoch
+    pub stake: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum GovernanceState {
+    Uninitialized,
+    Proposal(Proposal),
+    VoteRecord(VoteRecord),
+}
+
+impl Default for GovernanceState {
+    fn default() -> Self {
+        GovernanceState::Uninitialized
+    }
+}
+
+impl GovernanceState {
+    /// Size of the account holding a proposal with `description` and `actions`
+    pub fn proposal_size_of(description: &str, actions: &[ProposalAction]) -> usize {
+        let proposal = Proposal {
+            description: description.to_string(),
+            actions: actions.to_vec(),
+            ..Proposal::default()
+        };
+        bincode::serialized_size(&GovernanceState::Proposal(proposal)).unwrap() as usize
+    }
+
+    pub fn vote_record_size_of() -> usize {
+        bincode::serialized_size(&GovernanceState::VoteRecord(VoteRecord {
+            proposal: Pubkey::default(),
+            vote_account: Pubkey::default(),
+            choice: VoteChoice::Yes,
+            stake: 0,
+        }))
+        .unwrap() as usize
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, InstructionError> {
+        bincode::deserialize(data).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    pub fn proposal(self) -> Option<Proposal> {
+        match self {
+            GovernanceState::Proposal(proposal) => Some(proposal),
+            _ => None,
+        }
+    }
+
+    pub fn vote_record(self) -> Option<VoteRecord> {
+        match self {
+            GovernanceState::VoteRecord(vote_record) => Some(vote_record),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, mundis_sdk::system_instruction};
+
+    #[test]
+    fn test_proposal_size_of() {
+        let actions = vec![
+            ProposalAction::Invoke(system_instruction::transfer(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                42,
+            )),
+            ProposalAction::ActivateFeature(Pubkey::new_unique()),
+        ];
+        let size = GovernanceState::proposal_size_of("raise the fee", &actions);
+        let proposal = GovernanceState::Proposal(Proposal {
+            proposer: Pubkey::new_unique(),
+            description: "raise the fee".to_string(),
+            actions,
+            stake_epoch: 8,
+            total_stake: 300,
+            voting_end_epoch: 10,
+            yes_stake: 1,
+            no_stake: 2,
+            status: ProposalStatus::Executed,
+        });
+        let mut data = vec![0; size];
+        bincode::serialize_into(&mut data[..], &proposal).unwrap();
+        assert_eq!(GovernanceState::deserialize(&data).unwrap(), proposal);
+        assert_eq!(
+            GovernanceState::deserialize(&[0; 4]).unwrap(),
+            GovernanceState::Uninitialized
+        );
+    }
+
+    #[test]
+    fn test_tally() {
+        let proposal = Proposal {
+            total_stake: 300,
+            voting_end_epoch: 10,
+            ..Proposal::default()
+        };
+        assert_eq!(proposal.tally(0), ProposalStatus::Voting);
+        assert_eq!(proposal.tally(10), ProposalStatus::Defeated);
+
+        let proposal = Proposal {
+            yes_stake: 200,
+            ..proposal
+        };
+        assert_eq!(proposal.tally(0), ProposalStatus::Succeeded);
+
+        let proposal = Proposal {
+            total_stake: 301,
+            ..proposal
+        };
+        assert_eq!(proposal.tally(0), ProposalStatus::Voting);
+
+        let proposal = Proposal {
+            no_stake: 101,
+            ..proposal
+        };
+        assert_eq!(proposal.tally(0), ProposalStatus::Defeated);
+
+        let proposal = Proposal {
+            status: ProposalStatus::Executed,
+            ..proposal
+        };
+        assert_eq!(proposal.tally(0), ProposalStatus::Executed);
+    }
+}
@@ -1,4 +1,3 @@
-use std::str::from_utf8;
 use mundis_program_runtime::ic_msg;
 use mundis_program_runtime::invoke_context::InvokeContext;
 use mundis_sdk::instruction::InstructionError;
@@ -21,12 +20,10 @@ pub fn process_instruction(
     if missing_required_signature {
         return Err(InstructionError::MissingRequiredSignature);
     }
-    let memo = from_utf8(data).map_err(|err| {
-        ic_msg!(invoke_context, "Invalid UTF-8, from byte {}", err.valid_up_to());
-        InstructionError::InvalidInstructionData
-    })?;
-
-    ic_msg!(invoke_context, "Memo (len {}): {:?}", memo.len(), memo);
+    // Memos are arbitrary bytes, token accounts requiring a memo on incoming
+    // transfers may additionally require it to be valid UTF-8
+    let memo = String::from_utf8_lossy(data);
+    ic_msg!(invoke_context, "Memo (len {}): {:?}", data.len(), memo);
     Ok(())
 }
 
@@ -63,12 +60,9 @@ mod tests {
         assert_eq!(emoji, bytes);
         assert_eq!(Ok(()), process_memo_instruction(emoji, &[]));
 
-        let mut bad_utf8 = bytes;
-        bad_utf8[3] = 0xFF; // Invalid UTF-8 byte
-        assert_eq!(
-            Err(InstructionError::InvalidInstructionData),
-            process_memo_instruction(&bad_utf8, &[])
-        );
+        let mut binary = bytes;
+        binary[3] = 0xFF; // Invalid UTF-8 byte
+        assert_eq!(Ok(()), process_memo_instruction(&binary, &[]));
     }
 
     #[test]
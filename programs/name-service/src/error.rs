@@ -0,0 +1,67 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use thiserror::Error;
+
+use mundis_sdk::decode_error::{DecodeError, PrintInstructionError};
+use mundis_sdk::instruction::InstructionError;
+
+/// Errors that may be returned by the NameService program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum NameServiceError {
+    // 0
+    /// The name is not made of valid labels.
+    #[error("Invalid name")]
+    InvalidName,
+    /// The name is registered and has not expired.
+    #[error("Name already registered")]
+    AlreadyRegistered,
+    /// The name record is not initialized.
+    #[error("Name is not registered")]
+    NotRegistered,
+    /// The name's lease has expired.
+    #[error("Name has expired")]
+    Expired,
+    /// The owner did not sign the instruction.
+    #[error("Wrong name owner")]
+    WrongOwner,
+
+    // 5
+    /// A program derived address does not match the expected derivation.
+    #[error("Invalid program address")]
+    InvalidProgramAddress,
+    /// The parent name is missing, expired, or its owner did not sign.
+    #[error("Invalid parent name")]
+    InvalidParent,
+    /// A name must be leased for at least one epoch.
+    #[error("Invalid number of epochs")]
+    InvalidEpochs,
+    /// The record data does not fit in the name record.
+    #[error("Record data too large")]
+    DataTooLarge,
+    /// Operation overflowed
+    #[error("Operation overflowed")]
+    Overflow,
+}
+
+impl From<NameServiceError> for InstructionError {
+    fn from(e: NameServiceError) -> Self {
+        InstructionError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for NameServiceError {
+    fn type_of() -> &'static str {
+        "NameServiceError"
+    }
+}
+
+impl PrintInstructionError for NameServiceError {
+    fn print<E>(&self)
+        where
+            E: 'static + std::error::Error + DecodeError<E> + PrintInstructionError + FromPrimitive,
+    {
+        eprintln!("{}", self);
+    }
+}
@@ -0,0 +1,121 @@
+#![allow(incomplete_features)]
+#![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(specialization))]
+#![cfg_attr(RUSTC_NEEDS_PROC_MACRO_HYGIENE, feature(proc_macro_hygiene))]
+
+//! A registry of hierarchical names, such as `alice.world`, each resolving to
+//! an owner `Pubkey` and arbitrary record data. Names are leased for a number
+//! of epochs and paid for in MUNDIS.
+
+use mundis_sdk::{
+    hash::{hashv, Hash},
+    native_token::LAMPORTS_PER_MUNDIS,
+    pubkey::Pubkey,
+};
+
+pub mod error;
+pub mod name_service_instruction;
+pub mod name_service_processor;
+pub mod state;
+
+pub use mundis_sdk::name_service::program::{check_id, id};
+
+/// Seed for the account holding a name record
+const NAME_RECORD: &[u8] = b"name";
+/// Seed for the system account collecting registration fees
+const TREASURY: &[u8] = b"treasury";
+
+/// Longest name that can be registered, including separators
+pub const MAX_NAME_LENGTH: usize = 253;
+/// Longest single label of a name
+pub const MAX_LABEL_LENGTH: usize = 63;
+/// Largest record data a name can hold
+pub const MAX_RECORD_DATA_LENGTH: u32 = 1024;
+
+/// Checks that `name` is a sequence of dot-separated labels made of lowercase
+/// ASCII letters, digits and inner hyphens
+pub fn is_valid_name(name: &str) -> bool {
+    name.len() <= MAX_NAME_LENGTH
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= MAX_LABEL_LENGTH
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        })
+}
+
+/// Returns the name one level up the hierarchy, `world` for `alice.world`
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.split_once('.').map(|(_, parent)| parent)
+}
+
+pub fn hash_name(name: &str) -> Hash {
+    hashv(&[NAME_RECORD, name.as_bytes()])
+}
+
+/// Derives the record account of a name
+pub fn find_name_record_address(name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NAME_RECORD, hash_name(name).as_ref()], &id())
+}
+
+/// Derives the account collecting registration fees
+pub fn find_treasury_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY], &id())
+}
+
+/// Lamports charged for each epoch a name is leased, shorter names cost more
+pub fn price_per_epoch(name: &str) -> u64 {
+    let label_len = name.split('.').next().map(str::len).unwrap_or_default();
+    match label_len {
+        0..=3 => LAMPORTS_PER_MUNDIS / 2,
+        4..=5 => LAMPORTS_PER_MUNDIS / 20,
+        _ => LAMPORTS_PER_MUNDIS / 200,
+    }
+}
+
+/// Lamports charged to register or renew `name` for `epochs`
+pub fn registration_price(name: &str, epochs: u64) -> Option<u64> {
+    price_per_epoch(name).checked_mul(epochs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("world"));
+        assert!(is_valid_name("alice.world"));
+        assert!(is_valid_name("my-wallet.alice.world"));
+        assert!(is_valid_name("42.world"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("alice..world"));
+        assert!(!is_valid_name(".world"));
+        assert!(!is_valid_name("alice."));
+        assert!(!is_valid_name("Alice.world"));
+        assert!(!is_valid_name("-alice.world"));
+        assert!(!is_valid_name("alice_bob.world"));
+        assert!(!is_valid_name(&"a".repeat(MAX_LABEL_LENGTH + 1)));
+    }
+
+    #[test]
+    fn test_parent_name() {
+        assert_eq!(parent_name("world"), None);
+        assert_eq!(parent_name("alice.world"), Some("world"));
+        assert_eq!(parent_name("pay.alice.world"), Some("alice.world"));
+    }
+
+    #[test]
+    fn test_registration_price() {
+        assert_eq!(registration_price("abc", 2), Some(LAMPORTS_PER_MUNDIS));
+        assert_eq!(registration_price("alice.world", 10), Some(LAMPORTS_PER_MUNDIS / 2));
+        assert_eq!(registration_price("mundis", 200), Some(LAMPORTS_PER_MUNDIS));
+        assert_eq!(registration_price("abc", u64::MAX), None);
+        assert_ne!(
+            find_name_record_address("alice.world"),
+            find_name_record_address("bob.world")
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Instruction types
+
+use {
+    crate::{find_name_record_address, find_treasury_address, id, parent_name},
+    mundis_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Instructions supported by the name service program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum NameServiceInstruction {
+    /// Registers a name, or re-registers one whose lease has expired.
+    ///
+    /// The record account is created at the address derived from the name.
+    /// Registering a name with a parent, e.g. `alice.world`, requires the
+    /// parent's owner to sign.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer of the rent and registration fee.
+    ///   1. `[writable]` Name record, derived from the name.
+    ///   2. `[writable]` Treasury.
+    ///   3. `[]` Owner of the name.
+    ///   4. `[]` System program.
+    ///   5. `[]` Parent name record, if the name has a parent.
+    ///   6. `[signer]` Owner of the parent name, if the name has a parent.
+    Register {
+        /// Name to register
+        name: String,
+        /// Number of epochs the name is leased for
+        epochs: u64,
+        /// Bytes reserved for record data
+        space: u32,
+    },
+
+    /// Extends the lease of a registered name. Anyone may pay for a renewal.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer of the renewal fee.
+    ///   1. `[writable]` Name record.
+    ///   2. `[writable]` Treasury.
+    ///   3. `[]` System program.
+    Renew {
+        /// Number of epochs to add to the lease
+        epochs: u64,
+    },
+
+    /// Writes record data at an offset.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Name record.
+    ///   1. `[signer]` Owner of the name.
+    Update {
+        /// Offset into the record data
+        offset: u32,
+        /// Bytes to write
+        data: Vec<u8>,
+    },
+
+    /// Transfers a name to a new owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Name record.
+    ///   1. `[signer]` Owner of the name.
+    Transfer {
+        /// New owner of the name
+        new_owner: Pubkey,
+    },
+
+    /// Deletes a name and returns the record's rent. The fee paid for the
+    /// remaining lease is not refunded.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Name record.
+    ///   1. `[signer]` Owner of the name.
+    ///   2. `[writable]` Account receiving the rent.
+    Delete,
+}
+
+pub fn register(
+    payer: &Pubkey,
+    name: &str,
+    owner: &Pubkey,
+    parent_owner: Option<&Pubkey>,
+    epochs: u64,
+    space: u32,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(find_name_record_address(name).0, false),
+        AccountMeta::new(find_treasury_address().0, false),
+        AccountMeta::new_readonly(*owner, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let (Some(parent), Some(parent_owner)) = (parent_name(name), parent_owner) {
+        accounts.push(AccountMeta::new_readonly(find_name_record_address(parent).0, false));
+        accounts.push(AccountMeta::new_readonly(*parent_owner, true));
+    }
+    Instruction::new_with_bincode(
+        id(),
+        &NameServiceInstruction::Register {
+            name: name.to_string(),
+            epochs,
+            space,
+        },
+        accounts,
+    )
+}
+
+pub fn renew(payer: &Pubkey, name: &str, epochs: u64) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &NameServiceInstruction::Renew { epochs },
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(find_name_record_address(name).0, false),
+            AccountMeta::new(find_treasury_address().0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn update(name: &str, owner: &Pubkey, offset: u32, data: Vec<u8>) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &NameServiceInstruction::Update { offset, data },
+        vec![
+            AccountMeta::new(find_name_record_address(name).0, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+pub fn transfer(name: &str, owner: &Pubkey, new_owner: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &NameServiceInstruction::Transfer {
+            new_owner: *new_owner,
+        },
+        vec![
+            AccountMeta::new(find_name_record_address(name).0, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+pub fn delete(name: &str, owner: &Pubkey, destination: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &NameServiceInstruction::Delete,
+        vec![
+            AccountMeta::new(find_name_record_address(name).0, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*destination, false),
+        ],
+    )
+}
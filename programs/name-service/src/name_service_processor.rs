@@ -0,0 +1,343 @@
+use {
+    crate::{
+        error::NameServiceError,
+        find_name_record_address, find_treasury_address, is_valid_name,
+        name_service_instruction::NameServiceInstruction,
+        parent_name, registration_price,
+        state::{NameRecord, NameServiceState},
+        MAX_RECORD_DATA_LENGTH,
+    },
+    mundis_program_runtime::{ic_msg, invoke_context::InvokeContext},
+    mundis_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        account_utils::State,
+        decode_error::PrintInstructionError,
+        instruction::InstructionError,
+        keyed_account::{keyed_account_at_index, next_keyed_account, KeyedAccount},
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+};
+
+pub fn process_instruction(
+    first_instruction_account: usize,
+    data: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    if let Err(error) = Processor::process(first_instruction_account, data, invoke_context) {
+        // catch the error so we can print it
+        error.print::<NameServiceError>();
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn get_name_record(record_info: &KeyedAccount) -> Result<NameRecord, InstructionError> {
+    if record_info.owner()? != crate::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    NameServiceState::deserialize(record_info.try_account_ref()?.data())?
+        .name_record()
+        .ok_or_else(|| NameServiceError::NotRegistered.into())
+}
+
+fn check_address(
+    invoke_context: &InvokeContext,
+    keyed_account: &KeyedAccount,
+    expected: &Pubkey,
+) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != expected {
+        ic_msg!(
+            invoke_context,
+            "Expected account {}, got {}",
+            expected,
+            keyed_account.unsigned_key()
+        );
+        return Err(NameServiceError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+fn check_program(keyed_account: &KeyedAccount, program_id: &Pubkey) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != program_id {
+        return Err(InstructionError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+fn check_owner(record: &NameRecord, owner_info: &KeyedAccount) -> Result<(), InstructionError> {
+    if owner_info.signer_key() != Some(&record.owner) {
+        return Err(NameServiceError::WrongOwner.into());
+    }
+    Ok(())
+}
+
+fn check_active(
+    invoke_context: &InvokeContext,
+    record: &NameRecord,
+) -> Result<(), InstructionError> {
+    let clock = invoke_context.get_sysvar_cache().get_clock()?;
+    if !record.is_active(clock.epoch) {
+        ic_msg!(invoke_context, "{} expired in epoch {}", record.name, record.expiry_epoch);
+        return Err(NameServiceError::Expired.into());
+    }
+    Ok(())
+}
+
+pub struct Processor {}
+
+impl Processor {
+    fn process(
+        first_instruction_account: usize,
+        data: &[u8],
+        invoke_context: &mut InvokeContext,
+    ) -> Result<(), InstructionError> {
+        match limited_deserialize(data)? {
+            NameServiceInstruction::Register {
+                name,
+                epochs,
+                space,
+            } => {
+                ic_msg!(invoke_context, "Instruction: Register");
+                Self::process_register(invoke_context, first_instruction_account, name, epochs, space)
+            }
+            NameServiceInstruction::Renew { epochs } => {
+                ic_msg!(invoke_context, "Instruction: Renew");
+                Self::process_renew(invoke_context, first_instruction_account, epochs)
+            }
+            NameServiceInstruction::Update { offset, data } => {
+                ic_msg!(invoke_context, "Instruction: Update");
+                Self::process_update(invoke_context, first_instruction_account, offset, data)
+            }
+            NameServiceInstruction::Transfer { new_owner } => {
+                ic_msg!(invoke_context, "Instruction: Transfer");
+                Self::process_transfer(invoke_context, first_instruction_account, new_owner)
+            }
+            NameServiceInstruction::Delete => {
+                ic_msg!(invoke_context, "Instruction: Delete");
+                Self::process_delete(invoke_context, first_instruction_account)
+            }
+        }
+    }
+
+    fn process_register(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        name: String,
+        epochs: u64,
+        space: u32,
+    ) -> Result<(), InstructionError> {
+        if !is_valid_name(&name) {
+            return Err(NameServiceError::InvalidName.into());
+        }
+        if epochs == 0 {
+            return Err(NameServiceError::InvalidEpochs.into());
+        }
+        if space > MAX_RECORD_DATA_LENGTH {
+            return Err(NameServiceError::DataTooLarge.into());
+        }
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let payer_info = next_keyed_account(accounts_iter)?;
+        let record_info = next_keyed_account(accounts_iter)?;
+        let treasury_info = next_keyed_account(accounts_iter)?;
+        let owner_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        let payer = *payer_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let record = find_name_record_address(&name).0;
+        check_address(invoke_context, record_info, &record)?;
+        let treasury = find_treasury_address().0;
+        check_address(invoke_context, treasury_info, &treasury)?;
+        let owner = *owner_info.unsigned_key();
+
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        let parent = match parent_name(&name) {
+            Some(parent_name) => {
+                let parent_info = next_keyed_account(accounts_iter)?;
+                let parent_owner_info = next_keyed_account(accounts_iter)?;
+                check_address(invoke_context, parent_info, &find_name_record_address(parent_name).0)?;
+                let parent_record = get_name_record(parent_info)?;
+                if !parent_record.is_active(clock.epoch)
+                    || parent_owner_info.signer_key() != Some(&parent_record.owner)
+                {
+                    ic_msg!(invoke_context, "{} must be active and its owner must sign", parent_name);
+                    return Err(NameServiceError::InvalidParent.into());
+                }
+                Some(*parent_info.unsigned_key())
+            }
+            None => None,
+        };
+
+        let size = NameServiceState::name_record_size_of(&name, space);
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let record_rent = rent.minimum_balance(size);
+        let mut instructions = vec![];
+        if record_info.owner()? == system_program::id() {
+            let record_lamports = record_info.lamports()?;
+            if record_lamports == 0 {
+                instructions.push(system_instruction::create_account(
+                    &payer,
+                    &record,
+                    record_rent,
+                    size as u64,
+                    &crate::id(),
+                ));
+            } else {
+                // Someone funded the address ahead of time, top it up instead
+                if record_lamports < record_rent {
+                    instructions.push(system_instruction::transfer(
+                        &payer,
+                        &record,
+                        record_rent - record_lamports,
+                    ));
+                }
+                instructions.push(system_instruction::allocate(&record, size as u64));
+                instructions.push(system_instruction::assign(&record, &crate::id()));
+            }
+        } else {
+            // Only an expired name can be registered again
+            if let Ok(existing) = get_name_record(record_info) {
+                if existing.is_active(clock.epoch) {
+                    return Err(NameServiceError::AlreadyRegistered.into());
+                }
+            }
+            if record_info.data_len()? < size {
+                ic_msg!(invoke_context, "Existing record holds less than {} bytes", space);
+                return Err(NameServiceError::DataTooLarge.into());
+            }
+        }
+        let fee = registration_price(&name, epochs).ok_or(NameServiceError::Overflow)?;
+        instructions.push(system_instruction::transfer(&payer, &treasury, fee));
+        let expiry_epoch = clock
+            .epoch
+            .checked_add(epochs)
+            .ok_or(NameServiceError::Overflow)?;
+
+        for instruction in instructions {
+            invoke_context.native_invoke(instruction, &[payer, record])?;
+        }
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let record_info = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        record_info.set_state(&NameServiceState::NameRecord(NameRecord {
+            name,
+            owner,
+            parent,
+            expiry_epoch,
+            data: vec![0; space as usize],
+        }))
+    }
+
+    fn process_renew(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        epochs: u64,
+    ) -> Result<(), InstructionError> {
+        if epochs == 0 {
+            return Err(NameServiceError::InvalidEpochs.into());
+        }
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let payer_info = next_keyed_account(accounts_iter)?;
+        let record_info = next_keyed_account(accounts_iter)?;
+        let treasury_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        let payer = *payer_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let treasury = find_treasury_address().0;
+        check_address(invoke_context, treasury_info, &treasury)?;
+        let mut record = get_name_record(record_info)?;
+        check_active(invoke_context, &record)?;
+
+        let fee = registration_price(&record.name, epochs).ok_or(NameServiceError::Overflow)?;
+        record.expiry_epoch = record
+            .expiry_epoch
+            .checked_add(epochs)
+            .ok_or(NameServiceError::Overflow)?;
+        record_info.set_state(&NameServiceState::NameRecord(record))?;
+
+        invoke_context.native_invoke(
+            system_instruction::transfer(&payer, &treasury, fee),
+            &[payer],
+        )
+    }
+
+    fn process_update(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        offset: u32,
+        data: Vec<u8>,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let record_info = next_keyed_account(accounts_iter)?;
+        let owner_info = next_keyed_account(accounts_iter)?;
+
+        let mut record = get_name_record(record_info)?;
+        check_owner(&record, owner_info)?;
+        check_active(invoke_context, &record)?;
+
+        let start = offset as usize;
+        let end = start
+            .checked_add(data.len())
+            .ok_or(NameServiceError::Overflow)?;
+        if end > record.data.len() {
+            ic_msg!(invoke_context, "Record holds {} bytes of data", record.data.len());
+            return Err(NameServiceError::DataTooLarge.into());
+        }
+        record.data[start..end].copy_from_slice(&data);
+        record_info.set_state(&NameServiceState::NameRecord(record))
+    }
+
+    fn process_transfer(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        new_owner: Pubkey,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let record_info = next_keyed_account(accounts_iter)?;
+        let owner_info = next_keyed_account(accounts_iter)?;
+
+        let mut record = get_name_record(record_info)?;
+        check_owner(&record, owner_info)?;
+        check_active(invoke_context, &record)?;
+
+        record.owner = new_owner;
+        record_info.set_state(&NameServiceState::NameRecord(record))
+    }
+
+    fn process_delete(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let record_info = next_keyed_account(accounts_iter)?;
+        let owner_info = next_keyed_account(accounts_iter)?;
+        let destination_info = next_keyed_account(accounts_iter)?;
+
+        let record = get_name_record(record_info)?;
+        check_owner(&record, owner_info)?;
+        if record_info.unsigned_key() == destination_info.unsigned_key() {
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let record_lamports = record_info.lamports()?;
+        record_info.set_state(&NameServiceState::Uninitialized)?;
+        record_info.try_account_ref_mut()?.set_lamports(0);
+        destination_info
+            .try_account_ref_mut()?
+            .checked_add_lamports(record_lamports)?;
+        Ok(())
+    }
+}
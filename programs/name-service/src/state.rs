@@ -0,0 +1,90 @@
+//! State transition types
+
+use {
+    mundis_sdk::{clock::Epoch, instruction::InstructionError, pubkey::Pubkey},
+    serde_derive::{Deserialize, Serialize},
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct NameRecord {
+    /// Full name, e.g. `alice.world`
+    pub name: String,
+    /// Authority that can update, transfer and delete the name
+    pub owner: Pubkey,
+    /// Record of the parent name, `None` for top-level names
+    pub parent: Option<Pubkey>,
+    /// First epoch the name is no longer valid
+    pub expiry_epoch: Epoch,
+    /// Arbitrary data attached by the owner, its length is fixed at registration
+    pub data: Vec<u8>,
+}
+
+impl NameRecord {
+    pub fn is_active(&self, epoch: Epoch) -> bool {
+        epoch < self.expiry_epoch
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum NameServiceState {
+    Uninitialized,
+    NameRecord(NameRecord),
+}
+
+impl Default for NameServiceState {
+    fn default() -> Self {
+        NameServiceState::Uninitialized
+    }
+}
+
+impl NameServiceState {
+    /// Size of the account holding `name` with `space` bytes of record data
+    pub fn name_record_size_of(name: &str, space: u32) -> usize {
+        let record = NameRecord {
+            name: name.to_string(),
+            parent: Some(Pubkey::default()),
+            data: vec![0; space as usize],
+            ..NameRecord::default()
+        };
+        bincode::serialized_size(&NameServiceState::NameRecord(record)).unwrap() as usize
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, InstructionError> {
+        bincode::deserialize(data).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    pub fn name_record(self) -> Option<NameRecord> {
+        match self {
+            NameServiceState::NameRecord(record) => Some(record),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_record_size_of() {
+        let size = NameServiceState::name_record_size_of("alice.world", 32);
+        let record = NameServiceState::NameRecord(NameRecord {
+            name: "alice.world".to_string(),
+            owner: Pubkey::new_unique(),
+            parent: None,
+            expiry_epoch: 10,
+            data: vec![7; 32],
+        });
+        let mut data = vec![0; size];
+        bincode::serialize_into(&mut data[..], &record).unwrap();
+        assert_eq!(NameServiceState::deserialize(&data).unwrap(), record);
+        assert_eq!(
+            NameServiceState::deserialize(&[0; 4]).unwrap(),
+            NameServiceState::Uninitialized
+        );
+
+        let record = record.name_record().unwrap();
+        assert!(record.is_active(9));
+        assert!(!record.is_active(10));
+    }
+}
@@ -0,0 +1,98 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use thiserror::Error;
+
+use mundis_sdk::decode_error::{DecodeError, PrintInstructionError};
+use mundis_sdk::instruction::InstructionError;
+
+/// Errors that may be returned by the StakePool program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum StakePoolError {
+    // 0
+    /// The stake pool account is already initialized.
+    #[error("Stake pool already in use")]
+    AlreadyInUse,
+    /// The stake pool or ticket account is not initialized.
+    #[error("Stake pool state is uninitialized")]
+    UninitializedState,
+    /// A program derived address does not match the expected derivation.
+    #[error("Invalid program address")]
+    InvalidProgramAddress,
+    /// The pool mint does not match, or its authority is not the pool.
+    #[error("Invalid pool mint")]
+    InvalidMint,
+    /// The manager fee account is not a token account of the pool mint.
+    #[error("Invalid manager fee account")]
+    InvalidFeeAccount,
+
+    // 5
+    /// The manager did not sign the instruction.
+    #[error("Wrong manager")]
+    WrongManager,
+    /// The staker did not sign the instruction.
+    #[error("Wrong staker")]
+    WrongStaker,
+    /// The fee numerator is greater than its denominator.
+    #[error("Fee is too high")]
+    FeeTooHigh,
+    /// The validator list is at its maximum size.
+    #[error("Validator list is full")]
+    ValidatorListFull,
+    /// The vote account is already part of the pool.
+    #[error("Validator already added")]
+    ValidatorAlreadyAdded,
+
+    // 10
+    /// The vote account is not part of the pool.
+    #[error("Validator not found")]
+    ValidatorNotFound,
+    /// The validator still has stake delegated by the pool.
+    #[error("Validator still holds pool stake")]
+    ValidatorHasStake,
+    /// The transient stake account is still in use.
+    #[error("Transient stake account in use")]
+    TransientAccountInUse,
+    /// The pool was not updated for the current epoch.
+    #[error("Stake pool is not updated for the current epoch")]
+    StakePoolOutOfDate,
+    /// The reserve does not hold enough undelegated lamports.
+    #[error("Insufficient lamports in the reserve")]
+    InsufficientReserve,
+
+    // 15
+    /// The amount is too small to be converted.
+    #[error("Amount too small")]
+    AmountTooSmall,
+    /// The withdrawal ticket cannot be claimed yet.
+    #[error("Withdrawal ticket is not claimable yet")]
+    TicketNotClaimable,
+    /// The beneficiary did not sign the claim.
+    #[error("Wrong withdrawal ticket beneficiary")]
+    WrongBeneficiary,
+    /// Operation overflowed
+    #[error("Operation overflowed")]
+    Overflow,
+}
+
+impl From<StakePoolError> for InstructionError {
+    fn from(e: StakePoolError) -> Self {
+        InstructionError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for StakePoolError {
+    fn type_of() -> &'static str {
+        "StakePoolError"
+    }
+}
+
+impl PrintInstructionError for StakePoolError {
+    fn print<E>(&self)
+        where
+            E: 'static + std::error::Error + DecodeError<E> + PrintInstructionError + FromPrimitive,
+    {
+        eprintln!("{}", self);
+    }
+}
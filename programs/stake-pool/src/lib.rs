@@ -0,0 +1,56 @@
+#![allow(incomplete_features)]
+#![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(specialization))]
+#![cfg_attr(RUSTC_NEEDS_PROC_MACRO_HYGIENE, feature(proc_macro_hygiene))]
+
+//! A liquid staking pool: MUNDIS deposits are delegated across a curated set of
+//! validators and represented by a pool token minted through the token program.
+
+use mundis_sdk::pubkey::Pubkey;
+
+pub mod error;
+pub mod stake_pool_instruction;
+pub mod stake_pool_processor;
+pub mod state;
+
+pub use mundis_sdk::stake_pool::program::{check_id, id};
+
+/// Seed for the authority that owns the pool's stake accounts and mints pool tokens
+const AUTHORITY_WITHDRAW: &[u8] = b"withdraw";
+/// Seed for the system account holding undelegated pool lamports
+const RESERVE: &[u8] = b"reserve";
+/// Seed for the main stake account delegated to a validator
+const VALIDATOR_STAKE: &[u8] = b"stake";
+/// Seed for the stake account used while activating or deactivating stake
+const TRANSIENT_STAKE: &[u8] = b"transient";
+
+/// Derives the withdraw authority of a stake pool
+pub fn find_withdraw_authority_program_address(stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), AUTHORITY_WITHDRAW], &id())
+}
+
+/// Derives the reserve account of a stake pool
+pub fn find_reserve_program_address(stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), RESERVE], &id())
+}
+
+/// Derives the stake account delegated to the given vote account
+pub fn find_validator_stake_program_address(
+    vote_account: &Pubkey,
+    stake_pool: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[vote_account.as_ref(), stake_pool.as_ref(), VALIDATOR_STAKE],
+        &id(),
+    )
+}
+
+/// Derives the transient stake account for the given vote account
+pub fn find_transient_stake_program_address(
+    vote_account: &Pubkey,
+    stake_pool: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[vote_account.as_ref(), stake_pool.as_ref(), TRANSIENT_STAKE],
+        &id(),
+    )
+}
@@ -0,0 +1,539 @@
+//! Instruction types
+
+use {
+    crate::{
+        find_reserve_program_address, find_transient_stake_program_address,
+        find_validator_stake_program_address, find_withdraw_authority_program_address, id,
+        state::{Fee, FeeType},
+    },
+    mundis_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        stake, system_program, sysvar,
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Instructions supported by the stake pool program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum StakePoolInstruction {
+    /// Initializes a new stake pool.
+    ///
+    /// The pool account must be allocated by the system program with
+    /// `StakePoolState::stake_pool_size_of(max_validators)` bytes and assigned
+    /// to this program. The reserve must be funded with at least the
+    /// rent-exempt minimum of an empty account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool to initialize.
+    ///   1. `[signer]` Manager.
+    ///   2. `[]` Staker.
+    ///   3. `[]` Withdraw authority, derived from the stake pool.
+    ///   4. `[]` Reserve, derived from the stake pool.
+    ///   5. `[]` Pool mint, its mint authority must be the withdraw authority.
+    ///   6. `[]` Pool token account receiving manager fees.
+    Initialize {
+        /// Fee taken from staking rewards every epoch
+        epoch_fee: Fee,
+        /// Fee taken from instant and delayed withdrawals
+        withdrawal_fee: Fee,
+        /// Maximum number of validators in the pool
+        max_validators: u32,
+    },
+
+    /// Adds a validator to the pool's list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Staker.
+    ///   2. `[]` Vote account of the validator.
+    AddValidator,
+
+    /// Removes a validator that holds no pool stake from the pool's list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Staker.
+    ///   2. `[]` Vote account of the validator.
+    ///   3. `[]` Validator stake account.
+    ///   4. `[]` Transient stake account.
+    RemoveValidator,
+
+    /// Moves lamports from the reserve into a new stake account delegated to
+    /// the validator. The validator stake account is used if it does not exist
+    /// yet, otherwise the transient stake account is used and merged into it by
+    /// `UpdateStakePool` once active.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Staker.
+    ///   2. `[]` Withdraw authority.
+    ///   3. `[writable]` Reserve.
+    ///   4. `[writable]` Validator stake account.
+    ///   5. `[writable]` Transient stake account.
+    ///   6. `[]` Vote account of the validator.
+    ///   7. `[]` Clock sysvar.
+    ///   8. `[]` Rent sysvar.
+    ///   9. `[]` Stake history sysvar.
+    ///   10. `[]` Stake config account.
+    ///   11. `[]` System program.
+    ///   12. `[]` Stake program.
+    IncreaseValidatorStake {
+        /// Lamports to delegate, including the stake account's rent-exempt reserve
+        lamports: u64,
+    },
+
+    /// Splits lamports from the validator stake account into the transient
+    /// stake account and deactivates them. `UpdateStakePool` returns them to
+    /// the reserve once the stake is inactive.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Staker.
+    ///   2. `[]` Withdraw authority.
+    ///   3. `[writable]` Validator stake account.
+    ///   4. `[writable]` Transient stake account.
+    ///   5. `[]` Clock sysvar.
+    ///   6. `[]` System program.
+    ///   7. `[]` Stake program.
+    DecreaseValidatorStake {
+        /// Lamports to deactivate
+        lamports: u64,
+    },
+
+    /// Updates the pool's balances for the current epoch. Fully active
+    /// transient stake is merged into the validator stake, inactive transient
+    /// stake is returned to the reserve, and the epoch fee is minted to the
+    /// manager from the rewards earned since the last update. Anyone may call
+    /// this instruction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[]` Withdraw authority.
+    ///   2. `[writable]` Reserve.
+    ///   3. `[writable]` Manager fee account.
+    ///   4. `[writable]` Pool mint.
+    ///   5. `[]` Clock sysvar.
+    ///   6. `[]` Stake history sysvar.
+    ///   7. `[]` Stake program.
+    ///   8. `[]` Token program.
+    ///   9. ..9+2N `[writable]` Validator and transient stake account pairs,
+    ///      in the order of the pool's validator list.
+    UpdateStakePool,
+
+    /// Deposits MUNDIS into the reserve in exchange for pool tokens.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[]` Withdraw authority.
+    ///   2. `[writable]` Reserve.
+    ///   3. `[writable, signer]` Depositor.
+    ///   4. `[writable]` Pool token account receiving the pool tokens.
+    ///   5. `[writable]` Pool mint.
+    ///   6. `[]` System program.
+    ///   7. `[]` Token program.
+    DepositMundis {
+        /// Lamports to deposit
+        lamports: u64,
+    },
+
+    /// Burns pool tokens and withdraws their value from the reserve
+    /// immediately, minus the withdrawal fee.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[]` Withdraw authority.
+    ///   2. `[writable]` Reserve.
+    ///   3. `[writable]` Pool token account to burn from.
+    ///   4. `[signer]` Owner or delegate of the pool token account.
+    ///   5. `[writable]` Account receiving the lamports.
+    ///   6. `[writable]` Manager fee account.
+    ///   7. `[writable]` Pool mint.
+    ///   8. `[]` System program.
+    ///   9. `[]` Token program.
+    WithdrawInstant {
+        /// Pool tokens to redeem
+        pool_tokens: u64,
+    },
+
+    /// Burns pool tokens and issues a withdrawal ticket claimable from the
+    /// next epoch, giving the staker time to deactivate stake. The withdrawal
+    /// fee is charged as for `WithdrawInstant`.
+    ///
+    /// The ticket account must be allocated by the system program with
+    /// `StakePoolState::withdrawal_ticket_size_of()` bytes and assigned to this
+    /// program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[writable]` Uninitialized withdrawal ticket.
+    ///   2. `[writable]` Pool token account to burn from.
+    ///   3. `[signer]` Owner or delegate of the pool token account.
+    ///   4. `[]` Beneficiary allowed to claim the ticket.
+    ///   5. `[writable]` Manager fee account.
+    ///   6. `[writable]` Pool mint.
+    ///   7. `[]` Token program.
+    RequestWithdrawal {
+        /// Pool tokens to redeem
+        pool_tokens: u64,
+    },
+
+    /// Pays out a withdrawal ticket from the reserve and closes it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[writable]` Withdrawal ticket.
+    ///   2. `[writable]` Reserve.
+    ///   3. `[signer]` Beneficiary of the ticket.
+    ///   4. `[writable]` Account receiving the lamports.
+    ///   5. `[]` System program.
+    ClaimWithdrawal,
+
+    /// Updates one of the pool's fees.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Manager.
+    SetFee {
+        fee_type: FeeType,
+        fee: Fee,
+    },
+
+    /// Updates the staker.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Manager or current staker.
+    ///   2. `[]` New staker.
+    SetStaker,
+
+    /// Updates the manager and the manager fee account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The stake pool.
+    ///   1. `[signer]` Manager.
+    ///   2. `[signer]` New manager.
+    ///   3. `[]` New manager fee account.
+    SetManager,
+}
+
+/// Creates an `Initialize` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    staker: &Pubkey,
+    pool_mint: &Pubkey,
+    manager_fee_account: &Pubkey,
+    epoch_fee: Fee,
+    withdrawal_fee: Fee,
+    max_validators: u32,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::Initialize {
+            epoch_fee,
+            withdrawal_fee,
+            max_validators,
+        },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*manager, true),
+            AccountMeta::new_readonly(*staker, false),
+            AccountMeta::new_readonly(find_withdraw_authority_program_address(stake_pool).0, false),
+            AccountMeta::new_readonly(find_reserve_program_address(stake_pool).0, false),
+            AccountMeta::new_readonly(*pool_mint, false),
+            AccountMeta::new_readonly(*manager_fee_account, false),
+        ],
+    )
+}
+
+/// Creates an `AddValidator` instruction.
+pub fn add_validator(stake_pool: &Pubkey, staker: &Pubkey, vote_account: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::AddValidator,
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new_readonly(*vote_account, false),
+        ],
+    )
+}
+
+/// Creates a `RemoveValidator` instruction.
+pub fn remove_validator(
+    stake_pool: &Pubkey,
+    staker: &Pubkey,
+    vote_account: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::RemoveValidator,
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new_readonly(*vote_account, false),
+            AccountMeta::new_readonly(
+                find_validator_stake_program_address(vote_account, stake_pool).0,
+                false,
+            ),
+            AccountMeta::new_readonly(
+                find_transient_stake_program_address(vote_account, stake_pool).0,
+                false,
+            ),
+        ],
+    )
+}
+
+/// Creates an `IncreaseValidatorStake` instruction.
+pub fn increase_validator_stake(
+    stake_pool: &Pubkey,
+    staker: &Pubkey,
+    vote_account: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::IncreaseValidatorStake { lamports },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new_readonly(find_withdraw_authority_program_address(stake_pool).0, false),
+            AccountMeta::new(find_reserve_program_address(stake_pool).0, false),
+            AccountMeta::new(
+                find_validator_stake_program_address(vote_account, stake_pool).0,
+                false,
+            ),
+            AccountMeta::new(
+                find_transient_stake_program_address(vote_account, stake_pool).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*vote_account, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+            AccountMeta::new_readonly(stake::config::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+        ],
+    )
+}
+
+/// Creates a `DecreaseValidatorStake` instruction.
+pub fn decrease_validator_stake(
+    stake_pool: &Pubkey,
+    staker: &Pubkey,
+    vote_account: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::DecreaseValidatorStake { lamports },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new_readonly(find_withdraw_authority_program_address(stake_pool).0, false),
+            AccountMeta::new(
+                find_validator_stake_program_address(vote_account, stake_pool).0,
+                false,
+            ),
+            AccountMeta::new(
+                find_transient_stake_program_address(vote_account, stake_pool).0,
+                false,
+            ),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+        ],
+    )
+}
+
+/// Creates an `UpdateStakePool` instruction for the pool's validators, given
+/// in the order of the pool's validator list.
+pub fn update_stake_pool(
+    stake_pool: &Pubkey,
+    pool_mint: &Pubkey,
+    manager_fee_account: &Pubkey,
+    vote_accounts: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(find_withdraw_authority_program_address(stake_pool).0, false),
+        AccountMeta::new(find_reserve_program_address(stake_pool).0, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(stake::program::id(), false),
+        AccountMeta::new_readonly(mundis_token_program::id(), false),
+    ];
+    for vote_account in vote_accounts {
+        accounts.push(AccountMeta::new(
+            find_validator_stake_program_address(vote_account, stake_pool).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            find_transient_stake_program_address(vote_account, stake_pool).0,
+            false,
+        ));
+    }
+    Instruction::new_with_bincode(id(), &StakePoolInstruction::UpdateStakePool, accounts)
+}
+
+/// Creates a `DepositMundis` instruction.
+pub fn deposit_mundis(
+    stake_pool: &Pubkey,
+    depositor: &Pubkey,
+    pool_token_account: &Pubkey,
+    pool_mint: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::DepositMundis { lamports },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(find_withdraw_authority_program_address(stake_pool).0, false),
+            AccountMeta::new(find_reserve_program_address(stake_pool).0, false),
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(*pool_token_account, false),
+            AccountMeta::new(*pool_mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(mundis_token_program::id(), false),
+        ],
+    )
+}
+
+/// Creates a `WithdrawInstant` instruction.
+pub fn withdraw_instant(
+    stake_pool: &Pubkey,
+    pool_token_account: &Pubkey,
+    pool_token_authority: &Pubkey,
+    destination: &Pubkey,
+    manager_fee_account: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_tokens: u64,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::WithdrawInstant { pool_tokens },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(find_withdraw_authority_program_address(stake_pool).0, false),
+            AccountMeta::new(find_reserve_program_address(stake_pool).0, false),
+            AccountMeta::new(*pool_token_account, false),
+            AccountMeta::new_readonly(*pool_token_authority, true),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(*manager_fee_account, false),
+            AccountMeta::new(*pool_mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(mundis_token_program::id(), false),
+        ],
+    )
+}
+
+/// Creates a `RequestWithdrawal` instruction.
+pub fn request_withdrawal(
+    stake_pool: &Pubkey,
+    withdrawal_ticket: &Pubkey,
+    pool_token_account: &Pubkey,
+    pool_token_authority: &Pubkey,
+    beneficiary: &Pubkey,
+    manager_fee_account: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_tokens: u64,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::RequestWithdrawal { pool_tokens },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new(*withdrawal_ticket, false),
+            AccountMeta::new(*pool_token_account, false),
+            AccountMeta::new_readonly(*pool_token_authority, true),
+            AccountMeta::new_readonly(*beneficiary, false),
+            AccountMeta::new(*manager_fee_account, false),
+            AccountMeta::new(*pool_mint, false),
+            AccountMeta::new_readonly(mundis_token_program::id(), false),
+        ],
+    )
+}
+
+/// Creates a `ClaimWithdrawal` instruction.
+pub fn claim_withdrawal(
+    stake_pool: &Pubkey,
+    withdrawal_ticket: &Pubkey,
+    beneficiary: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::ClaimWithdrawal,
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new(*withdrawal_ticket, false),
+            AccountMeta::new(find_reserve_program_address(stake_pool).0, false),
+            AccountMeta::new_readonly(*beneficiary, true),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Creates a `SetFee` instruction.
+pub fn set_fee(stake_pool: &Pubkey, manager: &Pubkey, fee_type: FeeType, fee: Fee) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::SetFee { fee_type, fee },
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*manager, true),
+        ],
+    )
+}
+
+/// Creates a `SetStaker` instruction.
+pub fn set_staker(stake_pool: &Pubkey, authority: &Pubkey, new_staker: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::SetStaker,
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_staker, false),
+        ],
+    )
+}
+
+/// Creates a `SetManager` instruction.
+pub fn set_manager(
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    new_manager: &Pubkey,
+    new_manager_fee_account: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakePoolInstruction::SetManager,
+        vec![
+            AccountMeta::new(*stake_pool, false),
+            AccountMeta::new_readonly(*manager, true),
+            AccountMeta::new_readonly(*new_manager, true),
+            AccountMeta::new_readonly(*new_manager_fee_account, false),
+        ],
+    )
+}
@@ -0,0 +1,1141 @@
+use {
+    crate::{
+        error::StakePoolError,
+        find_reserve_program_address, find_transient_stake_program_address,
+        find_validator_stake_program_address, find_withdraw_authority_program_address,
+        stake_pool_instruction::StakePoolInstruction,
+        state::{Fee, FeeType, StakePool, StakePoolState, ValidatorStakeInfo, WithdrawalTicket},
+    },
+    mundis_program_runtime::{ic_msg, invoke_context::InvokeContext},
+    mundis_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        account_utils::State,
+        clock::Epoch,
+        decode_error::PrintInstructionError,
+        instruction::InstructionError,
+        keyed_account::{from_keyed_account, keyed_account_at_index, next_keyed_account, KeyedAccount},
+        program_pack::Pack,
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        stake::{
+            self,
+            state::{Authorized, Lockup, StakeState},
+        },
+        system_instruction, system_program,
+        sysvar::stake_history::StakeHistory,
+    },
+    mundis_token_program::{
+        state::{Mint, TokenAccount},
+        token_instruction,
+    },
+};
+
+pub fn process_instruction(
+    first_instruction_account: usize,
+    data: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    if let Err(error) = Processor::process(first_instruction_account, data, invoke_context) {
+        // catch the error so we can print it
+        error.print::<StakePoolError>();
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn checked_add(a: u64, b: u64) -> Result<u64, InstructionError> {
+    a.checked_add(b).ok_or_else(|| StakePoolError::Overflow.into())
+}
+
+fn checked_sub(a: u64, b: u64) -> Result<u64, InstructionError> {
+    a.checked_sub(b).ok_or_else(|| StakePoolError::Overflow.into())
+}
+
+fn get_stake_pool(stake_pool_info: &KeyedAccount) -> Result<StakePool, InstructionError> {
+    if stake_pool_info.owner()? != crate::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    StakePoolState::deserialize(stake_pool_info.try_account_ref()?.data())?
+        .stake_pool()
+        .ok_or_else(|| StakePoolError::UninitializedState.into())
+}
+
+fn check_address(
+    invoke_context: &InvokeContext,
+    keyed_account: &KeyedAccount,
+    expected: &Pubkey,
+) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != expected {
+        ic_msg!(
+            invoke_context,
+            "Expected account {}, got {}",
+            expected,
+            keyed_account.unsigned_key()
+        );
+        return Err(StakePoolError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+fn check_program(keyed_account: &KeyedAccount, program_id: &Pubkey) -> Result<(), InstructionError> {
+    if keyed_account.unsigned_key() != program_id {
+        return Err(InstructionError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+fn check_staker(stake_pool: &StakePool, staker_info: &KeyedAccount) -> Result<(), InstructionError> {
+    if staker_info.signer_key() != Some(&stake_pool.staker) {
+        return Err(StakePoolError::WrongStaker.into());
+    }
+    Ok(())
+}
+
+fn check_manager(stake_pool: &StakePool, manager_info: &KeyedAccount) -> Result<(), InstructionError> {
+    if manager_info.signer_key() != Some(&stake_pool.manager) {
+        return Err(StakePoolError::WrongManager.into());
+    }
+    Ok(())
+}
+
+fn check_up_to_date(stake_pool: &StakePool, epoch: Epoch) -> Result<(), InstructionError> {
+    if stake_pool.last_update_epoch < epoch {
+        return Err(StakePoolError::StakePoolOutOfDate.into());
+    }
+    Ok(())
+}
+
+fn get_pool_mint(stake_pool: &StakePool, pool_mint_info: &KeyedAccount) -> Result<Mint, InstructionError> {
+    if *pool_mint_info.unsigned_key() != stake_pool.pool_mint
+        || pool_mint_info.owner()? != mundis_token_program::id()
+    {
+        return Err(StakePoolError::InvalidMint.into());
+    }
+    Mint::unpack(pool_mint_info.try_account_ref()?.data())
+}
+
+/// Lamports in the reserve that are neither needed for rent nor owed to
+/// withdrawal ticket holders
+fn available_reserve_lamports(
+    stake_pool: &StakePool,
+    reserve_info: &KeyedAccount,
+    reserve_rent: u64,
+) -> Result<u64, InstructionError> {
+    Ok(reserve_info
+        .lamports()?
+        .saturating_sub(reserve_rent)
+        .saturating_sub(stake_pool.pending_withdrawal_lamports))
+}
+
+/// Pool tokens of a withdrawal of `pool_tokens` from `source` that go to the
+/// manager. The manager withdraws fee-free, everyone else pays the withdrawal
+/// fee, whether the withdrawal is instant or delayed
+fn withdrawal_fee_pool_tokens(
+    stake_pool: &StakePool,
+    source: &Pubkey,
+    pool_tokens: u64,
+) -> Result<u64, InstructionError> {
+    if *source == stake_pool.manager_fee_account {
+        return Ok(0);
+    }
+    Ok(stake_pool
+        .withdrawal_fee
+        .apply(pool_tokens)
+        .ok_or(StakePoolError::Overflow)?)
+}
+
+/// Classifies a pool stake account for `UpdateStakePool`
+enum StakeAccountStatus {
+    /// The account does not exist
+    Empty,
+    /// Fully active and delegated
+    Active,
+    /// Initialized but not delegated, or fully deactivated
+    Inactive,
+    /// Activating or deactivating
+    Transient,
+}
+
+fn get_stake_account_status(
+    stake_info: &KeyedAccount,
+    epoch: Epoch,
+    stake_history: &StakeHistory,
+) -> Result<StakeAccountStatus, InstructionError> {
+    if stake_info.lamports()? == 0 {
+        return Ok(StakeAccountStatus::Empty);
+    }
+    if stake_info.owner()? != stake::program::id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    match stake_info.state()? {
+        StakeState::Initialized(_) => Ok(StakeAccountStatus::Inactive),
+        StakeState::Stake(_, stake) => {
+            let status = stake
+                .delegation
+                .stake_activating_and_deactivating(epoch, Some(stake_history));
+            match (status.effective, status.activating, status.deactivating) {
+                (0, 0, 0) if stake.delegation.deactivation_epoch != Epoch::MAX => {
+                    Ok(StakeAccountStatus::Inactive)
+                }
+                (_, 0, 0) if stake.delegation.deactivation_epoch == Epoch::MAX => {
+                    Ok(StakeAccountStatus::Active)
+                }
+                _ => Ok(StakeAccountStatus::Transient),
+            }
+        }
+        _ => Err(InstructionError::InvalidAccountData),
+    }
+}
+
+pub struct Processor {}
+
+impl Processor {
+    fn process(
+        first_instruction_account: usize,
+        data: &[u8],
+        invoke_context: &mut InvokeContext,
+    ) -> Result<(), InstructionError> {
+        match limited_deserialize(data)? {
+            StakePoolInstruction::Initialize {
+                epoch_fee,
+                withdrawal_fee,
+                max_validators,
+            } => {
+                ic_msg!(invoke_context, "Instruction: Initialize");
+                Self::process_initialize(
+                    invoke_context,
+                    first_instruction_account,
+                    epoch_fee,
+                    withdrawal_fee,
+                    max_validators,
+                )
+            }
+            StakePoolInstruction::AddValidator => {
+                ic_msg!(invoke_context, "Instruction: AddValidator");
+                Self::process_add_validator(invoke_context, first_instruction_account)
+            }
+            StakePoolInstruction::RemoveValidator => {
+                ic_msg!(invoke_context, "Instruction: RemoveValidator");
+                Self::process_remove_validator(invoke_context, first_instruction_account)
+            }
+            StakePoolInstruction::IncreaseValidatorStake { lamports } => {
+                ic_msg!(invoke_context, "Instruction: IncreaseValidatorStake");
+                Self::process_increase_validator_stake(invoke_context, first_instruction_account, lamports)
+            }
+            StakePoolInstruction::DecreaseValidatorStake { lamports } => {
+                ic_msg!(invoke_context, "Instruction: DecreaseValidatorStake");
+                Self::process_decrease_validator_stake(invoke_context, first_instruction_account, lamports)
+            }
+            StakePoolInstruction::UpdateStakePool => {
+                ic_msg!(invoke_context, "Instruction: UpdateStakePool");
+                Self::process_update_stake_pool(invoke_context, first_instruction_account)
+            }
+            StakePoolInstruction::DepositMundis { lamports } => {
+                ic_msg!(invoke_context, "Instruction: DepositMundis");
+                Self::process_deposit_mundis(invoke_context, first_instruction_account, lamports)
+            }
+            StakePoolInstruction::WithdrawInstant { pool_tokens } => {
+                ic_msg!(invoke_context, "Instruction: WithdrawInstant");
+                Self::process_withdraw_instant(invoke_context, first_instruction_account, pool_tokens)
+            }
+            StakePoolInstruction::RequestWithdrawal { pool_tokens } => {
+                ic_msg!(invoke_context, "Instruction: RequestWithdrawal");
+                Self::process_request_withdrawal(invoke_context, first_instruction_account, pool_tokens)
+            }
+            StakePoolInstruction::ClaimWithdrawal => {
+                ic_msg!(invoke_context, "Instruction: ClaimWithdrawal");
+                Self::process_claim_withdrawal(invoke_context, first_instruction_account)
+            }
+            StakePoolInstruction::SetFee { fee_type, fee } => {
+                ic_msg!(invoke_context, "Instruction: SetFee");
+                Self::process_set_fee(invoke_context, first_instruction_account, fee_type, fee)
+            }
+            StakePoolInstruction::SetStaker => {
+                ic_msg!(invoke_context, "Instruction: SetStaker");
+                Self::process_set_staker(invoke_context, first_instruction_account)
+            }
+            StakePoolInstruction::SetManager => {
+                ic_msg!(invoke_context, "Instruction: SetManager");
+                Self::process_set_manager(invoke_context, first_instruction_account)
+            }
+        }
+    }
+
+    fn process_initialize(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        epoch_fee: Fee,
+        withdrawal_fee: Fee,
+        max_validators: u32,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let manager_info = next_keyed_account(accounts_iter)?;
+        let staker_info = next_keyed_account(accounts_iter)?;
+        let withdraw_authority_info = next_keyed_account(accounts_iter)?;
+        let reserve_info = next_keyed_account(accounts_iter)?;
+        let pool_mint_info = next_keyed_account(accounts_iter)?;
+        let manager_fee_info = next_keyed_account(accounts_iter)?;
+
+        if stake_pool_info.owner()? != crate::id() {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        if StakePoolState::deserialize(stake_pool_info.try_account_ref()?.data())?
+            != StakePoolState::Uninitialized
+        {
+            return Err(StakePoolError::AlreadyInUse.into());
+        }
+        if stake_pool_info.data_len()? < StakePoolState::stake_pool_size_of(max_validators) {
+            ic_msg!(invoke_context, "Stake pool account is too small for {} validators", max_validators);
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        let manager = *manager_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        if !epoch_fee.is_valid() || !withdrawal_fee.is_valid() {
+            return Err(StakePoolError::FeeTooHigh.into());
+        }
+
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let (withdraw_authority, _) = find_withdraw_authority_program_address(&stake_pool_key);
+        check_address(invoke_context, withdraw_authority_info, &withdraw_authority)?;
+        let (reserve, _) = find_reserve_program_address(&stake_pool_key);
+        check_address(invoke_context, reserve_info, &reserve)?;
+
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        if !rent.is_exempt(stake_pool_info.lamports()?, stake_pool_info.data_len()?) {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        if reserve_info.owner()? != system_program::id()
+            || reserve_info.lamports()? < rent.minimum_balance(0)
+        {
+            ic_msg!(invoke_context, "Reserve must be a rent-exempt system account");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        if pool_mint_info.owner()? != mundis_token_program::id() {
+            return Err(StakePoolError::InvalidMint.into());
+        }
+        let pool_mint = Mint::unpack(pool_mint_info.try_account_ref()?.data())?;
+        if pool_mint.mint_authority != Some(withdraw_authority) || pool_mint.supply != 0 {
+            ic_msg!(invoke_context, "Pool mint must be empty and minted by the withdraw authority");
+            return Err(StakePoolError::InvalidMint.into());
+        }
+        if pool_mint.freeze_authority.is_some() {
+            ic_msg!(invoke_context, "Pool mint must not have a freeze authority");
+            return Err(StakePoolError::InvalidMint.into());
+        }
+
+        if manager_fee_info.owner()? != mundis_token_program::id() {
+            return Err(StakePoolError::InvalidFeeAccount.into());
+        }
+        let manager_fee_account = TokenAccount::unpack(manager_fee_info.try_account_ref()?.data())?;
+        if manager_fee_account.mint != *pool_mint_info.unsigned_key() {
+            return Err(StakePoolError::InvalidFeeAccount.into());
+        }
+
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        stake_pool_info.set_state(&StakePoolState::StakePool(StakePool {
+            manager,
+            staker: *staker_info.unsigned_key(),
+            pool_mint: *pool_mint_info.unsigned_key(),
+            manager_fee_account: *manager_fee_info.unsigned_key(),
+            total_lamports: 0,
+            pool_token_supply: 0,
+            pending_withdrawal_lamports: 0,
+            last_update_epoch: clock.epoch,
+            epoch_fee,
+            withdrawal_fee,
+            max_validators,
+            validators: vec![],
+        }))
+    }
+
+    fn process_add_validator(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let staker_info = next_keyed_account(accounts_iter)?;
+        let vote_account_info = next_keyed_account(accounts_iter)?;
+
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        check_staker(&stake_pool, staker_info)?;
+        if vote_account_info.owner()? != mundis_sdk::vote::program::id() {
+            ic_msg!(invoke_context, "{} is not a vote account", vote_account_info.unsigned_key());
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        let vote_account = *vote_account_info.unsigned_key();
+        if stake_pool.find_validator(&vote_account).is_some() {
+            return Err(StakePoolError::ValidatorAlreadyAdded.into());
+        }
+        if stake_pool.validators.len() >= stake_pool.max_validators as usize {
+            return Err(StakePoolError::ValidatorListFull.into());
+        }
+
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        stake_pool.validators.push(ValidatorStakeInfo {
+            vote_account,
+            active_stake_lamports: 0,
+            transient_stake_lamports: 0,
+            last_update_epoch: clock.epoch,
+        });
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+
+    fn process_remove_validator(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let staker_info = next_keyed_account(accounts_iter)?;
+        let vote_account_info = next_keyed_account(accounts_iter)?;
+        let validator_stake_info = next_keyed_account(accounts_iter)?;
+        let transient_stake_info = next_keyed_account(accounts_iter)?;
+
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        check_staker(&stake_pool, staker_info)?;
+        let stake_pool_key = stake_pool_info.unsigned_key();
+        let vote_account = vote_account_info.unsigned_key();
+        check_address(
+            invoke_context,
+            validator_stake_info,
+            &find_validator_stake_program_address(vote_account, stake_pool_key).0,
+        )?;
+        check_address(
+            invoke_context,
+            transient_stake_info,
+            &find_transient_stake_program_address(vote_account, stake_pool_key).0,
+        )?;
+
+        let validator = stake_pool
+            .find_validator(vote_account)
+            .ok_or(StakePoolError::ValidatorNotFound)?;
+        if validator.stake_lamports() != 0
+            || validator_stake_info.lamports()? != 0
+            || transient_stake_info.lamports()? != 0
+        {
+            return Err(StakePoolError::ValidatorHasStake.into());
+        }
+
+        stake_pool
+            .validators
+            .retain(|info| info.vote_account != *vote_account);
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+
+    fn process_increase_validator_stake(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        lamports: u64,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let staker_info = next_keyed_account(accounts_iter)?;
+        let withdraw_authority_info = next_keyed_account(accounts_iter)?;
+        let reserve_info = next_keyed_account(accounts_iter)?;
+        let validator_stake_info = next_keyed_account(accounts_iter)?;
+        let transient_stake_info = next_keyed_account(accounts_iter)?;
+        let vote_account_info = next_keyed_account(accounts_iter)?;
+        let _clock_info = next_keyed_account(accounts_iter)?;
+        let _rent_info = next_keyed_account(accounts_iter)?;
+        let _stake_history_info = next_keyed_account(accounts_iter)?;
+        let _stake_config_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+        let stake_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        check_program(stake_program_info, &stake::program::id())?;
+        let stake_pool = get_stake_pool(stake_pool_info)?;
+        check_staker(&stake_pool, staker_info)?;
+
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let vote_account = *vote_account_info.unsigned_key();
+        let withdraw_authority = find_withdraw_authority_program_address(&stake_pool_key).0;
+        let reserve = find_reserve_program_address(&stake_pool_key).0;
+        let validator_stake = find_validator_stake_program_address(&vote_account, &stake_pool_key).0;
+        let transient_stake = find_transient_stake_program_address(&vote_account, &stake_pool_key).0;
+        check_address(invoke_context, withdraw_authority_info, &withdraw_authority)?;
+        check_address(invoke_context, reserve_info, &reserve)?;
+        check_address(invoke_context, validator_stake_info, &validator_stake)?;
+        check_address(invoke_context, transient_stake_info, &transient_stake)?;
+        if stake_pool.find_validator(&vote_account).is_none() {
+            return Err(StakePoolError::ValidatorNotFound.into());
+        }
+
+        // Stake goes into the validator stake account when the pool has none
+        // delegated yet, otherwise into the transient account until it is
+        // active and can be merged
+        let stake_account = if validator_stake_info.lamports()? == 0 {
+            validator_stake
+        } else if transient_stake_info.lamports()? == 0 {
+            transient_stake
+        } else {
+            return Err(StakePoolError::TransientAccountInUse.into());
+        };
+
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let stake_rent = rent.minimum_balance(std::mem::size_of::<StakeState>());
+        if lamports <= stake_rent {
+            ic_msg!(invoke_context, "Need more than {} lamports for the stake account", stake_rent);
+            return Err(StakePoolError::AmountTooSmall.into());
+        }
+        let available = available_reserve_lamports(&stake_pool, reserve_info, rent.minimum_balance(0))?;
+        if lamports > available {
+            ic_msg!(invoke_context, "Reserve has {} lamports available, {} requested", available, lamports);
+            return Err(StakePoolError::InsufficientReserve.into());
+        }
+
+        invoke_context.native_invoke(
+            system_instruction::create_account(
+                &reserve,
+                &stake_account,
+                lamports,
+                std::mem::size_of::<StakeState>() as u64,
+                &stake::program::id(),
+            ),
+            &[reserve, stake_account],
+        )?;
+        invoke_context.native_invoke(
+            stake::instruction::initialize(
+                &stake_account,
+                &Authorized::auto(&withdraw_authority),
+                &Lockup::default(),
+            ),
+            &[],
+        )?;
+        invoke_context.native_invoke(
+            stake::instruction::delegate_stake(&stake_account, &withdraw_authority, &vote_account),
+            &[withdraw_authority],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let stake_pool_info = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        let validator = stake_pool
+            .find_validator_mut(&vote_account)
+            .ok_or(StakePoolError::ValidatorNotFound)?;
+        if stake_account == validator_stake {
+            validator.active_stake_lamports = checked_add(validator.active_stake_lamports, lamports)?;
+        } else {
+            validator.transient_stake_lamports = checked_add(validator.transient_stake_lamports, lamports)?;
+        }
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+
+    fn process_decrease_validator_stake(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        lamports: u64,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let staker_info = next_keyed_account(accounts_iter)?;
+        let withdraw_authority_info = next_keyed_account(accounts_iter)?;
+        let validator_stake_info = next_keyed_account(accounts_iter)?;
+        let transient_stake_info = next_keyed_account(accounts_iter)?;
+        let _clock_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+        let stake_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        check_program(stake_program_info, &stake::program::id())?;
+        let stake_pool = get_stake_pool(stake_pool_info)?;
+        check_staker(&stake_pool, staker_info)?;
+
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let withdraw_authority = find_withdraw_authority_program_address(&stake_pool_key).0;
+        check_address(invoke_context, withdraw_authority_info, &withdraw_authority)?;
+        let validator_stake = *validator_stake_info.unsigned_key();
+        let transient_stake = *transient_stake_info.unsigned_key();
+        let vote_account = match validator_stake_info.state()? {
+            StakeState::Stake(_, stake) => stake.delegation.voter_pubkey,
+            _ => return Err(InstructionError::InvalidAccountData),
+        };
+        if stake_pool.find_validator(&vote_account).is_none() {
+            return Err(StakePoolError::ValidatorNotFound.into());
+        }
+        check_address(
+            invoke_context,
+            validator_stake_info,
+            &find_validator_stake_program_address(&vote_account, &stake_pool_key).0,
+        )?;
+        check_address(
+            invoke_context,
+            transient_stake_info,
+            &find_transient_stake_program_address(&vote_account, &stake_pool_key).0,
+        )?;
+        if transient_stake_info.lamports()? != 0 {
+            return Err(StakePoolError::TransientAccountInUse.into());
+        }
+
+        for instruction in stake::instruction::split(
+            &validator_stake,
+            &withdraw_authority,
+            lamports,
+            &transient_stake,
+        ) {
+            invoke_context.native_invoke(instruction, &[transient_stake, withdraw_authority])?;
+        }
+        invoke_context.native_invoke(
+            stake::instruction::deactivate_stake(&transient_stake, &withdraw_authority),
+            &[withdraw_authority],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let stake_pool_info = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        let validator = stake_pool
+            .find_validator_mut(&vote_account)
+            .ok_or(StakePoolError::ValidatorNotFound)?;
+        validator.active_stake_lamports = validator.active_stake_lamports.saturating_sub(lamports);
+        validator.transient_stake_lamports = checked_add(validator.transient_stake_lamports, lamports)?;
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+
+    fn process_update_stake_pool(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let withdraw_authority_info = next_keyed_account(accounts_iter)?;
+        let reserve_info = next_keyed_account(accounts_iter)?;
+        let manager_fee_info = next_keyed_account(accounts_iter)?;
+        let pool_mint_info = next_keyed_account(accounts_iter)?;
+        let _clock_info = next_keyed_account(accounts_iter)?;
+        let stake_history_info = next_keyed_account(accounts_iter)?;
+        let stake_program_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(stake_program_info, &stake::program::id())?;
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let stake_pool = get_stake_pool(stake_pool_info)?;
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let withdraw_authority = find_withdraw_authority_program_address(&stake_pool_key).0;
+        let reserve = find_reserve_program_address(&stake_pool_key).0;
+        check_address(invoke_context, withdraw_authority_info, &withdraw_authority)?;
+        check_address(invoke_context, reserve_info, &reserve)?;
+        check_address(invoke_context, manager_fee_info, &stake_pool.manager_fee_account)?;
+        get_pool_mint(&stake_pool, pool_mint_info)?;
+
+        let stake_history = from_keyed_account::<StakeHistory>(stake_history_info)?;
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+
+        let validator_accounts = accounts_iter.as_slice();
+        if validator_accounts.len() != stake_pool.validators.len().saturating_mul(2) {
+            ic_msg!(
+                invoke_context,
+                "Expected a validator and transient stake account for each of {} validators",
+                stake_pool.validators.len()
+            );
+            return Err(InstructionError::NotEnoughAccountKeys);
+        }
+
+        // Decide what happens to each validator's stake accounts before
+        // invoking the stake program, which needs the accounts unborrowed.
+        // Inactive stake accounts are closed: their whole balance, including
+        // the rent-exempt reserve the pool's reserve paid when creating them,
+        // goes back to the reserve, so the address is free for the next
+        // `IncreaseValidatorStake`
+        let mut merges = vec![];
+        let mut closures = vec![];
+        for (validator, stake_infos) in stake_pool
+            .validators
+            .iter()
+            .zip(validator_accounts.chunks(2))
+        {
+            let (validator_stake_info, transient_stake_info) = (&stake_infos[0], &stake_infos[1]);
+            let vote_account = &validator.vote_account;
+            check_address(
+                invoke_context,
+                validator_stake_info,
+                &find_validator_stake_program_address(vote_account, &stake_pool_key).0,
+            )?;
+            check_address(
+                invoke_context,
+                transient_stake_info,
+                &find_transient_stake_program_address(vote_account, &stake_pool_key).0,
+            )?;
+            let validator_status = get_stake_account_status(validator_stake_info, clock.epoch, &stake_history)?;
+            let transient_status = get_stake_account_status(transient_stake_info, clock.epoch, &stake_history)?;
+
+            match (&validator_status, transient_status) {
+                (StakeAccountStatus::Active, StakeAccountStatus::Active) => merges.push((
+                    *validator_stake_info.unsigned_key(),
+                    *transient_stake_info.unsigned_key(),
+                )),
+                (_, StakeAccountStatus::Inactive) => closures.push((
+                    *transient_stake_info.unsigned_key(),
+                    transient_stake_info.lamports()?,
+                )),
+                _ => {}
+            }
+            if let StakeAccountStatus::Inactive = validator_status {
+                closures.push((
+                    *validator_stake_info.unsigned_key(),
+                    validator_stake_info.lamports()?,
+                ));
+            }
+        }
+
+        for (validator_stake, transient_stake) in merges {
+            for instruction in stake::instruction::merge(&validator_stake, &transient_stake, &withdraw_authority) {
+                invoke_context.native_invoke(instruction, &[withdraw_authority])?;
+            }
+        }
+        for (stake_account, lamports) in closures {
+            invoke_context.native_invoke(
+                stake::instruction::withdraw(&stake_account, &withdraw_authority, &reserve, lamports, None),
+                &[withdraw_authority],
+            )?;
+            // The stake program deinitializes a stake account withdrawn to zero
+            let keyed_accounts = invoke_context.get_keyed_accounts()?;
+            let stake_info = keyed_accounts
+                .iter()
+                .find(|keyed_account| *keyed_account.unsigned_key() == stake_account)
+                .ok_or(InstructionError::NotEnoughAccountKeys)?;
+            if stake_info.lamports()? != 0 {
+                ic_msg!(invoke_context, "Stake account {} was not closed", stake_account);
+                return Err(InstructionError::InvalidAccountData);
+            }
+        }
+
+        // Recompute balances from the stake accounts
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts = &keyed_accounts[first_instruction_account..];
+        let stake_pool_info = &accounts[0];
+        let reserve_info = &accounts[2];
+        let pool_mint_info = &accounts[4];
+        let validator_accounts = &accounts[9..];
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        for (validator, stake_infos) in stake_pool
+            .validators
+            .iter_mut()
+            .zip(validator_accounts.chunks(2))
+        {
+            validator.active_stake_lamports = stake_infos[0].lamports()?;
+            validator.transient_stake_lamports = stake_infos[1].lamports()?;
+            validator.last_update_epoch = clock.epoch;
+        }
+
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let reserve_lamports = available_reserve_lamports(&stake_pool, reserve_info, rent.minimum_balance(0))?;
+        let total_lamports = checked_add(reserve_lamports, stake_pool.total_stake_lamports())?;
+        let pool_token_supply = get_pool_mint(&stake_pool, pool_mint_info)?.supply;
+        let reward_lamports = total_lamports.saturating_sub(stake_pool.total_lamports);
+        let fee_lamports = stake_pool
+            .epoch_fee
+            .apply(reward_lamports)
+            .ok_or(StakePoolError::Overflow)?;
+
+        stake_pool.total_lamports = total_lamports;
+        stake_pool.pool_token_supply = pool_token_supply;
+        let fee_pool_tokens = stake_pool
+            .calc_fee_pool_tokens(fee_lamports)
+            .ok_or(StakePoolError::Overflow)?;
+        stake_pool.pool_token_supply = checked_add(pool_token_supply, fee_pool_tokens)?;
+        stake_pool.last_update_epoch = clock.epoch;
+        let pool_mint = stake_pool.pool_mint;
+        let manager_fee_account = stake_pool.manager_fee_account;
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))?;
+
+        if fee_pool_tokens > 0 {
+            ic_msg!(invoke_context, "Minting {} pool tokens in epoch fees", fee_pool_tokens);
+            invoke_context.native_invoke(
+                token_instruction::mint_to(
+                    &mundis_token_program::id(),
+                    &pool_mint,
+                    &manager_fee_account,
+                    &withdraw_authority,
+                    &[],
+                    fee_pool_tokens,
+                )?,
+                &[withdraw_authority],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn process_deposit_mundis(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        lamports: u64,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let withdraw_authority_info = next_keyed_account(accounts_iter)?;
+        let reserve_info = next_keyed_account(accounts_iter)?;
+        let depositor_info = next_keyed_account(accounts_iter)?;
+        let destination_info = next_keyed_account(accounts_iter)?;
+        let pool_mint_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        check_up_to_date(&stake_pool, clock.epoch)?;
+
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let withdraw_authority = find_withdraw_authority_program_address(&stake_pool_key).0;
+        let reserve = find_reserve_program_address(&stake_pool_key).0;
+        check_address(invoke_context, withdraw_authority_info, &withdraw_authority)?;
+        check_address(invoke_context, reserve_info, &reserve)?;
+        let depositor = *depositor_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let destination = *destination_info.unsigned_key();
+        stake_pool.pool_token_supply = get_pool_mint(&stake_pool, pool_mint_info)?.supply;
+
+        let pool_tokens = stake_pool
+            .calc_pool_tokens_for_deposit(lamports)
+            .ok_or(StakePoolError::Overflow)?;
+        if pool_tokens == 0 {
+            return Err(StakePoolError::AmountTooSmall.into());
+        }
+        stake_pool.total_lamports = checked_add(stake_pool.total_lamports, lamports)?;
+        stake_pool.pool_token_supply = checked_add(stake_pool.pool_token_supply, pool_tokens)?;
+        let pool_mint = stake_pool.pool_mint;
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))?;
+
+        invoke_context.native_invoke(
+            system_instruction::transfer(&depositor, &reserve, lamports),
+            &[],
+        )?;
+        invoke_context.native_invoke(
+            token_instruction::mint_to(
+                &mundis_token_program::id(),
+                &pool_mint,
+                &destination,
+                &withdraw_authority,
+                &[],
+                pool_tokens,
+            )?,
+            &[withdraw_authority],
+        )
+    }
+
+    fn process_withdraw_instant(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        pool_tokens: u64,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let withdraw_authority_info = next_keyed_account(accounts_iter)?;
+        let reserve_info = next_keyed_account(accounts_iter)?;
+        let source_info = next_keyed_account(accounts_iter)?;
+        let source_authority_info = next_keyed_account(accounts_iter)?;
+        let destination_info = next_keyed_account(accounts_iter)?;
+        let manager_fee_info = next_keyed_account(accounts_iter)?;
+        let pool_mint_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        check_up_to_date(&stake_pool, clock.epoch)?;
+
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let withdraw_authority = find_withdraw_authority_program_address(&stake_pool_key).0;
+        let reserve = find_reserve_program_address(&stake_pool_key).0;
+        check_address(invoke_context, withdraw_authority_info, &withdraw_authority)?;
+        check_address(invoke_context, reserve_info, &reserve)?;
+        check_address(invoke_context, manager_fee_info, &stake_pool.manager_fee_account)?;
+        let source = *source_info.unsigned_key();
+        let source_authority = *source_authority_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let destination = *destination_info.unsigned_key();
+        stake_pool.pool_token_supply = get_pool_mint(&stake_pool, pool_mint_info)?.supply;
+
+        let fee_pool_tokens = withdrawal_fee_pool_tokens(&stake_pool, &source, pool_tokens)?;
+        let burn_pool_tokens = checked_sub(pool_tokens, fee_pool_tokens)?;
+        let lamports = stake_pool
+            .calc_lamports_withdraw_amount(burn_pool_tokens)
+            .ok_or(StakePoolError::Overflow)?;
+        if lamports == 0 {
+            return Err(StakePoolError::AmountTooSmall.into());
+        }
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let available = available_reserve_lamports(&stake_pool, reserve_info, rent.minimum_balance(0))?;
+        if lamports > available {
+            ic_msg!(
+                invoke_context,
+                "Reserve has {} lamports available, {} requested; request a delayed withdrawal instead",
+                available,
+                lamports
+            );
+            return Err(StakePoolError::InsufficientReserve.into());
+        }
+
+        stake_pool.total_lamports = checked_sub(stake_pool.total_lamports, lamports)?;
+        stake_pool.pool_token_supply = checked_sub(stake_pool.pool_token_supply, burn_pool_tokens)?;
+        let pool_mint = stake_pool.pool_mint;
+        let manager_fee_account = stake_pool.manager_fee_account;
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))?;
+
+        if fee_pool_tokens > 0 {
+            invoke_context.native_invoke(
+                token_instruction::transfer(
+                    &mundis_token_program::id(),
+                    &source,
+                    &manager_fee_account,
+                    &source_authority,
+                    &[],
+                    fee_pool_tokens,
+                )?,
+                &[],
+            )?;
+        }
+        invoke_context.native_invoke(
+            token_instruction::burn(
+                &mundis_token_program::id(),
+                &source,
+                &pool_mint,
+                &source_authority,
+                &[],
+                burn_pool_tokens,
+            )?,
+            &[],
+        )?;
+        invoke_context.native_invoke(
+            system_instruction::transfer(&reserve, &destination, lamports),
+            &[reserve],
+        )
+    }
+
+    fn process_request_withdrawal(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        pool_tokens: u64,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let ticket_info = next_keyed_account(accounts_iter)?;
+        let source_info = next_keyed_account(accounts_iter)?;
+        let source_authority_info = next_keyed_account(accounts_iter)?;
+        let beneficiary_info = next_keyed_account(accounts_iter)?;
+        let manager_fee_info = next_keyed_account(accounts_iter)?;
+        let pool_mint_info = next_keyed_account(accounts_iter)?;
+        let token_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(token_program_info, &mundis_token_program::id())?;
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        check_up_to_date(&stake_pool, clock.epoch)?;
+        check_address(invoke_context, manager_fee_info, &stake_pool.manager_fee_account)?;
+
+        if ticket_info.owner()? != crate::id() {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        if StakePoolState::deserialize(ticket_info.try_account_ref()?.data())?
+            != StakePoolState::Uninitialized
+        {
+            return Err(StakePoolError::AlreadyInUse.into());
+        }
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        if !rent.is_exempt(ticket_info.lamports()?, ticket_info.data_len()?) {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        let source = *source_info.unsigned_key();
+        let source_authority = *source_authority_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        stake_pool.pool_token_supply = get_pool_mint(&stake_pool, pool_mint_info)?.supply;
+
+        let fee_pool_tokens = withdrawal_fee_pool_tokens(&stake_pool, &source, pool_tokens)?;
+        let burn_pool_tokens = checked_sub(pool_tokens, fee_pool_tokens)?;
+        let lamports = stake_pool
+            .calc_lamports_withdraw_amount(burn_pool_tokens)
+            .ok_or(StakePoolError::Overflow)?;
+        if lamports == 0 {
+            return Err(StakePoolError::AmountTooSmall.into());
+        }
+        ticket_info.set_state(&StakePoolState::WithdrawalTicket(WithdrawalTicket {
+            stake_pool: *stake_pool_info.unsigned_key(),
+            beneficiary: *beneficiary_info.unsigned_key(),
+            lamports,
+            claimable_epoch: clock.epoch.saturating_add(1),
+        }))?;
+
+        stake_pool.total_lamports = checked_sub(stake_pool.total_lamports, lamports)?;
+        stake_pool.pool_token_supply = checked_sub(stake_pool.pool_token_supply, burn_pool_tokens)?;
+        stake_pool.pending_withdrawal_lamports =
+            checked_add(stake_pool.pending_withdrawal_lamports, lamports)?;
+        let pool_mint = stake_pool.pool_mint;
+        let manager_fee_account = stake_pool.manager_fee_account;
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))?;
+
+        if fee_pool_tokens > 0 {
+            invoke_context.native_invoke(
+                token_instruction::transfer(
+                    &mundis_token_program::id(),
+                    &source,
+                    &manager_fee_account,
+                    &source_authority,
+                    &[],
+                    fee_pool_tokens,
+                )?,
+                &[],
+            )?;
+        }
+        invoke_context.native_invoke(
+            token_instruction::burn(
+                &mundis_token_program::id(),
+                &source,
+                &pool_mint,
+                &source_authority,
+                &[],
+                burn_pool_tokens,
+            )?,
+            &[],
+        )
+    }
+
+    fn process_claim_withdrawal(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let ticket_info = next_keyed_account(accounts_iter)?;
+        let reserve_info = next_keyed_account(accounts_iter)?;
+        let beneficiary_info = next_keyed_account(accounts_iter)?;
+        let destination_info = next_keyed_account(accounts_iter)?;
+        let system_program_info = next_keyed_account(accounts_iter)?;
+
+        check_program(system_program_info, &system_program::id())?;
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        let stake_pool_key = *stake_pool_info.unsigned_key();
+        let reserve = find_reserve_program_address(&stake_pool_key).0;
+        check_address(invoke_context, reserve_info, &reserve)?;
+
+        if ticket_info.owner()? != crate::id() {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        let ticket = StakePoolState::deserialize(ticket_info.try_account_ref()?.data())?
+            .withdrawal_ticket()
+            .ok_or(StakePoolError::UninitializedState)?;
+        if ticket.stake_pool != stake_pool_key {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if beneficiary_info.signer_key() != Some(&ticket.beneficiary) {
+            return Err(StakePoolError::WrongBeneficiary.into());
+        }
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+        if clock.epoch < ticket.claimable_epoch {
+            ic_msg!(invoke_context, "Ticket is claimable from epoch {}", ticket.claimable_epoch);
+            return Err(StakePoolError::TicketNotClaimable.into());
+        }
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let reserve_lamports = reserve_info.lamports()?.saturating_sub(rent.minimum_balance(0));
+        if reserve_lamports < ticket.lamports {
+            ic_msg!(invoke_context, "Reserve has {} lamports, the staker must deactivate more stake", reserve_lamports);
+            return Err(StakePoolError::InsufficientReserve.into());
+        }
+
+        // Close the ticket, returning its rent to the destination
+        let ticket_lamports = ticket_info.lamports()?;
+        ticket_info.set_state(&StakePoolState::Uninitialized)?;
+        ticket_info.try_account_ref_mut()?.set_lamports(0);
+        destination_info
+            .try_account_ref_mut()?
+            .checked_add_lamports(ticket_lamports)?;
+
+        stake_pool.pending_withdrawal_lamports =
+            checked_sub(stake_pool.pending_withdrawal_lamports, ticket.lamports)?;
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))?;
+        let destination = *destination_info.unsigned_key();
+
+        invoke_context.native_invoke(
+            system_instruction::transfer(&reserve, &destination, ticket.lamports),
+            &[reserve],
+        )
+    }
+
+    fn process_set_fee(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        fee_type: FeeType,
+        fee: Fee,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let manager_info = next_keyed_account(accounts_iter)?;
+
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        check_manager(&stake_pool, manager_info)?;
+        if !fee.is_valid() {
+            return Err(StakePoolError::FeeTooHigh.into());
+        }
+        match fee_type {
+            FeeType::Epoch => stake_pool.epoch_fee = fee,
+            FeeType::Withdrawal => stake_pool.withdrawal_fee = fee,
+        }
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+
+    fn process_set_staker(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let authority_info = next_keyed_account(accounts_iter)?;
+        let new_staker_info = next_keyed_account(accounts_iter)?;
+
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        if check_manager(&stake_pool, authority_info).is_err() {
+            check_staker(&stake_pool, authority_info)?;
+        }
+        stake_pool.staker = *new_staker_info.unsigned_key();
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+
+    fn process_set_manager(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let accounts_iter = &mut keyed_accounts[first_instruction_account..].iter();
+        let stake_pool_info = next_keyed_account(accounts_iter)?;
+        let manager_info = next_keyed_account(accounts_iter)?;
+        let new_manager_info = next_keyed_account(accounts_iter)?;
+        let new_manager_fee_info = next_keyed_account(accounts_iter)?;
+
+        let mut stake_pool = get_stake_pool(stake_pool_info)?;
+        check_manager(&stake_pool, manager_info)?;
+        let new_manager = *new_manager_info
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        if new_manager_fee_info.owner()? != mundis_token_program::id() {
+            return Err(StakePoolError::InvalidFeeAccount.into());
+        }
+        let new_manager_fee_account = TokenAccount::unpack(new_manager_fee_info.try_account_ref()?.data())?;
+        if new_manager_fee_account.mint != stake_pool.pool_mint {
+            return Err(StakePoolError::InvalidFeeAccount.into());
+        }
+        stake_pool.manager = new_manager;
+        stake_pool.manager_fee_account = *new_manager_fee_info.unsigned_key();
+        stake_pool_info.set_state(&StakePoolState::StakePool(stake_pool))
+    }
+}
@@ -0,0 +1,283 @@
+//! State transition types
+
+use {
+    mundis_sdk::{clock::Epoch, instruction::InstructionError, pubkey::Pubkey},
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Fee rate as a ratio, e.g. 3/100 is a 3% fee
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Fee {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl Fee {
+    /// A fee is valid when it is zero or its ratio does not exceed one
+    pub fn is_valid(&self) -> bool {
+        self.numerator == 0 || (self.denominator != 0 && self.numerator <= self.denominator)
+    }
+
+    /// Applies the fee's rate to an amount, rounding down
+    pub fn apply(&self, amount: u64) -> Option<u64> {
+        if self.numerator == 0 || self.denominator == 0 {
+            return Some(0);
+        }
+        u64::try_from(
+            (amount as u128)
+                .checked_mul(self.numerator as u128)?
+                .checked_div(self.denominator as u128)?,
+        )
+        .ok()
+    }
+}
+
+/// The fee a pool manager can update
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FeeType {
+    /// Taken from staking rewards every epoch
+    Epoch,
+    /// Taken from pool tokens on instant and delayed withdrawals
+    Withdrawal,
+}
+
+/// Stake held by the pool for a single validator
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct ValidatorStakeInfo {
+    /// Vote account the pool delegates to
+    pub vote_account: Pubkey,
+    /// Lamports in the validator's main stake account
+    pub active_stake_lamports: u64,
+    /// Lamports in the validator's activating or deactivating stake account
+    pub transient_stake_lamports: u64,
+    /// Last epoch the balances were updated
+    pub last_update_epoch: Epoch,
+}
+
+impl ValidatorStakeInfo {
+    pub fn stake_lamports(&self) -> u64 {
+        self.active_stake_lamports
+            .saturating_add(self.transient_stake_lamports)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct StakePool {
+    /// Manager authority, receives fees and curates fee rates
+    pub manager: Pubkey,
+    /// Staker authority, curates the validator list and moves stake
+    pub staker: Pubkey,
+    /// Mint of the pool token
+    pub pool_mint: Pubkey,
+    /// Pool token account that receives manager fees
+    pub manager_fee_account: Pubkey,
+    /// Lamports backing the outstanding pool tokens
+    pub total_lamports: u64,
+    /// Pool token supply at the last update
+    pub pool_token_supply: u64,
+    /// Lamports owed to holders of unclaimed withdrawal tickets
+    pub pending_withdrawal_lamports: u64,
+    /// Last epoch `total_lamports` was updated
+    pub last_update_epoch: Epoch,
+    /// Fee taken from staking rewards
+    pub epoch_fee: Fee,
+    /// Fee taken from instant and delayed withdrawals
+    pub withdrawal_fee: Fee,
+    /// Maximum number of validators, fixes the size of the account
+    pub max_validators: u32,
+    /// Validators the pool delegates to
+    pub validators: Vec<ValidatorStakeInfo>,
+}
+
+impl StakePool {
+    /// Calculates the pool tokens that should be minted for a deposit of `lamports`
+    pub fn calc_pool_tokens_for_deposit(&self, lamports: u64) -> Option<u64> {
+        if self.total_lamports == 0 || self.pool_token_supply == 0 {
+            return Some(lamports);
+        }
+        u64::try_from(
+            (lamports as u128)
+                .checked_mul(self.pool_token_supply as u128)?
+                .checked_div(self.total_lamports as u128)?,
+        )
+        .ok()
+    }
+
+    /// Calculates the lamports owed for burning `pool_tokens`
+    pub fn calc_lamports_withdraw_amount(&self, pool_tokens: u64) -> Option<u64> {
+        if self.pool_token_supply == 0 {
+            return None;
+        }
+        u64::try_from(
+            (pool_tokens as u128)
+                .checked_mul(self.total_lamports as u128)?
+                .checked_div(self.pool_token_supply as u128)?,
+        )
+        .ok()
+    }
+
+    /// Calculates the pool tokens to mint so the manager receives `fee_lamports`
+    /// worth of a pool now holding `total_lamports`
+    pub fn calc_fee_pool_tokens(&self, fee_lamports: u64) -> Option<u64> {
+        if fee_lamports == 0 || self.pool_token_supply == 0 {
+            return Some(0);
+        }
+        let remaining_lamports = self.total_lamports.checked_sub(fee_lamports)?;
+        if remaining_lamports == 0 {
+            return None;
+        }
+        u64::try_from(
+            (fee_lamports as u128)
+                .checked_mul(self.pool_token_supply as u128)?
+                .checked_div(remaining_lamports as u128)?,
+        )
+        .ok()
+    }
+
+    pub fn find_validator(&self, vote_account: &Pubkey) -> Option<&ValidatorStakeInfo> {
+        self.validators
+            .iter()
+            .find(|info| info.vote_account == *vote_account)
+    }
+
+    pub fn find_validator_mut(&mut self, vote_account: &Pubkey) -> Option<&mut ValidatorStakeInfo> {
+        self.validators
+            .iter_mut()
+            .find(|info| info.vote_account == *vote_account)
+    }
+
+    /// Lamports staked across all validators
+    pub fn total_stake_lamports(&self) -> u64 {
+        self.validators
+            .iter()
+            .fold(0, |acc, info| acc.saturating_add(info.stake_lamports()))
+    }
+}
+
+/// Lamports reserved for a holder who requested a delayed withdrawal
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct WithdrawalTicket {
+    /// Pool the ticket was issued by
+    pub stake_pool: Pubkey,
+    /// Authority that can claim the ticket
+    pub beneficiary: Pubkey,
+    /// Lamports owed
+    pub lamports: u64,
+    /// First epoch the ticket can be claimed
+    pub claimable_epoch: Epoch,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum StakePoolState {
+    Uninitialized,
+    StakePool(StakePool),
+    WithdrawalTicket(WithdrawalTicket),
+}
+
+impl Default for StakePoolState {
+    fn default() -> Self {
+        StakePoolState::Uninitialized
+    }
+}
+
+impl StakePoolState {
+    /// Size of a stake pool account able to hold `max_validators` validators
+    pub fn stake_pool_size_of(max_validators: u32) -> usize {
+        let stake_pool = StakePool {
+            max_validators,
+            validators: vec![ValidatorStakeInfo::default(); max_validators as usize],
+            ..StakePool::default()
+        };
+        bincode::serialized_size(&StakePoolState::StakePool(stake_pool)).unwrap() as usize
+    }
+
+    /// Size of a withdrawal ticket account
+    pub fn withdrawal_ticket_size_of() -> usize {
+        bincode::serialized_size(&StakePoolState::WithdrawalTicket(
+            WithdrawalTicket::default(),
+        ))
+        .unwrap() as usize
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, InstructionError> {
+        bincode::deserialize(data).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    pub fn stake_pool(self) -> Option<StakePool> {
+        match self {
+            StakePoolState::StakePool(stake_pool) => Some(stake_pool),
+            _ => None,
+        }
+    }
+
+    pub fn withdrawal_ticket(self) -> Option<WithdrawalTicket> {
+        match self {
+            StakePoolState::WithdrawalTicket(ticket) => Some(ticket),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee() {
+        assert!(Fee::default().is_valid());
+        assert!(Fee { numerator: 1, denominator: 100 }.is_valid());
+        assert!(!Fee { numerator: 101, denominator: 100 }.is_valid());
+        assert!(!Fee { numerator: 1, denominator: 0 }.is_valid());
+
+        assert_eq!(Fee::default().apply(1_000), Some(0));
+        assert_eq!(Fee { numerator: 3, denominator: 100 }.apply(1_000), Some(30));
+        assert_eq!(Fee { numerator: 1, denominator: 3 }.apply(10), Some(3));
+        assert_eq!(Fee { numerator: 1, denominator: 1 }.apply(u64::MAX), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_exchange_rate() {
+        let mut stake_pool = StakePool::default();
+        // empty pool mints 1:1
+        assert_eq!(stake_pool.calc_pool_tokens_for_deposit(100), Some(100));
+        assert_eq!(stake_pool.calc_lamports_withdraw_amount(100), None);
+
+        stake_pool.total_lamports = 1_100;
+        stake_pool.pool_token_supply = 1_000;
+        assert_eq!(stake_pool.calc_pool_tokens_for_deposit(110), Some(100));
+        assert_eq!(stake_pool.calc_lamports_withdraw_amount(100), Some(110));
+        assert_eq!(stake_pool.calc_lamports_withdraw_amount(1_000), Some(1_100));
+
+        // 10 of 110 lamports in rewards belong to the manager
+        stake_pool.total_lamports = 110;
+        stake_pool.pool_token_supply = 100;
+        let fee_tokens = stake_pool.calc_fee_pool_tokens(10).unwrap();
+        assert_eq!(fee_tokens, 10);
+        stake_pool.pool_token_supply += fee_tokens;
+        assert_eq!(stake_pool.calc_lamports_withdraw_amount(fee_tokens), Some(10));
+        assert_eq!(stake_pool.calc_fee_pool_tokens(110), None);
+    }
+
+    #[test]
+    fn test_sizes() {
+        let size = StakePoolState::stake_pool_size_of(10);
+        let stake_pool = StakePool {
+            max_validators: 10,
+            validators: vec![ValidatorStakeInfo::default(); 3],
+            ..StakePool::default()
+        };
+        let state = StakePoolState::StakePool(stake_pool);
+        let mut data = vec![0; size];
+        bincode::serialize_into(&mut data[..], &state).unwrap();
+        assert_eq!(StakePoolState::deserialize(&data).unwrap(), state);
+
+        let mut data = vec![0; StakePoolState::withdrawal_ticket_size_of()];
+        let ticket = StakePoolState::WithdrawalTicket(WithdrawalTicket::default());
+        bincode::serialize_into(&mut data[..], &ticket).unwrap();
+        assert_eq!(StakePoolState::deserialize(&data).unwrap(), ticket);
+        assert_eq!(
+            StakePoolState::deserialize(&[0; 4]).unwrap(),
+            StakePoolState::Uninitialized
+        );
+    }
+}
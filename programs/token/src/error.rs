@@ -76,6 +76,14 @@ pub enum TokenError {
     /// Instruction does not support non-native tokens
     #[error("Instruction does not support non-native tokens")]
     NonNativeNotSupported,
+
+    // 20
+    /// The destination requires a memo instruction right before the transfer
+    #[error("No memo in previous instruction; required for recipient to receive a transfer")]
+    NoMemo,
+    /// The destination requires the memo to be valid UTF-8
+    #[error("Memo of the previous instruction is not valid UTF-8")]
+    MemoNotUtf8,
 }
 
 impl From<TokenError> for InstructionError {
@@ -2,6 +2,7 @@
 
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use num_enum::TryFromPrimitive;
+use serde_derive::{Deserialize, Serialize};
 use mundis_sdk::instruction::InstructionError;
 use mundis_sdk::program_pack::{IsInitialized, Pack, Sealed};
 use mundis_sdk::pubkey::Pubkey;
@@ -127,6 +128,8 @@ pub struct TokenAccount {
     pub delegated_amount: u64,
     /// Optional authority to close the account.
     pub close_authority: Option<Pubkey>,
+    /// Memo an incoming transfer must be preceded by.
+    pub memo_transfer: MemoTransfer,
 }
 
 impl TokenAccount {
@@ -172,12 +175,14 @@ impl Pack for TokenAccount {
             ref is_native,
             delegated_amount,
             ref close_authority,
+            memo_transfer,
         } = self;
         mint_dst.copy_from_slice(mint.as_ref());
         owner_dst.copy_from_slice(owner.as_ref());
         *amount_dst = amount.to_le_bytes();
         pack_option_key(delegate, delegate_dst);
-        state_dst[0] = state as u8;
+        // The memo requirement is kept in the upper half of the state byte
+        state_dst[0] = state as u8 | (memo_transfer as u8) << 4;
         pack_option_u64(is_native, is_native_dst);
         *delegated_amount_dst = delegated_amount.to_le_bytes();
         pack_option_key(close_authority, close_authority_dst);
@@ -192,11 +197,13 @@ impl Pack for TokenAccount {
             owner: Pubkey::new_from_array(*owner),
             amount: u64::from_le_bytes(*amount),
             delegate: unpack_option_key(delegate)?,
-            state: AccountState::try_from_primitive(state[0])
+            state: AccountState::try_from_primitive(state[0] & 0x0f)
                 .or(Err(InstructionError::InvalidAccountData))?,
             is_native: unpack_option_u64(is_native)?,
             delegated_amount: u64::from_le_bytes(*delegated_amount),
             close_authority: unpack_option_key(close_authority)?,
+            memo_transfer: MemoTransfer::try_from_primitive(state[0] >> 4)
+                .or(Err(InstructionError::InvalidAccountData))?,
         })
     }
 }
@@ -221,6 +228,31 @@ impl Default for AccountState {
     }
 }
 
+/// Memo requirement on transfers into an account.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive, Serialize, Deserialize)]
+pub enum MemoTransfer {
+    /// Transfers need no memo
+    Disabled,
+    /// Transfers must be preceded by a memo instruction
+    Required,
+    /// Transfers must be preceded by a memo instruction holding valid UTF-8
+    RequiredUtf8,
+}
+
+impl MemoTransfer {
+    /// Checks if a memo is required
+    pub fn is_required(&self) -> bool {
+        *self != MemoTransfer::Disabled
+    }
+}
+
+impl Default for MemoTransfer {
+    fn default() -> Self {
+        MemoTransfer::Disabled
+    }
+}
+
 /// Multisignature data.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Multisig {
@@ -285,7 +317,7 @@ mod tests {
     use mundis_sdk::program_pack::Pack;
     use mundis_sdk::pubkey::Pubkey;
     use crate::InstructionError;
-    use crate::state::{TokenAccount, AccountState, Mint, Multisig, unpack_option_u64, unpack_option_key};
+    use crate::state::{TokenAccount, AccountState, MemoTransfer, Mint, Multisig, unpack_option_u64, unpack_option_key};
 
     #[test]
     fn test_mint_unpack_from_slice() {
@@ -373,11 +405,27 @@ mod tests {
             is_native: None,
             delegated_amount: 30,
             close_authority: Some(owner),
+            memo_transfer: MemoTransfer::Disabled,
         };
         TokenAccount::pack(token_account.clone(), &mut dst).unwrap();
 
         let unpacked = TokenAccount::unpack(&dst).unwrap();
         assert_eq!(token_account, unpacked);
+
+        let token_account = TokenAccount {
+            state: AccountState::Frozen,
+            memo_transfer: MemoTransfer::RequiredUtf8,
+            ..token_account
+        };
+        TokenAccount::pack(token_account.clone(), &mut dst).unwrap();
+        assert_eq!(dst[108], 0x22);
+        assert_eq!(TokenAccount::unpack(&dst).unwrap(), token_account);
+
+        dst[108] = 0x31;
+        assert_eq!(
+            TokenAccount::unpack(&dst).unwrap_err(),
+            InstructionError::InvalidAccountData
+        );
     }
 }
 
@@ -1,6 +1,7 @@
 //! Instruction types
 
 use crate::check_program_account;
+use crate::state::MemoTransfer;
 use serde_derive::{Deserialize, Serialize};
 use mundis_sdk::instruction::{AccountMeta, Instruction, InstructionError};
 use mundis_sdk::pubkey::Pubkey;
@@ -365,6 +366,23 @@ pub enum TokenInstruction {
     ///
     ///   0. `[writable]`  The native token account to sync with its underlying lamports.
     SyncNative,
+    /// Sets whether transfers into an account must be preceded by a memo
+    /// instruction, e.g. to carry the deposit tag of an exchange.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account to update.
+    ///   1. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account to update.
+    ///   1. `[]` The account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetMemoTransfer {
+        /// The memo requirement on incoming transfers.
+        memo_transfer: MemoTransfer,
+    },
 }
 
 /// Specifies the authority type for SetAuthority instructions
@@ -899,6 +917,33 @@ pub fn sync_native(
     ))
 }
 
+/// Creates a `SetMemoTransfer` instruction
+pub fn set_memo_transfer(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    memo_transfer: MemoTransfer,
+) -> Result<Instruction, InstructionError> {
+    check_program_account(token_program_id)?;
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction::new_with_bincode(
+        *token_program_id,
+        &TokenInstruction::SetMemoTransfer { memo_transfer },
+        accounts,
+    ))
+}
+
 /// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS
 pub fn is_valid_signer_index(index: usize) -> bool {
     (MIN_SIGNERS..=MAX_SIGNERS).contains(&index)
@@ -9,8 +9,9 @@ use mundis_sdk::program_utils::limited_deserialize;
 use mundis_sdk::pubkey::Pubkey;
 use mundis_sdk::rent::Rent;
 use mundis_sdk::usafe_memory_utils::{unsafe_memcmp, unsafe_memset};
+use std::str::from_utf8;
 
-use crate::{error::TokenError, state::{AccountState, Mint, Multisig, TokenAccount}, token_instruction::{AuthorityType, is_valid_signer_index, MAX_SIGNERS, TokenInstruction}};
+use crate::{error::TokenError, state::{AccountState, MemoTransfer, Mint, Multisig, TokenAccount}, token_instruction::{AuthorityType, is_valid_signer_index, MAX_SIGNERS, TokenInstruction}};
 
 pub fn process_instruction(
     first_instruction_account: usize,
@@ -62,7 +63,7 @@ impl Processor {
             }
             TokenInstruction::Transfer { amount } => {
                 ic_msg!(invoke_context, "Instruction: Transfer");
-                Self::process_transfer(program_id, accounts, amount, None)
+                Self::process_transfer(invoke_context, program_id, accounts, amount, None)
             }
             TokenInstruction::Approve { amount } => {
                 ic_msg!(invoke_context, "Instruction: Approve");
@@ -101,7 +102,7 @@ impl Processor {
             }
             TokenInstruction::TransferChecked { amount, decimals } => {
                 ic_msg!(invoke_context, "Instruction: TransferChecked");
-                Self::process_transfer(program_id, accounts, amount, Some(decimals))
+                Self::process_transfer(invoke_context, program_id, accounts, amount, Some(decimals))
             }
             TokenInstruction::ApproveChecked { amount, decimals } => {
                 ic_msg!(invoke_context, "Instruction: ApproveChecked");
@@ -119,6 +120,10 @@ impl Processor {
                 ic_msg!(invoke_context, "Instruction: SyncNative");
                 Self::process_sync_native(program_id, accounts)
             }
+            TokenInstruction::SetMemoTransfer { memo_transfer } => {
+                ic_msg!(invoke_context, "Instruction: SetMemoTransfer");
+                Self::process_set_memo_transfer(program_id, accounts, memo_transfer)
+            }
         }
     }
 
@@ -253,6 +258,7 @@ impl Processor {
 
     /// Processes a [Transfer](enum.TokenInstruction.html) instruction.
     pub fn process_transfer(
+        invoke_context: &InvokeContext,
         program_id: &Pubkey,
         accounts: &[KeyedAccount],
         amount: u64,
@@ -336,6 +342,10 @@ impl Processor {
             return Ok(());
         }
 
+        if dest_account.memo_transfer.is_required() {
+            Self::check_previous_memo(invoke_context, dest_account.memo_transfer)?;
+        }
+
         source_account.amount = source_account
             .amount
             .checked_sub(amount)
@@ -787,6 +797,52 @@ impl Processor {
         TokenAccount::pack(native_account, native_account_info.try_account_ref_mut()?.data_as_mut_slice())
     }
 
+    /// Processes a [SetMemoTransfer](enum.TokenInstruction.html) instruction.
+    pub fn process_set_memo_transfer(
+        program_id: &Pubkey,
+        accounts: &[KeyedAccount],
+        memo_transfer: MemoTransfer,
+    ) -> Result<(), InstructionError> {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_keyed_account(account_info_iter)?;
+        let owner_info = next_keyed_account(account_info_iter)?;
+
+        let mut source_account = TokenAccount::unpack(source_account_info.try_account_ref()?.data())?;
+        if source_account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        Self::validate_owner(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        source_account.memo_transfer = memo_transfer;
+
+        TokenAccount::pack(source_account, source_account_info.try_account_ref_mut()?.data_as_mut_slice())
+    }
+
+    /// Checks that the instruction processed right before a transfer is a memo
+    /// meeting the destination's requirement
+    fn check_previous_memo(
+        invoke_context: &InvokeContext,
+        memo_transfer: MemoTransfer,
+    ) -> Result<(), InstructionError> {
+        let memo = invoke_context
+            .get_processed_sibling_instruction(0)
+            .filter(|instruction| {
+                mundis_sdk::memo::program::check_id(&instruction.program_id)
+                    && !instruction.data.is_empty()
+            })
+            .ok_or(TokenError::NoMemo)?;
+        if memo_transfer == MemoTransfer::RequiredUtf8 && from_utf8(&memo.data).is_err() {
+            return Err(TokenError::MemoNotUtf8.into());
+        }
+        Ok(())
+    }
+
     /// Checks that the account is owned by the expected program
     pub fn check_account_owner(program_id: &Pubkey, account_info: &KeyedAccount) -> Result<(), InstructionError> {
         if !Self::cmp_pubkeys(program_id, &account_info.owner()?) {
@@ -0,0 +1,122 @@
+//! Runs deployed programs
+
+use {
+    crate::{
+        serialization::{deserialize_parameters, serialize_parameters},
+        syscalls::{Syscall, SyscallContext},
+        vm::{Instance, Module, VmError},
+    },
+    mundis_program_runtime::{
+        ic_logger_msg, ic_msg,
+        invoke_context::{Executor, InvokeContext},
+        stable_log,
+    },
+    mundis_sdk::instruction::InstructionError,
+    std::sync::Arc,
+};
+
+/// Compiles the bytecode of a program
+pub fn create_executor(
+    invoke_context: &InvokeContext,
+    bytecode: &[u8],
+) -> Result<Arc<WasmExecutor>, InstructionError> {
+    let module = Module::new(bytecode, Syscall::resolve).map_err(|error| {
+        ic_msg!(invoke_context, "{}", error);
+        InstructionError::InvalidAccountData
+    })?;
+    Ok(Arc::new(WasmExecutor {
+        module: Arc::new(module),
+    }))
+}
+
+/// A compiled program, cached by the bank between transactions
+#[derive(Debug)]
+pub struct WasmExecutor {
+    module: Arc<Module>,
+}
+
+impl Executor for WasmExecutor {
+    fn execute<'a, 'b>(
+        &self,
+        first_instruction_account: usize,
+        instruction_data: &[u8],
+        invoke_context: &'a mut InvokeContext<'b>,
+        _use_jit: bool,
+    ) -> Result<(), InstructionError> {
+        let log_collector = invoke_context.get_log_collector();
+        let compute_meter = invoke_context.get_compute_meter();
+        let program_account_index = first_instruction_account + 1;
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let program_id = *keyed_accounts
+            .get(program_account_index)
+            .ok_or(InstructionError::NotEnoughAccountKeys)?
+            .unsigned_key();
+        let (input, layout) = serialize_parameters(
+            &program_id,
+            &keyed_accounts[program_account_index + 1..],
+            instruction_data,
+        )?;
+
+        let mut instance = Instance::new(self.module.clone()).map_err(|error| {
+            ic_logger_msg!(log_collector, "{}", error);
+            InstructionError::ProgramEnvironmentSetupFailure
+        })?;
+        let input_address = instance.memory.append(&input).map_err(|error| {
+            ic_logger_msg!(log_collector, "{}", error);
+            InstructionError::ProgramEnvironmentSetupFailure
+        })?;
+
+        let budget = compute_meter.borrow().get_remaining();
+        let result = {
+            let mut context = SyscallContext {
+                invoke_context,
+                program_id,
+                program_account_index,
+                input_address,
+                input_len: input.len() as u64,
+                layout: &layout,
+            };
+            instance.call_entrypoint(&mut context, input_address as u32)
+        };
+        ic_logger_msg!(
+            log_collector,
+            "Program {} consumed {} of {} compute units",
+            program_id,
+            budget.saturating_sub(compute_meter.borrow().get_remaining()),
+            budget
+        );
+
+        match result {
+            Ok(0) => {
+                let input = instance
+                    .memory
+                    .read(input_address, input.len() as u64)
+                    .map_err(|_| InstructionError::ProgramFailedToComplete)?;
+                let keyed_accounts = invoke_context.get_keyed_accounts()?;
+                deserialize_parameters(
+                    &keyed_accounts[program_account_index + 1..],
+                    &layout,
+                    input,
+                )?;
+                let (return_program_id, return_data) = &invoke_context.return_data;
+                if !return_data.is_empty() {
+                    stable_log::program_return(&log_collector, return_program_id, return_data);
+                }
+                Ok(())
+            }
+            Ok(code) => {
+                ic_logger_msg!(log_collector, "Program returned error code {}", code);
+                Err(InstructionError::from(code))
+            }
+            Err(VmError::Host(error)) => Err(error),
+            Err(VmError::ExceededBudget) => {
+                ic_logger_msg!(log_collector, "{}", VmError::ExceededBudget);
+                Err(InstructionError::ComputationalBudgetExceeded)
+            }
+            Err(error) => {
+                ic_logger_msg!(log_collector, "{}", error);
+                Err(InstructionError::ProgramFailedToComplete)
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+#![allow(incomplete_features)]
+#![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(specialization))]
+#![cfg_attr(RUSTC_NEEDS_PROC_MACRO_HYGIENE, feature(proc_macro_hygiene))]
+
+//! Loader for upgradeable programs written in WebAssembly. The bytecode of a
+//! program lives in a ProgramData account derived from the program's
+//! address, so the program keeps its address across upgrades. Programs are
+//! compiled once per bank, metered against the transaction's compute budget
+//! and reach the runtime through the host functions in [`syscalls`].
+
+pub mod executor;
+pub mod loader_instruction;
+pub mod loader_processor;
+pub mod serialization;
+pub mod syscalls;
+pub mod vm;
+
+pub use mundis_sdk::wasm_loader_upgradeable::{
+    check_id, get_program_data_address, id, UpgradeableLoaderState,
+};
@@ -0,0 +1,242 @@
+//! Instruction types
+
+use {
+    crate::id,
+    mundis_sdk::{
+        instruction::{AccountMeta, Instruction, InstructionError},
+        pubkey::Pubkey,
+        system_instruction, system_program,
+        wasm_loader_upgradeable::{get_program_data_address, UpgradeableLoaderState},
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+/// Instructions supported by the upgradeable WASM loader.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum LoaderInstruction {
+    /// Initializes a buffer account to write bytecode into.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Buffer account, owned by the loader.
+    ///   1. `[]` Buffer authority.
+    InitializeBuffer,
+
+    /// Writes bytecode into a buffer account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Buffer account.
+    ///   1. `[signer]` Buffer authority.
+    Write {
+        /// Offset of the bytes in the bytecode
+        offset: u32,
+        /// Bytecode to write
+        bytes: Vec<u8>,
+    },
+
+    /// Deploys the bytecode of a buffer as a program. Creates the program
+    /// data account, sized for bytecode up to `max_data_len`, moves the
+    /// bytecode into it and marks the program account executable. The
+    /// buffer is closed and its lamports go to the payer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer of the program data account's rent.
+    ///   1. `[writable]` Program data account, derived from the program.
+    ///   2. `[writable]` Uninitialized program account, owned by the loader.
+    ///   3. `[writable]` Buffer account holding the bytecode.
+    ///   4. `[]` System program.
+    ///   5. `[signer]` Authority of the buffer, which becomes the upgrade authority.
+    DeployWithMaxDataLen {
+        /// Maximum length of the program's bytecode
+        max_data_len: usize,
+    },
+
+    /// Replaces the bytecode of a program with that of a buffer. The buffer
+    /// is closed, and lamports not needed for the program data account's
+    /// rent go to the spill account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Program data account.
+    ///   1. `[]` Program account.
+    ///   2. `[writable]` Buffer account holding the new bytecode.
+    ///   3. `[writable]` Spill account.
+    ///   4. `[signer]` Upgrade authority, which must also be the buffer's authority.
+    Upgrade,
+
+    /// Sets the authority of a buffer or the upgrade authority of a program.
+    /// Without a new authority a program becomes immutable; a buffer always
+    /// needs one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Buffer or program data account.
+    ///   1. `[signer]` Current authority.
+    ///   2. `[]` New authority, optional.
+    SetAuthority,
+
+    /// Closes a buffer or the program data account of a program, sending its
+    /// lamports to the recipient. A closed program can no longer be invoked.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Buffer or program data account.
+    ///   1. `[writable]` Recipient of the lamports.
+    ///   2. `[signer]` Authority, not needed for uninitialized accounts.
+    ///   3. `[]` Program account, when closing a program data account.
+    Close,
+}
+
+/// Creates a buffer account able to hold `program_len` bytes of bytecode
+pub fn create_buffer(
+    payer: &Pubkey,
+    buffer: &Pubkey,
+    authority: &Pubkey,
+    lamports: u64,
+    program_len: usize,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            payer,
+            buffer,
+            lamports,
+            UpgradeableLoaderState::buffer_len(program_len) as u64,
+            &id(),
+        ),
+        Instruction::new_with_bincode(
+            id(),
+            &LoaderInstruction::InitializeBuffer,
+            vec![
+                AccountMeta::new(*buffer, false),
+                AccountMeta::new_readonly(*authority, false),
+            ],
+        ),
+    ]
+}
+
+/// Writes `bytes` at `offset` into the bytecode of a buffer
+pub fn write(buffer: &Pubkey, authority: &Pubkey, offset: u32, bytes: Vec<u8>) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &LoaderInstruction::Write { offset, bytes },
+        vec![
+            AccountMeta::new(*buffer, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Creates the program account and deploys the bytecode of `buffer` to it
+pub fn deploy_with_max_program_len(
+    payer: &Pubkey,
+    program: &Pubkey,
+    buffer: &Pubkey,
+    upgrade_authority: &Pubkey,
+    program_lamports: u64,
+    max_data_len: usize,
+) -> Result<Vec<Instruction>, InstructionError> {
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            program,
+            program_lamports,
+            UpgradeableLoaderState::program_len() as u64,
+            &id(),
+        ),
+        Instruction::new_with_bincode(
+            id(),
+            &LoaderInstruction::DeployWithMaxDataLen { max_data_len },
+            vec![
+                AccountMeta::new(*payer, true),
+                AccountMeta::new(get_program_data_address(program), false),
+                AccountMeta::new(*program, false),
+                AccountMeta::new(*buffer, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(*upgrade_authority, true),
+            ],
+        ),
+    ])
+}
+
+/// Upgrades `program` to the bytecode of `buffer`
+pub fn upgrade(
+    program: &Pubkey,
+    buffer: &Pubkey,
+    authority: &Pubkey,
+    spill: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &LoaderInstruction::Upgrade,
+        vec![
+            AccountMeta::new(get_program_data_address(program), false),
+            AccountMeta::new_readonly(*program, false),
+            AccountMeta::new(*buffer, false),
+            AccountMeta::new(*spill, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Hands a buffer over to a new authority
+pub fn set_buffer_authority(
+    buffer: &Pubkey,
+    current_authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &LoaderInstruction::SetAuthority,
+        vec![
+            AccountMeta::new(*buffer, false),
+            AccountMeta::new_readonly(*current_authority, true),
+            AccountMeta::new_readonly(*new_authority, false),
+        ],
+    )
+}
+
+/// Changes the upgrade authority of a program, or makes it immutable
+pub fn set_upgrade_authority(
+    program: &Pubkey,
+    current_authority: &Pubkey,
+    new_authority: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(get_program_data_address(program), false),
+        AccountMeta::new_readonly(*current_authority, true),
+    ];
+    if let Some(new_authority) = new_authority {
+        accounts.push(AccountMeta::new_readonly(*new_authority, false));
+    }
+    Instruction::new_with_bincode(id(), &LoaderInstruction::SetAuthority, accounts)
+}
+
+/// Closes a buffer, sending its lamports to `recipient`
+pub fn close_buffer(buffer: &Pubkey, recipient: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &LoaderInstruction::Close,
+        vec![
+            AccountMeta::new(*buffer, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Closes a program, sending the lamports of its program data account to
+/// `recipient`
+pub fn close_program(program: &Pubkey, recipient: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &LoaderInstruction::Close,
+        vec![
+            AccountMeta::new(get_program_data_address(program), false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*program, false),
+        ],
+    )
+}
@@ -0,0 +1,550 @@
+use {
+    crate::{executor::create_executor, loader_instruction::LoaderInstruction},
+    mundis_program_runtime::{ic_logger_msg, ic_msg, invoke_context::InvokeContext},
+    mundis_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        account_utils::State,
+        instruction::InstructionError,
+        keyed_account::{keyed_account_at_index, KeyedAccount},
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        system_instruction::{self, MAX_PERMITTED_DATA_LENGTH},
+        wasm_loader_upgradeable::{get_program_data_address, UpgradeableLoaderState},
+    },
+};
+
+pub fn process_instruction(
+    first_instruction_account: usize,
+    data: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    if *invoke_context.get_caller()? != crate::id() {
+        return execute_program(first_instruction_account, data, invoke_context);
+    }
+    Processor::process(first_instruction_account, data, invoke_context)
+}
+
+/// Runs a deployed program. The keyed accounts start with the program's
+/// ProgramData account, followed by the program account and the accounts of
+/// the instruction.
+fn execute_program(
+    first_instruction_account: usize,
+    data: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    let keyed_accounts = invoke_context.get_keyed_accounts()?;
+    let programdata = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+    let program = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+    let program_id = *program.unsigned_key();
+    if program.owner()? != crate::id() || !program.executable()? {
+        ic_msg!(invoke_context, "Program {} is not deployed", program_id);
+        return Err(InstructionError::IncorrectProgramId);
+    }
+    match program.state()? {
+        UpgradeableLoaderState::Program {
+            programdata_address,
+        } if programdata_address == *programdata.unsigned_key() => {}
+        _ => {
+            ic_msg!(invoke_context, "Wrong ProgramData account for this program");
+            return Err(InstructionError::InvalidAccountData);
+        }
+    }
+    if !matches!(
+        programdata.state()?,
+        UpgradeableLoaderState::ProgramData { .. }
+    ) {
+        ic_msg!(invoke_context, "Program {} has been closed", program_id);
+        return Err(InstructionError::InvalidAccountData);
+    }
+
+    let executor = match invoke_context.get_executor(&program_id) {
+        Some(executor) => executor,
+        None => {
+            let metadata_size = UpgradeableLoaderState::programdata_metadata_size();
+            let executor = create_executor(
+                invoke_context,
+                programdata
+                    .try_account_ref()?
+                    .data()
+                    .get(metadata_size..)
+                    .ok_or(InstructionError::AccountDataTooSmall)?,
+            )?;
+            invoke_context.add_executor(&program_id, executor.clone());
+            executor
+        }
+    };
+    executor.execute(first_instruction_account, data, invoke_context, false)
+}
+
+fn check_authority(
+    invoke_context: &InvokeContext,
+    authority: Option<Pubkey>,
+    authority_info: &KeyedAccount,
+) -> Result<(), InstructionError> {
+    if authority != Some(*authority_info.unsigned_key()) {
+        ic_msg!(invoke_context, "Incorrect authority provided");
+        return Err(InstructionError::IncorrectAuthority);
+    }
+    if authority_info.signer_key().is_none() {
+        ic_msg!(invoke_context, "Authority did not sign");
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Returns the bytecode stored in a buffer
+fn buffer_bytecode(buffer: &KeyedAccount) -> Result<Vec<u8>, InstructionError> {
+    Ok(buffer
+        .try_account_ref()?
+        .data()
+        .get(UpgradeableLoaderState::buffer_metadata_size()..)
+        .ok_or(InstructionError::AccountDataTooSmall)?
+        .to_vec())
+}
+
+/// Moves all lamports of `from` to `to`
+fn drain(from: &KeyedAccount, to: &KeyedAccount) -> Result<(), InstructionError> {
+    let lamports = from.lamports()?;
+    to.try_account_ref_mut()?.checked_add_lamports(lamports)?;
+    from.try_account_ref_mut()?.set_lamports(0);
+    Ok(())
+}
+
+pub struct Processor {}
+impl Processor {
+    fn process(
+        first_instruction_account: usize,
+        input: &[u8],
+        invoke_context: &mut InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let instruction = limited_deserialize(input)?;
+        match instruction {
+            LoaderInstruction::InitializeBuffer => {
+                ic_msg!(invoke_context, "Instruction: InitializeBuffer");
+                Self::process_initialize_buffer(invoke_context, first_instruction_account)
+            }
+            LoaderInstruction::Write { offset, bytes } => {
+                ic_msg!(invoke_context, "Instruction: Write");
+                Self::process_write(invoke_context, first_instruction_account, offset, &bytes)
+            }
+            LoaderInstruction::DeployWithMaxDataLen { max_data_len } => {
+                ic_msg!(invoke_context, "Instruction: DeployWithMaxDataLen");
+                Self::process_deploy(invoke_context, first_instruction_account, max_data_len)
+            }
+            LoaderInstruction::Upgrade => {
+                ic_msg!(invoke_context, "Instruction: Upgrade");
+                Self::process_upgrade(invoke_context, first_instruction_account)
+            }
+            LoaderInstruction::SetAuthority => {
+                ic_msg!(invoke_context, "Instruction: SetAuthority");
+                Self::process_set_authority(invoke_context, first_instruction_account)
+            }
+            LoaderInstruction::Close => {
+                ic_msg!(invoke_context, "Instruction: Close");
+                Self::process_close(invoke_context, first_instruction_account)
+            }
+        }
+    }
+
+    fn process_initialize_buffer(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let buffer = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let authority = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+
+        if UpgradeableLoaderState::Uninitialized != buffer.state()? {
+            ic_msg!(invoke_context, "Buffer account already initialized");
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+        buffer.set_state(&UpgradeableLoaderState::Buffer {
+            authority_address: Some(*authority.unsigned_key()),
+        })
+    }
+
+    fn process_write(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        offset: u32,
+        bytes: &[u8],
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let buffer = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let authority = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+
+        match buffer.state()? {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                check_authority(invoke_context, authority_address, authority)?
+            }
+            _ => {
+                ic_msg!(invoke_context, "Invalid Buffer account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+        }
+        let start = UpgradeableLoaderState::buffer_metadata_size() + offset as usize;
+        let end = start.saturating_add(bytes.len());
+        let mut account = buffer.try_account_ref_mut()?;
+        match account.data_as_mut_slice().get_mut(start..end) {
+            Some(data) => {
+                data.copy_from_slice(bytes);
+                Ok(())
+            }
+            None => {
+                ic_msg!(
+                    invoke_context,
+                    "Write overflow: {} < {}",
+                    account.data().len(),
+                    end
+                );
+                Err(InstructionError::AccountDataTooSmall)
+            }
+        }
+    }
+
+    fn process_deploy(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+        max_data_len: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let payer = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let programdata = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        let program = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+        let buffer = keyed_account_at_index(keyed_accounts, first_instruction_account + 3)?;
+        let authority = keyed_account_at_index(keyed_accounts, first_instruction_account + 5)?;
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+
+        // Verify the program account
+        if UpgradeableLoaderState::Uninitialized != program.state()? {
+            ic_msg!(invoke_context, "Program account already initialized");
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+        if program.data_len()? < UpgradeableLoaderState::program_len() {
+            ic_msg!(invoke_context, "Program account too small");
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        if program.lamports()? < rent.minimum_balance(program.data_len()?) {
+            ic_msg!(invoke_context, "Program account not rent-exempt");
+            return Err(InstructionError::ExecutableAccountNotRentExempt);
+        }
+        let program_id = *program.unsigned_key();
+
+        // Verify the buffer account
+        match buffer.state()? {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                check_authority(invoke_context, authority_address, authority)?
+            }
+            _ => {
+                ic_msg!(invoke_context, "Invalid Buffer account");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+        let bytecode = buffer_bytecode(buffer)?;
+        if bytecode.is_empty() {
+            ic_msg!(invoke_context, "Buffer account too small");
+            return Err(InstructionError::InvalidAccountData);
+        }
+        if max_data_len < bytecode.len() {
+            ic_msg!(
+                invoke_context,
+                "Max data length is too small to hold Buffer data"
+            );
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        let programdata_len = UpgradeableLoaderState::programdata_len(max_data_len);
+        if programdata_len as u64 > MAX_PERMITTED_DATA_LENGTH {
+            ic_msg!(invoke_context, "Max data length is too large");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        // Verify the ProgramData account
+        let programdata_address = get_program_data_address(&program_id);
+        if programdata_address != *programdata.unsigned_key() {
+            ic_msg!(invoke_context, "ProgramData address is not derived");
+            return Err(InstructionError::InvalidArgument);
+        }
+        let payer_address = *payer
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+        let upgrade_authority = *authority.unsigned_key();
+
+        // Compile before spending anything on a program that cannot run
+        let executor = create_executor(invoke_context, &bytecode)?;
+
+        invoke_context.native_invoke(
+            system_instruction::create_account(
+                &payer_address,
+                &programdata_address,
+                rent.minimum_balance(programdata_len).max(1),
+                programdata_len as u64,
+                &crate::id(),
+            ),
+            &[programdata_address],
+        )?;
+
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let payer = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let programdata = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        let program = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+        let buffer = keyed_account_at_index(keyed_accounts, first_instruction_account + 3)?;
+        // The buffer is no longer needed, its lamports refund the payer
+        drain(buffer, payer)?;
+        programdata.set_state(&UpgradeableLoaderState::ProgramData {
+            slot: clock.slot,
+            upgrade_authority_address: Some(upgrade_authority),
+        })?;
+        let metadata_size = UpgradeableLoaderState::programdata_metadata_size();
+        programdata.try_account_ref_mut()?.data_as_mut_slice()
+            [metadata_size..metadata_size + bytecode.len()]
+            .copy_from_slice(&bytecode);
+        program.set_state(&UpgradeableLoaderState::Program {
+            programdata_address,
+        })?;
+        program.try_account_ref_mut()?.set_executable(true);
+        invoke_context.update_executor(&program_id, executor);
+
+        ic_logger_msg!(
+            invoke_context.get_log_collector(),
+            "Deployed program {}",
+            program_id
+        );
+        Ok(())
+    }
+
+    fn process_upgrade(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let programdata = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let program = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        let buffer = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+        let spill = keyed_account_at_index(keyed_accounts, first_instruction_account + 3)?;
+        let authority = keyed_account_at_index(keyed_accounts, first_instruction_account + 4)?;
+        let rent = invoke_context.get_sysvar_cache().get_rent()?;
+        let clock = invoke_context.get_sysvar_cache().get_clock()?;
+
+        // Verify the program account
+        if program.owner()? != crate::id() || !program.executable()? {
+            ic_msg!(invoke_context, "Program account not deployed");
+            return Err(InstructionError::AccountNotExecutable);
+        }
+        match program.state()? {
+            UpgradeableLoaderState::Program {
+                programdata_address,
+            } if programdata_address == *programdata.unsigned_key() => {}
+            _ => {
+                ic_msg!(invoke_context, "Wrong ProgramData account for this program");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+        let program_id = *program.unsigned_key();
+
+        // Verify the ProgramData account
+        match programdata.state()? {
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address: None,
+                ..
+            } => {
+                ic_msg!(invoke_context, "Program not upgradeable");
+                return Err(InstructionError::Immutable);
+            }
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            } => check_authority(invoke_context, upgrade_authority_address, authority)?,
+            _ => {
+                ic_msg!(invoke_context, "Invalid ProgramData account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+        }
+
+        // Verify the buffer account
+        match buffer.state()? {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                check_authority(invoke_context, authority_address, authority)?
+            }
+            _ => {
+                ic_msg!(invoke_context, "Invalid Buffer account");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+        let bytecode = buffer_bytecode(buffer)?;
+        let metadata_size = UpgradeableLoaderState::programdata_metadata_size();
+        let programdata_len = programdata.data_len()?;
+        if bytecode.is_empty() {
+            ic_msg!(invoke_context, "Buffer account too small");
+            return Err(InstructionError::InvalidAccountData);
+        }
+        if programdata_len < metadata_size + bytecode.len() {
+            ic_msg!(invoke_context, "ProgramData account not large enough");
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        let minimum_balance = rent.minimum_balance(programdata_len).max(1);
+        let available = programdata
+            .lamports()?
+            .checked_add(buffer.lamports()?)
+            .ok_or(InstructionError::ArithmeticOverflow)?;
+        if available < minimum_balance {
+            ic_msg!(
+                invoke_context,
+                "Buffer account balance too low to fund upgrade"
+            );
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        let executor = create_executor(invoke_context, &bytecode)?;
+
+        // Replace the bytecode and zero what is left of the old one
+        programdata.set_state(&UpgradeableLoaderState::ProgramData {
+            slot: clock.slot,
+            upgrade_authority_address: Some(*authority.unsigned_key()),
+        })?;
+        {
+            let mut account = programdata.try_account_ref_mut()?;
+            let data = account.data_as_mut_slice();
+            data[metadata_size..metadata_size + bytecode.len()].copy_from_slice(&bytecode);
+            data[metadata_size + bytecode.len()..].fill(0);
+        }
+
+        // Keep the ProgramData account rent-exempt, spill the rest
+        spill
+            .try_account_ref_mut()?
+            .checked_add_lamports(available - minimum_balance)?;
+        buffer.try_account_ref_mut()?.set_lamports(0);
+        programdata
+            .try_account_ref_mut()?
+            .set_lamports(minimum_balance);
+        invoke_context.update_executor(&program_id, executor);
+
+        ic_logger_msg!(
+            invoke_context.get_log_collector(),
+            "Upgraded program {}",
+            program_id
+        );
+        Ok(())
+    }
+
+    fn process_set_authority(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let account = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let present_authority =
+            keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        let new_authority = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)
+            .ok()
+            .map(|account| *account.unsigned_key());
+
+        match account.state()? {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                if new_authority.is_none() {
+                    ic_msg!(invoke_context, "Buffer authority is not optional");
+                    return Err(InstructionError::IncorrectAuthority);
+                }
+                check_authority(invoke_context, authority_address, present_authority)?;
+                account.set_state(&UpgradeableLoaderState::Buffer {
+                    authority_address: new_authority,
+                })?;
+            }
+            UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } => {
+                if upgrade_authority_address.is_none() {
+                    ic_msg!(invoke_context, "Program not upgradeable");
+                    return Err(InstructionError::Immutable);
+                }
+                check_authority(invoke_context, upgrade_authority_address, present_authority)?;
+                account.set_state(&UpgradeableLoaderState::ProgramData {
+                    slot,
+                    upgrade_authority_address: new_authority,
+                })?;
+            }
+            _ => {
+                ic_msg!(invoke_context, "Account does not support authorities");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+
+        ic_logger_msg!(
+            invoke_context.get_log_collector(),
+            "New authority {:?}",
+            new_authority
+        );
+        Ok(())
+    }
+
+    fn process_close(
+        invoke_context: &mut InvokeContext,
+        first_instruction_account: usize,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let close = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+        let recipient = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+        if close.unsigned_key() == recipient.unsigned_key() {
+            ic_msg!(
+                invoke_context,
+                "Recipient is the same as the account being closed"
+            );
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        match close.state()? {
+            UpgradeableLoaderState::Uninitialized => {
+                drain(close, recipient)?;
+                ic_msg!(
+                    invoke_context,
+                    "Closed Uninitialized {}",
+                    close.unsigned_key()
+                );
+            }
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                let authority =
+                    keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+                check_authority(invoke_context, authority_address, authority)?;
+                drain(close, recipient)?;
+                close.set_state(&UpgradeableLoaderState::Uninitialized)?;
+                ic_msg!(invoke_context, "Closed Buffer {}", close.unsigned_key());
+            }
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            } => {
+                let authority =
+                    keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+                let program =
+                    keyed_account_at_index(keyed_accounts, first_instruction_account + 3)?;
+                if program.owner()? != crate::id() {
+                    ic_msg!(invoke_context, "Program account not owned by loader");
+                    return Err(InstructionError::IncorrectProgramId);
+                }
+                match program.state()? {
+                    UpgradeableLoaderState::Program {
+                        programdata_address,
+                    } if programdata_address == *close.unsigned_key() => {}
+                    _ => {
+                        ic_msg!(invoke_context, "ProgramData does not belong to the program");
+                        return Err(InstructionError::InvalidArgument);
+                    }
+                }
+                if upgrade_authority_address.is_none() {
+                    ic_msg!(invoke_context, "Program not upgradeable");
+                    return Err(InstructionError::Immutable);
+                }
+                check_authority(invoke_context, upgrade_authority_address, authority)?;
+                drain(close, recipient)?;
+                close.set_state(&UpgradeableLoaderState::Uninitialized)?;
+                ic_msg!(invoke_context, "Closed Program {}", program.unsigned_key());
+            }
+            _ => {
+                ic_msg!(invoke_context, "Account does not support closing");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! The input a program receives in its linear memory.
+//!
+//! All integers are little-endian:
+//!
+//! ```text
+//! u64                  number of accounts
+//! for each account:
+//!   u8                 NON_DUP_MARKER, or the index of the account it duplicates
+//!   duplicates:        7 bytes of padding
+//!   others:
+//!     u8               is_signer
+//!     u8               is_writable
+//!     u8               executable
+//!     u32              padding
+//!     [u8; 32]         key
+//!     [u8; 32]         owner
+//!     u64              lamports
+//!     u64              data length
+//!     [u8]             data, followed by MAX_PERMITTED_DATA_INCREASE bytes to grow into
+//!                      and padding to 8-byte alignment
+//!     u64              rent epoch
+//! u64                  instruction data length
+//! [u8]                 instruction data
+//! [u8; 32]             program id
+//! ```
+//!
+//! Programs change lamports, owner, data length and data in place; the
+//! loader applies those changes to the accounts once the program returns, or
+//! before it invokes another program.
+
+use {
+    mundis_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        entrypoint::MAX_PERMITTED_DATA_INCREASE,
+        instruction::InstructionError,
+        keyed_account::KeyedAccount,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Marks an account that does not duplicate an earlier one
+pub const NON_DUP_MARKER: u8 = u8::MAX;
+
+/// Where a serialized account lives in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializedAccount {
+    /// Offset of the account's key
+    pub offset: usize,
+    /// Length of the account's data when it was serialized
+    pub original_data_len: usize,
+}
+
+const KEY_OFFSET: usize = 0;
+const OWNER_OFFSET: usize = KEY_OFFSET + size_of::<Pubkey>();
+const LAMPORTS_OFFSET: usize = OWNER_OFFSET + size_of::<Pubkey>();
+const DATA_LEN_OFFSET: usize = LAMPORTS_OFFSET + size_of::<u64>();
+const DATA_OFFSET: usize = DATA_LEN_OFFSET + size_of::<u64>();
+
+fn data_region_len(original_data_len: usize) -> usize {
+    let len = original_data_len + MAX_PERMITTED_DATA_INCREASE;
+    (len + 7) & !7
+}
+
+/// Serializes the program's accounts and instruction data. Returns the
+/// input and, for each account, where it was serialized, `None` for
+/// duplicates.
+pub fn serialize_parameters(
+    program_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    instruction_data: &[u8],
+) -> Result<(Vec<u8>, Vec<Option<SerializedAccount>>), InstructionError> {
+    let mut input = Vec::new();
+    let mut layout = Vec::with_capacity(keyed_accounts.len());
+    input.extend_from_slice(&(keyed_accounts.len() as u64).to_le_bytes());
+    for (index, keyed_account) in keyed_accounts.iter().enumerate() {
+        let duplicate = keyed_accounts[..index]
+            .iter()
+            .position(|other| other.unsigned_key() == keyed_account.unsigned_key());
+        if let Some(original) = duplicate {
+            input.push(original as u8);
+            input.extend_from_slice(&[0; 7]);
+            layout.push(None);
+            continue;
+        }
+        input.push(NON_DUP_MARKER);
+        input.push(keyed_account.signer_key().is_some() as u8);
+        input.push(keyed_account.is_writable() as u8);
+        input.push(keyed_account.executable()? as u8);
+        input.extend_from_slice(&[0; 4]);
+
+        let account = keyed_account.try_account_ref()?;
+        let offset = input.len();
+        let original_data_len = account.data().len();
+        input.extend_from_slice(keyed_account.unsigned_key().as_ref());
+        input.extend_from_slice(account.owner().as_ref());
+        input.extend_from_slice(&account.lamports().to_le_bytes());
+        input.extend_from_slice(&(original_data_len as u64).to_le_bytes());
+        input.extend_from_slice(account.data());
+        input.resize(
+            input.len() + data_region_len(original_data_len) - original_data_len,
+            0,
+        );
+        input.extend_from_slice(&account.rent_epoch().to_le_bytes());
+        layout.push(Some(SerializedAccount {
+            offset,
+            original_data_len,
+        }));
+    }
+    input.extend_from_slice(&(instruction_data.len() as u64).to_le_bytes());
+    input.extend_from_slice(instruction_data);
+    input.extend_from_slice(program_id.as_ref());
+    Ok((input, layout))
+}
+
+fn read_u64(input: &[u8], offset: usize) -> Result<u64, InstructionError> {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(
+        input
+            .get(offset..offset + 8)
+            .ok_or(InstructionError::InvalidArgument)?,
+    );
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Applies the changes the program made to its input to the accounts
+pub fn deserialize_parameters(
+    keyed_accounts: &[KeyedAccount],
+    layout: &[Option<SerializedAccount>],
+    input: &[u8],
+) -> Result<(), InstructionError> {
+    for (keyed_account, serialized) in keyed_accounts.iter().zip(layout) {
+        let serialized = match serialized {
+            Some(serialized) => serialized,
+            None => continue,
+        };
+        let base = serialized.offset;
+        let lamports = read_u64(input, base + LAMPORTS_OFFSET)?;
+        let owner = Pubkey::new(&input[base + OWNER_OFFSET..base + LAMPORTS_OFFSET]);
+        let data_len = read_u64(input, base + DATA_LEN_OFFSET)? as usize;
+        if data_len > serialized.original_data_len + MAX_PERMITTED_DATA_INCREASE {
+            return Err(InstructionError::InvalidRealloc);
+        }
+        let data = &input[base + DATA_OFFSET..base + DATA_OFFSET + data_len];
+
+        // Only touch what changed, so untouched read-only accounts pass the
+        // runtime's checks
+        let account = keyed_account.try_account_ref()?;
+        let changed_lamports = account.lamports() != lamports;
+        let changed_owner = account.owner() != &owner;
+        let changed_data = account.data() != data;
+        drop(account);
+        if changed_lamports || changed_owner || changed_data {
+            let mut account = keyed_account.try_account_ref_mut()?;
+            if changed_lamports {
+                account.set_lamports(lamports);
+            }
+            if changed_data {
+                account.set_data(data.to_vec());
+            }
+            if changed_owner {
+                account.set_owner(owner);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Refreshes the input with the state of the accounts, after another
+/// program was invoked
+pub fn update_parameters(
+    keyed_accounts: &[KeyedAccount],
+    layout: &[Option<SerializedAccount>],
+    input: &mut [u8],
+) -> Result<(), InstructionError> {
+    for (keyed_account, serialized) in keyed_accounts.iter().zip(layout) {
+        let serialized = match serialized {
+            Some(serialized) => serialized,
+            None => continue,
+        };
+        let account = keyed_account.try_account_ref()?;
+        let data_len = account.data().len();
+        if data_len > serialized.original_data_len + MAX_PERMITTED_DATA_INCREASE {
+            return Err(InstructionError::InvalidRealloc);
+        }
+        let base = serialized.offset;
+        input[base + OWNER_OFFSET..base + LAMPORTS_OFFSET]
+            .copy_from_slice(account.owner().as_ref());
+        input[base + LAMPORTS_OFFSET..base + DATA_LEN_OFFSET]
+            .copy_from_slice(&account.lamports().to_le_bytes());
+        input[base + DATA_LEN_OFFSET..base + DATA_OFFSET]
+            .copy_from_slice(&(data_len as u64).to_le_bytes());
+        input[base + DATA_OFFSET..base + DATA_OFFSET + data_len].copy_from_slice(account.data());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, mundis_sdk::account::AccountSharedData, std::cell::RefCell};
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let new_owner = Pubkey::new_unique();
+        let account = RefCell::new(AccountSharedData::new(42, 3, &program_id));
+        let keyed_accounts = [
+            KeyedAccount::new(&key, true, &account),
+            KeyedAccount::new(&key, true, &account),
+        ];
+        let (mut input, layout) =
+            serialize_parameters(&program_id, &keyed_accounts, &[7, 8]).unwrap();
+        assert_eq!(layout[1], None);
+        let serialized = layout[0].unwrap();
+        assert_eq!(
+            &input[serialized.offset..serialized.offset + 32],
+            key.as_ref()
+        );
+        assert_eq!(&input[input.len() - 32..], program_id.as_ref());
+
+        // The program moves lamports, grows the data and changes the owner
+        let base = serialized.offset;
+        input[base + OWNER_OFFSET..base + LAMPORTS_OFFSET].copy_from_slice(new_owner.as_ref());
+        input[base + LAMPORTS_OFFSET..base + DATA_LEN_OFFSET].copy_from_slice(&41u64.to_le_bytes());
+        input[base + DATA_LEN_OFFSET..base + DATA_OFFSET].copy_from_slice(&5u64.to_le_bytes());
+        input[base + DATA_OFFSET + 4] = 9;
+        deserialize_parameters(&keyed_accounts, &layout, &input).unwrap();
+        assert_eq!(account.borrow().lamports(), 41);
+        assert_eq!(account.borrow().data(), &[0, 0, 0, 0, 9]);
+        assert_eq!(account.borrow().owner(), &new_owner);
+
+        account.borrow_mut().set_lamports(100);
+        update_parameters(&keyed_accounts, &layout, &mut input).unwrap();
+        assert_eq!(read_u64(&input, base + LAMPORTS_OFFSET).unwrap(), 100);
+
+        // Growing past the permitted increase fails
+        let too_long = (3 + MAX_PERMITTED_DATA_INCREASE + 1) as u64;
+        input[base + DATA_LEN_OFFSET..base + DATA_OFFSET].copy_from_slice(&too_long.to_le_bytes());
+        assert_eq!(
+            deserialize_parameters(&keyed_accounts, &layout, &input),
+            Err(InstructionError::InvalidRealloc)
+        );
+    }
+}
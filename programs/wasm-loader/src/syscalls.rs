@@ -0,0 +1,385 @@
+//! Host functions programs import from the `env` module
+
+use {
+    crate::{
+        serialization::{deserialize_parameters, update_parameters, SerializedAccount},
+        vm::{FuncType, Host, HostFunction, Memory, ValType, VmError},
+    },
+    mundis_program_runtime::{ic_logger_msg, invoke_context::InvokeContext, stable_log},
+    mundis_sdk::{
+        instruction::{AccountMeta, Instruction, InstructionError},
+        keyed_account::KeyedAccount,
+        pubkey::{Pubkey, MAX_SEEDS},
+    },
+    serde::Serialize,
+    std::{mem::size_of, str},
+};
+
+/// Module name programs import host functions from
+pub const SYSCALL_MODULE: &str = "env";
+/// Maximum size of the data a program can return
+pub const MAX_RETURN_DATA: usize = 1024;
+/// Maximum number of program derived signers of an invocation
+pub const MAX_SIGNERS: usize = 16;
+
+/// Functions the loader exposes to programs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syscall {
+    /// `mundis_log(message: *const u8, len: u32)`
+    Log,
+    /// `mundis_log_64(a: u64, b: u64, c: u64, d: u64, e: u64)`
+    Log64,
+    /// `mundis_log_pubkey(pubkey: *const [u8; 32])`
+    LogPubkey,
+    /// `mundis_log_compute_units()`
+    LogComputeUnits,
+    /// `mundis_panic(message: *const u8, len: u32)`, aborts the program
+    Panic,
+    /// `mundis_create_program_address(seeds: *const &[u8], seeds_len: u32,
+    /// program_id: *const [u8; 32], address: *mut [u8; 32]) -> u64`
+    CreateProgramAddress,
+    /// `mundis_try_find_program_address(seeds: *const &[u8], seeds_len: u32,
+    /// program_id: *const [u8; 32], address: *mut [u8; 32], bump_seed: *mut u8) -> u64`
+    TryFindProgramAddress,
+    /// `mundis_invoke_signed(instruction: *const u8, instruction_len: u32,
+    /// signers_seeds: *const &[&[u8]], signers_seeds_len: u32) -> u64`, the
+    /// instruction being bincode encoded
+    InvokeSigned,
+    /// `mundis_get_clock_sysvar(clock: *mut u8) -> u64`, bincode encoded
+    GetClockSysvar,
+    /// `mundis_get_rent_sysvar(rent: *mut u8) -> u64`, bincode encoded
+    GetRentSysvar,
+    /// `mundis_get_epoch_schedule_sysvar(epoch_schedule: *mut u8) -> u64`,
+    /// bincode encoded
+    GetEpochScheduleSysvar,
+    /// `mundis_set_return_data(data: *const u8, len: u32)`
+    SetReturnData,
+    /// `mundis_get_return_data(data: *mut u8, len: u32, program_id: *mut [u8; 32]) -> u64`,
+    /// returns the full length of the return data
+    GetReturnData,
+}
+
+impl Syscall {
+    pub const ALL: [Syscall; 13] = [
+        Syscall::Log,
+        Syscall::Log64,
+        Syscall::LogPubkey,
+        Syscall::LogComputeUnits,
+        Syscall::Panic,
+        Syscall::CreateProgramAddress,
+        Syscall::TryFindProgramAddress,
+        Syscall::InvokeSigned,
+        Syscall::GetClockSysvar,
+        Syscall::GetRentSysvar,
+        Syscall::GetEpochScheduleSysvar,
+        Syscall::SetReturnData,
+        Syscall::GetReturnData,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Syscall::Log => "mundis_log",
+            Syscall::Log64 => "mundis_log_64",
+            Syscall::LogPubkey => "mundis_log_pubkey",
+            Syscall::LogComputeUnits => "mundis_log_compute_units",
+            Syscall::Panic => "mundis_panic",
+            Syscall::CreateProgramAddress => "mundis_create_program_address",
+            Syscall::TryFindProgramAddress => "mundis_try_find_program_address",
+            Syscall::InvokeSigned => "mundis_invoke_signed",
+            Syscall::GetClockSysvar => "mundis_get_clock_sysvar",
+            Syscall::GetRentSysvar => "mundis_get_rent_sysvar",
+            Syscall::GetEpochScheduleSysvar => "mundis_get_epoch_schedule_sysvar",
+            Syscall::SetReturnData => "mundis_set_return_data",
+            Syscall::GetReturnData => "mundis_get_return_data",
+        }
+    }
+
+    pub fn signature(&self) -> FuncType {
+        use ValType::*;
+        match self {
+            Syscall::Log | Syscall::Panic | Syscall::SetReturnData => {
+                FuncType::new(&[I32, I32], &[])
+            }
+            Syscall::Log64 => FuncType::new(&[I64, I64, I64, I64, I64], &[]),
+            Syscall::LogPubkey => FuncType::new(&[I32], &[]),
+            Syscall::LogComputeUnits => FuncType::new(&[], &[]),
+            Syscall::CreateProgramAddress => FuncType::new(&[I32, I32, I32, I32], &[I64]),
+            Syscall::TryFindProgramAddress => FuncType::new(&[I32, I32, I32, I32, I32], &[I64]),
+            Syscall::InvokeSigned => FuncType::new(&[I32, I32, I32, I32], &[I64]),
+            Syscall::GetClockSysvar | Syscall::GetRentSysvar | Syscall::GetEpochScheduleSysvar => {
+                FuncType::new(&[I32], &[I64])
+            }
+            Syscall::GetReturnData => FuncType::new(&[I32, I32, I32], &[I64]),
+        }
+    }
+
+    /// Resolves an import of a module
+    pub fn resolve(module: &str, name: &str) -> Option<HostFunction> {
+        if module != SYSCALL_MODULE {
+            return None;
+        }
+        Self::ALL
+            .iter()
+            .position(|syscall| syscall.name() == name)
+            .map(|id| HostFunction {
+                id,
+                signature: Self::ALL[id].signature(),
+            })
+    }
+}
+
+/// What syscalls need to know about the running program
+pub struct SyscallContext<'a, 'b> {
+    pub invoke_context: &'a mut InvokeContext<'b>,
+    pub program_id: Pubkey,
+    /// Index of the program account among the keyed accounts
+    pub program_account_index: usize,
+    /// Address and length of the program's input in its memory
+    pub input_address: u64,
+    pub input_len: u64,
+    pub layout: &'a [Option<SerializedAccount>],
+}
+
+impl<'a, 'b> SyscallContext<'a, 'b> {
+    fn instruction_accounts(&self) -> Result<&[KeyedAccount], InstructionError> {
+        self.invoke_context
+            .get_keyed_accounts()?
+            .get(self.program_account_index + 1..)
+            .ok_or(InstructionError::NotEnoughAccountKeys)
+    }
+
+    fn log(&self, message: &str) {
+        stable_log::program_log(&self.invoke_context.get_log_collector(), message);
+    }
+
+    fn write_sysvar<T: Serialize>(
+        &mut self,
+        memory: &mut Memory,
+        address: u64,
+        sysvar: &T,
+    ) -> Result<Option<u64>, VmError> {
+        self.consume_units(self.invoke_context.get_compute_budget().sysvar_base_cost)?;
+        let bytes = bincode::serialize(sysvar).map_err(|_| InstructionError::GenericError)?;
+        memory.write(address, &bytes)?;
+        Ok(Some(0))
+    }
+
+    fn invoke_signed(
+        &mut self,
+        memory: &mut Memory,
+        instruction: &[u8],
+        signers: Vec<Pubkey>,
+    ) -> Result<(), VmError> {
+        let budget = *self.invoke_context.get_compute_budget();
+        let instruction: Instruction =
+            bincode::deserialize(instruction).map_err(|_| InstructionError::InvalidArgument)?;
+        let size = instruction.accounts.len() * size_of::<AccountMeta>() + instruction.data.len();
+        if size > budget.max_cpi_instruction_size {
+            ic_logger_msg!(
+                self.invoke_context.get_log_collector(),
+                "Instruction too large {} > {}",
+                size,
+                budget.max_cpi_instruction_size
+            );
+            return Err(InstructionError::InvalidArgument.into());
+        }
+        self.consume_units(instruction.data.len() as u64 / budget.cpi_bytes_per_unit)?;
+
+        // Hand the caller's changes over to the callee, then pick up the
+        // callee's changes
+        let input = memory.read(self.input_address, self.input_len)?;
+        deserialize_parameters(self.instruction_accounts()?, self.layout, input)?;
+        self.invoke_context.native_invoke(instruction, &signers)?;
+        let input = memory.read_mut(self.input_address, self.input_len)?;
+        update_parameters(self.instruction_accounts()?, self.layout, input)?;
+        Ok(())
+    }
+}
+
+fn translate_string(memory: &Memory, address: u64, len: u64) -> Result<&str, VmError> {
+    str::from_utf8(memory.read(address, len)?).map_err(|_| VmError::trap("invalid UTF-8 string"))
+}
+
+fn translate_pubkey(memory: &Memory, address: u64) -> Result<Pubkey, VmError> {
+    Ok(Pubkey::new(memory.read(address, 32)?))
+}
+
+/// Reads an array of `len` slices laid out as (address, length) pairs of
+/// 32-bit integers, as `&[&[u8]]` is on wasm32
+fn translate_slices(memory: &Memory, address: u64, len: u64) -> Result<Vec<&[u8]>, VmError> {
+    (0..len)
+        .map(|index| {
+            let slice = address + index * 8;
+            let slice_address = memory.read_u32(slice)? as u64;
+            let slice_len = memory.read_u32(slice + 4)? as u64;
+            memory.read(slice_address, slice_len)
+        })
+        .collect()
+}
+
+impl<'a, 'b> Host for SyscallContext<'a, 'b> {
+    fn remaining_units(&self) -> u64 {
+        self.invoke_context
+            .get_compute_meter()
+            .borrow()
+            .get_remaining()
+    }
+
+    fn consume_units(&mut self, units: u64) -> Result<(), VmError> {
+        self.invoke_context
+            .get_compute_meter()
+            .borrow_mut()
+            .consume(units)
+            .map_err(|_| VmError::ExceededBudget)
+    }
+
+    fn call(
+        &mut self,
+        id: usize,
+        args: &[u64],
+        memory: &mut Memory,
+    ) -> Result<Option<u64>, VmError> {
+        let budget = *self.invoke_context.get_compute_budget();
+        let arg = |index: usize| args[index] as u32 as u64;
+        match Syscall::ALL[id] {
+            Syscall::Log => {
+                self.consume_units(budget.syscall_base_cost.max(arg(1)))?;
+                let message = translate_string(memory, arg(0), arg(1))?;
+                self.log(message);
+                Ok(None)
+            }
+            Syscall::Log64 => {
+                self.consume_units(budget.log_64_units)?;
+                self.log(&format!(
+                    "{:#x}, {:#x}, {:#x}, {:#x}, {:#x}",
+                    args[0], args[1], args[2], args[3], args[4]
+                ));
+                Ok(None)
+            }
+            Syscall::LogPubkey => {
+                self.consume_units(budget.log_pubkey_units)?;
+                let pubkey = translate_pubkey(memory, arg(0))?;
+                self.log(&pubkey.to_string());
+                Ok(None)
+            }
+            Syscall::LogComputeUnits => {
+                self.consume_units(budget.syscall_base_cost)?;
+                ic_logger_msg!(
+                    self.invoke_context.get_log_collector(),
+                    "Program consumption: {} units remaining",
+                    self.remaining_units()
+                );
+                Ok(None)
+            }
+            Syscall::Panic => {
+                self.consume_units(budget.syscall_base_cost)?;
+                let message = String::from_utf8_lossy(memory.read(arg(0), arg(1))?);
+                Err(VmError::Trap(format!("program panicked: {}", message)))
+            }
+            Syscall::CreateProgramAddress => {
+                self.consume_units(budget.create_program_address_units)?;
+                if arg(1) > MAX_SEEDS as u64 {
+                    return Ok(Some(1));
+                }
+                let seeds = translate_slices(memory, arg(0), arg(1))?;
+                let program_id = translate_pubkey(memory, arg(2))?;
+                match Pubkey::create_program_address(&seeds, &program_id) {
+                    Ok(address) => {
+                        memory.write(arg(3), address.as_ref())?;
+                        Ok(Some(0))
+                    }
+                    Err(_) => Ok(Some(1)),
+                }
+            }
+            Syscall::TryFindProgramAddress => {
+                if arg(1) >= MAX_SEEDS as u64 {
+                    self.consume_units(budget.create_program_address_units)?;
+                    return Ok(Some(1));
+                }
+                let seeds = translate_slices(memory, arg(0), arg(1))?
+                    .into_iter()
+                    .map(|seed| seed.to_vec())
+                    .collect::<Vec<_>>();
+                let program_id = translate_pubkey(memory, arg(2))?;
+                let mut bump_seed = [u8::MAX];
+                for _ in 0..u8::MAX {
+                    self.consume_units(budget.create_program_address_units)?;
+                    let mut seeds_with_bump =
+                        seeds.iter().map(|seed| seed.as_slice()).collect::<Vec<_>>();
+                    seeds_with_bump.push(&bump_seed);
+                    if let Ok(address) =
+                        Pubkey::create_program_address(&seeds_with_bump, &program_id)
+                    {
+                        memory.write(arg(3), address.as_ref())?;
+                        memory.write(arg(4), &bump_seed)?;
+                        return Ok(Some(0));
+                    }
+                    bump_seed[0] -= 1;
+                }
+                Ok(Some(1))
+            }
+            Syscall::InvokeSigned => {
+                self.consume_units(budget.invoke_units)?;
+                if arg(3) > MAX_SIGNERS as u64 {
+                    return Err(InstructionError::MaxSeedLengthExceeded.into());
+                }
+                let mut signers = Vec::with_capacity(arg(3) as usize);
+                for index in 0..arg(3) {
+                    let signer = arg(2) + index * 8;
+                    let seeds_address = memory.read_u32(signer)? as u64;
+                    let seeds_len = memory.read_u32(signer + 4)? as u64;
+                    if seeds_len > MAX_SEEDS as u64 {
+                        return Err(InstructionError::MaxSeedLengthExceeded.into());
+                    }
+                    let seeds = translate_slices(memory, seeds_address, seeds_len)?;
+                    signers.push(
+                        Pubkey::create_program_address(&seeds, &self.program_id)
+                            .map_err(|_| InstructionError::InvalidSeeds)?,
+                    );
+                }
+                let instruction = memory.read(arg(0), arg(1))?.to_vec();
+                self.invoke_signed(memory, &instruction, signers)?;
+                Ok(Some(0))
+            }
+            Syscall::GetClockSysvar => {
+                let clock = self.invoke_context.get_sysvar_cache().get_clock()?;
+                self.write_sysvar(memory, arg(0), &*clock)
+            }
+            Syscall::GetRentSysvar => {
+                let rent = self.invoke_context.get_sysvar_cache().get_rent()?;
+                self.write_sysvar(memory, arg(0), &*rent)
+            }
+            Syscall::GetEpochScheduleSysvar => {
+                let epoch_schedule = self
+                    .invoke_context
+                    .get_sysvar_cache()
+                    .get_epoch_schedule()?;
+                self.write_sysvar(memory, arg(0), &*epoch_schedule)
+            }
+            Syscall::SetReturnData => {
+                let len = arg(1);
+                self.consume_units(budget.syscall_base_cost + len / budget.cpi_bytes_per_unit)?;
+                if len > MAX_RETURN_DATA as u64 {
+                    return Err(VmError::trap(format!(
+                        "return data too large ({} > {})",
+                        len, MAX_RETURN_DATA
+                    )));
+                }
+                let data = memory.read(arg(0), len)?.to_vec();
+                self.invoke_context.return_data = (self.program_id, data);
+                Ok(None)
+            }
+            Syscall::GetReturnData => {
+                self.consume_units(budget.syscall_base_cost)?;
+                let (program_id, data) = self.invoke_context.return_data.clone();
+                let len = arg(1).min(data.len() as u64);
+                if len > 0 {
+                    self.consume_units(len / budget.cpi_bytes_per_unit)?;
+                    memory.write(arg(0), &data[..len as usize])?;
+                    memory.write(arg(2), program_id.as_ref())?;
+                }
+                Ok(Some(data.len() as u64))
+            }
+        }
+    }
+}
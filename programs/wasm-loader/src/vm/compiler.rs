@@ -0,0 +1,553 @@
+//! Validation of function bodies and lowering into a compact instruction
+//! stream with resolved branch targets
+
+use super::{
+    module::{valtype, Module, Reader},
+    FuncType, ValType, VmError,
+};
+
+/// A branch to `target`, moving the `keep` topmost operands down over the
+/// `drop` operands below them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Branch {
+    pub target: u32,
+    pub drop: u32,
+    pub keep: u32,
+}
+
+/// A lowered instruction. Structured control flow is resolved to branches,
+/// so `block`, `loop`, `nop` and `end` leave nothing behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    Unreachable,
+    Br(Branch),
+    BrIf(Branch),
+    /// Taken when the condition is zero, to skip the body of an `if`
+    BrUnless(Branch),
+    /// Indexed by the operand, the last branch being the default
+    BrTable(Box<[Branch]>),
+    Return,
+    Call(u32),
+    CallIndirect(u32),
+    Drop,
+    Select,
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    /// Load by opcode, with the static offset
+    Load(u8, u32),
+    /// Store by opcode, with the static offset
+    Store(u8, u32),
+    MemorySize,
+    MemoryGrow,
+    MemoryCopy,
+    MemoryFill,
+    Const(u64),
+    /// Integer arithmetic, comparison or conversion by opcode
+    Numeric(u8),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Kind {
+    Function,
+    Block,
+    Loop,
+    If,
+    Else,
+}
+
+struct Control {
+    kind: Kind,
+    params: Vec<ValType>,
+    results: Vec<ValType>,
+    /// Height of the operand stack below the block's parameters
+    height: usize,
+    unreachable: bool,
+    /// Where a loop starts over
+    start: u32,
+    /// Branches (and their `br_table` entry) to patch with the end of the block
+    pending: Vec<(usize, usize)>,
+    /// Branch of an `if` to patch with the start of the `else`
+    if_branch: Option<usize>,
+}
+
+struct Compiler<'a> {
+    module: &'a Module,
+    locals: &'a [ValType],
+    results: &'a [ValType],
+    /// Operand types, `None` standing for any type in unreachable code
+    values: Vec<Option<ValType>>,
+    controls: Vec<Control>,
+    code: Vec<Op>,
+}
+
+/// Validates the body of the `index`th defined function and lowers it
+pub(crate) fn compile_function(
+    module: &Module,
+    index: usize,
+    body: &[u8],
+) -> Result<Vec<Op>, VmError> {
+    let function = &module.functions[index];
+    let signature = &module.types[function.type_index as usize];
+    let mut compiler = Compiler {
+        module,
+        locals: &function.locals,
+        results: &signature.results,
+        values: vec![],
+        controls: vec![],
+        code: vec![],
+    };
+    compiler.push_control(Kind::Function, vec![], signature.results.clone());
+    compiler.compile(&mut Reader::new(body))?;
+    Ok(compiler.code)
+}
+
+impl<'a> Compiler<'a> {
+    fn compile(&mut self, reader: &mut Reader) -> Result<(), VmError> {
+        use ValType::*;
+
+        while !self.controls.is_empty() {
+            let opcode = reader.u8()?;
+            match opcode {
+                0x00 => {
+                    self.code.push(Op::Unreachable);
+                    self.set_unreachable();
+                }
+                0x01 => {}
+                0x02 | 0x03 => {
+                    let block_type = self.block_type(reader)?;
+                    self.pop_all(&block_type.params)?;
+                    let kind = if opcode == 0x02 {
+                        Kind::Block
+                    } else {
+                        Kind::Loop
+                    };
+                    self.push_control(kind, block_type.params, block_type.results);
+                }
+                0x04 => {
+                    let block_type = self.block_type(reader)?;
+                    self.pop_expect(I32)?;
+                    self.pop_all(&block_type.params)?;
+                    self.push_control(Kind::If, block_type.params, block_type.results);
+                    let at = self.code.len();
+                    self.code.push(Op::BrUnless(Branch::default()));
+                    self.controls.last_mut().unwrap().if_branch = Some(at);
+                }
+                0x05 => {
+                    let control = self.pop_control()?;
+                    if control.kind != Kind::If {
+                        return Err(VmError::invalid("else without if"));
+                    }
+                    // The end of the then branch jumps over the else branch
+                    let mut pending = control.pending;
+                    pending.push((self.code.len(), 0));
+                    self.code.push(Op::Br(Branch::default()));
+                    let else_start = self.code.len() as u32;
+                    self.patch(control.if_branch.unwrap(), 0, else_start);
+                    self.push_control(Kind::Else, control.params, control.results);
+                    self.controls.last_mut().unwrap().pending = pending;
+                }
+                0x0b => {
+                    let control = self.pop_control()?;
+                    if control.kind == Kind::If && control.params != control.results {
+                        return Err(VmError::invalid("if without else changes the stack"));
+                    }
+                    let end = self.code.len() as u32;
+                    if let Some(at) = control.if_branch {
+                        self.patch(at, 0, end);
+                    }
+                    for (at, entry) in control.pending {
+                        self.patch(at, entry, end);
+                    }
+                    self.push_all(&control.results);
+                    if control.kind == Kind::Function {
+                        self.code.push(Op::Return);
+                        if !reader.is_empty() {
+                            return Err(VmError::invalid("trailing bytes after function body"));
+                        }
+                    }
+                }
+                0x0c => {
+                    let (index, branch, types) = self.label(reader.u32()?)?;
+                    self.pop_all(&types)?;
+                    self.emit_branch(index, Op::Br(branch));
+                    self.set_unreachable();
+                }
+                0x0d => {
+                    let depth = reader.u32()?;
+                    self.pop_expect(I32)?;
+                    let (index, branch, types) = self.label(depth)?;
+                    self.pop_all(&types)?;
+                    self.push_all(&types);
+                    self.emit_branch(index, Op::BrIf(branch));
+                }
+                0x0e => {
+                    let count = reader.count()?;
+                    let depths = (0..=count)
+                        .map(|_| reader.u32())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    self.pop_expect(I32)?;
+                    let mut branches = Vec::with_capacity(depths.len());
+                    let mut targets = Vec::with_capacity(depths.len());
+                    let mut arity = None;
+                    for depth in depths {
+                        let (index, branch, types) = self.label(depth)?;
+                        if *arity.get_or_insert(types.len()) != types.len() {
+                            return Err(VmError::invalid("br_table arity mismatch"));
+                        }
+                        self.pop_all(&types)?;
+                        self.push_all(&types);
+                        branches.push(branch);
+                        targets.push((index, types));
+                    }
+                    let at = self.code.len();
+                    for (entry, (index, _)) in targets.iter().enumerate() {
+                        if self.controls[*index].kind != Kind::Loop {
+                            self.controls[*index].pending.push((at, entry));
+                        }
+                    }
+                    self.pop_all(&targets.last().unwrap().1)?;
+                    self.code.push(Op::BrTable(branches.into_boxed_slice()));
+                    self.set_unreachable();
+                }
+                0x0f => {
+                    self.pop_all(self.results)?;
+                    self.code.push(Op::Return);
+                    self.set_unreachable();
+                }
+                0x10 => {
+                    let index = reader.u32()?;
+                    let signature = self
+                        .module
+                        .function_type(index)
+                        .ok_or_else(|| VmError::invalid("unknown function"))?;
+                    self.pop_all(&signature.params)?;
+                    self.push_all(&signature.results);
+                    self.code.push(Op::Call(index));
+                }
+                0x11 => {
+                    let type_index = reader.u32()?;
+                    if reader.u8()? != 0 {
+                        return Err(VmError::invalid("unknown table"));
+                    }
+                    let signature = self
+                        .module
+                        .types
+                        .get(type_index as usize)
+                        .ok_or_else(|| VmError::invalid("unknown function type"))?;
+                    self.pop_expect(I32)?;
+                    self.pop_all(&signature.params)?;
+                    self.push_all(&signature.results);
+                    self.code.push(Op::CallIndirect(type_index));
+                }
+                0x1a => {
+                    self.pop()?;
+                    self.code.push(Op::Drop);
+                }
+                0x1b => {
+                    self.pop_expect(I32)?;
+                    let first = self.pop()?;
+                    let second = self.pop()?;
+                    if matches!((first, second), (Some(a), Some(b)) if a != b) {
+                        return Err(VmError::invalid("type mismatch"));
+                    }
+                    self.values.push(first.or(second));
+                    self.code.push(Op::Select);
+                }
+                0x1c => {
+                    if reader.u32()? != 1 {
+                        return Err(VmError::invalid("invalid result arity"));
+                    }
+                    let valtype = reader.valtype()?;
+                    self.pop_expect(I32)?;
+                    self.pop_expect(valtype)?;
+                    self.pop_expect(valtype)?;
+                    self.push(valtype);
+                    self.code.push(Op::Select);
+                }
+                0x20..=0x22 => {
+                    let index = reader.u32()?;
+                    let valtype = *self
+                        .locals
+                        .get(index as usize)
+                        .ok_or_else(|| VmError::invalid("unknown local"))?;
+                    match opcode {
+                        0x20 => {
+                            self.push(valtype);
+                            self.code.push(Op::LocalGet(index));
+                        }
+                        0x21 => {
+                            self.pop_expect(valtype)?;
+                            self.code.push(Op::LocalSet(index));
+                        }
+                        _ => {
+                            self.pop_expect(valtype)?;
+                            self.push(valtype);
+                            self.code.push(Op::LocalTee(index));
+                        }
+                    }
+                }
+                0x23 | 0x24 => {
+                    let index = reader.u32()?;
+                    let global = *self
+                        .module
+                        .globals
+                        .get(index as usize)
+                        .ok_or_else(|| VmError::invalid("unknown global"))?;
+                    if opcode == 0x23 {
+                        self.push(global.valtype);
+                        self.code.push(Op::GlobalGet(index));
+                    } else {
+                        if !global.mutable {
+                            return Err(VmError::invalid("global is immutable"));
+                        }
+                        self.pop_expect(global.valtype)?;
+                        self.code.push(Op::GlobalSet(index));
+                    }
+                }
+                0x28..=0x3e => {
+                    let (align, valtype) = memory_access(opcode)?;
+                    self.check_memory()?;
+                    if reader.u32()? > align {
+                        return Err(VmError::invalid("alignment exceeds natural alignment"));
+                    }
+                    let offset = reader.u32()?;
+                    if opcode <= 0x35 {
+                        self.pop_expect(I32)?;
+                        self.push(valtype);
+                        self.code.push(Op::Load(opcode, offset));
+                    } else {
+                        self.pop_expect(valtype)?;
+                        self.pop_expect(I32)?;
+                        self.code.push(Op::Store(opcode, offset));
+                    }
+                }
+                0x3f | 0x40 => {
+                    self.check_memory()?;
+                    if reader.u8()? != 0 {
+                        return Err(VmError::invalid("unknown memory"));
+                    }
+                    if opcode == 0x3f {
+                        self.code.push(Op::MemorySize);
+                    } else {
+                        self.pop_expect(I32)?;
+                        self.code.push(Op::MemoryGrow);
+                    }
+                    self.push(I32);
+                }
+                0x41 => {
+                    let value = reader.i32()?;
+                    self.push(I32);
+                    self.code.push(Op::Const(value as u32 as u64));
+                }
+                0x42 => {
+                    let value = reader.i64()?;
+                    self.push(I64);
+                    self.code.push(Op::Const(value as u64));
+                }
+                0xfc => {
+                    let opcode = reader.u32()?;
+                    let memories = match opcode {
+                        0..=7 => return Err(float_error()),
+                        10 => 2,
+                        11 => 1,
+                        _ => return Err(VmError::invalid("unsupported bulk instruction")),
+                    };
+                    self.check_memory()?;
+                    for _ in 0..memories {
+                        if reader.u8()? != 0 {
+                            return Err(VmError::invalid("unknown memory"));
+                        }
+                    }
+                    self.pop_all(&[I32, I32, I32])?;
+                    self.code.push(if opcode == 10 {
+                        Op::MemoryCopy
+                    } else {
+                        Op::MemoryFill
+                    });
+                }
+                _ => {
+                    let (params, result) = numeric_signature(opcode)?;
+                    self.pop_all(params)?;
+                    self.push(result);
+                    self.code.push(Op::Numeric(opcode));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn block_type(&self, reader: &mut Reader) -> Result<FuncType, VmError> {
+        match reader.s33()? {
+            -64 => Ok(FuncType::default()),
+            index if index >= 0 => self
+                .module
+                .types
+                .get(index as usize)
+                .cloned()
+                .ok_or_else(|| VmError::invalid("unknown block type")),
+            byte => Ok(FuncType::new(&[], &[valtype((byte & 0x7f) as u8)?])),
+        }
+    }
+
+    fn check_memory(&self) -> Result<(), VmError> {
+        if self.module.memory.is_none() {
+            return Err(VmError::invalid("unknown memory"));
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, valtype: ValType) {
+        self.values.push(Some(valtype));
+    }
+
+    fn push_all(&mut self, types: &[ValType]) {
+        for valtype in types {
+            self.push(*valtype);
+        }
+    }
+
+    fn pop(&mut self) -> Result<Option<ValType>, VmError> {
+        let control = self.controls.last().unwrap();
+        if self.values.len() == control.height {
+            if control.unreachable {
+                return Ok(None);
+            }
+            return Err(VmError::invalid("operand stack underflow"));
+        }
+        Ok(self.values.pop().unwrap())
+    }
+
+    fn pop_expect(&mut self, expected: ValType) -> Result<(), VmError> {
+        match self.pop()? {
+            Some(actual) if actual != expected => Err(VmError::invalid("type mismatch")),
+            _ => Ok(()),
+        }
+    }
+
+    fn pop_all(&mut self, types: &[ValType]) -> Result<(), VmError> {
+        for valtype in types.iter().rev() {
+            self.pop_expect(*valtype)?;
+        }
+        Ok(())
+    }
+
+    fn push_control(&mut self, kind: Kind, params: Vec<ValType>, results: Vec<ValType>) {
+        let height = self.values.len();
+        self.push_all(&params);
+        self.controls.push(Control {
+            kind,
+            params,
+            results,
+            height,
+            unreachable: false,
+            start: self.code.len() as u32,
+            pending: vec![],
+            if_branch: None,
+        });
+    }
+
+    fn pop_control(&mut self) -> Result<Control, VmError> {
+        let results = self.controls.last().unwrap().results.clone();
+        self.pop_all(&results)?;
+        let control = self.controls.pop().unwrap();
+        if self.values.len() != control.height {
+            return Err(VmError::invalid("operands left at the end of a block"));
+        }
+        Ok(control)
+    }
+
+    fn set_unreachable(&mut self) {
+        let control = self.controls.last_mut().unwrap();
+        self.values.truncate(control.height);
+        control.unreachable = true;
+    }
+
+    /// Resolves the label `depth` blocks out, returning the index of its
+    /// block, the branch to it from the current operand stack and the types
+    /// the branch carries
+    fn label(&self, depth: u32) -> Result<(usize, Branch, Vec<ValType>), VmError> {
+        let index = self
+            .controls
+            .len()
+            .checked_sub(depth as usize + 1)
+            .ok_or_else(|| VmError::invalid("unknown label"))?;
+        let control = &self.controls[index];
+        let types = if control.kind == Kind::Loop {
+            control.params.clone()
+        } else {
+            control.results.clone()
+        };
+        let branch = Branch {
+            // Forward branches are patched once the block ends
+            target: control.start,
+            drop: self
+                .values
+                .len()
+                .saturating_sub(control.height + types.len()) as u32,
+            keep: types.len() as u32,
+        };
+        Ok((index, branch, types))
+    }
+
+    fn emit_branch(&mut self, index: usize, op: Op) {
+        if self.controls[index].kind != Kind::Loop {
+            self.controls[index].pending.push((self.code.len(), 0));
+        }
+        self.code.push(op);
+    }
+
+    fn patch(&mut self, at: usize, entry: usize, target: u32) {
+        match &mut self.code[at] {
+            Op::Br(branch) | Op::BrIf(branch) | Op::BrUnless(branch) => branch.target = target,
+            Op::BrTable(branches) => branches[entry].target = target,
+            _ => {}
+        }
+    }
+}
+
+fn float_error() -> VmError {
+    VmError::invalid("floating point instructions are not supported")
+}
+
+/// Returns the natural alignment, as a power of two, and the value type of
+/// a load or store
+fn memory_access(opcode: u8) -> Result<(u32, ValType), VmError> {
+    use ValType::*;
+    Ok(match opcode {
+        0x2c | 0x2d | 0x3a => (0, I32),
+        0x2e | 0x2f | 0x3b => (1, I32),
+        0x28 | 0x36 => (2, I32),
+        0x30 | 0x31 | 0x3c => (0, I64),
+        0x32 | 0x33 | 0x3d => (1, I64),
+        0x34 | 0x35 | 0x3e => (2, I64),
+        0x29 | 0x37 => (3, I64),
+        _ => return Err(float_error()),
+    })
+}
+
+/// Returns the operand types and the result type of a numeric instruction
+fn numeric_signature(opcode: u8) -> Result<(&'static [ValType], ValType), VmError> {
+    use ValType::*;
+    Ok(match opcode {
+        0x45 | 0x67..=0x69 | 0xc0 | 0xc1 => (&[I32], I32),
+        0x46..=0x4f => (&[I32, I32], I32),
+        0x50 | 0xa7 => (&[I64], I32),
+        0x51..=0x5a => (&[I64, I64], I32),
+        0x6a..=0x78 => (&[I32, I32], I32),
+        0x79..=0x7b | 0xc2..=0xc4 => (&[I64], I64),
+        0x7c..=0x8a => (&[I64, I64], I64),
+        0xac | 0xad => (&[I32], I64),
+        0x43 | 0x44 | 0x5b..=0x66 | 0x8b..=0xa6 | 0xa8..=0xab | 0xae..=0xbf => {
+            return Err(float_error())
+        }
+        _ => {
+            return Err(VmError::invalid(format!(
+                "unsupported instruction {:#04x}",
+                opcode
+            )))
+        }
+    })
+}
@@ -0,0 +1,637 @@
+//! Execution of compiled modules
+
+use {
+    super::{
+        compiler::{Branch, Op},
+        module::Module,
+        VmError, BYTES_PER_UNIT, MAX_PAGES, PAGE_SIZE,
+    },
+    std::{ops::Range, sync::Arc},
+};
+
+/// Maximum depth of nested function calls
+pub const MAX_CALL_DEPTH: usize = 1024;
+/// Maximum number of locals and operands across all active calls
+pub const MAX_STACK_VALUES: usize = 256 * 1024;
+
+/// Services an instance gets from its host
+pub trait Host {
+    /// Returns the compute units left to spend
+    fn remaining_units(&self) -> u64;
+    /// Charges `units` compute units
+    fn consume_units(&mut self, units: u64) -> Result<(), VmError>;
+    /// Calls the imported function `id`
+    fn call(
+        &mut self,
+        id: usize,
+        args: &[u64],
+        memory: &mut Memory,
+    ) -> Result<Option<u64>, VmError>;
+}
+
+/// Linear memory of an instance
+#[derive(Debug)]
+pub struct Memory {
+    bytes: Vec<u8>,
+    max_pages: u32,
+}
+
+impl Memory {
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn pages(&self) -> u32 {
+        (self.bytes.len() / PAGE_SIZE) as u32
+    }
+
+    /// Grows the memory by `pages`, returning the previous number of pages
+    pub fn grow(&mut self, pages: u32) -> Option<u32> {
+        let previous = self.pages();
+        let new_pages = previous.checked_add(pages)?;
+        if new_pages > self.max_pages {
+            return None;
+        }
+        self.bytes.resize(new_pages as usize * PAGE_SIZE, 0);
+        Some(previous)
+    }
+
+    /// Maps `bytes` into fresh pages past the end of the memory, returning
+    /// their address. The pages do not count against the module's maximum.
+    pub fn append(&mut self, bytes: &[u8]) -> Result<u64, VmError> {
+        let address = self.bytes.len() as u64;
+        let pages = ((bytes.len() + PAGE_SIZE - 1) / PAGE_SIZE) as u32;
+        let new_pages = self.pages() + pages;
+        if new_pages > MAX_PAGES {
+            return Err(VmError::trap("input does not fit in memory"));
+        }
+        self.max_pages = self.max_pages.saturating_add(pages).min(MAX_PAGES);
+        self.bytes.resize(new_pages as usize * PAGE_SIZE, 0);
+        self.write(address, bytes)?;
+        Ok(address)
+    }
+
+    fn range(&self, address: u64, len: u64) -> Result<Range<usize>, VmError> {
+        match address.checked_add(len) {
+            Some(end) if end <= self.bytes.len() as u64 => Ok(address as usize..end as usize),
+            _ => Err(VmError::trap("out of bounds memory access")),
+        }
+    }
+
+    pub fn read(&self, address: u64, len: u64) -> Result<&[u8], VmError> {
+        let range = self.range(address, len)?;
+        Ok(&self.bytes[range])
+    }
+
+    pub fn read_mut(&mut self, address: u64, len: u64) -> Result<&mut [u8], VmError> {
+        let range = self.range(address, len)?;
+        Ok(&mut self.bytes[range])
+    }
+
+    pub fn write(&mut self, address: u64, bytes: &[u8]) -> Result<(), VmError> {
+        self.read_mut(address, bytes.len() as u64)?
+            .copy_from_slice(bytes);
+        Ok(())
+    }
+
+    pub fn read_u32(&self, address: u64) -> Result<u32, VmError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read(address, 4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    pub fn read_u64(&self, address: u64) -> Result<u64, VmError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read(address, 8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// An instantiated module with its own memory and globals
+#[derive(Debug)]
+pub struct Instance {
+    module: Arc<Module>,
+    pub memory: Memory,
+    globals: Vec<u64>,
+}
+
+#[derive(Clone, Copy)]
+struct Frame {
+    function: usize,
+    pc: usize,
+    base: usize,
+}
+
+impl Instance {
+    pub fn new(module: Arc<Module>) -> Result<Self, VmError> {
+        let (min, max) = module.memory.unwrap_or((0, 0));
+        let mut memory = Memory {
+            bytes: vec![0; min as usize * PAGE_SIZE],
+            max_pages: max.min(MAX_PAGES),
+        };
+        for segment in module.data.iter() {
+            memory
+                .write(segment.offset as u64, &segment.bytes)
+                .map_err(|_| VmError::invalid("data segment out of bounds"))?;
+        }
+        let globals = module.globals.iter().map(|global| global.value).collect();
+        Ok(Self {
+            module,
+            memory,
+            globals,
+        })
+    }
+
+    /// Calls the module's entrypoint with the address of its input
+    pub fn call_entrypoint(&mut self, host: &mut dyn Host, input: u32) -> Result<u64, VmError> {
+        let entrypoint = self.module.entrypoint;
+        let results = self.invoke(host, entrypoint, &[input as u64])?;
+        results
+            .first()
+            .copied()
+            .ok_or_else(|| VmError::trap("missing return value"))
+    }
+
+    /// Calls the defined function `function` and returns its results
+    pub fn invoke(
+        &mut self,
+        host: &mut dyn Host,
+        function: u32,
+        args: &[u64],
+    ) -> Result<Vec<u64>, VmError> {
+        let module = self.module.clone();
+        let function = (function as usize)
+            .checked_sub(module.imports.len())
+            .filter(|index| *index < module.functions.len())
+            .ok_or_else(|| VmError::trap("cannot invoke an imported function"))?;
+        let mut stack = Vec::with_capacity(1024);
+        stack.extend_from_slice(args);
+        let mut frames = vec![];
+        let mut frame = enter(&module, &mut stack, &mut frames, None, function)?;
+
+        let mut fuel = host.remaining_units();
+        let mut used = 0u64;
+        macro_rules! charge {
+            ($units:expr) => {
+                used = used.saturating_add($units);
+                if used > fuel {
+                    host.consume_units(used)?;
+                    return Err(VmError::ExceededBudget);
+                }
+            };
+        }
+
+        loop {
+            let op = &module.functions[frame.function].code[frame.pc];
+            frame.pc += 1;
+            charge!(1);
+
+            let mut call = None;
+            match op {
+                Op::Unreachable => return Err(VmError::trap("unreachable")),
+                Op::Br(target) => {
+                    frame.pc = take_branch(&mut stack, target)?;
+                }
+                Op::BrIf(target) => {
+                    if pop(&mut stack)? as u32 != 0 {
+                        frame.pc = take_branch(&mut stack, target)?;
+                    }
+                }
+                Op::BrUnless(target) => {
+                    if pop(&mut stack)? as u32 == 0 {
+                        frame.pc = take_branch(&mut stack, target)?;
+                    }
+                }
+                Op::BrTable(targets) => {
+                    let index = pop(&mut stack)? as u32 as usize;
+                    let target = targets
+                        .get(index)
+                        .or_else(|| targets.last())
+                        .ok_or_else(|| VmError::trap("empty branch table"))?;
+                    frame.pc = take_branch(&mut stack, target)?;
+                }
+                Op::Return => {
+                    let function = &module.functions[frame.function];
+                    let results = module.types[function.type_index as usize].results.len();
+                    let start = stack
+                        .len()
+                        .checked_sub(results)
+                        .filter(|start| *start >= frame.base)
+                        .ok_or_else(|| VmError::trap("operand stack underflow"))?;
+                    stack.copy_within(start.., frame.base);
+                    stack.truncate(frame.base + results);
+                    match frames.pop() {
+                        Some(caller) => frame = caller,
+                        None => break,
+                    }
+                }
+                Op::Call(index) => call = Some(*index),
+                Op::CallIndirect(type_index) => {
+                    let element = pop(&mut stack)? as u32 as usize;
+                    let index = module
+                        .table
+                        .get(element)
+                        .copied()
+                        .flatten()
+                        .ok_or_else(|| VmError::trap("undefined table element"))?;
+                    if module.function_type(index) != module.types.get(*type_index as usize) {
+                        return Err(VmError::trap("indirect call signature mismatch"));
+                    }
+                    call = Some(index);
+                }
+                Op::Drop => {
+                    pop(&mut stack)?;
+                }
+                Op::Select => {
+                    let condition = pop(&mut stack)? as u32;
+                    let second = pop(&mut stack)?;
+                    let first = pop(&mut stack)?;
+                    stack.push(if condition != 0 { first } else { second });
+                }
+                Op::LocalGet(index) => {
+                    let value = *local(&mut stack, &frame, *index)?;
+                    stack.push(value);
+                }
+                Op::LocalSet(index) => {
+                    let value = pop(&mut stack)?;
+                    *local(&mut stack, &frame, *index)? = value;
+                }
+                Op::LocalTee(index) => {
+                    let value = *stack
+                        .last()
+                        .ok_or_else(|| VmError::trap("operand stack underflow"))?;
+                    *local(&mut stack, &frame, *index)? = value;
+                }
+                Op::GlobalGet(index) => {
+                    let value = *self
+                        .globals
+                        .get(*index as usize)
+                        .ok_or_else(|| VmError::trap("unknown global"))?;
+                    stack.push(value);
+                }
+                Op::GlobalSet(index) => {
+                    let value = pop(&mut stack)?;
+                    *self
+                        .globals
+                        .get_mut(*index as usize)
+                        .ok_or_else(|| VmError::trap("unknown global"))? = value;
+                }
+                Op::Load(opcode, offset) => {
+                    let address = pop(&mut stack)? as u32 as u64 + *offset as u64;
+                    stack.push(load(&self.memory, *opcode, address)?);
+                }
+                Op::Store(opcode, offset) => {
+                    let value = pop(&mut stack)?;
+                    let address = pop(&mut stack)? as u32 as u64 + *offset as u64;
+                    let width = match opcode {
+                        0x3a | 0x3c => 1,
+                        0x3b | 0x3d => 2,
+                        0x36 | 0x3e => 4,
+                        _ => 8,
+                    };
+                    self.memory.write(address, &value.to_le_bytes()[..width])?;
+                }
+                Op::MemorySize => stack.push(self.memory.pages() as u64),
+                Op::MemoryGrow => {
+                    let pages = pop(&mut stack)? as u32;
+                    charge!(pages as u64 * PAGE_SIZE as u64 / BYTES_PER_UNIT);
+                    let previous = self.memory.grow(pages).unwrap_or(u32::MAX);
+                    stack.push(previous as u64);
+                }
+                Op::MemoryCopy => {
+                    let len = pop(&mut stack)? as u32 as u64;
+                    let source = pop(&mut stack)? as u32 as u64;
+                    let destination = pop(&mut stack)? as u32 as u64;
+                    charge!(len / BYTES_PER_UNIT);
+                    let source = self.memory.range(source, len)?;
+                    let destination = self.memory.range(destination, len)?;
+                    self.memory.bytes.copy_within(source, destination.start);
+                }
+                Op::MemoryFill => {
+                    let len = pop(&mut stack)? as u32 as u64;
+                    let value = pop(&mut stack)? as u8;
+                    let destination = pop(&mut stack)? as u32 as u64;
+                    charge!(len / BYTES_PER_UNIT);
+                    self.memory.read_mut(destination, len)?.fill(value);
+                }
+                Op::Const(value) => stack.push(*value),
+                Op::Numeric(opcode) => numeric(*opcode, &mut stack)?,
+            }
+
+            if let Some(index) = call {
+                let index = index as usize;
+                if let Some((host_function, type_index)) = module.imports.get(index) {
+                    let params = module.types[*type_index as usize].params.len();
+                    let start = stack
+                        .len()
+                        .checked_sub(params)
+                        .ok_or_else(|| VmError::trap("operand stack underflow"))?;
+                    let args = stack.split_off(start);
+                    host.consume_units(used)?;
+                    used = 0;
+                    let result = host.call(host_function.id, &args, &mut self.memory)?;
+                    fuel = host.remaining_units();
+                    stack.extend(result);
+                } else {
+                    let function = index - module.imports.len();
+                    frame = enter(&module, &mut stack, &mut frames, Some(frame), function)?;
+                }
+            }
+        }
+
+        host.consume_units(used)?;
+        Ok(stack)
+    }
+}
+
+/// Sets up the locals of a call to the defined function `function`
+fn enter(
+    module: &Module,
+    stack: &mut Vec<u64>,
+    frames: &mut Vec<Frame>,
+    caller: Option<Frame>,
+    function: usize,
+) -> Result<Frame, VmError> {
+    if frames.len() >= MAX_CALL_DEPTH {
+        return Err(VmError::trap("call stack exhausted"));
+    }
+    let callee = &module.functions[function];
+    let params = module.types[callee.type_index as usize].params.len();
+    let base = stack
+        .len()
+        .checked_sub(params)
+        .ok_or_else(|| VmError::trap("operand stack underflow"))?;
+    if base + callee.locals.len() > MAX_STACK_VALUES {
+        return Err(VmError::trap("value stack exhausted"));
+    }
+    stack.resize(base + callee.locals.len(), 0);
+    frames.extend(caller);
+    Ok(Frame {
+        function,
+        pc: 0,
+        base,
+    })
+}
+
+fn pop(stack: &mut Vec<u64>) -> Result<u64, VmError> {
+    stack
+        .pop()
+        .ok_or_else(|| VmError::trap("operand stack underflow"))
+}
+
+fn local<'a>(stack: &'a mut [u64], frame: &Frame, index: u32) -> Result<&'a mut u64, VmError> {
+    stack
+        .get_mut(frame.base + index as usize)
+        .ok_or_else(|| VmError::trap("unknown local"))
+}
+
+/// Unwinds the operand stack for `branch` and returns its target
+fn take_branch(stack: &mut Vec<u64>, branch: &Branch) -> Result<usize, VmError> {
+    if branch.drop > 0 {
+        let keep = branch.keep as usize;
+        let drop = branch.drop as usize;
+        let start = stack
+            .len()
+            .checked_sub(keep + drop)
+            .ok_or_else(|| VmError::trap("operand stack underflow"))?;
+        stack.copy_within(start + drop.., start);
+        stack.truncate(start + keep);
+    }
+    Ok(branch.target as usize)
+}
+
+fn load(memory: &Memory, opcode: u8, address: u64) -> Result<u64, VmError> {
+    let width = match opcode {
+        0x2c | 0x2d | 0x30 | 0x31 => 1,
+        0x2e | 0x2f | 0x32 | 0x33 => 2,
+        0x28 | 0x34 | 0x35 => 4,
+        _ => 8,
+    };
+    let mut bytes = [0; 8];
+    bytes[..width].copy_from_slice(memory.read(address, width as u64)?);
+    let value = u64::from_le_bytes(bytes);
+    Ok(match opcode {
+        0x2c => value as i8 as u32 as u64,
+        0x2e => value as i16 as u32 as u64,
+        0x30 => value as i8 as u64,
+        0x32 => value as i16 as u64,
+        0x34 => value as i32 as u64,
+        _ => value,
+    })
+}
+
+fn numeric(opcode: u8, stack: &mut Vec<u64>) -> Result<(), VmError> {
+    let divide_by_zero = || VmError::trap("integer divide by zero");
+    let overflow = || VmError::trap("integer overflow");
+    let value = match opcode {
+        0x45 | 0x67..=0x69 | 0xac | 0xad | 0xc0 | 0xc1 => {
+            let a = pop(stack)? as u32;
+            match opcode {
+                0x45 => (a == 0) as u64,
+                0x67 => a.leading_zeros() as u64,
+                0x68 => a.trailing_zeros() as u64,
+                0x69 => a.count_ones() as u64,
+                0xac => a as i32 as u64,
+                0xad => a as u64,
+                0xc0 => a as i8 as u32 as u64,
+                _ => a as i16 as u32 as u64,
+            }
+        }
+        0x50 | 0x79..=0x7b | 0xa7 | 0xc2..=0xc4 => {
+            let a = pop(stack)?;
+            match opcode {
+                0x50 => (a == 0) as u64,
+                0x79 => a.leading_zeros() as u64,
+                0x7a => a.trailing_zeros() as u64,
+                0x7b => a.count_ones() as u64,
+                0xa7 => a as u32 as u64,
+                0xc2 => a as i8 as u64,
+                0xc3 => a as i16 as u64,
+                _ => a as i32 as u64,
+            }
+        }
+        0x46..=0x4f | 0x6a..=0x78 => {
+            let b = pop(stack)? as u32;
+            let a = pop(stack)? as u32;
+            let (sa, sb) = (a as i32, b as i32);
+            match opcode {
+                0x46 => (a == b) as u64,
+                0x47 => (a != b) as u64,
+                0x48 => (sa < sb) as u64,
+                0x49 => (a < b) as u64,
+                0x4a => (sa > sb) as u64,
+                0x4b => (a > b) as u64,
+                0x4c => (sa <= sb) as u64,
+                0x4d => (a <= b) as u64,
+                0x4e => (sa >= sb) as u64,
+                0x4f => (a >= b) as u64,
+                0x6a => a.wrapping_add(b) as u64,
+                0x6b => a.wrapping_sub(b) as u64,
+                0x6c => a.wrapping_mul(b) as u64,
+                0x6d => {
+                    if b == 0 {
+                        return Err(divide_by_zero());
+                    }
+                    sa.checked_div(sb).ok_or_else(overflow)? as u32 as u64
+                }
+                0x6e => a.checked_div(b).ok_or_else(divide_by_zero)? as u64,
+                0x6f => {
+                    if b == 0 {
+                        return Err(divide_by_zero());
+                    }
+                    sa.wrapping_rem(sb) as u32 as u64
+                }
+                0x70 => a.checked_rem(b).ok_or_else(divide_by_zero)? as u64,
+                0x71 => (a & b) as u64,
+                0x72 => (a | b) as u64,
+                0x73 => (a ^ b) as u64,
+                0x74 => a.wrapping_shl(b) as u64,
+                0x75 => sa.wrapping_shr(b) as u32 as u64,
+                0x76 => a.wrapping_shr(b) as u64,
+                0x77 => a.rotate_left(b % 32) as u64,
+                _ => a.rotate_right(b % 32) as u64,
+            }
+        }
+        0x51..=0x5a | 0x7c..=0x8a => {
+            let b = pop(stack)?;
+            let a = pop(stack)?;
+            let (sa, sb) = (a as i64, b as i64);
+            match opcode {
+                0x51 => (a == b) as u64,
+                0x52 => (a != b) as u64,
+                0x53 => (sa < sb) as u64,
+                0x54 => (a < b) as u64,
+                0x55 => (sa > sb) as u64,
+                0x56 => (a > b) as u64,
+                0x57 => (sa <= sb) as u64,
+                0x58 => (a <= b) as u64,
+                0x59 => (sa >= sb) as u64,
+                0x5a => (a >= b) as u64,
+                0x7c => a.wrapping_add(b),
+                0x7d => a.wrapping_sub(b),
+                0x7e => a.wrapping_mul(b),
+                0x7f => {
+                    if b == 0 {
+                        return Err(divide_by_zero());
+                    }
+                    sa.checked_div(sb).ok_or_else(overflow)? as u64
+                }
+                0x80 => a.checked_div(b).ok_or_else(divide_by_zero)?,
+                0x81 => {
+                    if b == 0 {
+                        return Err(divide_by_zero());
+                    }
+                    sa.wrapping_rem(sb) as u64
+                }
+                0x82 => a.checked_rem(b).ok_or_else(divide_by_zero)?,
+                0x83 => a & b,
+                0x84 => a | b,
+                0x85 => a ^ b,
+                0x86 => a.wrapping_shl(b as u32),
+                0x87 => sa.wrapping_shr(b as u32) as u64,
+                0x88 => a.wrapping_shr(b as u32),
+                0x89 => a.rotate_left((b % 64) as u32),
+                _ => a.rotate_right((b % 64) as u32),
+            }
+        }
+        _ => return Err(VmError::trap("unsupported instruction")),
+    };
+    stack.push(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestHost {
+        units: u64,
+    }
+
+    impl Host for TestHost {
+        fn remaining_units(&self) -> u64 {
+            self.units
+        }
+
+        fn consume_units(&mut self, units: u64) -> Result<(), VmError> {
+            self.units = self
+                .units
+                .checked_sub(units)
+                .ok_or(VmError::ExceededBudget)?;
+            Ok(())
+        }
+
+        fn call(&mut self, _: usize, _: &[u64], _: &mut Memory) -> Result<Option<u64>, VmError> {
+            Err(VmError::trap("no imports"))
+        }
+    }
+
+    fn section(id: u8, contents: &[u8]) -> Vec<u8> {
+        [&[id, contents.len() as u8], contents].concat()
+    }
+
+    /// Returns three times its input, counting down in a loop
+    fn times_three() -> Vec<u8> {
+        #[rustfmt::skip]
+        let body = [
+            0x01, 0x01, 0x7e, // one i64 local
+            0x02, 0x40, 0x03, 0x40, // block, loop
+            0x20, 0x00, 0x45, 0x0d, 0x01, // exit once the input is zero
+            0x20, 0x01, 0x42, 0x03, 0x7c, 0x21, 0x01, // local 1 += 3
+            0x20, 0x00, 0x41, 0x01, 0x6b, 0x21, 0x00, // local 0 -= 1
+            0x0c, 0x00, 0x0b, 0x0b, // br 0, end, end
+            0x20, 0x01, 0x0b,
+        ];
+        let mut entrypoint = vec![0x01, 0x0a];
+        entrypoint.extend_from_slice(b"entrypoint");
+        entrypoint.extend_from_slice(&[0x00, 0x00]);
+        [
+            b"\0asm\x01\0\0\0".to_vec(),
+            section(1, &[0x01, 0x60, 0x01, 0x7f, 0x01, 0x7e]),
+            section(3, &[0x01, 0x00]),
+            section(5, &[0x01, 0x00, 0x01]),
+            section(7, &entrypoint),
+            section(10, &[&[0x01, body.len() as u8], &body[..]].concat()),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_metered_execution() {
+        let mut bytes = times_three();
+        // Accounts holding bytecode are zero padded
+        bytes.extend_from_slice(&[0; 16]);
+        let module = Arc::new(Module::new(&bytes, |_, _| None).unwrap());
+
+        let mut host = TestHost { units: 1_000 };
+        let mut instance = Instance::new(module.clone()).unwrap();
+        assert_eq!(instance.call_entrypoint(&mut host, 10), Ok(30));
+        assert!(host.units < 1_000);
+
+        let mut host = TestHost { units: 1_000 };
+        let mut instance = Instance::new(module).unwrap();
+        assert_eq!(
+            instance.call_entrypoint(&mut host, 1_000),
+            Err(VmError::ExceededBudget)
+        );
+    }
+
+    #[test]
+    fn test_invalid_modules() {
+        let bytes = times_three();
+        assert!(Module::new(&bytes[..bytes.len() - 1], |_, _| None).is_err());
+        assert!(Module::new(b"\0asm\x02\0\0\0", |_, _| None).is_err());
+
+        // Floating point types are not supported
+        let mut floats = bytes;
+        floats[13] = 0x7d;
+        assert_eq!(
+            Module::new(&floats, |_, _| None).map(|_| ()),
+            Err(VmError::invalid("floating point types are not supported"))
+        );
+    }
+}
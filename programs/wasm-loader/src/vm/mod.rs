@@ -0,0 +1,76 @@
+//! A deterministic interpreter for the integer subset of WebAssembly 1.0.
+//!
+//! Floating point types and instructions are rejected when a module is
+//! loaded, so every accepted program behaves identically on every validator.
+//! Execution is metered one compute unit per instruction, plus the cost of
+//! bulk memory operations and of the host functions a program imports.
+
+pub mod compiler;
+pub mod interpreter;
+pub mod module;
+
+use {mundis_sdk::instruction::InstructionError, thiserror::Error};
+
+pub use {
+    interpreter::{Host, Instance, Memory},
+    module::{HostFunction, Module},
+};
+
+/// Size of a WebAssembly memory page
+pub const PAGE_SIZE: usize = 64 * 1024;
+/// Upper bound on the linear memory of a program, 10 MiB
+pub const MAX_PAGES: u32 = 160;
+/// Bytes copied or filled per compute unit by bulk memory instructions
+pub const BYTES_PER_UNIT: u64 = 250;
+
+/// Errors raised while loading or running a WebAssembly module
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum VmError {
+    #[error("invalid module: {0}")]
+    InvalidModule(String),
+    #[error("program trapped: {0}")]
+    Trap(String),
+    #[error("exceeded the compute budget")]
+    ExceededBudget,
+    #[error("host function failed: {0}")]
+    Host(InstructionError),
+}
+
+impl VmError {
+    pub(crate) fn invalid<T: ToString>(message: T) -> Self {
+        VmError::InvalidModule(message.to_string())
+    }
+
+    pub(crate) fn trap<T: ToString>(message: T) -> Self {
+        VmError::Trap(message.to_string())
+    }
+}
+
+impl From<InstructionError> for VmError {
+    fn from(error: InstructionError) -> Self {
+        VmError::Host(error)
+    }
+}
+
+/// Value types of the supported subset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValType {
+    I32,
+    I64,
+}
+
+/// Signature of a function
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuncType {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+}
+
+impl FuncType {
+    pub fn new(params: &[ValType], results: &[ValType]) -> Self {
+        Self {
+            params: params.to_vec(),
+            results: results.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! Decoding and validation of the WebAssembly binary format
+
+use {
+    super::{
+        compiler::{compile_function, Op},
+        FuncType, ValType, VmError, MAX_PAGES,
+    },
+    std::str,
+};
+
+const MAGIC: &[u8] = b"\0asm";
+const VERSION: u32 = 1;
+
+/// Name of the exported function the loader calls into
+pub const ENTRYPOINT: &str = "entrypoint";
+
+/// Maximum number of functions, globals, table elements or data segments
+const MAX_ITEMS: u32 = 100_000;
+/// Maximum number of locals in a single function
+const MAX_LOCALS: u32 = 50_000;
+
+/// A function the host provides to modules that import it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostFunction {
+    /// Opaque identifier handed back to the host on calls
+    pub id: usize,
+    pub signature: FuncType,
+}
+
+#[derive(Debug)]
+pub(crate) struct Function {
+    pub type_index: u32,
+    /// Types of the parameters followed by the declared locals
+    pub locals: Vec<ValType>,
+    pub code: Vec<Op>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Global {
+    pub valtype: ValType,
+    pub mutable: bool,
+    pub value: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct DataSegment {
+    pub offset: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// A decoded, validated and compiled module, ready to be instantiated
+#[derive(Debug, Default)]
+pub struct Module {
+    pub(crate) types: Vec<FuncType>,
+    /// Imported host functions, which come first in the function index space
+    pub(crate) imports: Vec<(HostFunction, u32)>,
+    pub(crate) functions: Vec<Function>,
+    pub(crate) table: Vec<Option<u32>>,
+    pub(crate) memory: Option<(u32, u32)>,
+    pub(crate) globals: Vec<Global>,
+    pub(crate) data: Vec<DataSegment>,
+    pub(crate) entrypoint: u32,
+}
+
+impl Module {
+    /// Decodes `bytes`, resolving each imported function with `resolve`,
+    /// which takes the import's module and field name.
+    pub fn new<F>(bytes: &[u8], resolve: F) -> Result<Self, VmError>
+    where
+        F: Fn(&str, &str) -> Option<HostFunction>,
+    {
+        let mut reader = Reader::new(bytes);
+        if reader.bytes(4)? != MAGIC {
+            return Err(VmError::invalid("bad magic number"));
+        }
+        if u32::from_le_bytes(reader.array()?) != VERSION {
+            return Err(VmError::invalid("unsupported version"));
+        }
+
+        let mut module = Module::default();
+        let mut function_types = vec![];
+        let mut bodies = vec![];
+        let mut entrypoint = None;
+        let mut last_section = 0;
+        while !reader.is_empty() {
+            // Program data accounts are zero padded past the bytecode
+            if reader.rest().iter().all(|byte| *byte == 0) {
+                break;
+            }
+            let id = reader.u8()?;
+            let size = reader.u32()? as usize;
+            let mut section = Reader::new(reader.bytes(size)?);
+            if id != 0 {
+                // The data count section sits between the function and code sections
+                let order = if id == 12 {
+                    10
+                } else if id >= 10 {
+                    id + 1
+                } else {
+                    id
+                };
+                if order <= last_section {
+                    return Err(VmError::invalid("sections out of order"));
+                }
+                last_section = order;
+            }
+            match id {
+                0 => continue,
+                1 => {
+                    for _ in 0..section.count()? {
+                        if section.u8()? != 0x60 {
+                            return Err(VmError::invalid("expected a function type"));
+                        }
+                        let params = section.valtypes()?;
+                        let results = section.valtypes()?;
+                        module.types.push(FuncType { params, results });
+                    }
+                }
+                2 => {
+                    for _ in 0..section.count()? {
+                        let import_module = section.name()?;
+                        let field = section.name()?;
+                        if section.u8()? != 0x00 {
+                            return Err(VmError::invalid(format!(
+                                "import {}.{} is not a function",
+                                import_module, field
+                            )));
+                        }
+                        let type_index = section.u32()?;
+                        let host = resolve(import_module, field).ok_or_else(|| {
+                            VmError::invalid(format!("unknown import {}.{}", import_module, field))
+                        })?;
+                        if module.types.get(type_index as usize) != Some(&host.signature) {
+                            return Err(VmError::invalid(format!(
+                                "import {}.{} has the wrong signature",
+                                import_module, field
+                            )));
+                        }
+                        module.imports.push((host, type_index));
+                    }
+                }
+                3 => {
+                    for _ in 0..section.count()? {
+                        let type_index = section.u32()?;
+                        if type_index as usize >= module.types.len() {
+                            return Err(VmError::invalid("unknown function type"));
+                        }
+                        function_types.push(type_index);
+                    }
+                }
+                4 => {
+                    if section.count()? != 1 || section.u8()? != 0x70 {
+                        return Err(VmError::invalid("expected a single funcref table"));
+                    }
+                    let (min, _) = section.limits(MAX_ITEMS)?;
+                    module.table = vec![None; min as usize];
+                }
+                5 => {
+                    if section.count()? != 1 {
+                        return Err(VmError::invalid("expected a single memory"));
+                    }
+                    module.memory = Some(section.limits(MAX_PAGES)?);
+                }
+                6 => {
+                    for _ in 0..section.count()? {
+                        let valtype = section.valtype()?;
+                        let mutable = match section.u8()? {
+                            0 => false,
+                            1 => true,
+                            _ => return Err(VmError::invalid("bad global mutability")),
+                        };
+                        let value = section.const_expr(valtype, &module.globals)?;
+                        module.globals.push(Global {
+                            valtype,
+                            mutable,
+                            value,
+                        });
+                    }
+                }
+                7 => {
+                    for _ in 0..section.count()? {
+                        let name = section.name()?;
+                        let kind = section.u8()?;
+                        let index = section.u32()?;
+                        if kind == 0x00 && name == ENTRYPOINT {
+                            entrypoint = Some(index);
+                        }
+                    }
+                }
+                8 => return Err(VmError::invalid("start functions are not supported")),
+                9 => {
+                    for _ in 0..section.count()? {
+                        if section.u32()? != 0 {
+                            return Err(VmError::invalid("unsupported element segment"));
+                        }
+                        let offset =
+                            section.const_expr(ValType::I32, &module.globals)? as u32 as usize;
+                        let count = section.count()? as usize;
+                        if offset.saturating_add(count) > module.table.len() {
+                            return Err(VmError::invalid("element segment out of bounds"));
+                        }
+                        for slot in module.table[offset..offset + count].iter_mut() {
+                            *slot = Some(section.u32()?);
+                        }
+                    }
+                }
+                10 => {
+                    let count = section.count()?;
+                    if count as usize != function_types.len() {
+                        return Err(VmError::invalid("function and code counts differ"));
+                    }
+                    for type_index in function_types.iter() {
+                        let size = section.u32()? as usize;
+                        let mut body = Reader::new(section.bytes(size)?);
+                        let mut locals = module.types[*type_index as usize].params.clone();
+                        for _ in 0..body.count()? {
+                            let count = body.u32()?;
+                            let valtype = body.valtype()?;
+                            if locals.len() as u64 + count as u64 > MAX_LOCALS as u64 {
+                                return Err(VmError::invalid("too many locals"));
+                            }
+                            locals.extend(std::iter::repeat(valtype).take(count as usize));
+                        }
+                        module.functions.push(Function {
+                            type_index: *type_index,
+                            locals,
+                            code: vec![],
+                        });
+                        // Compiled once all function signatures are known
+                        bodies.push(body.rest());
+                    }
+                }
+                11 => {
+                    for _ in 0..section.count()? {
+                        if section.u32()? != 0 {
+                            return Err(VmError::invalid("unsupported data segment"));
+                        }
+                        let offset = section.const_expr(ValType::I32, &module.globals)? as u32;
+                        let size = section.u32()? as usize;
+                        let bytes = section.bytes(size)?.to_vec();
+                        module.data.push(DataSegment { offset, bytes });
+                    }
+                }
+                12 => {
+                    section.u32()?;
+                }
+                _ => return Err(VmError::invalid(format!("unknown section {}", id))),
+            }
+            if !section.is_empty() {
+                return Err(VmError::invalid(format!("malformed section {}", id)));
+            }
+        }
+        if function_types.len() != module.functions.len() {
+            return Err(VmError::invalid("function and code counts differ"));
+        }
+
+        let num_functions = module.imports.len() + module.functions.len();
+        if module
+            .table
+            .iter()
+            .flatten()
+            .any(|index| *index as usize >= num_functions)
+        {
+            return Err(VmError::invalid("unknown function in table"));
+        }
+        for (index, body) in bodies.into_iter().enumerate() {
+            module.functions[index].code = compile_function(&module, index, body)?;
+        }
+
+        let entrypoint = entrypoint.ok_or_else(|| VmError::invalid("missing entrypoint export"))?;
+        let signature = module
+            .function_type(entrypoint)
+            .ok_or_else(|| VmError::invalid("unknown entrypoint function"))?;
+        if *signature != FuncType::new(&[ValType::I32], &[ValType::I64]) {
+            return Err(VmError::invalid(
+                "entrypoint must take an i32 and return an i64",
+            ));
+        }
+        if (entrypoint as usize) < module.imports.len() {
+            return Err(VmError::invalid("entrypoint cannot be an import"));
+        }
+        if module.memory.is_none() {
+            return Err(VmError::invalid("missing memory"));
+        }
+        module.entrypoint = entrypoint;
+        Ok(module)
+    }
+
+    /// Returns the signature of a function by its index
+    pub(crate) fn function_type(&self, index: u32) -> Option<&FuncType> {
+        let index = index as usize;
+        let type_index = if index < self.imports.len() {
+            self.imports[index].1
+        } else {
+            self.functions.get(index - self.imports.len())?.type_index
+        };
+        self.types.get(type_index as usize)
+    }
+}
+
+/// Cursor over the bytes of a module
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    pub fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    pub fn u8(&mut self) -> Result<u8, VmError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| VmError::invalid("unexpected end of module"))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], VmError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| VmError::invalid("unexpected end of module"))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], VmError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    /// Reads an unsigned LEB128 integer of at most `bits` bits
+    fn unsigned(&mut self, bits: u32) -> Result<u64, VmError> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift + 7 >= bits {
+                // Last byte, the unused high bits must be zero
+                if byte & 0x80 != 0 || (byte & 0x7f) >> (bits - shift) != 0 {
+                    return Err(VmError::invalid("integer too large"));
+                }
+            }
+            result |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Reads a signed LEB128 integer of at most `bits` bits
+    fn signed(&mut self, bits: u32) -> Result<i64, VmError> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift + 7 >= bits {
+                // Last byte, the unused high bits must extend the sign bit
+                let mask = 0x7f & !((1u8 << (bits - shift - 1)) - 1);
+                if byte & 0x80 != 0 || (byte & mask != 0 && byte & mask != mask) {
+                    return Err(VmError::invalid("integer too large"));
+                }
+            }
+            result |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+                return Ok(result);
+            }
+        }
+    }
+
+    pub fn u32(&mut self) -> Result<u32, VmError> {
+        Ok(self.unsigned(32)? as u32)
+    }
+
+    pub fn i32(&mut self) -> Result<i32, VmError> {
+        Ok(self.signed(32)? as i32)
+    }
+
+    pub fn i64(&mut self) -> Result<i64, VmError> {
+        self.signed(64)
+    }
+
+    pub fn s33(&mut self) -> Result<i64, VmError> {
+        self.signed(33)
+    }
+
+    /// Reads the length of a vector, capped to keep allocations bounded
+    pub fn count(&mut self) -> Result<u32, VmError> {
+        let count = self.u32()?;
+        if count > MAX_ITEMS {
+            return Err(VmError::invalid("too many items"));
+        }
+        Ok(count)
+    }
+
+    fn name(&mut self) -> Result<&'a str, VmError> {
+        let len = self.u32()? as usize;
+        str::from_utf8(self.bytes(len)?).map_err(|_| VmError::invalid("malformed name"))
+    }
+
+    pub fn valtype(&mut self) -> Result<ValType, VmError> {
+        valtype(self.u8()?)
+    }
+
+    fn valtypes(&mut self) -> Result<Vec<ValType>, VmError> {
+        (0..self.count()?).map(|_| self.valtype()).collect()
+    }
+
+    fn limits(&mut self, cap: u32) -> Result<(u32, u32), VmError> {
+        let (min, max) = match self.u8()? {
+            0 => (self.u32()?, cap),
+            1 => (self.u32()?, self.u32()?),
+            _ => return Err(VmError::invalid("bad limits")),
+        };
+        if min > max || min > cap {
+            return Err(VmError::invalid("limits out of range"));
+        }
+        Ok((min, max.min(cap)))
+    }
+
+    /// Reads a constant initializer expression of type `valtype`
+    fn const_expr(&mut self, valtype: ValType, globals: &[Global]) -> Result<u64, VmError> {
+        let value = match (self.u8()?, valtype) {
+            (0x41, ValType::I32) => self.i32()? as u32 as u64,
+            (0x42, ValType::I64) => self.i64()? as u64,
+            (0x23, _) => {
+                let global = globals
+                    .get(self.u32()? as usize)
+                    .filter(|global| global.valtype == valtype && !global.mutable)
+                    .ok_or_else(|| VmError::invalid("bad global in constant expression"))?;
+                global.value
+            }
+            _ => return Err(VmError::invalid("unsupported constant expression")),
+        };
+        if self.u8()? != 0x0b {
+            return Err(VmError::invalid("unterminated constant expression"));
+        }
+        Ok(value)
+    }
+}
+
+pub(crate) fn valtype(byte: u8) -> Result<ValType, VmError> {
+    match byte {
+        0x7f => Ok(ValType::I32),
+        0x7e => Ok(ValType::I64),
+        0x7d | 0x7c => Err(VmError::invalid("floating point types are not supported")),
+        _ => Err(VmError::invalid("unknown value type")),
+    }
+}
@@ -22,7 +22,7 @@ use {
         rpc_request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-            MAX_GET_PROGRAM_ACCOUNT_FILTERS,
+            MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_GET_RECENT_PRIORITIZATION_FEES_QUERY_ITEMS,
             MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
             NUM_LARGEST_ACCOUNTS,
         },
@@ -41,6 +41,7 @@ use {
     mundis_runtime::{
         accounts::AccountAddressFilter,
         accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig},
+        account_overrides::AccountOverrides,
         bank::{Bank, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
@@ -52,6 +53,7 @@ use {
     mundis_sdk::{
         account::{AccountSharedData, ReadableAccount},
         account_utils::StateMut,
+        bundle::{has_conflicting_account_locks, VersionedBundle, MAX_BUNDLE_TRANSACTIONS},
         clock::{Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
         commitment_config::{CommitmentConfig, CommitmentLevel},
         epoch_info::EpochInfo,
@@ -70,24 +72,30 @@ use {
         transaction::{self, SanitizedTransaction, TransactionError, VersionedTransaction},
     },
     mundis_send_transaction_service::{
-        send_transaction_service::{SendTransactionService, TransactionInfo},
+        send_transaction_service::{
+            Config as SendTransactionServiceConfig, DurableNonceStatuses,
+            DurableNonceTransactionStatus, SendTransactionService, TransactionInfo,
+        },
         tpu_info::NullTpuInfo,
     },
-    mundis_storage_bigtable::Error as StorageError,
+    mundis_storage_bigtable::{Error as StorageError, LedgerStorageAdapter},
     mundis_streamer::socket::SocketAddrSpace,
     mundis_transaction_status::{
+        token_balances::collect_token_balances_for_transaction, ConfirmedBlock,
         ConfirmedBlockWithOptionalMetadata, ConfirmedTransactionStatusWithSignature,
-        ConfirmedTransactionWithOptionalMetadata, EncodedConfirmedTransaction, Reward, RewardType,
-        TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
+        ConfirmedTransactionWithOptionalMetadata, EncodedConfirmedTransaction, InnerInstructions,
+        Reward, RewardType, TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock,
+        UiInnerInstructions, UiTransactionEncoding,
     },
     mundis_vote_program::vote_state::{VoteState, MAX_LOCKOUT_HISTORY},
     mundis_token_program::state::{TokenAccount as TokenAccount, Mint},
     std::{
         any::type_name,
         cmp::{max, min},
-        collections::{HashMap, HashSet},
+        collections::{HashMap, HashSet, VecDeque},
         convert::TryFrom,
         net::SocketAddr,
+        path::PathBuf,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -103,6 +111,9 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 
 pub const MAX_REQUEST_PAYLOAD_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+pub const MAX_SIMULATE_BUNDLE_TRANSACTIONS: usize = 16;
+// Number of submitted bundles whose transactions are remembered for `getBundleStatuses`
+pub const MAX_SUBMITTED_BUNDLES: usize = 100_000;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
@@ -146,6 +157,7 @@ pub struct JsonRpcConfig {
     pub rpc_threads: usize,
     pub rpc_niceness_adj: i8,
     pub rpc_bigtable_timeout: Option<Duration>,
+    pub local_ledger_storage_path: Option<PathBuf>,
     pub full_api: bool,
     pub rpc_scan_and_fix_roots: bool,
 }
@@ -172,15 +184,41 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Arc<Mutex<Sender<TransactionInfo>>>,
-    bigtable_ledger_storage: Option<mundis_storage_bigtable::LedgerStorage>,
+    bigtable_ledger_storage: Option<Arc<dyn LedgerStorageAdapter>>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
     max_slots: Arc<MaxSlots>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
+    submitted_bundles: Arc<RwLock<SubmittedBundles>>,
+    durable_nonce_statuses: Arc<RwLock<DurableNonceStatuses>>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
+/// The transaction signatures of the most recently submitted bundles, by bundle id
+#[derive(Default)]
+struct SubmittedBundles {
+    signatures: HashMap<Hash, Vec<Signature>>,
+    bundle_ids: VecDeque<Hash>,
+}
+
+impl SubmittedBundles {
+    fn insert(&mut self, bundle_id: Hash, signatures: Vec<Signature>) {
+        if self.signatures.insert(bundle_id, signatures).is_none() {
+            self.bundle_ids.push_back(bundle_id);
+        }
+        while self.bundle_ids.len() > MAX_SUBMITTED_BUNDLES {
+            if let Some(bundle_id) = self.bundle_ids.pop_front() {
+                self.signatures.remove(&bundle_id);
+            }
+        }
+    }
+
+    fn get(&self, bundle_id: &Hash) -> Option<&Vec<Signature>> {
+        self.signatures.get(bundle_id)
+    }
+}
+
 impl JsonRpcRequestProcessor {
     #[allow(deprecated)]
     fn bank(&self, commitment: Option<CommitmentConfig>) -> Arc<Bank> {
@@ -236,6 +274,27 @@ impl JsonRpcRequestProcessor {
         })
     }
 
+    /// Returns the bank to simulate against: the bank at `config.slot` if given, which must be
+    /// frozen, otherwise the bank for `config.commitment`
+    fn simulation_bank(&self, config: &RpcSimulateTransactionConfig) -> Result<Arc<Bank>> {
+        match config.slot {
+            Some(slot) => self
+                .bank_forks
+                .read()
+                .unwrap()
+                .get(slot)
+                .filter(|bank| bank.is_frozen())
+                .ok_or_else(|| RpcCustomError::BlockNotAvailable { slot }.into()),
+            None => Ok(self.bank(config.commitment)),
+        }
+    }
+
+    /// Outcomes of the durable-nonce transactions sent through this node, to be recorded by
+    /// its `SendTransactionService`
+    pub fn durable_nonce_statuses(&self) -> Arc<RwLock<DurableNonceStatuses>> {
+        self.durable_nonce_statuses.clone()
+    }
+
     fn genesis_creation_time(&self) -> UnixTimestamp {
         self.bank(None).genesis_creation_time()
     }
@@ -251,7 +310,7 @@ impl JsonRpcRequestProcessor {
         health: Arc<RpcHealth>,
         cluster_info: Arc<ClusterInfo>,
         genesis_hash: Hash,
-        bigtable_ledger_storage: Option<mundis_storage_bigtable::LedgerStorage>,
+        bigtable_ledger_storage: Option<Arc<dyn LedgerStorageAdapter>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
         max_slots: Arc<MaxSlots>,
@@ -277,6 +336,8 @@ impl JsonRpcRequestProcessor {
                 max_slots,
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
+                submitted_bundles: Arc::default(),
+                durable_nonce_statuses: Arc::default(),
             },
             receiver,
         )
@@ -298,13 +359,18 @@ impl JsonRpcRequestProcessor {
         ));
         let tpu_address = cluster_info.my_contact_info().tpu;
         let (sender, receiver) = channel();
-        SendTransactionService::new::<NullTpuInfo>(
+        let durable_nonce_statuses = Arc::default();
+        SendTransactionService::new_with_durable_nonce_statuses::<NullTpuInfo>(
             tpu_address,
             &bank_forks,
             None,
             receiver,
-            1000,
-            1,
+            SendTransactionServiceConfig {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..SendTransactionServiceConfig::default()
+            },
+            Arc::clone(&durable_nonce_statuses),
         );
 
         Self {
@@ -330,6 +396,8 @@ impl JsonRpcRequestProcessor {
             max_slots: Arc::new(MaxSlots::default()),
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
+            submitted_bundles: Arc::default(),
+            durable_nonce_statuses,
         }
     }
 
@@ -391,6 +459,10 @@ impl JsonRpcRequestProcessor {
                 self.get_filtered_token_accounts_by_owner(&bank, program_id, &owner, filters)?
             } else if let Some(mint) = get_token_mint_filter(program_id, &filters) {
                 self.get_filtered_token_accounts_by_mint(&bank, program_id, &mint, filters)?
+            } else if let Some(index_key) =
+                get_custom_index_filter(&self.config.account_indexes, program_id, &filters)
+            {
+                self.get_filtered_custom_indexed_accounts(&bank, program_id, &index_key, filters)?
             } else {
                 self.get_filtered_program_accounts(&bank, program_id, filters)?
             }
@@ -972,6 +1044,7 @@ impl JsonRpcRequestProcessor {
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
             let transaction_details = config.transaction_details.unwrap_or_default();
             let show_rewards = config.rewards.unwrap_or(true);
+            let max_supported_transaction_version = config.max_supported_transaction_version;
             let commitment = config.commitment.unwrap_or_default();
             check_is_at_least_confirmed(commitment)?;
 
@@ -987,34 +1060,43 @@ impl JsonRpcRequestProcessor {
                 let result = self.blockstore.get_rooted_block(slot, true);
                 self.check_blockstore_root(&result, slot)?;
                 let configure_block = |confirmed_block: ConfirmedBlockWithOptionalMetadata| {
-                    let mut confirmed_block =
-                        confirmed_block.configure(encoding, transaction_details, show_rewards);
-                    if slot == 0 {
-                        confirmed_block.block_time = Some(self.genesis_creation_time());
-                        confirmed_block.block_height = Some(0);
-                    }
                     confirmed_block
+                        .configure(
+                            encoding,
+                            transaction_details,
+                            show_rewards,
+                            max_supported_transaction_version,
+                        )
+                        .map(|mut confirmed_block| {
+                            if slot == 0 {
+                                confirmed_block.block_time = Some(self.genesis_creation_time());
+                                confirmed_block.block_height = Some(0);
+                            }
+                            confirmed_block
+                        })
+                        .map_err(|err| Error::from(RpcCustomError::from(err)))
                 };
                 if result.is_err() {
                     if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
                         let bigtable_result =
                             bigtable_ledger_storage.get_confirmed_block(slot).await;
                         self.check_bigtable_result(&bigtable_result)?;
-                        return Ok(bigtable_result.ok().map(configure_block));
+                        return bigtable_result.ok().map(configure_block).transpose();
                     }
                 }
                 self.check_slot_cleaned_up(&result, slot)?;
-                return Ok(result
+                return result
                     .ok()
                     .map(ConfirmedBlockWithOptionalMetadata::from)
-                    .map(configure_block));
+                    .map(configure_block)
+                    .transpose();
             } else if commitment.is_confirmed() {
                 // Check if block is confirmed
                 let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
                 if confirmed_bank.status_cache_ancestors().contains(&slot) {
                     self.check_status_is_complete(slot)?;
                     let result = self.blockstore.get_complete_block(slot, true);
-                    return Ok(result.ok().map(|mut confirmed_block| {
+                    let configure_block = |mut confirmed_block: ConfirmedBlock| {
                         if confirmed_block.block_time.is_none()
                             || confirmed_block.block_height.is_none()
                         {
@@ -1029,12 +1111,16 @@ impl JsonRpcRequestProcessor {
                                 }
                             }
                         }
-                        ConfirmedBlockWithOptionalMetadata::from(confirmed_block).configure(
-                            encoding,
-                            transaction_details,
-                            show_rewards,
-                        )
-                    }));
+                        ConfirmedBlockWithOptionalMetadata::from(confirmed_block)
+                            .configure(
+                                encoding,
+                                transaction_details,
+                                show_rewards,
+                                max_supported_transaction_version,
+                            )
+                            .map_err(|err| Error::from(RpcCustomError::from(err)))
+                    };
+                    return result.ok().map(configure_block).transpose();
                 }
             }
         } else {
@@ -1308,6 +1394,90 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, statuses))
     }
 
+    pub fn get_bundle_statuses(
+        &self,
+        bundle_ids: Vec<Hash>,
+    ) -> Result<RpcResponse<Vec<Option<RpcBundleStatus>>>> {
+        let bank = self.bank(Some(CommitmentConfig::processed()));
+        let submitted_bundles = self.submitted_bundles.read().unwrap();
+        let statuses = bundle_ids
+            .into_iter()
+            .map(|bundle_id| {
+                let signatures = submitted_bundles.get(&bundle_id)?;
+                // A bundle lands all at once, so it is found only once every transaction is
+                let statuses = signatures
+                    .iter()
+                    .map(|signature| self.get_transaction_status(*signature, &bank))
+                    .collect::<Option<Vec<_>>>()?;
+                let status = statuses.first()?;
+                Some(RpcBundleStatus {
+                    bundle_id: bundle_id.to_string(),
+                    slot: status.slot,
+                    transactions: signatures
+                        .iter()
+                        .map(|signature| signature.to_string())
+                        .collect(),
+                    confirmation_status: status.confirmation_status.clone(),
+                })
+            })
+            .collect();
+        Ok(new_response(&bank, statuses))
+    }
+
+    pub fn get_durable_nonce_transaction_statuses(
+        &self,
+        signatures: Vec<Signature>,
+    ) -> Result<RpcResponse<Vec<Option<RpcDurableNonceTransactionStatus>>>> {
+        let bank = self.bank(Some(CommitmentConfig::processed()));
+        let durable_nonce_statuses = self.durable_nonce_statuses.read().unwrap();
+        let statuses = signatures
+            .iter()
+            .map(|signature| {
+                durable_nonce_statuses
+                    .get(signature)
+                    .map(|status| match status {
+                        DurableNonceTransactionStatus::Pending => {
+                            RpcDurableNonceTransactionStatus::Pending
+                        }
+                        DurableNonceTransactionStatus::Landed(slot) => {
+                            RpcDurableNonceTransactionStatus::Landed { slot }
+                        }
+                        DurableNonceTransactionStatus::NonceConsumed => {
+                            RpcDurableNonceTransactionStatus::NonceConsumed
+                        }
+                        DurableNonceTransactionStatus::MaxRetriesElapsed => {
+                            RpcDurableNonceTransactionStatus::MaxRetriesElapsed
+                        }
+                    })
+            })
+            .collect();
+        Ok(new_response(&bank, statuses))
+    }
+
+    /// Local minimum priority fees of the given accounts for the processed bank and its
+    /// unrooted ancestors, most recent slot first
+    pub fn get_recent_prioritization_fees(
+        &self,
+        pubkeys: Vec<Pubkey>,
+    ) -> Result<Vec<RpcPrioritizationFee>> {
+        let bank = self.bank(Some(CommitmentConfig::processed()));
+        let parents = bank.parents();
+        Ok(std::iter::once(&bank)
+            .chain(parents.iter())
+            .map(|bank| {
+                let cost_tracker = bank.read_cost_tracker().unwrap();
+                RpcPrioritizationFee {
+                    slot: bank.slot(),
+                    prioritization_fee: pubkeys
+                        .iter()
+                        .map(|pubkey| cost_tracker.local_minimum_priority_fee(pubkey))
+                        .max()
+                        .unwrap_or_default(),
+                }
+            })
+            .collect())
+    }
+
     fn get_transaction_status(
         &self,
         signature: Signature,
@@ -1354,6 +1524,7 @@ impl JsonRpcRequestProcessor {
             .map(|config| config.convert_to_current())
             .unwrap_or_default();
         let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
+        let max_supported_transaction_version = config.max_supported_transaction_version;
         let commitment = config.commitment.unwrap_or_default();
         check_is_at_least_confirmed(commitment)?;
 
@@ -1381,7 +1552,8 @@ impl JsonRpcRequestProcessor {
                         }
                         return Ok(Some(
                             ConfirmedTransactionWithOptionalMetadata::from(confirmed_transaction)
-                                .encode(encoding),
+                                .encode(encoding, max_supported_transaction_version)
+                                .map_err(RpcCustomError::from)?,
                         ));
                     }
                     if confirmed_transaction.slot
@@ -1393,17 +1565,23 @@ impl JsonRpcRequestProcessor {
                     {
                         return Ok(Some(
                             ConfirmedTransactionWithOptionalMetadata::from(confirmed_transaction)
-                                .encode(encoding),
+                                .encode(encoding, max_supported_transaction_version)
+                                .map_err(RpcCustomError::from)?,
                         ));
                     }
                 }
                 None => {
                     if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
-                        return Ok(bigtable_ledger_storage
+                        return bigtable_ledger_storage
                             .get_confirmed_transaction(&signature)
                             .await
                             .unwrap_or(None)
-                            .map(|confirmed| confirmed.encode(encoding)));
+                            .map(|confirmed| {
+                                confirmed
+                                    .encode(encoding, max_supported_transaction_version)
+                                    .map_err(|err| Error::from(RpcCustomError::from(err)))
+                            })
+                            .transpose();
                     }
                 }
             }
@@ -2015,6 +2193,34 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// Get an iterator of program accounts from a custom account index
+    fn get_filtered_custom_indexed_accounts(
+        &self,
+        bank: &Arc<Bank>,
+        program_id: &Pubkey,
+        index_key: &IndexKey,
+        filters: Vec<RpcFilterType>,
+    ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
+        Ok(bank
+            .get_filtered_indexed_accounts(
+                index_key,
+                |account| {
+                    // As with the program-id index, stale entries are filtered out by checking
+                    // the owner and the filters again
+                    account.owner() == program_id
+                        && filters.iter().all(|filter_type| match filter_type {
+                            RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
+                            RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
+                        })
+                },
+                &ScanConfig::default(),
+                bank.byte_limit_for_scans(),
+            )
+            .map_err(|e| RpcCustomError::ScanError {
+                message: e.to_string(),
+            })?)
+    }
+
     fn get_latest_blockhash(
         &self,
         commitment: Option<CommitmentConfig>,
@@ -2228,6 +2434,32 @@ fn encode_account<T: ReadableAccount>(
     }
 }
 
+/// Find a custom account index of `program_id` that a memcmp filter can be answered from, and
+/// return the index key to look up.
+/// NOTE: `optimize_filters()` should almost always be called before using this method because of
+/// the strict match on `MemcmpEncodedBytes::Bytes`.
+fn get_custom_index_filter(
+    account_indexes: &AccountSecondaryIndexes,
+    program_id: &Pubkey,
+    filters: &[RpcFilterType],
+) -> Option<IndexKey> {
+    account_indexes
+        .custom_indexes()
+        .filter(|custom_index| custom_index.program_id == *program_id)
+        .find_map(|custom_index| {
+            filters.iter().find_map(|filter| match filter {
+                RpcFilterType::Memcmp(Memcmp {
+                    offset,
+                    bytes: MemcmpEncodedBytes::Bytes(bytes),
+                    ..
+                }) if *offset == custom_index.offset => custom_index
+                    .key(bytes)
+                    .map(|key| IndexKey::Custom(*custom_index, key)),
+                _ => None,
+            })
+        })
+}
+
 /// Analyze custom filters to determine if the result will be a subset of mundis-token-program accounts by
 /// owner.
 /// NOTE: `optimize_filters()` should almost always be called before using this method because of
@@ -3146,6 +3378,13 @@ pub mod rpc_full {
             limit: Option<usize>,
         ) -> Result<Vec<RpcPerfSample>>;
 
+        #[rpc(meta, name = "getRecentPrioritizationFees")]
+        fn get_recent_prioritization_fees(
+            &self,
+            meta: Self::Metadata,
+            pubkey_strs: Option<Vec<String>>,
+        ) -> Result<Vec<RpcPrioritizationFee>>;
+
         #[rpc(meta, name = "getSignatureStatuses")]
         fn get_signature_statuses(
             &self,
@@ -3177,6 +3416,28 @@ pub mod rpc_full {
             config: Option<RpcSendTransactionConfig>,
         ) -> Result<String>;
 
+        #[rpc(meta, name = "sendBundle")]
+        fn send_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSendTransactionConfig>,
+        ) -> Result<String>;
+
+        #[rpc(meta, name = "getBundleStatuses")]
+        fn get_bundle_statuses(
+            &self,
+            meta: Self::Metadata,
+            bundle_id_strs: Vec<String>,
+        ) -> Result<RpcResponse<Vec<Option<RpcBundleStatus>>>>;
+
+        #[rpc(meta, name = "getDurableNonceTransactionStatuses")]
+        fn get_durable_nonce_transaction_statuses(
+            &self,
+            meta: Self::Metadata,
+            signature_strs: Vec<String>,
+        ) -> Result<RpcResponse<Vec<Option<RpcDurableNonceTransactionStatus>>>>;
+
         #[rpc(meta, name = "simulateTransaction")]
         fn simulate_transaction(
             &self,
@@ -3185,6 +3446,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "simulateBundle")]
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3301,6 +3570,29 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_recent_prioritization_fees(
+            &self,
+            meta: Self::Metadata,
+            pubkey_strs: Option<Vec<String>>,
+        ) -> Result<Vec<RpcPrioritizationFee>> {
+            let pubkey_strs = pubkey_strs.unwrap_or_default();
+            debug!(
+                "get_recent_prioritization_fees rpc request received: {:?} pubkeys",
+                pubkey_strs.len()
+            );
+            if pubkey_strs.len() > MAX_GET_RECENT_PRIORITIZATION_FEES_QUERY_ITEMS {
+                return Err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {}",
+                    MAX_GET_RECENT_PRIORITIZATION_FEES_QUERY_ITEMS
+                )));
+            }
+            let pubkeys = pubkey_strs
+                .into_iter()
+                .map(|pubkey_str| verify_pubkey(&pubkey_str))
+                .collect::<Result<Vec<_>>>()?;
+            meta.get_recent_prioritization_fees(pubkeys)
+        }
+
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
             debug!("get_cluster_nodes rpc request received");
             let cluster_info = &meta.cluster_info;
@@ -3472,9 +3764,8 @@ pub mod rpc_full {
                 .map(|&pubkey| (pubkey, *transaction.message().recent_blockhash()));
             if durable_nonce_info.is_some() {
                 // While it uses a defined constant, this last_valid_block_height value is chosen arbitrarily.
-                // It provides a fallback timeout for durable-nonce transaction retries in case of
-                // malicious packing of the retry queue. Durable-nonce transactions are otherwise
-                // retried until the nonce is advanced.
+                // The send-transaction service doesn't expire durable-nonce transactions by block
+                // height, it retries them until the nonce is advanced or max_retries elapses.
                 last_valid_block_height =
                     preflight_bank.block_height() + MAX_RECENT_BLOCKHASHES as u64;
             }
@@ -3507,7 +3798,11 @@ pub mod rpc_full {
                     logs,
                     post_simulation_accounts: _,
                     units_consumed,
-                } = preflight_bank.simulate_transaction(transaction)
+                    return_data,
+                    loaded_accounts_data_size,
+                    loaded_accounts_data_size_limit,
+                    ..
+                } = preflight_bank.simulate_transaction(transaction, false)
                 {
                     match err {
                         TransactionError::BlockhashNotFound => {
@@ -3524,6 +3819,12 @@ pub mod rpc_full {
                             logs: Some(logs),
                             accounts: None,
                             units_consumed: Some(units_consumed),
+                            return_data: return_data.map(|return_data| return_data.into()),
+                            inner_instructions: None,
+                            pre_token_balances: None,
+                            post_token_balances: None,
+                            loaded_accounts_data_size: Some(loaded_accounts_data_size),
+                            loaded_accounts_data_size_limit: Some(loaded_accounts_data_size_limit),
                         },
                     }
                     .into());
@@ -3540,97 +3841,276 @@ pub mod rpc_full {
             )
         }
 
-        fn simulate_transaction(
+        fn send_bundle(
             &self,
             meta: Self::Metadata,
-            data: String,
-            config: Option<RpcSimulateTransactionConfig>,
-        ) -> Result<RpcResponse<RpcSimulateTransactionResult>> {
-            debug!("simulate_transaction rpc request received");
+            data: Vec<String>,
+            config: Option<RpcSendTransactionConfig>,
+        ) -> Result<String> {
+            debug!(
+                "send_bundle rpc request received: {} transactions",
+                data.len()
+            );
+            if data.is_empty() || data.len() > MAX_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Invalid bundle length; must contain between 1 and {} transactions",
+                    MAX_BUNDLE_TRANSACTIONS
+                )));
+            }
             let config = config.unwrap_or_default();
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let (_, mut unsanitized_tx) =
-                decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
+            let unsanitized_txs = data
+                .into_iter()
+                .map(|data| {
+                    decode_and_deserialize::<VersionedTransaction>(data, encoding)
+                        .map(|(_, unsanitized_tx)| unsanitized_tx)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let bundle = VersionedBundle::new(unsanitized_txs);
+            bundle
+                .sanitize()
+                .map_err(|err| Error::invalid_params(format!("invalid bundle: {}", err)))?;
+            let wire_bundle = bundle
+                .to_packets_data()
+                .map_err(|_| Error::invalid_params("bundle transaction too large for a packet"))?;
+            let bundle_id = bundle.id();
 
-            let bank = &*meta.bank(config.commitment);
-            if config.replace_recent_blockhash {
-                if config.sig_verify {
-                    return Err(Error::invalid_params(
-                        "sigVerify may not be used with replaceRecentBlockhash",
-                    ));
-                }
-                unsanitized_tx
-                    .message
-                    .set_recent_blockhash(bank.last_blockhash());
+            let preflight_commitment = config
+                .preflight_commitment
+                .map(|commitment| CommitmentConfig { commitment });
+            let preflight_bank = &*meta.bank(preflight_commitment);
+            let transactions = bundle
+                .transactions
+                .into_iter()
+                .map(sanitize_transaction)
+                .collect::<Result<Vec<_>>>()?;
+            // A bundle is recorded as a single entry, whose accounts replay locks all at once
+            // unless it executes the entry in order
+            if !preflight_bank
+                .feature_set
+                .is_active(&feature_set::execute_conflicting_entries_in_order::id())
+                && has_conflicting_account_locks(&transactions)
+            {
+                return Err(Error::invalid_params(
+                    "invalid bundle: transactions lock the same accounts",
+                ));
             }
+            let signatures: Vec<Signature> = transactions
+                .iter()
+                .map(|transaction| *transaction.signature())
+                .collect();
 
-            let transaction = sanitize_transaction(unsanitized_tx)?;
-            if config.sig_verify {
-                verify_transaction(&transaction, &bank.feature_set)?;
-            }
-            let number_of_accounts = transaction.message().account_keys_len();
-
-            let TransactionSimulationResult {
-                result,
-                logs,
-                post_simulation_accounts,
-                units_consumed,
-            } = bank.simulate_transaction(transaction);
-
-            let accounts = if let Some(config_accounts) = config.accounts {
-                let accounts_encoding = config_accounts
-                    .encoding
-                    .unwrap_or(UiAccountEncoding::Base64);
-
-                if accounts_encoding == UiAccountEncoding::Binary
-                    || accounts_encoding == UiAccountEncoding::Base58
-                {
-                    return Err(Error::invalid_params("base58 encoding not supported"));
+            // The bundle is retried until its first transaction to expire does
+            let last_valid_block_height = transactions
+                .iter()
+                .map(|transaction| {
+                    preflight_bank
+                        .get_blockhash_last_valid_block_height(
+                            transaction.message().recent_blockhash(),
+                        )
+                        .unwrap_or(0)
+                })
+                .min()
+                .unwrap_or(0);
+
+            if !config.skip_preflight {
+                for transaction in &transactions {
+                    verify_transaction(transaction, &preflight_bank.feature_set)?;
                 }
 
-                if config_accounts.addresses.len() > number_of_accounts {
-                    return Err(Error::invalid_params(format!(
-                        "Too many accounts provided; max {}",
-                        number_of_accounts
-                    )));
+                match meta.health.check() {
+                    RpcHealthStatus::Ok => (),
+                    RpcHealthStatus::Unknown => {
+                        inc_new_counter_info!("rpc-send-bundle_health-unknown", 1);
+                        return Err(RpcCustomError::NodeUnhealthy {
+                            num_slots_behind: None,
+                        }
+                        .into());
+                    }
+                    RpcHealthStatus::Behind { num_slots } => {
+                        inc_new_counter_info!("rpc-send-bundle_health-behind", 1);
+                        return Err(RpcCustomError::NodeUnhealthy {
+                            num_slots_behind: Some(num_slots),
+                        }
+                        .into());
+                    }
                 }
 
-                if result.is_err() {
-                    Some(vec![None; config_accounts.addresses.len()])
-                } else {
-                    let mut accounts = vec![];
-                    for address_str in config_accounts.addresses {
-                        let address = verify_pubkey(&address_str)?;
-                        accounts.push(
-                            post_simulation_accounts
-                                .iter()
-                                .find(|(key, _account)| key == &address)
-                                .map(|(pubkey, account)| {
-                                    UiAccount::encode(
-                                        pubkey,
-                                        account,
-                                        accounts_encoding,
-                                        None,
-                                        None,
-                                    )
-                                }),
-                        );
+                let simulation_results = preflight_bank.simulate_transactions(transactions, false);
+                if let Some(TransactionSimulationResult {
+                    result: Err(err),
+                    logs,
+                    units_consumed,
+                    return_data,
+                    loaded_accounts_data_size,
+                    loaded_accounts_data_size_limit,
+                    ..
+                }) = simulation_results
+                    .into_iter()
+                    .find(|simulation_result| simulation_result.result.is_err())
+                {
+                    inc_new_counter_info!("rpc-send-bundle_err", 1);
+                    return Err(RpcCustomError::SendTransactionPreflightFailure {
+                        message: format!("Bundle simulation failed: {}", err),
+                        result: RpcSimulateTransactionResult {
+                            err: Some(err),
+                            logs: Some(logs),
+                            accounts: None,
+                            units_consumed: Some(units_consumed),
+                            return_data: return_data.map(|return_data| return_data.into()),
+                            inner_instructions: None,
+                            pre_token_balances: None,
+                            post_token_balances: None,
+                            loaded_accounts_data_size: Some(loaded_accounts_data_size),
+                            loaded_accounts_data_size_limit: Some(loaded_accounts_data_size_limit),
+                        },
                     }
-                    Some(accounts)
+                    .into());
                 }
-            } else {
-                None
-            };
+            }
 
-            Ok(new_response(
+            meta.submitted_bundles
+                .write()
+                .unwrap()
+                .insert(bundle_id, signatures.clone());
+            // Each transaction of the bundle is sent in a packet of its own
+            for (signature, wire_transaction) in signatures.into_iter().zip(wire_bundle) {
+                _send_transaction(
+                    meta.clone(),
+                    signature,
+                    wire_transaction,
+                    last_valid_block_height,
+                    None,
+                    config.max_retries,
+                )?;
+            }
+            Ok(bundle_id.to_string())
+        }
+
+        fn get_bundle_statuses(
+            &self,
+            meta: Self::Metadata,
+            bundle_id_strs: Vec<String>,
+        ) -> Result<RpcResponse<Vec<Option<RpcBundleStatus>>>> {
+            debug!(
+                "get_bundle_statuses rpc request received: {:?}",
+                bundle_id_strs.len()
+            );
+            if bundle_id_strs.len() > MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS {
+                return Err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {}",
+                    MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS
+                )));
+            }
+            let bundle_ids = bundle_id_strs
+                .iter()
+                .map(|bundle_id_str| verify_hash(bundle_id_str))
+                .collect::<Result<Vec<_>>>()?;
+            meta.get_bundle_statuses(bundle_ids)
+        }
+
+        fn get_durable_nonce_transaction_statuses(
+            &self,
+            meta: Self::Metadata,
+            signature_strs: Vec<String>,
+        ) -> Result<RpcResponse<Vec<Option<RpcDurableNonceTransactionStatus>>>> {
+            debug!(
+                "get_durable_nonce_transaction_statuses rpc request received: {:?}",
+                signature_strs.len()
+            );
+            if signature_strs.len() > MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS {
+                return Err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {}",
+                    MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS
+                )));
+            }
+            let signatures = signature_strs
+                .iter()
+                .map(|signature_str| verify_signature(signature_str))
+                .collect::<Result<Vec<_>>>()?;
+            meta.get_durable_nonce_transaction_statuses(signatures)
+        }
+
+        fn simulate_transaction(
+            &self,
+            meta: Self::Metadata,
+            data: String,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<RpcSimulateTransactionResult>> {
+            debug!("simulate_transaction rpc request received");
+            let config = config.unwrap_or_default();
+            let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let (_, unsanitized_tx) =
+                decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
+
+            let bank = &*meta.simulation_bank(&config)?;
+            let transaction = sanitize_simulated_transaction(unsanitized_tx, bank, &config)?;
+            let simulation_result =
+                bank.simulate_transaction(transaction.clone(), config.inner_instructions);
+            let result = encode_simulation_result(
                 bank,
-                RpcSimulateTransactionResult {
-                    err: result.err(),
-                    logs: Some(logs),
-                    accounts,
-                    units_consumed: Some(units_consumed),
-                },
-            ))
+                &transaction,
+                &simulation_result,
+                &AccountOverrides::default(),
+                &config,
+            )?;
+            Ok(new_response(bank, result))
+        }
+
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>> {
+            debug!(
+                "simulate_bundle rpc request received: {} transactions",
+                data.len()
+            );
+            if data.is_empty() || data.len() > MAX_SIMULATE_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Invalid bundle length; must contain between 1 and {} transactions",
+                    MAX_SIMULATE_BUNDLE_TRANSACTIONS
+                )));
+            }
+            let config = config.unwrap_or_default();
+            let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let bank = &*meta.simulation_bank(&config)?;
+            if !bank.is_frozen() {
+                return Err(Error::invalid_params(format!(
+                    "Bank at slot {} is not frozen",
+                    bank.slot()
+                )));
+            }
+
+            let transactions = data
+                .into_iter()
+                .map(|data| {
+                    let (_, unsanitized_tx) =
+                        decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
+                    sanitize_simulated_transaction(unsanitized_tx, bank, &config)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            // Each transaction observes the writable accounts of the preceding successful ones
+            let mut account_overrides = AccountOverrides::default();
+            let mut results = Vec::with_capacity(transactions.len());
+            for transaction in transactions {
+                let simulation_result = bank.simulate_transaction_with_overrides(
+                    transaction.clone(),
+                    config.inner_instructions,
+                    &account_overrides,
+                );
+                results.push(encode_simulation_result(
+                    bank,
+                    &transaction,
+                    &simulation_result,
+                    &account_overrides,
+                    &config,
+                )?);
+                account_overrides.apply_simulation_result(&transaction, &simulation_result);
+            }
+            Ok(new_response(bank, results))
         }
 
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot> {
@@ -4124,6 +4604,161 @@ fn sanitize_transaction(transaction: VersionedTransaction) -> Result<SanitizedTr
     .map_err(|err| Error::invalid_params(format!("invalid transaction: {}", err)))
 }
 
+/// Applies the `replaceRecentBlockhash` and `sigVerify` options of a simulation request
+fn sanitize_simulated_transaction(
+    mut unsanitized_tx: VersionedTransaction,
+    bank: &Bank,
+    config: &RpcSimulateTransactionConfig,
+) -> Result<SanitizedTransaction> {
+    if config.replace_recent_blockhash {
+        if config.sig_verify {
+            return Err(Error::invalid_params(
+                "sigVerify may not be used with replaceRecentBlockhash",
+            ));
+        }
+        unsanitized_tx
+            .message
+            .set_recent_blockhash(bank.last_blockhash());
+    }
+
+    let transaction = sanitize_transaction(unsanitized_tx)?;
+    if config.sig_verify {
+        verify_transaction(&transaction, &bank.feature_set)?;
+    }
+    Ok(transaction)
+}
+
+/// Builds the rpc response for a simulated transaction. Accounts are read from `account_overrides`
+/// before falling back to `bank`, so that simulations within a bundle observe the effects of the
+/// preceding transactions.
+fn encode_simulation_result(
+    bank: &Bank,
+    transaction: &SanitizedTransaction,
+    simulation_result: &TransactionSimulationResult,
+    account_overrides: &AccountOverrides,
+    config: &RpcSimulateTransactionConfig,
+) -> Result<RpcSimulateTransactionResult> {
+    let TransactionSimulationResult {
+        result,
+        logs,
+        post_simulation_accounts,
+        units_consumed,
+        return_data,
+        inner_instructions,
+        loaded_accounts_data_size,
+        loaded_accounts_data_size_limit,
+    } = simulation_result;
+    let number_of_accounts = transaction.message().account_keys_len();
+
+    let accounts = if let Some(config_accounts) = &config.accounts {
+        let accounts_encoding = config_accounts
+            .encoding
+            .unwrap_or(UiAccountEncoding::Base64);
+
+        if accounts_encoding == UiAccountEncoding::Binary
+            || accounts_encoding == UiAccountEncoding::Base58
+        {
+            return Err(Error::invalid_params("base58 encoding not supported"));
+        }
+
+        if config_accounts.addresses.len() > number_of_accounts {
+            return Err(Error::invalid_params(format!(
+                "Too many accounts provided; max {}",
+                number_of_accounts
+            )));
+        }
+
+        if result.is_err() {
+            Some(vec![None; config_accounts.addresses.len()])
+        } else {
+            let mut accounts = vec![];
+            for address_str in &config_accounts.addresses {
+                let address = verify_pubkey(address_str)?;
+                accounts.push(
+                    post_simulation_accounts
+                        .iter()
+                        .find(|(key, _account)| key == &address)
+                        .map(|(pubkey, account)| {
+                            UiAccount::encode(pubkey, account, accounts_encoding, None, None)
+                        }),
+                );
+            }
+            Some(accounts)
+        }
+    } else {
+        None
+    };
+
+    let inner_instructions = inner_instructions.as_ref().map(|inner_instructions| {
+        let account_keys: Vec<Pubkey> =
+            transaction.message().account_keys_iter().copied().collect();
+        inner_instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instructions)| !instructions.is_empty())
+            .map(|(index, instructions)| {
+                UiInnerInstructions::parse(
+                    InnerInstructions {
+                        index: index as u8,
+                        instructions: instructions.clone(),
+                    },
+                    &account_keys,
+                )
+            })
+            .collect()
+    });
+
+    let (pre_token_balances, post_token_balances) = if config.token_balances {
+        let get_pre_account = |pubkey: &Pubkey| {
+            account_overrides
+                .get(pubkey)
+                .cloned()
+                .or_else(|| bank.get_account(pubkey))
+        };
+        let get_post_account = |pubkey: &Pubkey| {
+            if result.is_ok() {
+                if let Some((_, account)) = post_simulation_accounts
+                    .iter()
+                    .find(|(key, _account)| key == pubkey)
+                {
+                    return Some(account.clone());
+                }
+            }
+            get_pre_account(pubkey)
+        };
+        let mut mint_decimals = HashMap::new();
+        let pre_token_balances = collect_token_balances_for_transaction(
+            transaction,
+            &get_pre_account,
+            &mut mint_decimals,
+        );
+        let post_token_balances = collect_token_balances_for_transaction(
+            transaction,
+            get_post_account,
+            &mut mint_decimals,
+        );
+        (
+            Some(pre_token_balances.into_iter().map(Into::into).collect()),
+            Some(post_token_balances.into_iter().map(Into::into).collect()),
+        )
+    } else {
+        (None, None)
+    };
+
+    Ok(RpcSimulateTransactionResult {
+        err: result.clone().err(),
+        logs: Some(logs.clone()),
+        accounts,
+        units_consumed: Some(*units_consumed),
+        return_data: return_data.clone().map(|return_data| return_data.into()),
+        inner_instructions,
+        pre_token_balances,
+        post_token_balances,
+        loaded_accounts_data_size: Some(*loaded_accounts_data_size),
+        loaded_accounts_data_size_limit: Some(*loaded_accounts_data_size_limit),
+    })
+}
+
 pub(crate) fn create_validator_exit(exit: &Arc<AtomicBool>) -> Arc<RwLock<Exit>> {
     let mut validator_exit = Exit::default();
     let exit_ = exit.clone();
@@ -4228,7 +4863,10 @@ pub mod tests {
         bincode::deserialize,
         jsonrpc_core::{futures, ErrorCode, MetaIoHandler, Output, Response, Value},
         jsonrpc_core_client::transports::local,
-        mundis_client::rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        mundis_client::{
+            nonce_utils,
+            rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        },
         mundis_gossip::{contact_info::ContactInfo, socketaddr},
         mundis_ledger::{
             blockstore_meta::PerfSample,
@@ -4237,7 +4875,8 @@ pub mod tests {
         },
         mundis_runtime::{
             accounts_background_service::AbsRequestSender, commitment::BlockCommitment,
-            non_circulating_supply::non_circulating_accounts,
+            cost_model::TransactionCost, cost_tracker::MAX_LOCAL_MINIMUM_PRIORITY_FEE,
+            non_circulating_supply::non_circulating_accounts, secondary_index::CustomAccountIndex,
         },
         mundis_sdk::{
             account::Account,
@@ -4261,7 +4900,7 @@ pub mod tests {
             vote_state::{BlockTimestamp, Vote, VoteInit, VoteStateVersions, MAX_LOCKOUT_HISTORY},
         },
         mundis_token_program::{
-            state::{AccountState as TokenAccountState, Mint},
+            state::{AccountState as TokenAccountState, MemoTransfer, Mint},
         },
         std::collections::HashMap,
     };
@@ -4449,13 +5088,17 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
             max_complete_transaction_status_slot,
         );
-        SendTransactionService::new::<NullTpuInfo>(
+        SendTransactionService::new_with_durable_nonce_statuses::<NullTpuInfo>(
             tpu_address,
             &bank_forks,
             None,
             receiver,
-            1000,
-            1,
+            SendTransactionServiceConfig {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..SendTransactionServiceConfig::default()
+            },
+            meta.durable_nonce_statuses(),
         );
 
         cluster_info.insert_info(ContactInfo::new_with_pubkey_socketaddr(
@@ -4608,6 +5251,68 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_recent_prioritization_fees() {
+        let bob_pubkey = mundis_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let hot_account = mundis_sdk::pubkey::new_rand();
+        let processed_bank = meta.bank(Some(CommitmentConfig::processed()));
+        {
+            let mut cost_tracker = processed_bank.write_cost_tracker().unwrap();
+            cost_tracker.set_limits(1_000, 10_000, 10_000);
+            cost_tracker.add_executed_transaction_cost(&TransactionCost {
+                writable_accounts: vec![hot_account],
+                execution_cost: 750,
+                ..TransactionCost::default()
+            });
+        }
+        let expected_fee = MAX_LOCAL_MINIMUM_PRIORITY_FEE / 2;
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getRecentPrioritizationFees","params":[["{}","{}"]]}}"#,
+            bob_pubkey, hot_account
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let fees: Vec<RpcPrioritizationFee> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            fees[0],
+            RpcPrioritizationFee {
+                slot: processed_bank.slot(),
+                prioritization_fee: expected_fee,
+            }
+        );
+
+        // Without accounts there is nothing to pay for
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getRecentPrioritizationFees"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let fees: Vec<RpcPrioritizationFee> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert!(fees.iter().all(|fee| fee.prioritization_fee == 0));
+
+        let too_many_accounts =
+            vec![hot_account.to_string(); MAX_GET_RECENT_PRIORITIZATION_FEES_QUERY_ITEMS + 1];
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getRecentPrioritizationFees",
+            "params": [too_many_accounts],
+        })
+        .to_string();
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["message"],
+            "Too many inputs provided; max 128"
+        );
+    }
+
     #[test]
     fn test_rpc_get_recent_performance_samples_invalid_limit() {
         let bob_pubkey = mundis_sdk::pubkey::new_rand();
@@ -5490,6 +6195,14 @@ pub mod tests {
 
         bank.freeze(); // Ensure the root bank is frozen, `start_rpc_handler_with_tx()` doesn't do this
 
+        // Only the system program, loaded as the executable of the transfer, has data
+        let loaded_accounts_data_size = bank
+            .get_account(&system_program::id())
+            .unwrap()
+            .data()
+            .len();
+        let loaded_accounts_data_size_limit = 64 * 1024 * 1024;
+
         // Good signature with sigVerify=true
         let req = format!(
             r#"{{"jsonrpc":"2.0",
@@ -5531,7 +6244,13 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "returnData":null,
+                    "innerInstructions":null,
+                    "preTokenBalances":null,
+                    "postTokenBalances":null,
+                    "loadedAccountsDataSize":loaded_accounts_data_size,
+                    "loadedAccountsDataSizeLimit":loaded_accounts_data_size_limit
                 }
             },
             "id": 1,
@@ -5617,7 +6336,13 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "returnData":null,
+                    "innerInstructions":null,
+                    "preTokenBalances":null,
+                    "postTokenBalances":null,
+                    "loadedAccountsDataSize":loaded_accounts_data_size,
+                    "loadedAccountsDataSizeLimit":loaded_accounts_data_size_limit
                 }
             },
             "id": 1,
@@ -5645,7 +6370,13 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "returnData":null,
+                    "innerInstructions":null,
+                    "preTokenBalances":null,
+                    "postTokenBalances":null,
+                    "loadedAccountsDataSize":loaded_accounts_data_size,
+                    "loadedAccountsDataSizeLimit":loaded_accounts_data_size_limit
                 }
             },
             "id": 1,
@@ -5694,7 +6425,13 @@ pub mod tests {
                     "err":"BlockhashNotFound",
                     "accounts":null,
                     "logs":[],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "returnData":null,
+                    "innerInstructions":null,
+                    "preTokenBalances":null,
+                    "postTokenBalances":null,
+                    "loadedAccountsDataSize":0,
+                    "loadedAccountsDataSizeLimit":loaded_accounts_data_size_limit
                 }
             },
             "id":1
@@ -5723,7 +6460,13 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "returnData":null,
+                    "innerInstructions":null,
+                    "preTokenBalances":null,
+                    "postTokenBalances":null,
+                    "loadedAccountsDataSize":loaded_accounts_data_size,
+                    "loadedAccountsDataSizeLimit":loaded_accounts_data_size_limit
                 }
             },
             "id": 1,
@@ -5736,6 +6479,244 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_simulate_bundle() {
+        let RpcHandler {
+            io,
+            meta,
+            blockhash,
+            alice,
+            bank,
+            ..
+        } = start_rpc_handler_with_tx(&mundis_sdk::pubkey::new_rand());
+
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let bob = Keypair::new();
+        let carol_pubkey = mundis_sdk::pubkey::new_rand();
+        let fund_bob =
+            system_transaction::transfer(&alice, &bob.pubkey(), 10 * rent_exempt_amount, blockhash);
+        let pay_carol =
+            system_transaction::transfer(&bob, &carol_pubkey, rent_exempt_amount, blockhash);
+        let fund_bob_encoded = bs58::encode(serialize(&fund_bob).unwrap()).into_string();
+        let pay_carol_encoded = bs58::encode(serialize(&pay_carol).unwrap()).into_string();
+
+        bank.freeze();
+
+        // Bob has no funds on his own
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{}"]}}"#,
+            pay_carol_encoded,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_ne!(json["result"]["value"]["err"], Value::Null);
+
+        // But can pay Carol once funded earlier in the bundle
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"simulateBundle","params":[["{}", "{}"], {{"accounts": {{"addresses": ["{}"]}}}}]}}"#,
+            fund_bob_encoded, pay_carol_encoded, carol_pubkey,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let results: Vec<RpcSimulateTransactionResult> =
+            serde_json::from_value(json["result"]["value"].clone())
+                .expect("actual response deserialization");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].err, None);
+        assert_eq!(results[1].err, None);
+        let carol_account = results[1].accounts.as_ref().unwrap()[0].as_ref().unwrap();
+        assert_eq!(carol_account.lamports, rent_exempt_amount);
+
+        // The simulated state is never committed
+        assert_eq!(bank.get_balance(&bob.pubkey()), 0);
+
+        // Empty bundles are rejected
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"simulateBundle","params":[[]]}"#;
+        let res = io.handle_request_sync(req, meta);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_send_bundle() {
+        let RpcHandler {
+            io,
+            meta,
+            blockhash,
+            alice,
+            bank,
+            ..
+        } = start_rpc_handler_with_tx(&mundis_sdk::pubkey::new_rand());
+
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let bob = Keypair::new();
+        let carol_pubkey = mundis_sdk::pubkey::new_rand();
+        let dave_pubkey = mundis_sdk::pubkey::new_rand();
+        bank.transfer(10 * rent_exempt_amount, &alice, &bob.pubkey())
+            .unwrap();
+        let pay_dave =
+            system_transaction::transfer(&alice, &dave_pubkey, rent_exempt_amount, blockhash);
+        let pay_carol =
+            system_transaction::transfer(&bob, &carol_pubkey, rent_exempt_amount, blockhash);
+        let pay_dave_encoded = bs58::encode(serialize(&pay_dave).unwrap()).into_string();
+        let pay_carol_encoded = bs58::encode(serialize(&pay_carol).unwrap()).into_string();
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"sendBundle","params":[["{}", "{}"], {{"skipPreflight": true}}]}}"#,
+            pay_dave_encoded, pay_carol_encoded,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let bundle_id = VersionedBundle::id_from_signatures(
+            [pay_dave.signatures[0], pay_carol.signatures[0]].iter(),
+        );
+        assert_eq!(json["result"], bundle_id.to_string());
+
+        // The bundle has not landed yet
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getBundleStatuses","params":[["{}", "{}"]]}}"#,
+            bundle_id,
+            Hash::new_unique(),
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["result"]["value"], json!([null, null]));
+
+        // Only bundles whose transactions have all landed are reported
+        bank.process_transaction(&pay_dave).unwrap();
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["result"]["value"], json!([null, null]));
+
+        bank.process_transaction(&pay_carol).unwrap();
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let statuses: Vec<Option<RpcBundleStatus>> =
+            serde_json::from_value(json["result"]["value"].clone())
+                .expect("actual response deserialization");
+        let status = statuses[0].as_ref().unwrap();
+        assert_eq!(status.bundle_id, bundle_id.to_string());
+        assert_eq!(status.slot, bank.slot());
+        assert_eq!(
+            status.transactions,
+            vec![
+                pay_dave.signatures[0].to_string(),
+                pay_carol.signatures[0].to_string()
+            ]
+        );
+        assert_eq!(statuses[1], None);
+
+        // Bundles with duplicate transactions are rejected
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"sendBundle","params":[["{}", "{}"], {{"skipPreflight": true}}]}}"#,
+            pay_dave_encoded, pay_dave_encoded,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+
+        // A transaction may depend on the ones before it in the bundle
+        let fund_bob = system_transaction::transfer(&alice, &bob.pubkey(), 1, blockhash);
+        let fund_bob_encoded = bs58::encode(serialize(&fund_bob).unwrap()).into_string();
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"sendBundle","params":[["{}", "{}"], {{"skipPreflight": true}}]}}"#,
+            fund_bob_encoded, pay_carol_encoded,
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let bundle_id = VersionedBundle::id_from_signatures(
+            [fund_bob.signatures[0], pay_carol.signatures[0]].iter(),
+        );
+        assert_eq!(json["result"], bundle_id.to_string());
+    }
+
+    #[test]
+    fn test_rpc_get_durable_nonce_transaction_statuses() {
+        let RpcHandler {
+            io,
+            meta,
+            blockhash,
+            alice,
+            bank,
+            ..
+        } = start_rpc_handler_with_tx(&mundis_sdk::pubkey::new_rand());
+
+        let nonce_keypair = Keypair::new();
+        let instruction = system_instruction::create_nonce_account(
+            &alice.pubkey(),
+            &nonce_keypair.pubkey(),
+            &alice.pubkey(),
+            100_000,
+        );
+        let message = Message::new(&instruction, Some(&alice.pubkey()));
+        let tx = Transaction::new(&[&alice, &nonce_keypair], message, blockhash);
+        bank.process_transaction(&tx).unwrap();
+        let nonce_account = bank.get_account(&nonce_keypair.pubkey()).unwrap();
+        let durable_nonce = nonce_utils::data_from_account(&nonce_account)
+            .unwrap()
+            .blockhash();
+
+        let message = Message::new_with_nonce(
+            vec![system_instruction::transfer(
+                &alice.pubkey(),
+                &mundis_sdk::pubkey::new_rand(),
+                1,
+            )],
+            Some(&alice.pubkey()),
+            &nonce_keypair.pubkey(),
+            &alice.pubkey(),
+        );
+        let nonce_tx = Transaction::new(&[&alice], message, durable_nonce);
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"sendTransaction","params":["{}", {{"skipPreflight": true}}]}}"#,
+            bs58::encode(serialize(&nonce_tx).unwrap()).into_string(),
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["result"], nonce_tx.signatures[0].to_string());
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getDurableNonceTransactionStatuses","params":[["{}", "{}"]]}}"#,
+            nonce_tx.signatures[0],
+            Signature::default(),
+        );
+        let get_statuses = || {
+            let res = io.handle_request_sync(&req, meta.clone());
+            let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+            serde_json::from_value::<Vec<Option<RpcDurableNonceTransactionStatus>>>(
+                json["result"]["value"].clone(),
+            )
+            .expect("actual response deserialization")
+        };
+        let wait_for_status = |expected_status: RpcDurableNonceTransactionStatus| {
+            for _ in 0..50 {
+                if get_statuses()[0] == Some(expected_status.clone()) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            panic!("durable-nonce transaction status not reported");
+        };
+
+        // The transaction is retried until its nonce advances...
+        wait_for_status(RpcDurableNonceTransactionStatus::Pending);
+        assert_eq!(get_statuses()[1], None);
+
+        // ... and is reported as such when another transaction advances the nonce first
+        let nonce_state = nonce::state::Versions::new(
+            nonce::State::Initialized(nonce::state::Data::new(
+                alice.pubkey(),
+                nonce::state::DurableNonce::from_blockhash(&Hash::new_unique(), true),
+                42,
+            )),
+            true, // separate_domains
+        );
+        let nonce_account =
+            AccountSharedData::new_data(100_000, &nonce_state, &system_program::id()).unwrap();
+        bank.store_account(&nonce_keypair.pubkey(), &nonce_account);
+        wait_for_status(RpcDurableNonceTransactionStatus::NonceConsumed);
+    }
+
     #[test]
     #[should_panic]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -6082,7 +7063,7 @@ pub mod tests {
         assert_eq!(
             res,
             Some(
-                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","logs":[],"unitsConsumed":0}},"id":1}"#.to_string(),
+                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","logs":[],"unitsConsumed":0,"returnData":null,"innerInstructions":null,"preTokenBalances":null,"postTokenBalances":null,"loadedAccountsDataSize":0,"loadedAccountsDataSizeLimit":67108864}},"id":1}"#.to_string(),
             )
         );
 
@@ -6453,8 +7434,9 @@ pub mod tests {
         assert_eq!(confirmed_block.transactions.len(), 2);
         assert_eq!(confirmed_block.rewards, vec![]);
 
-        for EncodedTransactionWithStatusMeta { transaction, meta } in
-            confirmed_block.transactions.into_iter()
+        for EncodedTransactionWithStatusMeta {
+            transaction, meta, ..
+        } in confirmed_block.transactions.into_iter()
         {
             if let EncodedTransaction::Json(transaction) = transaction {
                 if transaction.signatures[0] == confirmed_block_signatures[0].to_string() {
@@ -6498,8 +7480,9 @@ pub mod tests {
         assert_eq!(confirmed_block.transactions.len(), 2);
         assert_eq!(confirmed_block.rewards, vec![]);
 
-        for EncodedTransactionWithStatusMeta { transaction, meta } in
-            confirmed_block.transactions.into_iter()
+        for EncodedTransactionWithStatusMeta {
+            transaction, meta, ..
+        } in confirmed_block.transactions.into_iter()
         {
             if let EncodedTransaction::LegacyBinary(transaction) = transaction {
                 let decoded_transaction: Transaction =
@@ -6560,6 +7543,7 @@ pub mod tests {
                 transaction_details: Some(TransactionDetails::Signatures),
                 rewards: Some(false),
                 commitment: None,
+                max_supported_transaction_version: None,
             })
         );
         let res = io.handle_request_sync(&req, meta.clone());
@@ -6581,6 +7565,7 @@ pub mod tests {
                 transaction_details: Some(TransactionDetails::None),
                 rewards: Some(true),
                 commitment: None,
+                max_supported_transaction_version: None,
             })
         );
         let res = io.handle_request_sync(&req, meta);
@@ -7176,6 +8161,7 @@ pub mod tests {
             is_native: None,
             delegated_amount: 30,
             close_authority: Some(owner),
+            memo_transfer: MemoTransfer::Disabled,
         };
         TokenAccount::pack(token_account, &mut account_data).unwrap();
         let token_account = AccountSharedData::from(Account {
@@ -7274,6 +8260,7 @@ pub mod tests {
             is_native: None,
             delegated_amount: 30,
             close_authority: Some(owner),
+            memo_transfer: MemoTransfer::Disabled,
         };
         TokenAccount::pack(token_account, &mut account_data).unwrap();
         let token_account = AccountSharedData::from(Account {
@@ -7519,6 +8506,7 @@ pub mod tests {
             is_native: None,
             delegated_amount: 30,
             close_authority: Some(owner),
+            memo_transfer: MemoTransfer::Disabled,
         };
         TokenAccount::pack(token_account, &mut account_data).unwrap();
         let token_account = AccountSharedData::from(Account {
@@ -7583,6 +8571,7 @@ pub mod tests {
             is_native: Some(10),
             delegated_amount: 30,
             close_authority: Some(owner),
+            memo_transfer: MemoTransfer::Disabled,
         };
         TokenAccount::pack(token_account, &mut account_data).unwrap();
         let token_account = AccountSharedData::from(Account {
@@ -7735,6 +8724,57 @@ pub mod tests {
         .is_none());
     }
 
+    #[test]
+    fn test_get_custom_index_filter() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex {
+            program_id,
+            offset: 8,
+            len: 4,
+        };
+        let mut account_indexes = AccountSecondaryIndexes::default();
+        account_indexes
+            .indexes
+            .insert(AccountIndex::Custom(custom_index));
+        let memcmp = |offset, bytes: &[u8]| {
+            RpcFilterType::Memcmp(Memcmp {
+                offset,
+                bytes: MemcmpEncodedBytes::Bytes(bytes.to_vec()),
+                encoding: None,
+            })
+        };
+
+        assert_eq!(
+            get_custom_index_filter(
+                &account_indexes,
+                &program_id,
+                &[RpcFilterType::DataSize(64), memcmp(8, &[1, 2, 3, 4])],
+            ),
+            Some(IndexKey::Custom(
+                custom_index,
+                custom_index.key(&[1, 2, 3, 4]).unwrap()
+            ))
+        );
+
+        // Wrong offset, wrong length or wrong program id
+        assert!(get_custom_index_filter(
+            &account_indexes,
+            &program_id,
+            &[memcmp(4, &[1, 2, 3, 4])]
+        )
+        .is_none());
+        assert!(
+            get_custom_index_filter(&account_indexes, &program_id, &[memcmp(8, &[1, 2, 3])])
+                .is_none()
+        );
+        assert!(get_custom_index_filter(
+            &account_indexes,
+            &Pubkey::new_unique(),
+            &[memcmp(8, &[1, 2, 3, 4])]
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_rpc_single_gossip() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -36,8 +36,10 @@ use {
         native_token::lamports_to_mdis, pubkey::Pubkey,
     },
     mundis_send_transaction_service::send_transaction_service::{self, SendTransactionService},
+    mundis_storage_bigtable::{LedgerStorage, LedgerStorageAdapter, LocalLedgerStorage},
     std::{
         collections::HashSet,
+        io::SeekFrom,
         net::SocketAddr,
         path::{Path, PathBuf},
         sync::{
@@ -47,6 +49,7 @@ use {
         },
         thread::{self, Builder, JoinHandle},
     },
+    tokio::io::{AsyncReadExt, AsyncSeekExt},
     tokio_util::codec::{BytesCodec, FramedRead},
 };
 
@@ -63,6 +66,46 @@ pub struct JsonRpcService {
     close_handle: Option<CloseHandle>,
 }
 
+/// The part of a file requested by the `Range` header of a file GET
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    /// start and (exclusive) end offsets
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=start-end`, `bytes=start-` or `bytes=-suffix_length` range, as sent
+/// by resumable and multi-connection downloaders.  Anything else is answered with the full file.
+fn parse_byte_range(range: Option<&str>, file_length: u64) -> ByteRange {
+    let (start, end) = match range
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.split_once('-'))
+    {
+        Some(start_end) => start_end,
+        None => return ByteRange::Full,
+    };
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix_length) => match suffix_length.parse::<u64>() {
+            Ok(suffix_length) => (file_length.saturating_sub(suffix_length), file_length),
+            Err(_) => return ByteRange::Full,
+        },
+        (start, "") => match start.parse() {
+            Ok(start) => (start, file_length),
+            Err(_) => return ByteRange::Full,
+        },
+        (start, end) => match (start.parse(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => (start, file_length.min(end + 1)),
+            _ => return ByteRange::Full,
+        },
+    };
+    if start >= file_length {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
 struct RpcRequestMiddleware {
     ledger_path: PathBuf,
     full_snapshot_archive_path_regex: Regex,
@@ -155,7 +198,7 @@ impl RpcRequestMiddleware {
         tokio::fs::File::open(path).await
     }
 
-    fn process_file_get(&self, path: &str) -> RequestMiddlewareAction {
+    fn process_file_get(&self, path: &str, range: Option<&str>) -> RequestMiddlewareAction {
         let stem = path.split_at(1).1; // Drop leading '/' from path
         let filename = {
             match path {
@@ -174,27 +217,55 @@ impl RpcRequestMiddleware {
             }
         };
 
-        let file_length = std::fs::metadata(&filename)
-            .map(|m| m.len())
-            .unwrap_or(0)
-            .to_string();
-        info!("get {} -> {:?} ({} bytes)", path, filename, file_length);
+        let file_length = std::fs::metadata(&filename).map(|m| m.len()).unwrap_or(0);
+        let byte_range = parse_byte_range(range, file_length);
+        info!(
+            "get {} -> {:?} ({} bytes, {:?})",
+            path, filename, file_length, byte_range
+        );
         RequestMiddlewareAction::Respond {
             should_validate_hosts: true,
-            response: Box::pin(async {
+            response: Box::pin(async move {
                 match Self::open_no_follow(filename).await {
                     Err(err) => Ok(if err.kind() == std::io::ErrorKind::NotFound {
                         Self::not_found()
                     } else {
                         Self::internal_server_error()
                     }),
-                    Ok(file) => {
-                        let stream =
-                            FramedRead::new(file, BytesCodec::new()).map_ok(|b| b.freeze());
+                    Ok(mut file) => {
+                        let response = hyper::Response::builder()
+                            .header(hyper::header::ACCEPT_RANGES, "bytes");
+                        let (response, start, end) = match byte_range {
+                            ByteRange::Full => (response, 0, file_length),
+                            ByteRange::Partial(start, end) => (
+                                response.status(hyper::StatusCode::PARTIAL_CONTENT).header(
+                                    hyper::header::CONTENT_RANGE,
+                                    format!("bytes {}-{}/{}", start, end - 1, file_length),
+                                ),
+                                start,
+                                end,
+                            ),
+                            ByteRange::Unsatisfiable => {
+                                return Ok(response
+                                    .status(hyper::StatusCode::RANGE_NOT_SATISFIABLE)
+                                    .header(
+                                        hyper::header::CONTENT_RANGE,
+                                        format!("bytes */{}", file_length),
+                                    )
+                                    .body(hyper::Body::empty())
+                                    .unwrap());
+                            }
+                        };
+                        if file.seek(SeekFrom::Start(start)).await.is_err() {
+                            return Ok(Self::internal_server_error());
+                        }
+
+                        let stream = FramedRead::new(file.take(end - start), BytesCodec::new())
+                            .map_ok(|b| b.freeze());
                         let body = hyper::Body::wrap_stream(stream);
 
-                        Ok(hyper::Response::builder()
-                            .header(hyper::header::CONTENT_LENGTH, file_length)
+                        Ok(response
+                            .header(hyper::header::CONTENT_LENGTH, end - start)
                             .body(body)
                             .unwrap())
                     }
@@ -269,7 +340,11 @@ impl RequestMiddleware for RpcRequestMiddleware {
                 .unwrap()
                 .into()
         } else if self.is_file_get_path(request.uri().path()) {
-            self.process_file_get(request.uri().path())
+            let range = request
+                .headers()
+                .get(hyper::header::RANGE)
+                .and_then(|range| range.to_str().ok());
+            self.process_file_get(request.uri().path(), range)
         } else if request.uri().path() == "/health" {
             hyper::Response::builder()
                 .status(hyper::StatusCode::OK)
@@ -365,15 +440,25 @@ impl JsonRpcService {
 
         let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
             if config.enable_bigtable_ledger_storage || config.enable_bigtable_ledger_upload {
-                runtime
-                    .block_on(mundis_storage_bigtable::LedgerStorage::new(
-                        !config.enable_bigtable_ledger_upload,
-                        config.rpc_bigtable_timeout,
-                        None,
-                    ))
+                let read_only = !config.enable_bigtable_ledger_upload;
+                let ledger_storage = match &config.local_ledger_storage_path {
+                    Some(path) => LocalLedgerStorage::open(path, read_only).map(|storage| {
+                        info!("Local ledger storage initialized");
+                        Arc::new(storage) as Arc<dyn LedgerStorageAdapter>
+                    }),
+                    None => runtime
+                        .block_on(LedgerStorage::new(
+                            read_only,
+                            config.rpc_bigtable_timeout,
+                            None,
+                        ))
+                        .map(|storage| {
+                            info!("BigTable ledger storage initialized");
+                            Arc::new(storage) as Arc<dyn LedgerStorageAdapter>
+                        }),
+                };
+                ledger_storage
                     .map(|bigtable_ledger_storage| {
-                        info!("BigTable ledger storage initialized");
-
                         let bigtable_ledger_upload_service = if config.enable_bigtable_ledger_upload
                         {
                             Some(Arc::new(BigTableUploadService::new(
@@ -394,7 +479,7 @@ impl JsonRpcService {
                         )
                     })
                     .unwrap_or_else(|err| {
-                        error!("Failed to initialize BigTable ledger storage: {:?}", err);
+                        error!("Failed to initialize long-term ledger storage: {:?}", err);
                         (None, None)
                     })
             } else {
@@ -422,13 +507,15 @@ impl JsonRpcService {
 
         let leader_info =
             poh_recorder.map(|recorder| ClusterTpuInfo::new(cluster_info.clone(), recorder));
-        let _send_transaction_service = Arc::new(SendTransactionService::new_with_config(
-            tpu_address,
-            &bank_forks,
-            leader_info,
-            receiver,
-            send_transaction_service_config,
-        ));
+        let _send_transaction_service =
+            Arc::new(SendTransactionService::new_with_durable_nonce_statuses(
+                tpu_address,
+                &bank_forks,
+                leader_info,
+                receiver,
+                send_transaction_service_config,
+                request_processor.durable_nonce_statuses(),
+            ));
 
         #[cfg(test)]
         let test_request_processor = request_processor.clone();
@@ -713,7 +800,7 @@ mod tests {
         );
 
         // File does not exist => request should fail.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -728,7 +815,7 @@ mod tests {
         }
 
         // Normal file exist => request should succeed.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -737,6 +824,34 @@ mod tests {
             panic!("Unexpected RequestMiddlewareAction variant");
         }
 
+        // Range request => only the requested bytes are returned
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, Some("bytes=3-4"));
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response);
+            let response = response.unwrap();
+            assert_eq!(response.status(), 206);
+            assert_eq!(
+                response.headers()[hyper::header::CONTENT_RANGE],
+                "bytes 3-4/12"
+            );
+            let body = runtime
+                .block_on(hyper::body::to_bytes(response.into_body()))
+                .unwrap();
+            assert_eq!(&body[..], b"ul");
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+
+        // Range past the end of the file => request should fail.
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, Some("bytes=12-"));
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response);
+            let response = response.unwrap();
+            assert_eq!(response.status(), 416);
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+
         #[cfg(unix)]
         {
             std::fs::remove_file(&genesis_path).unwrap();
@@ -747,7 +862,7 @@ mod tests {
             symlink::symlink_file("wrong", &genesis_path).unwrap();
 
             // File is a symbolic link => request should fail.
-            let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+            let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
             if let RequestMiddlewareAction::Respond { response, .. } = action {
                 let response = runtime.block_on(response);
                 let response = response.unwrap();
@@ -758,6 +873,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_byte_range() {
+        assert_eq!(parse_byte_range(None, 100), ByteRange::Full);
+        assert_eq!(
+            parse_byte_range(Some("bytes=0-9"), 100),
+            ByteRange::Partial(0, 10)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=90-"), 100),
+            ByteRange::Partial(90, 100)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=90-200"), 100),
+            ByteRange::Partial(90, 100)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=-10"), 100),
+            ByteRange::Partial(90, 100)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(parse_byte_range(Some("bytes=9-0"), 100), ByteRange::Full);
+        assert_eq!(
+            parse_byte_range(Some("bytes=0-1,5-6"), 100),
+            ByteRange::Full
+        );
+        assert_eq!(parse_byte_range(Some("items=0-9"), 100), ByteRange::Full);
+    }
+
     #[test]
     fn test_health_check_with_no_known_validators() {
         let rm = RpcRequestMiddleware::new(
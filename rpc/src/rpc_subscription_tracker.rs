@@ -140,6 +140,7 @@ pub struct BlockSubscriptionParams {
     pub kind: BlockSubscriptionKind,
     pub transaction_details: TransactionDetails,
     pub show_rewards: bool,
+    pub max_supported_transaction_version: Option<u8>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
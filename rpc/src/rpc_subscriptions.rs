@@ -39,7 +39,7 @@ use {
         timing::timestamp,
         transaction,
     },
-    mundis_transaction_status::{ConfirmedBlock, ConfirmedBlockWithOptionalMetadata},
+    mundis_transaction_status::{ConfirmedBlock, ConfirmedBlockWithOptionalMetadata, EncodeError},
     mundis_vote_program::vote_state::Vote,
     std::{
         cell::RefCell,
@@ -303,7 +303,16 @@ fn filter_block_result_txs(
         BlockSubscriptionKind::MentionsAccountOrProgram(pk) => block
             .transactions
             .into_iter()
-            .filter(|tx_with_meta| tx_with_meta.transaction.message.account_keys.contains(&pk))
+            .filter(|tx_with_meta| {
+                let loaded_addresses = &tx_with_meta.meta.loaded_addresses;
+                tx_with_meta
+                    .transaction
+                    .message
+                    .static_account_keys_iter()
+                    .chain(loaded_addresses.writable.iter())
+                    .chain(loaded_addresses.readonly.iter())
+                    .any(|key| key == &pk)
+            })
             .collect(),
     };
 
@@ -313,20 +322,28 @@ fn filter_block_result_txs(
         }
     }
 
-    let block = ConfirmedBlockWithOptionalMetadata::from(block).configure(
+    let block_update = match ConfirmedBlockWithOptionalMetadata::from(block).configure(
         params.encoding,
         params.transaction_details,
         params.show_rewards,
-    );
+        params.max_supported_transaction_version,
+    ) {
+        Ok(block) => RpcBlockUpdate {
+            slot: last_modified_slot,
+            block: Some(block),
+            err: None,
+        },
+        Err(EncodeError::UnsupportedTransactionVersion(version)) => RpcBlockUpdate {
+            slot: last_modified_slot,
+            block: None,
+            err: Some(RpcBlockUpdateError::UnsupportedTransactionVersion(version)),
+        },
+    };
 
     // If last_modified_slot < last_notified_slot, then the last notif was for a fork.
     // That's the risk clients take when subscribing to non-finalized commitments.
     // This code lets the logic for dealing with forks live on the client side.
-    Some(RpcBlockUpdate {
-        slot: last_modified_slot,
-        block: Some(block),
-        err: None,
-    })
+    Some(block_update)
 }
 
 fn filter_account_result(
@@ -1382,6 +1399,7 @@ pub(crate) mod tests {
             encoding: Some(UiTransactionEncoding::Json),
             transaction_details: Some(TransactionDetails::Signatures),
             show_rewards: None,
+            max_supported_transaction_version: None,
         };
         let params = BlockSubscriptionParams {
             kind: BlockSubscriptionKind::All,
@@ -1389,6 +1407,7 @@ pub(crate) mod tests {
             encoding: config.encoding.unwrap(),
             transaction_details: config.transaction_details.unwrap(),
             show_rewards: config.show_rewards.unwrap_or_default(),
+            max_supported_transaction_version: config.max_supported_transaction_version,
         };
         let sub_id = rpc.block_subscribe(filter, Some(config)).unwrap();
 
@@ -1417,11 +1436,14 @@ pub(crate) mod tests {
         let actual_resp = serde_json::from_str::<serde_json::Value>(&actual_resp).unwrap();
 
         let block = blockstore.get_complete_block(slot, false).unwrap();
-        let block = ConfirmedBlockWithOptionalMetadata::from(block).configure(
-            params.encoding,
-            params.transaction_details,
-            false,
-        );
+        let block = ConfirmedBlockWithOptionalMetadata::from(block)
+            .configure(
+                params.encoding,
+                params.transaction_details,
+                false,
+                params.max_supported_transaction_version,
+            )
+            .unwrap();
         let expected_resp = RpcBlockUpdate {
             slot,
             block: Some(block),
@@ -1491,6 +1513,7 @@ pub(crate) mod tests {
             encoding: Some(UiTransactionEncoding::Json),
             transaction_details: Some(TransactionDetails::Signatures),
             show_rewards: None,
+            max_supported_transaction_version: None,
         };
         let params = BlockSubscriptionParams {
             kind: BlockSubscriptionKind::MentionsAccountOrProgram(keypair1.pubkey()),
@@ -1498,6 +1521,7 @@ pub(crate) mod tests {
             encoding: config.encoding.unwrap(),
             transaction_details: config.transaction_details.unwrap(),
             show_rewards: config.show_rewards.unwrap_or_default(),
+            max_supported_transaction_version: config.max_supported_transaction_version,
         };
         let sub_id = rpc.block_subscribe(filter, Some(config)).unwrap();
 
@@ -1529,14 +1553,17 @@ pub(crate) mod tests {
         block.transactions.retain(|tx| {
             tx.transaction
                 .message
-                .account_keys
+                .static_account_keys()
                 .contains(&keypair1.pubkey())
         });
-        let block = ConfirmedBlockWithOptionalMetadata::from(block).configure(
-            params.encoding,
-            params.transaction_details,
-            false,
-        );
+        let block = ConfirmedBlockWithOptionalMetadata::from(block)
+            .configure(
+                params.encoding,
+                params.transaction_details,
+                false,
+                params.max_supported_transaction_version,
+            )
+            .unwrap();
         let expected_resp = RpcBlockUpdate {
             slot,
             block: Some(block),
@@ -1594,6 +1621,7 @@ pub(crate) mod tests {
             encoding: Some(UiTransactionEncoding::Json),
             transaction_details: Some(TransactionDetails::Signatures),
             show_rewards: None,
+            max_supported_transaction_version: None,
         };
         let params = BlockSubscriptionParams {
             kind: BlockSubscriptionKind::All,
@@ -1601,6 +1629,7 @@ pub(crate) mod tests {
             encoding: config.encoding.unwrap(),
             transaction_details: config.transaction_details.unwrap(),
             show_rewards: config.show_rewards.unwrap_or_default(),
+            max_supported_transaction_version: config.max_supported_transaction_version,
         };
         let sub_id = rpc.block_subscribe(filter, Some(config)).unwrap();
         subscriptions
@@ -1633,11 +1662,14 @@ pub(crate) mod tests {
         let actual_resp = serde_json::from_str::<serde_json::Value>(&actual_resp).unwrap();
 
         let block = blockstore.get_complete_block(slot, false).unwrap();
-        let block = ConfirmedBlockWithOptionalMetadata::from(block).configure(
-            params.encoding,
-            params.transaction_details,
-            false,
-        );
+        let block = ConfirmedBlockWithOptionalMetadata::from(block)
+            .configure(
+                params.encoding,
+                params.transaction_details,
+                false,
+                params.max_supported_transaction_version,
+            )
+            .unwrap();
         let expected_resp = RpcBlockUpdate {
             slot,
             block: Some(block),
@@ -96,7 +96,8 @@ impl TransactionStatusService {
                             log_messages,
                             inner_instructions,
                             durable_nonce_fee,
-                            ..
+                            return_data,
+                            executed_units,
                         } = details;
                         let lamports_per_signature = match durable_nonce_fee {
                             Some(DurableNonceFee::Valid(lamports_per_signature)) => {
@@ -151,6 +152,9 @@ impl TransactionStatusService {
                             pre_token_balances,
                             post_token_balances,
                             rewards,
+                            loaded_addresses: transaction.get_loaded_addresses(),
+                            return_data,
+                            compute_units_consumed: Some(executed_units),
                         };
 
                         if let Some(transaction_notifier) = transaction_notifier.as_ref() {
@@ -338,6 +342,7 @@ pub(crate) mod tests {
                 )
                     .unwrap(),
             )),
+            return_data: None,
             executed_units: 0u64,
         });
 
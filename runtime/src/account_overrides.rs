@@ -1,25 +1,119 @@
-use mundis_sdk::{account::AccountSharedData, pubkey::Pubkey, sysvar};
+use {
+    crate::bank::TransactionSimulationResult,
+    mundis_sdk::{
+        account::AccountSharedData, pubkey::Pubkey, sysvar, transaction::SanitizedTransaction,
+    },
+    std::collections::HashMap,
+};
 
 /// Encapsulates overridden accounts, typically used for transaction simulations
 #[derive(Default)]
 pub struct AccountOverrides {
-    pub slot_history: Option<AccountSharedData>,
+    accounts: HashMap<Pubkey, AccountSharedData>,
 }
 
 impl AccountOverrides {
+    /// Sets an account override, or removes it when `account` is `None`
+    pub fn set_account(&mut self, pubkey: &Pubkey, account: Option<AccountSharedData>) {
+        match account {
+            Some(account) => self.accounts.insert(*pubkey, account),
+            None => self.accounts.remove(pubkey),
+        };
+    }
+
     /// Sets in the slot history
     ///
     /// Note: no checks are performed on the correctness of the contained data
     pub fn set_slot_history(&mut self, slot_history: Option<AccountSharedData>) {
-        self.slot_history = slot_history;
+        self.set_account(&sysvar::slot_history::id(), slot_history);
     }
 
     /// Gets the account if it's found in the list of overrides
     pub fn get(&self, pubkey: &Pubkey) -> Option<&AccountSharedData> {
-        if pubkey == &sysvar::slot_history::id() {
-            self.slot_history.as_ref()
-        } else {
-            None
+        self.accounts.get(pubkey)
+    }
+
+    /// Records the writable accounts of a successfully simulated transaction so
+    /// that subsequent simulations observe its effects. Failed simulations leave
+    /// the overrides untouched.
+    pub fn apply_simulation_result(
+        &mut self,
+        transaction: &SanitizedTransaction,
+        simulation_result: &TransactionSimulationResult,
+    ) {
+        if simulation_result.result.is_err() {
+            return;
         }
+        self.apply_transaction_accounts(
+            transaction,
+            &simulation_result.post_simulation_accounts,
+        );
     }
-}
\ No newline at end of file
+
+    /// Records the writable accounts of a transaction, as they were after it executed, so that
+    /// subsequent transactions observe its effects
+    pub fn apply_transaction_accounts(
+        &mut self,
+        transaction: &SanitizedTransaction,
+        accounts: &[(Pubkey, AccountSharedData)],
+    ) {
+        let message = transaction.message();
+        for (index, (pubkey, account)) in accounts.iter().enumerate() {
+            if message.is_writable(index) {
+                self.accounts.insert(*pubkey, account.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        mundis_sdk::{
+            hash::Hash, signature::Keypair, signer::Signer, system_program, system_transaction,
+            transaction::TransactionError,
+        },
+    };
+
+    #[test]
+    fn test_apply_simulation_result() {
+        let from = Keypair::new();
+        let to = Pubkey::new_unique();
+        let transaction = SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&from, &to, 1, Hash::default()),
+        );
+        let from_account = AccountSharedData::new(41, 0, &Pubkey::default());
+        let to_account = AccountSharedData::new(1, 0, &Pubkey::default());
+        let program_account = AccountSharedData::default();
+        let mut simulation_result = TransactionSimulationResult {
+            result: Ok(()),
+            logs: vec![],
+            post_simulation_accounts: vec![
+                (from.pubkey(), from_account.clone()),
+                (to, to_account.clone()),
+                (system_program::id(), program_account),
+            ],
+            units_consumed: 0,
+            return_data: None,
+            inner_instructions: None,
+            loaded_accounts_data_size: 0,
+            loaded_accounts_data_size_limit: 0,
+        };
+
+        let mut account_overrides = AccountOverrides::default();
+        simulation_result.result = Err(TransactionError::AccountNotFound);
+        account_overrides.apply_simulation_result(&transaction, &simulation_result);
+        assert!(account_overrides.get(&from.pubkey()).is_none());
+
+        simulation_result.result = Ok(());
+        account_overrides.apply_simulation_result(&transaction, &simulation_result);
+        assert_eq!(account_overrides.get(&from.pubkey()), Some(&from_account));
+        assert_eq!(account_overrides.get(&to), Some(&to_account));
+        // Program accounts are never writable and must not be overridden
+        assert!(account_overrides.get(&system_program::id()).is_none());
+
+        account_overrides.set_account(&to, None);
+        assert!(account_overrides.get(&to).is_none());
+    }
+}
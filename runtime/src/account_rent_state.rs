@@ -30,12 +30,18 @@ impl RentState {
         }
     }
 
+    /// Once `rent_exempt_only` is active no account may be left RentPaying, not even one that
+    /// already was; legacy RentPaying accounts must be topped up to rent-exempt or closed
     pub(crate) fn transition_allowed_from(
         &self,
         pre_rent_state: &RentState,
         do_support_realloc: bool,
+        rent_exempt_only: bool,
     ) -> bool {
         if let Self::RentPaying(post_data_size) = self {
+            if rent_exempt_only {
+                return false;
+            }
             if let Self::RentPaying(pre_data_size) = pre_rent_state {
                 if do_support_realloc {
                     post_data_size == pre_data_size // Cannot be RentPaying if resized
@@ -71,15 +77,27 @@ pub(crate) fn check_rent_state(
     post_rent_state: Option<&RentState>,
     address: &Pubkey,
     account: &AccountSharedData,
+    account_index: usize,
     do_support_realloc: bool,
+    rent_exempt_only: bool,
 ) -> Result<()> {
     if let Some((pre_rent_state, post_rent_state)) = pre_rent_state.zip(post_rent_state) {
         submit_rent_state_metrics(pre_rent_state, post_rent_state);
         if !mundis_sdk::incinerator::check_id(address)
-            && !post_rent_state.transition_allowed_from(pre_rent_state, do_support_realloc)
+            && !post_rent_state.transition_allowed_from(
+                pre_rent_state,
+                do_support_realloc,
+                rent_exempt_only,
+            )
         {
             debug!("Account {:?} not rent exempt, state {:?}", address, account);
-            return Err(TransactionError::InvalidRentPayingAccount);
+            return Err(if rent_exempt_only {
+                TransactionError::InsufficientFundsForRent {
+                    account_index: account_index as u8,
+                }
+            } else {
+                TransactionError::InvalidRentPayingAccount
+            });
         }
     }
     Ok(())
@@ -138,20 +156,78 @@ mod tests {
     #[test]
     fn test_transition_allowed_from() {
         let post_rent_state = RentState::Uninitialized;
-        assert!(post_rent_state.transition_allowed_from(&RentState::Uninitialized, true));
-        assert!(post_rent_state.transition_allowed_from(&RentState::RentExempt, true));
-        assert!(post_rent_state.transition_allowed_from(&RentState::RentPaying(0), true));
+        assert!(post_rent_state.transition_allowed_from(&RentState::Uninitialized, true, false));
+        assert!(post_rent_state.transition_allowed_from(&RentState::RentExempt, true, false));
+        assert!(post_rent_state.transition_allowed_from(&RentState::RentPaying(0), true, false));
 
         let post_rent_state = RentState::RentExempt;
-        assert!(post_rent_state.transition_allowed_from(&RentState::Uninitialized, true));
-        assert!(post_rent_state.transition_allowed_from(&RentState::RentExempt, true));
-        assert!(post_rent_state.transition_allowed_from(&RentState::RentPaying(0), true));
+        assert!(post_rent_state.transition_allowed_from(&RentState::Uninitialized, true, false));
+        assert!(post_rent_state.transition_allowed_from(&RentState::RentExempt, true, false));
+        assert!(post_rent_state.transition_allowed_from(&RentState::RentPaying(0), true, false));
+
+        let post_rent_state = RentState::RentPaying(2);
+        assert!(!post_rent_state.transition_allowed_from(&RentState::Uninitialized, true, false));
+        assert!(!post_rent_state.transition_allowed_from(&RentState::RentExempt, true, false));
+        assert!(!post_rent_state.transition_allowed_from(&RentState::RentPaying(3), true, false));
+        assert!(!post_rent_state.transition_allowed_from(&RentState::RentPaying(1), true, false));
+        assert!(post_rent_state.transition_allowed_from(&RentState::RentPaying(2), true, false));
+    }
+
+    #[test]
+    fn test_transition_allowed_from_rent_exempt_only() {
+        for pre_rent_state in [
+            RentState::Uninitialized,
+            RentState::RentExempt,
+            RentState::RentPaying(2),
+        ] {
+            assert!(RentState::Uninitialized.transition_allowed_from(&pre_rent_state, true, true));
+            assert!(RentState::RentExempt.transition_allowed_from(&pre_rent_state, true, true));
+            assert!(!RentState::RentPaying(2).transition_allowed_from(&pre_rent_state, true, true));
+        }
+    }
 
+    #[test]
+    fn test_check_rent_state_rent_exempt_only() {
+        let address = Pubkey::new_unique();
+        let account = AccountSharedData::new(1, 2, &Pubkey::default());
+        let pre_rent_state = RentState::RentPaying(2);
         let post_rent_state = RentState::RentPaying(2);
-        assert!(!post_rent_state.transition_allowed_from(&RentState::Uninitialized, true));
-        assert!(!post_rent_state.transition_allowed_from(&RentState::RentExempt, true));
-        assert!(!post_rent_state.transition_allowed_from(&RentState::RentPaying(3), true));
-        assert!(!post_rent_state.transition_allowed_from(&RentState::RentPaying(1), true));
-        assert!(post_rent_state.transition_allowed_from(&RentState::RentPaying(2), true));
+
+        assert_eq!(
+            check_rent_state(
+                Some(&pre_rent_state),
+                Some(&post_rent_state),
+                &address,
+                &account,
+                3,
+                true,
+                false,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_rent_state(
+                Some(&pre_rent_state),
+                Some(&post_rent_state),
+                &address,
+                &account,
+                3,
+                true,
+                true,
+            ),
+            Err(TransactionError::InsufficientFundsForRent { account_index: 3 })
+        );
+        assert_eq!(
+            check_rent_state(
+                Some(&pre_rent_state),
+                Some(&post_rent_state),
+                &mundis_sdk::incinerator::id(),
+                &account,
+                3,
+                true,
+                true,
+            ),
+            Ok(())
+        );
     }
 }
@@ -26,6 +26,7 @@ use {
     log::*,
     rand::{thread_rng, Rng},
     mundis_address_lookup_table_program::state::AddressLookupTable,
+    mundis_program_runtime::compute_budget::ComputeBudget,
     mundis_sdk::{
         account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
         account_utils::StateMut,
@@ -50,6 +51,7 @@ use {
             AddressLookupError, Result, SanitizedTransaction, TransactionAccountLocks,
             TransactionError,
         },
+        wasm_loader_upgradeable::{self, UpgradeableLoaderState},
     },
     std::{
         cmp::Reverse,
@@ -127,6 +129,7 @@ pub struct LoadedTransaction {
     pub program_indices: TransactionProgramIndices,
     pub rent: TransactionRent,
     pub rent_debits: RentDebits,
+    pub loaded_accounts_data_size: usize,
 }
 
 pub type TransactionLoadResult = (Result<LoadedTransaction>, Option<NonceFull>);
@@ -294,6 +297,8 @@ impl Accounts {
             let mut account_deps = Vec::with_capacity(message.account_keys_len());
             let mut rent_debits = RentDebits::default();
             let rent_for_sysvars = feature_set.is_active(&feature_set::rent_for_sysvars::id());
+            let rent_exempt_only =
+                feature_set.is_active(&feature_set::rent_exempt_only_accounts::id());
             for (i, key) in message.account_keys_iter().enumerate() {
                 let account = if !message.is_non_loader_key(i) {
                     // Fill in an empty account for the program slots.
@@ -324,6 +329,7 @@ impl Accounts {
                                                 key,
                                                 &mut account,
                                                 rent_for_sysvars,
+                                                rent_exempt_only,
                                                 self.accounts_db.filler_account_suffix.as_ref(),
                                             )
                                             .rent_amount;
@@ -340,6 +346,30 @@ impl Accounts {
                             return Err(TransactionError::InvalidWritableAccount);
                         }
 
+                        if account.executable()
+                            && wasm_loader_upgradeable::check_id(account.owner())
+                        {
+                            // Cross-program invocations of an upgradeable
+                            // program need its ProgramData account as well
+                            if let Ok(UpgradeableLoaderState::Program {
+                                programdata_address,
+                            }) = UpgradeableLoaderState::deserialize(account.data())
+                            {
+                                let in_message = message
+                                    .account_keys_iter()
+                                    .any(|key| key == &programdata_address);
+                                if !in_message {
+                                    if let Some((programdata_account, _)) = self
+                                        .accounts_db
+                                        .load_with_fixed_root(ancestors, &programdata_address)
+                                    {
+                                        account_deps
+                                            .push((programdata_address, programdata_account));
+                                    }
+                                }
+                            }
+                        }
+
                         tx_rent += rent;
                         rent_debits.insert(key, rent, account.lamports());
 
@@ -394,12 +424,16 @@ impl Accounts {
                     Some(&payer_post_rent_state),
                     payer_address,
                     payer_account,
+                    payer_index,
                     feature_set.is_active(&feature_set::do_support_realloc::id()),
+                    rent_exempt_only,
                 );
                 // Feature gate only wraps the actual error return so that the metrics and debug
                 // logging generated by `check_rent_state()` can be examined before
                 // feature activation
-                if feature_set.is_active(&feature_set::require_rent_exempt_accounts::id()) {
+                if feature_set.is_active(&feature_set::require_rent_exempt_accounts::id())
+                    || rent_exempt_only
+                {
                     rent_state_result?;
                 }
 
@@ -415,11 +449,21 @@ impl Accounts {
                         )
                     })
                     .collect::<Result<Vec<Vec<usize>>>>()?;
+
+                let loaded_accounts_data_size = Self::loaded_accounts_data_size(&accounts);
+                if feature_set.is_active(&feature_set::cap_transaction_accounts_data_size::id())
+                    && loaded_accounts_data_size > Self::loaded_accounts_data_size_limit(message)
+                {
+                    error_counters.max_loaded_accounts_data_size_exceeded += 1;
+                    return Err(TransactionError::MaxLoadedAccountsDataSizeExceeded);
+                }
+
                 Ok(LoadedTransaction {
                     accounts,
                     program_indices,
                     rent: tx_rent,
                     rent_debits,
+                    loaded_accounts_data_size,
                 })
             } else {
                 error_counters.account_not_found += 1;
@@ -428,6 +472,28 @@ impl Accounts {
         }
     }
 
+    /// Total data size of the accounts loaded for a transaction. Program accounts are both
+    /// placeholders in the message and loaded as executables, so each key is counted once.
+    fn loaded_accounts_data_size(accounts: &[(Pubkey, AccountSharedData)]) -> usize {
+        accounts
+            .iter()
+            .fold(HashMap::new(), |mut data_sizes, (key, account)| {
+                let data_size = data_sizes.entry(key).or_insert(0);
+                *data_size = account.data().len().max(*data_size);
+                data_sizes
+            })
+            .values()
+            .fold(0usize, |total, data_size| total.saturating_add(*data_size))
+    }
+
+    /// Loaded accounts data size limit requested through the compute budget program, or the
+    /// default limit. Invalid compute budget instructions fail the transaction at execution.
+    pub fn loaded_accounts_data_size_limit(message: &SanitizedMessage) -> usize {
+        let mut compute_budget = ComputeBudget::default();
+        let _ = compute_budget.process_message(message, true, true);
+        compute_budget.loaded_accounts_data_size_limit
+    }
+
     fn load_executable_accounts(
         &self,
         ancestors: &Ancestors,
@@ -468,6 +534,33 @@ impl Accounts {
             // Add loader to chain
             let program_owner = *program.owner();
             account_indices.insert(0, program_account_index);
+
+            if wasm_loader_upgradeable::check_id(&program_owner) {
+                // The upgradeable loader requires the derived ProgramData account
+                if let Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) = UpgradeableLoaderState::deserialize(program.data())
+                {
+                    let programdata_account_index = match self
+                        .accounts_db
+                        .load_with_fixed_root(ancestors, &programdata_address)
+                    {
+                        Some((programdata_account, _)) => {
+                            let account_index = accounts.len();
+                            accounts.push((programdata_address, programdata_account));
+                            account_index
+                        }
+                        None => {
+                            error_counters.account_not_found += 1;
+                            return Err(TransactionError::ProgramAccountNotFound);
+                        }
+                    };
+                    account_indices.insert(0, programdata_account_index);
+                } else {
+                    error_counters.invalid_program_for_execution += 1;
+                    return Err(TransactionError::InvalidProgramForExecution);
+                }
+            }
             program_id = program_owner;
         }
         Ok(account_indices)
@@ -1024,6 +1117,50 @@ impl Accounts {
             .collect()
     }
 
+    /// Lock the accounts of all the transactions of a bundle at once. An account that is writable
+    /// in any of the transactions is write locked. Either every account is locked or, if any
+    /// transaction is invalid or any account is already in use, none is.
+    #[must_use]
+    pub fn lock_bundle_accounts<'a>(
+        &self,
+        txs: impl Iterator<Item = &'a SanitizedTransaction>,
+        feature_set: &FeatureSet,
+    ) -> Result<()> {
+        let (writable_keys, readonly_keys) =
+            Self::bundle_account_locks(txs, |tx| tx.get_account_locks(feature_set))?;
+        let account_locks = &mut self.account_locks.lock().unwrap();
+        self.lock_account(account_locks, writable_keys, readonly_keys)
+    }
+
+    /// Unlock the accounts locked by `lock_bundle_accounts`
+    pub fn unlock_bundle_accounts<'a>(&self, txs: impl Iterator<Item = &'a SanitizedTransaction>) {
+        let (writable_keys, readonly_keys) =
+            Self::bundle_account_locks(txs, |tx| Ok(tx.get_account_locks_unchecked()))
+                .expect("unchecked account locks are infallible");
+        let mut account_locks = self.account_locks.lock().unwrap();
+        debug!("bank unlock bundle accounts");
+        self.unlock_account(&mut account_locks, writable_keys, readonly_keys);
+    }
+
+    // Merges the account locks of the transactions of a bundle, each account being locked once
+    fn bundle_account_locks<'a>(
+        txs: impl Iterator<Item = &'a SanitizedTransaction>,
+        get_account_locks: impl Fn(&'a SanitizedTransaction) -> Result<TransactionAccountLocks<'a>>,
+    ) -> Result<(Vec<&'a Pubkey>, Vec<&'a Pubkey>)> {
+        let mut writable_keys = HashSet::new();
+        let mut readonly_keys = HashSet::new();
+        for tx in txs {
+            let tx_account_locks = get_account_locks(tx)?;
+            writable_keys.extend(tx_account_locks.writable);
+            readonly_keys.extend(tx_account_locks.readonly);
+        }
+        readonly_keys.retain(|key| !writable_keys.contains(key));
+        Ok((
+            writable_keys.into_iter().collect(),
+            readonly_keys.into_iter().collect(),
+        ))
+    }
+
     /// Once accounts are unlocked, new transactions that modify that state can enter the pipeline
     #[allow(clippy::needless_collect)]
     pub fn unlock_accounts<'a>(
@@ -1041,7 +1178,8 @@ impl Accounts {
                 | Err(TransactionError::WouldExceedMaxBlockCostLimit)
                 | Err(TransactionError::WouldExceedMaxVoteCostLimit)
                 | Err(TransactionError::WouldExceedMaxAccountCostLimit)
-                | Err(TransactionError::WouldExceedMaxAccountDataCostLimit) => None,
+                | Err(TransactionError::WouldExceedMaxAccountDataCostLimit)
+                | Err(TransactionError::InsufficientPriorityFee) => None,
                 _ => Some(tx.get_account_locks_unchecked()),
             })
             .collect();
@@ -1272,10 +1410,11 @@ mod tests {
         mundis_program_runtime::invoke_context::Executors,
         mundis_sdk::{
             account::{AccountSharedData, WritableAccount},
+            compute_budget::{self, ComputeBudgetInstruction},
             epoch_schedule::EpochSchedule,
             genesis_config::ClusterType,
             hash::Hash,
-            instruction::{CompiledInstruction, InstructionError},
+            instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
             message::{Message, MessageHeader},
             nonce, nonce_account,
             rent::Rent,
@@ -1315,6 +1454,7 @@ mod tests {
                 log_messages: None,
                 inner_instructions: None,
                 durable_nonce_fee: nonce.map(DurableNonceFee::from),
+                return_data: None,
                 executed_units: 0u64,
             },
             executors: Rc::new(RefCell::new(Executors::default())),
@@ -1364,13 +1504,16 @@ mod tests {
         lamports_per_signature: u64,
         error_counters: &mut TransactionErrorMetrics,
     ) -> Vec<TransactionLoadResult> {
+        // Tests load legacy rent-paying fee payers
+        let mut feature_set = FeatureSet::all_enabled();
+        feature_set.deactivate(&feature_set::rent_exempt_only_accounts::id());
         load_accounts_with_fee_and_rent(
             tx,
             ka,
             lamports_per_signature,
             &RentCollector::default(),
             error_counters,
-            &FeatureSet::all_enabled(),
+            &feature_set,
             &FeeStructure::default(),
         )
     }
@@ -1787,6 +1930,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_accounts_loaded_accounts_data_size_limit() {
+        let keypair = Keypair::new();
+        let data_key = Pubkey::new_unique();
+        let compute_budget_account =
+            native_loader::create_loadable_account_for_test("compute_budget_program");
+        let accounts = vec![
+            (
+                keypair.pubkey(),
+                AccountSharedData::new(1, 0, &Pubkey::default()),
+            ),
+            (
+                data_key,
+                AccountSharedData::new(1, 1024, &Pubkey::default()),
+            ),
+            (compute_budget::id(), compute_budget_account.clone()),
+        ];
+        // The compute budget program is counted once, even though it is both a message key
+        // and an executable
+        let loaded_accounts_data_size = 1024 + compute_budget_account.data().len();
+        let new_tx = |limit: usize| {
+            let instruction = Instruction::new_with_borsh(
+                compute_budget::id(),
+                &ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(limit as u32),
+                vec![AccountMeta::new_readonly(data_key, false)],
+            );
+            let message = Message::new(&[instruction], Some(&keypair.pubkey()));
+            Transaction::new(&[&keypair], message, Hash::default())
+        };
+
+        let mut error_counters = TransactionErrorMetrics::default();
+        let loaded_accounts = load_accounts(
+            new_tx(loaded_accounts_data_size),
+            &accounts,
+            &mut error_counters,
+        );
+        let (load_res, _nonce) = &loaded_accounts[0];
+        assert_eq!(
+            load_res.as_ref().unwrap().loaded_accounts_data_size,
+            loaded_accounts_data_size
+        );
+        assert_eq!(error_counters.max_loaded_accounts_data_size_exceeded, 0);
+
+        let loaded_accounts = load_accounts(
+            new_tx(loaded_accounts_data_size - 1),
+            &accounts,
+            &mut error_counters,
+        );
+        assert_eq!(
+            loaded_accounts[0],
+            (
+                Err(TransactionError::MaxLoadedAccountsDataSizeExceeded),
+                None
+            )
+        );
+        assert_eq!(error_counters.max_loaded_accounts_data_size_exceeded, 1);
+    }
+
     #[test]
     fn test_load_lookup_table_addresses_account_not_found() {
         let ancestors = vec![(0, 0)].into_iter().collect();
@@ -2419,6 +2620,93 @@ mod tests {
             .contains(&keypair1.pubkey()));
     }
 
+    #[test]
+    fn test_lock_bundle_accounts() {
+        let keypair0 = Keypair::new();
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+        );
+
+        let instructions = vec![CompiledInstruction::new(2, &(), vec![0, 1])];
+        let message = Message::new_with_compiled_instructions(
+            1,
+            0,
+            1,
+            vec![keypair0.pubkey(), keypair1.pubkey(), native_loader::id()],
+            Hash::default(),
+            instructions,
+        );
+        let tx0 = new_sanitized_tx(&[&keypair0], message, Hash::default());
+        let instructions = vec![CompiledInstruction::new(2, &(), vec![0, 1])];
+        let message = Message::new_with_compiled_instructions(
+            1,
+            0,
+            2,
+            vec![keypair1.pubkey(), keypair2.pubkey(), native_loader::id()],
+            Hash::default(),
+            instructions,
+        );
+        let tx1 = new_sanitized_tx(&[&keypair1], message, Hash::default());
+        let bundle = [tx0, tx1];
+
+        // keypair1 is writable in both transactions and only locked once
+        assert!(accounts
+            .lock_bundle_accounts(bundle.iter(), &FeatureSet::all_enabled())
+            .is_ok());
+        {
+            let account_locks = accounts.account_locks.lock().unwrap();
+            assert!(account_locks.is_locked_write(&keypair0.pubkey()));
+            assert!(account_locks.is_locked_write(&keypair1.pubkey()));
+            assert_eq!(
+                account_locks.readonly_locks.get(&keypair2.pubkey()),
+                Some(&1)
+            );
+            assert_eq!(
+                account_locks.readonly_locks.get(&native_loader::id()),
+                Some(&1)
+            );
+        }
+
+        // The bundle cannot be locked twice
+        assert_eq!(
+            accounts.lock_bundle_accounts(bundle.iter(), &FeatureSet::all_enabled()),
+            Err(TransactionError::AccountInUse)
+        );
+
+        // Readonly accounts can still be shared with other transactions
+        let payer = Keypair::new();
+        let instructions = vec![CompiledInstruction::new(2, &(), vec![0, 1])];
+        let message = Message::new_with_compiled_instructions(
+            1,
+            0,
+            2,
+            vec![payer.pubkey(), keypair2.pubkey(), native_loader::id()],
+            Hash::default(),
+            instructions,
+        );
+        let reader = new_sanitized_tx(&[&payer], message, Hash::default());
+        let results = accounts.lock_accounts([reader].iter(), &FeatureSet::all_enabled());
+        assert!(results[0].is_ok());
+
+        // Unlocking the bundle leaves the other transaction's locks in place
+        accounts.unlock_bundle_accounts(bundle.iter());
+        let account_locks = accounts.account_locks.lock().unwrap();
+        assert!(!account_locks.is_locked_write(&keypair0.pubkey()));
+        assert!(!account_locks.is_locked_write(&keypair1.pubkey()));
+        assert_eq!(
+            account_locks.readonly_locks.get(&keypair2.pubkey()),
+            Some(&1)
+        );
+        assert!(account_locks.is_locked_write(&payer.pubkey()));
+    }
+
     #[test]
     fn test_accounts_locks_with_results() {
         let keypair0 = Keypair::new();
@@ -2575,6 +2863,7 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                loaded_accounts_data_size: 0,
             }),
             None,
         );
@@ -2585,6 +2874,7 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                loaded_accounts_data_size: 0,
             }),
             None,
         );
@@ -2677,6 +2967,9 @@ mod tests {
         let mut hash_queue = BlockhashQueue::new(100);
         hash_queue.register_hash(tx.message().recent_blockhash(), 10);
 
+        let mut feature_set = FeatureSet::all_enabled();
+        feature_set.deactivate(&feature_set::rent_exempt_only_accounts::id());
+
         let ancestors = vec![(0, 0)].into_iter().collect();
         let mut error_counters = TransactionErrorMetrics::default();
         accounts.load_accounts(
@@ -2686,7 +2979,7 @@ mod tests {
             &hash_queue,
             &mut error_counters,
             &rent_collector,
-            &FeatureSet::all_enabled(),
+            &feature_set,
             &FeeStructure::default(),
             account_overrides,
         )
@@ -3084,6 +3377,7 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                loaded_accounts_data_size: 0,
             }),
             nonce.clone(),
         );
@@ -3213,6 +3507,7 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                loaded_accounts_data_size: 0,
             }),
             nonce.clone(),
         );
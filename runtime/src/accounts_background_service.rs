@@ -108,7 +108,34 @@ impl SnapshotRequestHandler {
                     status_cache_slot_deltas,
                 } = snapshot_request;
 
-                let previous_hash = if test_hash_calculation {
+                let block_height = snapshot_root_bank.block_height();
+                let snapshot_type = if snapshot_utils::should_take_full_snapshot(
+                    block_height,
+                    self.snapshot_config.full_snapshot_archive_interval_slots,
+                ) {
+                    Some(SnapshotType::FullSnapshot)
+                } else if snapshot_utils::should_take_incremental_snapshot(
+                    block_height,
+                    self.snapshot_config
+                        .incremental_snapshot_archive_interval_slots,
+                    *last_full_snapshot_slot,
+                ) {
+                    Some(SnapshotType::IncrementalSnapshot(
+                        last_full_snapshot_slot.unwrap(),
+                    ))
+                } else {
+                    None
+                };
+                // An incremental snapshot only needs the hash of the accounts modified since the
+                // full snapshot it is based on, instead of the hash of every account
+                let incremental_snapshot_base_slot = match snapshot_type {
+                    Some(SnapshotType::IncrementalSnapshot(base_slot)) => Some(base_slot),
+                    _ => None,
+                };
+
+                let previous_hash = if test_hash_calculation
+                    && incremental_snapshot_base_slot.is_none()
+                {
                     // We have to use the index version here.
                     // We cannot calculate the non-index way because cache has not been flushed and stores don't match reality.
                     snapshot_root_bank.update_accounts_hash_with_index_option(true, false, None, false)
@@ -146,17 +173,25 @@ impl SnapshotRequestHandler {
                 flush_accounts_cache_time.stop();
 
                 let mut hash_time = Measure::start("hash_time");
-                let this_hash = snapshot_root_bank.update_accounts_hash_with_index_option(
-                    use_index_hash_calculation,
-                    test_hash_calculation,
-                    Some(snapshot_root_bank.epoch_schedule().slots_per_epoch),
-                    false,
-                );
-                let hash_for_testing = if test_hash_calculation {
-                    assert_eq!(previous_hash, this_hash);
-                    Some(snapshot_root_bank.get_accounts_hash())
+                let (this_hash, hash_for_testing) = if let Some(base_slot) =
+                    incremental_snapshot_base_slot
+                {
+                    let this_hash = snapshot_root_bank.update_incremental_accounts_hash(base_slot);
+                    (this_hash, test_hash_calculation.then(|| this_hash))
                 } else {
-                    None
+                    let this_hash = snapshot_root_bank.update_accounts_hash_with_index_option(
+                        use_index_hash_calculation,
+                        test_hash_calculation,
+                        Some(snapshot_root_bank.epoch_schedule().slots_per_epoch),
+                        false,
+                    );
+                    let hash_for_testing = if test_hash_calculation {
+                        assert_eq!(previous_hash, this_hash);
+                        Some(snapshot_root_bank.get_accounts_hash())
+                    } else {
+                        None
+                    };
+                    (this_hash, hash_for_testing)
                 };
                 hash_time.stop();
 
@@ -171,28 +206,13 @@ impl SnapshotRequestHandler {
                 if accounts_db_caching_enabled {
                     shrink_time = Measure::start("shrink_time");
                     snapshot_root_bank.shrink_candidate_slots();
+                    snapshot_root_bank.shrink_ancient_slots(*last_full_snapshot_slot);
                     shrink_time.stop();
                 }
 
-                let block_height = snapshot_root_bank.block_height();
-                let snapshot_type = if snapshot_utils::should_take_full_snapshot(
-                    block_height,
-                    self.snapshot_config.full_snapshot_archive_interval_slots,
-                ) {
+                if snapshot_type == Some(SnapshotType::FullSnapshot) {
                     *last_full_snapshot_slot = Some(snapshot_root_bank.slot());
-                    Some(SnapshotType::FullSnapshot)
-                } else if snapshot_utils::should_take_incremental_snapshot(
-                    block_height,
-                    self.snapshot_config
-                        .incremental_snapshot_archive_interval_slots,
-                    *last_full_snapshot_slot,
-                ) {
-                    Some(SnapshotType::IncrementalSnapshot(
-                        last_full_snapshot_slot.unwrap(),
-                    ))
-                } else {
-                    None
-                };
+                }
 
                 // Snapshot the bank and send over an accounts package
                 let mut snapshot_time = Measure::start("snapshot_time");
@@ -223,7 +243,7 @@ impl SnapshotRequestHandler {
                 info!("Took bank snapshot. snapshot type: {:?}, slot: {}, accounts hash: {}, bank hash: {}",
                       snapshot_type,
                       snapshot_root_bank.slot(),
-                      snapshot_root_bank.get_accounts_hash(),
+                      this_hash,
                       snapshot_root_bank.hash(),
                   );
 
@@ -278,6 +298,8 @@ impl SnapshotRequestHandler {
             SnapshotError::MismatchedBaseSlot(..) => true,
             SnapshotError::NoSnapshotArchives => true,
             SnapshotError::MismatchedSlotHash(..) => true,
+            SnapshotError::MissingBankSnapshot(..) => true,
+            SnapshotError::BankVerificationFailed(..) => true,
         }
     }
 }
@@ -473,6 +495,7 @@ impl AccountsBackgroundService {
                                 bank.force_flush_accounts_cache();
                             }
                             bank.clean_accounts(true, false, last_full_snapshot_slot);
+                            bank.shrink_ancient_slots(last_full_snapshot_slot);
                             last_cleaned_block_height = bank.block_height();
                         }
                     }
@@ -24,15 +24,19 @@ use {
     crate::{
         accounts_background_service::{DroppedSlotsSender, SendDroppedBankCallback},
         accounts_cache::{AccountsCache, CachedAccount, SlotCache},
-        accounts_hash::{AccountsHash, CalculateHashIntermediate, HashStats, PreviousPass},
+        accounts_hash::{
+            AccountsHash, CalculateHashIntermediate, HashStats, PreviousPass,
+            ZERO_LAMPORT_ACCOUNT_HASH,
+        },
         accounts_index::{
-            AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex, AccountsIndexConfig,
-            AccountsIndexRootsStats, IndexKey, IndexValue, IsCached, RefCount, ScanConfig,
-            ScanResult, SlotList, SlotSlice, ZeroLamport, ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS,
-            ACCOUNTS_INDEX_CONFIG_FOR_TESTING,
+            AccountIndex, AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex,
+            AccountsIndexConfig, AccountsIndexRootsStats, IndexKey, IndexValue, IsCached, RefCount,
+            ScanConfig, ScanResult, SlotList, SlotSlice, ZeroLamport,
+            ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS, ACCOUNTS_INDEX_CONFIG_FOR_TESTING,
         },
         accounts_update_notifier_interface::AccountsUpdateNotifier,
         ancestors::Ancestors,
+        ancient_append_vecs::{get_ancient_append_vec_capacity, is_ancient, is_heavily_dead},
         append_vec::{AppendVec, StoredAccountMeta, StoredMeta, StoredMetaWriteVersion},
         cache_hash_data::CacheHashData,
         contains::Contains,
@@ -134,6 +138,7 @@ pub const ACCOUNTS_DB_CONFIG_FOR_TESTING: AccountsDbConfig = AccountsDbConfig {
     filler_account_count: None,
     hash_calc_num_passes: None,
     write_cache_limit_bytes: None,
+    ancient_append_vecs: false,
 };
 pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig {
     index: Some(ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS),
@@ -141,6 +146,7 @@ pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig
     filler_account_count: None,
     hash_calc_num_passes: None,
     write_cache_limit_bytes: None,
+    ancient_append_vecs: false,
 };
 
 pub type BinnedHashData = Vec<Vec<CalculateHashIntermediate>>;
@@ -158,6 +164,8 @@ pub struct AccountsDbConfig {
     pub filler_account_count: Option<usize>,
     pub hash_calc_num_passes: Option<usize>,
     pub write_cache_limit_bytes: Option<u64>,
+    /// pack the accounts of slots older than an epoch into ancient append vecs
+    pub ancient_append_vecs: bool,
 }
 
 struct FoundStoredAccount<'a> {
@@ -652,6 +660,10 @@ pub struct AccountStorageEntry {
     approx_store_count: AtomicUsize,
 
     alive_bytes: AtomicUsize,
+
+    /// The number of accounts stored, alive or dead, along with the length of the storage it was
+    /// counted at, so it is only recounted once the storage has been written to
+    stored_accounts_count: RwLock<Option<(usize, usize)>>,
 }
 
 impl AccountStorageEntry {
@@ -667,6 +679,7 @@ impl AccountStorageEntry {
             count_and_status: RwLock::new((0, AccountStorageStatus::Available)),
             approx_store_count: AtomicUsize::new(0),
             alive_bytes: AtomicUsize::new(0),
+            stored_accounts_count: RwLock::default(),
         }
     }
 
@@ -676,6 +689,8 @@ impl AccountStorageEntry {
         accounts: AppendVec,
         num_accounts: usize,
     ) -> Self {
+        // loading the storage from its file counted the accounts stored
+        let stored_accounts_count = RwLock::new(Some((accounts.len(), num_accounts)));
         Self {
             id: AtomicUsize::new(id),
             slot: AtomicU64::new(slot),
@@ -683,6 +698,7 @@ impl AccountStorageEntry {
             count_and_status: RwLock::new((0, AccountStorageStatus::Available)),
             approx_store_count: AtomicUsize::new(num_accounts),
             alive_bytes: AtomicUsize::new(0),
+            stored_accounts_count,
         }
     }
 
@@ -701,6 +717,7 @@ impl AccountStorageEntry {
             //  the append_vec has previously been completely full
             //
             self.accounts.reset();
+            self.clear_stored_accounts_count();
             status = AccountStorageStatus::Available;
         }
 
@@ -710,6 +727,7 @@ impl AccountStorageEntry {
     pub fn recycle(&self, slot: Slot, id: usize) {
         let mut count_and_status = self.count_and_status.write().unwrap();
         self.accounts.reset();
+        self.clear_stored_accounts_count();
         *count_and_status = (0, AccountStorageStatus::Available);
         self.slot.store(slot, Ordering::Release);
         self.id.store(id, Ordering::Release);
@@ -737,6 +755,36 @@ impl AccountStorageEntry {
         self.accounts.len() as u64
     }
 
+    /// Bytes written to the storage by accounts that are no longer alive
+    pub fn dead_bytes(&self) -> usize {
+        self.accounts.len().saturating_sub(self.alive_bytes())
+    }
+
+    /// The number of accounts stored, alive or dead.  Storages of rooted slots are not written
+    /// to anymore, and storages loaded from a snapshot were counted when loaded, so this only
+    /// scans a storage the first time it is snapshotted.
+    pub fn stored_accounts_count(&self) -> usize {
+        let len = self.accounts.len();
+        if let Some((counted_len, count)) = *self.stored_accounts_count.read().unwrap() {
+            if counted_len == len {
+                return count;
+            }
+        }
+
+        let count = self.accounts.accounts(0).len();
+        *self.stored_accounts_count.write().unwrap() = Some((len, count));
+        count
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_stored_accounts_count(&self, count: usize) {
+        *self.stored_accounts_count.write().unwrap() = Some((self.accounts.len(), count));
+    }
+
+    fn clear_stored_accounts_count(&self) {
+        *self.stored_accounts_count.write().unwrap() = None;
+    }
+
     pub fn total_bytes(&self) -> u64 {
         self.accounts.capacity()
     }
@@ -745,6 +793,13 @@ impl AccountStorageEntry {
         self.count() > 0
     }
 
+    /// Returns true if this is an ancient append vec with too many alive accounts for
+    /// shrink and clean to be worth rewriting or rescanning it.
+    pub fn is_ancient_and_mostly_alive(&self) -> bool {
+        is_ancient(&self.accounts)
+            && !is_heavily_dead(self.alive_bytes() as u64, self.total_bytes())
+    }
+
     pub fn slot(&self) -> Slot {
         self.slot.load(Ordering::Acquire)
     }
@@ -801,6 +856,7 @@ impl AccountStorageEntry {
             // otherwise, the storage may be in flight with a store()
             //   call
             self.accounts.reset();
+            self.clear_stored_accounts_count();
             status = AccountStorageStatus::Available;
         }
 
@@ -893,7 +949,11 @@ struct RecycleStores {
 pub const EXPIRATION_TTL_SECONDS: u64 = 1800;
 
 impl RecycleStores {
-    fn add_entry(&mut self, new_entry: Arc<AccountStorageEntry>) {
+    fn add_entry(&mut self, new_entry: Arc<AccountStorageEntry>, ancient_append_vecs: bool) {
+        // ancient append vecs are too large to be a good fit for a regular slot, drop them
+        if ancient_append_vecs && is_ancient(&new_entry.accounts) {
+            return;
+        }
         self.total_bytes += new_entry.total_bytes();
         self.entries.push((Instant::now(), new_entry))
     }
@@ -902,10 +962,17 @@ impl RecycleStores {
         self.entries.iter()
     }
 
-    fn add_entries(&mut self, new_entries: Vec<Arc<AccountStorageEntry>>) {
-        self.total_bytes += new_entries.iter().map(|e| e.total_bytes()).sum::<u64>();
+    fn add_entries(
+        &mut self,
+        new_entries: Vec<Arc<AccountStorageEntry>>,
+        ancient_append_vecs: bool,
+    ) {
         let now = Instant::now();
         for new_entry in new_entries {
+            if ancient_append_vecs && is_ancient(&new_entry.accounts) {
+                continue;
+            }
+            self.total_bytes += new_entry.total_bytes();
             self.entries.push((now, new_entry));
         }
     }
@@ -1021,6 +1088,11 @@ pub struct AccountsDb {
 
     pub bank_hashes: RwLock<HashMap<Slot, BankHashInfo>>,
 
+    /// The incremental accounts hash of each slot an incremental snapshot was taken of, along with
+    /// the full snapshot slot it is based on.  Kept apart from `BankHashInfo::snapshot_hash`,
+    /// which is always a full accounts hash.
+    incremental_accounts_hashes: RwLock<HashMap<Slot, (Slot, Hash)>>,
+
     stats: AccountsStats,
 
     clean_accounts_stats: CleanAccountsStats,
@@ -1065,6 +1137,10 @@ pub struct AccountsDb {
     /// for incremental snapshot support.
     zero_lamport_accounts_to_purge_after_full_snapshot: DashSet<(Slot, Pubkey)>,
 
+    /// Slots whose storages had no dead bytes when the snapshot they were loaded from was taken,
+    /// so there is nothing for the startup shrink to reclaim from them
+    pub(crate) slots_without_dead_bytes_at_snapshot: DashSet<Slot>,
+
     /// GeyserPlugin accounts update notifier
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
 
@@ -1076,6 +1152,9 @@ pub struct AccountsDb {
     // lower passes = faster total time, higher dynamic memory usage
     // passes=2 cuts dynamic memory usage in approximately half.
     pub num_hash_scan_passes: Option<usize>,
+
+    /// Pack the accounts of rooted slots older than an epoch into ancient append vecs
+    pub ancient_append_vecs: bool,
 }
 
 #[derive(Debug, Default)]
@@ -1591,6 +1670,7 @@ impl AccountsDb {
             thread_pool_clean: make_min_priority_thread_pool(),
             min_num_stores: num_threads,
             bank_hashes: RwLock::new(bank_hashes),
+            incremental_accounts_hashes: RwLock::default(),
             external_purge_slots_stats: PurgeStats::default(),
             clean_accounts_stats: CleanAccountsStats::default(),
             shrink_stats: ShrinkStats::default(),
@@ -1607,10 +1687,12 @@ impl AccountsDb {
             shrink_ratio: AccountShrinkThreshold::default(),
             dirty_stores: DashMap::default(),
             zero_lamport_accounts_to_purge_after_full_snapshot: DashSet::default(),
+            slots_without_dead_bytes_at_snapshot: DashSet::default(),
             accounts_update_notifier: None,
             filler_account_count: 0,
             filler_account_suffix: None,
             num_hash_scan_passes,
+            ancient_append_vecs: false,
         }
     }
 
@@ -1674,6 +1756,10 @@ impl AccountsDb {
             write_cache_limit_bytes: accounts_db_config
                 .as_ref()
                 .and_then(|x| x.write_cache_limit_bytes),
+            ancient_append_vecs: accounts_db_config
+                .as_ref()
+                .map(|config| config.ancient_append_vecs)
+                .unwrap_or_default(),
             ..Self::default_with_accounts_index(
                 accounts_index,
                 accounts_hash_cache_path,
@@ -2617,11 +2703,14 @@ impl AccountsDb {
         alive_total
     }
 
-    fn do_shrink_slot_stores<'a, I>(&'a self, slot: Slot, stores: I) -> usize
+    /// Returns the latest version of each account found in `stores`, along with the total
+    /// capacity of `stores` and how many of them there were.
+    fn get_unique_accounts_from_storages<'a, I>(
+        stores: I,
+    ) -> (HashMap<Pubkey, FoundStoredAccount<'a>>, u64, usize)
     where
         I: Iterator<Item = &'a Arc<AccountStorageEntry>>,
     {
-        debug!("do_shrink_slot_stores: slot: {}", slot);
         let mut stored_accounts: HashMap<Pubkey, FoundStoredAccount> = HashMap::new();
         let mut original_bytes = 0;
         let mut num_stores = 0;
@@ -2651,6 +2740,16 @@ impl AccountsDb {
             }
             num_stores += 1;
         }
+        (stored_accounts, original_bytes, num_stores)
+    }
+
+    fn do_shrink_slot_stores<'a, I>(&'a self, slot: Slot, stores: I) -> usize
+    where
+        I: Iterator<Item = &'a Arc<AccountStorageEntry>>,
+    {
+        debug!("do_shrink_slot_stores: slot: {}", slot);
+        let (stored_accounts, original_bytes, num_stores) =
+            Self::get_unique_accounts_from_storages(stores);
 
         // sort by pubkey to keep account index lookups close
         let mut stored_accounts = stored_accounts.into_iter().collect::<Vec<_>>();
@@ -2809,7 +2908,7 @@ impl AccountsDb {
 
         let mut drop_storage_entries_elapsed = Measure::start("drop_storage_entries_elapsed");
         if recycle_stores.entry_count() < MAX_RECYCLE_STORES {
-            recycle_stores.add_entries(dead_storages);
+            recycle_stores.add_entries(dead_storages, self.ancient_append_vecs);
             drop(recycle_stores);
         } else {
             self.stats
@@ -2881,6 +2980,12 @@ impl AccountsDb {
         if let Some(stores_lock) = self.storage.get_slot_stores(slot) {
             let stores: Vec<Arc<AccountStorageEntry>> =
                 stores_lock.read().unwrap().values().cloned().collect();
+            if stores
+                .iter()
+                .all(|store| self.is_ancient_and_mostly_alive(store))
+            {
+                return 0;
+            }
             if !Self::is_shrinking_productive(slot, &stores) {
                 return 0;
             }
@@ -3042,7 +3147,16 @@ impl AccountsDb {
         const DIRTY_STORES_CLEANING_THRESHOLD: usize = 10_000;
         const OUTER_CHUNK_SIZE: usize = 2000;
         if is_startup && self.caching_enabled {
-            let slots = self.all_slots_in_storage();
+            let mut slots = self.all_slots_in_storage();
+            if !self.slots_without_dead_bytes_at_snapshot.is_empty() {
+                let num_slots = slots.len();
+                slots.retain(|slot| !self.slots_without_dead_bytes_at_snapshot.contains(slot));
+                info!(
+                    "skipping shrink of {} slots without dead bytes at snapshot",
+                    num_slots - slots.len()
+                );
+                self.slots_without_dead_bytes_at_snapshot.clear();
+            }
             let threads = num_cpus::get();
             let inner_chunk_size = std::cmp::max(OUTER_CHUNK_SIZE / threads, 1);
             slots.chunks(OUTER_CHUNK_SIZE).for_each(|chunk| {
@@ -3069,6 +3183,220 @@ impl AccountsDb {
         }
     }
 
+    /// Packs the alive accounts of rooted slots more than an epoch older than the max root
+    /// into ancient append vecs. Slots newer than `last_full_snapshot_slot` are left alone so
+    /// that incremental snapshots still contain every update since the full snapshot.
+    pub fn shrink_ancient_slots(
+        &self,
+        epoch_schedule: &EpochSchedule,
+        last_full_snapshot_slot: Option<Slot>,
+    ) {
+        if !self.ancient_append_vecs {
+            return;
+        }
+        let max_ancient_slot = match self
+            .accounts_index
+            .max_root()
+            .checked_sub(epoch_schedule.slots_per_epoch)
+        {
+            Some(slot) => slot.min(last_full_snapshot_slot.unwrap_or(Slot::MAX)),
+            None => return,
+        };
+        let mut sorted_slots = self
+            .all_slots_in_storage()
+            .into_iter()
+            .filter(|slot| *slot <= max_ancient_slot && self.accounts_index.is_root(*slot))
+            .collect::<Vec<_>>();
+        sorted_slots.sort_unstable();
+        self.combine_ancient_slots(sorted_slots);
+    }
+
+    /// Moves the alive accounts of each of `sorted_slots`, in ascending slot order, into the
+    /// most recent ancient append vec, re-storing them at that ancient append vec's slot.
+    /// Emptied slots are then removed like any other dead slot. Once an ancient append vec is
+    /// full, the remaining accounts of the slot being packed become the next ancient append vec.
+    pub(crate) fn combine_ancient_slots(&self, sorted_slots: Vec<Slot>) {
+        let mut total_time = Measure::start("combine_ancient_slots");
+        let purge_stats = PurgeStats::default();
+        let mut current_ancient: Option<(Slot, Arc<AccountStorageEntry>)> = None;
+        let mut slots_combined = 0;
+        let mut ancient_stores_created = 0;
+        let mut accounts_moved = 0;
+        let mut bytes_written = 0;
+
+        for slot in sorted_slots {
+            let stores = match self.storage.get_slot_stores(slot) {
+                Some(stores_lock) => stores_lock
+                    .read()
+                    .unwrap()
+                    .values()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                None => continue,
+            };
+            if stores.len() == 1 && is_ancient(&stores[0].accounts) {
+                // already packed, keep appending to it
+                current_ancient = Some((slot, stores[0].clone()));
+                continue;
+            }
+
+            let (stored_accounts, _original_bytes, _num_stores) =
+                Self::get_unique_accounts_from_storages(stores.iter());
+            let mut alive_accounts = stored_accounts
+                .into_iter()
+                .filter(|(pubkey, stored_account)| {
+                    self.accounts_index
+                        .get_account_read_entry(pubkey)
+                        .map(|locked_entry| {
+                            locked_entry.slot_list().iter().any(|(_slot, i)| {
+                                i.store_id == stored_account.store_id
+                                    && i.offset == stored_account.account.offset
+                            })
+                        })
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            // sort by pubkey to keep account index lookups close
+            alive_accounts.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+            if alive_accounts.is_empty() {
+                // nothing in this slot is referenced by the index anymore
+                let mut reclaim_result = ReclaimResult::default();
+                self.process_dead_slots(
+                    &std::iter::once(slot).collect(),
+                    Some(&mut reclaim_result.0),
+                    &purge_stats,
+                );
+                slots_combined += 1;
+                continue;
+            }
+
+            let mut num_moved = 0;
+            if let Some((ancient_slot, ancient_store)) = &current_ancient {
+                let mut remaining_bytes = ancient_store.accounts.remaining_bytes();
+                num_moved = alive_accounts
+                    .iter()
+                    .take_while(|(_pubkey, alive_account)| {
+                        let fits = alive_account.account_size as u64 <= remaining_bytes;
+                        remaining_bytes =
+                            remaining_bytes.saturating_sub(alive_account.account_size as u64);
+                        fits
+                    })
+                    .count();
+                if num_moved > 0 {
+                    let to_move = &alive_accounts[..num_moved];
+                    self.store_ancient_accounts(*ancient_slot, ancient_store, to_move);
+
+                    // the accounts now live in the ancient append vec, so their entries for
+                    // this slot are reclaimed. The slot is dead once all of them have moved.
+                    let mut reclaims = SlotList::new();
+                    for (pubkey, _alive_account) in to_move {
+                        self.accounts_index
+                            .purge_exact(pubkey, &slot, &mut reclaims);
+                    }
+                    let mut reclaim_result = ReclaimResult::default();
+                    self.handle_reclaims(
+                        &reclaims,
+                        Some(slot),
+                        Some(&purge_stats),
+                        Some(&mut reclaim_result),
+                        false,
+                    );
+                    accounts_moved += num_moved;
+                    bytes_written += to_move
+                        .iter()
+                        .map(|(_pubkey, alive_account)| alive_account.account_size as u64)
+                        .sum::<u64>();
+                }
+            }
+
+            let rest = &alive_accounts[num_moved..];
+            if rest.is_empty() {
+                slots_combined += 1;
+            } else {
+                let rest_bytes = rest
+                    .iter()
+                    .map(|(_pubkey, alive_account)| alive_account.account_size as u64)
+                    .sum::<u64>();
+                let ancient_store = self.create_and_insert_store(
+                    slot,
+                    get_ancient_append_vec_capacity(Self::page_align(rest_bytes)),
+                    "ancient_create",
+                );
+                self.store_ancient_accounts(slot, &ancient_store, rest);
+                self.remove_empty_storages(slot);
+                ancient_stores_created += 1;
+                bytes_written += rest_bytes;
+                current_ancient = Some((slot, ancient_store));
+            }
+        }
+        total_time.stop();
+
+        purge_stats.report("shrink_ancient_purge_stats", None);
+        datapoint_info!(
+            "shrink_ancient_stats",
+            ("total_us", total_time.as_us(), i64),
+            ("slots_combined", slots_combined, i64),
+            ("ancient_stores_created", ancient_stores_created, i64),
+            ("accounts_moved", accounts_moved, i64),
+            ("bytes_written", bytes_written, i64),
+        );
+    }
+
+    /// Writes `accounts` into `ancient_store` at `slot`, keeping their hashes and write
+    /// versions. The caller has made sure they fit.
+    fn store_ancient_accounts(
+        &self,
+        slot: Slot,
+        ancient_store: &Arc<AccountStorageEntry>,
+        accounts: &[(Pubkey, FoundStoredAccount)],
+    ) {
+        let mut accounts_to_store = Vec::with_capacity(accounts.len());
+        let mut hashes = Vec::with_capacity(accounts.len());
+        let mut write_versions = Vec::with_capacity(accounts.len());
+        for (pubkey, alive_account) in accounts {
+            accounts_to_store.push((pubkey, &alive_account.account));
+            hashes.push(alive_account.account.hash);
+            write_versions.push(alive_account.account.meta.write_version);
+        }
+
+        let ancient_store = ancient_store.clone();
+        self.store_accounts_frozen(
+            slot,
+            &accounts_to_store,
+            Some(&hashes),
+            Some(Box::new(move |_, _| ancient_store.clone())),
+            Some(Box::new(write_versions.into_iter())),
+        );
+    }
+
+    /// Removes the storages of `slot` that no longer hold any alive accounts, recycling them
+    /// when possible.
+    fn remove_empty_storages(&self, slot: Slot) {
+        let mut dead_storages = vec![];
+        if let Some(slot_stores) = self.storage.get_slot_stores(slot) {
+            slot_stores.write().unwrap().retain(|_key, store| {
+                if store.count() == 0 {
+                    self.dirty_stores
+                        .insert((slot, store.append_vec_id()), store.clone());
+                    dead_storages.push(store.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        let mut recycle_stores = self.recycle_stores.write().unwrap();
+        if recycle_stores.entry_count() < MAX_RECYCLE_STORES {
+            recycle_stores.add_entries(dead_storages, self.ancient_append_vecs);
+        } else {
+            self.stats
+                .dropped_stores
+                .fetch_add(dead_storages.len() as u64, Ordering::Relaxed);
+        }
+    }
+
     pub fn scan_accounts<F, A>(
         &self,
         ancestors: &Ancestors,
@@ -3179,12 +3507,16 @@ impl AccountsDb {
         F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
         A: Default,
     {
-        let key = match &index_key {
-            IndexKey::ProgramId(key) => key,
-            IndexKey::TokenMint(key) => key,
-            IndexKey::TokenOwner(key) => key,
+        let is_key_indexed = match &index_key {
+            IndexKey::ProgramId(key) | IndexKey::TokenMint(key) | IndexKey::TokenOwner(key) => {
+                self.account_indexes.include_key(key)
+            }
+            // Custom indexes ignore the include/exclude keys, but are each enabled separately
+            IndexKey::Custom(custom_index, _) => self
+                .account_indexes
+                .contains(&AccountIndex::Custom(*custom_index)),
         };
-        if !self.account_indexes.include_key(key) {
+        if !is_key_indexed {
             // the requested key was not indexed in the secondary index, so do a normal scan
             let used_index = false;
             let scan_result = self.scan_accounts(ancestors, bank_id, scan_func, config)?;
@@ -3997,7 +4329,7 @@ impl AccountsDb {
                         .fetch_add(dropped_count as u64, Ordering::Relaxed);
                     return recycle_stores_write_elapsed.as_us();
                 }
-                recycle_stores.add_entry(stores.clone());
+                recycle_stores.add_entry(stores.clone(), self.ancient_append_vecs);
                 recycled_count += 1;
             }
         }
@@ -5434,6 +5766,7 @@ impl AccountsDb {
 
     // storages are sorted by slot and have range info.
     // if we know slots_per_epoch, then add all stores older than slots_per_epoch to dirty_stores so clean visits these slots
+    // ancient append vecs are skipped unless most of their accounts are dead
     fn mark_old_slots_as_dirty(&self, storages: &SortedStorages, slots_per_epoch: Option<Slot>) {
         if let Some(slots_per_epoch) = slots_per_epoch {
             let max = storages.range().end;
@@ -5442,10 +5775,13 @@ impl AccountsDb {
             let in_epoch_range_start = max.saturating_sub(sub);
             for slot in storages.range().start..in_epoch_range_start {
                 if let Some(storages) = storages.get(slot) {
-                    storages.iter().for_each(|store| {
-                        self.dirty_stores
-                            .insert((slot, store.append_vec_id()), store.clone());
-                    });
+                    storages
+                        .iter()
+                        .filter(|store| !self.is_ancient_and_mostly_alive(store))
+                        .for_each(|store| {
+                            self.dirty_stores
+                                .insert((slot, store.append_vec_id()), store.clone());
+                        });
                 }
             }
         }
@@ -5586,6 +5922,132 @@ impl AccountsDb {
         (hash, total_lamports)
     }
 
+    /// Calculate the incremental accounts hash of `storages`, which hold the rooted slots after
+    /// a full snapshot slot.  Only the latest version of each account counts.  Unlike the full
+    /// accounts hash, zero-lamport accounts are included, as `ZERO_LAMPORT_ACCOUNT_HASH`, since
+    /// they are what removes an account from the state of the full snapshot.
+    pub fn calculate_incremental_accounts_hash(storages: &SnapshotStorages) -> Hash {
+        let mut scan_time = Measure::start("scan");
+        let latest_accounts = storages
+            .par_iter()
+            .flatten()
+            .map(|storage| {
+                let slot = storage.slot();
+                let mut latest_accounts = HashMap::new();
+                for account in storage.accounts.accounts(0) {
+                    let hash = if account.account_meta.lamports == 0 {
+                        ZERO_LAMPORT_ACCOUNT_HASH
+                    } else {
+                        *account.hash
+                    };
+                    Self::insert_if_latest(
+                        &mut latest_accounts,
+                        account.meta.pubkey,
+                        (slot, account.meta.write_version, hash),
+                    );
+                }
+                latest_accounts
+            })
+            .reduce(HashMap::new, |mut latest_accounts, other| {
+                for (pubkey, version) in other {
+                    Self::insert_if_latest(&mut latest_accounts, pubkey, version);
+                }
+                latest_accounts
+            });
+        scan_time.stop();
+
+        let num_accounts = latest_accounts.len();
+        let mut hash_time = Measure::start("hash");
+        let hash = AccountsHash::accumulate_account_hashes(
+            latest_accounts
+                .into_iter()
+                .map(|(pubkey, (_slot, _write_version, hash))| (pubkey, hash))
+                .collect(),
+        );
+        hash_time.stop();
+        datapoint_info!(
+            "calculate_incremental_accounts_hash",
+            ("accounts_scan", scan_time.as_us(), i64),
+            ("hash", hash_time.as_us(), i64),
+            ("num_accounts", num_accounts, i64),
+        );
+        hash
+    }
+
+    fn insert_if_latest(
+        latest_accounts: &mut HashMap<Pubkey, (Slot, StoredMetaWriteVersion, Hash)>,
+        pubkey: Pubkey,
+        version: (Slot, StoredMetaWriteVersion, Hash),
+    ) {
+        match latest_accounts.entry(pubkey) {
+            Entry::Occupied(mut occupied) => {
+                let (slot, write_version, _hash) = occupied.get();
+                if (version.0, version.1) > (*slot, *write_version) {
+                    occupied.insert(version);
+                }
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(version);
+            }
+        }
+    }
+
+    /// Calculate the incremental accounts hash of the rooted slots after `base_slot`, up to and
+    /// including `slot`, and record it as the incremental accounts hash of `slot`
+    pub fn update_incremental_accounts_hash(&self, slot: Slot, base_slot: Slot) -> Hash {
+        let (storages, _slots) = self.get_snapshot_storages(slot, Some(base_slot), None);
+        let hash = Self::calculate_incremental_accounts_hash(&storages);
+        self.set_incremental_accounts_hash(slot, (base_slot, hash));
+        hash
+    }
+
+    /// The incremental accounts hash recorded for `slot`, and the full snapshot slot it is based on
+    pub fn get_incremental_accounts_hash(&self, slot: Slot) -> Option<(Slot, Hash)> {
+        self.incremental_accounts_hashes
+            .read()
+            .unwrap()
+            .get(&slot)
+            .copied()
+    }
+
+    pub(crate) fn set_incremental_accounts_hash(
+        &self,
+        slot: Slot,
+        base_slot_and_hash: (Slot, Hash),
+    ) {
+        self.incremental_accounts_hashes
+            .write()
+            .unwrap()
+            .insert(slot, base_slot_and_hash);
+    }
+
+    /// Check the incremental accounts hash of `slot`, which was loaded from an incremental
+    /// snapshot based on `base_slot`, against the storages after `base_slot`
+    pub fn verify_incremental_accounts_hash(
+        &self,
+        slot: Slot,
+        base_slot: Slot,
+    ) -> Result<(), BankHashVerificationError> {
+        let expected_hash = match self.get_incremental_accounts_hash(slot) {
+            Some((expected_base_slot, expected_hash)) if expected_base_slot == base_slot => {
+                expected_hash
+            }
+            _ => return Err(BankHashVerificationError::MissingBankHash),
+        };
+
+        let (storages, _slots) = self.get_snapshot_storages(slot, Some(base_slot), None);
+        let calculated_hash = Self::calculate_incremental_accounts_hash(&storages);
+        if calculated_hash == expected_hash {
+            Ok(())
+        } else {
+            warn!(
+                "mismatched incremental accounts hash for slot {} based on slot {}: {} (calculated) != {} (expected)",
+                slot, base_slot, calculated_hash, expected_hash
+            );
+            Err(BankHashVerificationError::MismatchedBankHash)
+        }
+    }
+
     fn scan_snapshot_stores_with_cache(
         cache_hash_data: &CacheHashData,
         storage: &SortedStorages,
@@ -5809,6 +6271,42 @@ impl AccountsDb {
         }
     }
 
+    /// Check the accounts hash recorded for `slot` against the rooted storages up to and
+    /// including `slot`.  Only called at startup, to check the storages of the full snapshot that
+    /// an incremental snapshot was loaded on top of, before cleaning merges them with later slots.
+    pub fn verify_accounts_hash_at_slot(
+        &self,
+        slot: Slot,
+    ) -> Result<(), BankHashVerificationError> {
+        use BankHashVerificationError::*;
+
+        let use_index = false;
+        let check_hash = false; // this will not be supported anymore
+        let is_startup = true;
+        let can_cached_slot_be_unflushed = false;
+        let (calculated_hash, _calculated_lamports) = self.calculate_accounts_hash_helper(
+            use_index,
+            slot,
+            &Ancestors::default(),
+            check_hash,
+            can_cached_slot_be_unflushed,
+            None,
+            is_startup,
+        )?;
+
+        let bank_hashes = self.bank_hashes.read().unwrap();
+        let found_hash_info = bank_hashes.get(&slot).ok_or(MissingBankHash)?;
+        if calculated_hash == found_hash_info.snapshot_hash {
+            Ok(())
+        } else {
+            warn!(
+                "mismatched accounts hash for slot {}: {} (calculated) != {} (expected)",
+                slot, calculated_hash, found_hash_info.snapshot_hash
+            );
+            Err(MismatchedBankHash)
+        }
+    }
+
     /// Perform the scan for pubkeys that were written to in a slot
     fn do_scan_slot_for_dirty_pubkeys(
         &self,
@@ -5996,7 +6494,16 @@ impl AccountsDb {
         true
     }
 
+    /// Storages are only recognized as ancient append vecs by their size, so they are only
+    /// treated as such when ancient append vecs are enabled.
+    fn is_ancient_and_mostly_alive(&self, store: &AccountStorageEntry) -> bool {
+        self.ancient_append_vecs && store.is_ancient_and_mostly_alive()
+    }
+
     fn is_candidate_for_shrink(&self, store: &Arc<AccountStorageEntry>) -> bool {
+        if self.is_ancient_and_mostly_alive(store) {
+            return false;
+        }
         match self.shrink_ratio {
             AccountShrinkThreshold::TotalSpace { shrink_ratio: _ } => {
                 Self::page_align(store.alive_bytes() as u64) < store.total_bytes()
@@ -6040,6 +6547,8 @@ impl AccountsDb {
                     store.slot(), *slot
                 );
                 let count = store.remove_account(account_info.stored_size, reset_accounts);
+                // the slot has dead bytes now, whatever the snapshot it was loaded from recorded
+                self.slots_without_dead_bytes_at_snapshot.remove(slot);
                 if count == 0 {
                     self.dirty_stores
                         .insert((*slot, store.append_vec_id()), store.clone());
@@ -6126,8 +6635,10 @@ impl AccountsDb {
         );
         {
             let mut bank_hashes = self.bank_hashes.write().unwrap();
+            let mut incremental_accounts_hashes = self.incremental_accounts_hashes.write().unwrap();
             for slot in dead_slots_iter {
                 bank_hashes.remove(slot);
+                incremental_accounts_hashes.remove(slot);
             }
         }
         measure.stop();
@@ -6638,6 +7149,55 @@ impl AccountsDb {
         (result, slots)
     }
 
+    /// Call `func` with the latest version of every account stored in `storages`, without an
+    /// accounts index.  Slots are read newest first, so an account is latest the first time its
+    /// pubkey is seen; only the pubkeys seen so far are kept across slots.  Versions superseded by
+    /// a newer store and zero lamport accounts are skipped.  Stops at the first error from `func`.
+    pub fn scan_latest_stored_accounts<F, E>(
+        storages: &SnapshotStorages,
+        mut func: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Slot, &StoredAccountMeta) -> Result<(), E>,
+    {
+        let mut storages = storages.iter().flatten().collect::<Vec<_>>();
+        storages.sort_by_key(|storage| std::cmp::Reverse(storage.slot()));
+
+        let mut seen_pubkeys = HashSet::new();
+        // An account may be stored more than once in a slot, its highest write version wins
+        let mut slot_accounts: HashMap<Pubkey, StoredAccountMeta> = HashMap::new();
+        for (i, storage) in storages.iter().enumerate() {
+            for account in storage.all_accounts() {
+                if seen_pubkeys.contains(&account.meta.pubkey) {
+                    continue;
+                }
+                match slot_accounts.entry(account.meta.pubkey) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(account);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        if entry.get().meta.write_version < account.meta.write_version {
+                            entry.insert(account);
+                        }
+                    }
+                }
+            }
+
+            let is_last_of_slot = storages
+                .get(i + 1)
+                .map_or(true, |next_storage| next_storage.slot() != storage.slot());
+            if is_last_of_slot {
+                for (pubkey, account) in slot_accounts.drain() {
+                    seen_pubkeys.insert(pubkey);
+                    if account.account_meta.lamports != 0 {
+                        func(storage.slot(), &account)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn process_storage_slot<'a>(
         &self,
         storage_maps: &'a [Arc<AccountStorageEntry>],
@@ -7448,6 +8008,7 @@ pub mod tests {
         crate::{
             accounts_hash::MERKLE_FANOUT,
             accounts_index::{tests::*, AccountSecondaryIndexesIncludeExclude, RefCount},
+            ancient_append_vecs::ANCIENT_APPEND_VEC_SIZE,
             append_vec::{test_utils::TempFile, AccountMeta},
             inline_mundis_token,
         },
@@ -10111,6 +10672,117 @@ pub mod tests {
         assert_eq!(1, db.get_snapshot_storages(after_slot, None, None).0.len());
     }
 
+    #[test]
+    fn test_stored_accounts_count() {
+        let db = AccountsDb::new_single_for_tests();
+        let owner = Pubkey::default();
+        let pubkey0 = Pubkey::new_unique();
+        let pubkey1 = Pubkey::new_unique();
+        db.store_uncached(0, &[(&pubkey0, &AccountSharedData::new(1, 0, &owner))]);
+
+        let storage = db.storage.get_slot_storage_entries(0).unwrap().remove(0);
+        assert_eq!(storage.stored_accounts_count(), 1);
+
+        // a count recorded elsewhere is trusted until the storage is written to again
+        storage.set_stored_accounts_count(3);
+        assert_eq!(storage.stored_accounts_count(), 3);
+
+        db.store_uncached(0, &[(&pubkey1, &AccountSharedData::new(1, 0, &owner))]);
+        assert_eq!(storage.stored_accounts_count(), 2);
+        assert_eq!(storage.dead_bytes(), 0);
+    }
+
+    #[test]
+    fn test_calculate_incremental_accounts_hash() {
+        let db = AccountsDb::new_single_for_tests();
+        let owner = Pubkey::default();
+        let pubkey0 = Pubkey::new_unique();
+        let pubkey1 = Pubkey::new_unique();
+        let pubkey2 = Pubkey::new_unique();
+        let account = AccountSharedData::new(1, 0, &owner);
+        let zero_lamport_account = AccountSharedData::new(0, 0, &owner);
+        // slot 0 is the full snapshot slot
+        db.store_uncached(0, &[(&pubkey0, &account), (&pubkey1, &account)]);
+        db.add_root(0);
+        db.store_uncached(1, &[(&pubkey1, &AccountSharedData::new(2, 0, &owner))]);
+        db.add_root(1);
+        db.store_uncached(
+            2,
+            &[(&pubkey1, &account), (&pubkey2, &zero_lamport_account)],
+        );
+        db.add_root(2);
+
+        let (storages, _slots) = db.get_snapshot_storages(2, Some(0), None);
+        let hash = AccountsDb::calculate_incremental_accounts_hash(&storages);
+        // only the latest version of pubkey1 counts, and the zero-lamport pubkey2 is included
+        let expected_hash = AccountsHash::accumulate_account_hashes(vec![
+            (pubkey1, AccountsDb::hash_account(2, &account, &pubkey1)),
+            (pubkey2, ZERO_LAMPORT_ACCOUNT_HASH),
+        ]);
+        assert_eq!(hash, expected_hash);
+
+        assert_eq!(db.update_incremental_accounts_hash(2, 0), hash);
+        assert_eq!(db.get_incremental_accounts_hash(2), Some((0, hash)));
+        // the full accounts hash of the slot is left alone
+        assert_eq!(db.get_accounts_hash(2), Hash::default());
+        assert!(db.verify_incremental_accounts_hash(2, 0).is_ok());
+        assert_matches!(
+            db.verify_incremental_accounts_hash(2, 1),
+            Err(BankHashVerificationError::MissingBankHash)
+        );
+
+        // pubkey0 was only stored in the full snapshot slot
+        let (storages, _slots) = db.get_snapshot_storages(2, None, None);
+        assert_ne!(
+            AccountsDb::calculate_incremental_accounts_hash(&storages),
+            hash
+        );
+    }
+
+    #[test]
+    fn test_scan_latest_stored_accounts() {
+        let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
+
+        let owner = Pubkey::default();
+        let updated = Pubkey::new_unique();
+        let unchanged = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        db.store_uncached(
+            0,
+            &[
+                (&updated, &AccountSharedData::new(1, 0, &owner)),
+                (&unchanged, &AccountSharedData::new(2, 0, &owner)),
+                (&removed, &AccountSharedData::new(3, 0, &owner)),
+            ],
+        );
+        db.add_root(0);
+        db.store_uncached(
+            1,
+            &[
+                (&updated, &AccountSharedData::new(4, 0, &owner)),
+                (&removed, &AccountSharedData::new(0, 0, &owner)),
+            ],
+        );
+        // The latest write of a slot wins
+        db.store_uncached(1, &[(&updated, &AccountSharedData::new(5, 0, &owner))]);
+        db.add_root(1);
+
+        let storages = db.get_snapshot_storages(1, None, None).0;
+        let mut accounts = vec![];
+        AccountsDb::scan_latest_stored_accounts(&storages, |slot, account| -> Result<(), ()> {
+            accounts.push((slot, account.meta.pubkey, account.account_meta.lamports));
+            Ok(())
+        })
+        .unwrap();
+        accounts.sort_unstable();
+        assert_eq!(accounts, vec![(0, unchanged, 2), (1, updated, 5)]);
+
+        assert_eq!(
+            AccountsDb::scan_latest_stored_accounts(&storages, |_slot, _account| Err("stop")),
+            Err("stop")
+        );
+    }
+
     #[test]
     fn test_get_snapshot_storages_only_roots() {
         let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
@@ -12490,8 +13162,8 @@ pub mod tests {
         ));
 
         let mut recycle_stores = RecycleStores::default();
-        recycle_stores.add_entry(entry1);
-        recycle_stores.add_entry(entry2);
+        recycle_stores.add_entry(entry1, false);
+        recycle_stores.add_entry(entry2, false);
         assert_eq!(recycle_stores.entry_count(), 2);
 
         // no expiration for newly added entries
@@ -13124,6 +13796,125 @@ pub mod tests {
         assert!(!accounts.is_candidate_for_shrink(&entry));
     }
 
+    #[test]
+    fn test_is_candidate_for_shrink_ancient() {
+        let mut accounts = AccountsDb::new_single_for_tests();
+        let common_store_path = Path::new("");
+        let entry = Arc::new(AccountStorageEntry::new(
+            common_store_path,
+            0,
+            1,
+            ANCIENT_APPEND_VEC_SIZE,
+        ));
+        assert!(entry.is_ancient_and_mostly_alive());
+
+        // large storages are regular storages unless ancient append vecs are enabled
+        entry
+            .alive_bytes
+            .store(ANCIENT_APPEND_VEC_SIZE as usize / 2, Ordering::Release);
+        assert!(accounts.is_candidate_for_shrink(&entry));
+        let mut recycle_stores = RecycleStores::default();
+        recycle_stores.add_entry(entry.clone(), false);
+        assert_eq!(recycle_stores.entry_count(), 1);
+        accounts.ancient_append_vecs = true;
+
+        // mostly alive ancient append vecs are left alone
+        assert!(entry.is_ancient_and_mostly_alive());
+        assert!(!accounts.is_candidate_for_shrink(&entry));
+        let mut recycle_stores = RecycleStores::default();
+        recycle_stores.add_entry(entry.clone(), true);
+        assert_eq!(recycle_stores.entry_count(), 0);
+
+        // until most of their accounts are dead
+        entry
+            .alive_bytes
+            .store(ANCIENT_APPEND_VEC_SIZE as usize / 10, Ordering::Release);
+        assert!(!entry.is_ancient_and_mostly_alive());
+        assert!(accounts.is_candidate_for_shrink(&entry));
+    }
+
+    #[test]
+    fn test_combine_ancient_slots() {
+        mundis_logger::setup();
+
+        let db = AccountsDb::new_single_for_tests();
+        let owner = *AccountSharedData::default().owner();
+        let pubkeys: Vec<_> = (0..4).map(|_| mundis_sdk::pubkey::new_rand()).collect();
+        let account1 = AccountSharedData::new(1, 0, &owner);
+        let account2 = AccountSharedData::new(2, 0, &owner);
+        let account3 = AccountSharedData::new(3, 0, &owner);
+
+        // slot 3 updates an account from slot 1, slot 4 stays out of the ancient range
+        db.store_uncached(1, &[(&pubkeys[0], &account1), (&pubkeys[1], &account1)]);
+        db.store_uncached(2, &[(&pubkeys[2], &account2)]);
+        db.store_uncached(3, &[(&pubkeys[0], &account3)]);
+        db.store_uncached(4, &[(&pubkeys[3], &account3)]);
+        for slot in 1..=4 {
+            db.get_accounts_delta_hash(slot);
+            db.add_root(slot);
+        }
+        let ancestors = linear_ancestors(5);
+        let (accounts_hash, capitalization) = db.update_accounts_hash_test(4, &ancestors);
+
+        db.combine_ancient_slots(vec![1, 2, 3]);
+
+        // slots 2 and 3 were packed into the ancient append vec at slot 1
+        let mut slots = db.storage.all_slots();
+        slots.sort_unstable();
+        assert_eq!(slots, vec![1, 4]);
+        assert!(!db.accounts_index.is_root(2));
+        assert!(!db.accounts_index.is_root(3));
+        let stores = db.storage.get_slot_storage_entries(1).unwrap();
+        assert_eq!(stores.len(), 1);
+        assert!(is_ancient(&stores[0].accounts));
+
+        for (pubkey, expected_account, expected_slot) in [
+            (pubkeys[0], &account3, 1),
+            (pubkeys[1], &account1, 1),
+            (pubkeys[2], &account2, 1),
+            (pubkeys[3], &account3, 4),
+        ] {
+            let (account, slot) = db.load_without_fixed_root(&ancestors, &pubkey).unwrap();
+            assert!(accounts_equal(&account, expected_account));
+            assert_eq!(slot, expected_slot);
+        }
+
+        // accounts keep their hashes, so packing does not change the accounts hash
+        assert_eq!(
+            db.update_accounts_hash_test(4, &ancestors),
+            (accounts_hash, capitalization)
+        );
+
+        // packing again is a no-op
+        db.combine_ancient_slots(vec![1]);
+        assert_eq!(db.storage.get_slot_storage_entries(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_shrink_ancient_slots_respects_last_full_snapshot_slot() {
+        let mut db = AccountsDb::new_single_for_tests();
+        db.ancient_append_vecs = true;
+        let owner = *AccountSharedData::default().owner();
+        let account = AccountSharedData::new(1, 0, &owner);
+        let epoch_schedule = EpochSchedule::custom(32, 32, false);
+        for slot in 1..=70 {
+            db.store_uncached(slot, &[(&mundis_sdk::pubkey::new_rand(), &account)]);
+            db.get_accounts_delta_hash(slot);
+            db.add_root(slot);
+        }
+
+        // slots older than an epoch, but not past the last full snapshot, are packed
+        db.shrink_ancient_slots(&epoch_schedule, Some(20));
+        let mut slots = db.storage.all_slots();
+        slots.sort_unstable();
+        assert_eq!(slots, std::iter::once(1).chain(21..=70).collect::<Vec<_>>());
+
+        db.shrink_ancient_slots(&epoch_schedule, None);
+        let mut slots = db.storage.all_slots();
+        slots.sort_unstable();
+        assert_eq!(slots, std::iter::once(1).chain(39..=70).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_calculate_storage_count_and_alive_bytes() {
         let accounts = AccountsDb::new_single_for_tests();
@@ -3,13 +3,17 @@ use {
     rayon::prelude::*,
     mundis_measure::measure::Measure,
     mundis_sdk::{
-        hash::{Hash, Hasher},
+        hash::{Hash, Hasher, HASH_BYTES},
         pubkey::Pubkey,
     },
     std::{borrow::Borrow, convert::TryInto, sync::Mutex},
 };
 pub const ZERO_RAW_LAMPORTS_SENTINEL: u64 = std::u64::MAX;
 pub const MERKLE_FANOUT: usize = 16;
+/// The hash an incremental accounts hash uses for a zero-lamport account.  Zero-lamport accounts
+/// are left out of a full accounts hash, but an incremental accounts hash has to include them,
+/// since they remove accounts from the state of the full snapshot it is based on.
+pub const ZERO_LAMPORT_ACCOUNT_HASH: Hash = Hash::new_from_array([0; HASH_BYTES]);
 
 #[derive(Default, Debug)]
 pub struct PreviousPass {
@@ -2,7 +2,7 @@ use {
     crate::{
         accounts_index_storage::AccountsIndexStorage,
         ancestors::Ancestors,
-        bucket_map_holder::{Age, BucketMapHolder},
+        bucket_map_holder::{AccountsIndexResidency, Age, BucketMapHolder},
         contains::Contains,
         in_mem_accounts_index::{InMemAccountsIndex, InsertNewEntryResults},
         inline_mundis_token::{self, GenericTokenAccount},
@@ -19,7 +19,7 @@ use {
         pubkey::Pubkey,
     },
     std::{
-        collections::{btree_map::BTreeMap, HashSet},
+        collections::{btree_map::BTreeMap, HashMap, HashSet},
         fmt::Debug,
         ops::{
             Bound,
@@ -120,11 +120,13 @@ enum ScanTypes<R: RangeBounds<Pubkey>> {
     Indexed(IndexKey),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IndexKey {
     ProgramId(Pubkey),
     TokenMint(Pubkey),
     TokenOwner(Pubkey),
+    /// An index key built by `CustomAccountIndex::key()`
+    Custom(CustomAccountIndex, Pubkey),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -132,6 +134,7 @@ pub enum AccountIndex {
     ProgramId,
     TokenMint,
     TokenOwner,
+    Custom(CustomAccountIndex),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -163,6 +166,12 @@ impl AccountSecondaryIndexes {
     pub fn contains(&self, index: &AccountIndex) -> bool {
         self.indexes.contains(index)
     }
+    pub fn custom_indexes(&self) -> impl Iterator<Item = &CustomAccountIndex> {
+        self.indexes.iter().filter_map(|index| match index {
+            AccountIndex::Custom(custom_index) => Some(custom_index),
+            _ => None,
+        })
+    }
     pub fn include_key(&self, key: &Pubkey) -> bool {
         match &self.keys {
             Some(options) => options.exclude ^ options.keys.contains(key),
@@ -818,6 +827,9 @@ pub struct AccountsIndex<T: IndexValue> {
     program_id_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     anima_token_mint_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     anima_token_owner_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
+    // Created the first time an account owned by the indexed program is stored
+    custom_indexes:
+        RwLock<HashMap<CustomAccountIndex, Arc<SecondaryIndex<DashMapSecondaryIndexEntry>>>>,
     roots_tracker: RwLock<RootsTracker>,
     ongoing_scan_roots: RwLock<BTreeMap<Slot, u64>>,
     // Each scan has some latest slot `S` that is the tip of the fork the scan
@@ -866,6 +878,7 @@ impl<T: IndexValue> AccountsIndex<T> {
             anima_token_owner_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
                 "anima_token_owner_index_stats",
             ),
+            custom_indexes: RwLock::default(),
             roots_tracker: RwLock::<RootsTracker>::default(),
             ongoing_scan_roots: RwLock::<BTreeMap<Slot, u64>>::default(),
             removed_bank_ids: Mutex::<HashSet<BankId>>::default(),
@@ -1094,6 +1107,19 @@ impl<T: IndexValue> AccountsIndex<T> {
                     config,
                 );
             }
+            ScanTypes::Indexed(IndexKey::Custom(custom_index, key)) => {
+                // Nothing has been indexed yet if the index doesn't exist
+                if let Some(index) = self.get_custom_index(&custom_index) {
+                    self.do_scan_secondary_index(
+                        ancestors,
+                        func,
+                        &index,
+                        &key,
+                        Some(max_root),
+                        config,
+                    );
+                }
+            }
         }
 
         {
@@ -1578,6 +1604,55 @@ impl<T: IndexValue> AccountsIndex<T> {
         }
     }
 
+    fn get_custom_index(
+        &self,
+        custom_index: &CustomAccountIndex,
+    ) -> Option<Arc<SecondaryIndex<DashMapSecondaryIndexEntry>>> {
+        self.custom_indexes
+            .read()
+            .unwrap()
+            .get(custom_index)
+            .cloned()
+    }
+
+    fn get_or_create_custom_index(
+        &self,
+        custom_index: &CustomAccountIndex,
+    ) -> Arc<SecondaryIndex<DashMapSecondaryIndexEntry>> {
+        self.get_custom_index(custom_index).unwrap_or_else(|| {
+            self.custom_indexes
+                .write()
+                .unwrap()
+                .entry(*custom_index)
+                .or_insert_with(|| {
+                    Arc::new(SecondaryIndex::<DashMapSecondaryIndexEntry>::new(
+                        "custom_account_index_stats",
+                    ))
+                })
+                .clone()
+        })
+    }
+
+    fn update_custom_secondary_indexes(
+        &self,
+        pubkey: &Pubkey,
+        account_owner: &Pubkey,
+        account_data: &[u8],
+        account_indexes: &AccountSecondaryIndexes,
+    ) {
+        // The include/exclude keys don't apply here: each custom index already names the one
+        // program whose accounts it covers
+        for custom_index in account_indexes.custom_indexes() {
+            if custom_index.program_id != *account_owner {
+                continue;
+            }
+            if let Some(key) = custom_index.key_from_account_data(account_data) {
+                self.get_or_create_custom_index(custom_index)
+                    .insert(&key, pubkey);
+            }
+        }
+    }
+
     pub(crate) fn update_secondary_indexes(
         &self,
         pubkey: &Pubkey,
@@ -1615,6 +1690,8 @@ impl<T: IndexValue> AccountsIndex<T> {
             account_data,
             account_indexes,
         );
+
+        self.update_custom_secondary_indexes(pubkey, account_owner, account_data, account_indexes);
     }
 
     fn get_account_maps_write_lock(&self, pubkey: &Pubkey) -> AccountMapsWriteLock<T> {
@@ -1774,6 +1851,12 @@ impl<T: IndexValue> AccountsIndex<T> {
         if account_indexes.contains(&AccountIndex::TokenMint) {
             self.anima_token_mint_index.remove_by_inner_key(inner_key);
         }
+
+        for custom_index in account_indexes.custom_indexes() {
+            if let Some(index) = self.get_custom_index(custom_index) {
+                index.remove_by_inner_key(inner_key);
+            }
+        }
     }
 
     fn purge_older_root_entries(
@@ -1882,6 +1965,11 @@ impl<T: IndexValue> AccountsIndex<T> {
         self.roots_tracker.read().unwrap().max_root
     }
 
+    /// How much of the index is held in memory versus only on disk
+    pub fn residency(&self) -> AccountsIndexResidency {
+        self.storage.storage.residency()
+    }
+
     /// Remove the slot when the storage for the slot is freed
     /// Accounts no longer reference this slot.
     pub fn clean_dead_slot(&self, slot: Slot, stats: &mut AccountsIndexRootsStats) -> bool {
@@ -4080,6 +4168,107 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_account_index_from_str() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = format!("{}:8:4", program_id)
+            .parse::<CustomAccountIndex>()
+            .unwrap();
+        assert_eq!(
+            custom_index,
+            CustomAccountIndex {
+                program_id,
+                offset: 8,
+                len: 4,
+            }
+        );
+        assert_eq!(custom_index.to_string(), format!("{}:8:4", program_id));
+
+        assert!(format!("{}:8", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!(format!("{}:8:4:1", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!(format!("{}:8:0", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!(format!("{}:8:33", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!("not-a-pubkey:8:4".parse::<CustomAccountIndex>().is_err());
+    }
+
+    #[test]
+    fn test_custom_secondary_index() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex {
+            program_id,
+            offset: 2,
+            len: 4,
+        };
+        let mut secondary_indexes = AccountSecondaryIndexes::default();
+        secondary_indexes
+            .indexes
+            .insert(AccountIndex::Custom(custom_index));
+        let index = AccountsIndex::<bool>::default_for_tests();
+
+        let account_key = Pubkey::new_unique();
+        let account_data = vec![0, 0, 1, 2, 3, 4, 0];
+        let index_key = custom_index.key(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(
+            custom_index.key_from_account_data(&account_data),
+            Some(index_key)
+        );
+        assert_eq!(custom_index.key_from_account_data(&account_data[..5]), None);
+        assert_eq!(custom_index.key(&[1, 2, 3]), None);
+
+        // Wrong program id or account data too short
+        index.update_secondary_indexes(
+            &account_key,
+            &Pubkey::default(),
+            &account_data,
+            &secondary_indexes,
+        );
+        index.update_secondary_indexes(
+            &account_key,
+            &program_id,
+            &account_data[..5],
+            &secondary_indexes,
+        );
+        assert!(index.get_custom_index(&custom_index).is_none());
+
+        // Just right. Inserting the same index multiple times should be ok
+        for _ in 0..2 {
+            index.update_secondary_indexes(
+                &account_key,
+                &program_id,
+                &account_data,
+                &secondary_indexes,
+            );
+            let secondary_index = index.get_custom_index(&custom_index).unwrap();
+            check_secondary_index_mapping_correct(&secondary_index, &[index_key], &account_key);
+        }
+
+        index.upsert(
+            0,
+            &account_key,
+            &program_id,
+            &account_data,
+            &secondary_indexes,
+            true,
+            &mut vec![],
+            UPSERT_PREVIOUS_SLOT_ENTRY_WAS_CACHED_FALSE,
+        );
+        index.slot_list_mut(&account_key, |slot_list| slot_list.clear());
+
+        // Everything should be deleted
+        index.handle_dead_keys(&[&account_key], &secondary_indexes);
+        let secondary_index = index.get_custom_index(&custom_index).unwrap();
+        assert!(secondary_index.index.is_empty());
+        assert!(secondary_index.reverse_index.is_empty());
+    }
+
     fn run_test_secondary_indexes_same_slot_and_forks<
         SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send,
     >(
@@ -0,0 +1,75 @@
+//! Helpers for packing cold accounts into ancient append vecs.
+//!
+//! An ancient append vec is:
+//! 1. the single storage of a rooted slot that is more than an epoch old
+//! 2. the combined alive accounts of many such slots, all re-stored at the oldest one
+//! 3. much larger than the default append vec size, which is how it is recognized, both
+//!    at runtime and after being restored from a snapshot. Large storages are only treated
+//!    as ancient append vecs when `AccountsDb::ancient_append_vecs` is set.
+//!
+//! Accounts keep the hash they were stored with when they are moved into an ancient
+//! append vec, so the accounts hash is unchanged by packing.
+use crate::append_vec::{AppendVec, MAXIMUM_APPEND_VEC_FILE_SIZE};
+
+/// Capacity of a newly created ancient append vec.
+pub const ANCIENT_APPEND_VEC_SIZE: u64 = 128 * 1024 * 1024;
+
+/// An ancient append vec is left alone by shrink and clean until less than this ratio of
+/// its bytes are alive.
+pub const ANCIENT_APPEND_VEC_SHRINK_RATIO: f64 = 0.25;
+
+/// Returns the capacity to create an ancient append vec with so that `bytes` fit in it.
+pub fn get_ancient_append_vec_capacity(bytes: u64) -> u64 {
+    bytes
+        .max(ANCIENT_APPEND_VEC_SIZE)
+        .min(MAXIMUM_APPEND_VEC_FILE_SIZE)
+}
+
+pub fn is_ancient(storage: &AppendVec) -> bool {
+    storage.capacity() >= ANCIENT_APPEND_VEC_SIZE
+}
+
+/// Returns true if an ancient append vec with `alive_bytes` out of `total_bytes` is dead
+/// enough to be worth rewriting.
+pub fn is_heavily_dead(alive_bytes: u64, total_bytes: u64) -> bool {
+    (alive_bytes as f64) < (total_bytes as f64) * ANCIENT_APPEND_VEC_SHRINK_RATIO
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::append_vec::test_utils::get_append_vec_path};
+
+    #[test]
+    fn test_get_ancient_append_vec_capacity() {
+        assert_eq!(get_ancient_append_vec_capacity(0), ANCIENT_APPEND_VEC_SIZE);
+        assert_eq!(
+            get_ancient_append_vec_capacity(ANCIENT_APPEND_VEC_SIZE + 1),
+            ANCIENT_APPEND_VEC_SIZE + 1
+        );
+        assert_eq!(
+            get_ancient_append_vec_capacity(u64::MAX),
+            MAXIMUM_APPEND_VEC_FILE_SIZE
+        );
+    }
+
+    #[test]
+    fn test_is_ancient() {
+        for (size, expected_ancient) in [
+            (1024 * 1024, false),
+            (ANCIENT_APPEND_VEC_SIZE - 1, false),
+            (ANCIENT_APPEND_VEC_SIZE, true),
+        ] {
+            let path = get_append_vec_path("test_is_ancient");
+            let av = AppendVec::new(&path.path, true, size as usize);
+            assert_eq!(is_ancient(&av), expected_ancient, "size: {}", size);
+        }
+    }
+
+    #[test]
+    fn test_is_heavily_dead() {
+        assert!(is_heavily_dead(0, 100));
+        assert!(is_heavily_dead(24, 100));
+        assert!(!is_heavily_dead(25, 100));
+        assert!(!is_heavily_dead(100, 100));
+    }
+}
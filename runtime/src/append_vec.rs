@@ -37,7 +37,7 @@ macro_rules! u64_align {
     };
 }
 
-const MAXIMUM_APPEND_VEC_FILE_SIZE: u64 = 16 * 1024 * 1024 * 1024; // 16 GiB
+pub(crate) const MAXIMUM_APPEND_VEC_FILE_SIZE: u64 = 16 * 1024 * 1024 * 1024; // 16 GiB
 
 pub type StoredMetaWriteVersion = u64;
 
@@ -301,6 +301,13 @@ impl AppendVec {
         self.file_size
     }
 
+    /// Number of bytes that can still be appended, accounting for the alignment of the next
+    /// append
+    pub fn remaining_bytes(&self) -> u64 {
+        self.capacity()
+            .saturating_sub(u64_align!(self.len()) as u64)
+    }
+
     pub fn file_name(slot: Slot, id: usize) -> String {
         format!("{}.{}", slot, id)
     }
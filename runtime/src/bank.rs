@@ -138,7 +138,7 @@ use {
         timing::years_as_slots,
         transaction::{
             AddressLookupError, Result, SanitizedTransaction, Transaction, TransactionError,
-            TransactionVerificationMode, VersionedTransaction,
+            TransactionReturnData, TransactionVerificationMode, VersionedTransaction,
         },
     },
     mundis_stake_program::stake_state::{
@@ -603,6 +603,7 @@ pub struct TransactionExecutionDetails {
     pub log_messages: Option<Vec<String>>,
     pub inner_instructions: Option<Vec<Vec<CompiledInstruction>>>,
     pub durable_nonce_fee: Option<DurableNonceFee>,
+    pub return_data: Option<TransactionReturnData>,
     pub executed_units: u64,
 }
 
@@ -699,6 +700,10 @@ pub struct TransactionSimulationResult {
     pub logs: TransactionLogMessages,
     pub post_simulation_accounts: Vec<(Pubkey, AccountSharedData)>,
     pub units_consumed: u64,
+    pub return_data: Option<TransactionReturnData>,
+    pub inner_instructions: Option<InnerInstructionsList>,
+    pub loaded_accounts_data_size: u64,
+    pub loaded_accounts_data_size_limit: u64,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -3530,6 +3535,37 @@ impl Bank {
         TransactionBatch::new(lock_results, self, Cow::Borrowed(transactions))
     }
 
+    /// Prepare a transaction batch that locks the accounts of all the transactions of a bundle at
+    /// once. The lock results are either all `Ok` or all the same error.
+    pub fn prepare_sanitized_bundle_batch<'a, 'b>(
+        &'a self,
+        txs: &'b [SanitizedTransaction],
+    ) -> TransactionBatch<'a, 'b> {
+        let lock_result = self
+            .rc
+            .accounts
+            .lock_bundle_accounts(txs.iter(), &self.feature_set);
+        let mut batch = TransactionBatch::new(
+            vec![lock_result.clone(); txs.len()],
+            self,
+            Cow::Borrowed(txs),
+        );
+        batch.needs_unlock = lock_result.is_ok();
+        batch.is_bundle = true;
+        batch
+    }
+
+    /// Prepare a transaction batch without locking accounts, for transactions whose accounts are
+    /// already locked by a bundle batch.
+    pub fn prepare_unlocked_batch<'a, 'b>(
+        &'a self,
+        txs: &'b [SanitizedTransaction],
+    ) -> TransactionBatch<'a, 'b> {
+        let mut batch = TransactionBatch::new(vec![Ok(()); txs.len()], self, Cow::Borrowed(txs));
+        batch.needs_unlock = false;
+        batch
+    }
+
     /// Prepare a transaction batch without locking accounts for transaction simulation.
     pub(crate) fn prepare_simulation_batch<'a>(
         &'a self,
@@ -3546,10 +3582,11 @@ impl Bank {
     pub fn simulate_transaction(
         &self,
         transaction: SanitizedTransaction,
+        enable_cpi_recording: bool,
     ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
-        self.simulate_transaction_unchecked(transaction)
+        self.simulate_transaction_unchecked(transaction, enable_cpi_recording)
     }
 
     /// Run transactions against a bank without committing the results; does not check if the bank
@@ -3557,13 +3594,57 @@ impl Bank {
     pub fn simulate_transaction_unchecked(
         &self,
         transaction: SanitizedTransaction,
+        enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_overrides(
+            transaction,
+            enable_cpi_recording,
+            &AccountOverrides::default(),
+        )
+    }
+
+    /// Run a bundle of transactions sequentially against a frozen bank without committing the
+    /// results. Each transaction observes the accounts written by the successful transactions
+    /// simulated before it.
+    pub fn simulate_transactions(
+        &self,
+        transactions: Vec<SanitizedTransaction>,
+        enable_cpi_recording: bool,
+    ) -> Vec<TransactionSimulationResult> {
+        assert!(self.is_frozen(), "simulation bank must be frozen");
+
+        let mut account_overrides = AccountOverrides::default();
+        transactions
+            .into_iter()
+            .map(|transaction| {
+                let simulation_result = self.simulate_transaction_with_overrides(
+                    transaction.clone(),
+                    enable_cpi_recording,
+                    &account_overrides,
+                );
+                account_overrides.apply_simulation_result(&transaction, &simulation_result);
+                simulation_result
+            })
+            .collect()
+    }
+
+    /// Run a transaction against a bank without committing the results, loading any account
+    /// found in `account_overrides` from there instead of from the bank
+    pub fn simulate_transaction_with_overrides(
+        &self,
+        transaction: SanitizedTransaction,
+        enable_cpi_recording: bool,
+        account_overrides: &AccountOverrides,
     ) -> TransactionSimulationResult {
         let account_keys = transaction
             .message()
             .account_keys_iter()
             .collect::<Vec<_>>();
         let number_of_accounts = account_keys.len();
-        let account_overrides = self.get_account_overrides_for_simulation(&account_keys);
+        let account_overrides =
+            self.get_account_overrides_for_simulation(&account_keys, account_overrides);
+        let loaded_accounts_data_size_limit =
+            Accounts::loaded_accounts_data_size_limit(transaction.message()) as u64;
         let batch = self.prepare_simulation_batch(transaction);
         let mut timings = ExecuteTimings::default();
 
@@ -3577,24 +3658,27 @@ impl Bank {
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            enable_cpi_recording,
             true,
             &mut timings,
             Some(&account_overrides),
         );
 
-        let post_simulation_accounts = loaded_transactions
+        let (post_simulation_accounts, loaded_accounts_data_size) = loaded_transactions
             .into_iter()
             .next()
             .unwrap()
             .0
             .ok()
             .map(|loaded_transaction| {
-                loaded_transaction
-                    .accounts
-                    .into_iter()
-                    .take(number_of_accounts)
-                    .collect::<Vec<_>>()
+                (
+                    loaded_transaction
+                        .accounts
+                        .into_iter()
+                        .take(number_of_accounts)
+                        .collect::<Vec<_>>(),
+                    loaded_transaction.loaded_accounts_data_size as u64,
+                )
             })
             .unwrap_or_default();
 
@@ -3610,22 +3694,37 @@ impl Bank {
 
         let execution_result = execution_results.pop().unwrap();
         let flattened_result = execution_result.flattened_result();
-        let logs = match execution_result {
-            TransactionExecutionResult::Executed { details, .. } => details.log_messages,
-            TransactionExecutionResult::NotExecuted(_) => None,
-        }
-        .unwrap_or_default();
+        let (logs, return_data, inner_instructions) = match execution_result {
+            TransactionExecutionResult::Executed { details, .. } => (
+                details.log_messages,
+                details.return_data,
+                details.inner_instructions,
+            ),
+            TransactionExecutionResult::NotExecuted(_) => (None, None, None),
+        };
+        let logs = logs.unwrap_or_default();
 
         TransactionSimulationResult {
             result: flattened_result,
             logs,
             post_simulation_accounts,
             units_consumed,
+            return_data,
+            inner_instructions,
+            loaded_accounts_data_size,
+            loaded_accounts_data_size_limit,
         }
     }
 
-    fn get_account_overrides_for_simulation(&self, account_keys: &[&Pubkey]) -> AccountOverrides {
+    fn get_account_overrides_for_simulation(
+        &self,
+        account_keys: &[&Pubkey],
+        base_overrides: &AccountOverrides,
+    ) -> AccountOverrides {
         let mut account_overrides = AccountOverrides::default();
+        for pubkey in account_keys {
+            account_overrides.set_account(pubkey, base_overrides.get(pubkey).cloned());
+        }
         let slot_history_id = sysvar::slot_history::id();
         if account_keys
             .iter()
@@ -3651,9 +3750,15 @@ impl Bank {
     pub fn unlock_accounts(&self, batch: &mut TransactionBatch) {
         if batch.needs_unlock {
             batch.needs_unlock = false;
-            self.rc
-                .accounts
-                .unlock_accounts(batch.sanitized_transactions().iter(), batch.lock_results())
+            if batch.is_bundle {
+                self.rc
+                    .accounts
+                    .unlock_bundle_accounts(batch.sanitized_transactions().iter())
+            } else {
+                self.rc
+                    .accounts
+                    .unlock_accounts(batch.sanitized_transactions().iter(), batch.lock_results())
+            }
         }
     }
 
@@ -3957,6 +4062,7 @@ impl Bank {
         let (blockhash, lamports_per_signature) = self.last_blockhash_and_lamports_per_signature();
 
         let mut executed_units = 0u64;
+        let mut return_data = None;
 
         let mut process_message_time = Measure::start("process_message_time");
         let process_result = MessageProcessor::process_message(
@@ -3976,6 +4082,7 @@ impl Bank {
             lamports_per_signature,
             self.load_accounts_data_len(),
             &mut executed_units,
+            &mut return_data,
         );
         process_message_time.stop();
 
@@ -4008,7 +4115,8 @@ impl Bank {
             })
             .map_err(|err| {
                 match err {
-                    TransactionError::InvalidRentPayingAccount => {
+                    TransactionError::InvalidRentPayingAccount
+                    | TransactionError::InsufficientFundsForRent { .. } => {
                         error_counters.invalid_rent_paying_account += 1;
                     }
                     _ => {
@@ -4044,12 +4152,60 @@ impl Bank {
                 log_messages,
                 inner_instructions,
                 durable_nonce_fee,
+                return_data,
                 executed_units,
             },
             executors,
         }
     }
 
+    /// Load and execute the transactions of a bundle one at a time. Each transaction observes the
+    /// accounts written by the transactions before it, although nothing is committed. Execution
+    /// stops at the first transaction that does not execute successfully, whose output is then
+    /// the last one returned.
+    pub fn load_and_execute_bundle(
+        &self,
+        batch: &TransactionBatch,
+        max_age: usize,
+        enable_cpi_recording: bool,
+        enable_log_recording: bool,
+        timings: &mut ExecuteTimings,
+    ) -> Vec<LoadAndExecuteTransactionsOutput> {
+        let mut account_overrides = AccountOverrides::default();
+        let mut outputs = Vec::with_capacity(batch.sanitized_transactions().len());
+        for (tx, lock_result) in batch
+            .sanitized_transactions()
+            .iter()
+            .zip(batch.lock_results())
+        {
+            let mut tx_batch = TransactionBatch::new(
+                vec![lock_result.clone()],
+                self,
+                Cow::Borrowed(std::slice::from_ref(tx)),
+            );
+            tx_batch.needs_unlock = false;
+            let output = self.load_and_execute_transactions(
+                &tx_batch,
+                max_age,
+                enable_cpi_recording,
+                enable_log_recording,
+                timings,
+                Some(&account_overrides),
+            );
+            let executed_successfully = output.execution_results[0].was_executed_successfully();
+            if let (Ok(loaded_transaction), _) = &output.loaded_transactions[0] {
+                if executed_successfully {
+                    account_overrides.apply_transaction_accounts(tx, &loaded_transaction.accounts);
+                }
+            }
+            outputs.push(output);
+            if !executed_successfully {
+                break;
+            }
+        }
+        outputs
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn load_and_execute_transactions(
         &self,
@@ -4076,7 +4232,8 @@ impl Bank {
                 }
                 Err(TransactionError::WouldExceedMaxBlockCostLimit)
                 | Err(TransactionError::WouldExceedMaxVoteCostLimit)
-                | Err(TransactionError::WouldExceedMaxAccountCostLimit) => Some(index),
+                | Err(TransactionError::WouldExceedMaxAccountCostLimit)
+                | Err(TransactionError::InsufficientPriorityFee) => Some(index),
                 Err(_) => None,
                 Ok(_) => None,
             })
@@ -4800,6 +4957,7 @@ impl Bank {
 
         // parallelize?
         let rent_for_sysvars = self.rent_for_sysvars();
+        let rent_exempt_only = self.rent_exempt_only();
         let mut rent_debits = RentDebits::default();
         let mut total_collected = CollectedInfo::default();
         for (pubkey, mut account) in accounts {
@@ -4807,6 +4965,7 @@ impl Bank {
                 &pubkey,
                 &mut account,
                 rent_for_sysvars,
+                rent_exempt_only,
                 self.rc.accounts.accounts_db.filler_account_suffix.as_ref(),
             );
             total_collected += collected;
@@ -5937,6 +6096,15 @@ impl Bank {
         self.rc.accounts.accounts_db.get_accounts_hash(self.slot)
     }
 
+    /// The incremental accounts hash of this bank, and the full snapshot slot it is based on, if
+    /// an incremental snapshot was taken of it or it was loaded from one
+    pub fn get_incremental_accounts_hash(&self) -> Option<(Slot, Hash)> {
+        self.rc
+            .accounts
+            .accounts_db
+            .get_incremental_accounts_hash(self.slot)
+    }
+
     pub fn get_thread_pool(&self) -> &ThreadPool {
         &self.rc.accounts.accounts_db.thread_pool_clean
     }
@@ -6003,14 +6171,56 @@ impl Bank {
         self.update_accounts_hash_with_index_option(true, false, None, false)
     }
 
+    /// Calculate the hash of the accounts modified since the full snapshot at `base_slot`, and
+    /// record it as the accounts hash of an incremental snapshot of this bank.  Much cheaper than
+    /// `update_accounts_hash()`, since only the storages after `base_slot` are scanned.
+    pub fn update_incremental_accounts_hash(&self, base_slot: Slot) -> Hash {
+        self.rc
+            .accounts
+            .accounts_db
+            .update_incremental_accounts_hash(self.slot(), base_slot)
+    }
+
+    /// Check the accounts of a bank loaded from an incremental snapshot based on `base_slot`: the
+    /// storages of the full snapshot against its accounts hash, then the storages of the
+    /// incremental snapshot against its incremental accounts hash.  Both have to be checked
+    /// before cleaning, which merges the storages of the two snapshots.
+    #[must_use]
+    fn verify_incremental_snapshot_accounts_hashes(&self, base_slot: Slot) -> bool {
+        let accounts_db = &self.rc.accounts.accounts_db;
+        if let Err(err) = accounts_db.verify_accounts_hash_at_slot(base_slot) {
+            warn!(
+                "verify_accounts_hash_at_slot failed for full snapshot slot {}: {:?}",
+                base_slot, err
+            );
+            return false;
+        }
+        if let Err(err) = accounts_db.verify_incremental_accounts_hash(self.slot(), base_slot) {
+            warn!("verify_incremental_accounts_hash failed: {:?}", err);
+            return false;
+        }
+        true
+    }
+
     /// A snapshot bank should be purged of 0 lamport accounts which are not part of the hash
     /// calculation and could shield other real accounts.
+    /// A bank loaded from an incremental snapshot has no accounts hash of its combined state, so
+    /// the full snapshot and the incremental snapshot are checked against their own hashes, and
+    /// the combined state against the capitalization.
     pub fn verify_snapshot_bank(
         &self,
         test_hash_calculation: bool,
         accounts_db_skip_shrink: bool,
         last_full_snapshot_slot: Option<Slot>,
+        incremental_snapshot_base_slot: Option<Slot>,
     ) -> bool {
+        let mut verify_snapshot_hashes_time = Measure::start("verify_snapshot_hashes");
+        let verify_snapshot_hashes = incremental_snapshot_base_slot.map_or(true, |base_slot| {
+            info!("verify_incremental_snapshot_accounts_hashes..");
+            self.verify_incremental_snapshot_accounts_hashes(base_slot)
+        });
+        verify_snapshot_hashes_time.stop();
+
         info!("cleaning..");
         let mut clean_time = Measure::start("clean");
         if self.slot() > 0 {
@@ -6032,7 +6242,11 @@ impl Bank {
 
         info!("verify_bank_hash..");
         let mut verify_time = Measure::start("verify_bank_hash");
-        let mut verify = self.verify_bank_hash(test_hash_calculation);
+        let mut verify = verify_snapshot_hashes
+            && match incremental_snapshot_base_slot {
+                None => self.verify_bank_hash(test_hash_calculation),
+                Some(_) => self.calculate_and_verify_capitalization(test_hash_calculation),
+            };
         verify_time.stop();
         self.rc
             .accounts
@@ -6048,6 +6262,11 @@ impl Bank {
 
         datapoint_info!(
             "verify_snapshot_bank",
+            (
+                "verify_snapshot_hashes_us",
+                verify_snapshot_hashes_time.as_us(),
+                i64
+            ),
             ("clean_us", clean_time.as_us(), i64),
             ("shrink_all_slots_us", shrink_all_slots_time.as_us(), i64),
             ("verify_bank_hash_us", verify_time.as_us(), i64),
@@ -6352,6 +6571,13 @@ impl Bank {
         self.rc.accounts.accounts_db.shrink_candidate_slots()
     }
 
+    pub fn shrink_ancient_slots(&self, last_full_snapshot_slot: Option<Slot>) {
+        self.rc
+            .accounts
+            .accounts_db
+            .shrink_ancient_slots(self.epoch_schedule(), last_full_snapshot_slot)
+    }
+
     pub fn stake_program_advance_activating_credits_observed(&self) -> bool {
         self.feature_set
             .is_active(&feature_set::stake_program_advance_activating_credits_observed::id())
@@ -6678,6 +6904,11 @@ impl Bank {
             .is_active(&feature_set::rent_for_sysvars::id())
     }
 
+    fn rent_exempt_only(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::rent_exempt_only_accounts::id())
+    }
+
     /// Get all the accounts for this bank and calculate stats
     pub fn get_total_accounts_stats(&self) -> ScanResult<TotalAccountsStats> {
         let accounts = self.get_all_accounts_with_modified_slots()?;
@@ -6858,6 +7089,7 @@ pub(crate) mod tests {
                 log_messages: None,
                 inner_instructions: None,
                 durable_nonce_fee: nonce.map(DurableNonceFee::from),
+                return_data: None,
                 executed_units: 0u64,
             },
             executors: Rc::new(RefCell::new(Executors::default())),
@@ -7551,6 +7783,10 @@ pub(crate) mod tests {
             .accounts
             .remove(&feature_set::require_rent_exempt_accounts::id())
             .unwrap();
+        genesis_config
+            .accounts
+            .remove(&feature_set::rent_exempt_only_accounts::id())
+            .unwrap();
 
         genesis_config.epoch_schedule = EpochSchedule::custom(
             MINIMUM_SLOTS_PER_EPOCH,
@@ -8706,6 +8942,10 @@ pub(crate) mod tests {
 
         let (mut genesis_config, _mint_keypair) = create_genesis_config(1);
         activate_all_features(&mut genesis_config);
+        // Exercise rent collection as it was before rent-exempt-only accounts
+        genesis_config
+            .accounts
+            .remove(&feature_set::rent_exempt_only_accounts::id());
 
         let zero_lamport_pubkey = mundis_sdk::pubkey::new_rand();
         let rent_due_pubkey = mundis_sdk::pubkey::new_rand();
@@ -8786,6 +9026,64 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_rent_eager_rewrite_in_partition_when_rent_exempt_only() {
+        mundis_logger::setup();
+
+        let (mut genesis_config, _mint_keypair) = create_genesis_config(1);
+        activate_all_features(&mut genesis_config);
+
+        let rent_paying_pubkey = mundis_sdk::pubkey::new_rand();
+        let rent_exempt_pubkey = mundis_sdk::pubkey::new_rand();
+
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let little_lamports = 1234;
+        let large_lamports = 123_456_789;
+
+        bank.store_account(
+            &rent_paying_pubkey,
+            &AccountSharedData::new(little_lamports, 0, &Pubkey::default()),
+        );
+        bank.store_account(
+            &rent_exempt_pubkey,
+            &AccountSharedData::new(large_lamports, 0, &Pubkey::default()),
+        );
+
+        let genesis_slot = 0;
+        let some_slot = 1000;
+        let ancestors = vec![(some_slot, 0), (0, 1)].into_iter().collect();
+
+        let bank = Arc::new(Bank::new_from_parent(&bank, &Pubkey::default(), some_slot));
+        let capitalization = bank.capitalization();
+
+        bank.collect_rent_in_partition((0, 0, 1)); // all range
+
+        // Nothing is collected, not even from the legacy rent-paying account...
+        assert_eq!(bank.collected_rent.load(Relaxed), 0);
+        assert_eq!(bank.capitalization(), capitalization);
+        assert_eq!(
+            bank.get_account(&rent_paying_pubkey).unwrap().lamports(),
+            little_lamports
+        );
+        // ...but every account is still rewritten with its rent epoch advanced as before
+        assert_eq!(
+            bank.get_account(&rent_paying_pubkey).unwrap().rent_epoch(),
+            6
+        );
+        assert_eq!(
+            bank.get_account(&rent_exempt_pubkey).unwrap().rent_epoch(),
+            5
+        );
+        assert_eq!(
+            bank.slots_by_pubkey(&rent_paying_pubkey, &ancestors),
+            vec![genesis_slot, some_slot]
+        );
+        assert_eq!(
+            bank.slots_by_pubkey(&rent_exempt_pubkey, &ancestors),
+            vec![genesis_slot, some_slot]
+        );
+    }
+
     #[test]
     fn test_rent_eager_collect_rent_zero_lamport_deterministic() {
         mundis_logger::setup();
@@ -9201,6 +9499,51 @@ pub(crate) mod tests {
         assert_eq!(bank.get_signature_status(&t2.signatures[0]), None);
     }
 
+    #[test]
+    fn test_simulate_transactions_sequentially() {
+        let (genesis_config, mint_keypair) = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        bank.freeze();
+        let keypair1 = Keypair::new();
+        let key2 = mundis_sdk::pubkey::new_rand();
+        let blockhash = bank.last_blockhash();
+
+        // keypair1 is only funded by the first transaction of the bundle
+        let transactions: Vec<_> = vec![
+            system_transaction::transfer(&mint_keypair, &keypair1.pubkey(), 500, blockhash),
+            system_transaction::transfer(&keypair1, &key2, 200, blockhash),
+        ]
+        .into_iter()
+        .map(SanitizedTransaction::from_transaction_for_tests)
+        .collect();
+
+        let post_balance = |result: &TransactionSimulationResult, pubkey: &Pubkey| {
+            result
+                .post_simulation_accounts
+                .iter()
+                .find(|(key, _)| key == pubkey)
+                .map(|(_, account)| account.lamports())
+                .unwrap()
+        };
+
+        let results = bank.simulate_transactions(transactions.clone(), true);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result, Ok(()));
+        assert_eq!(results[0].inner_instructions, Some(vec![vec![]]));
+        assert_eq!(results[1].result, Ok(()));
+        assert_eq!(post_balance(&results[1], &keypair1.pubkey()), 300);
+        assert_eq!(post_balance(&results[1], &key2), 200);
+
+        // On its own the second transaction has no funded fee payer
+        let result = bank.simulate_transaction(transactions[1].clone(), false);
+        assert!(result.result.is_err());
+        assert_eq!(result.inner_instructions, None);
+
+        // Simulation never commits
+        assert_eq!(bank.get_balance(&keypair1.pubkey()), 0);
+        assert_eq!(bank.get_balance(&key2), 0);
+    }
+
     #[test]
     fn test_one_tx_two_out_atomic_fail() {
         let (genesis_config, mint_keypair) = create_genesis_config(1);
@@ -10272,11 +10615,56 @@ pub(crate) mod tests {
         bank.transfer(1_000, &mint_keypair, &pubkey).unwrap();
         bank.freeze();
         bank.update_accounts_hash();
-        assert!(bank.verify_snapshot_bank(true, false, None));
+        assert!(bank.verify_snapshot_bank(true, false, None, None));
 
         // tamper the bank after freeze!
         bank.increment_signature_count(1);
-        assert!(!bank.verify_snapshot_bank(true, false, None));
+        assert!(!bank.verify_snapshot_bank(true, false, None, None));
+    }
+
+    #[test]
+    fn test_verify_snapshot_bank_incremental() {
+        mundis_logger::setup();
+        let pubkey = mundis_sdk::pubkey::new_rand();
+        let (genesis_config, mint_keypair) = create_genesis_config(2_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        bank0.transfer(1_000, &mint_keypair, &pubkey).unwrap();
+        bank0.freeze();
+        bank0.squash();
+        bank0.force_flush_accounts_cache();
+        bank0.update_accounts_hash();
+
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank1.transfer(500, &mint_keypair, &pubkey).unwrap();
+        bank1.freeze();
+        bank1.squash();
+        bank1.force_flush_accounts_cache();
+        let incremental_accounts_hash = bank1.update_incremental_accounts_hash(bank0.slot());
+        let accounts_db = &bank1.rc.accounts.accounts_db;
+        assert!(bank1.verify_incremental_snapshot_accounts_hashes(bank0.slot()));
+
+        // a mismatching incremental snapshot is rejected
+        accounts_db.set_incremental_accounts_hash(bank1.slot(), (bank0.slot(), Hash::new_unique()));
+        assert!(!bank1.verify_incremental_snapshot_accounts_hashes(bank0.slot()));
+        accounts_db
+            .set_incremental_accounts_hash(bank1.slot(), (bank0.slot(), incremental_accounts_hash));
+
+        // so is a mismatching full snapshot, even with a matching incremental snapshot
+        let full_accounts_hash = bank0.get_accounts_hash();
+        let set_full_accounts_hash = |hash| {
+            accounts_db
+                .bank_hashes
+                .write()
+                .unwrap()
+                .get_mut(&bank0.slot())
+                .unwrap()
+                .snapshot_hash = hash;
+        };
+        set_full_accounts_hash(Hash::new_unique());
+        assert!(!bank1.verify_incremental_snapshot_accounts_hashes(bank0.slot()));
+        set_full_accounts_hash(full_accounts_hash);
+
+        assert!(bank1.verify_snapshot_bank(true, false, Some(bank0.slot()), Some(bank0.slot())));
     }
 
     // Test that two bank forks with the same accounts should not hash to the same value.
@@ -16755,8 +17143,12 @@ pub(crate) mod tests {
                 INITIAL_RENT_EPOCH + 1,
             ),
         );
-        // Activate features, including require_rent_exempt_accounts
+        // Activate features, including require_rent_exempt_accounts, but not yet
+        // rent_exempt_only_accounts under which legacy RentPaying accounts may not stay so
         activate_all_features(&mut genesis_config);
+        genesis_config
+            .accounts
+            .remove(&feature_set::rent_exempt_only_accounts::id());
 
         let mut bank = Bank::new_for_tests(&genesis_config);
         bank.add_builtin(
@@ -16832,6 +17224,89 @@ pub(crate) mod tests {
         assert!(bank.get_account(&rent_exempt_account.pubkey()).is_none());
     }
 
+    #[test]
+    fn test_rent_exempt_only_state_changes_existing_accounts() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_leader(mdis_to_lamports(100.), &Pubkey::new_unique(), 42);
+        genesis_config.rent = Rent::default();
+
+        let mock_program_id = Pubkey::new_unique();
+        let account_data_size = 100;
+        let rent_exempt_minimum = genesis_config.rent.minimum_balance(account_data_size);
+
+        // Create legacy rent-paying account
+        let rent_paying_account = Keypair::new();
+        genesis_config.accounts.insert(
+            rent_paying_account.pubkey(),
+            Account::new_rent_epoch(
+                rent_exempt_minimum - 1,
+                account_data_size,
+                &mock_program_id,
+                INITIAL_RENT_EPOCH + 1,
+            ),
+        );
+        // Activate features, including rent_exempt_only_accounts
+        activate_all_features(&mut genesis_config);
+
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        bank.add_builtin(
+            "mock_program",
+            &mock_program_id,
+            mock_transfer_process_instruction,
+        );
+        let recent_blockhash = bank.last_blockhash();
+
+        // Legacy RentPaying account can no longer stay RentPaying...
+        let tx = create_mock_transfer(
+            &mint_keypair,        // payer
+            &rent_paying_account, // from
+            &mint_keypair,        // to
+            1,
+            mock_program_id,
+            recent_blockhash,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InsufficientFundsForRent { account_index: 1 })
+        );
+        assert_eq!(
+            bank.get_balance(&rent_paying_account.pubkey()),
+            rent_exempt_minimum - 1
+        );
+
+        // ...but can still be closed
+        let tx = create_mock_transfer(
+            &mint_keypair,        // payer
+            &rent_paying_account, // from
+            &mint_keypair,        // to
+            rent_exempt_minimum - 1,
+            mock_program_id,
+            recent_blockhash,
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert!(bank.get_account(&rent_paying_account.pubkey()).is_none());
+
+        // ...or topped up, but only all the way to RentExempt
+        bank.store_account(
+            &rent_paying_account.pubkey(),
+            &AccountSharedData::new(rent_exempt_minimum - 2, account_data_size, &mock_program_id),
+        );
+        assert_eq!(
+            bank.transfer(1, &mint_keypair, &rent_paying_account.pubkey()),
+            Err(TransactionError::InsufficientFundsForRent { account_index: 1 })
+        );
+        assert!(bank
+            .transfer(2, &mint_keypair, &rent_paying_account.pubkey())
+            .is_ok());
+        assert_eq!(
+            bank.get_balance(&rent_paying_account.pubkey()),
+            rent_exempt_minimum
+        );
+    }
+
     #[test]
     fn test_invalid_rent_state_changes_new_accounts() {
         let GenesisConfigInfo {
@@ -17072,8 +17547,12 @@ pub(crate) mod tests {
             Account::new(rent_exempt_minimum, 0, &system_program::id()),
         );
 
-        // Activate features, including require_rent_exempt_accounts
+        // Activate features, including require_rent_exempt_accounts, but not yet
+        // rent_exempt_only_accounts which reports InsufficientFundsForRent instead
         activate_all_features(&mut genesis_config);
+        genesis_config
+            .accounts
+            .remove(&feature_set::rent_exempt_only_accounts::id());
 
         let bank = Bank::new_for_tests(&genesis_config);
         let recent_blockhash = bank.last_blockhash();
@@ -17538,6 +18017,9 @@ pub(crate) mod tests {
         } = create_genesis_config_with_leader(1_000_000_000, &Pubkey::new_unique(), 42);
         genesis_config.rent = Rent::default();
         activate_all_features(&mut genesis_config);
+        genesis_config
+            .accounts
+            .remove(&feature_set::rent_exempt_only_accounts::id());
 
         let mut bank = Bank::new_for_tests(&genesis_config);
 
@@ -1,7 +1,7 @@
 use {
     super::Bank,
-    mundis_program_runtime::sysvar_cache::SysvarCache,
-    mundis_sdk::{account::ReadableAccount, sysvar::Sysvar},
+    mundis_program_runtime::sysvar_cache::{EpochVoteStakes, SysvarCache},
+    mundis_sdk::{account::ReadableAccount, clock::Epoch, sysvar::Sysvar},
 };
 
 impl Bank {
@@ -33,6 +33,32 @@ impl Bank {
                 sysvar_cache.set_slot_hashes(slot_hashes);
             }
         }
+        if sysvar_cache.get_epoch_vote_stakes().is_err() {
+            if let Some(epoch_vote_stakes) = self.epoch_vote_stakes(self.epoch()) {
+                sysvar_cache.set_epoch_vote_stakes(epoch_vote_stakes);
+            }
+            sysvar_cache.set_previous_epoch_vote_stakes(
+                self.epoch_stakes_map()
+                    .keys()
+                    .filter(|epoch| **epoch < self.epoch())
+                    .filter_map(|epoch| self.epoch_vote_stakes(*epoch))
+                    .collect(),
+            );
+        }
+    }
+
+    fn epoch_vote_stakes(&self, epoch: Epoch) -> Option<EpochVoteStakes> {
+        let epoch_stakes = self.epoch_stakes(epoch)?;
+        Some(EpochVoteStakes {
+            epoch,
+            total_stake: epoch_stakes.total_stake(),
+            vote_stakes: epoch_stakes
+                .stakes()
+                .vote_accounts()
+                .iter()
+                .map(|(pubkey, (stake, _))| (*pubkey, *stake))
+                .collect(),
+        })
     }
 
     pub(crate) fn reset_sysvar_cache(&self) {
@@ -73,6 +99,13 @@ mod tests {
         assert!(bank0_cached_epoch_schedule.is_ok());
         assert!(bank0_cached_fees.is_ok());
         assert!(bank0_cached_rent.is_ok());
+        assert_eq!(
+            bank0_sysvar_cache
+                .get_epoch_vote_stakes()
+                .unwrap()
+                .total_stake,
+            bank0.epoch_stakes(0).unwrap().total_stake()
+        );
         assert!(bank0
             .sysvar_cache
             .read()
@@ -100,6 +133,31 @@ mod tests {
         assert_eq!(bank0_cached_rent, bank1_cached_rent);
     }
 
+    #[test]
+    fn test_sysvar_cache_previous_epoch_vote_stakes() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(100_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let slot = genesis_config.epoch_schedule.get_first_slot_in_epoch(1);
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), slot);
+        assert_eq!(bank1.epoch(), 1);
+
+        let sysvar_cache = bank1.sysvar_cache.read().unwrap();
+        assert_eq!(sysvar_cache.get_epoch_vote_stakes().unwrap().epoch, 1);
+        assert_eq!(
+            sysvar_cache.get_epoch_vote_stakes_at(1),
+            sysvar_cache.get_epoch_vote_stakes()
+        );
+        assert_eq!(
+            sysvar_cache
+                .get_epoch_vote_stakes_at(0)
+                .unwrap()
+                .total_stake,
+            bank1.epoch_stakes(0).unwrap().total_stake()
+        );
+        // The leader schedule epoch is not a previous epoch
+        assert!(sysvar_cache.get_epoch_vote_stakes_at(2).is_err());
+    }
+
     #[test]
     #[allow(deprecated)]
     fn test_reset_and_fill_sysvar_cache() {
@@ -113,6 +171,7 @@ mod tests {
         let bank1_cached_fees = bank1_sysvar_cache.get_fees();
         let bank1_cached_rent = bank1_sysvar_cache.get_rent();
         let bank1_cached_slot_hashes = bank1_sysvar_cache.get_slot_hashes();
+        let bank1_cached_epoch_vote_stakes = bank1_sysvar_cache.get_epoch_vote_stakes();
 
         assert!(bank1_cached_clock.is_ok());
         assert!(bank1_cached_epoch_schedule.is_ok());
@@ -129,6 +188,7 @@ mod tests {
         assert!(bank1_sysvar_cache.get_fees().is_err());
         assert!(bank1_sysvar_cache.get_rent().is_err());
         assert!(bank1_sysvar_cache.get_slot_hashes().is_err());
+        assert!(bank1_sysvar_cache.get_epoch_vote_stakes().is_err());
 
         drop(bank1_sysvar_cache);
         bank1.fill_missing_sysvar_cache_entries();
@@ -145,5 +205,9 @@ mod tests {
             bank1_sysvar_cache.get_slot_hashes(),
             bank1_cached_slot_hashes
         );
+        assert_eq!(
+            bank1_sysvar_cache.get_epoch_vote_stakes(),
+            bank1_cached_epoch_vote_stakes
+        );
     }
 }
@@ -56,22 +56,28 @@ impl Bank {
         let do_support_realloc = self
             .feature_set
             .is_active(&feature_set::do_support_realloc::id());
-        for ((pre_state_info, post_state_info), (pubkey, account_refcell)) in pre_state_infos
+        let rent_exempt_only = self
+            .feature_set
+            .is_active(&feature_set::rent_exempt_only_accounts::id());
+        for (i, ((pre_state_info, post_state_info), (pubkey, account_refcell))) in pre_state_infos
             .iter()
             .zip(post_state_infos)
             .zip(transaction_account_refcells)
+            .enumerate()
         {
             if let Err(err) = check_rent_state(
                 pre_state_info.rent_state.as_ref(),
                 post_state_info.rent_state.as_ref(),
                 pubkey,
                 &account_refcell.borrow(),
+                i,
                 do_support_realloc,
+                rent_exempt_only,
             ) {
                 // Feature gate only wraps the actual error return so that the metrics and debug
                 // logging generated by `check_rent_state()` can be examined before feature
                 // activation
-                if require_rent_exempt_accounts {
+                if require_rent_exempt_accounts || rent_exempt_only {
                     return Err(err);
                 }
             }
@@ -9,15 +9,18 @@ use {
         ops::RangeBounds,
         path::PathBuf,
         sync::{
-            atomic::{AtomicUsize, Ordering},
+            atomic::{AtomicU64, AtomicUsize, Ordering},
             Arc, Mutex,
         },
     },
 };
+use crate::bucket_map::bucket_api::BucketOccupancy;
 use crate::bucket_map::bucket_item::BucketItem;
 use crate::bucket_map::bucket_map::BucketMapError;
 use crate::bucket_map::bucket_stats::BucketMapStats;
-use crate::bucket_map::bucket_storage::{BucketStorage, DEFAULT_CAPACITY_POW2, Uid, UID_UNLOCKED};
+use crate::bucket_map::bucket_storage::{
+    BucketStorage, BucketStorageError, DEFAULT_CAPACITY_POW2, Uid, UID_UNLOCKED,
+};
 use crate::bucket_map::index_entry::IndexEntry;
 use crate::bucket_map::{MaxSearch, RefCount};
 
@@ -70,6 +73,8 @@ pub struct Bucket<T> {
     pub data: Vec<BucketStorage>,
     _phantom: PhantomData<T>,
     stats: Arc<BucketMapStats>,
+    // number of times the index or one of the data buckets was grown
+    resizes: AtomicU64,
 
     pub reallocated: Reallocated,
 }
@@ -79,29 +84,46 @@ impl<T: Clone + Copy> Bucket<T> {
         drives: Arc<Vec<PathBuf>>,
         max_search: MaxSearch,
         stats: Arc<BucketMapStats>,
-    ) -> Self {
+    ) -> Result<Self, BucketStorageError> {
         let index = BucketStorage::new(
             Arc::clone(&drives),
             1,
             std::mem::size_of::<IndexEntry>() as u64,
             max_search,
             Arc::clone(&stats.index),
-        );
-        Self {
+        )?;
+        Ok(Self {
             random: thread_rng().gen(),
             drives,
             index,
             data: vec![],
             _phantom: PhantomData::default(),
             stats,
+            resizes: AtomicU64::default(),
             reallocated: Reallocated::default(),
-        }
+        })
     }
 
     pub fn bucket_len(&self) -> u64 {
         self.index.used.load(Ordering::Relaxed)
     }
 
+    pub fn occupancy(&self) -> BucketOccupancy {
+        let mut occupancy = BucketOccupancy {
+            index_used: self.bucket_len(),
+            index_capacity: self.index.capacity(),
+            bytes: self.index.bytes(),
+            resizes: self.resizes.load(Ordering::Relaxed),
+            ..BucketOccupancy::default()
+        };
+        for data in &self.data {
+            occupancy.data_used += data.used.load(Ordering::Relaxed);
+            occupancy.data_capacity += data.capacity();
+            occupancy.bytes += data.bytes();
+        }
+        occupancy
+    }
+
     pub fn keys(&self) -> Vec<Pubkey> {
         let mut rv = vec![];
         for i in 0..self.index.capacity() {
@@ -308,7 +330,7 @@ impl<T: Clone + Copy> Bucket<T> {
         }
     }
 
-    pub fn grow_index(&self, current_capacity_pow2: u8) {
+    pub fn grow_index(&self, current_capacity_pow2: u8) -> Result<(), BucketStorageError> {
         if self.index.capacity_pow2 == current_capacity_pow2 {
             let mut m = Measure::start("grow_index");
             //debug!("GROW_INDEX: {}", current_capacity_pow2);
@@ -325,7 +347,7 @@ impl<T: Clone + Copy> Bucket<T> {
                     self.index.capacity_pow2 + i, // * 2,
                     self.index.max_search,
                     Arc::clone(&self.stats.index),
-                );
+                )?;
                 let random = thread_rng().gen();
                 let mut valid = true;
                 for ix in 0..self.index.capacity() {
@@ -362,12 +384,14 @@ impl<T: Clone + Copy> Bucket<T> {
                 }
             }
             m.stop();
+            self.resizes.fetch_add(1, Ordering::Relaxed);
             self.stats.index.resizes.fetch_add(1, Ordering::Relaxed);
             self.stats
                 .index
                 .resize_us
                 .fetch_add(m.as_us(), Ordering::Relaxed);
         }
+        Ok(())
     }
 
     pub fn apply_grow_index(&mut self, random: u64, index: BucketStorage) {
@@ -379,7 +403,11 @@ impl<T: Clone + Copy> Bucket<T> {
         std::mem::size_of::<T>() as u64
     }
 
-    pub fn apply_grow_data(&mut self, ix: usize, bucket: BucketStorage) {
+    pub fn apply_grow_data(
+        &mut self,
+        ix: usize,
+        bucket: BucketStorage,
+    ) -> Result<(), BucketStorageError> {
         if self.data.get(ix).is_none() {
             for i in self.data.len()..ix {
                 // insert empty data buckets
@@ -389,17 +417,22 @@ impl<T: Clone + Copy> Bucket<T> {
                     Self::elem_size(),
                     self.index.max_search,
                     Arc::clone(&self.stats.data),
-                ))
+                )?)
             }
             self.data.push(bucket);
         } else {
             self.data[ix] = bucket;
         }
+        Ok(())
     }
 
     /// grow a data bucket
     /// The application of the new bucket is deferred until the next write lock.
-    pub fn grow_data(&self, data_index: u64, current_capacity_pow2: u8) {
+    pub fn grow_data(
+        &self,
+        data_index: u64,
+        current_capacity_pow2: u8,
+    ) -> Result<(), BucketStorageError> {
         let new_bucket = BucketStorage::new_resized(
             &self.drives,
             self.index.max_search,
@@ -408,10 +441,12 @@ impl<T: Clone + Copy> Bucket<T> {
             1 << data_index,
             Self::elem_size(),
             &self.stats.data,
-        );
+        )?;
+        self.resizes.fetch_add(1, Ordering::Relaxed);
         self.reallocated.add_reallocation();
         let mut items = self.reallocated.items.lock().unwrap();
         items.data = Some((data_index, new_bucket));
+        Ok(())
     }
 
     fn bucket_index_ix(index: &BucketStorage, key: &Pubkey, random: u64) -> u64 {
@@ -429,21 +464,24 @@ impl<T: Clone + Copy> Bucket<T> {
 
     /// grow the appropriate piece. Note this takes an immutable ref.
     /// The actual grow is set into self.reallocated and applied later on a write lock
-    pub fn grow(&self, err: BucketMapError) {
+    pub fn grow(&self, err: BucketMapError) -> Result<(), BucketMapError> {
         match err {
             BucketMapError::DataNoSpace((data_index, current_capacity_pow2)) => {
                 //debug!("GROWING SPACE {:?}", (data_index, current_capacity_pow2));
-                self.grow_data(data_index, current_capacity_pow2);
+                self.grow_data(data_index, current_capacity_pow2)?;
             }
             BucketMapError::IndexNoSpace(current_capacity_pow2) => {
                 //debug!("GROWING INDEX {}", sz);
-                self.grow_index(current_capacity_pow2);
+                self.grow_index(current_capacity_pow2)?;
             }
+            // there is nothing to grow when creating a file already failed
+            BucketMapError::Storage(_) => return Err(err),
         }
+        Ok(())
     }
 
     /// if a bucket was resized previously with a read lock, then apply that resize now
-    pub fn handle_delayed_grows(&mut self) {
+    pub fn handle_delayed_grows(&mut self) -> Result<(), BucketStorageError> {
         if self.reallocated.get_reallocated() {
             // swap out the bucket that was resized previously with a read lock
             let mut items = ReallocatedItems::default();
@@ -454,26 +492,27 @@ impl<T: Clone + Copy> Bucket<T> {
             } else {
                 // data bucket
                 let (i, new_bucket) = items.data.take().unwrap();
-                self.apply_grow_data(i as usize, new_bucket);
+                self.apply_grow_data(i as usize, new_bucket)?;
             }
         }
+        Ok(())
     }
 
-    pub fn insert(&mut self, key: &Pubkey, value: (&[T], RefCount)) {
+    pub fn insert(&mut self, key: &Pubkey, value: (&[T], RefCount)) -> Result<(), BucketMapError> {
         let (new, refct) = value;
         loop {
             let rv = self.try_write(key, new, refct);
             match rv {
-                Ok(_) => return,
+                Ok(_) => return Ok(()),
                 Err(err) => {
-                    self.grow(err);
-                    self.handle_delayed_grows();
+                    self.grow(err)?;
+                    self.handle_delayed_grows()?;
                 }
             }
         }
     }
 
-    pub fn update<F>(&mut self, key: &Pubkey, mut updatefn: F)
+    pub fn update<F>(&mut self, key: &Pubkey, mut updatefn: F) -> Result<(), BucketMapError>
     where
         F: FnMut(Option<(&[T], RefCount)>) -> Option<(Vec<T>, RefCount)>,
     {
@@ -481,9 +520,9 @@ impl<T: Clone + Copy> Bucket<T> {
         let new = updatefn(current);
         if new.is_none() {
             self.delete_key(key);
-            return;
+            return Ok(());
         }
         let (new, refct) = new.unwrap();
-        self.insert(key, (&new, refct));
+        self.insert(key, (&new, refct))
     }
 }
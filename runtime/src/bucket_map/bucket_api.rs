@@ -1,8 +1,13 @@
 use {
     crate::bucket_map::{
-        bucket::Bucket, bucket_item::BucketItem, bucket_map::BucketMapError,
-        bucket_stats::BucketMapStats, MaxSearch, RefCount,
+        bucket::Bucket,
+        bucket_item::BucketItem,
+        bucket_map::BucketMapError,
+        bucket_stats::{BucketMapStats, BucketOpStats},
+        bucket_storage::BucketStorageError,
+        MaxSearch, RefCount,
     },
+    mundis_measure::measure::Measure,
     mundis_sdk::pubkey::Pubkey,
     std::{
         ops::RangeBounds,
@@ -16,6 +21,52 @@ use {
 
 type LockedBucket<T> = RwLock<Option<Bucket<T>>>;
 
+/// How full the files backing one bucket are
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BucketOccupancy {
+    /// cells in use in the index file
+    pub index_used: u64,
+    /// cells allocated in the index file
+    pub index_capacity: u64,
+    /// cells in use across the data files
+    pub data_used: u64,
+    /// cells allocated across the data files
+    pub data_capacity: u64,
+    /// size of all of the files
+    pub bytes: u64,
+    /// number of times one of the files was grown
+    pub resizes: u64,
+}
+
+impl BucketOccupancy {
+    /// Percentage of the index cells in use
+    pub fn index_percent(&self) -> f64 {
+        if self.index_capacity == 0 {
+            0.0
+        } else {
+            self.index_used as f64 * 100.0 / self.index_capacity as f64
+        }
+    }
+
+    /// Percentage of the data cells in use
+    pub fn data_percent(&self) -> f64 {
+        if self.data_capacity == 0 {
+            0.0
+        } else {
+            self.data_used as f64 * 100.0 / self.data_capacity as f64
+        }
+    }
+
+    pub fn accumulate(&mut self, other: &Self) {
+        self.index_used += other.index_used;
+        self.index_capacity += other.index_capacity;
+        self.data_used += other.data_used;
+        self.data_capacity += other.data_capacity;
+        self.bytes += other.bytes;
+        self.resizes += other.resizes;
+    }
+}
+
 pub struct BucketApi<T: Clone + Copy> {
     drives: Arc<Vec<PathBuf>>,
     max_search: MaxSearch,
@@ -23,6 +74,7 @@ pub struct BucketApi<T: Clone + Copy> {
 
     bucket: LockedBucket<T>,
     count: Arc<AtomicU64>,
+    op_stats: Arc<BucketOpStats>,
 }
 
 impl<T: Clone + Copy> BucketApi<T> {
@@ -31,6 +83,7 @@ impl<T: Clone + Copy> BucketApi<T> {
         max_search: MaxSearch,
         stats: Arc<BucketMapStats>,
         count: Arc<AtomicU64>,
+        op_stats: Arc<BucketOpStats>,
     ) -> Self {
         Self {
             drives,
@@ -38,6 +91,7 @@ impl<T: Clone + Copy> BucketApi<T> {
             stats,
             bucket: RwLock::default(),
             count,
+            op_stats,
         }
     }
 
@@ -65,11 +119,42 @@ impl<T: Clone + Copy> BucketApi<T> {
 
     /// Get the values for Pubkey `key`
     pub fn read_value(&self, key: &Pubkey) -> Option<(Vec<T>, RefCount)> {
-        self.bucket.read().unwrap().as_ref().and_then(|bucket| {
+        let m = BucketOpStats::count(&self.op_stats.reads).then(|| Measure::start("read_value"));
+        let result = self.bucket.read().unwrap().as_ref().and_then(|bucket| {
             bucket
                 .read_value(key)
                 .map(|(value, ref_count)| (value.to_vec(), ref_count))
-        })
+        });
+        if let Some(mut m) = m {
+            m.stop();
+            self.op_stats
+                .sampled_read_us
+                .fetch_add(m.as_us(), Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Count a write, and start timing it if it is sampled
+    fn start_write(&self, name: &'static str) -> Option<Measure> {
+        BucketOpStats::count(&self.op_stats.writes).then(|| Measure::start(name))
+    }
+
+    fn update_write_stats(&self, m: Option<Measure>) {
+        if let Some(mut m) = m {
+            m.stop();
+            self.op_stats
+                .sampled_write_us
+                .fetch_add(m.as_us(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn occupancy(&self) -> BucketOccupancy {
+        self.bucket
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|bucket| bucket.occupancy())
+            .unwrap_or_default()
     }
 
     pub fn bucket_len(&self) -> u64 {
@@ -81,60 +166,76 @@ impl<T: Clone + Copy> BucketApi<T> {
             .unwrap_or_default()
     }
 
-    pub fn delete_key(&self, key: &Pubkey) {
-        let mut bucket = self.get_write_bucket();
+    pub fn delete_key(&self, key: &Pubkey) -> Result<(), BucketMapError> {
+        let mut bucket = self.get_write_bucket()?;
         if let Some(bucket) = bucket.as_mut() {
             bucket.delete_key(key)
         }
+        Ok(())
     }
 
-    fn get_write_bucket(&self) -> RwLockWriteGuard<Option<Bucket<T>>> {
+    /// Take the write lock, creating the bucket or applying the grows done under a read lock
+    /// first. Either one creates files, which fails once no drive has room for them.
+    fn get_write_bucket(&self) -> Result<RwLockWriteGuard<Option<Bucket<T>>>, BucketStorageError> {
         let mut bucket = self.bucket.write().unwrap();
         if bucket.is_none() {
             *bucket = Some(Bucket::new(
                 Arc::clone(&self.drives),
                 self.max_search,
                 Arc::clone(&self.stats),
-            ));
+            )?);
         } else {
             let write = bucket.as_mut().unwrap();
-            write.handle_delayed_grows();
+            write.handle_delayed_grows()?;
             self.count.store(write.bucket_len(), Ordering::Relaxed);
         }
-        bucket
+        Ok(bucket)
     }
 
-    pub fn addref(&self, key: &Pubkey) -> Option<RefCount> {
-        self.get_write_bucket()
+    pub fn addref(&self, key: &Pubkey) -> Result<Option<RefCount>, BucketMapError> {
+        Ok(self
+            .get_write_bucket()?
             .as_mut()
-            .and_then(|bucket| bucket.addref(key))
+            .and_then(|bucket| bucket.addref(key)))
     }
 
-    pub fn unref(&self, key: &Pubkey) -> Option<RefCount> {
-        self.get_write_bucket()
+    pub fn unref(&self, key: &Pubkey) -> Result<Option<RefCount>, BucketMapError> {
+        Ok(self
+            .get_write_bucket()?
             .as_mut()
-            .and_then(|bucket| bucket.unref(key))
+            .and_then(|bucket| bucket.unref(key)))
     }
 
-    pub fn insert(&self, pubkey: &Pubkey, value: (&[T], RefCount)) {
-        let mut bucket = self.get_write_bucket();
-        bucket.as_mut().unwrap().insert(pubkey, value)
+    pub fn insert(&self, pubkey: &Pubkey, value: (&[T], RefCount)) -> Result<(), BucketMapError> {
+        let m = self.start_write("insert");
+        let mut bucket = self.get_write_bucket()?;
+        let result = bucket.as_mut().unwrap().insert(pubkey, value);
+        self.update_write_stats(m);
+        result
     }
 
-    pub fn grow(&self, err: BucketMapError) {
+    pub fn grow(&self, err: BucketMapError) -> Result<(), BucketMapError> {
+        if let BucketMapError::Storage(_) = err {
+            // creating the bucket failed, so there is nothing to grow
+            return Err(err);
+        }
         // grows are special - they get a read lock and modify 'reallocated'
         // the grown changes are applied the next time there is a write lock taken
-        if let Some(bucket) = self.bucket.read().unwrap().as_ref() {
-            bucket.grow(err)
+        match self.bucket.read().unwrap().as_ref() {
+            Some(bucket) => bucket.grow(err),
+            None => Ok(()),
         }
     }
 
-    pub fn update<F>(&self, key: &Pubkey, updatefn: F)
+    pub fn update<F>(&self, key: &Pubkey, updatefn: F) -> Result<(), BucketMapError>
     where
         F: FnMut(Option<(&[T], RefCount)>) -> Option<(Vec<T>, RefCount)>,
     {
-        let mut bucket = self.get_write_bucket();
-        bucket.as_mut().unwrap().update(key, updatefn)
+        let m = self.start_write("update");
+        let mut bucket = self.get_write_bucket()?;
+        let result = bucket.as_mut().unwrap().update(key, updatefn);
+        self.update_write_stats(m);
+        result
     }
 
     pub fn try_write(
@@ -142,7 +243,10 @@ impl<T: Clone + Copy> BucketApi<T> {
         pubkey: &Pubkey,
         value: (&[T], RefCount),
     ) -> Result<(), BucketMapError> {
-        let mut bucket = self.get_write_bucket();
-        bucket.as_mut().unwrap().try_write(pubkey, value.0, value.1)
+        let m = self.start_write("try_write");
+        let mut bucket = self.get_write_bucket()?;
+        let result = bucket.as_mut().unwrap().try_write(pubkey, value.0, value.1);
+        self.update_write_stats(m);
+        result
     }
 }
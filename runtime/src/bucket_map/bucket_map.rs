@@ -1,7 +1,12 @@
 //! BucketMap is a mostly contention free concurrent map backed by MmapMut
 
 use {
-    crate::bucket_map::{bucket_api::BucketApi, bucket_stats::BucketMapStats, MaxSearch, RefCount},
+    crate::bucket_map::{
+        bucket_api::{BucketApi, BucketOccupancy},
+        bucket_stats::BucketMapStats,
+        bucket_storage::BucketStorageError,
+        MaxSearch, RefCount,
+    },
     mundis_sdk::pubkey::Pubkey,
     std::{convert::TryInto, fmt::Debug, fs, path::PathBuf, sync::Arc},
     tempfile::TempDir,
@@ -53,6 +58,14 @@ pub enum BucketMapError {
     DataNoSpace((u64, u8)),
     /// current_capacity_pow2
     IndexNoSpace(u8),
+    /// a file backing the bucket could not be created on any of the drives
+    Storage(BucketStorageError),
+}
+
+impl From<BucketStorageError> for BucketMapError {
+    fn from(err: BucketStorageError) -> Self {
+        Self::Storage(err)
+    }
 }
 
 impl<T: Clone + Copy + Debug> BucketMap<T> {
@@ -81,19 +94,24 @@ impl<T: Clone + Copy + Debug> BucketMap<T> {
 
         let mut per_bucket_count = Vec::with_capacity(config.max_buckets);
         per_bucket_count.resize_with(config.max_buckets, Arc::default);
+        let mut per_bucket_ops = Vec::with_capacity(config.max_buckets);
+        per_bucket_ops.resize_with(config.max_buckets, Arc::default);
         let stats = Arc::new(BucketMapStats {
             per_bucket_count,
+            per_bucket_ops,
             ..BucketMapStats::default()
         });
         let buckets = stats
             .per_bucket_count
             .iter()
-            .map(|per_bucket_count| {
+            .zip(stats.per_bucket_ops.iter())
+            .map(|(per_bucket_count, per_bucket_ops)| {
                 Arc::new(BucketApi::new(
                     Arc::clone(&drives),
                     max_search,
                     Arc::clone(&stats),
                     Arc::clone(per_bucket_count),
+                    Arc::clone(per_bucket_ops),
                 ))
             })
             .collect();
@@ -121,18 +139,26 @@ impl<T: Clone + Copy + Debug> BucketMap<T> {
         self.buckets.len()
     }
 
+    /// Get how full the files of each bucket are
+    pub fn occupancy(&self) -> Vec<BucketOccupancy> {
+        self.buckets
+            .iter()
+            .map(|bucket| bucket.occupancy())
+            .collect()
+    }
+
     /// Get the values for Pubkey `key`
     pub fn read_value(&self, key: &Pubkey) -> Option<(Vec<T>, RefCount)> {
         self.get_bucket(key).read_value(key)
     }
 
     /// Delete the Pubkey `key`
-    pub fn delete_key(&self, key: &Pubkey) {
-        self.get_bucket(key).delete_key(key);
+    pub fn delete_key(&self, key: &Pubkey) -> Result<(), BucketMapError> {
+        self.get_bucket(key).delete_key(key)
     }
 
     /// Update Pubkey `key`'s value with 'value'
-    pub fn insert(&self, key: &Pubkey, value: (&[T], RefCount)) {
+    pub fn insert(&self, key: &Pubkey, value: (&[T], RefCount)) -> Result<(), BucketMapError> {
         self.get_bucket(key).insert(key, value)
     }
 
@@ -142,7 +168,7 @@ impl<T: Clone + Copy + Debug> BucketMap<T> {
     }
 
     /// Update Pubkey `key`'s value with function `updatefn`
-    pub fn update<F>(&self, key: &Pubkey, updatefn: F)
+    pub fn update<F>(&self, key: &Pubkey, updatefn: F) -> Result<(), BucketMapError>
     where
         F: FnMut(Option<(&[T], RefCount)>) -> Option<(Vec<T>, RefCount)>,
     {
@@ -168,14 +194,14 @@ impl<T: Clone + Copy + Debug> BucketMap<T> {
     }
 
     /// Increment the refcount for Pubkey `key`
-    pub fn addref(&self, key: &Pubkey) -> Option<RefCount> {
+    pub fn addref(&self, key: &Pubkey) -> Result<Option<RefCount>, BucketMapError> {
         let ix = self.bucket_ix(key);
         let bucket = &self.buckets[ix];
         bucket.addref(key)
     }
 
     /// Decrement the refcount for Pubkey `key`
-    pub fn unref(&self, key: &Pubkey) -> Option<RefCount> {
+    pub fn unref(&self, key: &Pubkey) -> Result<Option<RefCount>, BucketMapError> {
         let ix = self.bucket_ix(key);
         let bucket = &self.buckets[ix];
         bucket.unref(key)
@@ -192,6 +218,7 @@ fn read_be_u64(input: &[u8]) -> u64 {
 mod tests {
     use {
         super::*,
+        assert_matches::assert_matches,
         rand::{thread_rng, Rng},
         std::{collections::HashMap, sync::RwLock},
     };
@@ -201,7 +228,7 @@ mod tests {
         let key = Pubkey::new_unique();
         let config = BucketMapConfig::new(1 << 1);
         let index = BucketMap::new(config);
-        index.update(&key, |_| Some((vec![0], 0)));
+        index.update(&key, |_| Some((vec![0], 0))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![0], 0)));
     }
 
@@ -213,7 +240,7 @@ mod tests {
             let index = BucketMap::new(config);
             let bucket = index.get_bucket(&key);
             if pass == 0 {
-                index.insert(&key, (&[0], 0));
+                index.insert(&key, (&[0], 0)).unwrap();
             } else {
                 let result = index.try_insert(&key, (&[0], 0));
                 assert!(result.is_err());
@@ -224,7 +251,7 @@ mod tests {
                     assert!(result.is_err());
                     assert_eq!(index.read_value(&key), None);
                 }
-                bucket.grow(result.unwrap_err());
+                bucket.grow(result.unwrap_err()).unwrap();
                 let result = index.try_insert(&key, (&[0], 0));
                 assert!(result.is_ok());
             }
@@ -232,14 +259,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bucket_map_test_spill_to_other_drive() {
+        let tmpdir = TempDir::new().unwrap();
+        let not_a_dir = tmpdir.path().join("file");
+        fs::write(&not_a_dir, b"").unwrap();
+        let good_drive = tmpdir.path().join("drive");
+        let config = BucketMapConfig {
+            drives: Some(vec![not_a_dir.join("drive"), good_drive.clone()]),
+            ..BucketMapConfig::new(1)
+        };
+        let index = BucketMap::<u64>::new(config);
+        let keys = (0..100).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        for (i, key) in keys.iter().enumerate() {
+            index.insert(key, (&[i as u64], 0)).unwrap();
+        }
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(index.read_value(key), Some((vec![i as u64], 0)));
+        }
+
+        // every file was created on the good drive
+        let occupancy = index.occupancy();
+        assert_eq!(occupancy.len(), 1);
+        assert_eq!(occupancy[0].index_used, keys.len() as u64);
+        assert_eq!(occupancy[0].data_used, keys.len() as u64);
+        assert!(occupancy[0].resizes > 0);
+        assert!(fs::read_dir(&good_drive).unwrap().count() > 0);
+        assert_eq!(index.stats.take_op_totals().reads, keys.len() as u64);
+        assert_eq!(index.stats.take_op_totals().reads, 0);
+    }
+
+    #[test]
+    fn bucket_map_test_no_usable_drive() {
+        let tmpdir = TempDir::new().unwrap();
+        let not_a_dir = tmpdir.path().join("file");
+        fs::write(&not_a_dir, b"").unwrap();
+        let config = BucketMapConfig {
+            drives: Some(vec![not_a_dir.join("drive")]),
+            ..BucketMapConfig::new(1)
+        };
+        let index = BucketMap::<u64>::new(config);
+        assert_matches!(
+            index.insert(&Pubkey::new_unique(), (&[0], 0)),
+            Err(BucketMapError::Storage(
+                BucketStorageError::NoSpaceOnDrives { .. }
+            ))
+        );
+    }
+
     #[test]
     fn bucket_map_test_update2() {
         let key = Pubkey::new_unique();
         let config = BucketMapConfig::new(1 << 1);
         let index = BucketMap::new(config);
-        index.insert(&key, (&[0], 0));
+        index.insert(&key, (&[0], 0)).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![0], 0)));
-        index.insert(&key, (&[1], 0));
+        index.insert(&key, (&[1], 0)).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![1], 0)));
     }
 
@@ -248,9 +323,9 @@ mod tests {
         let key = Pubkey::new_unique();
         let config = BucketMapConfig::new(1 << 1);
         let index = BucketMap::new(config);
-        index.update(&key, |_| Some((vec![0], 0)));
+        index.update(&key, |_| Some((vec![0], 0))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![0], 0)));
-        index.update(&key, |_| Some((vec![1], 0)));
+        index.update(&key, |_| Some((vec![1], 0))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![1], 0)));
     }
 
@@ -260,16 +335,16 @@ mod tests {
         let key = Pubkey::new_unique();
         let config = BucketMapConfig::new(1 << 1);
         let index = BucketMap::new(config);
-        index.update(&key, |_| Some((vec![0], 1)));
+        index.update(&key, |_| Some((vec![0], 1))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![0], 1)));
         // sets len to 0, updates in place
-        index.update(&key, |_| Some((vec![], 1)));
+        index.update(&key, |_| Some((vec![], 1))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![], 1)));
         // sets len to 0, doesn't update in place - finds a new place, which causes us to no longer have an allocation in data
-        index.update(&key, |_| Some((vec![], 2)));
+        index.update(&key, |_| Some((vec![], 2))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![], 2)));
         // sets len to 1, doesn't update in place - finds a new place
-        index.update(&key, |_| Some((vec![1], 2)));
+        index.update(&key, |_| Some((vec![1], 2))).unwrap();
         assert_eq!(index.read_value(&key), Some((vec![1], 2)));
     }
 
@@ -281,15 +356,15 @@ mod tests {
             let key = Pubkey::new_unique();
             assert_eq!(index.read_value(&key), None);
 
-            index.update(&key, |_| Some((vec![i], 0)));
+            index.update(&key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(&key), Some((vec![i], 0)));
 
-            index.delete_key(&key);
+            index.delete_key(&key).unwrap();
             assert_eq!(index.read_value(&key), None);
 
-            index.update(&key, |_| Some((vec![i], 0)));
+            index.update(&key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(&key), Some((vec![i], 0)));
-            index.delete_key(&key);
+            index.delete_key(&key).unwrap();
         }
     }
 
@@ -301,15 +376,15 @@ mod tests {
             let key = Pubkey::new_unique();
             assert_eq!(index.read_value(&key), None);
 
-            index.update(&key, |_| Some((vec![i], 0)));
+            index.update(&key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(&key), Some((vec![i], 0)));
 
-            index.delete_key(&key);
+            index.delete_key(&key).unwrap();
             assert_eq!(index.read_value(&key), None);
 
-            index.update(&key, |_| Some((vec![i], 0)));
+            index.update(&key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(&key), Some((vec![i], 0)));
-            index.delete_key(&key);
+            index.delete_key(&key).unwrap();
         }
     }
 
@@ -319,7 +394,7 @@ mod tests {
         let index = BucketMap::new(config);
         for i in 0..100 {
             let key = Pubkey::new_unique();
-            index.update(&key, |_| Some((vec![i], 0)));
+            index.update(&key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(&key), Some((vec![i], 0)));
         }
     }
@@ -331,7 +406,7 @@ mod tests {
         for k in 0..keys.len() {
             let key = &keys[k];
             let i = read_be_u64(key.as_ref());
-            index.update(key, |_| Some((vec![i], 0)));
+            index.update(key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(key), Some((vec![i], 0)));
             for (ix, key) in keys.iter().enumerate() {
                 let i = read_be_u64(key.as_ref());
@@ -349,7 +424,7 @@ mod tests {
         let keys: Vec<Pubkey> = (0..20).into_iter().map(|_| Pubkey::new_unique()).collect();
         for key in keys.iter() {
             let i = read_be_u64(key.as_ref());
-            index.update(key, |_| Some((vec![i], 0)));
+            index.update(key, |_| Some((vec![i], 0))).unwrap();
             assert_eq!(index.read_value(key), Some((vec![i], 0)));
         }
         for key in keys.iter() {
@@ -359,7 +434,7 @@ mod tests {
         }
         for k in 0..keys.len() {
             let key = &keys[k];
-            index.delete_key(key);
+            index.delete_key(key).unwrap();
             assert_eq!(index.read_value(key), None);
             for key in keys.iter().skip(k + 1) {
                 let i = read_be_u64(key.as_ref());
@@ -453,12 +528,13 @@ mod tests {
                 let insert = thread_rng().gen_range(0, 2) == 0;
                 maps.iter().for_each(|map| {
                     if insert {
-                        map.insert(&k, (&v.0, v.1))
+                        map.insert(&k, (&v.0, v.1)).unwrap()
                     } else {
                         map.update(&k, |current| {
                             assert!(current.is_none());
                             Some(v.clone())
                         })
+                        .unwrap()
                     }
                 });
                 return_key(k);
@@ -472,12 +548,13 @@ mod tests {
                     let insert = thread_rng().gen_range(0, 2) == 0;
                     maps.iter().for_each(|map| {
                         if insert {
-                            map.insert(&k, (&v, rc))
+                            map.insert(&k, (&v, rc)).unwrap()
                         } else {
                             map.update(&k, |current| {
                                 assert_eq!(current, v_old.map(|(v, rc)| (&v[..], *rc)), "{}", k);
                                 Some((v.clone(), rc))
                             })
+                            .unwrap()
                         }
                     });
                     drop(hm);
@@ -491,7 +568,7 @@ mod tests {
                     let mut hm = hash_map.write().unwrap();
                     hm.remove(&k);
                     maps.iter().for_each(|map| {
-                        map.delete_key(&k);
+                        map.delete_key(&k).unwrap();
                     });
                 }
             }
@@ -510,10 +587,11 @@ mod tests {
                     maps.iter().for_each(|map| {
                         if thread_rng().gen_range(0, 2) == 0 {
                             map.update(&k, |current| Some((current.unwrap().0.to_vec(), rc)))
+                                .unwrap()
                         } else if inc {
-                            map.addref(&k);
+                            map.addref(&k).unwrap();
                         } else {
-                            map.unref(&k);
+                            map.unref(&k).unwrap();
                         }
                     });
 
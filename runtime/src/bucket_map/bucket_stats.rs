@@ -1,4 +1,10 @@
-use std::sync::{atomic::AtomicU64, Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+/// Only one in this many reads and writes of a bucket is timed
+pub const OP_TIMING_SAMPLE_RATE: u64 = 64;
 
 #[derive(Debug, Default)]
 pub struct BucketStats {
@@ -8,6 +14,38 @@ pub struct BucketStats {
     pub new_file_us: AtomicU64,
     pub flush_file_us: AtomicU64,
     pub mmap_us: AtomicU64,
+    /// files created on another drive because the randomly chosen one was full or failed
+    pub spills: AtomicU64,
+}
+
+/// The reads and writes of one bucket. Each bucket counts its own, so threads working on
+/// different buckets do not contend on the counters.
+#[derive(Debug, Default)]
+pub struct BucketOpStats {
+    pub reads: AtomicU64,
+    /// time spent in the reads that were sampled
+    pub sampled_read_us: AtomicU64,
+    pub writes: AtomicU64,
+    /// time spent in the writes that were sampled
+    pub sampled_write_us: AtomicU64,
+}
+
+impl BucketOpStats {
+    /// Count an operation in `counter`, and return whether to time it
+    pub fn count(counter: &AtomicU64) -> bool {
+        counter.fetch_add(1, Ordering::Relaxed) % OP_TIMING_SAMPLE_RATE == 0
+    }
+}
+
+/// The reads and writes of all of the buckets since they were last reported
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BucketOpTotals {
+    pub reads: u64,
+    /// estimated from the sampled reads
+    pub read_us: u64,
+    pub writes: u64,
+    /// estimated from the sampled writes
+    pub write_us: u64,
 }
 
 #[derive(Debug, Default)]
@@ -15,4 +53,21 @@ pub struct BucketMapStats {
     pub index: Arc<BucketStats>,
     pub data: Arc<BucketStats>,
     pub per_bucket_count: Vec<Arc<AtomicU64>>,
+    pub per_bucket_ops: Vec<Arc<BucketOpStats>>,
+}
+
+impl BucketMapStats {
+    /// Sum up the reads and writes of all of the buckets, and reset them
+    pub fn take_op_totals(&self) -> BucketOpTotals {
+        let mut totals = BucketOpTotals::default();
+        for ops in &self.per_bucket_ops {
+            totals.reads += ops.reads.swap(0, Ordering::Relaxed);
+            totals.read_us += ops.sampled_read_us.swap(0, Ordering::Relaxed);
+            totals.writes += ops.writes.swap(0, Ordering::Relaxed);
+            totals.write_us += ops.sampled_write_us.swap(0, Ordering::Relaxed);
+        }
+        totals.read_us *= OP_TIMING_SAMPLE_RATE;
+        totals.write_us *= OP_TIMING_SAMPLE_RATE;
+        totals
+    }
 }
@@ -1,17 +1,19 @@
 use {
     crate::bucket_map::{bucket_stats::BucketStats, MaxSearch},
+    log::*,
     memmap2::MmapMut,
     rand::{thread_rng, Rng},
     mundis_measure::measure::Measure,
     std::{
         fs::{remove_file, OpenOptions},
-        io::{Seek, SeekFrom, Write},
-        path::PathBuf,
+        io::{self, Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
         sync::{
             atomic::{AtomicU64, Ordering},
             Arc,
         },
     },
+    thiserror::Error,
 };
 
 /*
@@ -69,9 +71,15 @@ pub struct BucketStorage {
     pub max_search: MaxSearch,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum BucketStorageError {
+    #[error("cell is already allocated")]
     AlreadyAllocated,
+    #[error(
+        "no drive can hold another {size} byte file ({errors}). Free up space or add drives \
+         with --accounts-index-path"
+    )]
+    NoSpaceOnDrives { size: u64, errors: String },
 }
 
 impl Drop for BucketStorage {
@@ -88,10 +96,10 @@ impl BucketStorage {
         capacity_pow2: u8,
         max_search: MaxSearch,
         stats: Arc<BucketStats>,
-    ) -> Self {
+    ) -> Result<Self, BucketStorageError> {
         let cell_size = elem_size * num_elems + std::mem::size_of::<Header>() as u64;
-        let (mmap, path) = Self::new_map(&drives, cell_size as usize, capacity_pow2, &stats);
-        Self {
+        let (mmap, path) = Self::new_map(&drives, cell_size as usize, capacity_pow2, &stats)?;
+        Ok(Self {
             path,
             mmap,
             cell_size,
@@ -99,7 +107,7 @@ impl BucketStorage {
             capacity_pow2,
             stats,
             max_search,
-        }
+        })
     }
 
     pub fn max_search(&self) -> u64 {
@@ -112,7 +120,7 @@ impl BucketStorage {
         elem_size: u64,
         max_search: MaxSearch,
         stats: Arc<BucketStats>,
-    ) -> Self {
+    ) -> Result<Self, BucketStorageError> {
         Self::new_with_capacity(
             drives,
             num_elems,
@@ -228,58 +236,116 @@ impl BucketStorage {
         }
     }
 
+    /// Whether `drive` has room for another `size` byte file. Bucket files are sparse, so
+    /// creating one on a full drive succeeds and only fails later, when its pages are written
+    /// through the mmap and there is no way to recover.
+    #[cfg(unix)]
+    fn drive_has_space(drive: &Path, size: u64) -> bool {
+        nix::sys::statvfs::statvfs(drive)
+            .map(|stat| {
+                u64::from(stat.blocks_available()).saturating_mul(u64::from(stat.fragment_size()))
+                    >= size
+            })
+            .unwrap_or(true)
+    }
+
+    #[cfg(not(unix))]
+    fn drive_has_space(_drive: &Path, _size: u64) -> bool {
+        true
+    }
+
     fn new_map(
         drives: &[PathBuf],
         cell_size: usize,
         capacity_pow2: u8,
         stats: &BucketStats,
-    ) -> (MmapMut, PathBuf) {
-        let mut measure_new_file = Measure::start("measure_new_file");
+    ) -> Result<(MmapMut, PathBuf), BucketStorageError> {
         let capacity = 1u64 << capacity_pow2;
-        let r = thread_rng().gen_range(0, drives.len());
-        let drive = &drives[r];
+        let size = capacity * cell_size as u64;
+        // Start on a random drive to spread the files out, and spill over to the following
+        // drives when a drive is full or the file can't be created there
+        let first_drive = thread_rng().gen_range(0, drives.len());
+        let mut errors = Vec::with_capacity(drives.len());
+        for i in 0..drives.len() {
+            let drive = &drives[(first_drive + i) % drives.len()];
+            let result = if Self::drive_has_space(drive, size) {
+                Self::new_map_on_drive(drive, size, stats)
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "not enough free space",
+                ))
+            };
+            match result {
+                Ok(res) => {
+                    if i > 0 {
+                        stats.spills.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return Ok(res);
+                }
+                Err(err) => {
+                    warn!(
+                        "Unable to create a {} byte accounts index file in {}: {}",
+                        size,
+                        drive.display(),
+                        err
+                    );
+                    errors.push(format!("{}: {}", drive.display(), err));
+                }
+            }
+        }
+        Err(BucketStorageError::NoSpaceOnDrives {
+            size,
+            errors: errors.join(", "),
+        })
+    }
+
+    fn new_map_on_drive(
+        drive: &Path,
+        size: u64,
+        stats: &BucketStats,
+    ) -> io::Result<(MmapMut, PathBuf)> {
+        let mut measure_new_file = Measure::start("measure_new_file");
         let pos = format!("{}", thread_rng().gen_range(0, u128::MAX),);
         let file = drive.join(pos);
         let mut data = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
-            .open(file.clone())
-            .map_err(|e| {
-                panic!(
-                    "Unable to create data file {} in current dir({:?}): {:?}",
-                    file.display(),
-                    std::env::current_dir(),
-                    e
-                );
-            })
-            .unwrap();
-
-        // Theoretical performance optimization: write a zero to the end of
-        // the file so that we won't have to resize it later, which may be
-        // expensive.
-        //debug!("GROWING file {}", capacity * cell_size as u64);
-        data.seek(SeekFrom::Start(capacity * cell_size as u64 - 1))
-            .unwrap();
-        data.write_all(&[0]).unwrap();
-        data.seek(SeekFrom::Start(0)).unwrap();
-        measure_new_file.stop();
-        let mut measure_flush = Measure::start("measure_flush");
-        data.flush().unwrap(); // can we skip this?
-        measure_flush.stop();
-        let mut measure_mmap = Measure::start("measure_mmap");
-        let res = (unsafe { MmapMut::map_mut(&data).unwrap() }, file);
-        measure_mmap.stop();
-        stats
-            .new_file_us
-            .fetch_add(measure_new_file.as_us(), Ordering::Relaxed);
-        stats
-            .flush_file_us
-            .fetch_add(measure_flush.as_us(), Ordering::Relaxed);
-        stats
-            .mmap_us
-            .fetch_add(measure_mmap.as_us(), Ordering::Relaxed);
-        res
+            .open(file.clone())?;
+        let result = (|| -> io::Result<MmapMut> {
+            // Theoretical performance optimization: write a zero to the end of
+            // the file so that we won't have to resize it later, which may be
+            // expensive.
+            //debug!("GROWING file {}", size);
+            data.seek(SeekFrom::Start(size - 1))?;
+            data.write_all(&[0])?;
+            data.seek(SeekFrom::Start(0))?;
+            measure_new_file.stop();
+            let mut measure_flush = Measure::start("measure_flush");
+            data.flush()?; // can we skip this?
+            measure_flush.stop();
+            let mut measure_mmap = Measure::start("measure_mmap");
+            let mmap = unsafe { MmapMut::map_mut(&data)? };
+            measure_mmap.stop();
+            stats
+                .new_file_us
+                .fetch_add(measure_new_file.as_us(), Ordering::Relaxed);
+            stats
+                .flush_file_us
+                .fetch_add(measure_flush.as_us(), Ordering::Relaxed);
+            stats
+                .mmap_us
+                .fetch_add(measure_mmap.as_us(), Ordering::Relaxed);
+            Ok(mmap)
+        })();
+        match result {
+            Ok(mmap) => Ok((mmap, file)),
+            Err(err) => {
+                let _ = remove_file(&file);
+                Err(err)
+            }
+        }
     }
 
     /// copy contents from 'old_bucket' to 'self'
@@ -316,7 +382,7 @@ impl BucketStorage {
         num_elems: u64,
         elem_size: u64,
         stats: &Arc<BucketStats>,
-    ) -> Self {
+    ) -> Result<Self, BucketStorageError> {
         let mut new_bucket = Self::new_with_capacity(
             Arc::clone(drives),
             num_elems,
@@ -324,7 +390,7 @@ impl BucketStorage {
             capacity_pow_2,
             max_search,
             Arc::clone(stats),
-        );
+        )?;
         if let Some(bucket) = bucket {
             new_bucket.copy_contents(bucket);
         }
@@ -333,11 +399,16 @@ impl BucketStorage {
             let mut max = new_bucket.stats.max_size.lock().unwrap();
             *max = std::cmp::max(*max, sz);
         }
-        new_bucket
+        Ok(new_bucket)
     }
 
     /// Return the number of cells currently allocated
     pub fn capacity(&self) -> u64 {
         1 << self.capacity_pow2
     }
+
+    /// Return the size of the file backing the cells
+    pub fn bytes(&self) -> u64 {
+        self.capacity() * self.cell_size
+    }
 }
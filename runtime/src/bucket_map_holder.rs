@@ -4,10 +4,14 @@ use {
         bucket_map_holder_stats::BucketMapHolderStats,
         in_mem_accounts_index::{InMemAccountsIndex, SlotT},
         waitable_condvar::WaitableCondvar,
-        bucket_map::bucket_map::{BucketMap, BucketMapConfig},
+        bucket_map::{
+            bucket_api::BucketOccupancy,
+            bucket_map::{BucketMap, BucketMapConfig},
+        },
     },
     mundis_measure::measure::Measure,
     mundis_sdk::{clock::SLOT_MS, timing::AtomicInterval},
+    serde::{Deserialize, Serialize},
     std::{
         fmt::Debug,
         sync::{
@@ -21,6 +25,27 @@ pub type Age = u8;
 
 const AGE_MS: u64 = SLOT_MS; // match one age per slot time
 
+/// How much of the accounts index is held in memory versus only on disk
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsIndexResidency {
+    pub bins: usize,
+    /// entries held in the in-memory index
+    pub count_in_mem: usize,
+    /// memory budget of the in-memory index, `None` if there is no disk index
+    pub mem_budget_mb: Option<usize>,
+    /// entries held in the disk index
+    pub count_on_disk: u64,
+    /// size of the files backing the disk index
+    pub disk_bytes: u64,
+    /// percentage of the disk index cells in use
+    pub disk_index_occupancy_percent: f64,
+    /// percentage of the disk data cells in use
+    pub disk_data_occupancy_percent: f64,
+    /// number of times one of the disk index files was grown
+    pub disk_resizes: u64,
+}
+
 pub struct BucketMapHolder<T: IndexValue> {
     pub disk: Option<BucketMap<SlotT<T>>>,
 
@@ -167,6 +192,25 @@ impl<T: IndexValue> BucketMapHolder<T> {
         }
     }
 
+    pub fn residency(&self) -> AccountsIndexResidency {
+        let mut occupancy = BucketOccupancy::default();
+        if let Some(disk) = self.disk.as_ref() {
+            disk.occupancy()
+                .iter()
+                .for_each(|bucket_occupancy| occupancy.accumulate(bucket_occupancy));
+        }
+        AccountsIndexResidency {
+            bins: self.bins,
+            count_in_mem: self.stats.count_in_mem.load(Ordering::Relaxed),
+            mem_budget_mb: self.mem_budget_mb,
+            count_on_disk: occupancy.index_used,
+            disk_bytes: occupancy.bytes,
+            disk_index_occupancy_percent: occupancy.index_percent(),
+            disk_data_occupancy_percent: occupancy.data_percent(),
+            disk_resizes: occupancy.resizes,
+        }
+    }
+
     // get the next bucket to flush, with the idea that the previous bucket
     // is perhaps being flushed by another thread already.
     pub fn next_bucket_to_flush(&self) -> usize {
@@ -1,5 +1,10 @@
 use {
-    crate::{accounts_index::IndexValue, bucket_map_holder::BucketMapHolder},
+    crate::{
+        accounts_index::IndexValue,
+        bucket_map::{bucket_api::BucketOccupancy, bucket_map::BucketMap},
+        bucket_map_holder::BucketMapHolder,
+        in_mem_accounts_index::SlotT,
+    },
     mundis_sdk::timing::AtomicInterval,
     std::{
         fmt::Debug,
@@ -150,6 +155,54 @@ impl BucketMapHolderStats {
         }
     }
 
+    fn report_disk_stats<T: IndexValue>(disk: &BucketMap<SlotT<T>>) {
+        let occupancy = disk.occupancy();
+        let mut total = BucketOccupancy::default();
+        occupancy
+            .iter()
+            .for_each(|bucket_occupancy| total.accumulate(bucket_occupancy));
+        let index_percent_stats = Self::get_stats(
+            occupancy
+                .iter()
+                .map(|bucket_occupancy| bucket_occupancy.index_percent() as usize)
+                .collect(),
+        );
+        let max_resizes_in_bin = occupancy
+            .iter()
+            .map(|bucket_occupancy| bucket_occupancy.resizes)
+            .max()
+            .unwrap_or_default();
+        let op_totals = disk.stats.take_op_totals();
+
+        datapoint_info!(
+            "accounts_index_disk",
+            ("index_used", total.index_used, i64),
+            ("index_capacity", total.index_capacity, i64),
+            ("data_used", total.data_used, i64),
+            ("data_capacity", total.data_capacity, i64),
+            ("bytes", total.bytes, i64),
+            ("min_index_percent_in_bin", index_percent_stats.0, i64),
+            ("max_index_percent_in_bin", index_percent_stats.1, i64),
+            ("median_index_percent_in_bin", index_percent_stats.3, i64),
+            ("resizes", total.resizes, i64),
+            ("max_resizes_in_bin", max_resizes_in_bin, i64),
+            ("reads", op_totals.reads, i64),
+            ("read_us", op_totals.read_us, i64),
+            ("writes", op_totals.writes, i64),
+            ("write_us", op_totals.write_us, i64),
+            (
+                "index_spills",
+                disk.stats.index.spills.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "data_spills",
+                disk.stats.data.spills.swap(0, Ordering::Relaxed),
+                i64
+            ),
+        );
+    }
+
     pub fn report_stats<T: IndexValue>(&self, storage: &BucketMapHolder<T>) {
         let elapsed_ms = self.last_time.elapsed_ms();
         if elapsed_ms < STATS_INTERVAL_MS {
@@ -426,6 +479,7 @@ impl BucketMapHolderStats {
                     i64
                 ),
             );
+            Self::report_disk_stats(disk.unwrap());
         } else {
             datapoint_info!(
                 if startup || was_startup {
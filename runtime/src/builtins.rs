@@ -238,6 +238,31 @@ fn genesis_builtins() -> Vec<Builtin> {
             "token_account_program",
             mundis_token_account_program::id(),
             with_program_logging!(mundis_token_account_program::token_account_processor::process_instruction),
+        ),
+        Builtin::new(
+            "stake_pool_program",
+            mundis_stake_pool_program::id(),
+            with_program_logging!(mundis_stake_pool_program::stake_pool_processor::process_instruction),
+        ),
+        Builtin::new(
+            "name_service_program",
+            mundis_name_service_program::id(),
+            with_program_logging!(mundis_name_service_program::name_service_processor::process_instruction),
+        ),
+        Builtin::new(
+            "governance_program",
+            mundis_governance_program::id(),
+            with_program_logging!(mundis_governance_program::governance_processor::process_instruction),
+        ),
+        Builtin::new(
+            "escrow_program",
+            mundis_escrow_program::id(),
+            with_program_logging!(mundis_escrow_program::escrow_processor::process_instruction),
+        ),
+        Builtin::new(
+            "wasm_loader_program",
+            mundis_wasm_loader_program::id(),
+            with_program_logging!(mundis_wasm_loader_program::loader_processor::process_instruction),
         )
     ]
 }
@@ -5,9 +5,17 @@
 //! The main function is `calculate_cost` which returns &TransactionCost.
 //!
 use {
-    crate::{block_cost_limits::*, execute_cost_table::ExecuteCostTable},
+    crate::{
+        block_cost_limits::*,
+        execute_cost_table::{
+            builtin_instruction_discriminator, BuiltinInstructionKey, ExecuteCostTable,
+        },
+    },
     log::*,
-    mundis_sdk::{pubkey::Pubkey, transaction::SanitizedTransaction},
+    mundis_sdk::{
+        program_utils::limited_deserialize, pubkey::Pubkey, system_instruction::SystemInstruction,
+        system_program, transaction::SanitizedTransaction,
+    },
     std::collections::HashMap,
 };
 
@@ -21,6 +29,7 @@ pub struct TransactionCost {
     pub write_lock_cost: u64,
     pub data_bytes_cost: u64,
     pub execution_cost: u64,
+    pub account_data_size: u64,
     pub is_simple_vote: bool,
 }
 
@@ -32,6 +41,7 @@ impl Default for TransactionCost {
             write_lock_cost: 0u64,
             data_bytes_cost: 0u64,
             execution_cost: 0u64,
+            account_data_size: 0u64,
             is_simple_vote: false,
         }
     }
@@ -51,6 +61,7 @@ impl TransactionCost {
         self.write_lock_cost = 0;
         self.data_bytes_cost = 0;
         self.execution_cost = 0;
+        self.account_data_size = 0;
         self.is_simple_vote = false;
     }
 
@@ -62,12 +73,14 @@ impl TransactionCost {
 #[derive(Debug, Default)]
 pub struct CostModel {
     instruction_execution_cost_table: ExecuteCostTable,
+    builtin_instruction_execution_cost_table: ExecuteCostTable<BuiltinInstructionKey>,
 }
 
 impl CostModel {
     pub fn new() -> Self {
         Self {
             instruction_execution_cost_table: ExecuteCostTable::default(),
+            builtin_instruction_execution_cost_table: ExecuteCostTable::default(),
         }
     }
 
@@ -101,6 +114,20 @@ impl CostModel {
         );
     }
 
+    pub fn initialize_builtin_instruction_cost_table(
+        &mut self,
+        cost_table: &[(BuiltinInstructionKey, u64)],
+    ) {
+        for (key, cost) in cost_table {
+            self.builtin_instruction_execution_cost_table
+                .upsert(key, *cost);
+        }
+        debug!(
+            "restored cost model builtin instruction cost table from blockstore, current values: {:?}",
+            self.get_builtin_instruction_cost_table()
+        );
+    }
+
     pub fn calculate_cost(&self, transaction: &SanitizedTransaction) -> TransactionCost {
         let mut tx_cost = TransactionCost::new_with_capacity(MAX_WRITABLE_ACCOUNTS);
 
@@ -108,6 +135,7 @@ impl CostModel {
         self.get_write_lock_cost(&mut tx_cost, transaction);
         tx_cost.data_bytes_cost = self.get_data_bytes_cost(transaction);
         tx_cost.execution_cost = self.get_transaction_cost(transaction);
+        tx_cost.account_data_size = self.calculate_account_data_size(transaction);
         tx_cost.is_simple_vote = transaction.is_simple_vote_transaction();
 
         debug!("transaction {:?} has cost {:?}", transaction, tx_cost);
@@ -131,6 +159,33 @@ impl CostModel {
         self.instruction_execution_cost_table.get_cost_table()
     }
 
+    pub fn upsert_builtin_instruction_cost(
+        &mut self,
+        key: &BuiltinInstructionKey,
+        cost: u64,
+    ) -> Result<u64, &'static str> {
+        self.builtin_instruction_execution_cost_table
+            .upsert(key, cost);
+        match self.builtin_instruction_execution_cost_table.get_cost(key) {
+            Some(cost) => Ok(*cost),
+            None => Err("failed to upsert to ExecuteCostTable"),
+        }
+    }
+
+    pub fn get_builtin_instruction_cost_table(&self) -> &HashMap<BuiltinInstructionKey, u64> {
+        self.builtin_instruction_execution_cost_table
+            .get_cost_table()
+    }
+
+    /// Learned cost of a builtin program instruction, falling back to the cost of its program
+    /// until the instruction has been seen
+    pub fn find_builtin_instruction_cost(&self, key: &BuiltinInstructionKey) -> u64 {
+        match self.builtin_instruction_execution_cost_table.get_cost(key) {
+            Some(cost) => *cost,
+            None => self.find_instruction_cost(&key.0),
+        }
+    }
+
     pub fn find_instruction_cost(&self, program_key: &Pubkey) -> u64 {
         match self.instruction_execution_cost_table.get_cost(program_key) {
             Some(cost) => *cost,
@@ -184,7 +239,12 @@ impl CostModel {
         let mut cost: u64 = 0;
 
         for (program_id, instruction) in transaction.message().program_instructions_iter() {
-            let instruction_cost = self.find_instruction_cost(program_id);
+            let instruction_cost = match builtin_instruction_discriminator(&instruction.data) {
+                Some(discriminator) => {
+                    self.find_builtin_instruction_cost(&(*program_id, discriminator))
+                }
+                None => self.find_instruction_cost(program_id),
+            };
             trace!(
                 "instruction {:?} has cost of {}",
                 instruction,
@@ -194,6 +254,24 @@ impl CostModel {
         }
         cost
     }
+
+    /// Number of bytes of account data the system program instructions of a transaction allocate
+    fn calculate_account_data_size(&self, transaction: &SanitizedTransaction) -> u64 {
+        transaction
+            .message()
+            .program_instructions_iter()
+            .filter(|(program_id, _)| system_program::check_id(program_id))
+            .filter_map(
+                |(_, instruction)| match limited_deserialize(&instruction.data) {
+                    Ok(SystemInstruction::CreateAccount { space, .. })
+                    | Ok(SystemInstruction::CreateAccountWithSeed { space, .. })
+                    | Ok(SystemInstruction::Allocate { space })
+                    | Ok(SystemInstruction::AllocateWithSeed { space, .. }) => Some(space),
+                    _ => None,
+                },
+            )
+            .fold(0, |total, space| total.saturating_add(space))
+    }
 }
 
 #[cfg(test)]
@@ -311,6 +389,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cost_model_builtin_instruction_cost() {
+        let (mint_keypair, start_hash) = test_setup();
+
+        let transfer_transaction = SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&mint_keypair, &Keypair::new().pubkey(), 2, start_hash),
+        );
+        let create_account_transaction =
+            SanitizedTransaction::from_transaction_for_tests(system_transaction::create_account(
+                &mint_keypair,
+                &Keypair::new(),
+                start_hash,
+                2,
+                10,
+                &Pubkey::new_unique(),
+            ));
+
+        // builtin instructions cost as much as their program until they've been seen
+        let mut testee = CostModel::default();
+        testee
+            .upsert_instruction_cost(&system_program::id(), 100)
+            .unwrap();
+        assert_eq!(100, testee.get_transaction_cost(&transfer_transaction));
+        assert_eq!(
+            100,
+            testee.get_transaction_cost(&create_account_transaction)
+        );
+
+        // then each instruction is costed on its own
+        let transfer_discriminator = 2;
+        testee
+            .upsert_builtin_instruction_cost(&(system_program::id(), transfer_discriminator), 40)
+            .unwrap();
+        assert_eq!(40, testee.get_transaction_cost(&transfer_transaction));
+        assert_eq!(
+            100,
+            testee.get_transaction_cost(&create_account_transaction)
+        );
+
+        testee.initialize_builtin_instruction_cost_table(&[((system_program::id(), 0), 300)]);
+        assert_eq!(40, testee.get_transaction_cost(&transfer_transaction));
+        assert_eq!(
+            300,
+            testee.get_transaction_cost(&create_account_transaction)
+        );
+        assert_eq!(2, testee.get_builtin_instruction_cost_table().len());
+    }
+
     #[test]
     fn test_cost_model_transaction_many_transfer_instructions() {
         let (mint_keypair, start_hash) = test_setup();
@@ -444,6 +570,33 @@ mod tests {
         assert_eq!(2, tx_cost.writable_accounts.len());
     }
 
+    #[test]
+    fn test_cost_model_calculate_account_data_size() {
+        let (mint_keypair, start_hash) = test_setup();
+        let new_account = Keypair::new();
+        let allocated_account = Keypair::new();
+        let instructions = vec![
+            system_instruction::create_account(
+                &mint_keypair.pubkey(),
+                &new_account.pubkey(),
+                1,
+                100,
+                &Pubkey::new_unique(),
+            ),
+            system_instruction::allocate(&allocated_account.pubkey(), 200),
+            system_instruction::transfer(&mint_keypair.pubkey(), &Pubkey::new_unique(), 1),
+        ];
+        let message = Message::new(&instructions, Some(&mint_keypair.pubkey()));
+        let tx = SanitizedTransaction::from_transaction_for_tests(Transaction::new(
+            &[&mint_keypair, &new_account, &allocated_account],
+            message,
+            start_hash,
+        ));
+
+        let tx_cost = CostModel::default().calculate_cost(&tx);
+        assert_eq!(300, tx_cost.account_data_size);
+    }
+
     #[test]
     fn test_cost_model_update_instruction_cost() {
         let key1 = Pubkey::new_unique();
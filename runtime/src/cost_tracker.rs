@@ -2,6 +2,9 @@
 //! The main functions are:
 //! - would_fit(&tx_cost), immutable function to test if tx with tx_cost would fit into current block
 //! - add_transaction_cost(&tx_cost), mutable function to accumulate tx_cost to tracker.
+//!   Besides compute units, the tracker caps the new account data allocated in a block.
+//! - local_minimum_priority_fee(&account), the priority fee a transaction write locking a
+//!   contended account should pay to be preferred in current block.
 //!
 use {
     crate::{block_cost_limits::*, cost_model::TransactionCost},
@@ -11,6 +14,13 @@ use {
 
 const WRITABLE_ACCOUNTS_PER_BLOCK: usize = 512;
 
+/// Percentage of an account's cost limit that can be used in a block before transactions write
+/// locking it are required to pay a priority fee
+pub const LOCAL_FEE_MARKET_THRESHOLD_PERCENT: u64 = 50;
+/// Minimum priority fee, in lamports, for transactions write locking an account whose cost limit
+/// is used up. The minimum grows linearly from zero at `LOCAL_FEE_MARKET_THRESHOLD_PERCENT`.
+pub const MAX_LOCAL_MINIMUM_PRIORITY_FEE: u64 = 1_000_000;
+
 #[derive(Debug, Clone)]
 pub enum CostTrackerError {
     /// would exceed block max limit
@@ -21,6 +31,9 @@ pub enum CostTrackerError {
 
     /// would exceed account max limit
     WouldExceedAccountMaxLimit,
+
+    /// would exceed account data block limit
+    WouldExceedAccountDataBlockLimit,
 }
 
 #[derive(AbiExample, Debug)]
@@ -28,10 +41,12 @@ pub struct CostTracker {
     account_cost_limit: u64,
     block_cost_limit: u64,
     vote_cost_limit: u64,
+    account_data_size_limit: u64,
     cost_by_writable_accounts: HashMap<Pubkey, u64>,
     block_cost: u64,
     vote_cost: u64,
     transaction_count: u64,
+    account_data_size: u64,
 }
 
 impl Default for CostTracker {
@@ -46,10 +61,12 @@ impl Default for CostTracker {
             account_cost_limit: MAX_WRITABLE_ACCOUNT_UNITS,
             block_cost_limit: MAX_BLOCK_UNITS,
             vote_cost_limit: MAX_VOTE_UNITS,
+            account_data_size_limit: MAX_ACCOUNT_DATA_LEN,
             cost_by_writable_accounts: HashMap::with_capacity(WRITABLE_ACCOUNTS_PER_BLOCK),
             block_cost: 0,
             vote_cost: 0,
             transaction_count: 0,
+            account_data_size: 0,
         }
     }
 }
@@ -62,10 +79,12 @@ impl CostTracker {
             account_cost_limit,
             block_cost_limit,
             vote_cost_limit,
+            account_data_size_limit: MAX_ACCOUNT_DATA_LEN,
             cost_by_writable_accounts: HashMap::with_capacity(WRITABLE_ACCOUNTS_PER_BLOCK),
             block_cost: 0,
             vote_cost: 0,
             transaction_count: 0,
+            account_data_size: 0,
         }
     }
 
@@ -118,6 +137,61 @@ impl CostTracker {
         self.block_cost
     }
 
+    /// Number of bytes of new account data allocated by the transactions in the block
+    pub fn account_data_size(&self) -> u64 {
+        self.account_data_size
+    }
+
+    /// Accumulates the cost of a transaction that was already included in the block, regardless
+    /// of limits. Used when replaying blocks, to track the contention of their accounts.
+    pub fn add_executed_transaction_cost(&mut self, tx_cost: &TransactionCost) {
+        self.add_transaction_cost(tx_cost);
+    }
+
+    /// Returns the minimum priority fee, in lamports, for a transaction write locking `account`
+    /// to be preferred in the current block
+    pub fn local_minimum_priority_fee(&self, account: &Pubkey) -> u64 {
+        self.cost_by_writable_accounts
+            .get(account)
+            .map(|cost| self.minimum_priority_fee_for_cost(*cost))
+            .unwrap_or_default()
+    }
+
+    /// Returns the highest local minimum priority fee of the accounts written by a transaction
+    pub fn transaction_minimum_priority_fee(&self, tx_cost: &TransactionCost) -> u64 {
+        tx_cost
+            .writable_accounts
+            .iter()
+            .map(|account| self.local_minimum_priority_fee(account))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns every contended account along with its local minimum priority fee
+    pub fn local_minimum_priority_fees(&self) -> HashMap<Pubkey, u64> {
+        self.cost_by_writable_accounts
+            .iter()
+            .filter_map(|(account, cost)| {
+                let fee = self.minimum_priority_fee_for_cost(*cost);
+                (fee > 0).then(|| (*account, fee))
+            })
+            .collect()
+    }
+
+    fn minimum_priority_fee_for_cost(&self, account_cost: u64) -> u64 {
+        let threshold = self
+            .account_cost_limit
+            .saturating_mul(LOCAL_FEE_MARKET_THRESHOLD_PERCENT)
+            / 100;
+        if account_cost <= threshold {
+            return 0;
+        }
+        let contended_cost = account_cost.min(self.account_cost_limit) - threshold;
+        let contended_range = self.account_cost_limit - threshold;
+        (MAX_LOCAL_MINIMUM_PRIORITY_FEE as u128 * contended_cost as u128 / contended_range as u128)
+            as u64
+    }
+
     pub fn transaction_count(&self) -> u64 {
         self.transaction_count
     }
@@ -136,6 +210,7 @@ impl CostTracker {
             ("block_cost", self.block_cost as i64, i64),
             ("vote_cost", self.vote_cost as i64, i64),
             ("transaction_count", self.transaction_count as i64, i64),
+            ("account_data_size", self.account_data_size as i64, i64),
             ("number_of_accounts", self.number_of_accounts() as i64, i64),
             ("costliest_account", costliest_account.to_string(), String),
             ("costliest_account_cost", costliest_account_cost as i64, i64),
@@ -170,6 +245,15 @@ impl CostTracker {
             return Err(CostTrackerError::WouldExceedVoteMaxLimit);
         }
 
+        // check if the new account data allocated would exceed the block's limit
+        if self
+            .account_data_size
+            .saturating_add(tx_cost.account_data_size)
+            > self.account_data_size_limit
+        {
+            return Err(CostTrackerError::WouldExceedAccountDataBlockLimit);
+        }
+
         // check if the transaction itself is more costly than the account_cost_limit
         if cost > self.account_cost_limit {
             return Err(CostTrackerError::WouldExceedAccountMaxLimit);
@@ -195,12 +279,16 @@ impl CostTracker {
     fn add_transaction_cost(&mut self, tx_cost: &TransactionCost) {
         let cost = tx_cost.sum();
         self.add_transaction_execution_cost(tx_cost, cost);
+        saturating_add_assign!(self.account_data_size, tx_cost.account_data_size);
         saturating_add_assign!(self.transaction_count, 1);
     }
 
     fn remove_transaction_cost(&mut self, tx_cost: &TransactionCost) {
         let cost = tx_cost.sum();
         self.sub_transaction_execution_cost(tx_cost, cost);
+        self.account_data_size = self
+            .account_data_size
+            .saturating_sub(tx_cost.account_data_size);
         self.transaction_count = self.transaction_count.saturating_sub(1);
     }
 
@@ -486,6 +574,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cost_tracker_reach_account_data_block_limit() {
+        let mut testee = CostTracker::default();
+        let tx_cost = |account_data_size: u64| TransactionCost {
+            writable_accounts: vec![Pubkey::new_unique()],
+            account_data_size,
+            ..TransactionCost::default()
+        };
+
+        let first_tx_cost = tx_cost(MAX_ACCOUNT_DATA_LEN - 1);
+        assert!(testee.try_add(&first_tx_cost).is_ok());
+        assert_eq!(MAX_ACCOUNT_DATA_LEN - 1, testee.account_data_size());
+
+        // no room for two more bytes of new account data in the block
+        assert!(matches!(
+            testee.try_add(&tx_cost(2)),
+            Err(CostTrackerError::WouldExceedAccountDataBlockLimit)
+        ));
+        assert_eq!(MAX_ACCOUNT_DATA_LEN - 1, testee.account_data_size());
+
+        // transactions that allocate no account data still fit
+        assert!(testee.try_add(&tx_cost(0)).is_ok());
+        assert!(testee.try_add(&tx_cost(1)).is_ok());
+
+        // removing a transaction frees its account data
+        testee.remove(&first_tx_cost);
+        assert_eq!(1, testee.account_data_size());
+    }
+
     #[test]
     fn test_cost_tracker_try_add_is_atomic() {
         let acct1 = Pubkey::new_unique();
@@ -719,4 +836,50 @@ mod tests {
         assert_eq!(0, cost_tracker.block_cost);
         assert_eq!(0, cost_tracker.vote_cost);
     }
+
+    #[test]
+    fn test_local_minimum_priority_fee() {
+        let account_cost_limit = 1_000;
+        let mut cost_tracker = CostTracker::new(account_cost_limit, 10_000, 10_000);
+        let hot_account = Pubkey::new_unique();
+        let cold_account = Pubkey::new_unique();
+        let tx_cost = |writable_accounts: Vec<Pubkey>, execution_cost: u64| TransactionCost {
+            writable_accounts,
+            execution_cost,
+            ..TransactionCost::default()
+        };
+
+        // No fee is required up to the threshold
+        cost_tracker.add_transaction_cost(&tx_cost(vec![hot_account, cold_account], 500));
+        assert_eq!(cost_tracker.local_minimum_priority_fee(&hot_account), 0);
+        assert_eq!(
+            cost_tracker.local_minimum_priority_fee(&Pubkey::new_unique()),
+            0
+        );
+        assert!(cost_tracker.local_minimum_priority_fees().is_empty());
+
+        // Then the fee grows with the account's contention only
+        cost_tracker.add_transaction_cost(&tx_cost(vec![hot_account], 250));
+        assert_eq!(
+            cost_tracker.local_minimum_priority_fee(&hot_account),
+            MAX_LOCAL_MINIMUM_PRIORITY_FEE / 2
+        );
+        assert_eq!(cost_tracker.local_minimum_priority_fee(&cold_account), 0);
+        assert_eq!(
+            cost_tracker
+                .transaction_minimum_priority_fee(&tx_cost(vec![cold_account, hot_account], 1)),
+            MAX_LOCAL_MINIMUM_PRIORITY_FEE / 2
+        );
+        assert_eq!(
+            cost_tracker.local_minimum_priority_fees(),
+            HashMap::from([(hot_account, MAX_LOCAL_MINIMUM_PRIORITY_FEE / 2)])
+        );
+
+        // Up to the maximum once the account's limit is reached
+        cost_tracker.add_executed_transaction_cost(&tx_cost(vec![hot_account], 500));
+        assert_eq!(
+            cost_tracker.local_minimum_priority_fee(&hot_account),
+            MAX_LOCAL_MINIMUM_PRIORITY_FEE
+        );
+    }
 }
\ No newline at end of file
@@ -1,10 +1,14 @@
 /// ExecuteCostTable is aggregated by Cost Model, it keeps each program's
 /// average cost in its HashMap, with fixed capacity to avoid from growing
-/// unchecked.
+/// unchecked. Builtin program instructions are kept in a separate table keyed
+/// by program id and instruction discriminator.
 /// When its capacity limit is reached, it prunes old and less-used programs
 /// to make room for new ones.
 use log::*;
-use {mundis_sdk::pubkey::Pubkey, std::collections::HashMap};
+use {
+    mundis_sdk::pubkey::Pubkey,
+    std::{collections::HashMap, hash::Hash},
+};
 
 // prune is rather expensive op, free up bulk space in each operation
 // would be more efficient. PRUNE_RATIO defines the after prune table
@@ -15,20 +19,33 @@ const OCCURRENCES_WEIGHT: i64 = 100;
 
 const DEFAULT_CAPACITY: usize = 1024;
 
+/// Key of a builtin program instruction: the program id and the instruction discriminator
+pub type BuiltinInstructionKey = (Pubkey, u32);
+
+/// Builtin programs deserialize their instructions with bincode, which encodes the
+/// instruction enum variant as a leading little-endian u32
+pub fn builtin_instruction_discriminator(instruction_data: &[u8]) -> Option<u32> {
+    instruction_data
+        .get(..4)?
+        .try_into()
+        .ok()
+        .map(u32::from_le_bytes)
+}
+
 #[derive(AbiExample, Debug)]
-pub struct ExecuteCostTable {
+pub struct ExecuteCostTable<K: Copy + Eq + Hash = Pubkey> {
     capacity: usize,
-    table: HashMap<Pubkey, u64>,
-    occurrences: HashMap<Pubkey, (usize, u128)>,
+    table: HashMap<K, u64>,
+    occurrences: HashMap<K, (usize, u128)>,
 }
 
-impl Default for ExecuteCostTable {
+impl<K: Copy + Eq + Hash> Default for ExecuteCostTable<K> {
     fn default() -> Self {
         ExecuteCostTable::new(DEFAULT_CAPACITY)
     }
 }
 
-impl ExecuteCostTable {
+impl<K: Copy + Eq + Hash> ExecuteCostTable<K> {
     pub fn new(cap: usize) -> Self {
         Self {
             capacity: cap,
@@ -37,7 +54,7 @@ impl ExecuteCostTable {
         }
     }
 
-    pub fn get_cost_table(&self) -> &HashMap<Pubkey, u64> {
+    pub fn get_cost_table(&self) -> &HashMap<K, u64> {
         &self.table
     }
 
@@ -73,11 +90,11 @@ impl ExecuteCostTable {
     // returns None if program doesn't exist in table. In this case,
     // client is advised to call `get_average()` or `get_mode()` to
     // assign a 'default' value for new program.
-    pub fn get_cost(&self, key: &Pubkey) -> Option<&u64> {
+    pub fn get_cost(&self, key: &K) -> Option<&u64> {
         self.table.get(key)
     }
 
-    pub fn upsert(&mut self, key: &Pubkey, value: u64) -> Option<u64> {
+    pub fn upsert(&mut self, key: &K, value: u64) -> Option<u64> {
         let need_to_add = self.table.get(key).is_none();
         let current_size = self.get_count();
         if current_size == self.capacity && need_to_add {
@@ -97,7 +114,7 @@ impl ExecuteCostTable {
         Some(*program_cost)
     }
 
-    pub fn get_program_keys(&self) -> Vec<&Pubkey> {
+    pub fn get_program_keys(&self) -> Vec<&K> {
         self.table.keys().collect()
     }
 
@@ -156,6 +173,30 @@ impl ExecuteCostTable {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_builtin_instruction_discriminator() {
+        assert_eq!(builtin_instruction_discriminator(&[]), None);
+        assert_eq!(builtin_instruction_discriminator(&[2, 0, 0]), None);
+        assert_eq!(builtin_instruction_discriminator(&[2, 0, 0, 0]), Some(2));
+        assert_eq!(
+            builtin_instruction_discriminator(&[1, 1, 0, 0, 42, 42]),
+            Some(257)
+        );
+    }
+
+    #[test]
+    fn test_execute_cost_table_builtin_instruction_keys() {
+        let mut testee = ExecuteCostTable::<BuiltinInstructionKey>::default();
+        let program_id = Pubkey::new_unique();
+
+        testee.upsert(&(program_id, 0), 100);
+        testee.upsert(&(program_id, 1), 300);
+        assert_eq!(2, testee.get_count());
+        assert_eq!(&100, testee.get_cost(&(program_id, 0)).unwrap());
+        assert_eq!(&300, testee.get_cost(&(program_id, 1)).unwrap());
+        assert!(testee.get_cost(&(program_id, 2)).is_none());
+    }
+
     #[test]
     fn test_execute_cost_table_prune_simple_table() {
         mundis_logger::setup();
@@ -8,6 +8,7 @@ use {
         bucket_map_holder_stats::BucketMapHolderStats,
         bucket_map::bucket_api::BucketApi
     },
+    log::*,
     rand::{thread_rng, Rng},
     mundis_measure::measure::Measure,
     mundis_sdk::{clock::Slot, pubkey::Pubkey},
@@ -239,7 +240,12 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
 
     fn delete_disk_key(&self, pubkey: &Pubkey) {
         if let Some(disk) = self.bucket.as_ref() {
-            disk.delete_key(pubkey)
+            if let Err(err) = disk.delete_key(pubkey) {
+                panic!(
+                    "Unable to delete {} from the disk-backed accounts index: {:?}",
+                    pubkey, err
+                );
+            }
         }
     }
 
@@ -575,6 +581,13 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
                     let ref_count = if account_info.is_cached() { 0 } else { 1 };
                     Some((vec![(slot, account_info)], ref_count))
                 }
+            })
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Unable to update {} in the disk-backed accounts index: {:?}",
+                    vacant.key(),
+                    err
+                )
             });
             existed
         } else {
@@ -830,8 +843,18 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
                 Err(err) => {
                     // grow the bucket, outside of all in-mem locks.
                     // then, loop to try again
-                    disk.grow(err);
+                    let grown = disk.grow(err);
                     Self::update_time_stat(&self.stats().flush_grow_us, m);
+                    if let Err(err) = grown {
+                        // The unflushed entries are still dirty, so they stay in memory until a
+                        // later flush, once there is room on the drives again
+                        error!(
+                            "Unable to grow the disk-backed accounts index, keeping bin {} in memory: {:?}",
+                            self.bin, err
+                        );
+                        self.set_bin_dirty();
+                        return;
+                    }
                 }
             }
         }
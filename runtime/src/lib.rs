@@ -11,6 +11,7 @@ pub mod accounts_index;
 pub mod accounts_index_storage;
 pub mod accounts_update_notifier_interface;
 pub mod ancestors;
+pub mod ancient_append_vecs;
 pub mod append_vec;
 pub mod bank;
 pub mod bank_client;
@@ -43,6 +44,7 @@ mod pubkey_bins;
 mod read_only_accounts_cache;
 pub mod rent_collector;
 pub mod secondary_index;
+pub mod seekable_zstd;
 pub mod serde_snapshot;
 mod shared_buffer_reader;
 pub mod snapshot_archive_info;
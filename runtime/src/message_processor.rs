@@ -1,4 +1,5 @@
 use {
+    crate::execute_cost_table::builtin_instruction_discriminator,
     serde::{Deserialize, Serialize},
     mundis_measure::measure::Measure,
     mundis_program_runtime::{
@@ -20,7 +21,7 @@ use {
         rent::Rent,
         saturating_add_assign,
         sysvar::instructions,
-        transaction::TransactionError,
+        transaction::{TransactionError, TransactionReturnData},
     },
     std::{borrow::Cow, cell::RefCell, rc::Rc, sync::Arc},
 };
@@ -71,6 +72,7 @@ impl MessageProcessor {
         lamports_per_signature: u64,
         current_accounts_data_len: u64,
         accumulated_consumed_units: &mut u64,
+        return_data: &mut Option<TransactionReturnData>,
     ) -> Result<ProcessedMessageInfo, TransactionError> {
         let mut invoke_context = InvokeContext::new(
             rent,
@@ -132,6 +134,20 @@ impl MessageProcessor {
                 compute_units_consumed,
                 result.is_err(),
             );
+            if let Some(discriminator) = builtin_instruction_discriminator(&instruction.data) {
+                if builtin_programs
+                    .iter()
+                    .any(|builtin_program| builtin_program.program_id == *program_id)
+                {
+                    timings.details.accumulate_builtin_instruction(
+                        program_id,
+                        discriminator,
+                        time.as_us(),
+                        compute_units_consumed,
+                        result.is_err(),
+                    );
+                }
+            }
             invoke_context.timings = {
                 timings.details.accumulate(&invoke_context.timings);
                 ExecuteDetailsTimings::default()
@@ -141,6 +157,16 @@ impl MessageProcessor {
                 time.as_us()
             );
 
+            let (program_id, data) = &invoke_context.return_data;
+            *return_data = if data.is_empty() {
+                None
+            } else {
+                Some(TransactionReturnData {
+                    program_id: *program_id,
+                    data: data.clone(),
+                })
+            };
+
             result.map_err(|err| {
                 instruction_trace.append(invoke_context.get_instruction_trace_mut());
                 TransactionError::InstructionError(instruction_index as u8, err)
@@ -260,6 +286,7 @@ mod tests {
             Some(&accounts[0].0),
         ));
         let sysvar_cache = SysvarCache::default();
+        let mut timings = ExecuteTimings::default();
         let result = MessageProcessor::process_message(
             builtin_programs,
             &message,
@@ -271,16 +298,26 @@ mod tests {
             &mut Vec::new(),
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
-            &mut ExecuteTimings::default(),
+            &mut timings,
             &sysvar_cache,
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert!(result.is_ok());
         assert_eq!(accounts[0].1.borrow().lamports(), 100);
         assert_eq!(accounts[1].1.borrow().lamports(), 0);
+        // Builtin instructions are timed by discriminator, `Correct` being the first variant
+        assert_eq!(
+            timings
+                .details
+                .per_builtin_instruction_timings
+                .get(&(mock_system_program_id, 0))
+                .map(|instruction_timings| instruction_timings.count),
+            Some(1)
+        );
 
         let message = SanitizedMessage::Legacy(Message::new(
             &[Instruction::new_with_bincode(
@@ -307,7 +344,8 @@ mod tests {
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -342,7 +380,8 @@ mod tests {
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -489,7 +528,8 @@ mod tests {
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -524,7 +564,8 @@ mod tests {
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert!(result.is_ok());
 
@@ -556,7 +597,8 @@ mod tests {
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert!(result.is_ok());
         assert_eq!(accounts[0].1.borrow().lamports(), 80);
@@ -615,7 +657,8 @@ mod tests {
             Hash::default(),
             0,
             0,
-            &mut 0
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
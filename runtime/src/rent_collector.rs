@@ -85,12 +85,17 @@ impl RentCollector {
     // Updates the account's lamports and status, and returns the amount of rent collected, if any.
     // This is NOT thread safe at some level. If we try to collect from the same account in
     // parallel, we may collect twice.
+    // With `rent_exempt_only` nothing is collected any more: the rent epoch still advances
+    // exactly as before, so eager rent collection keeps rewriting the same accounts and the
+    // accounts hash evolves identically on every node, but rent-paying accounts left over from
+    // before keep their lamports until a transaction tops them up or closes them.
     #[must_use = "add to Bank::collected_rent"]
     pub fn collect_from_existing_account(
         &self,
         address: &Pubkey,
         account: &mut AccountSharedData,
         rent_for_sysvars: bool,
+        rent_exempt_only: bool,
         filler_account_suffix: Option<&Pubkey>,
     ) -> CollectedInfo {
         if self.can_skip_rent_collection(address, account, rent_for_sysvars, filler_account_suffix)
@@ -112,6 +117,9 @@ impl RentCollector {
             RentDue::Paying(_) => 1,
         };
         account.set_rent_epoch(self.epoch + epoch_increment);
+        if rent_exempt_only {
+            return CollectedInfo::default();
+        }
 
         let begin_lamports = account.lamports();
         account.saturating_sub_lamports(rent_due.lamports());
@@ -138,7 +146,9 @@ impl RentCollector {
     ) -> CollectedInfo {
         // initialize rent_epoch as created at this epoch
         account.set_rent_epoch(self.epoch);
-        self.collect_from_existing_account(address, account, rent_for_sysvars, None)
+        // Accounts stored by transactions are never left rent-paying under rent-exempt-only
+        // accounts, so there is no rent to skip here
+        self.collect_from_existing_account(address, account, rent_for_sysvars, false, None)
     }
 
     /// Performs easy checks to see if rent collection can be skipped
@@ -225,6 +235,7 @@ mod tests {
             &mundis_sdk::pubkey::new_rand(),
             &mut existing_account,
             true,
+            false,
             None,
         );
         assert!(existing_account.lamports() < old_lamports);
@@ -256,7 +267,7 @@ mod tests {
 
         // first mark account as being collected while being rent-exempt
         let collected =
-            rent_collector.collect_from_existing_account(&pubkey, &mut account, true, None);
+            rent_collector.collect_from_existing_account(&pubkey, &mut account, true, false, None);
         assert_eq!(account.lamports(), huge_lamports);
         assert_eq!(collected, CollectedInfo::default());
 
@@ -265,7 +276,7 @@ mod tests {
 
         // ... and trigger another rent collection on the same epoch and check that rent is working
         let collected =
-            rent_collector.collect_from_existing_account(&pubkey, &mut account, true, None);
+            rent_collector.collect_from_existing_account(&pubkey, &mut account, true, false, None);
         assert_eq!(account.lamports(), tiny_lamports - collected.rent_amount);
         assert_ne!(collected, CollectedInfo::default());
     }
@@ -286,13 +297,13 @@ mod tests {
 
         // old behavior: sysvars are special-cased
         let collected =
-            rent_collector.collect_from_existing_account(&pubkey, &mut account, false, None);
+            rent_collector.collect_from_existing_account(&pubkey, &mut account, false, false, None);
         assert_eq!(account.lamports(), tiny_lamports);
         assert_eq!(collected, CollectedInfo::default());
 
         // new behavior: sysvars are NOT special-cased
         let collected =
-            rent_collector.collect_from_existing_account(&pubkey, &mut account, true, None);
+            rent_collector.collect_from_existing_account(&pubkey, &mut account, true, false, None);
         assert_eq!(account.lamports(), 0);
         assert_eq!(collected.rent_amount, 1);
     }
@@ -316,6 +327,7 @@ mod tests {
             &Pubkey::new_unique(),
             &mut account,
             true,
+            false,
             None,
         );
 
@@ -326,4 +338,32 @@ mod tests {
         );
         assert_eq!(account, AccountSharedData::default());
     }
+
+    #[test]
+    fn test_collect_nothing_when_rent_exempt_only() {
+        let account_lamports = 1; // below the rent-exempt minimum
+        let account_data_len = 567;
+        let account_rent_epoch = 11;
+        let mut account = AccountSharedData::from(Account {
+            lamports: account_lamports,
+            data: vec![u8::default(); account_data_len],
+            rent_epoch: account_rent_epoch,
+            ..Account::default()
+        });
+        let rent_collector = RentCollector::default().clone_with_epoch(account_rent_epoch + 2);
+
+        let collected = rent_collector.collect_from_existing_account(
+            &Pubkey::new_unique(),
+            &mut account,
+            true,
+            true,
+            None,
+        );
+
+        // The account is left alone apart from its rent epoch, which advances as before
+        assert_eq!(collected, CollectedInfo::default());
+        assert_eq!(account.lamports(), account_lamports);
+        assert_eq!(account.data().len(), account_data_len);
+        assert_eq!(account.rent_epoch(), rent_collector.epoch + 1);
+    }
 }
@@ -1,9 +1,13 @@
 use {
     dashmap::{mapref::entry::Entry::Occupied, DashMap},
-    mundis_sdk::{pubkey::Pubkey, timing::AtomicInterval},
+    mundis_sdk::{
+        pubkey::{Pubkey, PUBKEY_BYTES},
+        timing::AtomicInterval,
+    },
     std::{
         collections::HashSet,
-        fmt::Debug,
+        fmt::{self, Debug},
+        str::FromStr,
         sync::{
             atomic::{AtomicU64, Ordering},
             RwLock,
@@ -26,6 +30,75 @@ pub trait SecondaryIndexEntry: Debug {
     fn len(&self) -> usize;
 }
 
+/// A secondary index over the accounts owned by `program_id`, keyed by the `len` bytes of
+/// account data found at `offset`.  Parsed from `<program_id>:<offset>:<len>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomAccountIndex {
+    pub program_id: Pubkey,
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl CustomAccountIndex {
+    /// The index key of an indexed field.  Like the other secondary indexes this one is keyed
+    /// by `Pubkey`, so fields shorter than a pubkey are zero padded to `PUBKEY_BYTES`.
+    pub fn key(&self, field: &[u8]) -> Option<Pubkey> {
+        if field.len() != self.len {
+            return None;
+        }
+        let mut key = [0u8; PUBKEY_BYTES];
+        key[..self.len].copy_from_slice(field);
+        Some(Pubkey::new_from_array(key))
+    }
+
+    /// The index key of an account owned by `program_id`, or `None` if its data is too short
+    /// to hold the indexed field
+    pub fn key_from_account_data(&self, account_data: &[u8]) -> Option<Pubkey> {
+        account_data
+            .get(self.offset..self.offset.checked_add(self.len)?)
+            .and_then(|field| self.key(field))
+    }
+}
+
+impl FromStr for CustomAccountIndex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (program_id, offset, len) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(program_id), Some(offset), Some(len)) if parts.next().is_none() => {
+                (program_id, offset, len)
+            }
+            _ => return Err(format!("expected <program_id>:<offset>:<len>, got {}", s)),
+        };
+        let program_id = Pubkey::from_str(program_id)
+            .map_err(|err| format!("invalid program id {}: {}", program_id, err))?;
+        let offset = offset
+            .parse::<usize>()
+            .map_err(|err| format!("invalid offset {}: {}", offset, err))?;
+        let len = len
+            .parse::<usize>()
+            .map_err(|err| format!("invalid length {}: {}", len, err))?;
+        if len == 0 || len > PUBKEY_BYTES {
+            return Err(format!(
+                "length must be between 1 and {} bytes, got {}",
+                PUBKEY_BYTES, len
+            ));
+        }
+        Ok(Self {
+            program_id,
+            offset,
+            len,
+        })
+    }
+}
+
+impl fmt::Display for CustomAccountIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.program_id, self.offset, self.len)
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct SecondaryIndexStats {
     last_report: AtomicInterval,
@@ -0,0 +1,364 @@
+//! Chunked, seekable zstd streams for snapshot archives.
+//!
+//! A seekable stream is a sequence of independent zstd frames, each holding at most
+//! `SEEKABLE_ZSTD_FRAME_SIZE` bytes of uncompressed data, followed by a seek table stored in a
+//! skippable frame, as described by zstd's seekable format (without per-frame checksums).
+//! Since every frame is self contained, any zstd decoder can still read the stream
+//! sequentially, while `SeekableZstdDecoder` decompresses many frames at once and hands the
+//! data back in order.
+use {
+    byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+    crossbeam_channel::{bounded, Receiver},
+    log::*,
+    rayon::prelude::*,
+    std::{
+        fs::File,
+        io::{self, BufReader, Error as IoError, ErrorKind, Read, Seek, SeekFrom, Write},
+        thread::Builder,
+    },
+};
+
+/// Uncompressed bytes per frame written by `SeekableZstdEncoder`
+pub const SEEKABLE_ZSTD_FRAME_SIZE: usize = 16 * 1024 * 1024;
+/// Frames claiming to decompress to more than this are rejected, which bounds how much memory
+/// a malicious seek table can make the decoder allocate
+const MAX_SEEKABLE_ZSTD_FRAME_SIZE: u32 = 64 * 1024 * 1024;
+
+const SKIPPABLE_FRAME_MAGIC: u32 = 0x184D_2A5E;
+const SEEKABLE_MAGIC: u32 = 0x8F92_EAB1;
+const SKIPPABLE_FRAME_HEADER_SIZE: u64 = 8;
+const SEEK_TABLE_ENTRY_SIZE: u64 = 8;
+// number of frames (u32), descriptor (u8), seekable magic (u32)
+const SEEK_TABLE_FOOTER_SIZE: u64 = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeekTableEntry {
+    pub compressed_size: u32,
+    pub decompressed_size: u32,
+}
+
+fn invalid_data(message: &str) -> IoError {
+    IoError::new(ErrorKind::InvalidData, message.to_string())
+}
+
+/// Writes a seekable zstd stream. `finish()` must be called to write the seek table.
+pub struct SeekableZstdEncoder<W: Write> {
+    writer: W,
+    level: i32,
+    frame_size: usize,
+    buffer: Vec<u8>,
+    seek_table: Vec<SeekTableEntry>,
+}
+
+impl<W: Write> SeekableZstdEncoder<W> {
+    pub fn new(writer: W, level: i32) -> Self {
+        Self::new_with_frame_size(writer, level, SEEKABLE_ZSTD_FRAME_SIZE)
+    }
+
+    fn new_with_frame_size(writer: W, level: i32, frame_size: usize) -> Self {
+        assert!(frame_size > 0 && frame_size <= MAX_SEEKABLE_ZSTD_FRAME_SIZE as usize);
+        Self {
+            writer,
+            level,
+            frame_size,
+            buffer: Vec::with_capacity(frame_size),
+            seek_table: Vec::default(),
+        }
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let compressed = zstd::bulk::compress(&self.buffer, self.level)?;
+        self.writer.write_all(&compressed)?;
+        self.seek_table.push(SeekTableEntry {
+            compressed_size: compressed.len() as u32,
+            decompressed_size: self.buffer.len() as u32,
+        });
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Compress any buffered data, write the seek table and return the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_frame()?;
+        let num_frames = self.seek_table.len() as u32;
+        self.writer
+            .write_u32::<LittleEndian>(SKIPPABLE_FRAME_MAGIC)?;
+        self.writer.write_u32::<LittleEndian>(
+            (num_frames as u64 * SEEK_TABLE_ENTRY_SIZE + SEEK_TABLE_FOOTER_SIZE) as u32,
+        )?;
+        for entry in &self.seek_table {
+            self.writer
+                .write_u32::<LittleEndian>(entry.compressed_size)?;
+            self.writer
+                .write_u32::<LittleEndian>(entry.decompressed_size)?;
+        }
+        self.writer.write_u32::<LittleEndian>(num_frames)?;
+        self.writer.write_u8(0)?; // descriptor: no checksums
+        self.writer.write_u32::<LittleEndian>(SEEKABLE_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for SeekableZstdEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.frame_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == self.frame_size {
+            self.write_frame()?;
+        }
+        Ok(len)
+    }
+
+    // Frames are only cut when full so that flushing does not hurt the compression ratio
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Read and validate the seek table at the end of `reader`.
+/// The frames it describes must exactly cover the rest of the stream.
+pub fn read_seek_table<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<SeekTableEntry>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    if len < SKIPPABLE_FRAME_HEADER_SIZE + SEEK_TABLE_FOOTER_SIZE {
+        return Err(invalid_data("too short to hold a seek table"));
+    }
+
+    reader.seek(SeekFrom::End(-(SEEK_TABLE_FOOTER_SIZE as i64)))?;
+    let num_frames = reader.read_u32::<LittleEndian>()?;
+    let descriptor = reader.read_u8()?;
+    let magic = reader.read_u32::<LittleEndian>()?;
+    if magic != SEEKABLE_MAGIC {
+        return Err(invalid_data("missing seek table"));
+    }
+    if descriptor != 0 {
+        return Err(invalid_data("unsupported seek table descriptor"));
+    }
+
+    let table_size = num_frames as u64 * SEEK_TABLE_ENTRY_SIZE + SEEK_TABLE_FOOTER_SIZE;
+    if table_size + SKIPPABLE_FRAME_HEADER_SIZE > len {
+        return Err(invalid_data("seek table larger than the stream"));
+    }
+    reader.seek(SeekFrom::End(
+        -((table_size + SKIPPABLE_FRAME_HEADER_SIZE) as i64),
+    ))?;
+    if reader.read_u32::<LittleEndian>()? != SKIPPABLE_FRAME_MAGIC
+        || reader.read_u32::<LittleEndian>()? as u64 != table_size
+    {
+        return Err(invalid_data("malformed seek table frame"));
+    }
+
+    let mut compressed_len = 0;
+    let seek_table = (0..num_frames)
+        .map(|_| {
+            let entry = SeekTableEntry {
+                compressed_size: reader.read_u32::<LittleEndian>()?,
+                decompressed_size: reader.read_u32::<LittleEndian>()?,
+            };
+            if entry.decompressed_size > MAX_SEEKABLE_ZSTD_FRAME_SIZE {
+                return Err(invalid_data("seek table frame too large"));
+            }
+            compressed_len += entry.compressed_size as u64;
+            Ok(entry)
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    if compressed_len + table_size + SKIPPABLE_FRAME_HEADER_SIZE != len {
+        return Err(invalid_data("seek table does not match the stream"));
+    }
+
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(seek_table)
+}
+
+fn decompress_frame(compressed: &[u8], entry: &SeekTableEntry) -> io::Result<Vec<u8>> {
+    let data = zstd::bulk::decompress(compressed, entry.decompressed_size as usize)?;
+    if data.len() != entry.decompressed_size as usize {
+        return Err(invalid_data("frame size does not match the seek table"));
+    }
+    Ok(data)
+}
+
+/// Reads a seekable zstd stream, decompressing up to `num_threads` frames in parallel on a
+/// background thread. Frames are returned in order, so the output is identical to decoding
+/// the stream sequentially.
+pub struct SeekableZstdDecoder {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    frame: Vec<u8>,
+    position: usize,
+}
+
+impl SeekableZstdDecoder {
+    pub fn new(mut file: File, num_threads: usize) -> io::Result<Self> {
+        assert!(num_threads > 0);
+        let seek_table = read_seek_table(&mut file)?;
+        // a dedicated pool, since the callers unpacking the output may occupy the global one
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .thread_name(|i| format!("mundis-zstd-frame-decoder-{}", i))
+            .num_threads(num_threads)
+            .build()
+            .map_err(|err| IoError::new(ErrorKind::Other, err.to_string()))?;
+        let (sender, receiver) = bounded(num_threads);
+
+        Builder::new()
+            .name("mundis-seekable-zstd-reader".to_string())
+            .spawn(move || {
+                let mut reader = BufReader::new(file);
+                for entries in seek_table.chunks(num_threads) {
+                    let compressed = entries
+                        .iter()
+                        .map(|entry| {
+                            let mut compressed = vec![0; entry.compressed_size as usize];
+                            reader.read_exact(&mut compressed).map(|_| compressed)
+                        })
+                        .collect::<io::Result<Vec<_>>>();
+                    let frames = match compressed {
+                        Ok(compressed) => thread_pool.install(|| {
+                            compressed
+                                .par_iter()
+                                .zip(entries)
+                                .map(|(compressed, entry)| decompress_frame(compressed, entry))
+                                .collect::<Vec<_>>()
+                        }),
+                        Err(err) => vec![Err(err)],
+                    };
+                    for frame in frames {
+                        let is_err = frame.is_err();
+                        if sender.send(frame).is_err() {
+                            // the decoder was dropped
+                            return;
+                        }
+                        if is_err {
+                            return;
+                        }
+                    }
+                }
+                debug!("done decompressing seekable zstd stream");
+            })?;
+
+        Ok(Self {
+            receiver,
+            frame: Vec::default(),
+            position: 0,
+        })
+    }
+}
+
+impl Read for SeekableZstdDecoder {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.frame.len() {
+            match self.receiver.recv() {
+                Ok(frame) => {
+                    self.frame = frame?;
+                    self.position = 0;
+                }
+                // every frame has been read
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.frame.len() - self.position);
+        buf[..len].copy_from_slice(&self.frame[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::tempfile};
+
+    fn write_seekable(data: &[u8], frame_size: usize) -> File {
+        let mut encoder =
+            SeekableZstdEncoder::new_with_frame_size(tempfile().unwrap(), 0, frame_size);
+        encoder.write_all(data).unwrap();
+        let mut file = encoder.finish().unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_seekable_zstd_roundtrip() {
+        for (len, frame_size, num_threads) in [
+            (0, 16, 1),
+            (15, 16, 2),
+            (16, 16, 2),
+            (1000, 16, 3),
+            (100_000, 4096, 4),
+        ] {
+            let data = test_data(len);
+            let mut file = write_seekable(&data, frame_size);
+            let seek_table = read_seek_table(&mut file).unwrap();
+            assert_eq!(seek_table.len(), (len + frame_size - 1) / frame_size);
+            assert_eq!(
+                seek_table
+                    .iter()
+                    .map(|entry| entry.decompressed_size as usize)
+                    .sum::<usize>(),
+                len
+            );
+
+            let mut decoded = vec![];
+            SeekableZstdDecoder::new(file, num_threads)
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, data, "len: {}, frame_size: {}", len, frame_size);
+        }
+    }
+
+    #[test]
+    fn test_seekable_zstd_readable_by_zstd_decoder() {
+        let data = test_data(10_000);
+        let file = write_seekable(&data, 1024);
+        let mut decoded = vec![];
+        zstd::stream::read::Decoder::new(file)
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_seekable_zstd_rejects_bad_streams() {
+        // plain zstd, without a seek table
+        let mut encoder = zstd::stream::Encoder::new(tempfile().unwrap(), 0).unwrap();
+        encoder.write_all(&test_data(1000)).unwrap();
+        let mut file = encoder.finish().unwrap();
+        assert_eq!(
+            read_seek_table(&mut file).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+
+        // bytes prepended to the frames
+        let mut data = vec![0; 5];
+        write_seekable(&test_data(1000), 100)
+            .read_to_end(&mut data)
+            .unwrap();
+        let mut file = tempfile().unwrap();
+        file.write_all(&data).unwrap();
+        assert_eq!(
+            read_seek_table(&mut file).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+
+        // a corrupted frame is reported by the reader
+        let mut data = vec![];
+        write_seekable(&test_data(1000), 100)
+            .read_to_end(&mut data)
+            .unwrap();
+        data[1] ^= 0xff;
+        let mut file = tempfile().unwrap();
+        file.write_all(&data).unwrap();
+        let mut decoded = vec![];
+        assert!(SeekableZstdDecoder::new(file, 2)
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .is_err());
+    }
+}
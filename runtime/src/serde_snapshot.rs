@@ -3,7 +3,7 @@ use {
         accounts::Accounts,
         accounts_db::{
             AccountShrinkThreshold, AccountStorageEntry, AccountsDb, AccountsDbConfig, AppendVecId,
-            BankHashInfo, IndexGenerationInfo, SnapshotStorage,
+            BankHashInfo, IndexGenerationInfo, SnapshotStorage, SnapshotStorages,
         },
         accounts_index::AccountSecondaryIndexes,
         accounts_update_notifier_interface::AccountsUpdateNotifier,
@@ -43,13 +43,14 @@ use {
         },
         thread::Builder,
     },
-    storage::SerializableStorage,
+    storage::{SerializableStorage, SnapshotStorageEntry},
 };
 
 mod newer;
 mod storage;
 mod tests;
 mod utils;
+mod v1_3_0;
 
 // a number of test cases in accounts_db use this
 #[cfg(test)]
@@ -58,6 +59,7 @@ pub(crate) use tests::reconstruct_accounts_db_via_serialization;
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub(crate) enum SerdeStyle {
     Newer,
+    V1_3_0,
 }
 
 const MAX_STREAM_SIZE: u64 = 32 * 1024 * 1024 * 1024;
@@ -74,8 +76,83 @@ struct AccountsDbFields<T>(
     /// slots that were roots within the last epoch for which we care about the hash value
     #[serde(deserialize_with = "default_on_eof")]
     Vec<(Slot, Hash)>,
+    /// the incremental accounts hash of the snapshot slot, and the full snapshot slot it is based
+    /// on, if an incremental snapshot was taken of it
+    #[serde(deserialize_with = "default_on_eof")]
+    Option<(Slot, Hash)>,
 );
 
+impl<T: SerializableStorage> AccountsDbFields<T> {
+    /// Convert the storage entries to a type shared by every serde style, so the fields of a full
+    /// snapshot and an incremental snapshot of different styles can be combined
+    fn into_snapshot_storage_entries(self) -> AccountsDbFields<SnapshotStorageEntry> {
+        let AccountsDbFields(
+            storages,
+            version,
+            slot,
+            bank_hash_info,
+            prior_roots,
+            prior_roots_with_hash,
+            incremental_accounts_hash,
+        ) = self;
+        let storages = storages
+            .into_iter()
+            .map(|(slot, entries)| {
+                (
+                    slot,
+                    entries.iter().map(SnapshotStorageEntry::new).collect(),
+                )
+            })
+            .collect();
+        AccountsDbFields(
+            storages,
+            version,
+            slot,
+            bank_hash_info,
+            prior_roots,
+            prior_roots_with_hash,
+            incremental_accounts_hash,
+        )
+    }
+}
+
+/// A length prefixed, versioned section of a snapshot.  Readers decode the fields they know and
+/// skip whatever a newer writer appended to the same section version, so a section can gain
+/// fields without a new snapshot version.  Section versions newer than the reader are rejected.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct VersionedSection {
+    version: u32,
+    data: Vec<u8>,
+}
+
+impl VersionedSection {
+    pub(crate) fn new<T: Serialize + ?Sized>(version: u32, value: &T) -> Result<Self, Error> {
+        Ok(Self {
+            version,
+            data: bincode::serialize(value)?,
+        })
+    }
+
+    /// Decode the `name` section, which this reader knows up to `max_version` of
+    pub(crate) fn decode<T: DeserializeOwned>(
+        &self,
+        name: &str,
+        max_version: u32,
+    ) -> Result<T, Error> {
+        if self.version > max_version {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported {} section version {}, expected at most {}",
+                    name, self.version, max_version
+                ),
+            )
+            .into());
+        }
+        deserialize_from(self.data.as_slice())
+    }
+}
+
 /// Helper type to wrap BufReader streams when deserializing and reconstructing from either just a
 /// full snapshot, or both a full and incremental snapshot
 pub struct SnapshotStreams<'a, R> {
@@ -105,6 +182,7 @@ impl<T> SnapshotAccountsDbFields<T> {
                 incremental_snapshot_bank_hash_info,
                 incremental_snapshot_prior_roots,
                 incremental_snapshot_prior_roots_with_hash,
+                incremental_accounts_hash,
             )) => {
                 let full_snapshot_storages = self.full_snapshot_accounts_db_fields.0;
                 let full_snapshot_slot = self.full_snapshot_accounts_db_fields.2;
@@ -129,6 +207,7 @@ impl<T> SnapshotAccountsDbFields<T> {
                     incremental_snapshot_bank_hash_info,
                     incremental_snapshot_prior_roots,
                     incremental_snapshot_prior_roots_with_hash,
+                    incremental_accounts_hash,
                 ))
             }
         }
@@ -187,9 +266,42 @@ where
         .deserialize_from::<R, T>(reader)
 }
 
+/// Deserialize the bank and accounts db fields of a bank snapshot stream of any serde style
+fn deserialize_bank_fields<R>(
+    serde_style: SerdeStyle,
+    stream: &mut BufReader<R>,
+) -> Result<
+    (
+        BankFieldsToDeserialize,
+        AccountsDbFields<SnapshotStorageEntry>,
+    ),
+    Error,
+>
+where
+    R: Read,
+{
+    macro_rules! INTO {
+        ($style:ident) => {{
+            let (bank_fields, accounts_db_fields) =
+                $style::Context::deserialize_bank_fields(stream)?;
+            Ok((
+                bank_fields,
+                accounts_db_fields.into_snapshot_storage_entries(),
+            ))
+        }};
+    }
+    match serde_style {
+        SerdeStyle::Newer => INTO!(newer),
+        SerdeStyle::V1_3_0 => INTO!(v1_3_0),
+    }
+}
+
+/// Rebuild a bank from the stream of a full snapshot and, optionally, the stream of an
+/// incremental snapshot.  The incremental snapshot's serde style defaults to the full snapshot's.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn bank_from_streams<R>(
-    serde_style: SerdeStyle,
+    full_snapshot_serde_style: SerdeStyle,
+    incremental_snapshot_serde_style: Option<SerdeStyle>,
     snapshot_streams: &mut SnapshotStreams<R>,
     account_paths: &[PathBuf],
     unpacked_append_vec_map: UnpackedAppendVecMap,
@@ -207,53 +319,125 @@ pub(crate) fn bank_from_streams<R>(
 where
     R: Read,
 {
-    macro_rules! INTO {
-        ($style:ident) => {{
-            let (full_snapshot_bank_fields, full_snapshot_accounts_db_fields) =
-                $style::Context::deserialize_bank_fields(snapshot_streams.full_snapshot_stream)?;
-            let (incremental_snapshot_bank_fields, incremental_snapshot_accounts_db_fields) =
-                if let Some(ref mut incremental_snapshot_stream) =
-                    snapshot_streams.incremental_snapshot_stream
-                {
-                    let (bank_fields, accounts_db_fields) =
-                        $style::Context::deserialize_bank_fields(incremental_snapshot_stream)?;
-                    (Some(bank_fields), Some(accounts_db_fields))
-                } else {
-                    (None, None)
-                };
-
-            let snapshot_accounts_db_fields = SnapshotAccountsDbFields {
-                full_snapshot_accounts_db_fields,
-                incremental_snapshot_accounts_db_fields,
-            };
-            let bank = reconstruct_bank_from_fields(
-                incremental_snapshot_bank_fields.unwrap_or(full_snapshot_bank_fields),
-                snapshot_accounts_db_fields,
-                genesis_config,
-                account_paths,
-                unpacked_append_vec_map,
-                debug_keys,
-                additional_builtins,
-                account_secondary_indexes,
-                caching_enabled,
-                limit_load_slot_count_from_snapshot,
-                shrink_ratio,
-                verify_index,
-                accounts_db_config,
-                accounts_update_notifier,
+    let (full_snapshot_bank_fields, full_snapshot_accounts_db_fields) = deserialize_bank_fields(
+        full_snapshot_serde_style,
+        snapshot_streams.full_snapshot_stream,
+    )?;
+    let (incremental_snapshot_bank_fields, incremental_snapshot_accounts_db_fields) =
+        if let Some(ref mut incremental_snapshot_stream) =
+            snapshot_streams.incremental_snapshot_stream
+        {
+            let (bank_fields, accounts_db_fields) = deserialize_bank_fields(
+                incremental_snapshot_serde_style.unwrap_or(full_snapshot_serde_style),
+                incremental_snapshot_stream,
             )?;
-            Ok(bank)
-        }};
-    }
-    match serde_style {
-        SerdeStyle::Newer => INTO!(newer),
-    }
+            (Some(bank_fields), Some(accounts_db_fields))
+        } else {
+            (None, None)
+        };
+
+    let snapshot_accounts_db_fields = SnapshotAccountsDbFields {
+        full_snapshot_accounts_db_fields,
+        incremental_snapshot_accounts_db_fields,
+    };
+    reconstruct_bank_from_fields(
+        incremental_snapshot_bank_fields.unwrap_or(full_snapshot_bank_fields),
+        snapshot_accounts_db_fields,
+        genesis_config,
+        account_paths,
+        unpacked_append_vec_map,
+        debug_keys,
+        additional_builtins,
+        account_secondary_indexes,
+        caching_enabled,
+        limit_load_slot_count_from_snapshot,
+        shrink_ratio,
+        verify_index,
+        accounts_db_config,
+        accounts_update_notifier,
+    )
     .map_err(|err| {
         warn!("bankrc_from_stream error: {:?}", err);
         err
     })
 }
 
+/// Deserialize the slot, the accounts hash and the incremental accounts hash recorded in a bank
+/// snapshot stream, without reconstructing the bank
+pub(crate) fn slot_and_accounts_hashes_from_stream<R>(
+    serde_style: SerdeStyle,
+    stream: &mut BufReader<R>,
+) -> std::result::Result<(Slot, Hash, Option<(Slot, Hash)>), Error>
+where
+    R: Read,
+{
+    let (bank_fields, accounts_db_fields) = deserialize_bank_fields(serde_style, stream)?;
+    Ok((
+        bank_fields.slot,
+        accounts_db_fields.3.snapshot_hash,
+        accounts_db_fields.6,
+    ))
+}
+
+/// Open the storages recorded in the stream of a full snapshot and, optionally, the stream of an
+/// incremental snapshot, without reconstructing the accounts db or its index
+pub(crate) fn snapshot_storages_from_streams<R>(
+    full_snapshot_serde_style: SerdeStyle,
+    incremental_snapshot_serde_style: Option<SerdeStyle>,
+    snapshot_streams: &mut SnapshotStreams<R>,
+    unpacked_append_vec_map: &UnpackedAppendVecMap,
+) -> std::result::Result<SnapshotStorages, Error>
+where
+    R: Read,
+{
+    let (_, full_snapshot_accounts_db_fields) = deserialize_bank_fields(
+        full_snapshot_serde_style,
+        snapshot_streams.full_snapshot_stream,
+    )?;
+    let incremental_snapshot_accounts_db_fields = snapshot_streams
+        .incremental_snapshot_stream
+        .as_mut()
+        .map(|incremental_snapshot_stream| {
+            deserialize_bank_fields(
+                incremental_snapshot_serde_style.unwrap_or(full_snapshot_serde_style),
+                incremental_snapshot_stream,
+            )
+            .map(|(_, accounts_db_fields)| accounts_db_fields)
+        })
+        .transpose()?;
+    let AccountsDbFields(snapshot_storages, ..) = SnapshotAccountsDbFields {
+        full_snapshot_accounts_db_fields,
+        incremental_snapshot_accounts_db_fields,
+    }
+    .collapse_into()?;
+
+    let mut storages = snapshot_storages
+        .into_par_iter()
+        .map(|(slot, slot_storage)| {
+            let mut new_slot_storage = HashMap::new();
+            for storage_entry in &slot_storage {
+                let file_name = AppendVec::file_name(slot, storage_entry.id());
+                let append_vec_path = unpacked_append_vec_map.get(&file_name).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("{} not found in unpacked append vecs", file_name),
+                    )
+                })?;
+                reconstruct_single_storage(
+                    &slot,
+                    append_vec_path,
+                    storage_entry,
+                    None,
+                    &mut new_slot_storage,
+                )?;
+            }
+            Ok(new_slot_storage.into_values().collect())
+        })
+        .collect::<Result<SnapshotStorages, Error>>()?;
+    storages.retain(|slot_storage| !slot_storage.is_empty());
+    Ok(storages)
+}
+
 pub(crate) fn bank_to_stream<W>(
     serde_style: SerdeStyle,
     stream: &mut BufWriter<W>,
@@ -277,6 +461,7 @@ where
     }
     match serde_style {
         SerdeStyle::Newer => INTO!(newer),
+        SerdeStyle::V1_3_0 => INTO!(v1_3_0),
     }
     .map_err(|err| {
         warn!("bankrc_to_stream error: {:?}", err);
@@ -384,6 +569,20 @@ where
     let append_vec_id = remapped_append_vec_id.unwrap_or_else(|| storage_entry.id());
     let (accounts, num_accounts) =
         AppendVec::new_from_file(append_vec_path, storage_entry.current_len())?;
+    if let Some(metadata) = storage_entry.metadata() {
+        if metadata.count != num_accounts {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} holds {} accounts, but the snapshot recorded {}",
+                    append_vec_path.display(),
+                    num_accounts,
+                    metadata.count
+                ),
+            )
+            .into());
+        }
+    }
     let u_storage_entry =
         AccountStorageEntry::new_existing(*slot, append_vec_id, accounts, num_accounts);
 
@@ -424,6 +623,20 @@ where
         accounts_update_notifier,
     );
 
+    // Keep the accounts hash of the full snapshot an incremental snapshot is based on, so the
+    // storages of the full snapshot can be checked against it
+    let full_snapshot_bank_hash_info = snapshot_accounts_db_fields
+        .incremental_snapshot_accounts_db_fields
+        .as_ref()
+        .map(|_| {
+            let full_snapshot_accounts_db_fields =
+                &snapshot_accounts_db_fields.full_snapshot_accounts_db_fields;
+            (
+                full_snapshot_accounts_db_fields.2,
+                full_snapshot_accounts_db_fields.3.clone(),
+            )
+        });
+
     let AccountsDbFields(
         snapshot_storages,
         snapshot_version,
@@ -431,10 +644,26 @@ where
         snapshot_bank_hash_info,
         _snapshot_prior_roots,
         _snapshot_prior_roots_with_hash,
+        snapshot_incremental_accounts_hash,
     ) = snapshot_accounts_db_fields.collapse_into()?;
 
     let snapshot_storages = snapshot_storages.into_iter().collect::<Vec<_>>();
 
+    // The startup shrink has nothing to reclaim from slots that had no dead bytes when the
+    // snapshot was taken
+    for (slot, slot_storage) in &snapshot_storages {
+        let no_dead_bytes = slot_storage.iter().all(|storage_entry| {
+            storage_entry
+                .metadata()
+                .map_or(false, |metadata| metadata.dead_bytes == 0)
+        });
+        if no_dead_bytes {
+            accounts_db
+                .slots_without_dead_bytes_at_snapshot
+                .insert(*slot);
+        }
+    }
+
     // Ensure all account paths exist
     for path in &accounts_db.paths {
         std::fs::create_dir_all(path)
@@ -526,6 +755,16 @@ where
         .write()
         .unwrap()
         .insert(snapshot_slot, snapshot_bank_hash_info);
+    if let Some((full_snapshot_slot, full_snapshot_bank_hash_info)) = full_snapshot_bank_hash_info {
+        accounts_db
+            .bank_hashes
+            .write()
+            .unwrap()
+            .insert(full_snapshot_slot, full_snapshot_bank_hash_info);
+    }
+    if let Some(incremental_accounts_hash) = snapshot_incremental_accounts_hash {
+        accounts_db.set_incremental_accounts_hash(snapshot_slot, incremental_accounts_hash);
+    }
     accounts_db.storage.0.extend(
         storage
             .into_iter()
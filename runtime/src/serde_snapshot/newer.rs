@@ -237,9 +237,25 @@ impl<'a> TypeContext<'a> for Context {
             .get(&serializable_db.slot)
             .unwrap_or_else(|| panic!("No bank_hashes entry for slot {}", serializable_db.slot))
             .clone();
+        let incremental_accounts_hash = serializable_db
+            .accounts_db
+            .get_incremental_accounts_hash(serializable_db.slot);
+        // the prior roots are not recorded, but have to be written for the incremental accounts
+        // hash that follows them
+        let prior_roots = Vec::<Slot>::new();
+        let prior_roots_with_hash = Vec::<(Slot, Hash)>::new();
 
         let mut serialize_account_storage_timer = Measure::start("serialize_account_storage_ms");
-        let result = (entries, version, slot, hash).serialize(serializer);
+        let result = (
+            entries,
+            version,
+            slot,
+            hash,
+            prior_roots,
+            prior_roots_with_hash,
+            incremental_accounts_hash,
+        )
+            .serialize(serializer);
         serialize_account_storage_timer.stop();
         datapoint_info!(
             "serialize_account_storage_ms",
@@ -13,6 +13,12 @@ pub(super) struct SerializableAccountStorageEntry {
 pub(super) trait SerializableStorage {
     fn id(&self) -> AppendVecId;
     fn current_len(&self) -> usize;
+
+    /// What the snapshot recorded about the storage so it does not have to be recomputed on
+    /// load, if the snapshot format records anything
+    fn metadata(&self) -> Option<StorageMetadata> {
+        None
+    }
 }
 
 impl SerializableStorage for SerializableAccountStorageEntry {
@@ -33,5 +39,79 @@ impl From<&AccountStorageEntry> for SerializableAccountStorageEntry {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(super) struct StorageMetadata {
+    /// number of accounts stored, alive or dead
+    pub(super) count: usize,
+    /// bytes of the storage used by accounts that were dead when the snapshot was taken
+    pub(super) dead_bytes: usize,
+}
+
+// Serializable version of AccountStorageEntry for the 1.3.0 snapshot format
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(super) struct SerializableAccountStorageEntryV1_3_0 {
+    id: AppendVecId,
+    accounts_current_len: usize,
+    metadata: StorageMetadata,
+}
+
+impl SerializableStorage for SerializableAccountStorageEntryV1_3_0 {
+    fn id(&self) -> AppendVecId {
+        self.id
+    }
+    fn current_len(&self) -> usize {
+        self.accounts_current_len
+    }
+    fn metadata(&self) -> Option<StorageMetadata> {
+        Some(self.metadata)
+    }
+}
+
+impl From<&AccountStorageEntry> for SerializableAccountStorageEntryV1_3_0 {
+    fn from(rhs: &AccountStorageEntry) -> Self {
+        Self {
+            id: rhs.append_vec_id(),
+            accounts_current_len: rhs.accounts.len(),
+            metadata: StorageMetadata {
+                count: rhs.stored_accounts_count(),
+                dead_bytes: rhs.dead_bytes(),
+            },
+        }
+    }
+}
+
+/// A storage entry of any snapshot format
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) struct SnapshotStorageEntry {
+    id: AppendVecId,
+    accounts_current_len: usize,
+    metadata: Option<StorageMetadata>,
+}
+
+impl SnapshotStorageEntry {
+    pub(super) fn new<T: SerializableStorage>(storage_entry: &T) -> Self {
+        Self {
+            id: storage_entry.id(),
+            accounts_current_len: storage_entry.current_len(),
+            metadata: storage_entry.metadata(),
+        }
+    }
+}
+
+impl SerializableStorage for SnapshotStorageEntry {
+    fn id(&self) -> AppendVecId {
+        self.id
+    }
+    fn current_len(&self) -> usize {
+        self.accounts_current_len
+    }
+    fn metadata(&self) -> Option<StorageMetadata> {
+        self.metadata
+    }
+}
+
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
 impl mundis_frozen_abi::abi_example::IgnoreAsHelper for SerializableAccountStorageEntry {}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl mundis_frozen_abi::abi_example::IgnoreAsHelper for SerializableAccountStorageEntryV1_3_0 {}
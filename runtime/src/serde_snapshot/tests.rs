@@ -3,7 +3,9 @@ use {
     super::*,
     crate::{
         accounts::{create_test_accounts, Accounts},
-        accounts_db::{get_temp_accounts_paths, AccountShrinkThreshold},
+        accounts_db::{get_temp_accounts_paths, AccountShrinkThreshold, SnapshotStorages},
+        ancestors::Ancestors,
+        ancient_append_vecs::is_ancient,
         bank::{Bank, StatusCacheRc},
         hardened_unpack::UnpackedAppendVecMap,
     },
@@ -104,6 +106,11 @@ where
             account_paths,
             unpacked_append_vec_map,
         ),
+        SerdeStyle::V1_3_0 => context_accountsdb_from_stream::<v1_3_0::Context, R>(
+            stream,
+            account_paths,
+            unpacked_append_vec_map,
+        ),
     }
 }
 
@@ -127,6 +134,15 @@ where
                 phantom: std::marker::PhantomData::default(),
             },
         ),
+        SerdeStyle::V1_3_0 => serialize_into(
+            stream,
+            &SerializableAccountsDb::<v1_3_0::Context> {
+                accounts_db,
+                slot,
+                account_storage_entries,
+                phantom: std::marker::PhantomData::default(),
+            },
+        ),
     }
 }
 
@@ -231,6 +247,7 @@ fn test_bank_serialize_style(serde_style: SerdeStyle) {
     };
     let mut dbank = crate::serde_snapshot::bank_from_streams(
         serde_style,
+        None,
         &mut snapshot_streams,
         &dbank_paths,
         unpacked_append_vec_map,
@@ -289,6 +306,51 @@ pub(crate) fn reconstruct_accounts_db_via_serialization(
     accounts_db
 }
 
+#[test]
+fn test_accounts_serialize_ancient_append_vecs() {
+    mundis_logger::setup();
+    let accounts_db = AccountsDb::new_single_for_tests();
+    let owner = *AccountSharedData::default().owner();
+    let pubkeys: Vec<Pubkey> = (0..10).map(|_| mundis_sdk::pubkey::new_rand()).collect();
+    for (i, pubkey) in pubkeys.iter().enumerate() {
+        let slot = i as Slot + 1;
+        let account = AccountSharedData::new(slot, 0, &owner);
+        accounts_db.store_uncached(slot, &[(pubkey, &account)]);
+        accounts_db.get_accounts_delta_hash(slot);
+        accounts_db.add_root(slot);
+    }
+    let last_slot = pubkeys.len() as Slot;
+    accounts_db.combine_ancient_slots((1..last_slot).collect());
+    let ancestors = Ancestors::default();
+    let accounts_hash = accounts_db.update_accounts_hash(last_slot, &ancestors);
+
+    let daccounts_db = reconstruct_accounts_db_via_serialization(&accounts_db, last_slot);
+
+    // the packed slots are restored as a single ancient append vec
+    let storages = daccounts_db.get_snapshot_storages(last_slot, None, None).0;
+    assert_eq!(storages.len(), 2);
+    let ancient_storage = storages
+        .iter()
+        .flatten()
+        .find(|storage| storage.slot() == 1)
+        .unwrap();
+    assert!(is_ancient(&ancient_storage.accounts));
+
+    for (i, pubkey) in pubkeys.iter().enumerate() {
+        let (account, slot) = daccounts_db
+            .load_without_fixed_root(&ancestors, pubkey)
+            .unwrap();
+        assert_eq!(account.lamports(), i as u64 + 1);
+        // only the newest slot was left out of the ancient append vec
+        let expected_slot = if i == pubkeys.len() - 1 { last_slot } else { 1 };
+        assert_eq!(slot, expected_slot);
+    }
+    assert_eq!(
+        daccounts_db.update_accounts_hash(last_slot, &ancestors),
+        accounts_hash
+    );
+}
+
 #[test]
 fn test_accounts_serialize_newer() {
     test_accounts_serialize_style(SerdeStyle::Newer)
@@ -299,6 +361,153 @@ fn test_bank_serialize_newer() {
     test_bank_serialize_style(SerdeStyle::Newer)
 }
 
+#[test]
+fn test_accounts_serialize_v1_3_0() {
+    test_accounts_serialize_style(SerdeStyle::V1_3_0)
+}
+
+#[test]
+fn test_bank_serialize_v1_3_0() {
+    test_bank_serialize_style(SerdeStyle::V1_3_0)
+}
+
+#[test]
+fn test_accounts_serialize_v1_3_0_storage_metadata() {
+    mundis_logger::setup();
+    let accounts_db = AccountsDb::new_single_for_tests();
+    let owner = *AccountSharedData::default().owner();
+    let pubkey0 = mundis_sdk::pubkey::new_rand();
+    let pubkey1 = mundis_sdk::pubkey::new_rand();
+    let account = AccountSharedData::new(1, 0, &owner);
+    accounts_db.store_uncached(0, &[(&pubkey0, &account), (&pubkey1, &account)]);
+    accounts_db.add_root(0);
+    // the update in slot 1 leaves dead bytes behind in slot 0
+    accounts_db.store_uncached(1, &[(&pubkey1, &AccountSharedData::new(2, 0, &owner))]);
+    accounts_db.add_root(1);
+    accounts_db.clean_accounts(None, false, None);
+
+    let snapshot_storages = accounts_db.get_snapshot_storages(1, None, None).0;
+    let mut writer = Cursor::new(vec![]);
+    accountsdb_to_stream(
+        SerdeStyle::V1_3_0,
+        &mut writer,
+        &accounts_db,
+        1,
+        &snapshot_storages,
+    )
+    .unwrap();
+    let copied_accounts = TempDir::new().unwrap();
+    let unpacked_append_vec_map = copy_append_vecs(&accounts_db, copied_accounts.path()).unwrap();
+    let buf = writer.into_inner();
+    let mut reader = BufReader::new(&buf[..]);
+    let daccounts_db = accountsdb_from_stream(
+        SerdeStyle::V1_3_0,
+        &mut reader,
+        &[],
+        unpacked_append_vec_map,
+    )
+    .unwrap();
+
+    assert!(!daccounts_db
+        .slots_without_dead_bytes_at_snapshot
+        .contains(&0));
+    assert!(daccounts_db
+        .slots_without_dead_bytes_at_snapshot
+        .contains(&1));
+
+    let summaries = |storages: SnapshotStorages| {
+        storages
+            .iter()
+            .flatten()
+            .map(|storage| (storage.slot(), storage.stored_accounts_count()))
+            .collect::<HashMap<_, _>>()
+    };
+    let dsnapshot_storages = daccounts_db.get_snapshot_storages(1, None, None).0;
+    assert_eq!(summaries(dsnapshot_storages), summaries(snapshot_storages));
+}
+
+#[test]
+fn test_accounts_serialize_v1_3_0_storage_count_mismatch() {
+    mundis_logger::setup();
+    let accounts_db = AccountsDb::new_single_for_tests();
+    let owner = *AccountSharedData::default().owner();
+    let pubkey = mundis_sdk::pubkey::new_rand();
+    accounts_db.store_uncached(0, &[(&pubkey, &AccountSharedData::new(1, 0, &owner))]);
+    accounts_db.add_root(0);
+
+    let snapshot_storages = accounts_db.get_snapshot_storages(0, None, None).0;
+    // record a count that does not match what the storage holds
+    snapshot_storages[0][0].set_stored_accounts_count(2);
+    let mut writer = Cursor::new(vec![]);
+    accountsdb_to_stream(
+        SerdeStyle::V1_3_0,
+        &mut writer,
+        &accounts_db,
+        0,
+        &snapshot_storages,
+    )
+    .unwrap();
+    let copied_accounts = TempDir::new().unwrap();
+    let unpacked_append_vec_map = copy_append_vecs(&accounts_db, copied_accounts.path()).unwrap();
+    let buf = writer.into_inner();
+    let mut reader = BufReader::new(&buf[..]);
+    assert!(accountsdb_from_stream(
+        SerdeStyle::V1_3_0,
+        &mut reader,
+        &[],
+        unpacked_append_vec_map,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_versioned_section() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Fields {
+        a: u64,
+    }
+    #[derive(Serialize)]
+    struct NewerFields {
+        a: u64,
+        b: String,
+    }
+
+    let section = VersionedSection::new(1, &Fields { a: 42 }).unwrap();
+    assert_eq!(
+        section.decode::<Fields>("test", 1).unwrap(),
+        Fields { a: 42 }
+    );
+    assert_eq!(
+        section.decode::<Fields>("test", 2).unwrap(),
+        Fields { a: 42 }
+    );
+
+    // fields a newer writer appended to the same section version are skipped
+    let section = VersionedSection::new(
+        1,
+        &NewerFields {
+            a: 42,
+            b: "new".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        section.decode::<Fields>("test", 1).unwrap(),
+        Fields { a: 42 }
+    );
+
+    // a section version newer than the reader is rejected
+    let section = VersionedSection::new(2, &Fields { a: 42 }).unwrap();
+    assert!(section.decode::<Fields>("test", 1).is_err());
+
+    // sections embed in a stream and are read back whole
+    let mut buf = vec![];
+    serialize_into(&mut buf, &(section.clone(), 7u8)).unwrap();
+    let (dsection, trailer): (VersionedSection, u8) = deserialize_from(&buf[..]).unwrap();
+    assert_eq!(dsection, section);
+    assert_eq!(trailer, 7);
+}
+
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
 mod test_bank_serialize {
     use super::*;
@@ -0,0 +1,289 @@
+//! The 1.3.0 snapshot format.  Compared to the `newer` format, the bank fields drop the unused
+//! accounts, the epoch stakes move into a `VersionedSection` of their own, and every storage
+//! entry records the storage's account count and dead bytes.
+use {
+    super::{
+        storage::SerializableAccountStorageEntryV1_3_0,
+        utils::{serialize_iter_as_map, serialize_iter_as_seq},
+        *,
+    },
+    crate::{ancestors::AncestorsForSerialization, stakes::StakesCache},
+    mundis_measure::measure::Measure,
+    std::{cell::RefCell, sync::RwLock},
+};
+
+type AccountsDbFields = super::AccountsDbFields<SerializableAccountStorageEntryV1_3_0>;
+
+const EPOCH_STAKES_SECTION_VERSION: u32 = 1;
+
+// Deserializable version of Bank which need not be serializable,
+// because it's handled by SerializableVersionedBank.
+// So, sync fields with it!
+#[derive(Clone, Deserialize)]
+struct DeserializableVersionedBank {
+    blockhash_queue: BlockhashQueue,
+    ancestors: AncestorsForSerialization,
+    hash: Hash,
+    parent_hash: Hash,
+    parent_slot: Slot,
+    hard_forks: HardForks,
+    transaction_count: u64,
+    tick_height: u64,
+    signature_count: u64,
+    capitalization: u64,
+    max_tick_height: u64,
+    hashes_per_tick: Option<u64>,
+    ticks_per_slot: u64,
+    ns_per_slot: u128,
+    genesis_creation_time: UnixTimestamp,
+    slots_per_year: f64,
+    accounts_data_len: u64,
+    slot: Slot,
+    epoch: Epoch,
+    block_height: u64,
+    collector_id: Pubkey,
+    collector_fees: u64,
+    fee_calculator: FeeCalculator,
+    fee_rate_governor: FeeRateGovernor,
+    collected_rent: u64,
+    rent_collector: RentCollector,
+    epoch_schedule: EpochSchedule,
+    inflation: Inflation,
+    stakes: Stakes,
+    is_delta: bool,
+}
+
+impl DeserializableVersionedBank {
+    fn into_bank_fields(
+        self,
+        epoch_stakes: HashMap<Epoch, EpochStakes>,
+    ) -> BankFieldsToDeserialize {
+        BankFieldsToDeserialize {
+            blockhash_queue: self.blockhash_queue,
+            ancestors: self.ancestors,
+            hash: self.hash,
+            parent_hash: self.parent_hash,
+            parent_slot: self.parent_slot,
+            hard_forks: self.hard_forks,
+            transaction_count: self.transaction_count,
+            tick_height: self.tick_height,
+            signature_count: self.signature_count,
+            capitalization: self.capitalization,
+            max_tick_height: self.max_tick_height,
+            hashes_per_tick: self.hashes_per_tick,
+            ticks_per_slot: self.ticks_per_slot,
+            ns_per_slot: self.ns_per_slot,
+            genesis_creation_time: self.genesis_creation_time,
+            slots_per_year: self.slots_per_year,
+            accounts_data_len: self.accounts_data_len,
+            slot: self.slot,
+            epoch: self.epoch,
+            block_height: self.block_height,
+            collector_id: self.collector_id,
+            collector_fees: self.collector_fees,
+            fee_calculator: self.fee_calculator,
+            fee_rate_governor: self.fee_rate_governor,
+            collected_rent: self.collected_rent,
+            rent_collector: self.rent_collector,
+            epoch_schedule: self.epoch_schedule,
+            inflation: self.inflation,
+            stakes: self.stakes,
+            epoch_stakes,
+            is_delta: self.is_delta,
+        }
+    }
+}
+
+// Serializable version of Bank, not Deserializable to avoid cloning by using refs.
+// Sync fields with DeserializableVersionedBank!
+#[derive(Serialize)]
+struct SerializableVersionedBank<'a> {
+    blockhash_queue: &'a RwLock<BlockhashQueue>,
+    ancestors: &'a AncestorsForSerialization,
+    hash: Hash,
+    parent_hash: Hash,
+    parent_slot: Slot,
+    hard_forks: &'a RwLock<HardForks>,
+    transaction_count: u64,
+    tick_height: u64,
+    signature_count: u64,
+    capitalization: u64,
+    max_tick_height: u64,
+    hashes_per_tick: Option<u64>,
+    ticks_per_slot: u64,
+    ns_per_slot: u128,
+    genesis_creation_time: UnixTimestamp,
+    slots_per_year: f64,
+    accounts_data_len: u64,
+    slot: Slot,
+    epoch: Epoch,
+    block_height: u64,
+    collector_id: Pubkey,
+    collector_fees: u64,
+    fee_calculator: FeeCalculator,
+    fee_rate_governor: FeeRateGovernor,
+    collected_rent: u64,
+    rent_collector: RentCollector,
+    epoch_schedule: EpochSchedule,
+    inflation: Inflation,
+    stakes: &'a StakesCache,
+    is_delta: bool,
+}
+
+impl<'a> From<crate::bank::BankFieldsToSerialize<'a>> for SerializableVersionedBank<'a> {
+    fn from(rhs: crate::bank::BankFieldsToSerialize<'a>) -> Self {
+        Self {
+            blockhash_queue: rhs.blockhash_queue,
+            ancestors: rhs.ancestors,
+            hash: rhs.hash,
+            parent_hash: rhs.parent_hash,
+            parent_slot: rhs.parent_slot,
+            hard_forks: rhs.hard_forks,
+            transaction_count: rhs.transaction_count,
+            tick_height: rhs.tick_height,
+            signature_count: rhs.signature_count,
+            capitalization: rhs.capitalization,
+            max_tick_height: rhs.max_tick_height,
+            hashes_per_tick: rhs.hashes_per_tick,
+            ticks_per_slot: rhs.ticks_per_slot,
+            ns_per_slot: rhs.ns_per_slot,
+            genesis_creation_time: rhs.genesis_creation_time,
+            slots_per_year: rhs.slots_per_year,
+            accounts_data_len: rhs.accounts_data_len,
+            slot: rhs.slot,
+            epoch: rhs.epoch,
+            block_height: rhs.block_height,
+            collector_id: rhs.collector_id,
+            collector_fees: rhs.collector_fees,
+            fee_calculator: rhs.fee_calculator,
+            fee_rate_governor: rhs.fee_rate_governor,
+            collected_rent: rhs.collected_rent,
+            rent_collector: rhs.rent_collector,
+            epoch_schedule: rhs.epoch_schedule,
+            inflation: rhs.inflation,
+            stakes: rhs.stakes,
+            is_delta: rhs.is_delta,
+        }
+    }
+}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl<'a> mundis_frozen_abi::abi_example::IgnoreAsHelper for SerializableVersionedBank<'a> {}
+
+pub(super) struct Context {}
+
+impl<'a> TypeContext<'a> for Context {
+    type SerializableAccountStorageEntry = SerializableAccountStorageEntryV1_3_0;
+
+    fn serialize_bank_and_storage<S: serde::ser::Serializer>(
+        serializer: S,
+        serializable_bank: &SerializableBankAndStorage<'a, Self>,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        Self: std::marker::Sized,
+    {
+        let ancestors = HashMap::from(&serializable_bank.bank.ancestors);
+        let fields = serializable_bank.bank.get_fields_to_serialize(&ancestors);
+        let epoch_stakes = VersionedSection::new(EPOCH_STAKES_SECTION_VERSION, fields.epoch_stakes)
+            .map_err(serde::ser::Error::custom)?;
+        (
+            SerializableVersionedBank::from(fields),
+            epoch_stakes,
+            SerializableAccountsDb::<'a, Self> {
+                accounts_db: &*serializable_bank.bank.rc.accounts.accounts_db,
+                slot: serializable_bank.bank.rc.slot,
+                account_storage_entries: serializable_bank.snapshot_storages,
+                phantom: std::marker::PhantomData::default(),
+            },
+        )
+            .serialize(serializer)
+    }
+
+    fn serialize_accounts_db_fields<S: serde::ser::Serializer>(
+        serializer: S,
+        serializable_db: &SerializableAccountsDb<'a, Self>,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        Self: std::marker::Sized,
+    {
+        // sample write version before serializing storage entries
+        let version = serializable_db
+            .accounts_db
+            .write_version
+            .load(Ordering::Acquire);
+
+        // (1st of 3 elements) write the list of account storage entry lists out as a map
+        let entry_count = RefCell::<usize>::new(0);
+        let entries =
+            serialize_iter_as_map(serializable_db.account_storage_entries.iter().map(|x| {
+                *entry_count.borrow_mut() += x.len();
+                (
+                    x.first().unwrap().slot(),
+                    serialize_iter_as_seq(
+                        x.iter()
+                            .map(|x| Self::SerializableAccountStorageEntry::from(x.as_ref())),
+                    ),
+                )
+            }));
+        let slot = serializable_db.slot;
+        let hash = serializable_db
+            .accounts_db
+            .bank_hashes
+            .read()
+            .unwrap()
+            .get(&serializable_db.slot)
+            .unwrap_or_else(|| panic!("No bank_hashes entry for slot {}", serializable_db.slot))
+            .clone();
+        let incremental_accounts_hash = serializable_db
+            .accounts_db
+            .get_incremental_accounts_hash(serializable_db.slot);
+        // the prior roots are not recorded, but have to be written for the incremental accounts
+        // hash that follows them
+        let prior_roots = Vec::<Slot>::new();
+        let prior_roots_with_hash = Vec::<(Slot, Hash)>::new();
+
+        let mut serialize_account_storage_timer = Measure::start("serialize_account_storage_ms");
+        let result = (
+            entries,
+            version,
+            slot,
+            hash,
+            prior_roots,
+            prior_roots_with_hash,
+            incremental_accounts_hash,
+        )
+            .serialize(serializer);
+        serialize_account_storage_timer.stop();
+        datapoint_info!(
+            "serialize_account_storage_ms",
+            ("duration", serialize_account_storage_timer.as_ms(), i64),
+            ("num_entries", *entry_count.borrow(), i64),
+        );
+        result
+    }
+
+    fn deserialize_bank_fields<R>(
+        mut stream: &mut BufReader<R>,
+    ) -> Result<(BankFieldsToDeserialize, AccountsDbFields), Error>
+    where
+        R: Read,
+    {
+        let bank_fields = deserialize_from::<_, DeserializableVersionedBank>(&mut stream)?;
+        let epoch_stakes = deserialize_from::<_, VersionedSection>(&mut stream)?
+            .decode("epoch stakes", EPOCH_STAKES_SECTION_VERSION)?;
+        let accounts_db_fields = Self::deserialize_accounts_db_fields(stream)?;
+        Ok((
+            bank_fields.into_bank_fields(epoch_stakes),
+            accounts_db_fields,
+        ))
+    }
+
+    fn deserialize_accounts_db_fields<R>(
+        stream: &mut BufReader<R>,
+    ) -> Result<AccountsDbFields, Error>
+    where
+        R: Read,
+    {
+        deserialize_from(stream)
+    }
+}
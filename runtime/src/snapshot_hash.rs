@@ -20,7 +20,8 @@ pub struct FullSnapshotHash {
 
 /// Used by SnapshotPackagerService and SnapshotGossipManager, this struct adds type safety to
 /// ensure an incremental snapshot hash is pushed to the right CRDS.  `base` is the (full) snapshot
-/// this incremental snapshot (`hash`) is based on.
+/// this incremental snapshot (`hash`) is based on.  The hash of an incremental snapshot is its
+/// incremental accounts hash, over only the accounts modified after the base slot.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct IncrementalSnapshotHash {
     pub base: (Slot, Hash),
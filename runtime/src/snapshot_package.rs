@@ -88,6 +88,25 @@ impl AccountsPackage {
             );
         }
 
+        // An incremental snapshot is identified by its incremental accounts hash instead of the
+        // full accounts hash
+        let hash = match snapshot_type {
+            Some(SnapshotType::IncrementalSnapshot(incremental_snapshot_base_slot)) => {
+                let (base_slot, hash) = bank.get_incremental_accounts_hash().unwrap_or_else(|| {
+                    panic!(
+                        "Incremental accounts hash must be calculated before packaging an incremental snapshot of bank {}!",
+                        bank.slot()
+                    )
+                });
+                assert_eq!(
+                    base_slot, incremental_snapshot_base_slot,
+                    "Incremental accounts hash must be based on the incremental snapshot base slot!"
+                );
+                hash
+            }
+            _ => bank.get_accounts_hash(),
+        };
+
         // Hard link the snapshot into a tmpdir, to ensure its not removed prior to packaging.
         let snapshot_links = tempfile::Builder::new()
             .prefix(&format!("{}{}-", TMP_BANK_SNAPSHOT_PREFIX, bank.slot()))
@@ -109,7 +128,7 @@ impl AccountsPackage {
             slot_deltas,
             snapshot_links,
             snapshot_storages,
-            hash: bank.get_accounts_hash(),
+            hash,
             archive_format,
             snapshot_version,
             snapshot_archives_dir: snapshot_archives_dir.as_ref().to_path_buf(),
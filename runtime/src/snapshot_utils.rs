@@ -8,7 +8,11 @@ use {
         bank::{Bank, BankSlotDelta},
         builtins::Builtins,
         hardened_unpack::{unpack_snapshot, ParallelSelector, UnpackError, UnpackedAppendVecMap},
-        serde_snapshot::{bank_from_streams, bank_to_stream, SerdeStyle, SnapshotStreams},
+        seekable_zstd::{SeekableZstdDecoder, SeekableZstdEncoder},
+        serde_snapshot::{
+            bank_from_streams, bank_to_stream, slot_and_accounts_hashes_from_stream,
+            snapshot_storages_from_streams, SerdeStyle, SnapshotStreams, VersionedSection,
+        },
         shared_buffer_reader::{SharedBuffer, SharedBufferReader},
         snapshot_archive_info::{
             FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
@@ -32,7 +36,7 @@ use {
         collections::HashSet,
         fmt,
         fs::{self, File},
-        io::{BufReader, BufWriter, Error as IoError, ErrorKind, Read, Seek, Write},
+        io::{self, BufReader, BufWriter, Error as IoError, ErrorKind, Read, Seek, Write},
         path::{Path, PathBuf},
         process::ExitStatus,
         str::FromStr,
@@ -49,18 +53,21 @@ pub const DEFAULT_INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot = 100;
 const MAX_SNAPSHOT_DATA_FILE_SIZE: u64 = 32 * 1024 * 1024 * 1024; // 32 GiB
 const MAX_SNAPSHOT_VERSION_FILE_SIZE: u64 = 8; // byte
 const VERSION_STRING_V1_2_0: &str = "1.2.0";
+const VERSION_STRING_V1_3_0: &str = "1.3.0";
 const DEFAULT_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion::V1_2_0;
+const STATUS_CACHE_SECTION_VERSION: u32 = 1;
 pub(crate) const TMP_BANK_SNAPSHOT_PREFIX: &str = "tmp-bank-snapshot-";
 pub const TMP_SNAPSHOT_ARCHIVE_PREFIX: &str = "tmp-snapshot-archive-";
 pub const MAX_BANK_SNAPSHOTS_TO_RETAIN: usize = 8; // Save some bank snapshots but not too many
 pub const DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 2;
 pub const DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 4;
-pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz)$";
-pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz)$";
+pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4|tar\.szst)$";
+pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4|tar\.szst)$";
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
     V1_2_0,
+    V1_3_0,
 }
 
 impl Default for SnapshotVersion {
@@ -79,6 +86,7 @@ impl From<SnapshotVersion> for &'static str {
     fn from(snapshot_version: SnapshotVersion) -> &'static str {
         match snapshot_version {
             SnapshotVersion::V1_2_0 => VERSION_STRING_V1_2_0,
+            SnapshotVersion::V1_3_0 => VERSION_STRING_V1_3_0,
         }
     }
 }
@@ -98,6 +106,7 @@ impl FromStr for SnapshotVersion {
         };
         match version_string {
             VERSION_STRING_V1_2_0 => Ok(SnapshotVersion::V1_2_0),
+            VERSION_STRING_V1_3_0 => Ok(SnapshotVersion::V1_3_0),
             _ => Err("unsupported snapshot version"),
         }
     }
@@ -111,6 +120,13 @@ impl SnapshotVersion {
     fn maybe_from_string(version_string: &str) -> Option<SnapshotVersion> {
         version_string.parse::<Self>().ok()
     }
+
+    fn serde_style(self) -> SerdeStyle {
+        match self {
+            SnapshotVersion::V1_2_0 => SerdeStyle::Newer,
+            SnapshotVersion::V1_3_0 => SerdeStyle::V1_3_0,
+        }
+    }
 }
 
 /// The different archive formats used for snapshots
@@ -119,6 +135,10 @@ pub enum ArchiveFormat {
     TarBzip2,
     TarGzip,
     TarZstd,
+    /// Independently compressed zstd frames followed by a seek table, so the archive can be
+    /// decompressed in parallel
+    TarZstdSeekable,
+    TarLz4,
     Tar,
 }
 
@@ -207,6 +227,12 @@ pub enum SnapshotError {
 
     #[error("snapshot has mismatch: deserialized bank: {:?}, snapshot archive info: {:?}", .0, .1)]
     MismatchedSlotHash((Slot, Hash), (Slot, Hash)),
+
+    #[error("snapshot archive has no bank snapshot for slot {0}")]
+    MissingBankSnapshot(Slot),
+
+    #[error("snapshot bank for slot {0} failed to verify")]
+    BankVerificationFailed(Slot),
 }
 pub type Result<T> = std::result::Result<T, SnapshotError>;
 
@@ -215,6 +241,8 @@ fn get_archive_ext(archive_format: ArchiveFormat) -> &'static str {
         ArchiveFormat::TarBzip2 => "tar.bz2",
         ArchiveFormat::TarGzip => "tar.gz",
         ArchiveFormat::TarZstd => "tar.zst",
+        ArchiveFormat::TarZstdSeekable => "tar.szst",
+        ArchiveFormat::TarLz4 => "tar.lz4",
         ArchiveFormat::Tar => "tar",
     }
 }
@@ -256,6 +284,7 @@ pub fn archive_snapshot_package(
     serialize_status_cache(
         snapshot_package.slot(),
         &snapshot_package.slot_deltas,
+        snapshot_package.snapshot_version,
         &snapshot_package
             .snapshot_links
             .path()
@@ -338,10 +367,11 @@ pub fn archive_snapshot_package(
 
         let do_archive_files = |encoder: &mut dyn Write| -> Result<()> {
             let mut archive = tar::Builder::new(encoder);
+            // the version file goes first, so the bank snapshot can be read as it is unpacked
+            archive.append_path_with_name(staging_dir.as_ref().join("version"), "version")?;
             for dir in ["accounts", "snapshots"] {
                 archive.append_dir_all(dir, staging_dir.as_ref().join(dir))?;
             }
-            archive.append_path_with_name(staging_dir.as_ref().join("version"), "version")?;
             archive.into_inner()?;
             Ok(())
         };
@@ -364,6 +394,17 @@ pub fn archive_snapshot_package(
                 do_archive_files(&mut encoder)?;
                 encoder.finish()?;
             }
+            ArchiveFormat::TarZstdSeekable => {
+                let mut encoder = SeekableZstdEncoder::new(archive_file, 0);
+                do_archive_files(&mut encoder)?;
+                encoder.finish()?;
+            }
+            ArchiveFormat::TarLz4 => {
+                let mut encoder = lz4::EncoderBuilder::new().level(1).build(archive_file)?;
+                do_archive_files(&mut encoder)?;
+                let (_output, result) = encoder.finish();
+                result?;
+            }
             ArchiveFormat::Tar => {
                 do_archive_files(&mut archive_file)?;
             }
@@ -647,10 +688,12 @@ pub fn add_bank_snapshot<P: AsRef<Path>>(
 
     let mut bank_serialize = Measure::start("bank-serialize-ms");
     let bank_snapshot_serializer = move |stream: &mut BufWriter<File>| -> Result<()> {
-        let serde_style = match snapshot_version {
-            SnapshotVersion::V1_2_0 => SerdeStyle::Newer,
-        };
-        bank_to_stream(serde_style, stream.by_ref(), bank, snapshot_storages)?;
+        bank_to_stream(
+            snapshot_version.serde_style(),
+            stream.by_ref(),
+            bank,
+            snapshot_storages,
+        )?;
         Ok(())
     };
     let consumed_size =
@@ -680,11 +723,18 @@ pub fn add_bank_snapshot<P: AsRef<Path>>(
 fn serialize_status_cache(
     slot: Slot,
     slot_deltas: &[BankSlotDelta],
+    snapshot_version: SnapshotVersion,
     status_cache_path: &Path,
 ) -> Result<()> {
     let mut status_cache_serialize = Measure::start("status_cache_serialize-ms");
     let consumed_size = serialize_snapshot_data_file(status_cache_path, |stream| {
-        serialize_into(stream, slot_deltas)?;
+        match snapshot_version {
+            SnapshotVersion::V1_2_0 => serialize_into(stream, slot_deltas)?,
+            SnapshotVersion::V1_3_0 => serialize_into(
+                stream,
+                &VersionedSection::new(STATUS_CACHE_SECTION_VERSION, slot_deltas)?,
+            )?,
+        }
         Ok(())
     })?;
     status_cache_serialize.stop();
@@ -817,9 +867,10 @@ pub fn bank_from_snapshot_archives(
         test_hash_calculation,
         accounts_db_skip_shrink,
         Some(full_snapshot_archive_info.slot()),
+        incremental_snapshot_archive_info.map(|info| info.base_slot()),
     ) && limit_load_slot_count_from_snapshot.is_none()
     {
-        panic!("Snapshot bank for slot {} failed to verify", bank.slot());
+        return Err(SnapshotError::BankVerificationFailed(bank.slot()));
     }
     measure_verify.stop();
 
@@ -835,6 +886,81 @@ pub fn bank_from_snapshot_archives(
     Ok((bank, timings))
 }
 
+/// Unpack snapshot archives and open the account storages they hold, without rebuilding the bank
+/// or the accounts index.  Handles either just a full snapshot, or both a full snapshot and an
+/// incremental snapshot.  The storages are unpacked into `account_paths`.
+pub fn snapshot_storages_from_snapshot_archives(
+    account_paths: &[PathBuf],
+    bank_snapshots_dir: impl AsRef<Path>,
+    full_snapshot_archive_info: &FullSnapshotArchiveInfo,
+    incremental_snapshot_archive_info: Option<&IncrementalSnapshotArchiveInfo>,
+) -> Result<SnapshotStorages> {
+    check_are_snapshots_compatible(
+        full_snapshot_archive_info,
+        incremental_snapshot_archive_info,
+    )?;
+
+    let parallel_divisions = std::cmp::min(
+        PARALLEL_UNTAR_READERS_DEFAULT,
+        std::cmp::max(1, num_cpus::get() / 4),
+    );
+
+    let unarchived_full_snapshot = unarchive_snapshot(
+        &bank_snapshots_dir,
+        TMP_SNAPSHOT_ARCHIVE_PREFIX,
+        full_snapshot_archive_info.path(),
+        "snapshot untar",
+        account_paths,
+        full_snapshot_archive_info.archive_format(),
+        parallel_divisions,
+    )?;
+    let mut unarchived_incremental_snapshot = incremental_snapshot_archive_info
+        .map(|incremental_snapshot_archive_info| {
+            unarchive_snapshot(
+                &bank_snapshots_dir,
+                TMP_SNAPSHOT_ARCHIVE_PREFIX,
+                incremental_snapshot_archive_info.path(),
+                "incremental snapshot untar",
+                account_paths,
+                incremental_snapshot_archive_info.archive_format(),
+                parallel_divisions,
+            )
+        })
+        .transpose()?;
+
+    let (full_snapshot_version, full_snapshot_root_paths) =
+        verify_unpacked_snapshots_dir_and_version(
+            &unarchived_full_snapshot.unpacked_snapshots_dir_and_version,
+        )?;
+    let mut unpacked_append_vec_map = unarchived_full_snapshot.unpacked_append_vec_map;
+    let (incremental_snapshot_version, incremental_snapshot_root_paths) =
+        if let Some(ref mut unarchived_incremental_snapshot) = unarchived_incremental_snapshot {
+            let (snapshot_version, bank_snapshot_info) = verify_unpacked_snapshots_dir_and_version(
+                &unarchived_incremental_snapshot.unpacked_snapshots_dir_and_version,
+            )?;
+            unpacked_append_vec_map.extend(std::mem::take(
+                &mut unarchived_incremental_snapshot.unpacked_append_vec_map,
+            ));
+            (Some(snapshot_version), Some(bank_snapshot_info))
+        } else {
+            (None, None)
+        };
+
+    let snapshot_root_paths = SnapshotRootPaths {
+        full_snapshot_root_file_path: full_snapshot_root_paths.snapshot_path,
+        incremental_snapshot_root_file_path: incremental_snapshot_root_paths
+            .map(|root_paths| root_paths.snapshot_path),
+    };
+    deserialize_snapshot_data_files(&snapshot_root_paths, |snapshot_streams| {
+        Ok(snapshot_storages_from_streams(
+            full_snapshot_version.serde_style(),
+            incremental_snapshot_version.map(SnapshotVersion::serde_style),
+            snapshot_streams,
+            &unpacked_append_vec_map,
+        )?)
+    })
+}
+
 /// Rebuild bank from snapshot archives.  This function searches `snapshot_archives_dir` for the
 /// highest full snapshot and highest corresponding incremental snapshot, then rebuilds the bank.
 #[allow(clippy::too_many_arguments)]
@@ -899,17 +1025,20 @@ pub fn bank_from_latest_snapshot_archives(
         accounts_update_notifier,
     )?;
 
-    verify_bank_against_expected_slot_hash(
-        &bank,
-        incremental_snapshot_archive_info.as_ref().map_or(
+    match &incremental_snapshot_archive_info {
+        None => verify_bank_against_expected_slot_hash(
+            &bank,
+            SnapshotType::FullSnapshot,
             full_snapshot_archive_info.slot(),
-            |incremental_snapshot_archive_info| incremental_snapshot_archive_info.slot(),
-        ),
-        incremental_snapshot_archive_info.as_ref().map_or(
             *full_snapshot_archive_info.hash(),
-            |incremental_snapshot_archive_info| *incremental_snapshot_archive_info.hash(),
         ),
-    )?;
+        Some(incremental_snapshot_archive_info) => verify_bank_against_expected_slot_hash(
+            &bank,
+            SnapshotType::IncrementalSnapshot(incremental_snapshot_archive_info.base_slot()),
+            incremental_snapshot_archive_info.slot(),
+            *incremental_snapshot_archive_info.hash(),
+        ),
+    }?;
 
     Ok((
         bank,
@@ -920,14 +1049,19 @@ pub fn bank_from_latest_snapshot_archives(
 }
 
 /// Check to make sure the deserialized bank's slot and hash matches the snapshot archive's slot
-/// and hash
+/// and hash.  The hash of an incremental snapshot archive is the incremental accounts hash.
 fn verify_bank_against_expected_slot_hash(
     bank: &Bank,
+    snapshot_type: SnapshotType,
     expected_slot: Slot,
     expected_hash: Hash,
 ) -> Result<()> {
     let bank_slot = bank.slot();
-    let bank_hash = bank.get_accounts_hash();
+    let bank_hash = snapshot_hash(
+        snapshot_type,
+        bank.get_accounts_hash(),
+        bank.get_incremental_accounts_hash(),
+    );
 
     if bank_slot != expected_slot || bank_hash != expected_hash {
         return Err(SnapshotError::MismatchedSlotHash(
@@ -1077,6 +1211,8 @@ fn archive_format_from_str(archive_format: &str) -> Option<ArchiveFormat> {
         "tar.bz2" => Some(ArchiveFormat::TarBzip2),
         "tar.gz" => Some(ArchiveFormat::TarGzip),
         "tar.zst" => Some(ArchiveFormat::TarZstd),
+        "tar.szst" => Some(ArchiveFormat::TarZstdSeekable),
+        "tar.lz4" => Some(ArchiveFormat::TarLz4),
         "tar" => Some(ArchiveFormat::Tar),
         _ => None,
     }
@@ -1368,6 +1504,25 @@ fn unpack_snapshot_local<T: 'static + Read + std::marker::Send, F: Fn() -> T>(
     Ok(unpacked_append_vec_map)
 }
 
+/// Open a reader over the decompressed contents of a snapshot archive
+fn snapshot_archive_reader(
+    archive_path: &Path,
+    archive_format: ArchiveFormat,
+    num_threads: usize,
+) -> Result<Box<dyn Read + Send>> {
+    let file = File::open(archive_path)?;
+    Ok(match archive_format {
+        ArchiveFormat::TarBzip2 => Box::new(BzDecoder::new(BufReader::new(file))),
+        ArchiveFormat::TarGzip => Box::new(GzDecoder::new(BufReader::new(file))),
+        ArchiveFormat::TarZstd => Box::new(zstd::stream::read::Decoder::new(BufReader::new(file))?),
+        // frames are decompressed in parallel, while the decompressed stream still goes
+        // through `hardened_unpack` like every other format
+        ArchiveFormat::TarZstdSeekable => Box::new(SeekableZstdDecoder::new(file, num_threads)?),
+        ArchiveFormat::TarLz4 => Box::new(lz4::Decoder::new(BufReader::new(file))?),
+        ArchiveFormat::Tar => Box::new(BufReader::new(file)),
+    })
+}
+
 fn untar_snapshot_in<P: AsRef<Path>>(
     snapshot_tar: P,
     unpack_dir: &Path,
@@ -1375,34 +1530,123 @@ fn untar_snapshot_in<P: AsRef<Path>>(
     archive_format: ArchiveFormat,
     parallel_divisions: usize,
 ) -> Result<UnpackedAppendVecMap> {
-    let open_file = || File::open(&snapshot_tar).unwrap();
-    let account_paths_map = match archive_format {
-        ArchiveFormat::TarBzip2 => unpack_snapshot_local(
-            || BzDecoder::new(BufReader::new(open_file())),
-            unpack_dir,
-            account_paths,
-            parallel_divisions,
-        )?,
-        ArchiveFormat::TarGzip => unpack_snapshot_local(
-            || GzDecoder::new(BufReader::new(open_file())),
-            unpack_dir,
-            account_paths,
-            parallel_divisions,
-        )?,
-        ArchiveFormat::TarZstd => unpack_snapshot_local(
-            || zstd::stream::read::Decoder::new(BufReader::new(open_file())).unwrap(),
-            unpack_dir,
-            account_paths,
-            parallel_divisions,
-        )?,
-        ArchiveFormat::Tar => unpack_snapshot_local(
-            || BufReader::new(open_file()),
-            unpack_dir,
-            account_paths,
-            parallel_divisions,
-        )?,
-    };
-    Ok(account_paths_map)
+    unpack_snapshot_local(
+        || {
+            snapshot_archive_reader(snapshot_tar.as_ref(), archive_format, parallel_divisions)
+                .unwrap()
+        },
+        unpack_dir,
+        account_paths,
+        parallel_divisions,
+    )
+}
+
+/// The hash a snapshot archive of `snapshot_type` is identified by: the accounts hash for a full
+/// snapshot, and the incremental accounts hash based on the full snapshot slot for an incremental
+/// snapshot.  A missing incremental accounts hash is reported as the default hash.
+fn snapshot_hash(
+    snapshot_type: SnapshotType,
+    accounts_hash: Hash,
+    incremental_accounts_hash: Option<(Slot, Hash)>,
+) -> Hash {
+    match snapshot_type {
+        SnapshotType::FullSnapshot => accounts_hash,
+        SnapshotType::IncrementalSnapshot(base_slot) => incremental_accounts_hash
+            .filter(|(incremental_base_slot, _)| *incremental_base_slot == base_slot)
+            .map(|(_, hash)| hash)
+            .unwrap_or_default(),
+    }
+}
+
+/// Check a snapshot archive before accepting it: it must decompress completely, and the bank
+/// snapshot inside must be for the expected slot and record the expected hash for an archive of
+/// `snapshot_type`.  The accounts themselves are hashed again when the bank is rebuilt from the
+/// archive.
+pub fn verify_snapshot_archive_slot_hash(
+    archive_path: impl AsRef<Path>,
+    archive_format: ArchiveFormat,
+    snapshot_type: SnapshotType,
+    (expected_slot, expected_hash): (Slot, Hash),
+) -> Result<()> {
+    let archive_path = archive_path.as_ref();
+    let bank_snapshot_path = Path::new("snapshots")
+        .join(expected_slot.to_string())
+        .join(expected_slot.to_string());
+
+    // The bank snapshot is read while it is unpacked once the version file has been, which
+    // archives list first.  Older archives list the version file last, so their bank snapshot is
+    // only read by unpacking them a second time.
+    let mut snapshot_version = None;
+    let mut has_bank_snapshot = false;
+    let mut slot_hash = None;
+    let reader = snapshot_archive_reader(archive_path, archive_format, num_cpus::get())?;
+    let mut archive = Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path == Path::new("version") {
+            snapshot_version = Some(read_snapshot_version(&mut entry)?);
+        } else if path == bank_snapshot_path {
+            has_bank_snapshot = true;
+            if let Some(snapshot_version) = snapshot_version {
+                slot_hash = Some(read_slot_hash(&mut entry, snapshot_version, snapshot_type)?);
+            }
+        }
+    }
+    // read through the end of the compressed stream, so its checksums are verified as well
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
+
+    if has_bank_snapshot && slot_hash.is_none() {
+        let snapshot_version =
+            snapshot_version.ok_or_else(|| get_io_error("snapshot archive has no version file"))?;
+        let reader = snapshot_archive_reader(archive_path, archive_format, num_cpus::get())?;
+        let mut archive = Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.path()? == bank_snapshot_path {
+                slot_hash = Some(read_slot_hash(&mut entry, snapshot_version, snapshot_type)?);
+                break;
+            }
+        }
+    }
+
+    match slot_hash {
+        None => Err(SnapshotError::MissingBankSnapshot(expected_slot)),
+        Some(slot_hash) if slot_hash != (expected_slot, expected_hash) => Err(
+            SnapshotError::MismatchedSlotHash(slot_hash, (expected_slot, expected_hash)),
+        ),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Read the contents of a snapshot version file
+fn read_snapshot_version(version_file: impl Read) -> Result<SnapshotVersion> {
+    let mut snapshot_version = String::new();
+    version_file
+        .take(MAX_SNAPSHOT_VERSION_FILE_SIZE)
+        .read_to_string(&mut snapshot_version)?;
+    SnapshotVersion::maybe_from_string(snapshot_version.trim()).ok_or_else(|| {
+        get_io_error(&format!(
+            "unsupported snapshot version: {}",
+            snapshot_version
+        ))
+    })
+}
+
+/// Read the slot of a bank snapshot, and the hash it records for an archive of `snapshot_type`,
+/// without reading the rest of it
+fn read_slot_hash(
+    bank_snapshot: impl Read,
+    snapshot_version: SnapshotVersion,
+    snapshot_type: SnapshotType,
+) -> Result<(Slot, Hash)> {
+    let mut stream = BufReader::new(bank_snapshot.take(MAX_SNAPSHOT_DATA_FILE_SIZE));
+    let (slot, accounts_hash, incremental_accounts_hash) =
+        slot_and_accounts_hashes_from_stream(snapshot_version.serde_style(), &mut stream)?;
+    Ok((
+        slot,
+        snapshot_hash(snapshot_type, accounts_hash, incremental_accounts_hash),
+    ))
 }
 
 fn verify_unpacked_snapshots_dir_and_version(
@@ -1482,26 +1726,23 @@ fn rebuild_bank_from_snapshots(
     };
 
     let bank = deserialize_snapshot_data_files(&snapshot_root_paths, |snapshot_streams| {
-        Ok(
-            match incremental_snapshot_version.unwrap_or(full_snapshot_version) {
-                SnapshotVersion::V1_2_0 => bank_from_streams(
-                    SerdeStyle::Newer,
-                    snapshot_streams,
-                    account_paths,
-                    unpacked_append_vec_map,
-                    genesis_config,
-                    debug_keys,
-                    additional_builtins,
-                    account_secondary_indexes,
-                    accounts_db_caching_enabled,
-                    limit_load_slot_count_from_snapshot,
-                    shrink_ratio,
-                    verify_index,
-                    accounts_db_config,
-                    accounts_update_notifier,
-                ),
-            }?,
-        )
+        Ok(bank_from_streams(
+            full_snapshot_version.serde_style(),
+            incremental_snapshot_version.map(SnapshotVersion::serde_style),
+            snapshot_streams,
+            account_paths,
+            unpacked_append_vec_map,
+            genesis_config,
+            debug_keys,
+            additional_builtins,
+            account_secondary_indexes,
+            accounts_db_caching_enabled,
+            limit_load_slot_count_from_snapshot,
+            shrink_ratio,
+            verify_index,
+            accounts_db_config,
+            accounts_update_notifier,
+        )?)
     })?;
 
     // The status cache is rebuilt from the latest snapshot.  So, if there's an incremental
@@ -1525,11 +1766,17 @@ fn rebuild_bank_from_snapshots(
             "Rebuilding status cache from {}",
             status_cache_path.display()
         );
-        let slot_deltas: Vec<BankSlotDelta> = bincode::options()
+        let options = bincode::options()
             .with_limit(MAX_SNAPSHOT_DATA_FILE_SIZE)
             .with_fixint_encoding()
-            .allow_trailing_bytes()
-            .deserialize_from(stream)?;
+            .allow_trailing_bytes();
+        let slot_deltas: Vec<BankSlotDelta> =
+            match incremental_snapshot_version.unwrap_or(full_snapshot_version) {
+                SnapshotVersion::V1_2_0 => options.deserialize_from(stream)?,
+                SnapshotVersion::V1_3_0 => options
+                    .deserialize_from::<_, VersionedSection>(stream)?
+                    .decode("status cache", STATUS_CACHE_SECTION_VERSION)?,
+            };
         Ok(slot_deltas)
     })?;
 
@@ -1758,7 +2005,7 @@ pub fn bank_to_incremental_snapshot_archive(
     bank.squash(); // Bank may not be a root
     bank.force_flush_accounts_cache();
     bank.clean_accounts(true, false, Some(full_snapshot_slot));
-    bank.update_accounts_hash();
+    bank.update_incremental_accounts_hash(full_snapshot_slot);
     bank.rehash(); // Bank accounts may have been manually modified by the caller
 
     let temp_dir = tempfile::tempdir_in(bank_snapshots_dir)?;
@@ -2068,6 +2315,22 @@ mod tests {
                 .unwrap(),
             (44, Hash::default(), ArchiveFormat::Tar)
         );
+        assert_eq!(
+            parse_full_snapshot_archive_filename(&format!(
+                "snapshot-45-{}.tar.szst",
+                Hash::default()
+            ))
+            .unwrap(),
+            (45, Hash::default(), ArchiveFormat::TarZstdSeekable)
+        );
+        assert_eq!(
+            parse_full_snapshot_archive_filename(&format!(
+                "snapshot-46-{}.tar.lz4",
+                Hash::default()
+            ))
+            .unwrap(),
+            (46, Hash::default(), ArchiveFormat::TarLz4)
+        );
 
         assert!(parse_full_snapshot_archive_filename("invalid").is_err());
         assert!(
@@ -2663,43 +2926,179 @@ mod tests {
             original_bank.register_tick(&Hash::new_unique());
         }
 
-        let accounts_dir = tempfile::TempDir::new().unwrap();
+        for snapshot_archive_format in [
+            ArchiveFormat::TarBzip2,
+            ArchiveFormat::TarGzip,
+            ArchiveFormat::TarZstd,
+            ArchiveFormat::TarZstdSeekable,
+            ArchiveFormat::TarLz4,
+            ArchiveFormat::Tar,
+        ] {
+            let accounts_dir = tempfile::TempDir::new().unwrap();
+            let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+            let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+
+            let snapshot_archive_info = bank_to_full_snapshot_archive(
+                &bank_snapshots_dir,
+                &original_bank,
+                None,
+                snapshot_archives_dir.path(),
+                snapshot_archive_format,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+
+            let (roundtrip_bank, _) = bank_from_snapshot_archives(
+                &[PathBuf::from(accounts_dir.path())],
+                bank_snapshots_dir.path(),
+                &snapshot_archive_info,
+                None,
+                &genesis_config,
+                None,
+                None,
+                AccountSecondaryIndexes::default(),
+                false,
+                None,
+                AccountShrinkThreshold::default(),
+                false,
+                false,
+                false,
+                Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(original_bank, roundtrip_bank);
+        }
+    }
+
+    #[test]
+    fn test_verify_snapshot_archive_slot_hash() {
+        mundis_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let bank = Bank::new_for_tests(&genesis_config);
+        while !bank.is_complete() {
+            bank.register_tick(&Hash::new_unique());
+        }
+
+        for archive_format in [ArchiveFormat::TarZstdSeekable, ArchiveFormat::TarGzip] {
+            let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+            let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+            let snapshot_archive_info = bank_to_full_snapshot_archive(
+                &bank_snapshots_dir,
+                &bank,
+                None,
+                snapshot_archives_dir.path(),
+                archive_format,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+            let slot = snapshot_archive_info.slot();
+            let hash = *snapshot_archive_info.hash();
+
+            verify_snapshot_archive_slot_hash(
+                snapshot_archive_info.path(),
+                archive_format,
+                SnapshotType::FullSnapshot,
+                (slot, hash),
+            )
+            .unwrap();
+            assert_matches!(
+                verify_snapshot_archive_slot_hash(
+                    snapshot_archive_info.path(),
+                    archive_format,
+                    SnapshotType::FullSnapshot,
+                    (slot, Hash::new_unique()),
+                ),
+                Err(SnapshotError::MismatchedSlotHash(..))
+            );
+            assert_matches!(
+                verify_snapshot_archive_slot_hash(
+                    snapshot_archive_info.path(),
+                    archive_format,
+                    SnapshotType::FullSnapshot,
+                    (slot + 1, hash),
+                ),
+                Err(SnapshotError::MissingBankSnapshot(_))
+            );
+
+            // a truncated archive is rejected
+            let archive_len = fs::metadata(snapshot_archive_info.path()).unwrap().len();
+            fs::OpenOptions::new()
+                .write(true)
+                .open(snapshot_archive_info.path())
+                .unwrap()
+                .set_len(archive_len / 2)
+                .unwrap();
+            assert!(verify_snapshot_archive_slot_hash(
+                snapshot_archive_info.path(),
+                archive_format,
+                SnapshotType::FullSnapshot,
+                (slot, hash),
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_snapshot_archive_slot_hash_version_file_last() {
+        mundis_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let bank = Bank::new_for_tests(&genesis_config);
+        while !bank.is_complete() {
+            bank.register_tick(&Hash::new_unique());
+        }
+
         let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
         let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
-        let snapshot_archive_format = ArchiveFormat::Tar;
-
         let snapshot_archive_info = bank_to_full_snapshot_archive(
             &bank_snapshots_dir,
-            &original_bank,
+            &bank,
             None,
             snapshot_archives_dir.path(),
-            snapshot_archive_format,
+            ArchiveFormat::Tar,
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
         .unwrap();
+        let slot = snapshot_archive_info.slot();
+        let hash = *snapshot_archive_info.hash();
+
+        // rewrite the archive the way archives used to be written, with the version file last
+        let mut entries = vec![];
+        let mut archive = Archive::new(File::open(snapshot_archive_info.path()).unwrap());
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let mut data = vec![];
+            entry.read_to_end(&mut data).unwrap();
+            entries.push((entry.header().clone(), data));
+        }
+        assert_eq!(entries[0].0.path().unwrap(), Path::new("version"));
+        entries.rotate_left(1);
+        let mut archive = tar::Builder::new(File::create(snapshot_archive_info.path()).unwrap());
+        for (header, data) in entries {
+            archive.append(&header, data.as_slice()).unwrap();
+        }
+        archive.into_inner().unwrap();
 
-        let (roundtrip_bank, _) = bank_from_snapshot_archives(
-            &[PathBuf::from(accounts_dir.path())],
-            bank_snapshots_dir.path(),
-            &snapshot_archive_info,
-            None,
-            &genesis_config,
-            None,
-            None,
-            AccountSecondaryIndexes::default(),
-            false,
-            None,
-            AccountShrinkThreshold::default(),
-            false,
-            false,
-            false,
-            Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
-            None,
+        verify_snapshot_archive_slot_hash(
+            snapshot_archive_info.path(),
+            ArchiveFormat::Tar,
+            SnapshotType::FullSnapshot,
+            (slot, hash),
         )
         .unwrap();
-
-        assert_eq!(original_bank, roundtrip_bank);
+        assert_matches!(
+            verify_snapshot_archive_slot_hash(
+                snapshot_archive_info.path(),
+                ArchiveFormat::Tar,
+                SnapshotType::FullSnapshot,
+                (slot, Hash::new_unique()),
+            ),
+            Err(SnapshotError::MismatchedSlotHash(..))
+        );
     }
 
     /// Test roundtrip of bank to a full snapshot, then back again.  This test is more involved
@@ -2903,6 +3302,107 @@ mod tests {
         assert_eq!(*bank4, roundtrip_bank);
     }
 
+    /// Test roundtrip of bank to a 1.2.0 full snapshot and a 1.3.0 incremental snapshot on top of
+    /// it, then back again
+    #[test]
+    fn test_roundtrip_bank_to_and_from_snapshot_mixed_versions() {
+        mundis_logger::setup();
+        let collector = Pubkey::new_unique();
+        let key1 = Keypair::new();
+        let key2 = Keypair::new();
+
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        bank0.transfer(1, &mint_keypair, &key1.pubkey()).unwrap();
+        while !bank0.is_complete() {
+            bank0.register_tick(&Hash::new_unique());
+        }
+
+        let accounts_dir = tempfile::TempDir::new().unwrap();
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archive_format = ArchiveFormat::TarZstd;
+
+        let full_snapshot_archive_info = bank_to_full_snapshot_archive(
+            bank_snapshots_dir.path(),
+            &bank0,
+            Some(SnapshotVersion::V1_2_0),
+            snapshot_archives_dir.path(),
+            snapshot_archive_format,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &collector, 1));
+        bank1.transfer(1, &mint_keypair, &key1.pubkey()).unwrap();
+        bank1.transfer(2, &mint_keypair, &key2.pubkey()).unwrap();
+        while !bank1.is_complete() {
+            bank1.register_tick(&Hash::new_unique());
+        }
+
+        let incremental_snapshot_archive_info = bank_to_incremental_snapshot_archive(
+            bank_snapshots_dir.path(),
+            &bank1,
+            bank0.slot(),
+            Some(SnapshotVersion::V1_3_0),
+            snapshot_archives_dir.path(),
+            snapshot_archive_format,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+        verify_snapshot_archive_slot_hash(
+            incremental_snapshot_archive_info.path(),
+            snapshot_archive_format,
+            SnapshotType::IncrementalSnapshot(bank0.slot()),
+            (
+                incremental_snapshot_archive_info.slot(),
+                *incremental_snapshot_archive_info.hash(),
+            ),
+        )
+        .unwrap();
+        // the archive is identified by its incremental accounts hash, not the full accounts hash
+        assert_eq!(
+            *incremental_snapshot_archive_info.hash(),
+            bank1.get_incremental_accounts_hash().unwrap().1
+        );
+        assert_matches!(
+            verify_snapshot_archive_slot_hash(
+                incremental_snapshot_archive_info.path(),
+                snapshot_archive_format,
+                SnapshotType::FullSnapshot,
+                (
+                    incremental_snapshot_archive_info.slot(),
+                    *incremental_snapshot_archive_info.hash(),
+                ),
+            ),
+            Err(SnapshotError::MismatchedSlotHash(..))
+        );
+
+        let (roundtrip_bank, _) = bank_from_snapshot_archives(
+            &[PathBuf::from(accounts_dir.path())],
+            bank_snapshots_dir.path(),
+            &full_snapshot_archive_info,
+            Some(&incremental_snapshot_archive_info),
+            &genesis_config,
+            None,
+            None,
+            AccountSecondaryIndexes::default(),
+            false,
+            None,
+            AccountShrinkThreshold::default(),
+            false,
+            false,
+            false,
+            Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(*bank1, roundtrip_bank);
+    }
+
     /// Test rebuilding bank from the latest snapshot archives
     #[test]
     fn test_bank_from_latest_snapshot_archives() {
@@ -10,6 +10,7 @@ pub struct TransactionBatch<'a, 'b> {
     bank: &'a Bank,
     sanitized_txs: Cow<'b, [SanitizedTransaction]>,
     pub(crate) needs_unlock: bool,
+    pub(crate) is_bundle: bool,
 }
 
 impl<'a, 'b> TransactionBatch<'a, 'b> {
@@ -24,6 +25,7 @@ impl<'a, 'b> TransactionBatch<'a, 'b> {
             bank,
             sanitized_txs,
             needs_unlock: true,
+            is_bundle: false,
         }
     }
 
@@ -18,6 +18,7 @@ pub struct TransactionErrorMetrics {
     pub not_allowed_during_cluster_maintenance: usize,
     pub invalid_writable_account: usize,
     pub invalid_rent_paying_account: usize,
+    pub max_loaded_accounts_data_size_exceeded: usize,
 }
 
 impl TransactionErrorMetrics {
@@ -54,6 +55,10 @@ impl TransactionErrorMetrics {
             self.invalid_rent_paying_account,
             other.invalid_rent_paying_account
         );
+        saturating_add_assign!(
+            self.max_loaded_accounts_data_size_exceeded,
+            other.max_loaded_accounts_data_size_exceeded
+        );
     }
 
     pub fn report(&self, id: u32, slot: Slot) {
@@ -105,6 +110,11 @@ impl TransactionErrorMetrics {
                 self.invalid_rent_paying_account as i64,
                 i64
             ),
+            (
+                "max_loaded_accounts_data_size_exceeded",
+                self.max_loaded_accounts_data_size_exceeded as i64,
+                i64
+            ),
         );
     }
 }
\ No newline at end of file
@@ -26,14 +26,14 @@ fn bucket_map_test_mt() {
     });
     (0..threads).into_iter().into_par_iter().for_each(|_| {
         let key = Pubkey::new_unique();
-        index.update(&key, |_| Some((vec![0u64], 0)));
+        index.update(&key, |_| Some((vec![0u64], 0))).unwrap();
     });
     let mut timer = Measure::start("bucket_map_test_mt");
     (0..threads).into_iter().into_par_iter().for_each(|_| {
         for _ in 0..items {
             let key = Pubkey::new_unique();
             let ix: u64 = index.bucket_ix(&key) as u64;
-            index.update(&key, |_| Some((vec![ix], 0)));
+            index.update(&key, |_| Some((vec![ix], 0))).unwrap();
             assert_eq!(index.read_value(&key), Some((vec![ix], 0)));
         }
     });
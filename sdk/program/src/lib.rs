@@ -45,6 +45,7 @@ pub mod system_instruction;
 pub mod system_program;
 pub mod sysvar;
 pub mod usafe_memory_utils;
+pub mod wasm_loader_upgradeable;
 
 #[cfg(target_arch = "bpf")]
 pub use mundis_sdk_macro::wasm_bindgen_stub as wasm_bindgen;
@@ -87,6 +88,30 @@ pub mod token_vault {
     }
 }
 
+pub mod stake_pool {
+    pub mod program {
+        crate::declare_id!("StakePoo11111111111111111111111111111111111");
+    }
+}
+
+pub mod name_service {
+    pub mod program {
+        crate::declare_id!("NameService11111111111111111111111111111111");
+    }
+}
+
+pub mod governance {
+    pub mod program {
+        crate::declare_id!("Governance111111111111111111111111111111111");
+    }
+}
+
+pub mod escrow {
+    pub mod program {
+        crate::declare_id!("Escrow1111111111111111111111111111111111111");
+    }
+}
+
 /// Same as `declare_id` except report that this id has been deprecated
 pub use mundis_sdk_macro::program_declare_deprecated_id as declare_deprecated_id;
 /// Convenience macro to declare a static public key and functions to interact with it
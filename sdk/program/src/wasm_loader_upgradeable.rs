@@ -0,0 +1,121 @@
+//! The upgradeable WASM loader.
+//!
+//! A deployed program is split over two accounts: the executable program
+//! account, whose address never changes, and a program data account derived
+//! from it that holds the WASM bytecode and the upgrade authority. Bytecode is
+//! first written into a buffer account and then deployed or upgraded from it.
+
+use crate::{instruction::InstructionError, pubkey::Pubkey};
+
+crate::declare_id!("WasmLoaderUpgradeab1e1111111111111111111111");
+
+/// Upgradeable loader account states
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum UpgradeableLoaderState {
+    /// Account is not initialized.
+    Uninitialized,
+    /// A Buffer account.
+    Buffer {
+        /// Authority address
+        authority_address: Option<Pubkey>,
+        // The raw program data follows this serialized structure in the
+        // account's data.
+    },
+    /// An Program account.
+    Program {
+        /// Address of the ProgramData account.
+        programdata_address: Pubkey,
+    },
+    // A ProgramData account.
+    ProgramData {
+        /// Slot that the program was last modified.
+        slot: u64,
+        /// Address of the Program's upgrade authority.
+        upgrade_authority_address: Option<Pubkey>,
+        // The raw program data follows this serialized structure in the
+        // account's data.
+    },
+}
+
+impl UpgradeableLoaderState {
+    /// Size of a serialized buffer account header
+    pub fn buffer_metadata_size() -> usize {
+        37 // see test_state_size_of
+    }
+
+    /// Size of a buffer account holding `program_len` bytes of bytecode
+    pub fn buffer_len(program_len: usize) -> usize {
+        Self::buffer_metadata_size().saturating_add(program_len)
+    }
+
+    /// Size of a serialized program account
+    pub fn program_len() -> usize {
+        36 // see test_state_size_of
+    }
+
+    /// Size of a serialized program data account header
+    pub fn programdata_metadata_size() -> usize {
+        45 // see test_state_size_of
+    }
+
+    /// Size of a program data account holding `program_len` bytes of bytecode
+    pub fn programdata_len(program_len: usize) -> usize {
+        Self::programdata_metadata_size().saturating_add(program_len)
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, InstructionError> {
+        bincode::deserialize(data).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    pub fn serialize(&self, data: &mut [u8]) -> Result<(), InstructionError> {
+        bincode::serialize_into(data, self).map_err(|_| InstructionError::AccountDataTooSmall)
+    }
+}
+
+/// Returns the address of the program data account of `program_address`
+pub fn get_program_data_address(program_address: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_address.as_ref()], &id()).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_size_of() {
+        let buffer_state = UpgradeableLoaderState::Buffer {
+            authority_address: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(
+            bincode::serialized_size(&buffer_state).unwrap() as usize,
+            UpgradeableLoaderState::buffer_metadata_size()
+        );
+        let program_state = UpgradeableLoaderState::Program {
+            programdata_address: Pubkey::new_unique(),
+        };
+        assert_eq!(
+            bincode::serialized_size(&program_state).unwrap() as usize,
+            UpgradeableLoaderState::program_len()
+        );
+        let programdata_state = UpgradeableLoaderState::ProgramData {
+            slot: 42,
+            upgrade_authority_address: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(
+            bincode::serialized_size(&programdata_state).unwrap() as usize,
+            UpgradeableLoaderState::programdata_metadata_size()
+        );
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let state = UpgradeableLoaderState::ProgramData {
+            slot: 7,
+            upgrade_authority_address: None,
+        };
+        let mut data = vec![0; UpgradeableLoaderState::programdata_len(3)];
+        state.serialize(&mut data).unwrap();
+        assert_eq!(UpgradeableLoaderState::deserialize(&data).unwrap(), state);
+        assert!(state.serialize(&mut [0; 4]).is_err());
+    }
+}
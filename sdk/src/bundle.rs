@@ -0,0 +1,403 @@
+//! The `bundle` module defines groups of transactions that are executed atomically.
+//!
+//! A bundle is submitted to the TPU as one packet per transaction, all of them carrying the id of
+//! the bundle. Its transactions are executed back-to-back in the same slot and are only committed
+//! if every one of them succeeds.
+#![cfg(feature = "full")]
+
+use {
+    crate::{
+        hash::{hashv, Hash, HASH_BYTES},
+        packet::PACKET_DATA_SIZE,
+        sanitize::Sanitize,
+        signature::Signature,
+        transaction::{Result, SanitizedTransaction, TransactionError, VersionedTransaction},
+    },
+    bincode::Options,
+    std::collections::HashSet,
+};
+
+/// Maximum number of transactions in a bundle
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+/// First byte of a bundle packet.
+///
+/// A transaction packet starts with the compact-u16 length of its signatures. A first byte of
+/// `0xff` would announce at least 127 signatures, which can never fit in a packet, so the marker
+/// cannot be mistaken for a transaction.
+pub const BUNDLE_PACKET_MARKER: u8 = 0xff;
+
+/// Size of the header of a bundle packet: the marker, the bundle id, the index of the packet's
+/// transaction and the number of transactions of the bundle
+pub const BUNDLE_PACKET_HEADER_SIZE: usize = 1 + HASH_BYTES + 2;
+
+/// The header of a bundle packet, followed by one transaction of the bundle
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BundlePacketHeader {
+    pub bundle_id: Hash,
+    /// index of the packet's transaction within the bundle
+    pub index: u8,
+    /// number of transactions of the bundle
+    pub count: u8,
+}
+
+impl BundlePacketHeader {
+    /// Splits bundle packet data into its header and the serialized transaction that follows
+    pub fn from_packet_data(data: &[u8]) -> Option<(Self, &[u8])> {
+        if data.len() < BUNDLE_PACKET_HEADER_SIZE || data[0] != BUNDLE_PACKET_MARKER {
+            return None;
+        }
+        let (header, transaction) = data.split_at(BUNDLE_PACKET_HEADER_SIZE);
+        let header = Self {
+            bundle_id: Hash::new(&header[1..=HASH_BYTES]),
+            index: header[HASH_BYTES + 1],
+            count: header[HASH_BYTES + 2],
+        };
+        (header.index < header.count).then(|| (header, transaction))
+    }
+
+    fn to_packet_data(self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(PACKET_DATA_SIZE);
+        data.push(BUNDLE_PACKET_MARKER);
+        data.extend_from_slice(self.bundle_id.as_ref());
+        data.push(self.index);
+        data.push(self.count);
+        data
+    }
+}
+
+/// An ordered group of transactions that are committed all together or not at all
+#[derive(Debug, PartialEq, Default, Eq, Clone, Serialize, Deserialize)]
+pub struct VersionedBundle {
+    pub transactions: Vec<VersionedTransaction>,
+}
+
+impl VersionedBundle {
+    pub fn new(transactions: Vec<VersionedTransaction>) -> Self {
+        Self { transactions }
+    }
+
+    /// Returns the bundle id, the hash of the first signature of each of its transactions
+    pub fn id(&self) -> Hash {
+        Self::id_from_signatures(
+            self.transactions
+                .iter()
+                .filter_map(|transaction| transaction.signatures.first()),
+        )
+    }
+
+    /// Returns the id of the bundle made of the transactions with the given first signatures
+    pub fn id_from_signatures<'a>(signatures: impl Iterator<Item = &'a Signature>) -> Hash {
+        let signatures: Vec<&[u8]> = signatures.map(|signature| signature.as_ref()).collect();
+        hashv(&signatures)
+    }
+
+    /// Checks the number of transactions, that each transaction is well formed and that no
+    /// transaction appears twice
+    pub fn sanitize(&self) -> Result<()> {
+        if self.transactions.is_empty() || self.transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(TransactionError::SanitizeFailure);
+        }
+        let mut signatures = HashSet::new();
+        for transaction in &self.transactions {
+            transaction.sanitize()?;
+            let signature = transaction
+                .signatures
+                .first()
+                .ok_or(TransactionError::SanitizeFailure)?;
+            if !signatures.insert(signature) {
+                return Err(TransactionError::AlreadyProcessed);
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the signatures of every transaction of the bundle
+    pub fn verify(&self) -> Result<()> {
+        self.transactions
+            .iter()
+            .try_for_each(|transaction| transaction.verify_and_hash_message().map(|_| ()))
+    }
+
+    /// Serializes the bundle into the payloads of its packets, one per transaction, each of them
+    /// starting with a [`BundlePacketHeader`]
+    pub fn to_packets_data(&self) -> Result<Vec<Vec<u8>>> {
+        let bundle_id = self.id();
+        let count =
+            u8::try_from(self.transactions.len()).map_err(|_| TransactionError::SanitizeFailure)?;
+        (0..count)
+            .zip(&self.transactions)
+            .map(|(index, transaction)| {
+                let mut data = BundlePacketHeader {
+                    bundle_id,
+                    index,
+                    count,
+                }
+                .to_packet_data();
+                bincode::serialize_into(&mut data, transaction)
+                    .map_err(|_| TransactionError::SanitizeFailure)?;
+                if data.len() > PACKET_DATA_SIZE {
+                    return Err(TransactionError::SanitizeFailure);
+                }
+                Ok(data)
+            })
+            .collect()
+    }
+
+    /// Reassembles a bundle from the data of its packets, in any order.
+    ///
+    /// Returns `None` unless the packets hold every transaction of a single bundle exactly once.
+    pub fn from_packets_data<'a>(packets_data: impl IntoIterator<Item = &'a [u8]>) -> Option<Self> {
+        let mut bundle_id = None;
+        let mut transactions = vec![];
+        for data in packets_data {
+            let (header, transaction) = bundle_transaction_from_packet_data(data)?;
+            if *bundle_id.get_or_insert(header.bundle_id) != header.bundle_id {
+                return None;
+            }
+            if transactions.is_empty() {
+                transactions.resize(header.count as usize, None);
+            }
+            if transactions.len() != header.count as usize {
+                return None;
+            }
+            let slot = &mut transactions[header.index as usize];
+            if slot.is_some() {
+                return None;
+            }
+            *slot = Some(transaction);
+        }
+        let bundle = Self::new(transactions.into_iter().collect::<Option<_>>()?);
+        (Some(bundle.id()) == bundle_id).then(|| bundle)
+    }
+}
+
+/// Returns true if the packet data is part of a bundle rather than a single transaction
+pub fn is_bundle_packet_data(data: &[u8]) -> bool {
+    data.first() == Some(&BUNDLE_PACKET_MARKER)
+}
+
+/// Returns true if a transaction of a bundle writes an account that another one of its
+/// transactions also locks.
+///
+/// The transactions of a bundle are recorded as a single entry, and replay locks the accounts of
+/// all the transactions of an entry at once. Once `execute_conflicting_entries_in_order` is
+/// active, replay instead executes the transactions of an entry with such conflicts one at a
+/// time, in order; until then, such a bundle could not be replayed.
+pub fn has_conflicting_account_locks(transactions: &[SanitizedTransaction]) -> bool {
+    let mut writable_keys = HashSet::new();
+    let mut readonly_keys = HashSet::new();
+    for transaction in transactions {
+        let account_locks = transaction.get_account_locks_unchecked();
+        let conflicts = account_locks
+            .writable
+            .iter()
+            .any(|key| writable_keys.contains(key) || readonly_keys.contains(key))
+            || account_locks
+                .readonly
+                .iter()
+                .any(|key| writable_keys.contains(key));
+        if conflicts {
+            return true;
+        }
+        writable_keys.extend(account_locks.writable);
+        readonly_keys.extend(account_locks.readonly);
+    }
+    false
+}
+
+/// Deserializes the header and the transaction of a bundle packet
+pub fn bundle_transaction_from_packet_data(
+    data: &[u8],
+) -> Option<(BundlePacketHeader, VersionedTransaction)> {
+    let (header, transaction) = BundlePacketHeader::from_packet_data(data)?;
+    let transaction = bincode::options()
+        .with_limit(PACKET_DATA_SIZE as u64)
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(transaction)
+        .ok()?;
+    Some((header, transaction))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            pubkey::Pubkey, signature::Keypair, signer::Signer, system_transaction,
+            transaction::Transaction,
+        },
+    };
+
+    fn new_bundle(num_transactions: usize) -> VersionedBundle {
+        let from = Keypair::new();
+        VersionedBundle::new(
+            (0..num_transactions)
+                .map(|lamports| {
+                    system_transaction::transfer(
+                        &from,
+                        &Keypair::new().pubkey(),
+                        lamports as u64,
+                        Hash::default(),
+                    )
+                    .into()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_bundle_packets_data() {
+        let bundle = new_bundle(3);
+        let packets_data = bundle.to_packets_data().unwrap();
+        assert_eq!(packets_data.len(), 3);
+        for (index, data) in packets_data.iter().enumerate() {
+            assert!(is_bundle_packet_data(data));
+            let (header, transaction) = bundle_transaction_from_packet_data(data).unwrap();
+            assert_eq!(
+                header,
+                BundlePacketHeader {
+                    bundle_id: bundle.id(),
+                    index: index as u8,
+                    count: 3,
+                }
+            );
+            assert_eq!(transaction, bundle.transactions[index]);
+        }
+
+        // The packets can arrive in any order
+        assert_eq!(
+            VersionedBundle::from_packets_data(packets_data.iter().rev().map(Vec::as_slice)),
+            Some(bundle.clone())
+        );
+
+        // Every transaction is needed exactly once
+        assert_eq!(
+            VersionedBundle::from_packets_data(packets_data[..2].iter().map(Vec::as_slice)),
+            None
+        );
+        assert_eq!(
+            VersionedBundle::from_packets_data(
+                [&packets_data[0], &packets_data[0], &packets_data[1]]
+                    .into_iter()
+                    .map(Vec::as_slice)
+            ),
+            None
+        );
+
+        // Packets of other bundles are rejected
+        let other_packets_data = new_bundle(3).to_packets_data().unwrap();
+        assert_eq!(
+            VersionedBundle::from_packets_data(
+                [&packets_data[0], &packets_data[1], &other_packets_data[2]]
+                    .into_iter()
+                    .map(Vec::as_slice)
+            ),
+            None
+        );
+
+        // A bundle id that does not match the transactions is rejected
+        let forged_bundle_id = Hash::new_unique();
+        let mut forged_packets_data = packets_data.clone();
+        for data in &mut forged_packets_data {
+            data[1..=HASH_BYTES].copy_from_slice(forged_bundle_id.as_ref());
+        }
+        assert_eq!(
+            VersionedBundle::from_packets_data(forged_packets_data.iter().map(Vec::as_slice)),
+            None
+        );
+
+        // Transactions are never mistaken for bundles
+        let transaction_data = bincode::serialize(&bundle.transactions[0]).unwrap();
+        assert!(!is_bundle_packet_data(&transaction_data));
+        assert_eq!(bundle_transaction_from_packet_data(&transaction_data), None);
+
+        // Trailing bytes are rejected
+        let mut data = packets_data[0].clone();
+        data.push(0);
+        assert_eq!(bundle_transaction_from_packet_data(&data), None);
+    }
+
+    #[test]
+    fn test_bundle_sanitize_and_verify() {
+        assert_eq!(
+            VersionedBundle::default().sanitize(),
+            Err(TransactionError::SanitizeFailure)
+        );
+        assert_eq!(
+            new_bundle(MAX_BUNDLE_TRANSACTIONS + 1).sanitize(),
+            Err(TransactionError::SanitizeFailure)
+        );
+
+        let mut bundle = new_bundle(2);
+        assert_eq!(bundle.sanitize(), Ok(()));
+        assert_eq!(bundle.verify(), Ok(()));
+
+        bundle.transactions[1] = bundle.transactions[0].clone();
+        assert_eq!(bundle.sanitize(), Err(TransactionError::AlreadyProcessed));
+
+        bundle.transactions[1].signatures[0] = Signature::default();
+        assert_eq!(bundle.verify(), Err(TransactionError::SignatureFailure));
+    }
+
+    #[test]
+    fn test_has_conflicting_account_locks() {
+        let sanitize = |transaction: Transaction| {
+            SanitizedTransaction::try_from_legacy_transaction(transaction).unwrap()
+        };
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Pubkey::new_unique();
+        let dave = Pubkey::new_unique();
+        let pay_carol = sanitize(system_transaction::transfer(
+            &alice,
+            &carol,
+            1,
+            Hash::default(),
+        ));
+        let pay_dave = sanitize(system_transaction::transfer(
+            &bob,
+            &dave,
+            1,
+            Hash::default(),
+        ));
+        assert!(!has_conflicting_account_locks(&[
+            pay_carol.clone(),
+            pay_dave.clone()
+        ]));
+
+        // Both transactions write the account of carol
+        let bob_pays_carol = sanitize(system_transaction::transfer(
+            &bob,
+            &carol,
+            1,
+            Hash::default(),
+        ));
+        assert!(has_conflicting_account_locks(&[pay_carol, bob_pays_carol]));
+
+        // The second transaction spends the lamports received in the first one
+        let fund_bob = sanitize(system_transaction::transfer(
+            &alice,
+            &bob.pubkey(),
+            1,
+            Hash::default(),
+        ));
+        assert!(has_conflicting_account_locks(&[fund_bob, pay_dave]));
+    }
+
+    #[test]
+    fn test_bundle_id() {
+        let bundle = new_bundle(2);
+        let signatures: Vec<_> = bundle
+            .transactions
+            .iter()
+            .map(|transaction| transaction.signatures[0])
+            .collect();
+        assert_eq!(
+            bundle.id(),
+            VersionedBundle::id_from_signatures(signatures.iter())
+        );
+        assert_ne!(bundle.id(), new_bundle(2).id());
+    }
+}
@@ -32,6 +32,9 @@ pub enum ComputeBudgetInstruction {
     /// The value requested must be a multiple of 1024. This new heap region
     /// size applies to each program executed, including all calls to CPIs.
     RequestHeapFrame(u32),
+    /// Set a specific limit, in bytes, on the total data size of the accounts
+    /// the transaction is allowed to load, including its programs.
+    SetLoadedAccountsDataSizeLimit(u32),
 }
 
 impl ComputeBudgetInstruction {
@@ -55,4 +58,13 @@ impl ComputeBudgetInstruction {
             vec![],
         )
     }
+
+    /// Create a `ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit` `Instruction`
+    pub fn set_loaded_accounts_data_size_limit(bytes: u32) -> Instruction {
+        Instruction::new_with_borsh(
+            id(),
+            &ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(bytes),
+            vec![],
+        )
+    }
 }
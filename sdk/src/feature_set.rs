@@ -173,6 +173,18 @@ pub mod warp_timestamp_with_a_vengeance {
     mundis_sdk::declare_id!("3BX6SBeEBibHaVQXywdkcgyUk6evfYZkHdztXiDtEpFS");
 }
 
+pub mod rent_exempt_only_accounts {
+    mundis_sdk::declare_id!("8Lg6cvY3SPhmXJRGpnpftRLTHbC8mAe6Z2a9mZtaVwg3");
+}
+
+pub mod cap_transaction_accounts_data_size {
+    mundis_sdk::declare_id!("Ez2gWBvGiqNkBkqY4LWQkJTbyCFxK9eTH4GHnEM4uJMm");
+}
+
+pub mod execute_conflicting_entries_in_order {
+    mundis_sdk::declare_id!("bz9CfPvkWJjvKdYpaAyAh784AzNeS66kEQrkjivxYaf");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -211,6 +223,9 @@ lazy_static! {
         (default_units_per_instruction::id(), "Default max tx-wide compute units calculated per instruction"),
         (add_shred_type_to_shred_seed::id(), "add shred-type to shred seed #25556"),
         (warp_timestamp_with_a_vengeance::id(), "warp timestamp again, adjust bounding to 150% slow #25666"),
+        (rent_exempt_only_accounts::id(), "stop collecting rent and reject transactions leaving any account rent-paying"),
+        (cap_transaction_accounts_data_size::id(), "cap transaction loaded accounts data size"),
+        (execute_conflicting_entries_in_order::id(), "replay entries whose transactions conflict with each other one transaction at a time, in order"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
@@ -22,6 +22,7 @@ bitflags! {
         const REPAIR         = 0b00000100;
         const SIMPLE_VOTE_TX = 0b00001000;
         const TRACER_TX      = 0b00010000;
+        const BUNDLE         = 0b00100000;
     }
 }
 
@@ -150,6 +151,11 @@ impl Meta {
     pub fn is_tracer_tx(&self) -> bool {
         self.flags.contains(PacketFlags::TRACER_TX)
     }
+
+    #[inline]
+    pub fn is_bundle(&self) -> bool {
+        self.flags.contains(PacketFlags::BUNDLE)
+    }
 }
 
 impl Default for Meta {
@@ -133,6 +133,18 @@ pub enum TransactionError {
     /// Transaction would exceed max Vote Cost Limit
     #[error("Transaction would exceed max Vote Cost Limit")]
     WouldExceedMaxVoteCostLimit,
+
+    /// Transaction leaves an account with data with a lower balance than rent-exempt minimum
+    #[error("Transaction results in an account ({account_index}) without enough lamports to be rent-exempt")]
+    InsufficientFundsForRent { account_index: u8 },
+
+    /// Transaction loads accounts whose total data size exceeds its requested limit
+    #[error("Transaction loads accounts whose total data size exceeds its requested limit")]
+    MaxLoadedAccountsDataSizeExceeded,
+
+    /// Transaction pays less than the local minimum priority fee of a writable account
+    #[error("Transaction pays less than the local minimum priority fee of a writable account")]
+    InsufficientPriorityFee,
 }
 
 impl From<SanitizeError> for TransactionError {
@@ -147,6 +147,14 @@ pub enum TransactionVerificationMode {
 
 pub type Result<T> = result::Result<T, TransactionError>;
 
+/// Data set by the last program to call `set_return_data` while a
+/// transaction was processed.
+#[derive(Debug, PartialEq, Default, Eq, Clone, Serialize, Deserialize, AbiExample)]
+pub struct TransactionReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
 /// An atomically-commited sequence of instructions.
 ///
 /// While [`Instruction`]s are the basic unit of computation in Mundis,
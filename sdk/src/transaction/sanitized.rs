@@ -138,6 +138,14 @@ impl SanitizedTransaction {
         }
     }
 
+    /// Return the addresses loaded from on-chain address lookup tables
+    pub fn get_loaded_addresses(&self) -> LoadedAddresses {
+        match &self.message {
+            SanitizedMessage::V0(sanitized_msg) => sanitized_msg.loaded_addresses.clone(),
+            SanitizedMessage::Legacy(_) => LoadedAddresses::default(),
+        }
+    }
+
     /// Validate and return the account keys locked by this transaction
     pub fn get_account_locks(
         &self,
@@ -3,9 +3,12 @@ use {
     log::*,
     mundis_metrics::{datapoint_warn, inc_new_counter_info},
     mundis_runtime::{bank::Bank, bank_forks::BankForks},
-    mundis_sdk::{hash::Hash, nonce_account, pubkey::Pubkey, signature::Signature},
+    mundis_sdk::{clock::Slot, hash::Hash, nonce_account, pubkey::Pubkey, signature::Signature},
     std::{
-        collections::hash_map::{Entry, HashMap},
+        collections::{
+            hash_map::{Entry, HashMap},
+            VecDeque,
+        },
         net::{SocketAddr, UdpSocket},
         sync::{
             mpsc::{Receiver, RecvTimeoutError},
@@ -24,6 +27,8 @@ const DEFAULT_RETRY_RATE_MS: u64 = 2_000;
 const DEFAULT_LEADER_FORWARD_COUNT: u64 = 2;
 /// Default max number of time the service will retry broadcast
 const DEFAULT_SERVICE_MAX_RETRIES: usize = usize::MAX;
+/// Number of durable-nonce transactions whose outcome is remembered
+const MAX_DURABLE_NONCE_STATUSES: usize = 100_000;
 
 pub struct SendTransactionService {
     thread: JoinHandle<()>,
@@ -57,6 +62,43 @@ impl TransactionInfo {
     }
 }
 
+/// What became of a durable-nonce transaction handed to the service
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DurableNonceTransactionStatus {
+    /// The nonce has not advanced yet, the transaction is still being retried
+    Pending,
+    /// The transaction landed in the given slot and advanced the nonce
+    Landed(Slot),
+    /// The nonce was advanced by another transaction, this one can never land
+    NonceConsumed,
+    /// The service stopped retrying before the nonce advanced
+    MaxRetriesElapsed,
+}
+
+/// Statuses of the most recently submitted durable-nonce transactions, by signature
+#[derive(Default)]
+pub struct DurableNonceStatuses {
+    statuses: HashMap<Signature, DurableNonceTransactionStatus>,
+    signatures: VecDeque<Signature>,
+}
+
+impl DurableNonceStatuses {
+    fn insert(&mut self, signature: Signature, status: DurableNonceTransactionStatus) {
+        if self.statuses.insert(signature, status).is_none() {
+            self.signatures.push_back(signature);
+        }
+        while self.signatures.len() > MAX_DURABLE_NONCE_STATUSES {
+            if let Some(signature) = self.signatures.pop_front() {
+                self.statuses.remove(&signature);
+            }
+        }
+    }
+
+    pub fn get(&self, signature: &Signature) -> Option<DurableNonceTransactionStatus> {
+        self.statuses.get(signature).copied()
+    }
+}
+
 #[derive(Default, Debug, PartialEq)]
 struct ProcessTransactionsResult {
     rooted: u64,
@@ -65,6 +107,7 @@ struct ProcessTransactionsResult {
     max_retries_elapsed: u64,
     failed: u64,
     retained: u64,
+    nonce_consumed: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -109,6 +152,26 @@ impl SendTransactionService {
         leader_info: Option<T>,
         receiver: Receiver<TransactionInfo>,
         config: Config,
+    ) -> Self {
+        Self::new_with_durable_nonce_statuses(
+            tpu_address,
+            bank_forks,
+            leader_info,
+            receiver,
+            config,
+            Arc::default(),
+        )
+    }
+
+    /// Like `new_with_config`, recording the outcome of durable-nonce transactions in
+    /// `durable_nonce_statuses`
+    pub fn new_with_durable_nonce_statuses<T: TpuInfo + std::marker::Send + 'static>(
+        tpu_address: SocketAddr,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        leader_info: Option<T>,
+        receiver: Receiver<TransactionInfo>,
+        config: Config,
+        durable_nonce_statuses: Arc<RwLock<DurableNonceStatuses>>,
     ) -> Self {
         let thread = Self::retry_thread(
             tpu_address,
@@ -116,6 +179,7 @@ impl SendTransactionService {
             bank_forks.clone(),
             leader_info,
             config,
+            durable_nonce_statuses,
         );
         Self { thread }
     }
@@ -126,6 +190,7 @@ impl SendTransactionService {
         bank_forks: Arc<RwLock<BankForks>>,
         mut leader_info: Option<T>,
         config: Config,
+        durable_nonce_statuses: Arc<RwLock<DurableNonceStatuses>>,
     ) -> JoinHandle<()> {
         let mut last_status_check = Instant::now();
         let mut last_leader_refresh = Instant::now();
@@ -168,6 +233,12 @@ impl SendTransactionService {
                             }
                             if transactions_len < MAX_TRANSACTION_QUEUE_SIZE {
                                 inc_new_counter_info!("send_transaction_service-insert-tx", 1);
+                                if transaction_info.durable_nonce_info.is_some() {
+                                    durable_nonce_statuses.write().unwrap().insert(
+                                        transaction_info.signature,
+                                        DurableNonceTransactionStatus::Pending,
+                                    );
+                                }
                                 entry.or_insert(transaction_info);
                             } else {
                                 datapoint_warn!("send_transaction_service-queue-overflow");
@@ -198,6 +269,7 @@ impl SendTransactionService {
                             &send_socket,
                             &tpu_address,
                             &mut transactions,
+                            &durable_nonce_statuses,
                             &leader_info,
                             &config,
                         );
@@ -220,38 +292,58 @@ impl SendTransactionService {
         send_socket: &UdpSocket,
         tpu_address: &SocketAddr,
         transactions: &mut HashMap<Signature, TransactionInfo>,
+        durable_nonce_statuses: &RwLock<DurableNonceStatuses>,
         leader_info: &Option<T>,
         config: &Config,
     ) -> ProcessTransactionsResult {
         let mut result = ProcessTransactionsResult::default();
+        let mut durable_nonce_statuses = durable_nonce_statuses.write().unwrap();
 
         transactions.retain(|signature, mut transaction_info| {
             if transaction_info.durable_nonce_info.is_some() {
                 inc_new_counter_info!("send_transaction_service-nonced", 1);
             }
-            if root_bank.has_signature(signature) {
+            if let Some((slot, _)) = root_bank.get_signature_status_slot(signature) {
                 info!("Transaction is rooted: {}", signature);
                 result.rooted += 1;
                 inc_new_counter_info!("send_transaction_service-rooted", 1);
+                if transaction_info.durable_nonce_info.is_some() {
+                    durable_nonce_statuses
+                        .insert(*signature, DurableNonceTransactionStatus::Landed(slot));
+                }
                 return false;
             }
+            let signature_status = working_bank.get_signature_status_slot(signature);
             if let Some((nonce_pubkey, durable_nonce)) = transaction_info.durable_nonce_info {
+                // Durable-nonce transactions don't expire with their blockhash, they are
+                // retried until the nonce advances
                 let nonce_account = working_bank.get_account(&nonce_pubkey).unwrap_or_default();
                 let verify_nonce_account = nonce_account::verify_nonce_account(
                     &nonce_account,
                     &durable_nonce,
                     true,
                 );
-                if verify_nonce_account.is_none()
-                    && working_bank.get_signature_status_slot(signature).is_none()
-                {
-                    info!("Dropping expired durable-nonce transaction: {}", signature);
-                    result.expired += 1;
-                    inc_new_counter_info!("send_transaction_service-expired", 1);
-                    return false;
+                if verify_nonce_account.is_none() {
+                    match &signature_status {
+                        Some((slot, _)) => {
+                            // This transaction advanced the nonce, keep it until it's rooted
+                            durable_nonce_statuses
+                                .insert(*signature, DurableNonceTransactionStatus::Landed(*slot));
+                        }
+                        None => {
+                            info!(
+                                "Dropping durable-nonce transaction, nonce consumed: {}",
+                                signature
+                            );
+                            result.nonce_consumed += 1;
+                            inc_new_counter_info!("send_transaction_service-nonce_consumed", 1);
+                            durable_nonce_statuses
+                                .insert(*signature, DurableNonceTransactionStatus::NonceConsumed);
+                            return false;
+                        }
+                    }
                 }
-            }
-            if transaction_info.last_valid_block_height < root_bank.block_height() {
+            } else if transaction_info.last_valid_block_height < root_bank.block_height() {
                 info!("Dropping expired transaction: {}", signature);
                 result.expired += 1;
                 inc_new_counter_info!("send_transaction_service-expired", 1);
@@ -268,11 +360,15 @@ impl SendTransactionService {
                     info!("Dropping transaction due to max retries: {}", signature);
                     result.max_retries_elapsed += 1;
                     inc_new_counter_info!("send_transaction_service-max_retries", 1);
+                    if transaction_info.durable_nonce_info.is_some() && signature_status.is_none() {
+                        durable_nonce_statuses
+                            .insert(*signature, DurableNonceTransactionStatus::MaxRetriesElapsed);
+                    }
                     return false;
                 }
             }
 
-            match working_bank.get_signature_status_slot(signature) {
+            match signature_status {
                 None => {
                     // Transaction is unknown to the working bank, it might have been
                     // dropped or landed in another fork.  Re-send it
@@ -301,11 +397,16 @@ impl SendTransactionService {
                     }
                     true
                 }
-                Some((_slot, status)) => {
+                Some((slot, status)) => {
                     if status.is_err() {
                         info!("Dropping failed transaction: {}", signature);
                         result.failed += 1;
                         inc_new_counter_info!("send_transaction_service-failed", 1);
+                        // A failed durable-nonce transaction still advances its nonce
+                        if transaction_info.durable_nonce_info.is_some() {
+                            durable_nonce_statuses
+                                .insert(*signature, DurableNonceTransactionStatus::Landed(slot));
+                        }
                         false
                     } else {
                         result.retained += 1;
@@ -408,6 +509,7 @@ mod test {
         };
 
         let mut transactions = HashMap::new();
+        let durable_nonce_statuses = RwLock::default();
 
         info!("Expired transactions are dropped...");
         transactions.insert(
@@ -426,6 +528,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -455,6 +558,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -484,6 +588,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -513,6 +618,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -543,6 +649,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -583,6 +690,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -601,6 +709,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -669,6 +778,7 @@ mod test {
         };
 
         let mut transactions = HashMap::new();
+        let durable_nonce_statuses = RwLock::default();
 
         info!("Rooted durable-nonce transactions are dropped...");
         transactions.insert(
@@ -687,6 +797,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -698,6 +809,13 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        assert_eq!(
+            durable_nonce_statuses
+                .read()
+                .unwrap()
+                .get(&rooted_signature),
+            Some(DurableNonceTransactionStatus::Landed(root_bank.slot()))
+        );
         // Nonce expired case
         transactions.insert(
             rooted_signature,
@@ -715,6 +833,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -727,8 +846,7 @@ mod test {
             }
         );
 
-        // Expired durable-nonce transactions are dropped; nonce has advanced...
-        info!("Expired durable-nonce transactions are dropped...");
+        info!("Durable-nonce transactions whose nonce was consumed by another are dropped...");
         transactions.insert(
             Signature::default(),
             TransactionInfo::new(
@@ -745,6 +863,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -752,11 +871,19 @@ mod test {
         assert_eq!(
             result,
             ProcessTransactionsResult {
-                expired: 1,
+                nonce_consumed: 1,
                 ..ProcessTransactionsResult::default()
             }
         );
-        // ... or last_valid_block_height timeout has passed
+        assert_eq!(
+            durable_nonce_statuses
+                .read()
+                .unwrap()
+                .get(&Signature::default()),
+            Some(DurableNonceTransactionStatus::NonceConsumed)
+        );
+
+        info!("Durable-nonce transactions outlive their last_valid_block_height...");
         transactions.insert(
             Signature::default(),
             TransactionInfo::new(
@@ -773,17 +900,19 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
-        assert!(transactions.is_empty());
+        assert_eq!(transactions.len(), 1);
         assert_eq!(
             result,
             ProcessTransactionsResult {
-                expired: 1,
+                retried: 1,
                 ..ProcessTransactionsResult::default()
             }
         );
+        transactions.clear();
 
         info!("Failed durable-nonce transactions are dropped...");
         transactions.insert(
@@ -802,6 +931,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -813,6 +943,13 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        assert_eq!(
+            durable_nonce_statuses
+                .read()
+                .unwrap()
+                .get(&failed_signature),
+            Some(DurableNonceTransactionStatus::Landed(working_bank.slot()))
+        );
 
         info!("Non-rooted durable-nonce transactions are kept...");
         transactions.insert(
@@ -831,6 +968,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -842,6 +980,13 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        assert_eq!(
+            durable_nonce_statuses
+                .read()
+                .unwrap()
+                .get(&non_rooted_signature),
+            Some(DurableNonceTransactionStatus::Landed(working_bank.slot()))
+        );
         transactions.clear();
 
         info!("Unknown durable-nonce transactions are retried until nonce advances...");
@@ -861,6 +1006,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -892,6 +1038,7 @@ mod test {
             &send_socket,
             &tpu_address,
             &mut transactions,
+            &durable_nonce_statuses,
             &None,
             &config,
         );
@@ -899,7 +1046,7 @@ mod test {
         assert_eq!(
             result,
             ProcessTransactionsResult {
-                expired: 1,
+                nonce_consumed: 1,
                 ..ProcessTransactionsResult::default()
             }
         );
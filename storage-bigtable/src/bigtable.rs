@@ -717,7 +717,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         let mut bytes_written = 0;
         let mut new_row_data = vec![];
         for (row_key, data) in cells {
-            let data = compress_best(&bincode::serialize(&data).unwrap())?;
+            let data = serialize_bincode_cell_data(data)?;
             bytes_written += data.len();
             new_row_data.push((row_key, vec![("bin".to_string(), data)]));
         }
@@ -737,9 +737,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         let mut bytes_written = 0;
         let mut new_row_data = vec![];
         for (row_key, data) in cells {
-            let mut buf = Vec::with_capacity(data.encoded_len());
-            data.encode(&mut buf).unwrap();
-            let data = compress_best(&buf)?;
+            let data = serialize_protobuf_cell_data(data)?;
             bytes_written += data.len();
             new_row_data.push((row_key, vec![("proto".to_string(), data)]));
         }
@@ -749,6 +747,22 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
     }
 }
 
+pub(crate) fn serialize_bincode_cell_data<T>(data: &T) -> Result<CellValue>
+where
+    T: serde::ser::Serialize,
+{
+    Ok(compress_best(&bincode::serialize(data).unwrap())?)
+}
+
+pub(crate) fn serialize_protobuf_cell_data<T>(data: &T) -> Result<CellValue>
+where
+    T: prost::Message,
+{
+    let mut buf = Vec::with_capacity(data.encoded_len());
+    data.encode(&mut buf).unwrap();
+    Ok(compress_best(&buf)?)
+}
+
 pub(crate) fn deserialize_protobuf_or_bincode_cell_data<B, P>(
     row_data: RowDataSlice,
     table: &str,
@@ -816,7 +830,9 @@ mod tests {
         super::*,
         crate::StoredConfirmedBlock,
         prost::Message,
-        mundis_sdk::{hash::Hash, signature::Keypair, system_transaction},
+        mundis_sdk::{
+            hash::Hash, message::v0::LoadedAddresses, signature::Keypair, system_transaction,
+        },
         mundis_storage_proto::convert::generated,
         mundis_transaction_status::{
             ConfirmedBlock, ConfirmedBlockWithOptionalMetadata, TransactionStatusMeta,
@@ -853,7 +869,7 @@ mod tests {
         let recipient = mundis_sdk::pubkey::new_rand();
         let transaction = system_transaction::transfer(&from, &recipient, 42, Hash::default());
         let with_meta = TransactionWithMetadata {
-            transaction,
+            transaction: transaction.into(),
             meta: TransactionStatusMeta {
                 status: Ok(()),
                 fee: 1,
@@ -864,6 +880,9 @@ mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: LoadedAddresses::default(),
+                compute_units_consumed: Some(1234),
             },
         };
         let expected_block = ConfirmedBlock {
@@ -924,6 +943,7 @@ mod tests {
                 meta.pre_token_balances = None; // Legacy bincode implementation does not support token balances
                 meta.post_token_balances = None; // Legacy bincode implementation does not support token balances
                 meta.rewards = None; // Legacy bincode implementation does not support rewards
+                meta.compute_units_consumed = None; // Legacy bincode implementation does not support compute units
             }
             assert_eq!(
                 ConfirmedBlockWithOptionalMetadata::from(block),
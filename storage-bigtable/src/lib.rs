@@ -1,16 +1,18 @@
 #![allow(clippy::integer_arithmetic)]
 use {
-    crate::bigtable::RowKey,
+    crate::bigtable::{RowData, RowKey},
+    async_trait::async_trait,
     log::*,
     serde::{Deserialize, Serialize},
     mundis_metrics::inc_new_counter_debug,
     mundis_sdk::{
         clock::{Slot, UnixTimestamp},
         deserialize_utils::default_on_eof,
+        message::v0::LoadedAddresses,
         pubkey::Pubkey,
         signature::Signature,
         sysvar::is_sysvar_id,
-        transaction::{Transaction, TransactionError},
+        transaction::{TransactionError, VersionedTransaction},
     },
     mundis_storage_proto::convert::{generated, tx_by_addr},
     mundis_transaction_status::{
@@ -35,13 +37,19 @@ extern crate serde_derive;
 mod access_token;
 mod bigtable;
 mod compression;
+mod local_storage;
 mod root_ca_certificate;
 
+pub use local_storage::LocalLedgerStorage;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
     BigTableError(bigtable::Error),
 
+    #[error("RocksDB: {0}")]
+    RocksDbError(rocksdb::Error),
+
     #[error("I/O Error: {0}")]
     IoError(std::io::Error),
 
@@ -61,6 +69,12 @@ impl std::convert::From<bigtable::Error> for Error {
     }
 }
 
+impl std::convert::From<rocksdb::Error> for Error {
+    fn from(err: rocksdb::Error) -> Self {
+        Self::RocksDbError(err)
+    }
+}
+
 impl std::convert::From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
         Self::IoError(err)
@@ -69,6 +83,61 @@ impl std::convert::From<std::io::Error> for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Long-term storage of confirmed blocks, along with the transaction and address indexes built
+/// from them when a block is uploaded
+///
+/// `LedgerStorage` keeps the ledger in a BigTable instance, `LocalLedgerStorage` in a RocksDB
+/// database on local disk. Both use the same tables, row keys and cell encoding.
+#[async_trait]
+pub trait LedgerStorageAdapter: Send + Sync {
+    /// Return the available slot that contains a block
+    async fn get_first_available_block(&self) -> Result<Option<Slot>>;
+
+    /// Fetch the next slots after the provided slot that contains a block
+    ///
+    /// start_slot: slot to start the search from (inclusive)
+    /// limit: stop after this many slots have been found; if limit==0, all records in the table
+    /// after start_slot will be read
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>>;
+
+    /// Fetch the confirmed block from the desired slot
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlockWithOptionalMetadata>;
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus>;
+
+    /// Fetch a confirmed transaction
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithOptionalMetadata>>;
+
+    /// Get confirmed signatures for the provided address, in descending ledger order
+    ///
+    /// address: address to search for
+    /// before_signature: start with the first signature older than this one
+    /// until_signature: end with the last signature more recent than this one
+    /// limit: stop after this many signatures; if limit==0, all records in the table will be read
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    >;
+
+    // Upload a new confirmed block and associated meta data.
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: ConfirmedBlock,
+    ) -> Result<()>;
+}
+
 // Convert a slot to its bucket representation whereby lower slots are always lexically ordered
 // before higher slots
 fn slot_to_key(slot: Slot) -> String {
@@ -166,7 +235,7 @@ impl From<StoredConfirmedBlock> for ConfirmedBlockWithOptionalMetadata {
 
 #[derive(Serialize, Deserialize)]
 struct StoredConfirmedBlockTransaction {
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     meta: Option<StoredConfirmedBlockTransactionStatusMeta>,
 }
 
@@ -219,6 +288,9 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            compute_units_consumed: None,
         }
     }
 }
@@ -342,6 +414,196 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+fn confirmed_block_from_cell_data(
+    slot: Slot,
+    block_cell_data: bigtable::CellData<StoredConfirmedBlock, generated::ConfirmedBlock>,
+) -> Result<ConfirmedBlockWithOptionalMetadata> {
+    Ok(match block_cell_data {
+        bigtable::CellData::Bincode(block) => block.into(),
+        bigtable::CellData::Protobuf(block) => block.try_into().map_err(|_err| {
+            bigtable::Error::ObjectCorrupt(format!("blocks/{}", slot_to_blocks_key(slot)))
+        })?,
+    })
+}
+
+// Pick the transaction at `index` out of the block a `tx` row points to
+fn confirmed_transaction_from_block(
+    signature: &Signature,
+    slot: Slot,
+    index: u32,
+    block: ConfirmedBlockWithOptionalMetadata,
+) -> Option<ConfirmedTransactionWithOptionalMetadata> {
+    match block.transactions.into_iter().nth(index as usize) {
+        None => {
+            // report this somewhere actionable?
+            warn!("Transaction info for {} is corrupt", signature);
+            None
+        }
+        Some(bucket_block_transaction) => {
+            if bucket_block_transaction.transaction.signatures[0] != *signature {
+                warn!(
+                    "Transaction info or confirmed block for {} is corrupt",
+                    signature
+                );
+                None
+            } else {
+                Some(ConfirmedTransactionWithOptionalMetadata {
+                    slot,
+                    transaction: bucket_block_transaction,
+                    block_time: block.block_time,
+                })
+            }
+        }
+    }
+}
+
+// Collect the signatures held by the `tx-by-addr` rows of an address, in descending ledger order,
+// leaving out the records from `before_transaction_index` on in `first_slot` and up to
+// `until_transaction_index` in `last_slot`
+fn confirmed_signatures_from_tx_by_addr_rows(
+    address_prefix: &str,
+    tx_by_addr_data: Vec<(RowKey, RowData)>,
+    first_slot: Slot,
+    before_transaction_index: u32,
+    last_slot: Slot,
+    until_transaction_index: u32,
+    limit: usize,
+) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+    let mut infos = vec![];
+
+    'outer: for (row_key, data) in tx_by_addr_data {
+        let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
+            bigtable::Error::ObjectCorrupt(format!(
+                "Failed to convert key to slot: tx-by-addr/{}",
+                row_key
+            ))
+        })?;
+
+        let deserialized_cell_data = bigtable::deserialize_protobuf_or_bincode_cell_data::<
+            Vec<LegacyTransactionByAddrInfo>,
+            tx_by_addr::TransactionByAddr,
+        >(&data, "tx-by-addr", row_key.clone())?;
+
+        let mut cell_data: Vec<TransactionByAddrInfo> = match deserialized_cell_data {
+            bigtable::CellData::Bincode(tx_by_addr) => {
+                tx_by_addr.into_iter().map(|legacy| legacy.into()).collect()
+            }
+            bigtable::CellData::Protobuf(tx_by_addr) => tx_by_addr.try_into().map_err(|error| {
+                bigtable::Error::ObjectCorrupt(format!(
+                    "Failed to deserialize: {}: tx-by-addr/{}",
+                    error,
+                    row_key.clone()
+                ))
+            })?,
+        };
+
+        cell_data.reverse();
+        for tx_by_addr_info in cell_data.into_iter() {
+            // Filter out records before `before_transaction_index`
+            if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
+                continue;
+            }
+            // Filter out records after `until_transaction_index`
+            if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
+                continue;
+            }
+            infos.push((
+                ConfirmedTransactionStatusWithSignature {
+                    signature: tx_by_addr_info.signature,
+                    slot,
+                    err: tx_by_addr_info.err,
+                    memo: tx_by_addr_info.memo,
+                    block_time: tx_by_addr_info.block_time,
+                },
+                tx_by_addr_info.index,
+            ));
+            // Respect limit
+            if infos.len() >= limit {
+                break 'outer;
+            }
+        }
+    }
+    Ok(infos)
+}
+
+// The rows written to each table when a confirmed block is uploaded
+struct ConfirmedBlockRows {
+    tx_cells: Vec<(RowKey, TransactionInfo)>,
+    tx_by_addr_cells: Vec<(RowKey, tx_by_addr::TransactionByAddr)>,
+    blocks_cells: [(RowKey, generated::ConfirmedBlock); 1],
+    num_transactions: usize,
+}
+
+impl ConfirmedBlockRows {
+    fn new(slot: Slot, confirmed_block: ConfirmedBlock) -> Self {
+        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
+
+        let mut tx_cells = vec![];
+        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+            let TransactionWithMetadata { meta, transaction } = transaction_with_meta;
+            let err = meta.status.clone().err();
+            let index = index as u32;
+            let signature = transaction.signatures[0];
+            let memo = extract_and_fmt_memos(&transaction.message);
+
+            let account_keys = transaction
+                .message
+                .static_account_keys_iter()
+                .chain(meta.loaded_addresses.writable.iter())
+                .chain(meta.loaded_addresses.readonly.iter());
+            for address in account_keys {
+                if !is_sysvar_id(address) {
+                    by_addr
+                        .entry(address)
+                        .or_default()
+                        .push(TransactionByAddrInfo {
+                            signature,
+                            err: err.clone(),
+                            index,
+                            memo: memo.clone(),
+                            block_time: confirmed_block.block_time,
+                        });
+                }
+            }
+
+            tx_cells.push((
+                signature.to_string(),
+                TransactionInfo {
+                    slot,
+                    index,
+                    err,
+                    memo,
+                },
+            ));
+        }
+
+        let tx_by_addr_cells: Vec<_> = by_addr
+            .into_iter()
+            .map(|(address, transaction_info_by_addr)| {
+                (
+                    format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
+                    tx_by_addr::TransactionByAddr {
+                        tx_by_addrs: transaction_info_by_addr
+                            .into_iter()
+                            .map(|by_addr| by_addr.into())
+                            .collect(),
+                    },
+                )
+            })
+            .collect();
+
+        let num_transactions = confirmed_block.transactions.len();
+        let blocks_cells = [(slot_to_blocks_key(slot), confirmed_block.into())];
+
+        Self {
+            tx_cells,
+            tx_by_addr_cells,
+            blocks_cells,
+            num_transactions,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct LedgerStorage {
     connection: bigtable::BigTableConnection,
@@ -359,41 +621,6 @@ impl LedgerStorage {
         Ok(Self { connection })
     }
 
-    /// Return the available slot that contains a block
-    pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
-        debug!("LedgerStorage::get_first_available_block request received");
-        inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable.get_row_keys("blocks", None, None, 1).await?;
-        if blocks.is_empty() {
-            return Ok(None);
-        }
-        Ok(key_to_slot(&blocks[0]))
-    }
-
-    /// Fetch the next slots after the provided slot that contains a block
-    ///
-    /// start_slot: slot to start the search from (inclusive)
-    /// limit: stop after this many slots have been found; if limit==0, all records in the table
-    /// after start_slot will be read
-    pub async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
-        debug!(
-            "LedgerStorage::get_confirmed_blocks request received: {:?} {:?}",
-            start_slot, limit
-        );
-        inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable
-            .get_row_keys(
-                "blocks",
-                Some(slot_to_blocks_key(start_slot)),
-                None,
-                limit as i64,
-            )
-            .await?;
-        Ok(blocks.into_iter().filter_map(|s| key_to_slot(&s)).collect())
-    }
-
     // Fetches and gets a vector of confirmed blocks via a multirow fetch
     #[allow(clippy::needless_lifetimes)]
     pub async fn get_confirmed_blocks_with_data<'a>(
@@ -425,11 +652,146 @@ impl LedgerStorage {
         Ok(data)
     }
 
-    /// Fetch the confirmed block from the desired slot
-    pub async fn get_confirmed_block(
-        &self,
-        slot: Slot,
-    ) -> Result<ConfirmedBlockWithOptionalMetadata> {
+    // Delete a confirmed block and associated meta data.
+    pub async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+        let mut addresses: HashSet<&Pubkey> = HashSet::new();
+        let mut expected_tx_infos: HashMap<String, UploadedTransaction> = HashMap::new();
+        let confirmed_block = self.get_confirmed_block(slot).await?;
+        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+            let TransactionWithOptionalMetadata { meta, transaction } = transaction_with_meta;
+            let signature = transaction.signatures[0];
+            let index = index as u32;
+            let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
+
+            let loaded_addresses = meta.as_ref().map(|meta| &meta.loaded_addresses);
+            let account_keys = transaction.message.static_account_keys_iter().chain(
+                loaded_addresses
+                    .into_iter()
+                    .flat_map(|loaded| loaded.writable.iter().chain(loaded.readonly.iter())),
+            );
+            for address in account_keys {
+                if !is_sysvar_id(address) {
+                    addresses.insert(address);
+                }
+            }
+
+            expected_tx_infos.insert(
+                signature.to_string(),
+                UploadedTransaction { slot, index, err },
+            );
+        }
+
+        let address_slot_rows: Vec<_> = addresses
+            .into_iter()
+            .map(|address| format!("{}/{}", address, slot_to_tx_by_addr_key(slot)))
+            .collect();
+
+        let tx_deletion_rows = if !expected_tx_infos.is_empty() {
+            let signatures = expected_tx_infos
+                .iter()
+                .map(|(signature, _info)| signature)
+                .cloned()
+                .collect::<Vec<_>>();
+            let fetched_tx_infos: HashMap<String, std::result::Result<UploadedTransaction, _>> =
+                self.connection
+                    .get_bincode_cells_with_retry::<TransactionInfo>("tx", &signatures)
+                    .await?
+                    .into_iter()
+                    .map(|(signature, tx_info_res)| (signature, tx_info_res.map(Into::into)))
+                    .collect::<HashMap<_, _>>();
+
+            let mut deletion_rows = Vec::with_capacity(expected_tx_infos.len());
+            for (signature, expected_tx_info) in expected_tx_infos {
+                match fetched_tx_infos.get(&signature) {
+                    Some(Ok(fetched_tx_info)) if fetched_tx_info == &expected_tx_info => {
+                        deletion_rows.push(signature);
+                    }
+                    Some(Ok(fetched_tx_info)) => {
+                        warn!(
+                            "skipped tx row {} because the bigtable entry ({:?}) did not match to {:?}",
+                            signature,
+                            fetched_tx_info,
+                            &expected_tx_info,
+                        );
+                    }
+                    Some(Err(err)) => {
+                        warn!(
+                            "skipped tx row {} because the bigtable entry was corrupted: {:?}",
+                            signature, err
+                        );
+                    }
+                    None => {
+                        warn!("skipped tx row {} because it was not found", signature);
+                    }
+                }
+            }
+            deletion_rows
+        } else {
+            vec![]
+        };
+
+        if !dry_run {
+            if !address_slot_rows.is_empty() {
+                self.connection
+                    .delete_rows_with_retry("tx-by-addr", &address_slot_rows)
+                    .await?;
+            }
+
+            if !tx_deletion_rows.is_empty() {
+                self.connection
+                    .delete_rows_with_retry("tx", &tx_deletion_rows)
+                    .await?;
+            }
+
+            self.connection
+                .delete_rows_with_retry("blocks", &[slot_to_blocks_key(slot)])
+                .await?;
+        }
+
+        info!(
+            "{}deleted ledger data for slot {}: {} transaction rows, {} address slot rows",
+            if dry_run { "[dry run] " } else { "" },
+            slot,
+            tx_deletion_rows.len(),
+            address_slot_rows.len()
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LedgerStorageAdapter for LedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        debug!("LedgerStorage::get_first_available_block request received");
+        inc_new_counter_debug!("storage-bigtable-query", 1);
+        let mut bigtable = self.connection.client();
+        let blocks = bigtable.get_row_keys("blocks", None, None, 1).await?;
+        if blocks.is_empty() {
+            return Ok(None);
+        }
+        Ok(key_to_slot(&blocks[0]))
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        debug!(
+            "LedgerStorage::get_confirmed_blocks request received: {:?} {:?}",
+            start_slot, limit
+        );
+        inc_new_counter_debug!("storage-bigtable-query", 1);
+        let mut bigtable = self.connection.client();
+        let blocks = bigtable
+            .get_row_keys(
+                "blocks",
+                Some(slot_to_blocks_key(start_slot)),
+                None,
+                limit as i64,
+            )
+            .await?;
+        Ok(blocks.into_iter().filter_map(|s| key_to_slot(&s)).collect())
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlockWithOptionalMetadata> {
         debug!(
             "LedgerStorage::get_confirmed_block request received: {:?}",
             slot
@@ -446,15 +808,10 @@ impl LedgerStorage {
                 bigtable::Error::RowNotFound => Error::BlockNotFound(slot),
                 _ => err.into(),
             })?;
-        Ok(match block_cell_data {
-            bigtable::CellData::Bincode(block) => block.into(),
-            bigtable::CellData::Protobuf(block) => block.try_into().map_err(|_err| {
-                bigtable::Error::ObjectCorrupt(format!("blocks/{}", slot_to_blocks_key(slot)))
-            })?,
-        })
+        confirmed_block_from_cell_data(slot, block_cell_data)
     }
 
-    pub async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
         debug!(
             "LedgerStorage::get_signature_status request received: {:?}",
             signature
@@ -471,8 +828,7 @@ impl LedgerStorage {
         Ok(transaction_info.into())
     }
 
-    /// Fetch a confirmed transaction
-    pub async fn get_confirmed_transaction(
+    async fn get_confirmed_transaction(
         &self,
         signature: &Signature,
     ) -> Result<Option<ConfirmedTransactionWithOptionalMetadata>> {
@@ -494,37 +850,12 @@ impl LedgerStorage {
 
         // Load the block and return the transaction
         let block = self.get_confirmed_block(slot).await?;
-        match block.transactions.into_iter().nth(index as usize) {
-            None => {
-                // report this somewhere actionable?
-                warn!("Transaction info for {} is corrupt", signature);
-                Ok(None)
-            }
-            Some(bucket_block_transaction) => {
-                if bucket_block_transaction.transaction.signatures[0] != *signature {
-                    warn!(
-                        "Transaction info or confirmed block for {} is corrupt",
-                        signature
-                    );
-                    Ok(None)
-                } else {
-                    Ok(Some(ConfirmedTransactionWithOptionalMetadata {
-                        slot,
-                        transaction: bucket_block_transaction,
-                        block_time: block.block_time,
-                    }))
-                }
-            }
-        }
+        Ok(confirmed_transaction_from_block(
+            signature, slot, index, block,
+        ))
     }
 
-    /// Get confirmed signatures for the provided address, in descending ledger order
-    ///
-    /// address: address to search for
-    /// before_signature: start with the first signature older than this one
-    /// until_signature: end with the last signature more recent than this one
-    /// limit: stop after this many signatures; if limit==0, all records in the table will be read
-    pub async fn get_confirmed_signatures_for_address(
+    async fn get_confirmed_signatures_for_address(
         &self,
         address: &Pubkey,
         before_signature: Option<&Signature>,
@@ -568,8 +899,6 @@ impl LedgerStorage {
             }
         };
 
-        let mut infos = vec![];
-
         let starting_slot_tx_len = bigtable
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
@@ -603,122 +932,30 @@ impl LedgerStorage {
             )
             .await?;
 
-        'outer: for (row_key, data) in tx_by_addr_data {
-            let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
-                bigtable::Error::ObjectCorrupt(format!(
-                    "Failed to convert key to slot: tx-by-addr/{}",
-                    row_key
-                ))
-            })?;
-
-            let deserialized_cell_data = bigtable::deserialize_protobuf_or_bincode_cell_data::<
-                Vec<LegacyTransactionByAddrInfo>,
-                tx_by_addr::TransactionByAddr,
-            >(&data, "tx-by-addr", row_key.clone())?;
-
-            let mut cell_data: Vec<TransactionByAddrInfo> = match deserialized_cell_data {
-                bigtable::CellData::Bincode(tx_by_addr) => {
-                    tx_by_addr.into_iter().map(|legacy| legacy.into()).collect()
-                }
-                bigtable::CellData::Protobuf(tx_by_addr) => {
-                    tx_by_addr.try_into().map_err(|error| {
-                        bigtable::Error::ObjectCorrupt(format!(
-                            "Failed to deserialize: {}: tx-by-addr/{}",
-                            error,
-                            row_key.clone()
-                        ))
-                    })?
-                }
-            };
-
-            cell_data.reverse();
-            for tx_by_addr_info in cell_data.into_iter() {
-                // Filter out records before `before_transaction_index`
-                if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
-                    continue;
-                }
-                // Filter out records after `until_transaction_index`
-                if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
-                    continue;
-                }
-                infos.push((
-                    ConfirmedTransactionStatusWithSignature {
-                        signature: tx_by_addr_info.signature,
-                        slot,
-                        err: tx_by_addr_info.err,
-                        memo: tx_by_addr_info.memo,
-                        block_time: tx_by_addr_info.block_time,
-                    },
-                    tx_by_addr_info.index,
-                ));
-                // Respect limit
-                if infos.len() >= limit {
-                    break 'outer;
-                }
-            }
-        }
-        Ok(infos)
+        confirmed_signatures_from_tx_by_addr_rows(
+            &address_prefix,
+            tx_by_addr_data,
+            first_slot,
+            before_transaction_index,
+            last_slot,
+            until_transaction_index,
+            limit,
+        )
     }
 
-    // Upload a new confirmed block and associated meta data.
-    pub async fn upload_confirmed_block(
+    async fn upload_confirmed_block(
         &self,
         slot: Slot,
         confirmed_block: ConfirmedBlock,
     ) -> Result<()> {
+        let ConfirmedBlockRows {
+            tx_cells,
+            tx_by_addr_cells,
+            blocks_cells,
+            num_transactions,
+        } = ConfirmedBlockRows::new(slot, confirmed_block);
         let mut bytes_written = 0;
 
-        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
-
-        let mut tx_cells = vec![];
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            let TransactionWithMetadata { meta, transaction } = transaction_with_meta;
-            let err = meta.status.clone().err();
-            let index = index as u32;
-            let signature = transaction.signatures[0];
-            let memo = extract_and_fmt_memos(&transaction.message);
-
-            for address in &transaction.message.account_keys {
-                if !is_sysvar_id(address) {
-                    by_addr
-                        .entry(address)
-                        .or_default()
-                        .push(TransactionByAddrInfo {
-                            signature,
-                            err: err.clone(),
-                            index,
-                            memo: memo.clone(),
-                            block_time: confirmed_block.block_time,
-                        });
-                }
-            }
-
-            tx_cells.push((
-                signature.to_string(),
-                TransactionInfo {
-                    slot,
-                    index,
-                    err,
-                    memo,
-                },
-            ));
-        }
-
-        let tx_by_addr_cells: Vec<_> = by_addr
-            .into_iter()
-            .map(|(address, transaction_info_by_addr)| {
-                (
-                    format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
-                    tx_by_addr::TransactionByAddr {
-                        tx_by_addrs: transaction_info_by_addr
-                            .into_iter()
-                            .map(|by_addr| by_addr.into())
-                            .collect(),
-                    },
-                )
-            })
-            .collect();
-
         if !tx_cells.is_empty() {
             bytes_written += self
                 .connection
@@ -736,12 +973,9 @@ impl LedgerStorage {
                 .await?;
         }
 
-        let num_transactions = confirmed_block.transactions.len();
-
         // Store the block itself last, after all other metadata about the block has been
         // successfully stored.  This avoids partial uploaded blocks from becoming visible to
         // `get_confirmed_block()` and `get_confirmed_blocks()`
-        let blocks_cells = [(slot_to_blocks_key(slot), confirmed_block.into())];
         bytes_written += self
             .connection
             .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
@@ -753,107 +987,6 @@ impl LedgerStorage {
 
         Ok(())
     }
-
-    // Delete a confirmed block and associated meta data.
-    pub async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
-        let mut addresses: HashSet<&Pubkey> = HashSet::new();
-        let mut expected_tx_infos: HashMap<String, UploadedTransaction> = HashMap::new();
-        let confirmed_block = self.get_confirmed_block(slot).await?;
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            let TransactionWithOptionalMetadata { meta, transaction } = transaction_with_meta;
-            let signature = transaction.signatures[0];
-            let index = index as u32;
-            let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
-
-            for address in &transaction.message.account_keys {
-                if !is_sysvar_id(address) {
-                    addresses.insert(address);
-                }
-            }
-
-            expected_tx_infos.insert(
-                signature.to_string(),
-                UploadedTransaction { slot, index, err },
-            );
-        }
-
-        let address_slot_rows: Vec<_> = addresses
-            .into_iter()
-            .map(|address| format!("{}/{}", address, slot_to_tx_by_addr_key(slot)))
-            .collect();
-
-        let tx_deletion_rows = if !expected_tx_infos.is_empty() {
-            let signatures = expected_tx_infos
-                .iter()
-                .map(|(signature, _info)| signature)
-                .cloned()
-                .collect::<Vec<_>>();
-            let fetched_tx_infos: HashMap<String, std::result::Result<UploadedTransaction, _>> =
-                self.connection
-                    .get_bincode_cells_with_retry::<TransactionInfo>("tx", &signatures)
-                    .await?
-                    .into_iter()
-                    .map(|(signature, tx_info_res)| (signature, tx_info_res.map(Into::into)))
-                    .collect::<HashMap<_, _>>();
-
-            let mut deletion_rows = Vec::with_capacity(expected_tx_infos.len());
-            for (signature, expected_tx_info) in expected_tx_infos {
-                match fetched_tx_infos.get(&signature) {
-                    Some(Ok(fetched_tx_info)) if fetched_tx_info == &expected_tx_info => {
-                        deletion_rows.push(signature);
-                    }
-                    Some(Ok(fetched_tx_info)) => {
-                        warn!(
-                            "skipped tx row {} because the bigtable entry ({:?}) did not match to {:?}",
-                            signature,
-                            fetched_tx_info,
-                            &expected_tx_info,
-                        );
-                    }
-                    Some(Err(err)) => {
-                        warn!(
-                            "skipped tx row {} because the bigtable entry was corrupted: {:?}",
-                            signature, err
-                        );
-                    }
-                    None => {
-                        warn!("skipped tx row {} because it was not found", signature);
-                    }
-                }
-            }
-            deletion_rows
-        } else {
-            vec![]
-        };
-
-        if !dry_run {
-            if !address_slot_rows.is_empty() {
-                self.connection
-                    .delete_rows_with_retry("tx-by-addr", &address_slot_rows)
-                    .await?;
-            }
-
-            if !tx_deletion_rows.is_empty() {
-                self.connection
-                    .delete_rows_with_retry("tx", &tx_deletion_rows)
-                    .await?;
-            }
-
-            self.connection
-                .delete_rows_with_retry("blocks", &[slot_to_blocks_key(slot)])
-                .await?;
-        }
-
-        info!(
-            "{}deleted ledger data for slot {}: {} transaction rows, {} address slot rows",
-            if dry_run { "[dry run] " } else { "" },
-            slot,
-            tx_deletion_rows.len(),
-            address_slot_rows.len()
-        );
-
-        Ok(())
-    }
 }
 
 #[cfg(test)]
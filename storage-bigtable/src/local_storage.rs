@@ -0,0 +1,569 @@
+// Long-term ledger storage in a RocksDB database on local disk
+//
+// Every BigTable table is a column family of the database, and rows are stored under the same
+// row keys with the same cells, so the database holds exactly what the BigTable instance would.
+
+use {
+    crate::{
+        bigtable::{self, RowData, RowKey},
+        confirmed_block_from_cell_data, confirmed_signatures_from_tx_by_addr_rows,
+        confirmed_transaction_from_block, key_to_slot, slot_to_blocks_key, slot_to_tx_by_addr_key,
+        ConfirmedBlockRows, Error, LedgerStorageAdapter, Result, StoredConfirmedBlock,
+        TransactionInfo,
+    },
+    async_trait::async_trait,
+    log::*,
+    mundis_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    mundis_storage_proto::convert::{generated, tx_by_addr},
+    mundis_transaction_status::{
+        ConfirmedBlock, ConfirmedBlockWithOptionalMetadata,
+        ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithOptionalMetadata,
+        TransactionStatus,
+    },
+    rocksdb::{ColumnFamily, Options, WriteBatch, DB},
+    std::{io, path::Path, sync::Arc},
+};
+
+const TABLES: &[&str] = &["blocks", "tx", "tx-by-addr"];
+
+#[derive(Clone)]
+pub struct LocalLedgerStorage {
+    db: Arc<DB>,
+    secondary: bool,
+}
+
+impl LocalLedgerStorage {
+    /// Open the database at `path`
+    ///
+    /// Only one process can store blocks in the database: it opens it for writing, which creates
+    /// the database if it does not exist yet. Others open it `read_only`, as a RocksDB secondary
+    /// instance that catches up with the blocks stored by the writer before every read.
+    pub fn open(path: &Path, read_only: bool) -> Result<Self> {
+        let mut options = Options::default();
+        let db = if read_only {
+            let secondary_path = path.join("mundis-secondary");
+            // This is needed according to https://github.com/facebook/rocksdb/wiki/Secondary-instance
+            options.set_max_open_files(-1);
+            DB::open_cf_as_secondary(&options, path, &secondary_path, TABLES)?
+        } else {
+            options.create_if_missing(true);
+            options.create_missing_column_families(true);
+            DB::open_cf(&options, path, TABLES)?
+        };
+        info!(
+            "Opened local ledger storage at {:?}{}",
+            path,
+            if read_only { " as secondary" } else { "" }
+        );
+        Ok(Self {
+            db: Arc::new(db),
+            secondary: read_only,
+        })
+    }
+
+    /// RocksDB calls block, so they run on the blocking thread pool instead of the runtime that
+    /// serves requests. A secondary instance first catches up with the writer.
+    async fn run_blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if storage.secondary {
+                storage.db.try_catch_up_with_primary()?;
+            }
+            f(&storage)
+        })
+        .await
+        .map_err(|err| Error::IoError(io::Error::new(io::ErrorKind::Other, err)))?
+    }
+
+    fn cf(&self, table: &str) -> &ColumnFamily {
+        self.db.cf_handle(table).unwrap()
+    }
+
+    /// Get `table` row keys in lexical order.
+    ///
+    /// If `start_at` is provided, the row key listing will start with key. Otherwise the listing
+    /// will start from the start of the table. If `rows_limit` is zero, the listing will continue
+    /// until the end of the table.
+    fn get_row_keys(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        rows_limit: usize,
+    ) -> Result<Vec<RowKey>> {
+        let mut iterator = self.db.raw_iterator_cf(self.cf(table));
+        match start_at {
+            Some(start_at) => iterator.seek(start_at),
+            None => iterator.seek_to_first(),
+        }
+
+        let mut row_keys = vec![];
+        while iterator.valid() && (rows_limit == 0 || row_keys.len() < rows_limit) {
+            row_keys.push(String::from_utf8_lossy(iterator.key().unwrap()).into_owned());
+            iterator.next();
+        }
+        iterator.status()?;
+        Ok(row_keys)
+    }
+
+    /// Get the rows of `table` from `start_at` to `end_at`, both inclusive, in lexical order.
+    ///
+    /// If `rows_limit` is zero, the listing will continue until `end_at`.
+    fn get_row_data(
+        &self,
+        table: &str,
+        start_at: RowKey,
+        end_at: RowKey,
+        rows_limit: usize,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        let mut iterator = self.db.raw_iterator_cf(self.cf(table));
+        iterator.seek(start_at);
+
+        let mut rows = vec![];
+        while iterator.valid() && (rows_limit == 0 || rows.len() < rows_limit) {
+            let row_key = iterator.key().unwrap();
+            if row_key > end_at.as_bytes() {
+                break;
+            }
+            let row_key = String::from_utf8_lossy(row_key).into_owned();
+            let row_data = deserialize_row_data(table, &row_key, iterator.value().unwrap())?;
+            rows.push((row_key, row_data));
+            iterator.next();
+        }
+        iterator.status()?;
+        Ok(rows)
+    }
+
+    /// Get a single row of `table`, if that row exists.
+    fn get_single_row_data(&self, table: &str, row_key: &str) -> Result<Option<RowData>> {
+        self.db
+            .get_cf(self.cf(table), row_key)?
+            .map(|value| deserialize_row_data(table, row_key, &value))
+            .transpose()
+    }
+
+    fn put_row_data(&self, table: &str, row_data: &[(&RowKey, RowData)]) -> Result<()> {
+        let cf = self.cf(table);
+        let mut batch = WriteBatch::default();
+        for (row_key, row_data) in row_data {
+            batch.put_cf(cf, row_key, bincode::serialize(row_data).unwrap());
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    fn put_bincode_cells<T>(&self, table: &str, cells: &[(RowKey, T)]) -> Result<usize>
+    where
+        T: serde::ser::Serialize,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let data = bigtable::serialize_bincode_cell_data(data)?;
+            bytes_written += data.len();
+            new_row_data.push((row_key, vec![("bin".to_string(), data)]));
+        }
+
+        self.put_row_data(table, &new_row_data)?;
+        Ok(bytes_written)
+    }
+
+    fn put_protobuf_cells<T>(&self, table: &str, cells: &[(RowKey, T)]) -> Result<usize>
+    where
+        T: prost::Message,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let data = bigtable::serialize_protobuf_cell_data(data)?;
+            bytes_written += data.len();
+            new_row_data.push((row_key, vec![("proto".to_string(), data)]));
+        }
+
+        self.put_row_data(table, &new_row_data)?;
+        Ok(bytes_written)
+    }
+
+    fn get_transaction_info(&self, signature: &Signature) -> Result<TransactionInfo> {
+        let row_key = signature.to_string();
+        let row_data = self
+            .get_single_row_data("tx", &row_key)?
+            .ok_or(Error::SignatureNotFound)?;
+        Ok(bigtable::deserialize_bincode_cell_data(
+            &row_data, "tx", row_key,
+        )?)
+    }
+
+    fn confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlockWithOptionalMetadata> {
+        let row_key = slot_to_blocks_key(slot);
+        let row_data = self
+            .get_single_row_data("blocks", &row_key)?
+            .ok_or(Error::BlockNotFound(slot))?;
+        let block_cell_data = bigtable::deserialize_protobuf_or_bincode_cell_data::<
+            StoredConfirmedBlock,
+            generated::ConfirmedBlock,
+        >(&row_data, "blocks", row_key)?;
+        confirmed_block_from_cell_data(slot, block_cell_data)
+    }
+
+    fn confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        let address_prefix = format!("{}/", address);
+
+        // Figure out where to start listing from based on `before_signature`
+        let (first_slot, before_transaction_index) = match before_signature {
+            None => (Slot::MAX, 0),
+            Some(before_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(before_signature)?;
+                (slot, index)
+            }
+        };
+
+        // Figure out where to end listing from based on `until_signature`
+        let (last_slot, until_transaction_index) = match until_signature {
+            None => (0, u32::MAX),
+            Some(until_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(until_signature)?;
+                (slot, index)
+            }
+        };
+
+        // Every tx-by-addr row holds at least one signature, and only the rows of `first_slot`
+        // and `last_slot` can have all of theirs filtered out
+        let tx_by_addr_data = self.get_row_data(
+            "tx-by-addr",
+            format!("{}{}", address_prefix, slot_to_tx_by_addr_key(first_slot)),
+            format!("{}{}", address_prefix, slot_to_tx_by_addr_key(last_slot)),
+            limit.saturating_add(2),
+        )?;
+
+        confirmed_signatures_from_tx_by_addr_rows(
+            &address_prefix,
+            tx_by_addr_data,
+            first_slot,
+            before_transaction_index,
+            last_slot,
+            until_transaction_index,
+            limit,
+        )
+    }
+
+    fn store_confirmed_block(&self, slot: Slot, confirmed_block: ConfirmedBlock) -> Result<()> {
+        let ConfirmedBlockRows {
+            tx_cells,
+            tx_by_addr_cells,
+            blocks_cells,
+            num_transactions,
+        } = ConfirmedBlockRows::new(slot, confirmed_block);
+        let mut bytes_written = 0;
+
+        if !tx_cells.is_empty() {
+            bytes_written += self.put_bincode_cells::<TransactionInfo>("tx", &tx_cells)?;
+        }
+
+        if !tx_by_addr_cells.is_empty() {
+            bytes_written += self.put_protobuf_cells::<tx_by_addr::TransactionByAddr>(
+                "tx-by-addr",
+                &tx_by_addr_cells,
+            )?;
+        }
+
+        // Store the block itself last, so that a partially stored block never becomes visible to
+        // `get_confirmed_block()` and `get_confirmed_blocks()`
+        bytes_written +=
+            self.put_protobuf_cells::<generated::ConfirmedBlock>("blocks", &blocks_cells)?;
+        info!(
+            "stored block for slot {}: {} transactions, {} bytes",
+            slot, num_transactions, bytes_written
+        );
+
+        Ok(())
+    }
+}
+
+fn deserialize_row_data(table: &str, row_key: &str, value: &[u8]) -> Result<RowData> {
+    bincode::deserialize(value).map_err(|err| {
+        warn!("Failed to deserialize {}/{}: {}", table, row_key, err);
+        bigtable::Error::ObjectCorrupt(format!("{}/{}", table, row_key)).into()
+    })
+}
+
+#[async_trait]
+impl LedgerStorageAdapter for LocalLedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        debug!("LocalLedgerStorage::get_first_available_block request received");
+        self.run_blocking(|storage| {
+            let blocks = storage.get_row_keys("blocks", None, 1)?;
+            Ok(blocks.first().and_then(|row_key| key_to_slot(row_key)))
+        })
+        .await
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        debug!(
+            "LocalLedgerStorage::get_confirmed_blocks request received: {:?} {:?}",
+            start_slot, limit
+        );
+        self.run_blocking(move |storage| {
+            let blocks =
+                storage.get_row_keys("blocks", Some(slot_to_blocks_key(start_slot)), limit)?;
+            Ok(blocks.into_iter().filter_map(|s| key_to_slot(&s)).collect())
+        })
+        .await
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlockWithOptionalMetadata> {
+        debug!(
+            "LocalLedgerStorage::get_confirmed_block request received: {:?}",
+            slot
+        );
+        self.run_blocking(move |storage| storage.confirmed_block(slot))
+            .await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        debug!(
+            "LocalLedgerStorage::get_signature_status request received: {:?}",
+            signature
+        );
+        let signature = *signature;
+        self.run_blocking(move |storage| Ok(storage.get_transaction_info(&signature)?.into()))
+            .await
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithOptionalMetadata>> {
+        debug!(
+            "LocalLedgerStorage::get_confirmed_transaction request received: {:?}",
+            signature
+        );
+        let signature = *signature;
+        self.run_blocking(move |storage| {
+            let TransactionInfo { slot, index, .. } = storage.get_transaction_info(&signature)?;
+            let block = storage.confirmed_block(slot)?;
+            Ok(confirmed_transaction_from_block(
+                &signature, slot, index, block,
+            ))
+        })
+        .await
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    > {
+        debug!(
+            "LocalLedgerStorage::get_confirmed_signatures_for_address request received: {:?}",
+            address
+        );
+        let address = *address;
+        let before_signature = before_signature.copied();
+        let until_signature = until_signature.copied();
+        self.run_blocking(move |storage| {
+            storage.confirmed_signatures_for_address(
+                &address,
+                before_signature.as_ref(),
+                until_signature.as_ref(),
+                limit,
+            )
+        })
+        .await
+    }
+
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: ConfirmedBlock,
+    ) -> Result<()> {
+        self.run_blocking(move |storage| storage.store_confirmed_block(slot, confirmed_block))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        mundis_sdk::{
+            hash::Hash,
+            message::v0::LoadedAddresses,
+            signature::{Keypair, Signer},
+            system_transaction,
+        },
+        mundis_transaction_status::{
+            TransactionConfirmationStatus, TransactionStatusMeta, TransactionWithMetadata,
+        },
+    };
+
+    fn transfer_block(slot: Slot, from: &Keypair, recipient: &Pubkey) -> ConfirmedBlock {
+        let transaction = system_transaction::transfer(from, recipient, slot, Hash::new_unique());
+        ConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::default().to_string(),
+            parent_slot: slot.saturating_sub(1),
+            transactions: vec![TransactionWithMetadata {
+                transaction: transaction.into(),
+                meta: TransactionStatusMeta {
+                    status: Ok(()),
+                    fee: 1,
+                    pre_balances: vec![43, 0, 1],
+                    post_balances: vec![0, 42, 1],
+                    inner_instructions: Some(vec![]),
+                    log_messages: Some(vec![]),
+                    pre_token_balances: Some(vec![]),
+                    post_token_balances: Some(vec![]),
+                    rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    compute_units_consumed: Some(1234),
+                },
+            }],
+            rewards: vec![],
+            block_time: Some(1_234_567_890 + slot as i64),
+            block_height: Some(slot),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_ledger_storage() {
+        let ledger_storage_dir = tempfile::TempDir::new().unwrap();
+        let storage = LocalLedgerStorage::open(ledger_storage_dir.path(), false).unwrap();
+        assert_eq!(storage.get_first_available_block().await.unwrap(), None);
+
+        let from = Keypair::new();
+        let recipient = mundis_sdk::pubkey::new_rand();
+        let blocks: Vec<_> = [3, 5, 8]
+            .into_iter()
+            .map(|slot| (slot, transfer_block(slot, &from, &recipient)))
+            .collect();
+        for (slot, block) in &blocks {
+            storage
+                .upload_confirmed_block(*slot, block.clone())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.get_first_available_block().await.unwrap(), Some(3));
+        assert_eq!(
+            storage.get_confirmed_blocks(4, 0).await.unwrap(),
+            vec![5, 8]
+        );
+        assert_eq!(
+            storage.get_confirmed_blocks(0, 2).await.unwrap(),
+            vec![3, 5]
+        );
+
+        let (slot, block) = &blocks[1];
+        assert_eq!(
+            storage.get_confirmed_block(*slot).await.unwrap(),
+            ConfirmedBlockWithOptionalMetadata::from(block.clone())
+        );
+        assert!(matches!(
+            storage.get_confirmed_block(4).await,
+            Err(Error::BlockNotFound(4))
+        ));
+
+        let signature = block.transactions[0].transaction.signatures[0];
+        let status = storage.get_signature_status(&signature).await.unwrap();
+        assert_eq!(status.slot, *slot);
+        assert_eq!(
+            status.confirmation_status,
+            Some(TransactionConfirmationStatus::Finalized)
+        );
+        let transaction = storage
+            .get_confirmed_transaction(&signature)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.slot, *slot);
+        assert_eq!(transaction.block_time, block.block_time);
+        assert!(matches!(
+            storage.get_signature_status(&Signature::new_unique()).await,
+            Err(Error::SignatureNotFound)
+        ));
+
+        // Both accounts of the transfers are indexed, newest first
+        let signatures: Vec<_> = blocks
+            .iter()
+            .rev()
+            .map(|(slot, block)| (*slot, block.transactions[0].transaction.signatures[0]))
+            .collect();
+        for address in [from.pubkey(), recipient] {
+            let results = storage
+                .get_confirmed_signatures_for_address(&address, None, None, 10)
+                .await
+                .unwrap();
+            assert_eq!(
+                results
+                    .iter()
+                    .map(|(status, _index)| (status.slot, status.signature))
+                    .collect::<Vec<_>>(),
+                signatures
+            );
+        }
+
+        // `before` and `until` are exclusive
+        let results = storage
+            .get_confirmed_signatures_for_address(
+                &recipient,
+                Some(&signatures[0].1),
+                Some(&signatures[2].1),
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.signature, signatures[1].1);
+
+        let results = storage
+            .get_confirmed_signatures_for_address(&recipient, None, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].0.signature, signatures[1].1);
+
+        // A read-only instance follows the blocks stored by the writer
+        let secondary = LocalLedgerStorage::open(ledger_storage_dir.path(), true).unwrap();
+        assert_eq!(
+            secondary.get_confirmed_blocks(0, 0).await.unwrap(),
+            vec![3, 5, 8]
+        );
+        storage
+            .upload_confirmed_block(13, transfer_block(13, &from, &recipient))
+            .await
+            .unwrap();
+        assert_eq!(
+            secondary.get_confirmed_blocks(0, 0).await.unwrap(),
+            vec![3, 5, 8, 13]
+        );
+        assert_eq!(
+            secondary
+                .get_confirmed_signatures_for_address(&recipient, None, None, 1)
+                .await
+                .unwrap()[0]
+                .0
+                .slot,
+            13
+        );
+    }
+}
@@ -4,10 +4,13 @@ use {
     mundis_sdk::{
         hash::Hash,
         instruction::{CompiledInstruction, InstructionError},
-        message::{Message, MessageHeader},
+        message::{
+            v0::{self, LoadedAddresses, MessageAddressTableLookup},
+            Message, MessageHeader, VersionedMessage,
+        },
         pubkey::Pubkey,
         signature::Signature,
-        transaction::{Transaction, TransactionError},
+        transaction::{TransactionError, TransactionReturnData, VersionedTransaction},
     },
     mundis_transaction_status::{
         ConfirmedBlock, ConfirmedBlockWithOptionalMetadata, InnerInstructions, Reward, RewardType,
@@ -187,8 +190,8 @@ impl TryFrom<generated::ConfirmedTransaction> for TransactionWithOptionalMetadat
     }
 }
 
-impl From<Transaction> for generated::Transaction {
-    fn from(value: Transaction) -> Self {
+impl From<VersionedTransaction> for generated::Transaction {
+    fn from(value: VersionedTransaction) -> Self {
         Self {
             signatures: value
                 .signatures
@@ -200,7 +203,7 @@ impl From<Transaction> for generated::Transaction {
     }
 }
 
-impl From<generated::Transaction> for Transaction {
+impl From<generated::Transaction> for VersionedTransaction {
     fn from(value: generated::Transaction) -> Self {
         Self {
             signatures: value
@@ -224,21 +227,89 @@ impl From<Message> for generated::Message {
                 .collect(),
             recent_blockhash: value.recent_blockhash.to_bytes().into(),
             instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            versioned: false,
+            address_table_lookups: vec![],
         }
     }
 }
 
-impl From<generated::Message> for Message {
+impl From<VersionedMessage> for generated::Message {
+    fn from(value: VersionedMessage) -> Self {
+        match value {
+            VersionedMessage::Legacy(message) => message.into(),
+            VersionedMessage::V0(message) => Self {
+                header: Some(message.header.into()),
+                account_keys: message
+                    .account_keys
+                    .into_iter()
+                    .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+                    .collect(),
+                recent_blockhash: message.recent_blockhash.to_bytes().into(),
+                instructions: message
+                    .instructions
+                    .into_iter()
+                    .map(|ix| ix.into())
+                    .collect(),
+                versioned: true,
+                address_table_lookups: message
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl From<generated::Message> for VersionedMessage {
     fn from(value: generated::Message) -> Self {
+        let header = value.header.expect("header is required").into();
+        let account_keys = value
+            .account_keys
+            .into_iter()
+            .map(|key| Pubkey::new(&key))
+            .collect();
+        let recent_blockhash = Hash::new(&value.recent_blockhash);
+        let instructions = value.instructions.into_iter().map(|ix| ix.into()).collect();
+        if value.versioned {
+            Self::V0(v0::Message {
+                header,
+                account_keys,
+                recent_blockhash,
+                instructions,
+                address_table_lookups: value
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            })
+        } else {
+            Self::Legacy(Message {
+                header,
+                account_keys,
+                recent_blockhash,
+                instructions,
+            })
+        }
+    }
+}
+
+impl From<MessageAddressTableLookup> for generated::MessageAddressTableLookup {
+    fn from(value: MessageAddressTableLookup) -> Self {
         Self {
-            header: value.header.expect("header is required").into(),
-            account_keys: value
-                .account_keys
-                .into_iter()
-                .map(|key| Pubkey::new(&key))
-                .collect(),
-            recent_blockhash: Hash::new(&value.recent_blockhash),
-            instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            account_key: <Pubkey as AsRef<[u8]>>::as_ref(&value.account_key).into(),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
+        }
+    }
+}
+
+impl From<generated::MessageAddressTableLookup> for MessageAddressTableLookup {
+    fn from(value: generated::MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: Pubkey::new(&value.account_key),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
         }
     }
 }
@@ -275,6 +346,9 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -305,6 +379,18 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .into_iter()
             .map(|reward| reward.into())
             .collect();
+        let loaded_writable_addresses = loaded_addresses
+            .writable
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let loaded_readonly_addresses = loaded_addresses
+            .readonly
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let return_data_none = return_data.is_none();
+        let return_data = return_data.map(|return_data| return_data.into());
 
         Self {
             err,
@@ -318,6 +404,11 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
+            return_data,
+            return_data_none,
+            compute_units_consumed,
         }
     }
 }
@@ -345,6 +436,11 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
+            return_data,
+            return_data_none,
+            compute_units_consumed,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -378,6 +474,21 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .collect(),
         );
         let rewards = Some(rewards.into_iter().map(|reward| reward.into()).collect());
+        let loaded_addresses = LoadedAddresses {
+            writable: loaded_writable_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+            readonly: loaded_readonly_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+        };
+        let return_data = if return_data_none {
+            None
+        } else {
+            return_data.map(|return_data| return_data.into())
+        };
         Ok(Self {
             status,
             fee,
@@ -388,10 +499,31 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         })
     }
 }
 
+impl From<TransactionReturnData> for generated::ReturnData {
+    fn from(value: TransactionReturnData) -> Self {
+        Self {
+            program_id: value.program_id.to_bytes().into(),
+            data: value.data,
+        }
+    }
+}
+
+impl From<generated::ReturnData> for TransactionReturnData {
+    fn from(value: generated::ReturnData) -> Self {
+        Self {
+            program_id: Pubkey::new(&value.program_id),
+            data: value.data,
+        }
+    }
+}
+
 impl From<InnerInstructions> for generated::InnerInstructions {
     fn from(value: InnerInstructions) -> Self {
         Self {
@@ -549,6 +681,14 @@ impl TryFrom<tx_by_addr::TransactionError> for TransactionError {
             }
         }
 
+        if transaction_error.transaction_error == 29 {
+            if let Some(transaction_details) = transaction_error.transaction_details {
+                return Ok(TransactionError::InsufficientFundsForRent {
+                    account_index: transaction_details.index as u8,
+                });
+            }
+        }
+
         Ok(match transaction_error.transaction_error {
             0 => TransactionError::AccountInUse,
             1 => TransactionError::AccountLoadedTwice,
@@ -578,6 +718,8 @@ impl TryFrom<tx_by_addr::TransactionError> for TransactionError {
             26 => TransactionError::InvalidAddressLookupTableIndex,
             27 => TransactionError::InvalidRentPayingAccount,
             28 => TransactionError::WouldExceedMaxVoteCostLimit,
+            30 => TransactionError::MaxLoadedAccountsDataSizeExceeded,
+            31 => TransactionError::InsufficientPriorityFee,
             _ => return Err("Invalid TransactionError"),
         })
     }
@@ -672,6 +814,15 @@ impl From<TransactionError> for tx_by_addr::TransactionError {
                 TransactionError::WouldExceedMaxVoteCostLimit => {
                     tx_by_addr::TransactionErrorType::WouldExceedMaxVoteCostLimit
                 }
+                TransactionError::InsufficientFundsForRent { .. } => {
+                    tx_by_addr::TransactionErrorType::InsufficientFundsForRent
+                }
+                TransactionError::MaxLoadedAccountsDataSizeExceeded => {
+                    tx_by_addr::TransactionErrorType::MaxLoadedAccountsDataSizeExceeded
+                }
+                TransactionError::InsufficientPriorityFee => {
+                    tx_by_addr::TransactionErrorType::InsufficientPriorityFee
+                }
             } as i32,
             instruction_error: match transaction_error {
                 TransactionError::InstructionError(index, ref instruction_error) => {
@@ -843,6 +994,14 @@ impl From<TransactionError> for tx_by_addr::TransactionError {
                 }
                 _ => None,
             },
+            transaction_details: match transaction_error {
+                TransactionError::InsufficientFundsForRent { account_index } => {
+                    Some(tx_by_addr::TransactionDetails {
+                        index: account_index as u32,
+                    })
+                }
+                _ => None,
+            },
         }
     }
 }
@@ -937,6 +1096,87 @@ mod test {
         assert_eq!(reward, gen_reward.into());
     }
 
+    #[test]
+    fn test_transaction_status_meta_return_data_encode() {
+        let mut meta = TransactionStatusMeta {
+            pre_token_balances: Some(vec![]),
+            post_token_balances: Some(vec![]),
+            rewards: Some(vec![]),
+            ..TransactionStatusMeta::default()
+        };
+        let gen_meta: generated::TransactionStatusMeta = meta.clone().into();
+        assert!(gen_meta.return_data_none);
+        assert_eq!(meta, gen_meta.try_into().unwrap());
+
+        meta.return_data = Some(TransactionReturnData {
+            program_id: Pubkey::new_unique(),
+            data: vec![1, 2, 3],
+        });
+        let gen_meta: generated::TransactionStatusMeta = meta.clone().into();
+        assert_eq!(meta, gen_meta.try_into().unwrap());
+    }
+
+    #[test]
+    fn test_transaction_status_meta_loaded_addresses_encode() {
+        let meta = TransactionStatusMeta {
+            pre_token_balances: Some(vec![]),
+            post_token_balances: Some(vec![]),
+            rewards: Some(vec![]),
+            loaded_addresses: LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            },
+            compute_units_consumed: Some(4321),
+            ..TransactionStatusMeta::default()
+        };
+        let gen_meta: generated::TransactionStatusMeta = meta.clone().into();
+        assert_eq!(gen_meta.loaded_writable_addresses.len(), 1);
+        assert_eq!(gen_meta.loaded_readonly_addresses.len(), 2);
+        assert_eq!(meta, gen_meta.try_into().unwrap());
+    }
+
+    #[test]
+    fn test_versioned_transaction_encode() {
+        let header = MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        };
+        let account_keys = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let instructions = vec![CompiledInstruction::new_from_raw_parts(1, vec![7], vec![0, 2])];
+
+        let legacy = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(Message {
+                header: header.clone(),
+                account_keys: account_keys.clone(),
+                recent_blockhash: Hash::new_unique(),
+                instructions: instructions.clone(),
+            }),
+        };
+        let gen_transaction: generated::Transaction = legacy.clone().into();
+        assert!(!gen_transaction.message.as_ref().unwrap().versioned);
+        assert_eq!(legacy, gen_transaction.into());
+
+        let v0 = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header,
+                account_keys,
+                recent_blockhash: Hash::new_unique(),
+                instructions,
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![1, 2],
+                }],
+            }),
+        };
+        let gen_transaction: generated::Transaction = v0.clone().into();
+        assert!(gen_transaction.message.as_ref().unwrap().versioned);
+        assert_eq!(v0, gen_transaction.into());
+    }
+
     #[test]
     fn test_transaction_by_addr_encode() {
         let info = TransactionByAddrInfo {
@@ -1097,6 +1337,22 @@ mod test {
             tx_by_addr_transaction_error.try_into().unwrap()
         );
 
+        let transaction_error = TransactionError::InsufficientFundsForRent { account_index: 10 };
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::MaxLoadedAccountsDataSizeExceeded;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
         let transaction_error = TransactionError::WouldExceedMaxAccountCostLimit;
         let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
             transaction_error.clone().into();
@@ -1105,6 +1361,14 @@ mod test {
             tx_by_addr_transaction_error.try_into().unwrap()
         );
 
+        let transaction_error = TransactionError::InsufficientPriorityFee;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
         let transaction_error = TransactionError::UnsupportedVersion;
         let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
             transaction_error.clone().into();
@@ -1517,10 +1781,22 @@ mod test {
         let ix_index = 1;
         let custom_error = 42;
         for error in tx_by_addr::TransactionErrorType::into_enum_iter() {
-            if error != tx_by_addr::TransactionErrorType::InstructionError {
+            if error == tx_by_addr::TransactionErrorType::InsufficientFundsForRent {
+                let tx_by_addr_error = tx_by_addr::TransactionError {
+                    transaction_error: error as i32,
+                    instruction_error: None,
+                    transaction_details: Some(tx_by_addr::TransactionDetails { index: ix_index }),
+                };
+                let transaction_error: TransactionError = tx_by_addr_error
+                    .clone()
+                    .try_into()
+                    .unwrap_or_else(|_| panic!("{:?} conversion implemented?", error));
+                assert_eq!(tx_by_addr_error, transaction_error.into());
+            } else if error != tx_by_addr::TransactionErrorType::InstructionError {
                 let tx_by_addr_error = tx_by_addr::TransactionError {
                     transaction_error: error as i32,
                     instruction_error: None,
+                    transaction_details: None,
                 };
                 let transaction_error: TransactionError = tx_by_addr_error
                     .clone()
@@ -1537,6 +1813,7 @@ mod test {
                                 error: ix_error as i32,
                                 custom: None,
                             }),
+                            transaction_details: None,
                         };
                         let transaction_error: TransactionError = tx_by_addr_error
                             .clone()
@@ -1553,6 +1830,7 @@ mod test {
                                     custom: custom_error,
                                 }),
                             }),
+                            transaction_details: None,
                         };
                         let transaction_error: TransactionError =
                             tx_by_addr_error.clone().try_into().unwrap();
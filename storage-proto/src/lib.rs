@@ -4,7 +4,11 @@ use {
         parse_token::{real_number_string_trimmed, UiTokenAmount},
         StringAmount,
     },
-    mundis_sdk::{deserialize_utils::default_on_eof, transaction::Result},
+    mundis_sdk::{
+        deserialize_utils::default_on_eof,
+        message::v0::LoadedAddresses,
+        transaction::{Result, TransactionReturnData},
+    },
     mundis_transaction_status::{
         InnerInstructions, Reward, RewardType, TransactionStatusMeta, TransactionTokenBalance,
     },
@@ -165,6 +169,12 @@ pub struct StoredTransactionStatusMeta {
     pub post_token_balances: Option<Vec<StoredTransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Vec<StoredExtendedReward>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: LoadedAddresses,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_units_consumed: Option<u64>,
 }
 
 impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
@@ -179,6 +189,9 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         } = value;
         Self {
             status,
@@ -193,6 +206,9 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         }
     }
 }
@@ -209,6 +225,9 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         } = value;
         Self {
             status,
@@ -223,6 +242,9 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            loaded_addresses,
+            compute_units_consumed,
         }
     }
 }
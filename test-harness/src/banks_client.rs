@@ -134,6 +134,18 @@ impl BanksClient {
             .map_err(Into::into)
     }
 
+    pub fn simulate_transaction_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithSimulation, BanksClientError>> + '_
+    {
+        self.inner
+            .simulate_transaction_with_commitment_and_context(ctx, transaction, commitment)
+            .map_err(Into::into)
+    }
+
     pub fn get_account_with_commitment_and_context(
         &mut self,
         ctx: Context,
@@ -257,6 +269,30 @@ impl BanksClient {
         self.process_transaction_with_commitment(transaction, CommitmentLevel::default())
     }
 
+    /// Simulate a transaction at the given commitment level without committing it,
+    /// returning its result along with its logs, compute units and return data
+    pub fn simulate_transaction_with_commitment(
+        &mut self,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithSimulation, BanksClientError>> + '_
+    {
+        self.simulate_transaction_with_commitment_and_context(
+            context::current(),
+            transaction,
+            commitment,
+        )
+    }
+
+    /// Simulate a transaction at the default commitment level
+    pub fn simulate_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithSimulation, BanksClientError>> + '_
+    {
+        self.simulate_transaction_with_commitment(transaction, CommitmentLevel::default())
+    }
+
     pub async fn process_transactions_with_commitment(
         &mut self,
         transactions: Vec<Transaction>,
@@ -252,15 +252,18 @@ impl Banks for BanksServer {
             logs,
             post_simulation_accounts: _,
             units_consumed,
+            return_data,
+            ..
         } = self
             .bank(commitment)
-            .simulate_transaction_unchecked(sanitized_transaction)
+            .simulate_transaction_unchecked(sanitized_transaction, false)
         {
             return BanksTransactionResultWithSimulation {
                 result: Some(Err(err)),
                 simulation_details: Some(TransactionSimulationDetails {
                     logs,
                     units_consumed,
+                    return_data,
                 }),
             };
         }
@@ -272,6 +275,42 @@ impl Banks for BanksServer {
         }
     }
 
+    async fn simulate_transaction_with_commitment_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation {
+        let sanitized_transaction =
+            match SanitizedTransaction::try_from_legacy_transaction(transaction) {
+                Err(err) => {
+                    return BanksTransactionResultWithSimulation {
+                        result: Some(Err(err)),
+                        simulation_details: None,
+                    };
+                }
+                Ok(tx) => tx,
+            };
+        let TransactionSimulationResult {
+            result,
+            logs,
+            post_simulation_accounts: _,
+            units_consumed,
+            return_data,
+            ..
+        } = self
+            .bank(commitment)
+            .simulate_transaction_unchecked(sanitized_transaction, false);
+        BanksTransactionResultWithSimulation {
+            result: Some(result),
+            simulation_details: Some(TransactionSimulationDetails {
+                logs,
+                units_consumed,
+                return_data,
+            }),
+        }
+    }
+
     async fn process_transaction_with_commitment_and_context(
         self,
         _: Context,
@@ -9,7 +9,7 @@ use mundis_sdk::hash::Hash;
 use mundis_sdk::message::Message;
 use mundis_sdk::signature::Signature;
 use mundis_sdk::transaction;
-use mundis_sdk::transaction::{Transaction, TransactionError};
+use mundis_sdk::transaction::{Transaction, TransactionError, TransactionReturnData};
 use serde::{Deserialize, Serialize};
 
 pub mod banks_server;
@@ -39,6 +39,7 @@ pub struct TransactionStatus {
 pub struct TransactionSimulationDetails {
     pub logs: Vec<String>,
     pub units_consumed: u64,
+    pub return_data: Option<TransactionReturnData>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -65,6 +66,10 @@ pub trait Banks {
         transaction: Transaction,
         commitment: CommitmentLevel,
     ) -> BanksTransactionResultWithSimulation;
+    async fn simulate_transaction_with_commitment_and_context(
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation;
     async fn process_transaction_with_commitment_and_context(
         transaction: Transaction,
         commitment: CommitmentLevel,
@@ -327,6 +327,9 @@ pub struct ProgramTestContext {
     pub banks_client: BanksClient,
     pub last_blockhash: Hash,
     pub payer: Keypair,
+    /// Vote account of the bootstrap validator, which holds all of the stake.
+    /// It is also the account's authorized voter and withdrawer.
+    pub voting_keypair: Keypair,
     genesis_config: GenesisConfig,
     bank_forks: Arc<RwLock<BankForks>>,
     block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
@@ -371,6 +374,7 @@ impl ProgramTestContext {
             banks_client,
             last_blockhash,
             payer: genesis_config_info.mint_keypair,
+            voting_keypair: genesis_config_info.voting_keypair,
             genesis_config: genesis_config_info.genesis_config,
             bank_forks,
             block_commitment_cache,
@@ -0,0 +1,269 @@
+use mundis_escrow_program::error::EscrowError;
+use mundis_escrow_program::escrow_instruction::{cancel, close_expired, create_offer, take};
+use mundis_escrow_program::find_vault_address;
+use mundis_escrow_program::state::{EscrowState, Offer};
+use mundis_program::instruction::{Instruction, InstructionError};
+use mundis_program::program_pack::Pack;
+use mundis_program::pubkey::Pubkey;
+use mundis_program::rent::Rent;
+use mundis_sdk::account::Account;
+use mundis_sdk::signature::Keypair;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::transaction::{Transaction, TransactionError};
+use mundis_test_harness::banks_client::BanksClient;
+use mundis_test_harness::program_test::ProgramTest;
+use mundis_token_account_program::get_associated_token_address;
+use mundis_token_account_program::token_account_instruction::create_associated_token_account;
+use mundis_token_program::state::{Mint, TokenAccount};
+use mundis_token_program::token_instruction::{initialize_mint, mint_to};
+
+struct Market {
+    banks_client: BanksClient,
+    payer: Keypair,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+impl Market {
+    /// Sets up two mints, the maker holding 100 A and the taker holding 100 B
+    async fn new() -> Self {
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let mut pc = ProgramTest::new();
+        let rent = Rent::default();
+        for mint in [mint_a, mint_b] {
+            pc.add_account(
+                mint,
+                Account::new(
+                    rent.minimum_balance(Mint::get_packed_len()),
+                    Mint::get_packed_len(),
+                    &mundis_token_program::id(),
+                ),
+            );
+        }
+        let (banks_client, payer, _) = pc.start().await;
+        let mut market = Market {
+            banks_client,
+            payer,
+            maker: Keypair::new(),
+            taker: Keypair::new(),
+            mint_a,
+            mint_b,
+        };
+
+        let payer = market.payer.pubkey();
+        let mut instructions = vec![];
+        for (mint, name) in [(mint_a, "A"), (mint_b, "B")] {
+            instructions.push(
+                initialize_mint(
+                    &mundis_token_program::id(),
+                    &mint,
+                    &payer,
+                    None,
+                    &format!("Token {}", name),
+                    &name.to_string(),
+                    0,
+                )
+                .unwrap(),
+            );
+            for owner in [market.maker.pubkey(), market.taker.pubkey()] {
+                instructions.push(create_associated_token_account(&payer, &owner, &mint));
+            }
+        }
+        instructions.push(
+            mint_to(
+                &mundis_token_program::id(),
+                &mint_a,
+                &market.account(&market.maker, &mint_a),
+                &payer,
+                &[],
+                100,
+            )
+            .unwrap(),
+        );
+        instructions.push(
+            mint_to(
+                &mundis_token_program::id(),
+                &mint_b,
+                &market.account(&market.taker, &mint_b),
+                &payer,
+                &[],
+                100,
+            )
+            .unwrap(),
+        );
+        market.process(&instructions, &[]).await.unwrap();
+        market
+    }
+
+    fn account(&self, owner: &Keypair, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address(&owner.pubkey(), mint)
+    }
+
+    async fn balance(&mut self, owner: &Keypair, mint: &Pubkey) -> u64 {
+        let account = self.account(owner, mint);
+        self.banks_client
+            .get_packed_account_data::<TokenAccount>(account)
+            .await
+            .unwrap()
+            .amount
+    }
+
+    async fn get_offer(&mut self, offer: &Pubkey) -> Option<Offer> {
+        let account = self.banks_client.get_account(*offer).await.unwrap()?;
+        EscrowState::deserialize(&account.data).unwrap().offer()
+    }
+
+    async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let recent_blockhash = self.banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction =
+            Transaction::new_with_payer(instructions, Some(&self.payer.pubkey()));
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        transaction.sign(&all_signers, recent_blockhash);
+        self.banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    async fn create_offer(&mut self, expiry_slot: Option<u64>) -> Result<Pubkey, TransactionError> {
+        let offer = Keypair::new();
+        let instruction = create_offer(
+            &self.payer.pubkey(),
+            &offer.pubkey(),
+            &self.maker.pubkey(),
+            &self.account(&self.maker, &self.mint_a),
+            &self.mint_a,
+            &self.account(&self.maker, &self.mint_b),
+            100,
+            50,
+            expiry_slot,
+        );
+        let maker = Keypair::from_bytes(&self.maker.to_bytes()).unwrap();
+        self.process(&[instruction], &[&offer, &maker]).await?;
+        Ok(offer.pubkey())
+    }
+
+    fn take(&self, offer: &Pubkey, amount: u64) -> Instruction {
+        take(
+            offer,
+            &self.taker.pubkey(),
+            &self.account(&self.taker, &self.mint_b),
+            &self.account(&self.taker, &self.mint_a),
+            &self.account(&self.maker, &self.mint_b),
+            &self.maker.pubkey(),
+            amount,
+        )
+    }
+}
+
+#[tokio::test]
+async fn test_partial_and_full_fill() {
+    let mut market = Market::new().await;
+    let (mint_a, mint_b) = (market.mint_a, market.mint_b);
+    let maker = Keypair::from_bytes(&market.maker.to_bytes()).unwrap();
+    let taker = Keypair::from_bytes(&market.taker.to_bytes()).unwrap();
+
+    let offer = market.create_offer(None).await.unwrap();
+    let vault = find_vault_address(&offer).0;
+    assert_eq!(market.balance(&maker, &mint_a).await, 0);
+    let vault_account = market
+        .banks_client
+        .get_packed_account_data::<TokenAccount>(vault)
+        .await
+        .unwrap();
+    assert_eq!(vault_account.amount, 100);
+    assert_eq!(vault_account.mint, mint_a);
+
+    // Buying 10 of 100 A at 50 B costs 5 B
+    let instruction = market.take(&offer, 10);
+    market.process(&[instruction], &[&taker]).await.unwrap();
+    assert_eq!(market.balance(&taker, &mint_a).await, 10);
+    assert_eq!(market.balance(&taker, &mint_b).await, 95);
+    assert_eq!(market.balance(&maker, &mint_b).await, 5);
+    let state = market.get_offer(&offer).await.unwrap();
+    assert_eq!(state.remaining_amount, 90);
+    assert_eq!(state.want_mint, mint_b);
+
+    // Cannot take more than is left
+    let instruction = market.take(&offer, 91);
+    assert_eq!(
+        market.process(&[instruction], &[&taker]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::InsufficientOffer as u32)
+        )
+    );
+
+    // Filling the rest closes the offer and its vault
+    let maker_lamports = market
+        .banks_client
+        .get_balance(maker.pubkey())
+        .await
+        .unwrap();
+    let instruction = market.take(&offer, 90);
+    market.process(&[instruction], &[&taker]).await.unwrap();
+    assert_eq!(market.balance(&taker, &mint_a).await, 100);
+    assert_eq!(market.balance(&taker, &mint_b).await, 50);
+    assert_eq!(market.balance(&maker, &mint_b).await, 50);
+    assert!(market.banks_client.get_account(offer).await.unwrap().is_none());
+    assert!(market.banks_client.get_account(vault).await.unwrap().is_none());
+    assert!(
+        market
+            .banks_client
+            .get_balance(maker.pubkey())
+            .await
+            .unwrap()
+            > maker_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_cancel() {
+    let mut market = Market::new().await;
+    let mint_a = market.mint_a;
+    let maker = Keypair::from_bytes(&market.maker.to_bytes()).unwrap();
+    let taker = Keypair::from_bytes(&market.taker.to_bytes()).unwrap();
+    let refund_account = market.account(&maker, &mint_a);
+
+    // The expiry must lie in the future
+    assert_eq!(
+        market.create_offer(Some(0)).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::InvalidExpiry as u32)
+        )
+    );
+
+    let offer = market.create_offer(Some(1_000_000)).await.unwrap();
+    let instruction = market.take(&offer, 30);
+    market.process(&[instruction], &[&taker]).await.unwrap();
+
+    // Only the maker can cancel before the expiry
+    let instruction = close_expired(&offer, &maker.pubkey(), &refund_account);
+    assert_eq!(
+        market.process(&[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::NotExpired as u32)
+        )
+    );
+
+    let instruction = cancel(&offer, &maker.pubkey(), &refund_account);
+    market.process(&[instruction], &[&maker]).await.unwrap();
+    assert_eq!(market.balance(&maker, &mint_a).await, 70);
+    assert!(market.get_offer(&offer).await.is_none());
+    assert!(market
+        .banks_client
+        .get_account(find_vault_address(&offer).0)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,268 @@
+use mundis_governance_program::error::GovernanceError;
+use mundis_governance_program::find_authority_address;
+use mundis_governance_program::governance_instruction::{
+    cast_vote, create_proposal, escrow_feature, execute_proposal, finalize_proposal,
+};
+use mundis_governance_program::state::{
+    GovernanceState, Proposal, ProposalAction, ProposalStatus, VoteChoice,
+};
+use mundis_program::instruction::InstructionError;
+use mundis_program::pubkey::Pubkey;
+use mundis_sdk::feature::{self, Feature};
+use mundis_sdk::signature::Keypair;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::system_instruction;
+use mundis_sdk::transaction::{Transaction, TransactionError};
+use mundis_test_harness::banks_client::BanksClient;
+use mundis_test_harness::program_test::ProgramTest;
+
+async fn get_proposal(banks_client: &mut BanksClient, proposal: &Pubkey) -> Proposal {
+    let account = banks_client.get_account(*proposal).await.unwrap().unwrap();
+    GovernanceState::deserialize(&account.data)
+        .unwrap()
+        .proposal()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_passed_proposal_executes_actions() {
+    let mut context = ProgramTest::new().start_with_context().await;
+    let payer = &context.payer;
+    let voter = &context.voting_keypair;
+    let recent_blockhash = context.last_blockhash;
+    let banks_client = &mut context.banks_client;
+    let rent = banks_client.get_rent().await.unwrap();
+    let authority = find_authority_address().0;
+    let feature_keypair = Keypair::new();
+    let proposal_keypair = Keypair::new();
+    let proposal = proposal_keypair.pubkey();
+    let recipient = Pubkey::new_unique();
+
+    // The feature key holder hands the feature over, the authority is funded
+    let mut instructions = escrow_feature(
+        &payer.pubkey(),
+        &feature_keypair.pubkey(),
+        rent.minimum_balance(Feature::size_of()),
+    );
+    instructions.push(system_instruction::transfer(&payer.pubkey(), &authority, 2_000_000));
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    transaction.sign(&[payer, &feature_keypair], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let actions = vec![
+        ProposalAction::ActivateFeature(feature_keypair.pubkey()),
+        ProposalAction::Invoke(system_instruction::transfer(&authority, &recipient, 1_000_000)),
+    ];
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_proposal(
+                &payer.pubkey(),
+                &proposal,
+                &voter.pubkey(),
+                &voter.pubkey(),
+                "Activate a feature and pay a grant",
+                actions.clone(),
+                2,
+            ),
+            cast_vote(&proposal, &voter.pubkey(), &voter.pubkey(), &payer.pubkey(), VoteChoice::Yes),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, &proposal_keypair, voter], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The bootstrap validator holds all of the stake, its vote passes the proposal
+    let state = get_proposal(banks_client, &proposal).await;
+    assert_eq!(state.status, ProposalStatus::Succeeded);
+    assert_eq!(state.proposer, voter.pubkey());
+    assert!(state.yes_stake > 0);
+
+    // A vote account votes once
+    let mut transaction = Transaction::new_with_payer(
+        &[cast_vote(&proposal, &voter.pubkey(), &voter.pubkey(), &payer.pubkey(), VoteChoice::No)],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, voter], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(GovernanceError::AlreadyVoted as u32)
+        )
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[execute_proposal(&proposal, &actions)],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(
+        get_proposal(banks_client, &proposal).await.status,
+        ProposalStatus::Executed
+    );
+    assert_eq!(banks_client.get_balance(recipient).await.unwrap(), 1_000_000);
+    let feature_account = banks_client
+        .get_account(feature_keypair.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(feature_account.owner, feature::id());
+    assert_eq!(
+        feature::from_account(&feature_account),
+        Some(Feature { activated_at: None })
+    );
+
+    // Actions run once
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &authority, 1),
+            execute_proposal(&proposal, &actions),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(GovernanceError::NotSucceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_proposal() {
+    let mut context = ProgramTest::new().start_with_context().await;
+    let payer = &context.payer;
+    let voter = &context.voting_keypair;
+    let recent_blockhash = context.last_blockhash;
+    let banks_client = &mut context.banks_client;
+    let proposal_keypair = Keypair::new();
+    let proposal = proposal_keypair.pubkey();
+    let actions = vec![ProposalAction::Invoke(system_instruction::transfer(
+        &find_authority_address().0,
+        &payer.pubkey(),
+        1,
+    ))];
+
+    // Only the vote account's withdrawer can propose
+    let impostor = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[create_proposal(
+            &payer.pubkey(),
+            &proposal,
+            &voter.pubkey(),
+            &impostor.pubkey(),
+            "Drain the treasury",
+            actions.clone(),
+            2,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, &proposal_keypair, &impostor], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(GovernanceError::WrongWithdrawer as u32)
+        )
+    );
+
+    // Signatures other than the authority's cannot be borrowed
+    let mut transaction = Transaction::new_with_payer(
+        &[create_proposal(
+            &payer.pubkey(),
+            &proposal,
+            &voter.pubkey(),
+            &voter.pubkey(),
+            "Drain the payer",
+            vec![ProposalAction::Invoke(system_instruction::transfer(
+                &payer.pubkey(),
+                &voter.pubkey(),
+                1,
+            ))],
+            2,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, &proposal_keypair, voter], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(GovernanceError::InvalidAction as u32)
+        )
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_proposal(
+                &payer.pubkey(),
+                &proposal,
+                &voter.pubkey(),
+                &voter.pubkey(),
+                "Pay the payer",
+                actions.clone(),
+                2,
+            ),
+            cast_vote(&proposal, &voter.pubkey(), &voter.pubkey(), &payer.pubkey(), VoteChoice::No),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, &proposal_keypair, voter], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let state = get_proposal(banks_client, &proposal).await;
+    assert_eq!(state.status, ProposalStatus::Defeated);
+    assert_eq!(state.yes_stake, 0);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[execute_proposal(&proposal, &actions)],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(GovernanceError::NotSucceeded as u32)
+        )
+    );
+
+    let mut transaction =
+        Transaction::new_with_payer(&[finalize_proposal(&proposal)], Some(&payer.pubkey()));
+    transaction.sign(&[payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(GovernanceError::NotVoting as u32)
+        )
+    );
+}
@@ -0,0 +1,179 @@
+use mundis_memo_program::memo_instruction::build_memo;
+use mundis_program::instruction::{Instruction, InstructionError};
+use mundis_program::program_pack::Pack;
+use mundis_program::pubkey::Pubkey;
+use mundis_program::rent::Rent;
+use mundis_sdk::account::Account;
+use mundis_sdk::signature::Keypair;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::transaction::{Transaction, TransactionError};
+use mundis_test_harness::banks_client::BanksClient;
+use mundis_test_harness::program_test::ProgramTest;
+use mundis_token_account_program::get_associated_token_address;
+use mundis_token_account_program::token_account_instruction::create_associated_token_account;
+use mundis_token_program::error::TokenError;
+use mundis_token_program::state::{MemoTransfer, Mint, TokenAccount};
+use mundis_token_program::token_instruction::{initialize_mint, mint_to, set_memo_transfer, transfer};
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_required_memo_transfers() {
+    let mint = Pubkey::new_unique();
+    let mut pc = ProgramTest::new();
+    let rent = Rent::default();
+    pc.add_account(
+        mint,
+        Account::new(
+            rent.minimum_balance(Mint::get_packed_len()),
+            Mint::get_packed_len(),
+            &mundis_token_program::id(),
+        ),
+    );
+    let (mut banks_client, payer, _) = pc.start().await;
+    let exchange = Keypair::new();
+    let source = get_associated_token_address(&payer.pubkey(), &mint);
+    let deposit = get_associated_token_address(&exchange.pubkey(), &mint);
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            initialize_mint(
+                &mundis_token_program::id(),
+                &mint,
+                &payer.pubkey(),
+                None,
+                &"Test Token".to_string(),
+                &"TST".to_string(),
+                0,
+            )
+            .unwrap(),
+            create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint),
+            create_associated_token_account(&payer.pubkey(), &exchange.pubkey(), &mint),
+            mint_to(&mundis_token_program::id(), &mint, &source, &payer.pubkey(), &[], 100)
+                .unwrap(),
+            set_memo_transfer(
+                &mundis_token_program::id(),
+                &deposit,
+                &exchange.pubkey(),
+                &[],
+                MemoTransfer::RequiredUtf8,
+            )
+            .unwrap(),
+        ],
+        &[&exchange],
+    )
+    .await
+    .unwrap();
+    let deposit_account = banks_client
+        .get_packed_account_data::<TokenAccount>(deposit)
+        .await
+        .unwrap();
+    assert_eq!(deposit_account.memo_transfer, MemoTransfer::RequiredUtf8);
+
+    let transfer_ix = |amount| {
+        transfer(
+            &mundis_token_program::id(),
+            &source,
+            &deposit,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()
+    };
+
+    // A transfer without a memo is rejected
+    assert_eq!(
+        process(&mut banks_client, &payer, &[transfer_ix(1)], &[]).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenError::NoMemo as u32)
+        ))
+    );
+
+    // The memo must come right before the transfer
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            &[build_memo(b"deposit 42", &[]), transfer_ix(2), transfer_ix(3)],
+            &[],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(TokenError::NoMemo as u32)
+        ))
+    );
+
+    // Binary memos are accepted by the memo program, but not by this account
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            &[build_memo(&[0xF0, 0x9F, 0x90, 0xFF], &[]), transfer_ix(4)],
+            &[],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(TokenError::MemoNotUtf8 as u32)
+        ))
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[build_memo(b"deposit 42", &[]), transfer_ix(5)],
+        &[],
+    )
+    .await
+    .unwrap();
+    let deposit_account = banks_client
+        .get_packed_account_data::<TokenAccount>(deposit)
+        .await
+        .unwrap();
+    assert_eq!(deposit_account.amount, 5);
+
+    // Once disabled, plain transfers go through again
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            set_memo_transfer(
+                &mundis_token_program::id(),
+                &deposit,
+                &exchange.pubkey(),
+                &[],
+                MemoTransfer::Disabled,
+            )
+            .unwrap(),
+            transfer_ix(6),
+        ],
+        &[&exchange],
+    )
+    .await
+    .unwrap();
+    let deposit_account = banks_client
+        .get_packed_account_data::<TokenAccount>(deposit)
+        .await
+        .unwrap();
+    assert_eq!(deposit_account.amount, 11);
+}
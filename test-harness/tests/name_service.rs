@@ -0,0 +1,140 @@
+use mundis_name_service_program::error::NameServiceError;
+use mundis_name_service_program::name_service_instruction::{delete, register, renew, transfer, update};
+use mundis_name_service_program::state::{NameRecord, NameServiceState};
+use mundis_name_service_program::{find_name_record_address, find_treasury_address, registration_price};
+use mundis_program::instruction::InstructionError;
+use mundis_program::pubkey::Pubkey;
+use mundis_sdk::signature::Keypair;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::transaction::{Transaction, TransactionError};
+use mundis_test_harness::banks_client::BanksClient;
+use mundis_test_harness::program_test::ProgramTest;
+
+async fn get_name_record(banks_client: &mut BanksClient, name: &str) -> Option<NameRecord> {
+    banks_client
+        .get_account(find_name_record_address(name).0)
+        .await
+        .unwrap()
+        .and_then(|account| NameServiceState::deserialize(&account.data).ok())
+        .and_then(NameServiceState::name_record)
+}
+
+#[tokio::test]
+async fn test_register_and_manage_names() {
+    let (mut banks_client, payer, recent_blockhash) = ProgramTest::new().start().await;
+    let alice = Keypair::new();
+    let treasury = find_treasury_address().0;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            register(&payer.pubkey(), "world", &payer.pubkey(), None, 10, 0),
+            register(&payer.pubkey(), "alice.world", &alice.pubkey(), Some(&payer.pubkey()), 5, 32),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(treasury).await.unwrap(),
+        registration_price("world", 10).unwrap() + registration_price("alice.world", 5).unwrap()
+    );
+    let world = get_name_record(&mut banks_client, "world").await.unwrap();
+    assert_eq!(world.owner, payer.pubkey());
+    assert_eq!(world.parent, None);
+    let record = get_name_record(&mut banks_client, "alice.world").await.unwrap();
+    assert_eq!(record.owner, alice.pubkey());
+    assert_eq!(record.parent, Some(find_name_record_address("world").0));
+    assert_eq!(record.data, vec![0; 32]);
+    let expiry_epoch = record.expiry_epoch;
+
+    // An active name cannot be registered again
+    let mut transaction = Transaction::new_with_payer(
+        &[register(&payer.pubkey(), "world", &payer.pubkey(), None, 1, 0)],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(NameServiceError::AlreadyRegistered as u32)
+        )
+    );
+
+    // The owner updates the record and transfers the name, anyone may renew it
+    let bob = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            update("alice.world", &alice.pubkey(), 30, vec![1, 2]),
+            renew(&payer.pubkey(), "alice.world", 3),
+            transfer("alice.world", &alice.pubkey(), &bob),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &alice], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let record = get_name_record(&mut banks_client, "alice.world").await.unwrap();
+    assert_eq!(record.owner, bob);
+    assert_eq!(record.expiry_epoch, expiry_epoch + 3);
+    assert_eq!(&record.data[29..], &[0, 1, 2]);
+
+    // Writes past the reserved space are rejected
+    let mut transaction = Transaction::new_with_payer(
+        &[update("world", &payer.pubkey(), 0, vec![1])],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(NameServiceError::DataTooLarge as u32)
+        )
+    );
+
+    let destination = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(
+        &[delete("world", &payer.pubkey(), &destination)],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(get_name_record(&mut banks_client, "world").await.is_none());
+    assert!(banks_client.get_balance(destination).await.unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_register_requires_parent_owner() {
+    let (mut banks_client, payer, recent_blockhash) = ProgramTest::new().start().await;
+    let squatter = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            register(&payer.pubkey(), "world", &payer.pubkey(), None, 10, 0),
+            register(&payer.pubkey(), "alice.world", &squatter.pubkey(), Some(&squatter.pubkey()), 5, 0),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &squatter], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(NameServiceError::InvalidParent as u32)
+        )
+    );
+}
@@ -0,0 +1,223 @@
+use mundis_program::program_pack::Pack;
+use mundis_program::pubkey::Pubkey;
+use mundis_program::rent::Rent;
+use mundis_program::{system_instruction, system_program};
+use mundis_sdk::account::Account;
+use mundis_sdk::signature::Keypair;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::transaction::Transaction;
+use mundis_stake_pool_program::state::{Fee, StakePoolState};
+use mundis_stake_pool_program::stake_pool_instruction::{
+    deposit_mundis, initialize, request_withdrawal, withdraw_instant,
+};
+use mundis_stake_pool_program::{find_reserve_program_address, find_withdraw_authority_program_address};
+use mundis_test_harness::program_test::ProgramTest;
+use mundis_token_account_program::get_associated_token_address;
+use mundis_token_account_program::token_account_instruction::create_associated_token_account;
+use mundis_token_program::state::{Mint, TokenAccount};
+use mundis_token_program::token_instruction::initialize_mint;
+
+const MAX_VALIDATORS: u32 = 4;
+
+pub fn program_test(stake_pool: Pubkey, pool_mint: Pubkey) -> ProgramTest {
+    let mut pc = ProgramTest::new();
+    let rent = Rent::default();
+    let pool_size = StakePoolState::stake_pool_size_of(MAX_VALIDATORS);
+    pc.add_account(stake_pool, Account::new(rent.minimum_balance(pool_size), pool_size, &mundis_stake_pool_program::id()));
+    pc.add_account(pool_mint, Account::new(rent.minimum_balance(Mint::get_packed_len()), Mint::get_packed_len(), &mundis_token_program::id()));
+    pc.add_account(find_reserve_program_address(&stake_pool).0, Account::new(rent.minimum_balance(0), 0, &system_program::id()));
+    pc
+}
+
+#[tokio::test]
+async fn test_deposit_and_withdraw_instant() {
+    let stake_pool = Pubkey::new_unique();
+    let pool_mint = Pubkey::new_unique();
+    let (withdraw_authority, _) = find_withdraw_authority_program_address(&stake_pool);
+    let (reserve, _) = find_reserve_program_address(&stake_pool);
+    let withdrawal_fee = Fee { numerator: 1, denominator: 100 };
+
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(stake_pool, pool_mint).start().await;
+    let manager_fee_account = get_associated_token_address(&payer.pubkey(), &pool_mint);
+    let depositor_pool_account = get_associated_token_address(&payer.pubkey(), &pool_mint);
+    let reserve_balance = banks_client.get_balance(reserve).await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            initialize_mint(
+                &mundis_token_program::id(),
+                &pool_mint,
+                &withdraw_authority,
+                None,
+                &"Pool Token".to_string(),
+                &"POOL".to_string(),
+                9,
+            ).unwrap(),
+            create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &pool_mint),
+            initialize(
+                &stake_pool,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &pool_mint,
+                &manager_fee_account,
+                Fee::default(),
+                withdrawal_fee,
+                MAX_VALIDATORS,
+            ),
+            deposit_mundis(&stake_pool, &payer.pubkey(), &depositor_pool_account, &pool_mint, 1_000_000),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // An empty pool mints pool tokens 1:1
+    let pool_account = banks_client
+        .get_packed_account_data::<TokenAccount>(depositor_pool_account)
+        .await
+        .unwrap();
+    assert_eq!(pool_account.amount, 1_000_000);
+    assert_eq!(
+        banks_client.get_balance(reserve).await.unwrap(),
+        reserve_balance + 1_000_000
+    );
+
+    // A second holder withdraws instantly and pays the withdrawal fee to the manager
+    let holder = Keypair::new();
+    let holder_pool_account = get_associated_token_address(&holder.pubkey(), &pool_mint);
+    let destination = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_associated_token_account(&payer.pubkey(), &holder.pubkey(), &pool_mint),
+            deposit_mundis(&stake_pool, &payer.pubkey(), &holder_pool_account, &pool_mint, 2_000_000),
+            withdraw_instant(
+                &stake_pool,
+                &holder_pool_account,
+                &holder.pubkey(),
+                &destination,
+                &manager_fee_account,
+                &pool_mint,
+                1_000_000,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &holder], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let holder_account = banks_client
+        .get_packed_account_data::<TokenAccount>(holder_pool_account)
+        .await
+        .unwrap();
+    assert_eq!(holder_account.amount, 1_000_000);
+    let manager_account = banks_client
+        .get_packed_account_data::<TokenAccount>(manager_fee_account)
+        .await
+        .unwrap();
+    assert_eq!(manager_account.amount, 1_010_000);
+    assert_eq!(banks_client.get_balance(destination).await.unwrap(), 990_000);
+
+    let state = banks_client.get_account(stake_pool).await.unwrap().unwrap();
+    let pool = StakePoolState::deserialize(&state.data).unwrap().stake_pool().unwrap();
+    assert_eq!(pool.manager, payer.pubkey());
+    assert_eq!(pool.total_lamports, 2_010_000);
+    assert_eq!(pool.pool_token_supply, 2_010_000);
+}
+
+#[tokio::test]
+async fn test_request_withdrawal_pays_withdrawal_fee() {
+    let stake_pool = Pubkey::new_unique();
+    let pool_mint = Pubkey::new_unique();
+    let (withdraw_authority, _) = find_withdraw_authority_program_address(&stake_pool);
+    let withdrawal_fee = Fee { numerator: 1, denominator: 100 };
+
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(stake_pool, pool_mint).start().await;
+    let manager_fee_account = get_associated_token_address(&payer.pubkey(), &pool_mint);
+    let holder = Keypair::new();
+    let holder_pool_account = get_associated_token_address(&holder.pubkey(), &pool_mint);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            initialize_mint(
+                &mundis_token_program::id(),
+                &pool_mint,
+                &withdraw_authority,
+                None,
+                &"Pool Token".to_string(),
+                &"POOL".to_string(),
+                9,
+            ).unwrap(),
+            create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &pool_mint),
+            create_associated_token_account(&payer.pubkey(), &holder.pubkey(), &pool_mint),
+            initialize(
+                &stake_pool,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &pool_mint,
+                &manager_fee_account,
+                Fee::default(),
+                withdrawal_fee,
+                MAX_VALIDATORS,
+            ),
+            deposit_mundis(&stake_pool, &payer.pubkey(), &holder_pool_account, &pool_mint, 2_000_000),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // A delayed withdrawal pays the same withdrawal fee as an instant one
+    let ticket = Keypair::new();
+    let ticket_size = StakePoolState::withdrawal_ticket_size_of();
+    let beneficiary = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &ticket.pubkey(),
+                Rent::default().minimum_balance(ticket_size),
+                ticket_size as u64,
+                &mundis_stake_pool_program::id(),
+            ),
+            request_withdrawal(
+                &stake_pool,
+                &ticket.pubkey(),
+                &holder_pool_account,
+                &holder.pubkey(),
+                &beneficiary,
+                &manager_fee_account,
+                &pool_mint,
+                1_000_000,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &ticket, &holder], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let holder_account = banks_client
+        .get_packed_account_data::<TokenAccount>(holder_pool_account)
+        .await
+        .unwrap();
+    assert_eq!(holder_account.amount, 1_000_000);
+    let manager_account = banks_client
+        .get_packed_account_data::<TokenAccount>(manager_fee_account)
+        .await
+        .unwrap();
+    assert_eq!(manager_account.amount, 10_000);
+
+    let state = banks_client.get_account(ticket.pubkey()).await.unwrap().unwrap();
+    let ticket = StakePoolState::deserialize(&state.data).unwrap().withdrawal_ticket().unwrap();
+    assert_eq!(ticket.beneficiary, beneficiary);
+    assert_eq!(ticket.lamports, 990_000);
+
+    let state = banks_client.get_account(stake_pool).await.unwrap().unwrap();
+    let pool = StakePoolState::deserialize(&state.data).unwrap().stake_pool().unwrap();
+    assert_eq!(pool.pending_withdrawal_lamports, 990_000);
+    assert_eq!(pool.total_lamports, 1_010_000);
+    assert_eq!(pool.pool_token_supply, 1_010_000);
+}
@@ -0,0 +1,418 @@
+use mundis_program::instruction::{AccountMeta, Instruction, InstructionError};
+use mundis_program::pubkey::Pubkey;
+use mundis_program::rent::Rent;
+use mundis_sdk::account::Account;
+use mundis_sdk::signature::Keypair;
+use mundis_sdk::signer::Signer;
+use mundis_sdk::transaction::{Transaction, TransactionError, TransactionReturnData};
+use mundis_test_harness::banks_client::BanksClient;
+use mundis_test_harness::program_test::ProgramTest;
+use mundis_wasm_loader_program::loader_instruction::{
+    close_program, create_buffer, deploy_with_max_program_len, set_upgrade_authority, upgrade,
+    write,
+};
+use mundis_wasm_loader_program::{get_program_data_address, UpgradeableLoaderState};
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+fn leb(mut value: u32) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn sleb(mut value: i32) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn vector(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = leb(items.len() as u32);
+    items.iter().for_each(|item| bytes.extend_from_slice(item));
+    bytes
+}
+
+fn name(name: &str) -> Vec<u8> {
+    let mut bytes = leb(name.len() as u32);
+    bytes.extend_from_slice(name.as_bytes());
+    bytes
+}
+
+fn section(id: u8, contents: Vec<u8>) -> Vec<u8> {
+    let mut bytes = vec![id];
+    bytes.extend(leb(contents.len() as u32));
+    bytes.extend(contents);
+    bytes
+}
+
+/// Assembles a program for an instruction with a single account holding 8
+/// bytes of data. It copies the first byte of the instruction data into the
+/// account, logs `message` and returns the instruction data, unless the
+/// first byte is 0xff, in which case it fails with error code 7.
+fn assemble(message: &str) -> Vec<u8> {
+    const ACCOUNT_DATA: u32 = 96;
+    // Past the data, its room to grow and the rent epoch
+    const INSTRUCTION_DATA_LEN: u32 = ACCOUNT_DATA + 8 + 10240 + 8;
+    const INSTRUCTION_DATA: u32 = INSTRUCTION_DATA_LEN + 8;
+    const MESSAGE: u32 = 16;
+
+    let load_first_byte = [vec![0x20, 0x00, 0x2d, 0x00], leb(INSTRUCTION_DATA)].concat();
+    let code = [
+        vec![0x00], // no locals
+        vec![0x02, 0x40],
+        load_first_byte.clone(),
+        vec![0x41, 0xff, 0x01, 0x47, 0x0d, 0x00], // != 0xff, br_if 0
+        vec![0x42, 0x07, 0x0f],                   // return 7
+        vec![0x0b],
+        vec![0x20, 0x00],
+        load_first_byte,
+        [vec![0x3a, 0x00], leb(ACCOUNT_DATA)].concat(), // i32.store8
+        vec![0x41],
+        sleb(MESSAGE as i32),
+        vec![0x41],
+        sleb(message.len() as i32),
+        vec![0x10, 0x00], // call mundis_log
+        vec![0x20, 0x00, 0x41],
+        sleb(INSTRUCTION_DATA as i32),
+        vec![0x6a, 0x20, 0x00, 0x29, 0x03],
+        leb(INSTRUCTION_DATA_LEN),
+        vec![0xa7, 0x10, 0x01], // call mundis_set_return_data
+        vec![0x42, 0x00, 0x0b], // return 0
+    ]
+    .concat();
+
+    [
+        b"\0asm".to_vec(),
+        vec![1, 0, 0, 0],
+        section(
+            1,
+            vector(&[
+                vec![0x60, 0x02, 0x7f, 0x7f, 0x00],
+                vec![0x60, 0x01, 0x7f, 0x01, 0x7e],
+            ]),
+        ),
+        section(
+            2,
+            vector(&[
+                [name("env"), name("mundis_log"), vec![0x00, 0x00]].concat(),
+                [
+                    name("env"),
+                    name("mundis_set_return_data"),
+                    vec![0x00, 0x00],
+                ]
+                .concat(),
+            ]),
+        ),
+        section(3, vector(&[vec![0x01]])),
+        section(5, vector(&[vec![0x00, 0x01]])),
+        section(
+            7,
+            vector(&[[name("entrypoint"), vec![0x00, 0x02]].concat()]),
+        ),
+        section(10, vector(&[[leb(code.len() as u32), code].concat()])),
+        section(
+            11,
+            vector(&[[
+                vec![0x00, 0x41],
+                sleb(MESSAGE as i32),
+                vec![0x0b],
+                name(message),
+            ]
+            .concat()]),
+        ),
+    ]
+    .concat()
+}
+
+async fn create_filled_buffer(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    bytecode: &[u8],
+) -> Keypair {
+    let buffer = Keypair::new();
+    let rent = Rent::default();
+    let buffer_len = UpgradeableLoaderState::buffer_len(bytecode.len());
+    let mut instructions = create_buffer(
+        &payer.pubkey(),
+        &buffer.pubkey(),
+        &payer.pubkey(),
+        rent.minimum_balance(buffer_len),
+        bytecode.len(),
+    );
+    instructions.push(write(
+        &buffer.pubkey(),
+        &payer.pubkey(),
+        0,
+        bytecode.to_vec(),
+    ));
+    process(banks_client, payer, &instructions, &[&buffer])
+        .await
+        .unwrap();
+    buffer
+}
+
+#[tokio::test]
+async fn test_deploy_upgrade_and_close() {
+    let program = Keypair::new();
+    let account = Pubkey::new_unique();
+    let mut pc = ProgramTest::new();
+    let rent = Rent::default();
+    pc.add_account(
+        account,
+        Account::new(rent.minimum_balance(8), 8, &program.pubkey()),
+    );
+    let (mut banks_client, payer, _) = pc.start().await;
+
+    let bytecode = assemble("hello");
+    let buffer = create_filled_buffer(&mut banks_client, &payer, &bytecode).await;
+    process(
+        &mut banks_client,
+        &payer,
+        &deploy_with_max_program_len(
+            &payer.pubkey(),
+            &program.pubkey(),
+            &buffer.pubkey(),
+            &payer.pubkey(),
+            rent.minimum_balance(UpgradeableLoaderState::program_len()),
+            bytecode.len() * 2,
+        )
+        .unwrap(),
+        &[&program],
+    )
+    .await
+    .unwrap();
+    assert!(banks_client
+        .get_account(buffer.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+    let program_account = banks_client
+        .get_account(program.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(program_account.executable);
+
+    let invoke = |byte: u8| {
+        Instruction::new_with_bytes(
+            program.pubkey(),
+            &[byte, 2, 3],
+            vec![AccountMeta::new(account, false)],
+        )
+    };
+    process(&mut banks_client, &payer, &[invoke(42)], &[])
+        .await
+        .unwrap();
+    let data = banks_client
+        .get_account(account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(data, vec![42, 0, 0, 0, 0, 0, 0, 0]);
+
+    // Simulation reports the return data without committing anything
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[invoke(9)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(simulation.result, Some(Ok(())));
+    assert_eq!(
+        simulation.simulation_details.unwrap().return_data,
+        Some(TransactionReturnData {
+            program_id: program.pubkey(),
+            data: vec![9, 2, 3],
+        })
+    );
+    let data = banks_client
+        .get_account(account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(data[0], 42);
+
+    assert_eq!(
+        process(&mut banks_client, &payer, &[invoke(0xff)], &[]).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(7)
+        ))
+    );
+
+    // Upgrades keep the program's address
+    let bytecode = assemble("upgraded");
+    let buffer = create_filled_buffer(&mut banks_client, &payer, &bytecode).await;
+    process(
+        &mut banks_client,
+        &payer,
+        &[upgrade(
+            &program.pubkey(),
+            &buffer.pubkey(),
+            &payer.pubkey(),
+            &payer.pubkey(),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    process(&mut banks_client, &payer, &[invoke(7)], &[])
+        .await
+        .unwrap();
+    let data = banks_client
+        .get_account(account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(data[0], 7);
+
+    // Only the upgrade authority may close the program
+    let stranger = Keypair::new();
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            &[close_program(
+                &program.pubkey(),
+                &payer.pubkey(),
+                &stranger.pubkey()
+            )],
+            &[&stranger],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::IncorrectAuthority
+        ))
+    );
+    process(
+        &mut banks_client,
+        &payer,
+        &[close_program(
+            &program.pubkey(),
+            &payer.pubkey(),
+            &payer.pubkey(),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert!(banks_client
+        .get_account(get_program_data_address(&program.pubkey()))
+        .await
+        .unwrap()
+        .is_none());
+    assert!(process(&mut banks_client, &payer, &[invoke(1)], &[])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_immutable_program() {
+    let program = Keypair::new();
+    let (mut banks_client, payer, _) = ProgramTest::new().start().await;
+    let rent = Rent::default();
+
+    let bytecode = assemble("hello");
+    let buffer = create_filled_buffer(&mut banks_client, &payer, &bytecode).await;
+    let mut instructions = deploy_with_max_program_len(
+        &payer.pubkey(),
+        &program.pubkey(),
+        &buffer.pubkey(),
+        &payer.pubkey(),
+        rent.minimum_balance(UpgradeableLoaderState::program_len()),
+        bytecode.len(),
+    )
+    .unwrap();
+    instructions.push(set_upgrade_authority(
+        &program.pubkey(),
+        &payer.pubkey(),
+        None,
+    ));
+    process(&mut banks_client, &payer, &instructions, &[&program])
+        .await
+        .unwrap();
+
+    let buffer = create_filled_buffer(&mut banks_client, &payer, &bytecode).await;
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            &[upgrade(
+                &program.pubkey(),
+                &buffer.pubkey(),
+                &payer.pubkey(),
+                &payer.pubkey(),
+            )],
+            &[],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Immutable
+        ))
+    );
+
+    // Invalid bytecode is rejected at deploy time
+    let program = Keypair::new();
+    let buffer = create_filled_buffer(&mut banks_client, &payer, b"\0asm\x02\0\0\0").await;
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            &deploy_with_max_program_len(
+                &payer.pubkey(),
+                &program.pubkey(),
+                &buffer.pubkey(),
+                &payer.pubkey(),
+                rent.minimum_balance(UpgradeableLoaderState::program_len()),
+                64,
+            )
+            .unwrap(),
+            &[&program],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::InvalidAccountData
+        ))
+    );
+}
@@ -2,7 +2,7 @@ use {
     crate::parse_instruction::parse_memo_data,
     mundis_sdk::{
         instruction::CompiledInstruction,
-        message::{Message, SanitizedMessage},
+        message::{Message, SanitizedMessage, VersionedMessage},
         pubkey::Pubkey,
     },
 };
@@ -48,6 +48,18 @@ impl ExtractMemos for SanitizedMessage {
     }
 }
 
+impl ExtractMemos for VersionedMessage {
+    fn extract_memos(&self) -> Vec<String> {
+        // Program ids are always static keys, so loaded addresses are not needed
+        match self {
+            VersionedMessage::Legacy(message) => message.extract_memos(),
+            VersionedMessage::V0(message) => {
+                extract_memos_inner(message.account_keys.iter(), &message.instructions)
+            }
+        }
+    }
+}
+
 enum KeyType<'a> {
     MemoProgram,
     OtherProgram,
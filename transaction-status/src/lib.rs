@@ -7,17 +7,20 @@ extern crate serde_derive;
 pub mod extract_memos;
 pub mod parse_accounts;
 pub mod parse_associated_token;
+pub mod parse_escrow;
 pub mod parse_instruction;
 pub mod parse_stake;
+pub mod parse_stake_pool;
 pub mod parse_system;
 pub mod parse_token;
 pub mod parse_vote;
+pub mod parse_wasm_loader;
 pub mod token_balances;
 
 pub use {crate::extract_memos::extract_and_fmt_memos, mundis_runtime::bank::RewardType};
 use {
     crate::{
-        parse_accounts::{parse_accounts, ParsedAccount},
+        parse_accounts::{parse_accounts, parse_v0_message_accounts, ParsedAccount},
         parse_instruction::{parse, ParsedInstruction},
     },
     mundis_account_decoder::parse_token::UiTokenAmount,
@@ -26,14 +29,27 @@ use {
         commitment_config::CommitmentConfig,
         deserialize_utils::default_on_eof,
         instruction::CompiledInstruction,
-        message::{Message, MessageHeader},
+        message::{
+            v0::{self, LoadedAddresses, LoadedMessage, MessageAddressTableLookup},
+            Message, MessageHeader, VersionedMessage,
+        },
         pubkey::Pubkey,
         sanitize::Sanitize,
         signature::Signature,
-        transaction::{Result, Transaction, TransactionError},
+        transaction::{
+            Result, Transaction, TransactionError, TransactionReturnData, VersionedTransaction,
+        },
     },
     std::fmt,
+    thiserror::Error,
 };
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum EncodeError {
+    #[error("Encoding does not support transaction version {0}")]
+    UnsupportedTransactionVersion(u8),
+}
+
 /// A duplicate representation of an Instruction for pretty JSON serialization
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", untagged)]
@@ -50,13 +66,21 @@ pub enum UiParsedInstruction {
 }
 
 impl UiInstruction {
-    fn parse(instruction: &CompiledInstruction, message: &Message) -> Self {
-        let program_id = instruction.program_id(&message.account_keys);
-        if let Ok(parsed_instruction) = parse(program_id, instruction, &message.account_keys) {
+    fn parse(instruction: &CompiledInstruction, account_keys: &[Pubkey]) -> Self {
+        // Addresses loaded from lookup tables are unknown without metadata
+        let resolvable = std::iter::once(&instruction.program_id_index)
+            .chain(instruction.accounts.iter())
+            .all(|&index| usize::from(index) < account_keys.len());
+        if !resolvable {
+            return UiInstruction::Compiled(instruction.into());
+        }
+
+        let program_id = instruction.program_id(account_keys);
+        if let Ok(parsed_instruction) = parse(program_id, instruction, account_keys) {
             UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed_instruction))
         } else {
             UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
-                UiPartiallyDecodedInstruction::from(instruction, &message.account_keys),
+                UiPartiallyDecodedInstruction::from(instruction, account_keys),
             ))
         }
     }
@@ -155,13 +179,14 @@ impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
 }
 
 impl UiInnerInstructions {
-    fn parse(inner_instructions: InnerInstructions, message: &Message) -> Self {
+    /// Parses each inner instruction, resolving known programs against `account_keys`
+    pub fn parse(inner_instructions: InnerInstructions, account_keys: &[Pubkey]) -> Self {
         Self {
             index: inner_instructions.index,
             instructions: inner_instructions
                 .instructions
                 .iter()
-                .map(|ix| UiInstruction::parse(ix, message))
+                .map(|ix| UiInstruction::parse(ix, account_keys))
                 .collect(),
         }
     }
@@ -197,6 +222,12 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Rewards>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: LoadedAddresses,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_units_consumed: Option<u64>,
 }
 
 impl Default for TransactionStatusMeta {
@@ -211,6 +242,9 @@ impl Default for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            compute_units_consumed: None,
         }
     }
 }
@@ -229,10 +263,68 @@ pub struct UiTransactionStatusMeta {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_addresses: Option<UiLoadedAddresses>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_units_consumed: Option<u64>,
+}
+
+/// A duplicate representation of LoadedAddresses
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl From<&LoadedAddresses> for UiLoadedAddresses {
+    fn from(loaded_addresses: &LoadedAddresses) -> Self {
+        Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// A duplicate representation of TransactionReturnData for pretty JSON
+/// serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionReturnData {
+    pub program_id: String,
+    pub data: (String, UiReturnDataEncoding),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiReturnDataEncoding {
+    Base64,
+}
+
+impl From<TransactionReturnData> for UiTransactionReturnData {
+    fn from(return_data: TransactionReturnData) -> Self {
+        Self {
+            program_id: return_data.program_id.to_string(),
+            data: (
+                base64::encode(return_data.data),
+                UiReturnDataEncoding::Base64,
+            ),
+        }
+    }
 }
 
 impl UiTransactionStatusMeta {
-    fn parse(meta: TransactionStatusMeta, message: &Message) -> Self {
+    fn parse(meta: TransactionStatusMeta, account_keys: &[Pubkey]) -> Self {
         Self {
             err: meta.status.clone().err(),
             status: meta.status,
@@ -241,7 +333,7 @@ impl UiTransactionStatusMeta {
             post_balances: meta.post_balances,
             inner_instructions: meta.inner_instructions.map(|ixs| {
                 ixs.into_iter()
-                    .map(|ix| UiInnerInstructions::parse(ix, message))
+                    .map(|ix| UiInnerInstructions::parse(ix, account_keys))
                     .collect()
             }),
             log_messages: meta.log_messages,
@@ -252,6 +344,9 @@ impl UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            loaded_addresses: Some(UiLoadedAddresses::from(&meta.loaded_addresses)),
+            compute_units_consumed: meta.compute_units_consumed,
         }
     }
 }
@@ -275,6 +370,9 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            loaded_addresses: Some(UiLoadedAddresses::from(&meta.loaded_addresses)),
+            compute_units_consumed: meta.compute_units_consumed,
         }
     }
 }
@@ -391,20 +489,24 @@ impl From<ConfirmedBlock> for ConfirmedBlockWithOptionalMetadata {
 }
 
 impl ConfirmedBlockWithOptionalMetadata {
-    pub fn encode(self, encoding: UiTransactionEncoding) -> EncodedConfirmedBlock {
-        EncodedConfirmedBlock {
+    pub fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<EncodedConfirmedBlock, EncodeError> {
+        Ok(EncodedConfirmedBlock {
             previous_blockhash: self.previous_blockhash,
             blockhash: self.blockhash,
             parent_slot: self.parent_slot,
             transactions: self
                 .transactions
                 .into_iter()
-                .map(|tx| tx.encode(encoding))
-                .collect(),
+                .map(|tx| tx.encode(encoding, max_supported_transaction_version))
+                .collect::<std::result::Result<_, _>>()?,
             rewards: self.rewards,
             block_time: self.block_time,
             block_height: self.block_height,
-        }
+        })
     }
 
     pub fn configure(
@@ -412,14 +514,15 @@ impl ConfirmedBlockWithOptionalMetadata {
         encoding: UiTransactionEncoding,
         transaction_details: TransactionDetails,
         show_rewards: bool,
-    ) -> UiConfirmedBlock {
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<UiConfirmedBlock, EncodeError> {
         let (transactions, signatures) = match transaction_details {
             TransactionDetails::Full => (
                 Some(
                     self.transactions
                         .into_iter()
-                        .map(|tx| tx.encode(encoding))
-                        .collect(),
+                        .map(|tx| tx.encode(encoding, max_supported_transaction_version))
+                        .collect::<std::result::Result<_, _>>()?,
                 ),
                 None,
             ),
@@ -428,13 +531,16 @@ impl ConfirmedBlockWithOptionalMetadata {
                 Some(
                     self.transactions
                         .into_iter()
-                        .map(|tx| tx.transaction.signatures[0].to_string())
-                        .collect(),
+                        .map(|tx| {
+                            tx.validate_version(max_supported_transaction_version)?;
+                            Ok(tx.transaction.signatures[0].to_string())
+                        })
+                        .collect::<std::result::Result<_, _>>()?,
                 ),
             ),
             TransactionDetails::None => (None, None),
         };
-        UiConfirmedBlock {
+        Ok(UiConfirmedBlock {
             previous_blockhash: self.previous_blockhash,
             blockhash: self.blockhash,
             parent_slot: self.parent_slot,
@@ -447,7 +553,7 @@ impl ConfirmedBlockWithOptionalMetadata {
             },
             block_time: self.block_time,
             block_height: self.block_height,
-        }
+        })
     }
 }
 
@@ -547,12 +653,18 @@ impl From<ConfirmedTransaction> for ConfirmedTransactionWithOptionalMetadata {
 }
 
 impl ConfirmedTransactionWithOptionalMetadata {
-    pub fn encode(self, encoding: UiTransactionEncoding) -> EncodedConfirmedTransaction {
-        EncodedConfirmedTransaction {
+    pub fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<EncodedConfirmedTransaction, EncodeError> {
+        Ok(EncodedConfirmedTransaction {
             slot: self.slot,
-            transaction: self.transaction.encode(encoding),
+            transaction: self
+                .transaction
+                .encode(encoding, max_supported_transaction_version)?,
             block_time: self.block_time,
-        }
+        })
     }
 }
 
@@ -588,6 +700,27 @@ pub struct UiRawMessage {
     pub account_keys: Vec<String>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiCompiledInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
+}
+
+/// A duplicate representation of a MessageAddressTableLookup, in raw format, for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAddressTableLookup {
+    pub account_key: String,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+impl From<&MessageAddressTableLookup> for UiAddressTableLookup {
+    fn from(lookup: &MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: lookup.account_key.to_string(),
+            writable_indexes: lookup.writable_indexes.clone(),
+            readonly_indexes: lookup.readonly_indexes.clone(),
+        }
+    }
 }
 
 /// A duplicate representation of a Message, in parsed format, for pretty JSON serialization
@@ -597,17 +730,19 @@ pub struct UiParsedMessage {
     pub account_keys: Vec<ParsedAccount>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct TransactionWithMetadata {
-    pub transaction: Transaction,
+    pub transaction: VersionedTransaction,
     pub meta: TransactionStatusMeta,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct TransactionWithOptionalMetadata {
-    pub transaction: Transaction,
+    pub transaction: VersionedTransaction,
     pub meta: Option<TransactionStatusMeta>,
 }
 
@@ -621,14 +756,54 @@ impl From<TransactionWithMetadata> for TransactionWithOptionalMetadata {
 }
 
 impl TransactionWithOptionalMetadata {
-    fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
-        let message = self.transaction.message();
-        let meta = self.meta.map(|meta| meta.encode(encoding, message));
-        EncodedTransactionWithStatusMeta {
-            transaction: EncodedTransaction::encode(self.transaction, encoding),
-            meta,
+    /// Checks that the requester supports the version of the transaction.
+    /// Requests without a maximum version predate versioned transactions and
+    /// get responses without a `version` field.
+    fn validate_version(
+        &self,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<Option<TransactionVersion>, EncodeError> {
+        match (
+            max_supported_transaction_version,
+            &self.transaction.message,
+        ) {
+            (None, VersionedMessage::Legacy(_)) => Ok(None),
+            (None, VersionedMessage::V0(_)) => Err(EncodeError::UnsupportedTransactionVersion(0)),
+            (Some(_), VersionedMessage::Legacy(_)) => Ok(Some(TransactionVersion::LEGACY)),
+            (Some(_), VersionedMessage::V0(_)) => Ok(Some(TransactionVersion::Number(0))),
         }
     }
+
+    fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<EncodedTransactionWithStatusMeta, EncodeError> {
+        let version = self.validate_version(max_supported_transaction_version)?;
+        let loaded_addresses = self
+            .meta
+            .as_ref()
+            .map(|meta| meta.loaded_addresses.clone())
+            .unwrap_or_default();
+        let account_keys: Vec<Pubkey> = self
+            .transaction
+            .message
+            .static_account_keys_iter()
+            .chain(loaded_addresses.writable.iter())
+            .chain(loaded_addresses.readonly.iter())
+            .cloned()
+            .collect();
+        let meta = self.meta.map(|meta| meta.encode(encoding, &account_keys));
+        Ok(EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::encode_versioned(
+                self.transaction,
+                encoding,
+                &loaded_addresses,
+            ),
+            meta,
+            version,
+        })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -636,12 +811,36 @@ impl TransactionWithOptionalMetadata {
 pub struct EncodedTransactionWithStatusMeta {
     pub transaction: EncodedTransaction,
     pub meta: Option<UiTransactionStatusMeta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<TransactionVersion>,
+}
+
+/// Version of a transaction's message, serialized as `"legacy"` or a number
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", untagged)]
+pub enum TransactionVersion {
+    Legacy(Legacy),
+    Number(u8),
+}
+
+impl TransactionVersion {
+    pub const LEGACY: Self = Self::Legacy(Legacy::Legacy);
+}
+
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Legacy {
+    Legacy,
 }
 
 impl TransactionStatusMeta {
-    fn encode(self, encoding: UiTransactionEncoding, message: &Message) -> UiTransactionStatusMeta {
+    fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        account_keys: &[Pubkey],
+    ) -> UiTransactionStatusMeta {
         match encoding {
-            UiTransactionEncoding::JsonParsed => UiTransactionStatusMeta::parse(self, message),
+            UiTransactionEncoding::JsonParsed => UiTransactionStatusMeta::parse(self, account_keys),
             _ => self.into(),
         }
     }
@@ -675,6 +874,17 @@ pub enum EncodedTransaction {
 
 impl EncodedTransaction {
     pub fn encode(transaction: Transaction, encoding: UiTransactionEncoding) -> Self {
+        Self::encode_versioned(transaction.into(), encoding, &LoadedAddresses::default())
+    }
+
+    /// Encodes a transaction of any version. Parsing the instructions of a
+    /// version #0 transaction relies on the addresses it loaded from lookup
+    /// tables.
+    pub fn encode_versioned(
+        transaction: VersionedTransaction,
+        encoding: UiTransactionEncoding,
+        loaded_addresses: &LoadedAddresses,
+    ) -> Self {
         match encoding {
             UiTransactionEncoding::Binary => EncodedTransaction::LegacyBinary(
                 bs58::encode(bincode::serialize(&transaction).unwrap()).into_string(),
@@ -688,36 +898,13 @@ impl EncodedTransaction {
                 encoding,
             ),
             UiTransactionEncoding::Json | UiTransactionEncoding::JsonParsed => {
-                let message = if encoding == UiTransactionEncoding::Json {
-                    UiMessage::Raw(UiRawMessage {
-                        header: transaction.message.header,
-                        account_keys: transaction
-                            .message
-                            .account_keys
-                            .iter()
-                            .map(|pubkey| pubkey.to_string())
-                            .collect(),
-                        recent_blockhash: transaction.message.recent_blockhash.to_string(),
-                        instructions: transaction
-                            .message
-                            .instructions
-                            .iter()
-                            .map(|instruction| instruction.into())
-                            .collect(),
-                    })
-                } else {
-                    UiMessage::Parsed(UiParsedMessage {
-                        account_keys: parse_accounts(&transaction.message),
-                        recent_blockhash: transaction.message.recent_blockhash.to_string(),
-                        instructions: transaction
-                            .message
-                            .instructions
-                            .iter()
-                            .map(|instruction| {
-                                UiInstruction::parse(instruction, &transaction.message)
-                            })
-                            .collect(),
-                    })
+                let message = match transaction.message {
+                    VersionedMessage::Legacy(message) => {
+                        encode_legacy_message(message, encoding)
+                    }
+                    VersionedMessage::V0(message) => {
+                        encode_v0_message(message, encoding, loaded_addresses)
+                    }
                 };
                 EncodedTransaction::Json(UiTransaction {
                     signatures: transaction
@@ -730,6 +917,7 @@ impl EncodedTransaction {
             }
         }
     }
+
     pub fn decode(&self) -> Option<Transaction> {
         let transaction: Option<Transaction> = match self {
             EncodedTransaction::Json(_) => None,
@@ -754,6 +942,84 @@ impl EncodedTransaction {
     }
 }
 
+fn encode_legacy_message(message: Message, encoding: UiTransactionEncoding) -> UiMessage {
+    if encoding == UiTransactionEncoding::JsonParsed {
+        UiMessage::Parsed(UiParsedMessage {
+            account_keys: parse_accounts(&message),
+            recent_blockhash: message.recent_blockhash.to_string(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|instruction| UiInstruction::parse(instruction, &message.account_keys))
+                .collect(),
+            address_table_lookups: None,
+        })
+    } else {
+        UiMessage::Raw(UiRawMessage {
+            header: message.header,
+            account_keys: message
+                .account_keys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            recent_blockhash: message.recent_blockhash.to_string(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|instruction| instruction.into())
+                .collect(),
+            address_table_lookups: None,
+        })
+    }
+}
+
+fn encode_v0_message(
+    message: v0::Message,
+    encoding: UiTransactionEncoding,
+    loaded_addresses: &LoadedAddresses,
+) -> UiMessage {
+    let address_table_lookups = Some(
+        message
+            .address_table_lookups
+            .iter()
+            .map(UiAddressTableLookup::from)
+            .collect(),
+    );
+    if encoding == UiTransactionEncoding::JsonParsed {
+        let loaded_message = LoadedMessage {
+            message,
+            loaded_addresses: loaded_addresses.clone(),
+        };
+        let account_keys: Vec<Pubkey> = loaded_message.account_keys_iter().cloned().collect();
+        UiMessage::Parsed(UiParsedMessage {
+            account_keys: parse_v0_message_accounts(&loaded_message),
+            recent_blockhash: loaded_message.recent_blockhash.to_string(),
+            instructions: loaded_message
+                .instructions
+                .iter()
+                .map(|instruction| UiInstruction::parse(instruction, &account_keys))
+                .collect(),
+            address_table_lookups,
+        })
+    } else {
+        UiMessage::Raw(UiRawMessage {
+            header: message.header,
+            account_keys: message
+                .account_keys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            recent_blockhash: message.recent_blockhash.to_string(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|instruction| instruction.into())
+                .collect(),
+            address_table_lookups,
+        })
+    }
+}
+
 // A serialized `Vec<TransactionByAddrInfo>` is stored in the `tx-by-addr` table.  The row keys are
 // the one's compliment of the slot so that rows may be listed in reverse order
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -783,6 +1049,140 @@ mod test {
         assert!(unsanitary_transaction.decode().is_none());
     }
 
+    #[test]
+    fn test_encode_return_data() {
+        let program_id = Pubkey::new_unique();
+        let meta = TransactionStatusMeta {
+            return_data: Some(TransactionReturnData {
+                program_id,
+                data: vec![42, 2, 3],
+            }),
+            ..TransactionStatusMeta::default()
+        };
+        let ui_meta = UiTransactionStatusMeta::from(meta);
+        assert_eq!(
+            ui_meta.return_data,
+            Some(UiTransactionReturnData {
+                program_id: program_id.to_string(),
+                data: ("KgID".to_string(), UiReturnDataEncoding::Base64),
+            })
+        );
+        let json = serde_json::to_value(&ui_meta).unwrap();
+        assert_eq!(
+            json["returnData"]["data"],
+            serde_json::json!(["KgID", "base64"])
+        );
+
+        // Transactions without return data keep their previous encoding
+        let json = serde_json::to_value(UiTransactionStatusMeta::from(
+            TransactionStatusMeta::default(),
+        ))
+        .unwrap();
+        assert!(json.get("returnData").is_none());
+    }
+
+    #[test]
+    fn test_encode_max_supported_transaction_version() {
+        let lookup_table = Pubkey::new_unique();
+        let loaded_address = Pubkey::new_unique();
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 0,
+                },
+                account_keys: vec![Pubkey::new_unique()],
+                instructions: vec![CompiledInstruction::new_from_raw_parts(1, vec![0], vec![])],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: lookup_table,
+                    writable_indexes: vec![],
+                    readonly_indexes: vec![3],
+                }],
+                ..v0::Message::default()
+            }),
+        };
+        let with_meta = TransactionWithOptionalMetadata {
+            transaction,
+            meta: Some(TransactionStatusMeta {
+                loaded_addresses: LoadedAddresses {
+                    writable: vec![],
+                    readonly: vec![loaded_address],
+                },
+                compute_units_consumed: Some(1234),
+                ..TransactionStatusMeta::default()
+            }),
+        };
+
+        // Requests from clients unaware of versioned transactions are refused
+        assert_eq!(
+            with_meta
+                .clone()
+                .encode(UiTransactionEncoding::Json, None)
+                .unwrap_err(),
+            EncodeError::UnsupportedTransactionVersion(0)
+        );
+
+        let encoded = with_meta
+            .encode(UiTransactionEncoding::JsonParsed, Some(0))
+            .unwrap();
+        assert_eq!(encoded.version, Some(TransactionVersion::Number(0)));
+        let meta = encoded.meta.unwrap();
+        assert_eq!(meta.compute_units_consumed, Some(1234));
+        assert_eq!(
+            meta.loaded_addresses,
+            Some(UiLoadedAddresses {
+                writable: vec![],
+                readonly: vec![loaded_address.to_string()],
+            })
+        );
+        if let EncodedTransaction::Json(UiTransaction {
+            message: UiMessage::Parsed(message),
+            ..
+        }) = encoded.transaction
+        {
+            assert_eq!(message.account_keys.len(), 2);
+            assert_eq!(
+                message.address_table_lookups.unwrap()[0].account_key,
+                lookup_table.to_string()
+            );
+            assert_eq!(
+                message.instructions[0],
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+                    UiPartiallyDecodedInstruction {
+                        program_id: loaded_address.to_string(),
+                        accounts: vec![],
+                        data: bs58::encode(vec![0]).into_string(),
+                    }
+                ))
+            );
+        } else {
+            panic!("expected a parsed message");
+        }
+
+        // Legacy transactions report their version only when asked to
+        let legacy = TransactionWithOptionalMetadata {
+            transaction: Transaction::default().into(),
+            meta: None,
+        };
+        assert_eq!(
+            legacy
+                .clone()
+                .encode(UiTransactionEncoding::Base64, None)
+                .unwrap()
+                .version,
+            None
+        );
+        assert_eq!(
+            legacy
+                .encode(UiTransactionEncoding::Base64, Some(0))
+                .unwrap()
+                .version,
+            Some(TransactionVersion::LEGACY)
+        );
+    }
+
     #[test]
     fn test_satisfies_commitment() {
         let status = TransactionStatus {
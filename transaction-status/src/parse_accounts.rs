@@ -1,4 +1,4 @@
-use mundis_sdk::message::Message;
+use mundis_sdk::message::{v0::LoadedMessage, Message};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -20,9 +20,28 @@ pub fn parse_accounts(message: &Message) -> Vec<ParsedAccount> {
     accounts
 }
 
+pub fn parse_v0_message_accounts(message: &LoadedMessage) -> Vec<ParsedAccount> {
+    let num_signers = usize::from(message.header.num_required_signatures);
+    message
+        .account_keys_iter()
+        .enumerate()
+        .map(|(i, account_key)| ParsedAccount {
+            pubkey: account_key.to_string(),
+            writable: message.is_writable(i),
+            signer: i < num_signers,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
-    use {super::*, mundis_sdk::message::MessageHeader};
+    use {
+        super::*,
+        mundis_sdk::message::{
+            v0::{self, LoadedAddresses},
+            MessageHeader,
+        },
+    };
 
     #[test]
     fn test_parse_accounts() {
@@ -66,4 +85,53 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_parse_v0_message_accounts() {
+        let pubkey0 = mundis_sdk::pubkey::new_rand();
+        let pubkey1 = mundis_sdk::pubkey::new_rand();
+        let pubkey2 = mundis_sdk::pubkey::new_rand();
+        let pubkey3 = mundis_sdk::pubkey::new_rand();
+        let message = LoadedMessage {
+            message: v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![pubkey0, pubkey1],
+                ..v0::Message::default()
+            },
+            loaded_addresses: LoadedAddresses {
+                writable: vec![pubkey2],
+                readonly: vec![pubkey3],
+            },
+        };
+
+        assert_eq!(
+            parse_v0_message_accounts(&message),
+            vec![
+                ParsedAccount {
+                    pubkey: pubkey0.to_string(),
+                    writable: true,
+                    signer: true,
+                },
+                ParsedAccount {
+                    pubkey: pubkey1.to_string(),
+                    writable: false,
+                    signer: false,
+                },
+                ParsedAccount {
+                    pubkey: pubkey2.to_string(),
+                    writable: true,
+                    signer: false,
+                },
+                ParsedAccount {
+                    pubkey: pubkey3.to_string(),
+                    writable: false,
+                    signer: false,
+                },
+            ]
+        );
+    }
 }
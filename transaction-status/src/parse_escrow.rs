@@ -0,0 +1,158 @@
+use {
+    crate::parse_instruction::{
+        check_num_accounts, ParsableProgram, ParseInstructionError, ParsedInstructionEnum,
+    },
+    bincode::deserialize,
+    serde_json::json,
+    mundis_escrow_program::escrow_instruction::EscrowInstruction,
+    mundis_sdk::{instruction::CompiledInstruction, pubkey::Pubkey},
+};
+
+pub fn parse_escrow(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) -> Result<ParsedInstructionEnum, ParseInstructionError> {
+    let escrow_instruction: EscrowInstruction = deserialize(&instruction.data)
+        .map_err(|_| ParseInstructionError::InstructionNotParsable(ParsableProgram::Escrow))?;
+    match instruction.accounts.iter().max() {
+        Some(index) if (*index as usize) < account_keys.len() => {}
+        _ => {
+            // Runtime should prevent this from ever happening
+            return Err(ParseInstructionError::InstructionKeyMismatch(
+                ParsableProgram::Escrow,
+            ));
+        }
+    }
+    let key = |i: usize| account_keys[instruction.accounts[i] as usize].to_string();
+    match escrow_instruction {
+        EscrowInstruction::CreateOffer {
+            offer_amount,
+            want_amount,
+            expiry_slot,
+        } => {
+            check_num_escrow_accounts(&instruction.accounts, 10)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "createOffer".to_string(),
+                info: json!({
+                    "payer": key(0),
+                    "offer": key(1),
+                    "vault": key(2),
+                    "authority": key(3),
+                    "maker": key(4),
+                    "source": key(5),
+                    "offerMint": key(6),
+                    "receiveAccount": key(7),
+                    "offerAmount": offer_amount,
+                    "wantAmount": want_amount,
+                    "expirySlot": expiry_slot,
+                }),
+            })
+        }
+        EscrowInstruction::Take { amount } => {
+            check_num_escrow_accounts(&instruction.accounts, 9)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "take".to_string(),
+                info: json!({
+                    "offer": key(0),
+                    "vault": key(1),
+                    "authority": key(2),
+                    "taker": key(3),
+                    "paymentAccount": key(4),
+                    "receiveAccount": key(5),
+                    "makerReceiveAccount": key(6),
+                    "maker": key(7),
+                    "amount": amount,
+                }),
+            })
+        }
+        EscrowInstruction::Cancel => {
+            check_num_escrow_accounts(&instruction.accounts, 6)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "cancel".to_string(),
+                info: json!({
+                    "offer": key(0),
+                    "vault": key(1),
+                    "authority": key(2),
+                    "maker": key(3),
+                    "refundAccount": key(4),
+                }),
+            })
+        }
+    }
+}
+
+fn check_num_escrow_accounts(accounts: &[u8], num: usize) -> Result<(), ParseInstructionError> {
+    check_num_accounts(accounts, num, ParsableProgram::Escrow)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        mundis_escrow_program::{escrow_instruction, find_authority_address, find_vault_address},
+        mundis_sdk::message::Message,
+    };
+
+    #[test]
+    fn test_parse_escrow_instruction() {
+        let offer = Pubkey::new_unique();
+        let taker = Pubkey::new_unique();
+        let payment_account = Pubkey::new_unique();
+        let receive_account = Pubkey::new_unique();
+        let maker_receive_account = Pubkey::new_unique();
+        let maker = Pubkey::new_unique();
+        let (vault, _) = find_vault_address(&offer);
+        let (authority, _) = find_authority_address(&offer);
+
+        let instruction = escrow_instruction::take(
+            &offer,
+            &taker,
+            &payment_account,
+            &receive_account,
+            &maker_receive_account,
+            &maker,
+            42,
+        );
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_escrow(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "take".to_string(),
+                info: json!({
+                    "offer": offer.to_string(),
+                    "vault": vault.to_string(),
+                    "authority": authority.to_string(),
+                    "taker": taker.to_string(),
+                    "paymentAccount": payment_account.to_string(),
+                    "receiveAccount": receive_account.to_string(),
+                    "makerReceiveAccount": maker_receive_account.to_string(),
+                    "maker": maker.to_string(),
+                    "amount": 42,
+                }),
+            }
+        );
+
+        let refund_account = Pubkey::new_unique();
+        let instruction = escrow_instruction::close_expired(&offer, &maker, &refund_account);
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_escrow(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "cancel".to_string(),
+                info: json!({
+                    "offer": offer.to_string(),
+                    "vault": vault.to_string(),
+                    "authority": authority.to_string(),
+                    "maker": maker.to_string(),
+                    "refundAccount": refund_account.to_string(),
+                }),
+            }
+        );
+        let mut short_instruction = message.instructions[0].clone();
+        short_instruction.accounts.pop();
+        assert!(parse_escrow(&short_instruction, &message.account_keys).is_err());
+
+        let bad_instruction = CompiledInstruction::new_from_raw_parts(0, vec![42], vec![0, 1]);
+        assert!(parse_escrow(&bad_instruction, &message.account_keys).is_err());
+    }
+}
@@ -1,16 +1,22 @@
 use {
     crate::{
         extract_memos::anima_memo_id,
+        parse_escrow::parse_escrow,
         parse_associated_token::{parse_associated_token, anima_associated_token_id},
         parse_stake::parse_stake,
+        parse_stake_pool::parse_stake_pool,
         parse_system::parse_system,
         parse_token::parse_token,
         parse_vote::parse_vote,
+        parse_wasm_loader::parse_wasm_loader,
     },
     inflector::Inflector,
     serde_json::Value,
     mundis_account_decoder::parse_token::mundis_token_ids,
-    mundis_sdk::{instruction::CompiledInstruction, pubkey::Pubkey, stake, system_program},
+    mundis_sdk::{
+        instruction::CompiledInstruction, pubkey::Pubkey, stake, system_program,
+        wasm_loader_upgradeable,
+    },
     std::{
         collections::HashMap,
         str::{from_utf8, Utf8Error},
@@ -20,23 +26,29 @@ use {
 
 lazy_static! {
     static ref ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = anima_associated_token_id();
+    static ref ESCROW_PROGRAM_ID: Pubkey = mundis_escrow_program::id();
     static ref MEMO_PROGRAM_ID: Pubkey = anima_memo_id();
     static ref STAKE_PROGRAM_ID: Pubkey = stake::program::id();
+    static ref STAKE_POOL_PROGRAM_ID: Pubkey = mundis_stake_pool_program::id();
     static ref SYSTEM_PROGRAM_ID: Pubkey = system_program::id();
     static ref VOTE_PROGRAM_ID: Pubkey = mundis_vote_program::id();
+    static ref WASM_LOADER_PROGRAM_ID: Pubkey = wasm_loader_upgradeable::id();
     static ref PARSABLE_PROGRAM_IDS: HashMap<Pubkey, ParsableProgram> = {
         let mut m = HashMap::new();
         m.insert(
             *ASSOCIATED_TOKEN_PROGRAM_ID,
             ParsableProgram::TokenAccount,
         );
+        m.insert(*ESCROW_PROGRAM_ID, ParsableProgram::Escrow);
         m.insert(*MEMO_PROGRAM_ID, ParsableProgram::Memo);
         for anima_token_id in mundis_token_ids() {
             m.insert(anima_token_id, ParsableProgram::Token);
         }
         m.insert(*STAKE_PROGRAM_ID, ParsableProgram::Stake);
+        m.insert(*STAKE_POOL_PROGRAM_ID, ParsableProgram::StakePool);
         m.insert(*SYSTEM_PROGRAM_ID, ParsableProgram::System);
         m.insert(*VOTE_PROGRAM_ID, ParsableProgram::Vote);
+        m.insert(*WASM_LOADER_PROGRAM_ID, ParsableProgram::WasmLoader);
         m
     };
 }
@@ -77,11 +89,14 @@ pub struct ParsedInstructionEnum {
 #[serde(rename_all = "camelCase")]
 pub enum ParsableProgram {
     TokenAccount,
+    Escrow,
     Memo,
     Token,
     Stake,
+    StakePool,
     System,
     Vote,
+    WasmLoader,
 }
 
 pub fn parse(
@@ -96,11 +111,20 @@ pub fn parse(
         ParsableProgram::TokenAccount => {
             serde_json::to_value(parse_associated_token(instruction, account_keys)?)?
         }
+        ParsableProgram::Escrow => {
+            serde_json::to_value(parse_escrow(instruction, account_keys)?)?
+        }
         ParsableProgram::Memo => parse_memo(instruction)?,
         ParsableProgram::Token => serde_json::to_value(parse_token(instruction, account_keys)?)?,
         ParsableProgram::Stake => serde_json::to_value(parse_stake(instruction, account_keys)?)?,
+        ParsableProgram::StakePool => {
+            serde_json::to_value(parse_stake_pool(instruction, account_keys)?)?
+        }
         ParsableProgram::System => serde_json::to_value(parse_system(instruction, account_keys)?)?,
         ParsableProgram::Vote => serde_json::to_value(parse_vote(instruction, account_keys)?)?,
+        ParsableProgram::WasmLoader => {
+            serde_json::to_value(parse_wasm_loader(instruction, account_keys)?)?
+        }
     };
     Ok(ParsedInstruction {
         program: format!("{:?}", program_name).to_kebab_case(),
@@ -0,0 +1,309 @@
+use {
+    crate::parse_instruction::{
+        check_num_accounts, ParsableProgram, ParseInstructionError, ParsedInstructionEnum,
+    },
+    bincode::deserialize,
+    serde_json::json,
+    mundis_sdk::{instruction::CompiledInstruction, pubkey::Pubkey},
+    mundis_stake_pool_program::{
+        stake_pool_instruction::StakePoolInstruction,
+        state::{Fee, FeeType},
+    },
+};
+
+pub fn parse_stake_pool(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) -> Result<ParsedInstructionEnum, ParseInstructionError> {
+    let stake_pool_instruction: StakePoolInstruction = deserialize(&instruction.data)
+        .map_err(|_| ParseInstructionError::InstructionNotParsable(ParsableProgram::StakePool))?;
+    match instruction.accounts.iter().max() {
+        Some(index) if (*index as usize) < account_keys.len() => {}
+        _ => {
+            // Runtime should prevent this from ever happening
+            return Err(ParseInstructionError::InstructionKeyMismatch(
+                ParsableProgram::StakePool,
+            ));
+        }
+    }
+    let key = |i: usize| account_keys[instruction.accounts[i] as usize].to_string();
+    match stake_pool_instruction {
+        StakePoolInstruction::Initialize {
+            epoch_fee,
+            withdrawal_fee,
+            max_validators,
+        } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 7)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "initialize".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "manager": key(1),
+                    "staker": key(2),
+                    "withdrawAuthority": key(3),
+                    "reserve": key(4),
+                    "poolMint": key(5),
+                    "managerFeeAccount": key(6),
+                    "epochFee": fee_json(&epoch_fee),
+                    "withdrawalFee": fee_json(&withdrawal_fee),
+                    "maxValidators": max_validators,
+                }),
+            })
+        }
+        StakePoolInstruction::AddValidator => {
+            check_num_stake_pool_accounts(&instruction.accounts, 3)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "addValidator".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "staker": key(1),
+                    "voteAccount": key(2),
+                }),
+            })
+        }
+        StakePoolInstruction::RemoveValidator => {
+            check_num_stake_pool_accounts(&instruction.accounts, 5)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "removeValidator".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "staker": key(1),
+                    "voteAccount": key(2),
+                    "validatorStakeAccount": key(3),
+                    "transientStakeAccount": key(4),
+                }),
+            })
+        }
+        StakePoolInstruction::IncreaseValidatorStake { lamports } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 13)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "increaseValidatorStake".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "staker": key(1),
+                    "withdrawAuthority": key(2),
+                    "reserve": key(3),
+                    "validatorStakeAccount": key(4),
+                    "transientStakeAccount": key(5),
+                    "voteAccount": key(6),
+                    "lamports": lamports,
+                }),
+            })
+        }
+        StakePoolInstruction::DecreaseValidatorStake { lamports } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 8)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "decreaseValidatorStake".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "staker": key(1),
+                    "withdrawAuthority": key(2),
+                    "validatorStakeAccount": key(3),
+                    "transientStakeAccount": key(4),
+                    "lamports": lamports,
+                }),
+            })
+        }
+        StakePoolInstruction::UpdateStakePool => {
+            check_num_stake_pool_accounts(&instruction.accounts, 9)?;
+            let stake_accounts: Vec<String> = (9..instruction.accounts.len()).map(key).collect();
+            Ok(ParsedInstructionEnum {
+                instruction_type: "updateStakePool".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "withdrawAuthority": key(1),
+                    "reserve": key(2),
+                    "managerFeeAccount": key(3),
+                    "poolMint": key(4),
+                    "stakeAccounts": stake_accounts,
+                }),
+            })
+        }
+        StakePoolInstruction::DepositMundis { lamports } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 8)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "depositMundis".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "withdrawAuthority": key(1),
+                    "reserve": key(2),
+                    "depositor": key(3),
+                    "destination": key(4),
+                    "poolMint": key(5),
+                    "lamports": lamports,
+                }),
+            })
+        }
+        StakePoolInstruction::WithdrawInstant { pool_tokens } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 10)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "withdrawInstant".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "withdrawAuthority": key(1),
+                    "reserve": key(2),
+                    "source": key(3),
+                    "owner": key(4),
+                    "destination": key(5),
+                    "managerFeeAccount": key(6),
+                    "poolMint": key(7),
+                    "poolTokens": pool_tokens,
+                }),
+            })
+        }
+        StakePoolInstruction::RequestWithdrawal { pool_tokens } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 7)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "requestWithdrawal".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "withdrawalTicket": key(1),
+                    "source": key(2),
+                    "owner": key(3),
+                    "beneficiary": key(4),
+                    "poolMint": key(5),
+                    "poolTokens": pool_tokens,
+                }),
+            })
+        }
+        StakePoolInstruction::ClaimWithdrawal => {
+            check_num_stake_pool_accounts(&instruction.accounts, 6)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "claimWithdrawal".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "withdrawalTicket": key(1),
+                    "reserve": key(2),
+                    "beneficiary": key(3),
+                    "destination": key(4),
+                }),
+            })
+        }
+        StakePoolInstruction::SetFee { fee_type, fee } => {
+            check_num_stake_pool_accounts(&instruction.accounts, 2)?;
+            let fee_type = match fee_type {
+                FeeType::Epoch => "epoch",
+                FeeType::Withdrawal => "withdrawal",
+            };
+            Ok(ParsedInstructionEnum {
+                instruction_type: "setFee".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "manager": key(1),
+                    "feeType": fee_type,
+                    "fee": fee_json(&fee),
+                }),
+            })
+        }
+        StakePoolInstruction::SetStaker => {
+            check_num_stake_pool_accounts(&instruction.accounts, 3)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "setStaker".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "authority": key(1),
+                    "newStaker": key(2),
+                }),
+            })
+        }
+        StakePoolInstruction::SetManager => {
+            check_num_stake_pool_accounts(&instruction.accounts, 4)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "setManager".to_string(),
+                info: json!({
+                    "stakePool": key(0),
+                    "manager": key(1),
+                    "newManager": key(2),
+                    "newManagerFeeAccount": key(3),
+                }),
+            })
+        }
+    }
+}
+
+fn fee_json(fee: &Fee) -> serde_json::Value {
+    json!({
+        "numerator": fee.numerator,
+        "denominator": fee.denominator,
+    })
+}
+
+fn check_num_stake_pool_accounts(accounts: &[u8], num: usize) -> Result<(), ParseInstructionError> {
+    check_num_accounts(accounts, num, ParsableProgram::StakePool)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        mundis_sdk::message::Message,
+        mundis_stake_pool_program::{
+            find_reserve_program_address, find_withdraw_authority_program_address,
+            stake_pool_instruction,
+        },
+    };
+
+    #[test]
+    fn test_parse_stake_pool_instruction() {
+        let stake_pool = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let pool_mint = Pubkey::new_unique();
+        let (withdraw_authority, _) = find_withdraw_authority_program_address(&stake_pool);
+        let (reserve, _) = find_reserve_program_address(&stake_pool);
+
+        let instruction = stake_pool_instruction::deposit_mundis(
+            &stake_pool,
+            &depositor,
+            &destination,
+            &pool_mint,
+            42,
+        );
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_stake_pool(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "depositMundis".to_string(),
+                info: json!({
+                    "stakePool": stake_pool.to_string(),
+                    "withdrawAuthority": withdraw_authority.to_string(),
+                    "reserve": reserve.to_string(),
+                    "depositor": depositor.to_string(),
+                    "destination": destination.to_string(),
+                    "poolMint": pool_mint.to_string(),
+                    "lamports": 42,
+                }),
+            }
+        );
+
+        let manager = Pubkey::new_unique();
+        let fee = Fee {
+            numerator: 1,
+            denominator: 50,
+        };
+        let instruction =
+            stake_pool_instruction::set_fee(&stake_pool, &manager, FeeType::Withdrawal, fee);
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_stake_pool(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "setFee".to_string(),
+                info: json!({
+                    "stakePool": stake_pool.to_string(),
+                    "manager": manager.to_string(),
+                    "feeType": "withdrawal",
+                    "fee": {
+                        "numerator": 1,
+                        "denominator": 50,
+                    },
+                }),
+            }
+        );
+        let mut short_instruction = message.instructions[0].clone();
+        short_instruction.accounts.pop();
+        assert!(parse_stake_pool(&short_instruction, &message.account_keys).is_err());
+
+        let bad_instruction = CompiledInstruction::new_from_raw_parts(0, vec![42], vec![0, 1]);
+        assert!(parse_stake_pool(&bad_instruction, &message.account_keys).is_err());
+    }
+}
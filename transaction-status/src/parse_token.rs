@@ -3,7 +3,7 @@ use {
         check_num_accounts, ParsableProgram, ParseInstructionError, ParsedInstructionEnum,
     },
     serde_json::{json, Map, Value},
-    mundis_account_decoder::parse_token::token_amount_to_ui_amount,
+    mundis_account_decoder::parse_token::{token_amount_to_ui_amount, UiMemoTransfer},
     mundis_sdk::{
         instruction::CompiledInstruction,
         pubkey::Pubkey,
@@ -382,6 +382,26 @@ pub fn parse_token(
                 }),
             })
         }
+        TokenInstruction::SetMemoTransfer { memo_transfer } => {
+            check_num_token_accounts(&instruction.accounts, 2)?;
+            let mut value = json!({
+                "account": account_keys[instruction.accounts[0] as usize].to_string(),
+                "memoTransfer": UiMemoTransfer::from(memo_transfer),
+            });
+            let map = value.as_object_mut().unwrap();
+            parse_signers(
+                map,
+                1,
+                account_keys,
+                &instruction.accounts,
+                "owner",
+                "multisigOwner",
+            );
+            Ok(ParsedInstructionEnum {
+                instruction_type: "setMemoTransfer".to_string(),
+                info: value,
+            })
+        }
     }
 }
 
@@ -1061,6 +1081,29 @@ mod test {
                 })
             }
         );
+
+        // Test SetMemoTransfer
+        let set_memo_transfer_ix = set_memo_transfer(
+            &mundis_token_program::id(),
+            &keys[1],
+            &keys[0],
+            &[],
+            mundis_token_program::state::MemoTransfer::RequiredUtf8,
+        )
+        .unwrap();
+        let message = Message::new(&[set_memo_transfer_ix], None);
+        let compiled_instruction = convert_compiled_instruction(&message.instructions[0]);
+        assert_eq!(
+            parse_token(&compiled_instruction, &keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "setMemoTransfer".to_string(),
+                info: json!({
+                   "account": keys[1].to_string(),
+                   "memoTransfer": "requiredUtf8",
+                   "owner": keys[0].to_string(),
+                })
+            }
+        );
     }
 
     #[test]
@@ -0,0 +1,163 @@
+use {
+    crate::parse_instruction::{
+        check_num_accounts, ParsableProgram, ParseInstructionError, ParsedInstructionEnum,
+    },
+    bincode::deserialize,
+    mundis_sdk::{instruction::CompiledInstruction, pubkey::Pubkey},
+    mundis_wasm_loader_program::loader_instruction::LoaderInstruction,
+    serde_json::json,
+};
+
+pub fn parse_wasm_loader(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) -> Result<ParsedInstructionEnum, ParseInstructionError> {
+    let loader_instruction: LoaderInstruction = deserialize(&instruction.data)
+        .map_err(|_| ParseInstructionError::InstructionNotParsable(ParsableProgram::WasmLoader))?;
+    match instruction.accounts.iter().max() {
+        Some(index) if (*index as usize) < account_keys.len() => {}
+        _ => {
+            // Runtime should prevent this from ever happening
+            return Err(ParseInstructionError::InstructionKeyMismatch(
+                ParsableProgram::WasmLoader,
+            ));
+        }
+    }
+    let key = |i: usize| account_keys[instruction.accounts[i] as usize].to_string();
+    let optional_key = |i: usize| {
+        instruction
+            .accounts
+            .get(i)
+            .map(|index| account_keys[*index as usize].to_string())
+    };
+    match loader_instruction {
+        LoaderInstruction::InitializeBuffer => {
+            check_num_wasm_loader_accounts(&instruction.accounts, 2)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "initializeBuffer".to_string(),
+                info: json!({
+                    "account": key(0),
+                    "authority": key(1),
+                }),
+            })
+        }
+        LoaderInstruction::Write { offset, bytes } => {
+            check_num_wasm_loader_accounts(&instruction.accounts, 2)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "write".to_string(),
+                info: json!({
+                    "offset": offset,
+                    "bytes": base64::encode(&bytes),
+                    "account": key(0),
+                    "authority": key(1),
+                }),
+            })
+        }
+        LoaderInstruction::DeployWithMaxDataLen { max_data_len } => {
+            check_num_wasm_loader_accounts(&instruction.accounts, 6)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "deployWithMaxDataLen".to_string(),
+                info: json!({
+                    "maxDataLen": max_data_len,
+                    "payerAccount": key(0),
+                    "programDataAccount": key(1),
+                    "programAccount": key(2),
+                    "bufferAccount": key(3),
+                    "systemProgram": key(4),
+                    "authority": key(5),
+                }),
+            })
+        }
+        LoaderInstruction::Upgrade => {
+            check_num_wasm_loader_accounts(&instruction.accounts, 5)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "upgrade".to_string(),
+                info: json!({
+                    "programDataAccount": key(0),
+                    "programAccount": key(1),
+                    "bufferAccount": key(2),
+                    "spillAccount": key(3),
+                    "authority": key(4),
+                }),
+            })
+        }
+        LoaderInstruction::SetAuthority => {
+            check_num_wasm_loader_accounts(&instruction.accounts, 2)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "setAuthority".to_string(),
+                info: json!({
+                    "account": key(0),
+                    "authority": key(1),
+                    "newAuthority": optional_key(2),
+                }),
+            })
+        }
+        LoaderInstruction::Close => {
+            check_num_wasm_loader_accounts(&instruction.accounts, 2)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "close".to_string(),
+                info: json!({
+                    "account": key(0),
+                    "recipient": key(1),
+                    "authority": optional_key(2),
+                    "programAccount": optional_key(3),
+                }),
+            })
+        }
+    }
+}
+
+fn check_num_wasm_loader_accounts(
+    accounts: &[u8],
+    num: usize,
+) -> Result<(), ParseInstructionError> {
+    check_num_accounts(accounts, num, ParsableProgram::WasmLoader)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        mundis_sdk::message::Message,
+        mundis_wasm_loader_program::{get_program_data_address, loader_instruction},
+    };
+
+    #[test]
+    fn test_parse_wasm_loader_instruction() {
+        let program = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let spill = Pubkey::new_unique();
+
+        let instruction = loader_instruction::upgrade(&program, &buffer, &authority, &spill);
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_wasm_loader(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "upgrade".to_string(),
+                info: json!({
+                    "programDataAccount": get_program_data_address(&program).to_string(),
+                    "programAccount": program.to_string(),
+                    "bufferAccount": buffer.to_string(),
+                    "spillAccount": spill.to_string(),
+                    "authority": authority.to_string(),
+                }),
+            }
+        );
+
+        let instruction = loader_instruction::set_upgrade_authority(&program, &authority, None);
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_wasm_loader(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "setAuthority".to_string(),
+                info: json!({
+                    "account": get_program_data_address(&program).to_string(),
+                    "authority": authority.to_string(),
+                    "newAuthority": null,
+                }),
+            }
+        );
+        assert!(parse_wasm_loader(&message.instructions[0], &message.account_keys[..1]).is_err());
+    }
+}
@@ -7,7 +7,11 @@ use {
     mundis_measure::measure::Measure,
     mundis_metrics::datapoint_debug,
     mundis_runtime::{bank::Bank, transaction_batch::TransactionBatch},
-    mundis_sdk::{account::ReadableAccount, pubkey::Pubkey},
+    mundis_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        pubkey::Pubkey,
+        transaction::SanitizedTransaction,
+    },
     std::collections::HashMap,
 };
 use mundis_token_program::state::{Mint, TokenAccount as TokenAccount};
@@ -33,11 +37,14 @@ impl TransactionTokenBalancesSet {
     }
 }
 
-fn get_mint_decimals(bank: &Bank, mint: &Pubkey) -> Option<u8> {
+fn get_mint_decimals(
+    get_account: &impl Fn(&Pubkey) -> Option<AccountSharedData>,
+    mint: &Pubkey,
+) -> Option<u8> {
     if mint == &mundis_token_native_mint() {
         Some(mundis_token_program::native_mint::DECIMALS)
     } else {
-        let mint_account = bank.get_account(mint)?;
+        let mint_account = get_account(mint)?;
 
         if !is_known_mundis_token_id(mint_account.owner()) {
             return None;
@@ -60,33 +67,11 @@ pub fn collect_token_balances(
     let mut collect_time = Measure::start("collect_token_balances");
 
     for transaction in batch.sanitized_transactions() {
-        let has_token_program = transaction
-            .message()
-            .account_keys_iter()
-            .any(is_known_mundis_token_id);
-
-        let mut transaction_balances: Vec<TransactionTokenBalance> = vec![];
-        if has_token_program {
-            for (index, account_id) in transaction.message().account_keys_iter().enumerate() {
-                if transaction.message().is_invoked(index) || is_known_mundis_token_id(account_id) {
-                    continue;
-                }
-
-                if let Some(TokenBalanceData {
-                    mint,
-                    ui_token_amount,
-                    owner,
-                }) = collect_token_balance_from_account(bank, account_id, mint_decimals)
-                {
-                    transaction_balances.push(TransactionTokenBalance {
-                        account_index: index as u8,
-                        mint,
-                        ui_token_amount,
-                        owner,
-                    });
-                }
-            }
-        }
+        let transaction_balances = collect_token_balances_for_transaction(
+            transaction,
+            |account_id| bank.get_account(account_id),
+            mint_decimals,
+        );
         balances.push(transaction_balances);
     }
     collect_time.stop();
@@ -97,6 +82,43 @@ pub fn collect_token_balances(
     balances
 }
 
+/// Collects the token balances of a single transaction, loading accounts through `get_account`
+/// rather than from a bank. Simulations use this to read balances from simulated account states.
+pub fn collect_token_balances_for_transaction(
+    transaction: &SanitizedTransaction,
+    get_account: impl Fn(&Pubkey) -> Option<AccountSharedData>,
+    mint_decimals: &mut HashMap<Pubkey, u8>,
+) -> Vec<TransactionTokenBalance> {
+    let has_token_program = transaction
+        .message()
+        .account_keys_iter()
+        .any(is_known_mundis_token_id);
+
+    let mut transaction_balances: Vec<TransactionTokenBalance> = vec![];
+    if has_token_program {
+        for (index, account_id) in transaction.message().account_keys_iter().enumerate() {
+            if transaction.message().is_invoked(index) || is_known_mundis_token_id(account_id) {
+                continue;
+            }
+
+            if let Some(TokenBalanceData {
+                mint,
+                ui_token_amount,
+                owner,
+            }) = collect_token_balance_from_account(&get_account, account_id, mint_decimals)
+            {
+                transaction_balances.push(TransactionTokenBalance {
+                    account_index: index as u8,
+                    mint,
+                    ui_token_amount,
+                    owner,
+                });
+            }
+        }
+    }
+    transaction_balances
+}
+
 #[derive(Debug, PartialEq)]
 struct TokenBalanceData {
     mint: String,
@@ -105,11 +127,11 @@ struct TokenBalanceData {
 }
 
 fn collect_token_balance_from_account(
-    bank: &Bank,
+    get_account: &impl Fn(&Pubkey) -> Option<AccountSharedData>,
     account_id: &Pubkey,
     mint_decimals: &mut HashMap<Pubkey, u8>,
 ) -> Option<TokenBalanceData> {
-    let account = bank.get_account(account_id)?;
+    let account = get_account(account_id)?;
 
     if !is_known_mundis_token_id(account.owner()) {
         return None;
@@ -119,7 +141,7 @@ fn collect_token_balance_from_account(
     let mint = token_account.mint;
 
     let decimals = mint_decimals.get(&mint).cloned().or_else(|| {
-        let decimals = get_mint_decimals(bank, &mint)?;
+        let decimals = get_mint_decimals(get_account, &mint)?;
         mint_decimals.insert(mint, decimals);
         Some(decimals)
     })?;
@@ -184,6 +206,7 @@ mod test {
             is_native: Some(100),
             delegated_amount: 0,
             close_authority: None,
+            memo_transfer: mundis_token_program::state::MemoTransfer::Disabled,
         };
         let mut data = [0; TokenAccount::LEN];
         TokenAccount::pack(token_data, &mut data).unwrap();
@@ -212,6 +235,7 @@ mod test {
             is_native: Some(100),
             delegated_amount: 0,
             close_authority: None,
+            memo_transfer: mundis_token_program::state::MemoTransfer::Disabled,
         };
         let mut data = [0; TokenAccount::LEN];
         TokenAccount::pack(other_mint_data, &mut data).unwrap();
@@ -240,21 +264,22 @@ mod test {
         genesis_config.accounts = accounts;
 
         let bank = Bank::new_for_tests(&genesis_config);
+        let get_account = |account_id: &Pubkey| bank.get_account(account_id);
         let mut mint_decimals = HashMap::new();
 
         assert_eq!(
-            collect_token_balance_from_account(&bank, &account_pubkey, &mut mint_decimals),
+            collect_token_balance_from_account(&get_account, &account_pubkey, &mut mint_decimals),
             None
         );
 
         assert_eq!(
-            collect_token_balance_from_account(&bank, &mint_pubkey, &mut mint_decimals),
+            collect_token_balance_from_account(&get_account, &mint_pubkey, &mut mint_decimals),
             None
         );
 
         assert_eq!(
             collect_token_balance_from_account(
-                &bank,
+                &get_account,
                 &anima_token_account_pubkey,
                 &mut mint_decimals
             ),
@@ -271,13 +296,17 @@ mod test {
         );
 
         assert_eq!(
-            collect_token_balance_from_account(&bank, &other_account_pubkey, &mut mint_decimals),
+            collect_token_balance_from_account(
+                &get_account,
+                &other_account_pubkey,
+                &mut mint_decimals
+            ),
             None
         );
 
         assert_eq!(
             collect_token_balance_from_account(
-                &bank,
+                &get_account,
                 &other_mint_account_pubkey,
                 &mut mint_decimals
             ),
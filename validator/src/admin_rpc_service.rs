@@ -9,7 +9,7 @@ use {
         consensus::Tower, tower_storage::TowerStorage, validator::ValidatorStartProgress,
     },
     mundis_gossip::cluster_info::ClusterInfo,
-    mundis_runtime::bank_forks::BankForks,
+    mundis_runtime::{bank_forks::BankForks, bucket_map_holder::AccountsIndexResidency},
     mundis_sdk::{
         exit::Exit,
         pubkey::Pubkey,
@@ -44,9 +44,9 @@ pub struct AdminRpcRequestMetadata {
 impl Metadata for AdminRpcRequestMetadata {}
 
 impl AdminRpcRequestMetadata {
-    fn with_post_init<F>(&self, func: F) -> Result<()>
+    fn with_post_init<F, R>(&self, func: F) -> Result<R>
     where
-        F: FnOnce(&AdminRpcRequestMetadataPostInit) -> Result<()>,
+        F: FnOnce(&AdminRpcRequestMetadataPostInit) -> Result<R>,
     {
         if let Some(post_init) = self.post_init.read().unwrap().as_ref() {
             func(post_init)
@@ -90,6 +90,9 @@ pub trait AdminRpc {
         keypair_file: String,
         require_tower: bool,
     ) -> Result<()>;
+
+    #[rpc(meta, name = "accountsIndexResidency")]
+    fn accounts_index_residency(&self, meta: Self::Metadata) -> Result<AccountsIndexResidency>;
 }
 
 pub struct AdminRpcImpl;
@@ -201,6 +204,14 @@ impl AdminRpc for AdminRpcImpl {
             Ok(())
         })
     }
+
+    fn accounts_index_residency(&self, meta: Self::Metadata) -> Result<AccountsIndexResidency> {
+        debug!("accounts_index_residency admin rpc request received");
+        meta.with_post_init(|post_init| {
+            let root_bank = post_init.bank_forks.read().unwrap().root_bank();
+            Ok(root_bank.rc.accounts.accounts_db.accounts_index.residency())
+        })
+    }
 }
 
 // Start the Admin RPC interface
@@ -3,7 +3,9 @@ use {
     rand::{seq::SliceRandom, thread_rng, Rng},
     mundis_client::rpc_client::RpcClient,
     mundis_core::validator::{ValidatorConfig, ValidatorStartProgress},
-    mundis_download_utils::{download_snapshot_archive, DownloadProgressRecord},
+    mundis_download_utils::{
+        download_snapshot_archive, download_snapshot_archive_from_url, DownloadProgressRecord,
+    },
     crate::genesis_utils::download_then_check_genesis_hash,
     mundis_gossip::{
         cluster_info::{ClusterInfo, Node},
@@ -12,7 +14,7 @@ use {
         gossip_service::GossipService,
     },
     mundis_runtime::{
-        snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_archive_info::{IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter},
         snapshot_package::SnapshotType,
         snapshot_utils::{
             self, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
@@ -49,6 +51,7 @@ pub struct RpcBootstrapConfig {
     pub max_genesis_archive_unpacked_size: u64,
     pub check_vote_account: Option<String>,
     pub incremental_snapshot_fetch: bool,
+    pub snapshot_mirror: Option<String>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -400,6 +403,50 @@ fn get_highest_local_snapshot_hash(
     }
 }
 
+/// Try to download a snapshot archive from the snapshot mirror, if one is configured, before
+/// falling back to the RPC node.  Returns true if the mirror had the archive.
+fn download_snapshot_from_mirror(
+    snapshot_archives_dir: &Path,
+    validator_config: &ValidatorConfig,
+    bootstrap_config: &RpcBootstrapConfig,
+    use_progress_bar: bool,
+    desired_snapshot_hash: (Slot, Hash),
+    snapshot_type: SnapshotType,
+) -> bool {
+    let snapshot_mirror = match bootstrap_config.snapshot_mirror.as_ref() {
+        Some(snapshot_mirror) => snapshot_mirror,
+        None => return false,
+    };
+    let (maximum_full_snapshot_archives_to_retain, maximum_incremental_snapshot_archives_to_retain) =
+        if let Some(snapshot_config) = validator_config.snapshot_config.as_ref() {
+            (
+                snapshot_config.maximum_full_snapshot_archives_to_retain,
+                snapshot_config.maximum_incremental_snapshot_archives_to_retain,
+            )
+        } else {
+            (
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+        };
+    match download_snapshot_archive_from_url(
+        snapshot_mirror,
+        snapshot_archives_dir,
+        desired_snapshot_hash,
+        snapshot_type,
+        maximum_full_snapshot_archives_to_retain,
+        maximum_incremental_snapshot_archives_to_retain,
+        use_progress_bar,
+        &mut None,
+    ) {
+        Ok(()) => true,
+        Err(err) => {
+            warn!("Snapshot mirror did not provide the snapshot: {}", err);
+            false
+        }
+    }
+}
+
 mod without_incremental_snapshots {
     use super::*;
 
@@ -528,7 +575,16 @@ mod without_incremental_snapshots {
                     }
                 };
 
-                if use_local_snapshot {
+                if use_local_snapshot
+                    || download_snapshot_from_mirror(
+                        snapshot_archives_dir,
+                        validator_config,
+                        &bootstrap_config,
+                        use_progress_bar,
+                        snapshot_hash,
+                        SnapshotType::FullSnapshot,
+                    )
+                {
                     Ok(())
                 } else {
                     rpc_client
@@ -1511,6 +1567,10 @@ mod with_incremental_snapshots {
                     snapshot_archive.slot() == incremental_snapshot_hash.0
                         && snapshot_archive.hash() == &incremental_snapshot_hash.1
                         && snapshot_archive.base_slot() == full_snapshot_hash.0
+                        && is_incremental_snapshot_archive_valid(
+                            &snapshot_archive,
+                            incremental_snapshot_hash,
+                        )
                 })
             {
                 info!(
@@ -1537,6 +1597,32 @@ mod with_incremental_snapshots {
         Ok(())
     }
 
+    /// Check that the incremental accounts hash recorded in an incremental snapshot archive is
+    /// the one the cluster gossips for it, and not just the one in the archive name.  An invalid
+    /// archive is removed, so it gets downloaded again.
+    fn is_incremental_snapshot_archive_valid(
+        snapshot_archive: &IncrementalSnapshotArchiveInfo,
+        incremental_snapshot_hash: (Slot, Hash),
+    ) -> bool {
+        match snapshot_utils::verify_snapshot_archive_slot_hash(
+            snapshot_archive.path(),
+            snapshot_archive.archive_format(),
+            SnapshotType::IncrementalSnapshot(snapshot_archive.base_slot()),
+            incremental_snapshot_hash,
+        ) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!(
+                    "Removing invalid incremental snapshot archive {}: {}",
+                    snapshot_archive.path().display(),
+                    err
+                );
+                let _ = std::fs::remove_file(snapshot_archive.path());
+                false
+            }
+        }
+    }
+
     /// Download a snapshot
     #[allow(clippy::too_many_arguments)]
     fn download_snapshot(
@@ -1566,6 +1652,16 @@ mod with_incremental_snapshots {
                 DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             )
         };
+        if download_snapshot_from_mirror(
+            snapshot_archives_dir,
+            validator_config,
+            bootstrap_config,
+            use_progress_bar,
+            desired_snapshot_hash,
+            snapshot_type,
+        ) {
+            return Ok(());
+        }
         *start_progress.write().unwrap() = ValidatorStartProgress::DownloadingSnapshot {
             slot: desired_snapshot_hash.0,
             rpc_addr: rpc_contact_info.rpc,
@@ -1693,7 +1789,11 @@ mod with_incremental_snapshots {
 
     #[cfg(test)]
     mod tests {
-        use super::*;
+        use {
+            super::*,
+            mundis_runtime::{bank::Bank, snapshot_utils::ArchiveFormat},
+            mundis_sdk::genesis_config::GenesisConfig,
+        };
 
         impl PeerSnapshotHash {
             fn new(
@@ -1730,6 +1830,66 @@ mod with_incremental_snapshots {
             }
         }
 
+        #[test]
+        fn test_is_incremental_snapshot_archive_valid() {
+            let archive_format = ArchiveFormat::TarZstd;
+            let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+            let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+            let bank0 = Arc::new(Bank::new_for_tests(&GenesisConfig::default()));
+            while !bank0.is_complete() {
+                bank0.register_tick(&Hash::new_unique());
+            }
+            snapshot_utils::bank_to_full_snapshot_archive(
+                bank_snapshots_dir.path(),
+                &bank0,
+                None,
+                snapshot_archives_dir.path(),
+                archive_format,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+            let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+            while !bank1.is_complete() {
+                bank1.register_tick(&Hash::new_unique());
+            }
+            let snapshot_archive = snapshot_utils::bank_to_incremental_snapshot_archive(
+                bank_snapshots_dir.path(),
+                &bank1,
+                bank0.slot(),
+                None,
+                snapshot_archives_dir.path(),
+                archive_format,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+            assert!(is_incremental_snapshot_archive_valid(
+                &snapshot_archive,
+                (snapshot_archive.slot(), *snapshot_archive.hash()),
+            ));
+
+            // An archive named after the incremental snapshot hash the cluster gossips, but that
+            // holds a different incremental accounts hash, is rejected and removed
+            let gossiped_hash = Hash::new_unique();
+            let mismatching_archive_path = snapshot_utils::build_incremental_snapshot_archive_path(
+                snapshot_archives_dir.path().to_path_buf(),
+                bank0.slot(),
+                bank1.slot(),
+                &gossiped_hash,
+                archive_format,
+            );
+            std::fs::rename(snapshot_archive.path(), &mismatching_archive_path).unwrap();
+            let mismatching_archive =
+                IncrementalSnapshotArchiveInfo::new_from_path(mismatching_archive_path).unwrap();
+            assert_eq!(mismatching_archive.hash(), &gossiped_hash);
+            assert!(!is_incremental_snapshot_archive_valid(
+                &mismatching_archive,
+                (bank1.slot(), gossiped_hash),
+            ));
+            assert!(!mismatching_archive.path().exists());
+        }
+
         #[test]
         fn test_build_known_snapshot_hashes() {
             let full_snapshot_hashes1 = vec![
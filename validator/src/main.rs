@@ -47,6 +47,7 @@ use {
             AccountsIndexConfig,
         },
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
+        secondary_index::CustomAccountIndex,
         snapshot_config::SnapshotConfig,
         snapshot_utils::{
             self, ArchiveFormat, SnapshotVersion, DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
@@ -715,6 +716,16 @@ pub fn main() {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("local_ledger_storage")
+                .long("local-ledger-storage")
+                .value_name("DIR")
+                .takes_value(true)
+                .help("Use a local RocksDB database in DIR instead of a BigTable instance \
+                       for --enable-rpc-bigtable-ledger-storage and \
+                       --enable-bigtable-ledger-upload. Without upload, the database \
+                       follows the blocks stored by the process uploading into it"),
+        )
         .arg(
             Arg::with_name("enable_cpi_and_log_storage")
                 .long("enable-cpi-and-log-storage")
@@ -952,6 +963,15 @@ pub fn main() {
                 .help("The maximum number of times to abort and retry when encountering a \
                       slow snapshot download."),
         )
+        .arg(
+            Arg::with_name("snapshot_mirror")
+                .long("snapshot-mirror")
+                .value_name("URL")
+                .takes_value(true)
+                .help("Try to download snapshot archives from this mirror before the RPC node \
+                       advertising them. May be an HTTP URL, a file:// URL or a local directory \
+                       holding archives named as they are served over RPC."),
+        )
         .arg(
             Arg::with_name("contact_debug_interval")
                 .long("contact-debug-interval")
@@ -1410,7 +1430,7 @@ pub fn main() {
         .arg(
             Arg::with_name("snapshot_archive_format")
                 .long("snapshot-archive-format")
-                .possible_values(&["bz2", "gzip", "zstd", "tar", "none"])
+                .possible_values(&["bz2", "gzip", "zstd", "zstd-seekable", "lz4", "tar", "none"])
                 .default_value("zstd")
                 .value_name("ARCHIVE_TYPE")
                 .takes_value(true)
@@ -1473,6 +1493,18 @@ pub fn main() {
                 .value_name("INDEX")
                 .help("Enable an accounts index, indexed by the selected account field"),
         )
+        .arg(
+            Arg::with_name("account_index_custom")
+                .long("account-index-custom")
+                .takes_value(true)
+                .multiple(true)
+                .validator(|value| value.parse::<CustomAccountIndex>().map(|_| ()))
+                .value_name("PROGRAM_ID:OFFSET:LEN")
+                .help("Enable an accounts index over the accounts owned by PROGRAM_ID, indexed \
+                       by the LEN bytes of account data at OFFSET. LEN may be at most 32. \
+                       getProgramAccounts requests with a memcmp filter on exactly that field \
+                       are answered from the index"),
+        )
         .arg(
             Arg::with_name("account_index_exclude_key")
                 .long(EXCLUDE_KEY)
@@ -1511,6 +1543,12 @@ pub fn main() {
                 .takes_value(true)
                 .help("How large the write cache for account data can become. If this is exceeded, the cache is flushed more aggressively."),
         )
+        .arg(
+            Arg::with_name("accounts_db_ancient_append_vecs")
+                .long("accounts-db-ancient-append-vecs")
+                .help("Pack the accounts of rooted slots older than an epoch into large \
+                       ancient append vecs, reducing open files and rewrites of cold accounts"),
+        )
         .arg(
             Arg::with_name("accounts_index_scan_results_limit_mb")
                 .long("accounts-index-scan-results-limit-mb")
@@ -1733,6 +1771,10 @@ pub fn main() {
             )
             .after_help("Note: the new filter only applies to the currently running validator instance")
         )
+        .subcommand(
+            SubCommand::with_name("accounts-index")
+            .about("Display how much of the accounts index the validator holds in memory versus on disk")
+        )
         .subcommand(
             SubCommand::with_name("wait-for-restart-window")
             .about("Monitor the validator for a good time to restart")
@@ -1904,6 +1946,38 @@ pub fn main() {
                 });
             return;
         }
+        ("accounts-index", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let residency = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.accounts_index_residency().await })
+                .unwrap_or_else(|err| {
+                    println!("accountsIndexResidency request failed: {}", err);
+                    exit(1);
+                });
+            println_name_value("Bins:", &residency.bins.to_string());
+            println_name_value("Entries in memory:", &residency.count_in_mem.to_string());
+            match residency.mem_budget_mb {
+                Some(mem_budget_mb) => {
+                    println_name_value("Memory budget:", &format!("{} MB", mem_budget_mb));
+                    println_name_value("Entries on disk:", &residency.count_on_disk.to_string());
+                    println_name_value(
+                        "Disk index size:",
+                        &format!("{} bytes", residency.disk_bytes),
+                    );
+                    println_name_value(
+                        "Disk index occupancy:",
+                        &format!(
+                            "{:.1}% of index cells, {:.1}% of data cells",
+                            residency.disk_index_occupancy_percent,
+                            residency.disk_data_occupancy_percent
+                        ),
+                    );
+                    println_name_value("Disk index resizes:", &residency.disk_resizes.to_string());
+                }
+                None => println_name_value("Memory budget:", "unlimited, no disk index"),
+            }
+            return;
+        }
         ("wait-for-restart-window", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
             let identity = pubkey_of(subcommand_matches, "identity");
@@ -1989,6 +2063,13 @@ pub fn main() {
             u64
         ),
         incremental_snapshot_fetch: matches.is_present("incremental_snapshots"),
+        snapshot_mirror: matches.value_of("snapshot_mirror").map(|url| {
+            if url.contains("://") {
+                url.to_string()
+            } else {
+                format!("file://{}", url)
+            }
+        }),
     };
 
     let private_rpc = matches.is_present("private_rpc");
@@ -2170,6 +2251,7 @@ pub fn main() {
         write_cache_limit_bytes: value_t!(matches, "accounts_db_cache_limit_mb", u64)
             .ok()
             .map(|mb| mb * MB as u64),
+        ancient_append_vecs: matches.is_present("accounts_db_ancient_append_vecs"),
         ..AccountsDbConfig::default()
     };
 
@@ -2250,6 +2332,7 @@ pub fn main() {
             rpc_bigtable_timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
+            local_ledger_storage_path: matches.value_of("local_ledger_storage").map(PathBuf::from),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
         },
@@ -2430,6 +2513,8 @@ pub fn main() {
             "bz2" => ArchiveFormat::TarBzip2,
             "gzip" => ArchiveFormat::TarGzip,
             "zstd" => ArchiveFormat::TarZstd,
+            "zstd-seekable" => ArchiveFormat::TarZstdSeekable,
+            "lz4" => ArchiveFormat::TarLz4,
             "tar" | "none" => ArchiveFormat::Tar,
             _ => panic!("Archive format not recognized: {}", archive_format_str),
         }
@@ -2713,6 +2798,12 @@ fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
             "token-owner" => AccountIndex::TokenOwner,
             _ => unreachable!(),
         })
+        .chain(
+            values_t!(matches, "account_index_custom", CustomAccountIndex)
+                .unwrap_or_default()
+                .into_iter()
+                .map(AccountIndex::Custom),
+        )
         .collect();
 
     let account_indexes_include_keys: HashSet<Pubkey> =